@@ -0,0 +1,166 @@
+//! Benchmarks for constructing the route graph, enumerating paths, and
+//! selecting the best routes.
+//!
+//! Run these benchmarks with:
+//!
+//!     cargo bench --bench route_paths
+//!
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use navig18xx::game::{new_1861, new_1867};
+use navig18xx::prelude::*;
+use navig18xx::route::{paths_for_token_with, MapGraph, PathLimit};
+
+/// The companies whose tokens are placed on the 1867 map.
+const COMPANIES: [&str; 5] = ["CNR", "GW", "C&O", "CPR", "NTR"];
+
+/// Returns the map from the final operating round of the Bankruptcy Club's
+/// recorded game of 1867 (see the `1867_bc` example).
+fn map_1867_bc() -> (Box<dyn Game>, Example) {
+    let mut game = new_1867();
+    let hex = Hex::new(125.0);
+    let mut example = Example::new_game(&game, hex);
+    game.set_phase_name(example.map_mut(), "8");
+    let (cnr, gw, cno, cpr, ntr) = ("CNR", "GW", "C&O", "CPR", "NTR");
+    let tiles = vec![
+        tile_at("87", "B18").rotate_cw(1),
+        tile_at("63", "C17").token(0, cno),
+        tile_at("63", "D16").token(0, cnr),
+        tile_at("63", "E15").token(0, gw),
+        tile_at("42", "F14").rotate_acw(2),
+        tile_at("23", "G13").rotate_cw(1),
+        tile_at("27", "H12").rotate_acw(2),
+        tile_at("23", "I11").rotate_cw(1),
+        tile_at("24", "J10").rotate_acw(2),
+        tile_at("8", "K9").rotate_cw(1),
+        tile_at("16", "D18").rotate_acw(3),
+        tile_at("623", "E17").token(0, cno).token(1, cnr),
+        tile_at("124", "F16")
+            .token(0, cno)
+            .token(1, cnr)
+            .token(2, cpr),
+        tile_at("611", "G15").rotate_cw(1).token(0, cpr),
+        tile_at("204", "H14").rotate_acw(1),
+        tile_at("8", "I13").rotate_cw(2),
+        tile_at("X8", "J12").token(0, gw),
+        tile_at("31", "K11").rotate_acw(1),
+        tile_at("204", "L10").rotate_acw(2),
+        tile_at("57", "M9").rotate_cw(1),
+        tile_at("9", "N8").rotate_cw(1),
+        tile_at("15", "I15").rotate_cw(1).token(0, ntr),
+        tile_at("24", "J14").rotate_cw(1),
+        tile_at("911", "K13").rotate_acw(2),
+        tile_at("639", "L12")
+            .token(0, cpr)
+            .token(1, ntr)
+            .token(2, gw),
+        tile_at("58", "M13").rotate_cw(2),
+        tile_at("7", "C19").rotate_acw(2),
+    ];
+    example.place_tiles(tiles);
+    (Box::new(game), example)
+}
+
+/// Returns the search criteria for a company that owns a 5-train.
+fn criteria(game: &dyn Game, map: &Map, company: &str) -> Criteria {
+    Criteria {
+        token: map.token(company),
+        path_limit: Some(PathLimit::Cities { count: 5 }),
+        conflict_rule: game.single_route_conflicts(),
+        route_conflict_rule: game.multiple_routes_conflicts(),
+        token_rule: game.route_token_rule(map, company),
+    }
+}
+
+fn graph_construction(c: &mut Criterion) {
+    let hex = Hex::new(125.0);
+    let map_1861 = new_1861().create_map(&hex);
+    let (_game, example) = map_1867_bc();
+    let map_1867 = example.map();
+
+    let mut group = c.benchmark_group("graph");
+    group.bench_function("new_1861", |b| b.iter(|| MapGraph::new(&map_1861)));
+    group.bench_function("new_1867_bc", |b| {
+        b.iter(|| MapGraph::new(map_1867))
+    });
+    group.finish();
+}
+
+fn path_enumeration(c: &mut Criterion) {
+    let (game, example) = map_1867_bc();
+    let map = example.map();
+
+    let mut group = c.benchmark_group("paths");
+    group.sample_size(10);
+    group.bench_function("all_companies", |b| {
+        b.iter(|| {
+            COMPANIES
+                .iter()
+                .map(|company| {
+                    let criteria = criteria(game.as_ref(), map, company);
+                    paths_for_token(map, &criteria).len()
+                })
+                .sum::<usize>()
+        })
+    });
+    group.bench_function("all_companies_shared_graph", |b| {
+        b.iter(|| {
+            let graph = MapGraph::new(map);
+            COMPANIES
+                .iter()
+                .map(|company| {
+                    let criteria = criteria(game.as_ref(), map, company);
+                    paths_for_token_with(map, &graph, &criteria).len()
+                })
+                .sum::<usize>()
+        })
+    });
+    group.finish();
+}
+
+/// Returns the trains owned by a company that owns four trains.
+fn four_trains(game: &dyn Game) -> Trains {
+    ["2", "2", "3", "3"]
+        .iter()
+        .map(|name| {
+            *game
+                .train_types()
+                .into_iter()
+                .find(|t| game.train_name(t) == Some(name))
+                .unwrap()
+        })
+        .collect()
+}
+
+fn route_selection(c: &mut Criterion) {
+    let (game, example) = map_1867_bc();
+    let map = example.map();
+    let trains = four_trains(game.as_ref());
+    let criteria = Criteria {
+        token: map.token("CPR"),
+        path_limit: trains.path_limit(),
+        conflict_rule: game.single_route_conflicts(),
+        route_conflict_rule: game.multiple_routes_conflicts(),
+        token_rule: game.route_token_rule(map, "CPR"),
+    };
+    let paths = paths_for_token(map, &criteria);
+    let exhaustive = trains.clone().with_pruning(false);
+
+    let mut group = c.benchmark_group("routes");
+    group.sample_size(10);
+    group.bench_function("four_trains_pruned", |b| {
+        b.iter(|| trains.select_routes(paths.clone(), vec![]))
+    });
+    group.bench_function("four_trains_exhaustive", |b| {
+        b.iter(|| exhaustive.select_routes(paths.clone(), vec![]))
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    graph_construction,
+    path_enumeration,
+    route_selection
+);
+criterion_main!(benches);
@@ -0,0 +1,283 @@
+//! Draw badges (small boxed labels) on the map, such as the train name and
+//! revenue for each route, and the stop number of each visit.
+//!
+//! Each badge is positioned by a [BadgeLayout], which moves the badge away
+//! from its anchor point as needed to avoid any badges that have already
+//! been drawn (e.g., where routes cross each other or stop at the same
+//! city).
+
+use cairo::Context;
+
+use n18hex::theme::{AlignH, AlignV, Text};
+use n18hex::{Colour, Coord, Hex};
+use n18map::{HexAddress, Map};
+use n18route::{Route, StopLocation, Visit};
+use n18tile::{Draw, TokenSpace};
+
+/// A rectangular region, in map coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    /// The left edge.
+    pub x: f64,
+    /// The top edge.
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    /// Returns the rectangle with the specified size and centre.
+    pub fn centred_at(centre: Coord, width: f64, height: f64) -> Self {
+        Rect {
+            x: centre.x - 0.5 * width,
+            y: centre.y - 0.5 * height,
+            width,
+            height,
+        }
+    }
+
+    /// Returns the centre of this rectangle.
+    pub fn centre(&self) -> Coord {
+        Coord::from((self.x + 0.5 * self.width, self.y + 0.5 * self.height))
+    }
+
+    /// Returns `true` if this rectangle and `other` overlap.
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+}
+
+/// The directions in which badges are moved away from their anchor points,
+/// in order of preference.
+const DIRECTIONS: [(f64, f64); 8] = [
+    (0.0, -1.0),
+    (1.0, -1.0),
+    (1.0, 0.0),
+    (1.0, 1.0),
+    (0.0, 1.0),
+    (-1.0, 1.0),
+    (-1.0, 0.0),
+    (-1.0, -1.0),
+];
+
+/// The maximum number of badge widths (or heights) that a badge can be moved
+/// away from its anchor point.
+const MAX_DISTANCE: usize = 3;
+
+/// Positions badges so that they do not overlap each other.
+#[derive(Clone, Debug, Default)]
+pub struct BadgeLayout {
+    placed: Vec<Rect>,
+    gap: f64,
+}
+
+impl BadgeLayout {
+    /// Creates a new layout, where badges are separated by at least `gap`.
+    pub fn new(gap: f64) -> Self {
+        BadgeLayout {
+            placed: vec![],
+            gap,
+        }
+    }
+
+    /// Returns the region occupied by each badge that has been placed.
+    pub fn placed(&self) -> &[Rect] {
+        &self.placed
+    }
+
+    /// Returns `true` if `rect` does not overlap any placed badge.
+    pub fn is_free(&self, rect: &Rect) -> bool {
+        self.placed.iter().all(|other| !rect.overlaps(other))
+    }
+
+    /// Places a badge of the specified size next to `anchor`, and returns
+    /// the region that it occupies.
+    ///
+    /// Candidate positions are tried in each direction around the anchor,
+    /// moving further away from the anchor until a position that does not
+    /// overlap any placed badge is found.
+    /// If there is no such position, the badge is placed in the first
+    /// candidate position (directly above the anchor).
+    pub fn place(&mut self, anchor: Coord, width: f64, height: f64) -> Rect {
+        // Each successive candidate in the same direction is moved by the
+        // size of the badge, plus the gap between badges.
+        let offset = |dist: usize, size: f64| {
+            0.5 * size + self.gap + (dist - 1) as f64 * (size + self.gap)
+        };
+        let candidate = |dist: usize, (dx, dy): (f64, f64)| {
+            let centre = Coord::from((
+                anchor.x + dx * offset(dist, width),
+                anchor.y + dy * offset(dist, height),
+            ));
+            Rect::centred_at(centre, width, height)
+        };
+        let rect = (1..=MAX_DISTANCE)
+            .flat_map(|dist| DIRECTIONS.iter().map(move |dir| (dist, *dir)))
+            .map(|(dist, dir)| candidate(dist, dir))
+            .find(|rect| self.is_free(rect))
+            .unwrap_or_else(|| candidate(1, DIRECTIONS[0]));
+        self.placed.push(rect);
+        rect
+    }
+}
+
+/// Returns the text style for badges, which is the token label style
+/// without a maximum width, so that each badge contains a single line.
+fn badge_text(hex: &Hex) -> Text {
+    let mut text = hex.theme.token_label;
+    text.no_max_width();
+    text
+}
+
+/// Returns the size of the badge that contains `text`.
+pub fn badge_size(hex: &Hex, ctx: &Context, text: &str) -> (f64, f64) {
+    let size = badge_text(hex).labeller(ctx, hex).size(text);
+    let pad = 0.2 * size.height;
+    (size.width + 2.0 * pad, size.height + 2.0 * pad)
+}
+
+/// Draws a badge that contains `text` in the region `rect`, with a border
+/// of the specified colour.
+pub fn draw_badge(
+    hex: &Hex,
+    ctx: &Context,
+    text: &str,
+    rect: &Rect,
+    colour: Colour,
+) {
+    ctx.rectangle(rect.x, rect.y, rect.width, rect.height);
+    hex.theme.label_circle.apply_fill(ctx);
+    ctx.fill_preserve().unwrap();
+    colour.apply_colour(ctx);
+    ctx.set_line_width(2.0 * hex.theme.label_circle.line_width(hex));
+    ctx.stroke().unwrap();
+    let mut labeller = badge_text(hex).labeller(ctx, hex);
+    labeller.halign(AlignH::Centre).valign(AlignV::Middle);
+    labeller.draw(text, rect.centre());
+}
+
+/// Places a badge that contains `text` next to `anchor` and draws it, and
+/// returns the region that it occupies.
+pub fn place_badge(
+    hex: &Hex,
+    ctx: &Context,
+    layout: &mut BadgeLayout,
+    text: &str,
+    anchor: Coord,
+    colour: Colour,
+) -> Rect {
+    let (width, height) = badge_size(hex, ctx, text);
+    let rect = layout.place(anchor, width, height);
+    draw_badge(hex, ctx, text, &rect, colour);
+    rect
+}
+
+/// Returns the location of the city or dit at which a visit occurs, in map
+/// coordinates.
+pub fn visit_coord(
+    hex: &Hex,
+    ctx: &Context,
+    map: &Map,
+    visit: &Visit,
+) -> Option<Coord> {
+    let tile = map.tile_at(visit.addr)?;
+    let m = map.prepare_to_draw(visit.addr, hex, ctx);
+    // NOTE: off-board tiles that only show their off-board track segments
+    // do not draw their revenue centres, so use the hex centre instead.
+    let local = if tile.only_draw_offboard_track() {
+        Some(Coord::from((0.0, 0.0)))
+    } else {
+        match visit.visits {
+            StopLocation::City { ix } => {
+                tile.cities().get(ix).and_then(|city| {
+                    city.define_boundary(hex, ctx);
+                    let extents = ctx.fill_extents().ok();
+                    ctx.new_path();
+                    extents.map(|(x0, y0, x1, y1)| {
+                        Coord::from((0.5 * (x0 + x1), 0.5 * (y0 + y1)))
+                    })
+                })
+            }
+            StopLocation::Dit { ix } => tile
+                .dits()
+                .get(ix)
+                .and_then(|dit| tile.tracks()[dit.track_ix].dit_coord(hex)),
+        }
+    };
+    // Convert from the tile's coordinates to the map coordinates.
+    let device = local.map(|c| ctx.user_to_device(c.x, c.y));
+    ctx.set_matrix(m);
+    device
+        .and_then(|(x, y)| ctx.device_to_user(x, y).ok())
+        .map(Coord::from)
+}
+
+/// Returns the centre of a token space, in map coordinates.
+pub fn token_space_coord(
+    hex: &Hex,
+    ctx: &Context,
+    map: &Map,
+    addr: HexAddress,
+    token_space: &TokenSpace,
+) -> Option<Coord> {
+    let tile = map.tile_at(addr)?;
+    let m = map.prepare_to_draw(addr, hex, ctx);
+    tile.define_token_space(token_space, hex, ctx);
+    let extents = ctx.fill_extents().ok();
+    ctx.new_path();
+    // Convert from the tile's coordinates to the map coordinates.
+    let device = extents.map(|(x0, y0, x1, y1)| {
+        ctx.user_to_device(0.5 * (x0 + x1), 0.5 * (y0 + y1))
+    });
+    ctx.set_matrix(m);
+    device
+        .and_then(|(x, y)| ctx.device_to_user(x, y).ok())
+        .map(Coord::from)
+}
+
+/// Returns the centre of a map hex, in map coordinates.
+pub fn hex_centre_coord(
+    hex: &Hex,
+    ctx: &Context,
+    map: &Map,
+    addr: HexAddress,
+) -> Coord {
+    let m = map.prepare_to_draw(addr, hex, ctx);
+    let (x, y) = ctx.user_to_device(0.0, 0.0);
+    ctx.set_matrix(m);
+    ctx.device_to_user(x, y).unwrap().into()
+}
+
+/// Draws a badge that shows the stop number at each visit where a route
+/// earns revenue, and a badge that contains `title` (e.g., the train name
+/// and route revenue) next to the final visit.
+pub fn draw_route_badges(
+    hex: &Hex,
+    ctx: &Context,
+    map: &Map,
+    route: &Route,
+    title: &str,
+    colour: Colour,
+    layout: &mut BadgeLayout,
+) {
+    let stops: Vec<Coord> = route
+        .visits
+        .iter()
+        .filter(|visit| visit.revenue > 0)
+        .filter_map(|visit| visit_coord(hex, ctx, map, visit))
+        .collect();
+    for (ix, coord) in stops.iter().enumerate() {
+        let text = format!("{}", ix + 1);
+        place_badge(hex, ctx, layout, &text, *coord, colour);
+    }
+    let terminus = route
+        .visits
+        .last()
+        .and_then(|visit| visit_coord(hex, ctx, map, visit));
+    if let Some(coord) = terminus {
+        place_badge(hex, ctx, layout, title, coord, colour);
+    }
+}
@@ -0,0 +1,172 @@
+//! Decorate exported map images with a title block and a legend.
+//!
+//! The [Decorations] struct defines which elements are drawn, and
+//! [draw_decorations] draws them in a single column, which is typically
+//! placed to the right of the map.
+
+use cairo::Context;
+
+use n18hex::theme::{AlignH, AlignV, Text};
+use n18hex::{Coord, Hex, HexColour};
+use n18map::Map;
+use n18tile::{Label, Tile};
+
+/// Defines the elements of the title block and legend that are drawn
+/// alongside a map.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Decorations {
+    /// The title, such as the game name.
+    pub title: Option<String>,
+    /// The name of the current game phase.
+    pub phase: Option<String>,
+    /// The date, such as the date on which the image was exported.
+    pub date: Option<String>,
+    /// Whether to draw each company's token and name.
+    pub token_key: bool,
+    /// Whether to draw each kind of terrain hex (e.g., hexes with building
+    /// costs) and any track barriers that appear on the map.
+    pub terrain_key: bool,
+}
+
+impl Decorations {
+    /// Returns `true` if there are no elements to draw.
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.phase.is_none()
+            && self.date.is_none()
+            && !self.token_key
+            && !self.terrain_key
+    }
+}
+
+/// Returns each distinct terrain tile on the map, which are tiles that have
+/// no track or revenue centres and at least one note (e.g., building costs),
+/// along with the text of their notes.
+fn terrain_tiles(map: &Map) -> Vec<(&Tile, String)> {
+    let mut tiles: Vec<(&Tile, String)> = vec![];
+    for addr in map.hex_address_iter() {
+        let tile = match map.tile_at(*addr) {
+            Some(tile) => tile,
+            None => continue,
+        };
+        let is_terrain = tile.colour == HexColour::Empty
+            && tile.tracks().is_empty()
+            && tile.cities().is_empty()
+            && tile.dits().is_empty();
+        if !is_terrain || tiles.iter().any(|(t, _)| t.name == tile.name) {
+            continue;
+        }
+        let notes: Vec<&str> = tile
+            .labels()
+            .iter()
+            .filter_map(|(label, _posn)| match label {
+                Label::Note(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        if !notes.is_empty() {
+            tiles.push((tile, notes.join(" ")));
+        }
+    }
+    tiles
+}
+
+/// Draws the title block and legend as a single column, whose top-left
+/// corner is `origin`.
+///
+/// Returns the bottom-left corner of the column.
+pub fn draw_decorations(
+    hex: &Hex,
+    ctx: &Context,
+    map: &Map,
+    decorations: &Decorations,
+    origin: Coord,
+) -> Coord {
+    let spacing = 0.1 * hex.max_d;
+    let (x, mut y) = (origin.x, origin.y);
+
+    // Draws a line of text, and returns the height of the text.
+    let draw_text = |style: &Text, text: &str, x: f64, y: f64| -> f64 {
+        let mut labeller = style.labeller(ctx, hex);
+        labeller.halign(AlignH::Left).valign(AlignV::Top);
+        labeller.draw(text, Coord::from((x, y)));
+        labeller.size(text).height
+    };
+
+    if let Some(title) = &decorations.title {
+        y += draw_text(&hex.theme.city_label, title, x, y) + spacing;
+    }
+    for text in [&decorations.phase, &decorations.date]
+        .into_iter()
+        .flatten()
+    {
+        y += draw_text(&hex.theme.location_label, text, x, y) + spacing;
+    }
+
+    // Each key entry is drawn as a swatch, followed by a label.
+    let swatch = 0.5 * hex.max_d;
+    let label_x = x + swatch + spacing;
+    let label_y = |y: f64, height: f64| y + 0.5 * (swatch - height);
+    let label_height = |text: &str| {
+        hex.theme
+            .location_label
+            .labeller(ctx, hex)
+            .size(text)
+            .height
+    };
+
+    if decorations.token_key {
+        let radius = 0.5 * swatch;
+        for name in map.token_names() {
+            let token = match map.try_token(name) {
+                Some(token) => token,
+                None => continue,
+            };
+            ctx.new_path();
+            ctx.arc(
+                x + radius,
+                y + radius,
+                radius,
+                0.0,
+                2.0 * std::f64::consts::PI,
+            );
+            token.draw(hex, ctx, name, 0.0);
+            ctx.new_path();
+            let ly = label_y(y, label_height(name));
+            draw_text(&hex.theme.location_label, name, label_x, ly);
+            y += swatch + spacing;
+        }
+    }
+
+    if decorations.terrain_key {
+        // Draw each terrain tile at a reduced size.
+        let scale = swatch / hex.max_d;
+        for (tile, notes) in terrain_tiles(map) {
+            let m = ctx.matrix();
+            ctx.translate(x + 0.5 * swatch, y + 0.5 * swatch);
+            ctx.scale(scale, scale);
+            tile.draw(ctx, hex);
+            hex.define_boundary(ctx);
+            hex.theme.hex_border.apply_line_and_stroke(ctx, hex);
+            ctx.stroke().unwrap();
+            ctx.set_matrix(m);
+            let text = format!("Building cost: {}", notes);
+            let ly = label_y(y, label_height(&text));
+            draw_text(&hex.theme.location_label, &text, label_x, ly);
+            y += swatch + spacing;
+        }
+        if !map.barriers().is_empty() {
+            let mid_y = y + 0.5 * swatch;
+            ctx.move_to(x, mid_y);
+            ctx.line_to(x + swatch, mid_y);
+            hex.theme.hex_barrier.apply_line_and_stroke(ctx, hex);
+            ctx.stroke().unwrap();
+            let text = "Track barrier";
+            let ly = label_y(y, label_height(text));
+            draw_text(&hex.theme.location_label, text, label_x, ly);
+            y += swatch + spacing;
+        }
+    }
+
+    Coord::from((x, y))
+}
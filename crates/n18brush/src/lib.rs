@@ -1,12 +1,23 @@
+pub mod badges;
+pub mod legend;
+pub mod render;
+pub mod sheet;
+pub mod snapshot;
+pub mod terrain;
+
 use cairo::Context;
 use log::debug;
+use std::collections::BTreeMap;
 
-use n18hex::{Colour, Hex, HexColour};
-use n18map::{HexAddress, HexIter, Map};
-use n18route::{Path, Route, Step, StopLocation, Visit};
-use n18tile::{Connection, DitShape, Draw, Tile, TokenSpace};
+use n18hex::theme::{AlignH, AlignV};
+use n18hex::{Colour, Coord, Hex, HexColour, HexCorner, HexFace};
+use n18map::{Coordinates, HexAddress, HexIter, Map, Terrain};
+use n18route::{MapGraph, Path, Reach, Route, Step, StopLocation, Visit};
+use n18tile::{Connection, DitShape, Draw, Label, Tile, TokenSpace};
 use n18token::Token;
 
+use render::RenderOptions;
+
 /// Clears the surface with a uniform colour, or makes the surface entirely
 /// transparent if no colour is provided.
 pub fn clear_surface<C>(ctx: &Context, colour: C)
@@ -54,12 +65,37 @@ pub fn draw_hex_backgrounds(
     hex_iter.restart();
 }
 
+/// Draws the tile (if any) and tokens on each map hex.
+///
+/// Terrain (see [Map::set_terrain]) is drawn on empty hexes and on hexes
+/// whose tiles have no colour (i.e., pre-printed hexes), between the tile
+/// background and the tile contents.
 pub fn draw_tiles(hex: &Hex, ctx: &Context, mut hex_iter: &mut HexIter<'_>) {
+    // NOTE: copy the terrain, because the map cannot be borrowed while
+    // iterating over the map hexes.
+    let terrain: BTreeMap<HexAddress, Terrain> =
+        HexIter::map(hex_iter).terrain_iter().collect();
+
     hex_iter.restart();
     for hex_state in &mut hex_iter {
+        let terrain_opt = terrain.get(&hex_state.addr).copied();
+        // NOTE: undo the tile rotation, so that the terrain is upright.
+        let draw_terrain = |terrain| {
+            let m = ctx.matrix();
+            ctx.rotate(-hex_state.tile_rotation);
+            terrain::draw_terrain(hex, ctx, terrain);
+            ctx.set_matrix(m);
+        };
         if let Some((tile, token_spaces)) = hex_state.tile_state {
             // Draw the tile and any tokens.
-            tile.draw(ctx, hex);
+            match terrain_opt {
+                Some(terrain) if tile.colour == HexColour::Empty => {
+                    tile.draw_background(ctx, hex);
+                    draw_terrain(terrain);
+                    tile.draw_foreground(ctx, hex);
+                }
+                _ => tile.draw(ctx, hex),
+            }
             for (token_space, map_token) in token_spaces.iter() {
                 if tile.define_token_space(token_space, hex, ctx) {
                     let name =
@@ -72,6 +108,9 @@ pub fn draw_tiles(hex: &Hex, ctx: &Context, mut hex_iter: &mut HexIter<'_>) {
         } else {
             // Fill empty hexes with a background colour.
             draw_empty_hex(hex, ctx);
+            if let Some(terrain) = terrain_opt {
+                draw_terrain(terrain);
+            }
         }
     }
 
@@ -102,19 +141,115 @@ pub fn outline_empty_hexes(
     hex_iter.restart();
 }
 
-/// Draws the core map layers: hex backgrounds, tiles, empty hex borders, and
-/// track barriers.
+/// Hides the borders between adjacent map hexes that are part of the same
+/// location (e.g., an off-board area that spans several hexes; see
+/// [Map::set_location]), so that each location is drawn as a single area.
+pub fn merge_location_borders(
+    hex: &Hex,
+    ctx: &Context,
+    mut hex_iter: &mut HexIter<'_>,
+) {
+    // NOTE: identify the shared faces (relative to each tile's orientation)
+    // before iterating over the map hexes, because the map cannot be
+    // borrowed while iterating over the map hexes.
+    let map = HexIter::map(hex_iter);
+    let shared: BTreeMap<HexAddress, (HexColour, Vec<HexFace>)> = map
+        .location_iter()
+        .filter_map(|(addr, name)| {
+            let colour = map.tile_at(addr)?.colour;
+            let faces: Vec<HexFace> = map
+                .neighbours(addr)
+                .into_iter()
+                .filter(|nbr| map.location(nbr.addr) == Some(name))
+                .map(|nbr| nbr.tile_face)
+                .collect();
+            (!faces.is_empty()).then_some((addr, (colour, faces)))
+        })
+        .collect();
+    if shared.is_empty() {
+        return;
+    }
+
+    hex_iter.restart();
+    for hex_state in &mut hex_iter {
+        let (colour, faces) = if let Some(entry) = shared.get(&hex_state.addr)
+        {
+            entry
+        } else {
+            continue;
+        };
+        for face in faces {
+            let corners = face.corners();
+            let c0 = hex.corner_coord(&corners.0);
+            let c1 = hex.corner_coord(&corners.1);
+            ctx.move_to(c0.x, c0.y);
+            ctx.line_to(c1.x, c1.y);
+        }
+        // NOTE: cover the hex border with the tile's background colour.
+        ctx.set_line_width(2.0 * hex.theme.hex_border.line_width(hex));
+        ctx.set_line_cap(cairo::LineCap::Butt);
+        hex.theme.apply_hex_colour(ctx, *colour);
+        ctx.stroke().unwrap();
+    }
+
+    hex_iter.restart();
+}
+
+/// Draws the core map layers: hex backgrounds, tiles, empty hex borders,
+/// track barriers, and markers.
 pub fn draw_map(hex: &Hex, ctx: &Context, hex_iter: &mut HexIter<'_>) {
     draw_hex_backgrounds(hex, ctx, hex_iter);
     draw_tiles(hex, ctx, hex_iter);
+    merge_location_borders(hex, ctx, hex_iter);
     outline_empty_hexes(hex, ctx, hex_iter);
     // Note: use the fully-quantified syntax to call HexIter::map() rather
     // than Iterator::map() on `hex_iter`.
     draw_barriers(hex, ctx, HexIter::map(hex_iter));
+    draw_markers(hex, ctx, hex_iter);
+}
+
+/// Draws the coordinates of each map hex (e.g., "E11") near the top-left
+/// corner of the hex.
+pub fn draw_hex_coordinates(
+    hex: &Hex,
+    ctx: &Context,
+    mut hex_iter: &mut HexIter<'_>,
+    coords: &Coordinates,
+) {
+    // Place each label a quarter of the way from the corner to the centre.
+    let corner = hex.corner_coord(&HexCorner::TopLeft);
+    let centre = Coord::from((0.75 * corner.x, 0.75 * corner.y));
+
+    hex_iter.restart();
+    for hex_state in &mut hex_iter {
+        let text = if let Some(text) = coords.format(&hex_state.addr) {
+            text
+        } else {
+            continue;
+        };
+        // NOTE: undo the tile rotation, so that the labels are upright.
+        let m = ctx.matrix();
+        ctx.rotate(-hex_state.tile_rotation);
+        let labeller = hex.theme.coordinate_label.labeller(ctx, hex);
+        labeller.draw(&text, centre);
+        ctx.set_matrix(m);
+    }
+
+    hex_iter.restart();
+}
+
+/// Draws the row and column labels around the map border (e.g., "A", "B",
+/// "C" and "1", "2", "3"), if the map defines a coordinate system for these
+/// labels; see [Map::set_border_coordinates].
+pub fn draw_border_labels(hex: &Hex, ctx: &Context, map: &Map) {
+    let labeller = hex.theme.border_label.labeller(ctx, hex);
+    for (text, coord) in map.border_labels(hex) {
+        labeller.draw(&text, coord);
+    }
 }
 
 /// Draws the core map layers for a subset of map hexes: hex backgrounds,
-/// tiles, empty hex borders, and track barriers.
+/// tiles, empty hex borders, track barriers, and markers.
 ///
 /// The subset is defined by `hex_iter`; see [Map::hex_subset_iter].
 pub fn draw_map_subset(
@@ -125,8 +260,10 @@ pub fn draw_map_subset(
 ) {
     draw_hex_backgrounds(hex, ctx, hex_iter);
     draw_tiles(hex, ctx, hex_iter);
+    merge_location_borders(hex, ctx, hex_iter);
     outline_empty_hexes(hex, ctx, hex_iter);
     draw_barriers_subset(hex, ctx, map, hex_iter);
+    draw_markers(hex, ctx, hex_iter);
 }
 
 pub fn draw_barriers_subset(
@@ -168,6 +305,61 @@ pub fn draw_barriers(hex: &Hex, ctx: &Context, map: &Map) {
     }
 }
 
+/// Draws the markers (e.g., port and mine markers) that have been placed on
+/// each map hex, as labels that are stacked upwards from the bottom of the
+/// hex; see [Map::place_marker].
+pub fn draw_markers(
+    hex: &Hex,
+    ctx: &Context,
+    mut hex_iter: &mut HexIter<'_>,
+) {
+    // NOTE: copy the marker names, because the map cannot be borrowed while
+    // iterating over the map hexes.
+    let markers: BTreeMap<HexAddress, Vec<String>> = HexIter::map(hex_iter)
+        .marker_iter()
+        .map(|(addr, markers)| {
+            (addr, markers.iter().map(|m| m.name.clone()).collect())
+        })
+        .collect();
+    if markers.is_empty() {
+        return;
+    }
+
+    hex_iter.restart();
+    for hex_state in &mut hex_iter {
+        let names = if let Some(names) = markers.get(&hex_state.addr) {
+            names
+        } else {
+            continue;
+        };
+        // NOTE: undo the tile rotation, so that the markers are upright.
+        let m = ctx.matrix();
+        ctx.rotate(-hex_state.tile_rotation);
+        let mut labeller = hex.theme.token_label.labeller(ctx, hex);
+        labeller.halign(AlignH::Centre).valign(AlignV::Bottom);
+        let mut y = 0.35 * hex.min_d;
+        for name in names {
+            let size = labeller.size(name);
+            let pad = 0.2 * size.height;
+            ctx.rectangle(
+                -0.5 * size.width - pad,
+                y - size.height - pad,
+                size.width + 2.0 * pad,
+                size.height + 2.0 * pad,
+            );
+            hex.theme.label_circle.apply_fill(ctx);
+            ctx.fill_preserve().unwrap();
+            hex.theme.label_circle.apply_line_and_stroke(ctx, hex);
+            ctx.stroke().unwrap();
+            labeller.draw(name, Coord::from((0.0, y)));
+            y -= size.height + 3.0 * pad;
+        }
+        ctx.set_matrix(m);
+    }
+
+    hex_iter.restart();
+}
+
 /// Highlights tokens that satisfy a predicate by drawing borders around them
 /// and optionally filling the token space with, e.g., a semi-transparent
 /// colour.
@@ -220,6 +412,28 @@ pub fn highlight_token_space(
     }
 }
 
+/// Fills a token space with the `fill` colour and draws a border around it.
+pub fn fill_token_space(
+    hex: &Hex,
+    ctx: &Context,
+    map: &Map,
+    hex_addr: HexAddress,
+    token_space: &TokenSpace,
+    fill: n18hex::Colour,
+    border: n18hex::Colour,
+) {
+    if let Some(tile) = map.tile_at(hex_addr) {
+        let m = map.prepare_to_draw(hex_addr, hex, ctx);
+        tile.define_token_space(token_space, hex, ctx);
+        fill.apply_colour(ctx);
+        ctx.fill_preserve().unwrap();
+        border.apply_colour(ctx);
+        hex.theme.token_space_highlight.apply_line(ctx, hex);
+        ctx.stroke().unwrap();
+        ctx.set_matrix(m);
+    }
+}
+
 /// Highlights map hexes that satisfy a predicate by covering all other hexes
 /// with a partially-transparent layer.
 ///
@@ -284,6 +498,30 @@ pub fn highlight_active_hex(
     hex_iter.restart();
 }
 
+/// Draws a selection rectangle with opposite corners `(x0, y0)` and
+/// `(x1, y1)`, such as the area that the user has selected by dragging the
+/// mouse.
+///
+/// The rectangle is drawn with a dashed border and a partially-transparent
+/// fill.
+pub fn draw_selection_rect(
+    hex: &Hex,
+    ctx: &Context,
+    corners: (f64, f64, f64, f64),
+    colour: Colour,
+) {
+    let (x0, y0, x1, y1) = corners;
+    ctx.rectangle(x0.min(x1), y0.min(y1), (x1 - x0).abs(), (y1 - y0).abs());
+    colour.with_alpha(51).apply_colour(ctx);
+    ctx.fill_preserve().unwrap();
+    colour.apply_colour(ctx);
+    let width = hex.theme.hex_highlight.line_width(hex);
+    ctx.set_line_width(0.5 * width);
+    ctx.set_dash(&[2.0 * width, width], 0.0);
+    ctx.stroke().unwrap();
+    ctx.set_dash(&[], 0.0);
+}
+
 /// Highlights routes, using a different colour for each route.
 pub fn highlight_routes<F, C, R>(
     hex: &Hex,
@@ -424,6 +662,14 @@ pub fn highlight_route(hex: &Hex, ctx: &Context, map: &Map, route: &Route) {
     highlight_visits(hex, ctx, map, &route.visits);
 }
 
+/// Highlights a single visit along a route, using the current source.
+///
+/// Note that a visit where no revenue is earned is drawn in the default
+/// track colour, as in [highlight_route].
+pub fn highlight_visit(hex: &Hex, ctx: &Context, map: &Map, visit: &Visit) {
+    highlight_visits(hex, ctx, map, std::slice::from_ref(visit))
+}
+
 pub fn highlight_path(hex: &Hex, ctx: &Context, map: &Map, path: &Path) {
     // Draw track segments first.
     highlight_steps(hex, ctx, map, &path.steps);
@@ -431,6 +677,109 @@ pub fn highlight_path(hex: &Hex, ctx: &Context, map: &Map, path: &Path) {
     highlight_visits(hex, ctx, map, &path.visits);
 }
 
+/// Highlights the track segments and cities that a company can reach from
+/// its tokens (see [n18route::reachable_from_tokens]), using the current
+/// source.
+pub fn highlight_reach(hex: &Hex, ctx: &Context, map: &Map, reach: &Reach) {
+    let steps: Vec<Step> = reach.steps().copied().collect();
+    highlight_steps(hex, ctx, map, &steps);
+    for (addr, city_ix) in reach.cities() {
+        let Some(tile) = map.tile_at(addr) else {
+            continue;
+        };
+        if tile.only_draw_offboard_track() {
+            continue;
+        }
+        let m = map.prepare_to_draw(addr, hex, ctx);
+        hex.theme.token_space_highlight.apply_line(ctx, hex);
+        tile.cities()[city_ix].define_boundary(hex, ctx);
+        ctx.stroke().unwrap();
+        ctx.set_matrix(m);
+    }
+}
+
+/// Draws the connectivity recorded by `graph` on top of the map: a line
+/// between the centres of each pair of connected map hexes, and a marker at
+/// the centre of each map hex that contains cities or dits.
+///
+/// City markers are larger than dit markers, and off-board locations are
+/// drawn with a hollow marker.
+pub fn draw_route_graph(
+    hex: &Hex,
+    ctx: &Context,
+    map: &Map,
+    graph: &MapGraph,
+    colour: Colour,
+) {
+    let centre = |addr| badges::hex_centre_coord(hex, ctx, map, addr);
+    let width = hex.theme.hex_highlight.line_width(hex);
+    colour.apply_colour(ctx);
+    ctx.set_line_width(0.5 * width);
+    for edge in graph.edges() {
+        let start = centre(edge.addr);
+        let end = centre(edge.adj_addr);
+        ctx.move_to(start.x, start.y);
+        ctx.line_to(end.x, end.y);
+        ctx.stroke().unwrap();
+    }
+    for node in graph.nodes() {
+        let radius = if node.cities > 0 {
+            0.08 * hex.max_d
+        } else if node.dits > 0 {
+            0.05 * hex.max_d
+        } else {
+            continue;
+        };
+        let coord = centre(node.addr);
+        ctx.new_path();
+        ctx.arc(coord.x, coord.y, radius, 0.0, 2.0 * std::f64::consts::PI);
+        if node.off_board {
+            ctx.stroke().unwrap();
+        } else {
+            ctx.fill().unwrap();
+        }
+    }
+}
+
+/// Draws the tiles in the map's planning layer (see [Map::plan]) that
+/// satisfy a predicate, on top of the map with the specified opacity (from
+/// `0.0` to `1.0`).
+///
+/// The planned tiles are drawn as a single group and then composited onto
+/// the map, so that overlapping track segments do not accumulate opacity.
+pub fn draw_planned_tiles<P>(
+    hex: &Hex,
+    ctx: &Context,
+    map: &Map,
+    alpha: f64,
+    mut predicate: P,
+) where
+    P: FnMut(&HexAddress) -> bool,
+{
+    let plan = map.plan();
+    if plan.is_empty() {
+        return;
+    }
+    ctx.push_group();
+    for (addr, planned) in plan.iter() {
+        if !predicate(&addr) {
+            continue;
+        }
+        let Some(tile) = map.tile(&planned.tile) else {
+            continue;
+        };
+        // NOTE: the planned rotation replaces the current tile's rotation.
+        let current = map
+            .hex_state(addr)
+            .map(|hs| hs.rotation().radians())
+            .unwrap_or(0.0);
+        let radians = planned.rotation.radians() - current;
+        draw_tile_at(hex, ctx, map, &addr, tile, radians);
+    }
+    ctx.pop_group_to_source().unwrap();
+    ctx.paint_with_alpha(alpha).unwrap();
+}
+
 /// Draw an arbitrary tile at the specified map hex, rather than the tile that
 /// is currently placed at the map hex (if any).
 pub fn draw_tile_at(
@@ -497,6 +846,122 @@ pub fn draw_tile_and_tokens_at<'a, T>(
     ctx.set_matrix(m);
 }
 
+/// Selects the overlays that [draw_tile_preview] draws on top of a tile, to
+/// help when authoring new tile definitions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TileOverlays {
+    /// Mark the start (green) and end (red) of each track segment, labelled
+    /// by the segment's index.
+    pub track_ends: bool,
+    /// Outline each token space, labelled by its index (as used in saved
+    /// maps and games).
+    pub token_spaces: bool,
+    /// Mark the anchor point of the tile name and of each tile label,
+    /// labelled by the label's index.
+    pub label_anchors: bool,
+}
+
+impl TileOverlays {
+    /// Returns the overlay settings where every overlay is drawn.
+    pub fn all() -> Self {
+        TileOverlays {
+            track_ends: true,
+            token_spaces: true,
+            label_anchors: true,
+        }
+    }
+}
+
+/// Draws a tile and the selected overlays, centred on the current origin.
+pub fn draw_tile_preview(
+    hex: &Hex,
+    ctx: &Context,
+    tile: &Tile,
+    overlays: &TileOverlays,
+) {
+    tile.draw(ctx, hex);
+
+    let radius = 0.03 * hex.max_d;
+    let mut text = hex.theme.coordinate_label;
+    text.font_size(10.0).bold();
+    let labeller = |colour: Colour| {
+        let mut labeller = text.labeller(ctx, hex);
+        labeller.colour(colour);
+        labeller
+    };
+    let draw_marker = |coord: Coord, colour: Colour| {
+        ctx.new_path();
+        ctx.arc(coord.x, coord.y, radius, 0.0, 2.0 * std::f64::consts::PI);
+        colour.apply_colour(ctx);
+        ctx.fill().unwrap();
+    };
+
+    if overlays.track_ends {
+        let start_colour = Colour::from((0, 160, 0));
+        let end_colour = Colour::from((200, 0, 0));
+        for (ix, track) in tile.tracks().iter().enumerate() {
+            let label = ix.to_string();
+            let start = track.start(hex);
+            draw_marker(start, start_colour);
+            labeller(start_colour).draw(&label, start.scale_by(0.85));
+            let end = track.end(hex);
+            draw_marker(end, end_colour);
+            labeller(end_colour).draw(&label, end.scale_by(0.85));
+        }
+    }
+
+    if overlays.token_spaces {
+        let colour = Colour::from((0, 76, 179));
+        for (ix, space) in tile.token_spaces().iter().enumerate() {
+            ctx.new_path();
+            if !tile.define_token_space(space, hex, ctx) {
+                continue;
+            }
+            let (x0, y0, x1, y1) = ctx.fill_extents().unwrap();
+            colour.apply_colour(ctx);
+            hex.theme.token_space_highlight.apply_line(ctx, hex);
+            ctx.stroke().unwrap();
+            let centre = Coord::from((0.5 * (x0 + x1), 0.5 * (y0 + y1)));
+            labeller(colour).draw(&ix.to_string(), centre);
+        }
+    }
+
+    if overlays.label_anchors {
+        let colour = Colour::from((160, 0, 160));
+        if tile.is_tile_name_visible() {
+            let anchor = Label::tile_name_position(hex).coord(hex);
+            draw_marker(anchor, colour);
+        }
+        for (ix, (_label, pos)) in tile.labels().iter().enumerate() {
+            let anchor = pos.coord(hex);
+            draw_marker(anchor, colour);
+            let offset = Coord::from((0.0, 2.5 * radius));
+            labeller(colour).draw(&ix.to_string(), &anchor + &offset);
+        }
+    }
+    ctx.new_path();
+}
+
+/// Saves a PNG image of a tile and the selected overlays, drawn at the size
+/// of the provided hexagon.
+///
+/// See [draw_tile_preview] for details.
+pub fn save_tile_preview<P: AsRef<std::path::Path>>(
+    hex: &Hex,
+    tile: &Tile,
+    overlays: &TileOverlays,
+    dest: P,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let margin = hex.theme.tile_margin.margin(hex);
+    let width = hex.max_d + 2.0 * margin;
+    let draw_fn = |ctx: &Context| {
+        clear_surface(ctx, Colour::WHITE);
+        ctx.translate(0.5 * width, 0.5 * width);
+        draw_tile_preview(hex, ctx, tile, overlays);
+    };
+    ImageFormat::Png.save_image(width, width, 1.0, draw_fn, dest)
+}
+
 /// Supported output image formats.
 #[derive(Clone, Copy, Debug)]
 pub enum ImageFormat {
@@ -519,11 +984,56 @@ impl ImageFormat {
         }
     }
 
+    /// Returns the image format associated with a filename extension, if
+    /// any.
+    /// The extension is not case-sensitive.
+    ///
+    /// ```rust
+    /// # use n18brush::ImageFormat;
+    /// let format = ImageFormat::from_extension("SVG");
+    /// assert!(matches!(format, Some(ImageFormat::Svg)));
+    /// assert!(ImageFormat::from_extension("jpg").is_none());
+    /// ```
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        use ImageFormat::*;
+        [Pdf, Png, Svg]
+            .into_iter()
+            .find(|format| ext.eq_ignore_ascii_case(format.extension()))
+    }
+
     /// Saves the image drawn by `draw_fn` to an output file.
+    ///
+    /// The image is drawn at the given `scale`, so the output image will
+    /// have dimensions `scale * width` by `scale * height`.
+    /// For PNG images, use [dpi_scale] to choose the scale for a desired
+    /// print resolution.
     pub fn save_image<F, P>(
         &self,
         width: f64,
         height: f64,
+        scale: f64,
+        draw_fn: F,
+        dest: P,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        F: FnOnce(&cairo::Context),
+        P: std::convert::AsRef<std::path::Path>,
+    {
+        let options = RenderOptions::default();
+        self.save_image_with(&options, width, height, scale, draw_fn, dest)
+    }
+
+    /// Saves the image drawn by `draw_fn` to an output file, as per
+    /// [ImageFormat::save_image], using the provided rendering options.
+    ///
+    /// Note that the font family in `options` only affects hexagons that
+    /// have been configured with [RenderOptions::apply_to_hex].
+    pub fn save_image_with<F, P>(
+        &self,
+        options: &RenderOptions,
+        width: f64,
+        height: f64,
+        scale: f64,
         draw_fn: F,
         dest: P,
     ) -> Result<(), Box<dyn std::error::Error>>
@@ -532,20 +1042,26 @@ impl ImageFormat {
         P: std::convert::AsRef<std::path::Path>,
     {
         use ImageFormat::*;
+        let width = scale * width;
+        let height = scale * height;
         match self {
             Pdf => {
                 let surf = cairo::PdfSurface::new(width, height, dest)?;
                 let ctx = cairo::Context::new(&surf)?;
+                ctx.scale(scale, scale);
+                options.apply(&ctx);
                 draw_fn(&ctx);
                 surf.finish();
             }
             Png => {
                 let surf = cairo::ImageSurface::create(
                     cairo::Format::ARgb32,
-                    width as i32,
-                    height as i32,
+                    width.ceil() as i32,
+                    height.ceil() as i32,
                 )?;
                 let ctx = cairo::Context::new(&surf)?;
+                ctx.scale(scale, scale);
+                options.apply(&ctx);
                 let mut out_file = std::fs::File::create(dest.as_ref())
                     .expect("Could not create output file");
                 draw_fn(&ctx);
@@ -554,12 +1070,115 @@ impl ImageFormat {
             Svg => {
                 let surf = cairo::SvgSurface::new(width, height, Some(dest))?;
                 let ctx = cairo::Context::new(&surf)?;
+                ctx.scale(scale, scale);
+                options.apply(&ctx);
                 draw_fn(&ctx);
                 surf.finish();
             }
         }
         Ok(())
     }
+
+    /// Returns the contents of the image drawn by `draw_fn`, rather than
+    /// saving it to an output file.
+    ///
+    /// The image is drawn at the given `scale`, as per
+    /// [ImageFormat::save_image].
+    pub fn image_bytes<F>(
+        &self,
+        width: f64,
+        height: f64,
+        scale: f64,
+        draw_fn: F,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>>
+    where
+        F: FnOnce(&cairo::Context),
+    {
+        let options = RenderOptions::default();
+        self.image_bytes_with(&options, width, height, scale, draw_fn)
+    }
+
+    /// Returns the contents of the image drawn by `draw_fn`, as per
+    /// [ImageFormat::image_bytes], using the provided rendering options.
+    pub fn image_bytes_with<F>(
+        &self,
+        options: &RenderOptions,
+        width: f64,
+        height: f64,
+        scale: f64,
+        draw_fn: F,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>>
+    where
+        F: FnOnce(&cairo::Context),
+    {
+        use ImageFormat::*;
+        let width = scale * width;
+        let height = scale * height;
+        let stream = match self {
+            Pdf => {
+                let surf =
+                    cairo::PdfSurface::for_stream(width, height, Vec::new())?;
+                let ctx = cairo::Context::new(&surf)?;
+                ctx.scale(scale, scale);
+                options.apply(&ctx);
+                draw_fn(&ctx);
+                surf.finish_output_stream().map_err(std::io::Error::from)?
+            }
+            Png => {
+                let surf = cairo::ImageSurface::create(
+                    cairo::Format::ARgb32,
+                    width.ceil() as i32,
+                    height.ceil() as i32,
+                )?;
+                let ctx = cairo::Context::new(&surf)?;
+                ctx.scale(scale, scale);
+                options.apply(&ctx);
+                draw_fn(&ctx);
+                let mut bytes: Vec<u8> = vec![];
+                surf.write_to_png(&mut bytes)?;
+                return Ok(bytes);
+            }
+            Svg => {
+                let surf =
+                    cairo::SvgSurface::for_stream(width, height, Vec::new())?;
+                let ctx = cairo::Context::new(&surf)?;
+                ctx.scale(scale, scale);
+                options.apply(&ctx);
+                draw_fn(&ctx);
+                surf.finish_output_stream().map_err(std::io::Error::from)?
+            }
+        };
+        stream
+            .downcast::<Vec<u8>>()
+            .map(|bytes| *bytes)
+            .map_err(|_| "Could not retrieve the image contents".into())
+    }
+
+    /// Returns the MIME type associated with the image format.
+    pub fn mime_type(&self) -> &'static str {
+        use ImageFormat::*;
+        match self {
+            Pdf => "application/pdf",
+            Png => "image/png",
+            Svg => "image/svg+xml",
+        }
+    }
+}
+
+/// Returns the scale at which images should be drawn, in order to produce
+/// PNG images with the given resolution (in dots per inch).
+///
+/// This treats each drawing unit as a point (1/72 of an inch), as per PDF
+/// and SVG images, so that a PNG image saved at this scale has the same
+/// printed size as a PDF or SVG image saved at a scale of `1.0`.
+///
+/// ```rust
+/// # use n18brush::dpi_scale;
+/// assert_eq!(dpi_scale(72.0), 1.0);
+/// assert_eq!(dpi_scale(300.0), 300.0 / 72.0);
+/// ```
+pub fn dpi_scale(dpi: f64) -> f64 {
+    dpi / 72.0
 }
 
 /// Returns the width and height of the image drawn by `draw_fn`.
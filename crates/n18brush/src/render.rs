@@ -0,0 +1,97 @@
+//! Control how images are rendered, so that exported images and reference
+//! images are drawn identically on every platform.
+//!
+//! By default, text is drawn with the platform's default fonts, font hinting,
+//! and anti-aliasing settings, all of which can differ between platforms.
+//! [RenderOptions::deterministic] disables font hinting and sub-pixel
+//! anti-aliasing, and [RenderOptions::font_family] can be used to draw all
+//! text with a single font family that is installed on each platform.
+//!
+//! ```rust
+//! # use n18brush::render::RenderOptions;
+//! # use n18brush::ImageFormat;
+//! # use n18hex::Hex;
+//! let options = RenderOptions {
+//!     font_family: Some("Sans".to_string()),
+//!     ..RenderOptions::deterministic()
+//! };
+//! let mut hex = Hex::new(125.0);
+//! options.apply_to_hex(&mut hex);
+//! let image = ImageFormat::Png.image_bytes_with(
+//!     &options,
+//!     hex.max_d,
+//!     hex.max_d,
+//!     1.0,
+//!     |ctx| n18brush::draw_empty_hex(&hex, ctx),
+//! );
+//! assert!(image.is_ok());
+//! ```
+
+use cairo::{Antialias, Context};
+use n18hex::Hex;
+
+/// Options that affect how images are rendered, but not what is drawn.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// The anti-aliasing mode for shapes and text.
+    pub antialias: Antialias,
+    /// Whether to adjust glyph outlines so that they align with the pixel
+    /// grid, which depends on the platform's font configuration.
+    pub hinting: bool,
+    /// The font family for all text, if any, which overrides the font family
+    /// of each text style (see
+    /// [Theme::font_family](n18hex::Theme::font_family)).
+    pub font_family: Option<String>,
+    /// The seed for any elements that are drawn with random variations, so
+    /// that these elements are drawn identically every time.
+    pub seed: u64,
+}
+
+impl Default for RenderOptions {
+    /// Returns the options that use the platform's default settings.
+    fn default() -> Self {
+        RenderOptions {
+            antialias: Antialias::Default,
+            hinting: true,
+            font_family: None,
+            seed: 0,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Returns the options that disable font hinting and sub-pixel
+    /// anti-aliasing, so that text is drawn identically on every platform
+    /// that has the same fonts.
+    pub fn deterministic() -> Self {
+        RenderOptions {
+            antialias: Antialias::Gray,
+            hinting: false,
+            ..Default::default()
+        }
+    }
+
+    /// Configures the provided context to use these anti-aliasing and font
+    /// hinting settings.
+    pub fn apply(&self, ctx: &Context) {
+        ctx.set_antialias(self.antialias);
+        let Ok(mut options) = cairo::FontOptions::new() else {
+            return;
+        };
+        options.set_antialias(self.antialias);
+        if !self.hinting {
+            options.set_subpixel_order(cairo::SubpixelOrder::Default);
+            options.set_hint_style(cairo::HintStyle::None);
+            options.set_hint_metrics(cairo::HintMetrics::Off);
+        }
+        ctx.set_font_options(&options);
+    }
+
+    /// Configures the provided hexagon's theme to draw all text with this
+    /// font family, if one is defined.
+    pub fn apply_to_hex(&self, hex: &mut Hex) {
+        if let Some(family) = &self.font_family {
+            hex.theme.font_family = Some(family.clone())
+        }
+    }
+}
@@ -0,0 +1,379 @@
+//! Render tiles as individual images and as tile sheets, with deterministic
+//! file names.
+//!
+//! Use [file_stem] and [file_stems] to derive file names from game and tile
+//! names, and [TileSheet] to arrange many tiles in a single image.
+//! These are intended for tools that render every built-in game and tile
+//! (e.g., for documentation and visual regression baselines), where the
+//! same inputs must always produce the same output files.
+//!
+//! Use [PrintLayout] to arrange tiles on printed pages at their true
+//! physical size, with cut lines, for print-and-play games.
+
+use cairo::Context;
+use std::collections::BTreeMap;
+
+use n18hex::{Colour, Hex, Orientation};
+use n18tile::Tile;
+
+use crate::{clear_surface, ImageFormat};
+
+/// Returns a file name stem (i.e., without an extension) for `name`.
+///
+/// ASCII letters are converted to lower case, ASCII digits are retained,
+/// and each run of other characters is replaced by a single underscore.
+/// Leading and trailing underscores are removed, and if no characters
+/// remain this returns `"unnamed"`.
+///
+/// ```rust
+/// # use n18brush::sheet::file_stem;
+/// assert_eq!(file_stem("57"), "57");
+/// assert_eq!(file_stem("X1"), "x1");
+/// assert_eq!(file_stem("1830: Railways and Robber Barons"),
+///            "1830_railways_and_robber_barons");
+/// assert_eq!(file_stem("Toronto/Hamilton"), "toronto_hamilton");
+/// assert_eq!(file_stem("!?"), "unnamed");
+/// ```
+pub fn file_stem(name: &str) -> String {
+    let mut stem = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            stem.push(c.to_ascii_lowercase());
+        } else if !stem.is_empty() && !stem.ends_with('_') {
+            stem.push('_');
+        }
+    }
+    while stem.ends_with('_') {
+        stem.pop();
+    }
+    if stem.is_empty() {
+        "unnamed".to_string()
+    } else {
+        stem
+    }
+}
+
+/// Returns a distinct file name stem for each of the provided names, in the
+/// same order.
+///
+/// Each stem is defined by [file_stem].
+/// When several names have the same stem, the first name keeps this stem
+/// and the subsequent names have `-2`, `-3`, etc, appended to it.
+///
+/// ```rust
+/// # use n18brush::sheet::file_stems;
+/// let stems = file_stems(["X1", "x1", "57", "X 1"]);
+/// assert_eq!(stems, vec!["x1", "x1-2", "57", "x_1"]);
+/// ```
+pub fn file_stems<I, S>(names: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    names
+        .into_iter()
+        .map(|name| {
+            let stem = file_stem(name.as_ref());
+            let count = counts.entry(stem.clone()).or_insert(0);
+            *count += 1;
+            if *count == 1 {
+                stem
+            } else {
+                format!("{}-{}", stem, count)
+            }
+        })
+        .collect()
+}
+
+/// Arranges tiles in rows and columns, in the order that they are provided.
+///
+/// Each tile occupies a square whose sides are the maximal hex diameter, so
+/// that tiles can be drawn in either hex orientation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TileSheet {
+    /// The maximum number of tiles in each row.
+    pub columns: usize,
+    /// The space between adjacent tiles, and between the tiles and the
+    /// edges of the sheet.
+    pub spacing: f64,
+    /// The background colour of the sheet, or `None` for a transparent
+    /// background.
+    pub background: Option<Colour>,
+}
+
+impl Default for TileSheet {
+    fn default() -> Self {
+        TileSheet {
+            columns: 10,
+            spacing: 10.0,
+            background: Some(Colour::WHITE),
+        }
+    }
+}
+
+impl TileSheet {
+    /// Returns the number of rows and columns needed to arrange `count`
+    /// tiles.
+    pub fn grid(&self, count: usize) -> (usize, usize) {
+        let columns = self.columns.max(1);
+        if count == 0 {
+            return (0, 0);
+        }
+        let rows = count.div_ceil(columns);
+        (rows, count.min(columns))
+    }
+
+    /// Returns the width and height of a sheet that contains `count` tiles,
+    /// where each tile is drawn at the size of `hex`.
+    pub fn size(&self, hex: &Hex, count: usize) -> (f64, f64) {
+        let (rows, cols) = self.grid(count);
+        let cell = hex.max_d + self.spacing;
+        (
+            cols as f64 * cell + self.spacing,
+            rows as f64 * cell + self.spacing,
+        )
+    }
+
+    /// Returns the centre of the `ix`th tile on the sheet.
+    pub fn centre(&self, hex: &Hex, ix: usize) -> (f64, f64) {
+        let columns = self.columns.max(1);
+        let (row, col) = (ix / columns, ix % columns);
+        let cell = hex.max_d + self.spacing;
+        let offset = self.spacing + 0.5 * hex.max_d;
+        (offset + col as f64 * cell, offset + row as f64 * cell)
+    }
+
+    /// Draws each tile at its position on the sheet.
+    pub fn draw<'a, T>(&self, hex: &Hex, ctx: &Context, tiles: T)
+    where
+        T: IntoIterator<Item = &'a Tile>,
+    {
+        clear_surface(ctx, self.background);
+        for (ix, tile) in tiles.into_iter().enumerate() {
+            let (x, y) = self.centre(hex, ix);
+            let m = ctx.matrix();
+            ctx.translate(x, y);
+            tile.draw(ctx, hex);
+            ctx.set_matrix(m);
+        }
+    }
+
+    /// Saves an image of the sheet that contains each of the provided tiles,
+    /// drawn at the size of `hex`.
+    pub fn save<P: AsRef<std::path::Path>>(
+        &self,
+        hex: &Hex,
+        tiles: &[Tile],
+        format: ImageFormat,
+        dest: P,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (width, height) = self.size(hex, tiles.len());
+        let draw_fn = |ctx: &Context| self.draw(hex, ctx, tiles);
+        format.save_image(width, height, 1.0, draw_fn, dest)
+    }
+}
+
+/// Saves an image of a single tile, drawn at the size of `hex` with the
+/// provided background colour (if any).
+pub fn save_tile<P: AsRef<std::path::Path>>(
+    hex: &Hex,
+    tile: &Tile,
+    background: Option<Colour>,
+    format: ImageFormat,
+    dest: P,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let margin = hex.theme.tile_margin.margin(hex);
+    let width = hex.max_d + 2.0 * margin;
+    let draw_fn = |ctx: &Context| {
+        clear_surface(ctx, background);
+        ctx.translate(0.5 * width, 0.5 * width);
+        tile.draw(ctx, hex);
+    };
+    format.save_image(width, width, 1.0, draw_fn, dest)
+}
+
+/// The number of PDF points (the drawing units of PDF surfaces) in one
+/// millimetre.
+const POINTS_PER_MM: f64 = 72.0 / 25.4;
+
+/// Paper sizes for printed tile sheets (see [PrintLayout]).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PaperSize {
+    /// ISO A4 paper (210 mm by 297 mm).
+    #[default]
+    A4,
+    /// US Letter paper (8.5 inches by 11 inches).
+    Letter,
+    /// A custom paper size, defined by its width and height in millimetres.
+    Custom { width_mm: f64, height_mm: f64 },
+}
+
+impl PaperSize {
+    /// Returns the width and height of the paper in portrait orientation,
+    /// in millimetres.
+    pub fn size_mm(&self) -> (f64, f64) {
+        match self {
+            PaperSize::A4 => (210.0, 297.0),
+            PaperSize::Letter => (215.9, 279.4),
+            PaperSize::Custom {
+                width_mm,
+                height_mm,
+            } => (*width_mm, *height_mm),
+        }
+    }
+}
+
+/// Arranges tiles on printed pages at their true physical size, for
+/// print-and-play games.
+///
+/// Tiles are arranged in rows and columns within the page margins, in the
+/// order that they are provided, and as many pages as necessary are created.
+/// To print several copies of a tile, include the tile several times.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PrintLayout {
+    /// The paper size, in portrait orientation.
+    pub paper: PaperSize,
+    /// The printed width of each tile, measured between opposite hex faces,
+    /// in millimetres.
+    pub hex_width_mm: f64,
+    /// The blank space around the edges of each page, in millimetres.
+    pub margin_mm: f64,
+    /// The space between adjacent tiles, in millimetres.
+    pub gap_mm: f64,
+    /// Whether to draw a thin line around each tile, along which the tile
+    /// should be cut.
+    pub cut_lines: bool,
+}
+
+impl Default for PrintLayout {
+    fn default() -> Self {
+        PrintLayout {
+            paper: PaperSize::A4,
+            hex_width_mm: 38.0,
+            margin_mm: 10.0,
+            gap_mm: 2.0,
+            cut_lines: true,
+        }
+    }
+}
+
+impl PrintLayout {
+    /// Returns the scale that converts drawing units for `hex` into PDF
+    /// points, so that tiles are printed with the chosen width.
+    fn scale(&self, hex: &Hex) -> f64 {
+        self.hex_width_mm * POINTS_PER_MM / hex.min_d
+    }
+
+    /// Returns the width and height of the space that each tile occupies,
+    /// in millimetres.
+    pub fn cell_size_mm(&self, hex: &Hex) -> (f64, f64) {
+        let corners_mm = self.hex_width_mm * hex.max_d / hex.min_d;
+        match hex.orientation() {
+            Orientation::FlatTop => (corners_mm, self.hex_width_mm),
+            Orientation::PointedTop => (self.hex_width_mm, corners_mm),
+        }
+    }
+
+    /// Returns the number of rows and columns of tiles on each page.
+    pub fn grid(&self, hex: &Hex) -> (usize, usize) {
+        let (width, height) = self.paper.size_mm();
+        let (cell_width, cell_height) = self.cell_size_mm(hex);
+        let fit = |space: f64, cell: f64| {
+            let space = space - 2.0 * self.margin_mm + self.gap_mm;
+            let count = (space / (cell + self.gap_mm)).floor();
+            if count > 0.0 {
+                count as usize
+            } else {
+                0
+            }
+        };
+        (fit(height, cell_height), fit(width, cell_width))
+    }
+
+    /// Returns the number of tiles on each page.
+    pub fn tiles_per_page(&self, hex: &Hex) -> usize {
+        let (rows, cols) = self.grid(hex);
+        rows * cols
+    }
+
+    /// Returns the number of pages needed to print `count` tiles, or `None`
+    /// if the tiles are too large to fit on a page.
+    pub fn page_count(&self, hex: &Hex, count: usize) -> Option<usize> {
+        match self.tiles_per_page(hex) {
+            0 => None,
+            per_page => Some(count.div_ceil(per_page)),
+        }
+    }
+
+    /// Returns the centre of the `ix`th tile on its page, in millimetres
+    /// from the top-left corner of the page.
+    pub fn centre_mm(&self, hex: &Hex, ix: usize) -> (f64, f64) {
+        let (_rows, cols) = self.grid(hex);
+        let ix = ix % self.tiles_per_page(hex).max(1);
+        let (row, col) = (ix / cols.max(1), ix % cols.max(1));
+        let (cell_width, cell_height) = self.cell_size_mm(hex);
+        (
+            self.margin_mm
+                + col as f64 * (cell_width + self.gap_mm)
+                + 0.5 * cell_width,
+            self.margin_mm
+                + row as f64 * (cell_height + self.gap_mm)
+                + 0.5 * cell_height,
+        )
+    }
+
+    /// Saves a multi-page PDF that contains each of the provided tiles, and
+    /// returns the number of pages.
+    ///
+    /// The tiles are drawn for `hex`, and are then scaled so that they are
+    /// printed with the chosen width.
+    pub fn save_pdf<'a, T, P>(
+        &self,
+        hex: &Hex,
+        tiles: T,
+        dest: P,
+    ) -> Result<usize, Box<dyn std::error::Error>>
+    where
+        T: IntoIterator<Item = &'a Tile>,
+        P: AsRef<std::path::Path>,
+    {
+        let tiles: Vec<&Tile> = tiles.into_iter().collect();
+        let pages = self
+            .page_count(hex, tiles.len())
+            .ok_or("The tiles are too large to fit on a page")?;
+        let per_page = self.tiles_per_page(hex);
+        let (width_mm, height_mm) = self.paper.size_mm();
+        let surf = cairo::PdfSurface::new(
+            width_mm * POINTS_PER_MM,
+            height_mm * POINTS_PER_MM,
+            dest,
+        )?;
+        let ctx = Context::new(&surf)?;
+        let scale = self.scale(hex);
+        for page_tiles in tiles.chunks(per_page) {
+            for (ix, tile) in page_tiles.iter().enumerate() {
+                let (x, y) = self.centre_mm(hex, ix);
+                ctx.save()?;
+                ctx.translate(x * POINTS_PER_MM, y * POINTS_PER_MM);
+                ctx.scale(scale, scale);
+                tile.draw(&ctx, hex);
+                if self.cut_lines {
+                    hex.define_boundary(&ctx);
+                    ctx.restore()?;
+                    // NOTE: draw cut lines in device units, so that their
+                    // width does not depend on the tile scale.
+                    ctx.set_line_width(0.25);
+                    ctx.set_source_rgb(0.0, 0.0, 0.0);
+                    ctx.stroke()?;
+                } else {
+                    ctx.restore()?;
+                }
+            }
+            ctx.show_page()?;
+        }
+        drop(ctx);
+        surf.finish();
+        Ok(pages)
+    }
+}
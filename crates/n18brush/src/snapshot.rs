@@ -0,0 +1,196 @@
+//! Compare rendered images against reference images, for regression tests.
+//!
+//! Use [render_png] to draw an image in a deterministic rendering mode (see
+//! [RenderOptions::deterministic]), or [render_png_with] to choose the
+//! rendering options, and
+//! [compare_png] to compare the result against a stored reference image.
+//! Small rendering differences (e.g., due to anti-aliasing) are tolerated by
+//! ignoring pixels whose colours are within [Tolerance::colour_distance] of
+//! each other, and images are considered to match if the fraction of pixels
+//! that differ does not exceed [Tolerance::max_fraction].
+//!
+//! When images do not match, [Comparison::save_diff] writes an image that
+//! highlights the differing pixels in red.
+
+use cairo::{Context, Format, ImageSurface};
+use n18hex::Colour;
+
+use crate::render::RenderOptions;
+
+/// Defines how much a rendered image may differ from its reference image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    /// The minimum [distance](Colour::distance) between two pixel colours
+    /// for the pixels to be considered different.
+    pub colour_distance: f64,
+    /// The maximum fraction of pixels (`0.0..=1.0`) that may differ.
+    pub max_fraction: f64,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Tolerance {
+            colour_distance: 32.0,
+            max_fraction: 0.001,
+        }
+    }
+}
+
+/// The result of comparing a rendered image to a reference image.
+#[derive(Debug)]
+pub struct Comparison {
+    /// The width of the rendered image, in pixels.
+    pub width: i32,
+    /// The height of the rendered image, in pixels.
+    pub height: i32,
+    /// The number of pixels that differ from the reference image, including
+    /// every pixel that lies outside of the reference image.
+    pub differing: usize,
+    /// The number of pixels in the bounding box of both images.
+    total: usize,
+    /// An image that highlights the pixels that differ.
+    diff: ImageSurface,
+}
+
+impl Comparison {
+    /// Returns the fraction of pixels (`0.0..=1.0`) that differ from the
+    /// reference image.
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.differing as f64 / self.total as f64
+        }
+    }
+
+    /// Returns whether the images match, for the given tolerance.
+    pub fn matches(&self, tolerance: &Tolerance) -> bool {
+        self.fraction() <= tolerance.max_fraction
+    }
+
+    /// Saves an image that shows the reference image in faded colours, and
+    /// highlights each pixel that differs in red.
+    pub fn save_diff<P: AsRef<std::path::Path>>(
+        &self,
+        dest: P,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut out_file = std::fs::File::create(dest.as_ref())?;
+        self.diff.write_to_png(&mut out_file)?;
+        Ok(())
+    }
+}
+
+/// Configures the provided context so that text is drawn identically on
+/// every platform that has the same fonts, by disabling font hinting and
+/// sub-pixel anti-aliasing.
+pub fn deterministic_rendering(ctx: &Context) {
+    RenderOptions::deterministic().apply(ctx)
+}
+
+/// Returns the contents of a PNG image that is drawn by `draw_fn` in the
+/// deterministic rendering mode, on a white background.
+pub fn render_png<F>(
+    width: f64,
+    height: f64,
+    draw_fn: F,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>>
+where
+    F: FnOnce(&Context),
+{
+    render_png_with(&RenderOptions::deterministic(), width, height, draw_fn)
+}
+
+/// Returns the contents of a PNG image that is drawn by `draw_fn` with the
+/// provided rendering options, on a white background.
+pub fn render_png_with<F>(
+    options: &RenderOptions,
+    width: f64,
+    height: f64,
+    draw_fn: F,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>>
+where
+    F: FnOnce(&Context),
+{
+    let png = crate::ImageFormat::Png;
+    png.image_bytes_with(options, width, height, 1.0, |ctx| {
+        crate::clear_surface(ctx, Colour::WHITE);
+        draw_fn(ctx)
+    })
+}
+
+/// Returns the colour of a pixel in an ARGB32 image.
+fn pixel(data: &[u8], stride: i32, x: i32, y: i32) -> Colour {
+    let ix = (y * stride + 4 * x) as usize;
+    let value = u32::from_ne_bytes([
+        data[ix],
+        data[ix + 1],
+        data[ix + 2],
+        data[ix + 3],
+    ]);
+    let [alpha, red, green, blue] = value.to_be_bytes();
+    Colour::from((red, green, blue)).with_alpha(alpha)
+}
+
+/// Compares the contents of a rendered PNG image to a reference PNG image.
+///
+/// Returns an error if either image cannot be decoded.
+pub fn compare_png(
+    rendered: &[u8],
+    reference: &[u8],
+    tolerance: &Tolerance,
+) -> Result<Comparison, Box<dyn std::error::Error>> {
+    let mut actual = ImageSurface::create_from_png(&mut &rendered[..])?;
+    let mut expect = ImageSurface::create_from_png(&mut &reference[..])?;
+    let (width, height) = (actual.width(), actual.height());
+    let (ref_width, ref_height) = (expect.width(), expect.height());
+    let (act_stride, ref_stride) = (actual.stride(), expect.stride());
+
+    let mut diff = ImageSurface::create(Format::ARgb32, width, height)?;
+    let diff_stride = diff.stride();
+    let mut differing = 0;
+    {
+        let act_data = actual.data()?;
+        let ref_data = expect.data()?;
+        let mut diff_data = diff.data()?;
+        let faded = |colour: Colour| {
+            let fade = |value: u8| 191 + value / 4;
+            (fade(colour.red), fade(colour.green), fade(colour.blue))
+        };
+        for y in 0..height {
+            for x in 0..width {
+                let colour = pixel(&act_data, act_stride, x, y);
+                let (r, g, b) = if x < ref_width && y < ref_height {
+                    let expected = pixel(&ref_data, ref_stride, x, y);
+                    if colour.distance(&expected) < tolerance.colour_distance
+                    {
+                        faded(expected)
+                    } else {
+                        differing += 1;
+                        (255, 0, 0)
+                    }
+                } else {
+                    differing += 1;
+                    (255, 0, 0)
+                };
+                let value = u32::from_be_bytes([255, r, g, b]).to_ne_bytes();
+                let ix = (y * diff_stride + 4 * x) as usize;
+                diff_data[ix..ix + 4].copy_from_slice(&value);
+            }
+        }
+    }
+    // Pixels that are only present in the reference image also differ.
+    let ref_pixels = (ref_width as usize) * (ref_height as usize);
+    let shared =
+        (width.min(ref_width) as usize) * (height.min(ref_height) as usize);
+    differing += ref_pixels - shared;
+    let total =
+        (width.max(ref_width) as usize) * (height.max(ref_height) as usize);
+
+    Ok(Comparison {
+        width,
+        height,
+        differing,
+        total,
+        diff,
+    })
+}
@@ -0,0 +1,98 @@
+//! Draws decorative terrain (mountains and water) on map hexes; see
+//! [n18map::Terrain].
+
+use cairo::Context;
+
+use n18hex::{Hex, HexColour};
+use n18map::Terrain;
+
+/// Draws terrain on the current hex, which should be centred at the origin
+/// and upright (i.e., any tile rotation should be undone).
+///
+/// The terrain is clipped to the hex boundary.
+pub fn draw_terrain(hex: &Hex, ctx: &Context, terrain: Terrain) {
+    ctx.save().unwrap();
+    hex.define_boundary(ctx);
+    ctx.clip();
+    match terrain {
+        Terrain::Mountain => draw_mountains(hex, ctx),
+        Terrain::Water => draw_waves(hex, ctx),
+    }
+    ctx.restore().unwrap();
+}
+
+/// Draws a range of three peaks, where the right-hand face of each peak is
+/// shaded with hatching.
+fn draw_mountains(hex: &Hex, ctx: &Context) {
+    let r = 0.5 * hex.min_d;
+    // The centre, half-width, and height of each peak, where the rearmost
+    // peak is drawn first so that it is partially covered.
+    let peaks = [
+        (0.0, -0.1 * r, 0.3 * r, 0.45 * r),
+        (-0.3 * r, 0.15 * r, 0.25 * r, 0.35 * r),
+        (0.3 * r, 0.15 * r, 0.25 * r, 0.35 * r),
+    ];
+    let style = &hex.theme.terrain_mountain;
+    for (x, y, half_width, height) in peaks {
+        let base_y = y + 0.5 * height;
+        let apex_y = y - 0.5 * height;
+        ctx.move_to(x - half_width, base_y);
+        ctx.line_to(x, apex_y);
+        ctx.line_to(x + half_width, base_y);
+        // Cover any peaks that are behind this peak.
+        hex.theme.apply_hex_colour(ctx, HexColour::Empty);
+        ctx.fill_preserve().unwrap();
+        style.apply_line_and_stroke(ctx, hex);
+        ctx.stroke().unwrap();
+
+        // Shade the right-hand face with vertical hatching.
+        let hatches = 4;
+        for i in 1..=hatches {
+            let frac = i as f64 / (hatches + 1) as f64;
+            let hatch_x = x + frac * half_width;
+            let hatch_y = apex_y + frac * height;
+            ctx.move_to(hatch_x, hatch_y);
+            ctx.line_to(hatch_x, base_y);
+        }
+        style.apply_line_and_stroke(ctx, hex);
+        ctx.stroke().unwrap();
+    }
+}
+
+/// Draws several rows of waves, where alternate rows are offset so that
+/// the crests do not line up.
+fn draw_waves(hex: &Hex, ctx: &Context) {
+    let r = 0.5 * hex.min_d;
+    let period = 0.3 * r;
+    let amplitude = 0.06 * r;
+    let rows = [-0.45, -0.15, 0.15, 0.45];
+    for (ix, row) in rows.iter().enumerate() {
+        let y = row * r;
+        let offset = if ix % 2 == 0 { 0.0 } else { 0.5 * period };
+        // NOTE: the waves extend beyond the hex and are clipped.
+        let mut x = -1.2 * r - offset;
+        ctx.move_to(x, y);
+        while x < 1.2 * r {
+            let half = 0.5 * period;
+            ctx.curve_to(
+                x + half / 3.0,
+                y - 1.5 * amplitude,
+                x + 2.0 * half / 3.0,
+                y - 1.5 * amplitude,
+                x + half,
+                y,
+            );
+            ctx.curve_to(
+                x + 4.0 * half / 3.0,
+                y + 1.5 * amplitude,
+                x + 5.0 * half / 3.0,
+                y + 1.5 * amplitude,
+                x + period,
+                y,
+            );
+            x += period;
+        }
+    }
+    hex.theme.terrain_water.apply_line_and_stroke(ctx, hex);
+    ctx.stroke().unwrap();
+}
@@ -25,8 +25,10 @@ use std::iter::FromIterator;
 use n18hex::*;
 use n18tile::*;
 
+mod report;
 mod tiles;
 
+pub use report::{ColourSummary, Report};
 pub use tiles::Kind;
 
 #[cfg(test)]
@@ -110,6 +112,7 @@ pub struct Builder {
     hex: Hex,
     tiles: Vec<(Tile, Availability)>,
     tile_tree: BTreeMap<String, usize>,
+    duplicates: Vec<String>,
 }
 
 impl From<Vec<Tile>> for Builder {
@@ -148,6 +151,9 @@ impl FromIterator<(Tile, Availability)> for Builder {
 
 impl Builder {
     /// Converts the collected tiles into a [Catalogue].
+    ///
+    /// If several tiles were added with the same name, the catalogue will
+    /// only contain the most recently added tile; see [Builder::try_build].
     pub fn build(self) -> Catalogue {
         Catalogue {
             tiles: self.tiles,
@@ -155,12 +161,37 @@ impl Builder {
         }
     }
 
+    /// Converts the collected tiles into a [Catalogue], unless several tiles
+    /// were added with the same name, in which case this returns the
+    /// duplicate names.
+    ///
+    /// ```rust
+    /// # use n18catalogue::{Builder, Kind};
+    /// let mut builder = Builder::with_unlimited_tiles(vec![Kind::_3]).unwrap();
+    /// let tile = Kind::_3.build(builder.hex());
+    /// builder.add_unlimited_tile(tile);
+    /// assert_eq!(builder.try_build(), Err(vec!["3".to_string()]));
+    /// ```
+    pub fn try_build(self) -> Result<Catalogue, Vec<String>> {
+        if self.duplicates.is_empty() {
+            Ok(self.build())
+        } else {
+            Err(self.duplicates)
+        }
+    }
+
+    /// Returns the names of tiles that have been added more than once.
+    pub fn duplicates(&self) -> &[String] {
+        &self.duplicates
+    }
+
     /// Returns a [Builder] that contains no tiles.
     pub fn empty() -> Self {
         Builder {
             hex: Hex::default(),
             tiles: vec![],
             tile_tree: BTreeMap::new(),
+            duplicates: vec![],
         }
     }
 
@@ -259,12 +290,16 @@ impl Builder {
 
     /// Adds a tile to the collection, and returns the index of the previous
     /// tile with this name (if any).
+    ///
+    /// The new tile replaces the previous tile, and its name is recorded as
+    /// a [duplicate](Builder::duplicates).
     pub fn add_tile(
         &mut self,
         tile: Tile,
         limit: Availability,
     ) -> Option<usize> {
         if let Some(&ix) = self.tile_tree.get(&tile.name) {
+            self.duplicates.push(tile.name.clone());
             self.tiles[ix] = (tile, limit);
             Some(ix)
         } else {
@@ -350,7 +385,12 @@ impl Builder {
 }
 
 /// A tile catalogue is a collection of tiles.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// Each tile has a unique name, and tiles can be retrieved by name in
+/// constant time (see [Catalogue::tile] and [Catalogue::index_of]).
+/// Tiles are iterated over in the order that they were added, and each tile
+/// keeps its index when new tiles are added.
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Catalogue {
     tiles: Vec<(Tile, Availability)>,
     tile_tree: BTreeMap<String, usize>,
@@ -402,6 +442,36 @@ impl std::ops::Index<usize> for Catalogue {
 }
 
 impl Catalogue {
+    /// Returns a catalogue that contains the provided tiles, unless several
+    /// tiles have the same name, in which case this returns the duplicate
+    /// names.
+    ///
+    /// ```rust
+    /// # use n18catalogue::{Availability, Catalogue, Kind};
+    /// # use n18hex::Hex;
+    /// let hex = Hex::default();
+    /// let tiles = vec![
+    ///     (Kind::_3.build(&hex), Availability::Limited(4)),
+    ///     (Kind::_4.build(&hex), Availability::Unlimited),
+    /// ];
+    /// let catalogue = Catalogue::new(tiles).unwrap();
+    /// assert_eq!(catalogue.index_of("4"), Some(1));
+    ///
+    /// let tiles = vec![
+    ///     (Kind::_3.build(&hex), Availability::Limited(4)),
+    ///     (Kind::_3.build(&hex), Availability::Unlimited),
+    /// ];
+    /// assert_eq!(Catalogue::new(tiles), Err(vec!["3".to_string()]));
+    /// ```
+    pub fn new<T>(tiles: T) -> Result<Self, Vec<String>>
+    where
+        T: IntoIterator<Item = (Tile, Availability)>,
+    {
+        let mut catalogue = Catalogue::default();
+        catalogue.extend(tiles)?;
+        Ok(catalogue)
+    }
+
     pub fn is_empty(&self) -> bool {
         self.tiles.is_empty()
     }
@@ -430,6 +500,11 @@ impl Catalogue {
         self.tiles.iter().map(|(tile, _avail)| &tile.name)
     }
 
+    /// Returns `true` if the catalogue contains a tile with this name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.tile_tree.contains_key(name)
+    }
+
     pub fn index_of(&self, name: &str) -> Option<usize> {
         self.tile_tree.get(name).copied()
     }
@@ -449,6 +524,119 @@ impl Catalogue {
         self.tile_tree.get(name).map(|ix| &self.tiles[*ix])
     }
 
+    /// Adds tiles to this catalogue, and returns the index of each new tile.
+    ///
+    /// If any of the new tiles has the same name as a tile in this catalogue,
+    /// or as another new tile, the catalogue is not modified and this returns
+    /// the duplicate names.
+    ///
+    /// To add tiles from a JSON file, see `n18io::extend_catalogue`.
+    ///
+    /// # Example usage
+    ///
+    /// ```rust
+    /// # use n18catalogue::{Availability, Builder, Catalogue, Kind};
+    /// # use n18hex::Hex;
+    /// let tiles = vec![(Kind::_3, Availability::Limited(4))];
+    /// let mut catalogue = Builder::with_tiles(tiles).unwrap().build();
+    ///
+    /// let hex = Hex::default();
+    /// let extra = vec![(Kind::_4.build(&hex), Availability::Limited(2))];
+    /// assert_eq!(catalogue.extend(extra), Ok(vec![1]));
+    ///
+    /// let extra = vec![(Kind::_3.build(&hex), Availability::Unlimited)];
+    /// assert_eq!(catalogue.extend(extra), Err(vec!["3".to_string()]));
+    /// assert_eq!(catalogue.len(), 2);
+    /// ```
+    pub fn extend<T>(&mut self, tiles: T) -> Result<Vec<usize>, Vec<String>>
+    where
+        T: IntoIterator<Item = (Tile, Availability)>,
+    {
+        let tiles: Vec<(Tile, Availability)> = tiles.into_iter().collect();
+        let mut seen = std::collections::BTreeSet::new();
+        let duplicates: Vec<String> = tiles
+            .iter()
+            .map(|(tile, _avail)| &tile.name)
+            .filter(|name| {
+                !seen.insert(*name) || self.tile_tree.contains_key(*name)
+            })
+            .cloned()
+            .collect();
+        if !duplicates.is_empty() {
+            return Err(duplicates);
+        }
+        let indices = tiles
+            .into_iter()
+            .map(|(tile, avail)| {
+                let ix = self.tiles.len();
+                self.tile_tree.insert(tile.name.clone(), ix);
+                self.tiles.push((tile, avail));
+                ix
+            })
+            .collect();
+        Ok(indices)
+    }
+
+    /// Updates the definitions of existing tiles and adds any new tiles, with
+    /// the given availability, and returns the index of each tile.
+    ///
+    /// Each existing tile keeps its index and availability, so that maps
+    /// which refer to tiles by index remain valid.
+    /// This allows tile definitions to be modified while a map is in use
+    /// (e.g., when the tile definitions are reloaded from disk).
+    ///
+    /// # Example usage
+    ///
+    /// ```rust
+    /// # use n18catalogue::{Availability, Builder, Catalogue, Kind};
+    /// # use n18hex::Hex;
+    /// let tiles = vec![(Kind::_3, Availability::Limited(4))];
+    /// let mut catalogue = Builder::with_tiles(tiles).unwrap().build();
+    ///
+    /// let hex = Hex::default();
+    /// let tiles = vec![Kind::_3.build(&hex), Kind::_4.build(&hex)];
+    /// let indices = catalogue.update(tiles, Availability::Unlimited);
+    /// assert_eq!(indices, vec![0, 1]);
+    ///
+    /// // The existing tile keeps its availability.
+    /// let avail = catalogue.availability("3");
+    /// assert_eq!(avail, Some(&Availability::Limited(4)));
+    /// let avail = catalogue.availability("4");
+    /// assert_eq!(avail, Some(&Availability::Unlimited));
+    /// ```
+    pub fn update<T>(
+        &mut self,
+        tiles: T,
+        availability: Availability,
+    ) -> Vec<usize>
+    where
+        T: IntoIterator<Item = Tile>,
+    {
+        tiles
+            .into_iter()
+            .map(|tile| {
+                if let Some(&ix) = self.tile_tree.get(&tile.name) {
+                    self.tiles[ix].0 = tile;
+                    ix
+                } else {
+                    let ix = self.tiles.len();
+                    self.tile_tree.insert(tile.name.clone(), ix);
+                    self.tiles.push((tile, availability));
+                    ix
+                }
+            })
+            .collect()
+    }
+
+    /// Highlights the revenue for the game phase in which tiles of colour
+    /// `phase` are introduced, on each tile that has a phase revenue label
+    /// (see [Tile::set_revenue_phase]).
+    pub fn set_revenue_phase(&mut self, phase: HexColour) {
+        for (tile, _avail) in self.tiles.iter_mut() {
+            tile.set_revenue_phase(phase)
+        }
+    }
+
     pub fn get_subset<S>(&self, names: &[S]) -> Result<Vec<&Tile>, String>
     where
         S: AsRef<str>,
@@ -0,0 +1,178 @@
+//! Summarises the contents of a tile catalogue, to help maintain the
+//! growing collection of tiles.
+//!
+//! See [Catalogue::report] for details.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use n18hex::HexColour;
+use n18tile::Tile;
+
+use crate::Catalogue;
+
+/// The number of tiles, cities, and dits for a single tile colour.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ColourSummary {
+    /// The number of tiles.
+    pub tiles: usize,
+    /// The number of cities over all of the tiles.
+    pub cities: usize,
+    /// The number of dits over all of the tiles.
+    pub dits: usize,
+    /// The number of token spaces over all of the tiles.
+    pub token_spaces: usize,
+}
+
+/// Summary statistics for a tile catalogue, as returned by
+/// [Catalogue::report].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// The number of tiles, cities, and dits for each tile colour.
+    pub colours: BTreeMap<HexColour, ColourSummary>,
+    /// Groups of tiles that are identical in every respect except for their
+    /// names, in catalogue order.
+    pub duplicates: Vec<Vec<String>>,
+    /// The tiles that are not included in any of the catalogues passed to
+    /// [Catalogue::report], in catalogue order.
+    pub unused: Vec<String>,
+}
+
+impl Report {
+    /// Returns the total number of tiles, cities, and dits in the catalogue.
+    pub fn total(&self) -> ColourSummary {
+        self.colours.values().fold(
+            ColourSummary::default(),
+            |acc, summary| ColourSummary {
+                tiles: acc.tiles + summary.tiles,
+                cities: acc.cities + summary.cities,
+                dits: acc.dits + summary.dits,
+                token_spaces: acc.token_spaces + summary.token_spaces,
+            },
+        )
+    }
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<8} {:>6} {:>6} {:>6} {:>7}",
+            "Colour", "Tiles", "Cities", "Dits", "Tokens"
+        )?;
+        let rows = self
+            .colours
+            .iter()
+            .map(|(colour, summary)| (format!("{:?}", colour), *summary))
+            .chain(std::iter::once(("Total".to_string(), self.total())));
+        for (label, summary) in rows {
+            writeln!(
+                f,
+                "{:<8} {:>6} {:>6} {:>6} {:>7}",
+                label,
+                summary.tiles,
+                summary.cities,
+                summary.dits,
+                summary.token_spaces
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(f, "Identical tiles: {}", self.duplicates.len())?;
+        for names in &self.duplicates {
+            writeln!(f, "  {}", names.join(", "))?;
+        }
+        writeln!(f)?;
+        writeln!(f, "Unused tiles: {}", self.unused.len())?;
+        if !self.unused.is_empty() {
+            writeln!(f, "  {}", self.unused.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns `true` if two tiles are identical in every respect except for
+/// their names.
+fn identical_except_name(a: &Tile, b: &Tile) -> bool {
+    if a.name == b.name {
+        return a == b;
+    }
+    let mut renamed = b.clone();
+    renamed.name.clone_from(&a.name);
+    *a == renamed
+}
+
+impl Catalogue {
+    /// Summarises the tiles in this catalogue.
+    ///
+    /// The report counts the tiles, cities, and dits of each colour, and
+    /// identifies tiles that are identical except for their names.
+    /// Tiles are only considered identical if they have the same
+    /// orientation; tiles that only differ by a rotation are not reported.
+    ///
+    /// Any tile that is not included in at least one of the `used`
+    /// catalogues is reported as unused.
+    /// For example, pass the catalogue of each built-in game to identify the
+    /// tiles that are not used by any of these games.
+    ///
+    /// ```rust
+    /// # use n18catalogue::{Builder, Kind};
+    /// # use n18hex::HexColour;
+    /// let catalogue =
+    ///     Builder::with_unlimited_tiles(vec![Kind::_3, Kind::_4, Kind::_5])
+    ///         .unwrap()
+    ///         .build();
+    /// let game = Builder::with_unlimited_tiles(vec![Kind::_3, Kind::_4])
+    ///     .unwrap()
+    ///     .build();
+    /// let report = catalogue.report(&[game]);
+    /// assert_eq!(report.colours[&HexColour::Yellow].tiles, 3);
+    /// assert_eq!(report.total().dits, 2);
+    /// assert_eq!(report.total().cities, 1);
+    /// assert!(report.duplicates.is_empty());
+    /// assert_eq!(report.unused, vec!["5".to_string()]);
+    /// ```
+    pub fn report<'a, I>(&self, used: I) -> Report
+    where
+        I: IntoIterator<Item = &'a Catalogue>,
+    {
+        let used: BTreeSet<&String> =
+            used.into_iter().flat_map(|cat| cat.tile_names()).collect();
+
+        let mut colours: BTreeMap<HexColour, ColourSummary> = BTreeMap::new();
+        for tile in self.tile_iter() {
+            let summary = colours.entry(tile.colour).or_default();
+            summary.tiles += 1;
+            summary.cities += tile.cities().len();
+            summary.dits += tile.dit_count();
+            summary.token_spaces += tile.token_space_count();
+        }
+
+        // Collect groups of tiles that only differ by name.
+        let mut groups: Vec<Vec<&Tile>> = vec![];
+        for tile in self.tile_iter() {
+            let group = groups
+                .iter_mut()
+                .find(|group| identical_except_name(group[0], tile));
+            match group {
+                Some(group) => group.push(tile),
+                None => groups.push(vec![tile]),
+            }
+        }
+        let duplicates = groups
+            .into_iter()
+            .filter(|group| group.len() > 1)
+            .map(|group| group.iter().map(|t| t.name.clone()).collect())
+            .collect();
+
+        let unused = self
+            .tile_names()
+            .filter(|name| !used.contains(name))
+            .cloned()
+            .collect();
+
+        Report {
+            colours,
+            duplicates,
+            unused,
+        }
+    }
+}
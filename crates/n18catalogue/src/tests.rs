@@ -17,3 +17,55 @@ fn compare_tile_fns() {
     let bcat_tiles: Vec<Tile> = bcat.tile_iter().cloned().collect();
     assert_eq!(catalogue_tiles, bcat_tiles);
 }
+
+#[test]
+fn detect_duplicate_tiles() {
+    let mut builder =
+        Builder::with_unlimited_tiles(vec![Kind::_3, Kind::_4]).unwrap();
+    assert!(builder.duplicates().is_empty());
+    let tile = Kind::_4.build(builder.hex());
+    assert_eq!(builder.add_limited_tile(tile, 2), Some(1));
+    assert_eq!(builder.duplicates(), &["4".to_string()]);
+    assert_eq!(builder.try_build(), Err(vec!["4".to_string()]));
+
+    let catalogue = Builder::all_tiles().try_build().unwrap();
+    for (ix, name) in catalogue.tile_names().enumerate() {
+        assert!(catalogue.contains(name));
+        assert_eq!(catalogue.index_of(name), Some(ix));
+        assert_eq!(catalogue.tile(name), Some(&catalogue[ix].0));
+    }
+    assert!(!catalogue.contains("no such tile"));
+}
+
+#[test]
+fn report_identical_and_unused_tiles() {
+    let mut builder =
+        Builder::with_unlimited_tiles(vec![Kind::_3, Kind::_4, Kind::_57])
+            .unwrap();
+    let mut copy = Kind::_57.build(builder.hex());
+    copy.name = "57-copy".to_string();
+    builder.add_unlimited_tile(copy);
+    let catalogue = builder.build();
+
+    let game = Builder::with_unlimited_tiles(vec![Kind::_4])
+        .unwrap()
+        .build();
+    let report = catalogue.report(&[game]);
+    let yellow = report.colours[&HexColour::Yellow];
+    assert_eq!(yellow.tiles, 4);
+    assert_eq!(yellow.dits, 2);
+    assert_eq!(yellow.cities, 2);
+    assert_eq!(report.total(), yellow);
+    assert_eq!(
+        report.duplicates,
+        vec![vec!["57".to_string(), "57-copy".to_string()]]
+    );
+    assert_eq!(report.unused, vec!["3", "57", "57-copy"]);
+
+    // The built-in tiles should not contain any identical tiles.
+    let catalogue = Builder::all_tiles().build();
+    let report = catalogue.report(std::iter::empty());
+    assert!(report.duplicates.is_empty(), "{:?}", report.duplicates);
+    assert_eq!(report.unused.len(), catalogue.len());
+    assert_eq!(report.total().tiles, catalogue.len());
+}
@@ -3,10 +3,12 @@
 //! Initial version of 1830 map and tiles.
 //!
 
-use super::{Company, DividendKind, DividendOptions, Rounding};
+use super::private::{Ability, Private};
+use super::treasury::TokenCost;
+use super::{Company, CompanyTier, DividendKind, DividendOptions};
 use n18catalogue::Catalogue;
-use n18hex::{self, Colour, Hex, HexFace, Orientation, RotateCW};
-use n18map::{Coordinates, FirstRow, HexAddress, Letters, Map};
+use n18hex::{self, Colour, Hex, HexColour, HexFace, Orientation, RotateCW};
+use n18map::{Coordinates, FirstRow, HexAddress, Letters, Map, TokenRule};
 use n18route::{Bonus, ConflictRule, Train, TrainType};
 use n18tile::Label;
 use n18token::{Token, TokenStyle};
@@ -29,6 +31,16 @@ mod locns;
 #[doc(inline)]
 pub use locns::Location;
 
+/// Returns the game phase (identified by its tile colour) that determines
+/// the revenue of each off-board location in the specified game phase.
+fn offboard_phase(phase: usize) -> HexColour {
+    match phase {
+        0 => HexColour::Yellow,
+        1 | 2 => HexColour::Green,
+        _ => HexColour::Brown,
+    }
+}
+
 /// Defines the trains, tiles, and map for 1830: Railways and Robber Barons.
 ///
 /// - Each game starts in phase 2.
@@ -36,8 +48,10 @@ pub use locns::Location;
 /// - Brown tiles are available from phase 5.
 /// - Off-board locations provide the lower revenue for phases 2-4, and the
 ///   higher revenue from phase 5.
+/// - Each private company blocks a single hex, and closes in phase 5.
 pub struct Game {
     companies: Vec<Company>,
+    privates: Vec<Private>,
     trains: Vec<(&'static str, Train)>,
     catalogue: Catalogue,
     barriers: Vec<(HexAddress, HexFace)>,
@@ -126,6 +140,7 @@ impl Game {
             abbrev: abbrev.to_string(),
             full_name: full_name.to_string(),
             token: Token::new(TokenStyle::TopArcs { bg, fg, text }),
+            tier: CompanyTier::Major,
         })
         .collect();
 
@@ -151,8 +166,36 @@ impl Game {
         let phase_names = vec!["2", "3", "4", "5", "6", "7"];
         let phase = 0;
 
+        // NOTE: every private company closes in phase 5.
+        let privates = [
+            ("SVNRR", "Schuylkill Valley", 20, 5, "O7", false),
+            ("C&SL", "Champlain & St. Lawrence", 40, 10, "T2", true),
+            ("D&H", "Delaware & Hudson", 70, 15, "P6", true),
+            ("M&H", "Mohawk & Hudson", 110, 20, "R4", false),
+            ("C&A", "Camden & Amboy", 160, 25, "R8", false),
+            ("B&O", "Baltimore & Ohio", 220, 30, "O9", false),
+        ]
+        .iter()
+        .map(|&(abbrev, full_name, cost, revenue, hex, free_lay)| {
+            let addr = parse(hex).unwrap();
+            let mut abilities = vec![Ability::BlocksHex(addr)];
+            if free_lay {
+                abilities.push(Ability::FreeTileLay(addr));
+            }
+            Private {
+                abbrev: abbrev.to_string(),
+                full_name: full_name.to_string(),
+                cost,
+                revenue,
+                abilities,
+                closing_phase: Some(3),
+            }
+        })
+        .collect();
+
         Game {
             companies,
+            privates,
             trains,
             catalogue,
             barriers,
@@ -182,19 +225,49 @@ impl super::Game for Game {
         &self.companies
     }
 
+    /// Returns the private companies in this game.
+    fn privates(&self) -> &[Private] {
+        &self.privates
+    }
+
     /// Returns the options available to a company for distributing dividends
     /// to shareholders.
+    ///
+    /// A company either pays its full revenue to shareholders, or withholds
+    /// all of its revenue.
     fn dividend_options(&self, _abbrev: &str) -> Option<DividendOptions> {
+        let rounding = self.share_rounding();
         Some(DividendOptions {
             share_count: 10,
-            dividend_options: vec![(DividendKind::Full, Rounding::Exact)],
+            dividend_options: vec![
+                (DividendKind::Full, rounding),
+                (DividendKind::Withhold, rounding),
+            ],
         })
     }
 
+    /// A company may place at most one token in each city.
+    fn token_rule(&self) -> TokenRule {
+        TokenRule::OnePerCity
+    }
+
+    /// The home token is free, the second token costs $40, and every other
+    /// token costs $100.
+    fn token_cost(&self, _abbrev: &str) -> TokenCost {
+        TokenCost::Fixed(vec![40, 100])
+    }
+
     fn trains(&self) -> &[(&str, Train)] {
         &self.trains
     }
 
+    /// The bank holds six 2-trains, five 3-trains, four 4-trains, three
+    /// 5-trains, two 6-trains, and an unlimited number of 7-trains and
+    /// diesels.
+    fn train_counts(&self) -> Vec<Option<usize>> {
+        vec![Some(6), Some(5), Some(4), Some(3), Some(2), None, None]
+    }
+
     /// This game does not include any route bonuses.
     fn bonus_options(&self) -> Vec<&'static str> {
         vec![]
@@ -276,9 +349,21 @@ impl super::Game for Game {
             map.add_barrier(*addr, *face)
         }
 
+        map.set_revenue_phase(offboard_phase(self.phase));
+        map.record_initial_tiles();
         map
     }
 
+    /// Returns the starting tiles.
+    fn placed_tiles(&self) -> Vec<(HexAddress, String)> {
+        initial_map()
+            .into_iter()
+            .filter_map(|(addr, tile)| {
+                tile.map(|(name, _rotn)| (addr, name.to_string()))
+            })
+            .collect()
+    }
+
     /// Returns all game tiles, including special tiles that players cannot
     /// place on the map.
     fn catalogue(&self) -> &Catalogue {
@@ -296,30 +381,7 @@ impl super::Game for Game {
             return false;
         }
         self.phase = phase;
-
-        let suffix = match phase {
-            0 => "Yw",
-            1 | 2 => "Gn",
-            _ => "Bn",
-        };
-        let offboard_phase_locns = [
-            Location::Chicago,
-            Location::GulfOfMexicoS,
-            Location::CanadianWestE,
-            Location::DeepSouth,
-            Location::MaritimeProvinces,
-        ];
-        for locn in offboard_phase_locns {
-            let locn_addr = locn.address();
-            let locn_name = locn.as_str();
-            let tile_name = format!("{}_{}", locn_name, suffix);
-            if !map.place_tile(locn_addr, &tile_name, RotateCW::Zero) {
-                println!(
-                    "Could not place tile {} at {}",
-                    tile_name, locn_addr
-                )
-            }
-        }
+        map.set_revenue_phase(offboard_phase(phase));
         true
     }
 
@@ -327,6 +389,26 @@ impl super::Game for Game {
     fn phase_names(&self) -> &[&str] {
         &self.phase_names
     }
+
+    /// Returns the tile colours that can be placed in each game phase.
+    fn phase_tile_colours(&self, phase: usize) -> Vec<HexColour> {
+        let last = match phase {
+            0 => HexColour::Yellow,
+            1 | 2 => HexColour::Green,
+            _ => HexColour::Brown,
+        };
+        super::tile_colours_up_to(last)
+    }
+
+    /// Companies can operate at most four trains in phases 2 and 3, three
+    /// trains in phase 4, and two trains from phase 5 onwards.
+    fn phase_train_limit(&self, phase: usize) -> Option<usize> {
+        match phase {
+            0 | 1 => Some(4),
+            2 => Some(3),
+            _ => Some(2),
+        }
+    }
 }
 
 /// Returns the address of each map hex, and the tile that should be placed
@@ -338,9 +420,9 @@ pub fn initial_map() -> Vec<(HexAddress, Option<(&'static str, RotateCW)>)> {
         ("A9", Some(("Gulf of Mexico 2", Zero))),
         // Column B contains Lansing, Chicago, and Gulf of Mexico.
         ("B4", Some(("Lansing", Zero))),
-        ("B6", Some(("Chicago_Yw", Zero))),
+        ("B6", Some(("Chicago", Zero))),
         ("B8", None),
-        ("B10", Some(("Gulf of Mexico_Yw", Zero))),
+        ("B10", Some(("Gulf of Mexico", Zero))),
         // Column C contains empty hexes.
         ("C5", None),
         ("C7", None),
@@ -382,7 +464,7 @@ pub fn initial_map() -> Vec<(HexAddress, Option<(&'static str, RotateCW)>)> {
         ("J8", Some(("Pittsburgh", Zero))),
         ("J10", Some(("Cost_120", Zero))),
         // Column K contains the second tile of Canadian West, and Buffalo.
-        ("K1", Some(("Canadian West_Yw", Zero))),
+        ("K1", Some(("Canadian West", Zero))),
         ("K3", None),
         ("K5", Some(("Buffalo/Dunkirk", Zero))),
         ("K7", None),
@@ -398,7 +480,7 @@ pub fn initial_map() -> Vec<(HexAddress, Option<(&'static str, RotateCW)>)> {
         ("M5", None),
         ("M7", Some(("Cost_120", Zero))),
         ("M9", None),
-        ("M11", Some(("Deep South_Yw", Zero))),
+        ("M11", Some(("Deep South", Zero))),
         // Column N contains Rochester and Washington.
         ("N2", None),
         ("N4", Some(("Rochester", Zero))),
@@ -448,7 +530,7 @@ pub fn initial_map() -> Vec<(HexAddress, Option<(&'static str, RotateCW)>)> {
         ("W3", None),
         ("W5", Some(("Boston", Zero))),
         // Column X contains the off-board location Maritime Provinces.
-        ("X2", Some(("Maritime Provinces_Yw", Zero))),
+        ("X2", Some(("Maritime Provinces", Zero))),
         ("X4", Some(("Grey1", Zero))),
         ("X6", Some(("Fall River", Zero))),
     ]
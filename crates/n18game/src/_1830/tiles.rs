@@ -15,7 +15,7 @@ pub fn catalogue() -> Catalogue {
     let starting_tiles = initial_tiles(hex);
     builder.add_unavailable_tiles(offboard_tiles);
     builder.add_unavailable_tiles(starting_tiles);
-    builder.build()
+    builder.try_build().expect("Duplicate tile names")
 }
 
 /// Returns the tiles that are available to players at the start of the game.
@@ -569,101 +569,85 @@ fn empty_with_costs(hex: &Hex) -> Vec<Tile> {
 }
 
 /// Returns the tiles for each off-board location.
+///
+/// Note that the phase revenue labels highlight the revenue for the current
+/// game phase (see [n18map::Map::set_revenue_phase]).
 pub fn offboard_tiles(hex: &Hex) -> Vec<Tile> {
-    let suffixes = ["Yw", "Gn", "Bn"];
-    [
-        offboard_chicago(hex, &suffixes),
-        offboard_deep_south(hex, &suffixes),
-        offboard_maritime(hex, &suffixes),
-        offboard_mexico_1(hex, &suffixes),
-        offboard_canada_1(hex, &suffixes),
-        vec![offboard_mexico_2(hex), offboard_canada_2(hex)],
+    vec![
+        offboard_chicago(hex),
+        offboard_deep_south(hex),
+        offboard_maritime(hex),
+        offboard_mexico_1(hex),
+        offboard_canada_1(hex),
+        offboard_mexico_2(hex),
+        offboard_canada_2(hex),
     ]
-    .concat()
 }
 
-/// The off-board tiles for Chicago.
-fn offboard_chicago(hex: &Hex, suffixes: &[&str]) -> Vec<Tile> {
+/// Returns the phase revenue label for the yellow, green, and brown phases.
+fn phase_revenue(revenues: [usize; 3]) -> Label {
+    Label::PhaseRevenue(vec![
+        (HexColour::Yellow, revenues[0], true),
+        (HexColour::Green, revenues[1], false),
+        (HexColour::Brown, revenues[2], false),
+    ])
+}
+
+/// The off-board tile for Chicago.
+fn offboard_chicago(hex: &Hex) -> Tile {
     let city = "Chicago";
     let revenues = [40, 40, 70];
-    revenues
-        .iter()
-        .enumerate()
-        .map(|(ix, revenue)| {
-            let suffix = suffixes[ix];
-            let name = format!("{}_{}", city, suffix);
-            Tile::new(
-                HexColour::Red,
-                name,
-                vec![
-                    Track::straight(HexFace::Top).with_span(0.0, 0.5),
-                    Track::straight(HexFace::UpperRight).with_span(0.0, 0.5),
-                    Track::straight(HexFace::LowerRight).with_span(0.0, 0.5),
-                ],
-                vec![City::single(*revenue)],
-                hex,
-            )
-            .label(
-                Label::PhaseRevenue(vec![
-                    (HexColour::Yellow, revenues[0], ix == 0),
-                    (HexColour::Green, revenues[1], ix == 1),
-                    (HexColour::Brown, revenues[2], ix > 1),
-                ]),
-                HexCorner::BottomLeft.to_centre(0.1),
-            )
-            .label(
-                Label::MapLocation(city.to_string()),
-                HexFace::LowerLeft.to_centre(0.1),
-            )
-            .with_offboard_faces([
-                HexFace::Top,
-                HexFace::UpperRight,
-                HexFace::LowerRight,
-            ])
-            .hide_tile_name()
-        })
-        .collect()
+    Tile::new(
+        HexColour::Red,
+        city,
+        vec![
+            Track::straight(HexFace::Top).with_span(0.0, 0.5),
+            Track::straight(HexFace::UpperRight).with_span(0.0, 0.5),
+            Track::straight(HexFace::LowerRight).with_span(0.0, 0.5),
+        ],
+        vec![City::single(revenues[0])],
+        hex,
+    )
+    .label(
+        phase_revenue(revenues),
+        HexCorner::BottomLeft.to_centre(0.1),
+    )
+    .label(
+        Label::MapLocation(city.to_string()),
+        HexFace::LowerLeft.to_centre(0.1),
+    )
+    .with_offboard_faces([
+        HexFace::Top,
+        HexFace::UpperRight,
+        HexFace::LowerRight,
+    ])
+    .hide_tile_name()
 }
 
-/// The off-board tiles for the Gulf of Mexico that have phase-specific
+/// The off-board tile for the Gulf of Mexico that has phase-specific
 /// content.
-fn offboard_mexico_1(hex: &Hex, suffixes: &[&str]) -> Vec<Tile> {
+fn offboard_mexico_1(hex: &Hex) -> Tile {
     let name = "Gulf of Mexico";
     let text = "Gulf of\nMexico";
     let revenues = [30, 30, 60];
-    revenues
-        .iter()
-        .enumerate()
-        .map(|(ix, revenue)| {
-            let suffix = suffixes[ix];
-            let tile_name = format!("{}_{}", name, suffix);
-            Tile::new(
-                HexColour::Red,
-                tile_name,
-                vec![
-                    Track::straight(HexFace::UpperLeft).with_span(0.0, 0.5),
-                    Track::straight(HexFace::Top).with_span(0.0, 0.5),
-                    Track::straight(HexFace::UpperRight).with_span(0.0, 0.5),
-                ],
-                vec![City::single(*revenue)],
-                hex,
-            )
-            .label(
-                Label::PhaseRevenue(vec![
-                    (HexColour::Yellow, revenues[0], ix == 0),
-                    (HexColour::Green, revenues[1], ix == 1),
-                    (HexColour::Brown, revenues[2], ix > 1),
-                ]),
-                HexCorner::Left.to_centre(0.1),
-            )
-            .label(
-                Label::MapLocation(text.to_string()),
-                HexFace::Bottom.to_centre(0.1),
-            )
-            .with_offboard_faces([HexFace::Top, HexFace::UpperRight])
-            .hide_tile_name()
-        })
-        .collect()
+    Tile::new(
+        HexColour::Red,
+        name,
+        vec![
+            Track::straight(HexFace::UpperLeft).with_span(0.0, 0.5),
+            Track::straight(HexFace::Top).with_span(0.0, 0.5),
+            Track::straight(HexFace::UpperRight).with_span(0.0, 0.5),
+        ],
+        vec![City::single(revenues[0])],
+        hex,
+    )
+    .label(phase_revenue(revenues), HexCorner::Left.to_centre(0.1))
+    .label(
+        Label::MapLocation(text.to_string()),
+        HexFace::Bottom.to_centre(0.1),
+    )
+    .with_offboard_faces([HexFace::Top, HexFace::UpperRight])
+    .hide_tile_name()
 }
 
 /// The off-board tiles for the Gulf of Mexico that do not have phase-specific
@@ -681,44 +665,29 @@ fn offboard_mexico_2(hex: &Hex) -> Tile {
     .hide_tile_name()
 }
 
-/// The off-board tiles for the Canadian West that have phase-specific
+/// The off-board tile for the Canadian West that has phase-specific
 /// content.
-fn offboard_canada_1(hex: &Hex, suffixes: &[&str]) -> Vec<Tile> {
+fn offboard_canada_1(hex: &Hex) -> Tile {
     let city = "Canadian West";
     let revenues = [30, 30, 50];
-    revenues
-        .iter()
-        .enumerate()
-        .map(|(ix, revenue)| {
-            let suffix = suffixes[ix];
-            let name = format!("{}_{}", city, suffix);
-            Tile::new(
-                HexColour::Red,
-                name,
-                vec![
-                    Track::straight(HexFace::LowerLeft).with_span(0.0, 0.5),
-                    Track::straight(HexFace::Bottom).with_span(0.0, 0.5),
-                    Track::straight(HexFace::LowerRight).with_span(0.0, 0.5),
-                ],
-                vec![City::single(*revenue)],
-                hex,
-            )
-            .label(
-                Label::PhaseRevenue(vec![
-                    (HexColour::Yellow, revenues[0], ix == 0),
-                    (HexColour::Green, revenues[1], ix == 1),
-                    (HexColour::Brown, revenues[2], ix > 1),
-                ]),
-                HexFace::UpperRight.to_centre(0.1),
-            )
-            .label(
-                Label::MapLocation(city.to_string()),
-                HexCorner::TopLeft.to_centre(0.3),
-            )
-            .with_offboard_faces([HexFace::Bottom, HexFace::LowerRight])
-            .hide_tile_name()
-        })
-        .collect()
+    Tile::new(
+        HexColour::Red,
+        city,
+        vec![
+            Track::straight(HexFace::LowerLeft).with_span(0.0, 0.5),
+            Track::straight(HexFace::Bottom).with_span(0.0, 0.5),
+            Track::straight(HexFace::LowerRight).with_span(0.0, 0.5),
+        ],
+        vec![City::single(revenues[0])],
+        hex,
+    )
+    .label(phase_revenue(revenues), HexFace::UpperRight.to_centre(0.1))
+    .label(
+        Label::MapLocation(city.to_string()),
+        HexCorner::TopLeft.to_centre(0.3),
+    )
+    .with_offboard_faces([HexFace::Bottom, HexFace::LowerRight])
+    .hide_tile_name()
 }
 
 /// The off-board tiles for the Canadian West that do not have phase-specific
@@ -736,79 +705,49 @@ fn offboard_canada_2(hex: &Hex) -> Tile {
     .hide_tile_name()
 }
 
-/// The off-board tiles for the Deep South.
-fn offboard_deep_south(hex: &Hex, suffixes: &[&str]) -> Vec<Tile> {
+/// The off-board tile for the Deep South.
+fn offboard_deep_south(hex: &Hex) -> Tile {
     let city = "Deep South";
     let revenues = [30, 30, 40];
-    revenues
-        .iter()
-        .enumerate()
-        .map(|(ix, revenue)| {
-            let suffix = suffixes[ix];
-            let name = format!("{}_{}", city, suffix);
-            Tile::new(
-                HexColour::Red,
-                name,
-                vec![
-                    Track::straight(HexFace::Top).with_span(0.0, 0.5),
-                    Track::straight(HexFace::UpperLeft).with_span(0.0, 0.5),
-                ],
-                vec![City::single(*revenue)],
-                hex,
-            )
-            .label(
-                Label::PhaseRevenue(vec![
-                    (HexColour::Yellow, revenues[0], ix == 0),
-                    (HexColour::Green, revenues[1], ix == 1),
-                    (HexColour::Brown, revenues[2], ix > 1),
-                ]),
-                HexCorner::BottomRight.to_centre(0.5),
-            )
-            .label(
-                Label::MapLocation(city.to_string()),
-                HexPosition::centre(),
-            )
-            .with_offboard_faces([HexFace::Top, HexFace::UpperLeft])
-            .hide_tile_name()
-        })
-        .collect()
+    Tile::new(
+        HexColour::Red,
+        city,
+        vec![
+            Track::straight(HexFace::Top).with_span(0.0, 0.5),
+            Track::straight(HexFace::UpperLeft).with_span(0.0, 0.5),
+        ],
+        vec![City::single(revenues[0])],
+        hex,
+    )
+    .label(
+        phase_revenue(revenues),
+        HexCorner::BottomRight.to_centre(0.5),
+    )
+    .label(Label::MapLocation(city.to_string()), HexPosition::centre())
+    .with_offboard_faces([HexFace::Top, HexFace::UpperLeft])
+    .hide_tile_name()
 }
 
-/// The off-board tiles for the Maritime Provinces.
-fn offboard_maritime(hex: &Hex, suffixes: &[&str]) -> Vec<Tile> {
+/// The off-board tile for the Maritime Provinces.
+fn offboard_maritime(hex: &Hex) -> Tile {
     let name = "Maritime Provinces";
     let text = "Maritime\nProvinces";
     let revenues = [20, 20, 30];
-    revenues
-        .iter()
-        .enumerate()
-        .map(|(ix, revenue)| {
-            let suffix = suffixes[ix];
-            let tile_name = format!("{}_{}", name, suffix);
-            Tile::new(
-                HexColour::Red,
-                tile_name,
-                vec![
-                    Track::straight(HexFace::Bottom).with_span(0.0, 0.5),
-                    Track::straight(HexFace::LowerLeft).with_span(0.0, 0.5),
-                ],
-                vec![City::single(*revenue)],
-                hex,
-            )
-            .label(
-                Label::PhaseRevenue(vec![
-                    (HexColour::Yellow, revenues[0], ix == 0),
-                    (HexColour::Green, revenues[1], ix == 1),
-                    (HexColour::Brown, revenues[2], ix > 1),
-                ]),
-                HexCorner::Right.to_centre(0.1),
-            )
-            .label(
-                Label::MapLocation(text.to_string()),
-                HexCorner::TopRight.in_dir(W, 0.1),
-            )
-            .with_offboard_faces([HexFace::Bottom, HexFace::LowerLeft])
-            .hide_tile_name()
-        })
-        .collect()
+    Tile::new(
+        HexColour::Red,
+        name,
+        vec![
+            Track::straight(HexFace::Bottom).with_span(0.0, 0.5),
+            Track::straight(HexFace::LowerLeft).with_span(0.0, 0.5),
+        ],
+        vec![City::single(revenues[0])],
+        hex,
+    )
+    .label(phase_revenue(revenues), HexCorner::Right.to_centre(0.1))
+    .label(
+        Label::MapLocation(text.to_string()),
+        HexCorner::TopRight.in_dir(W, 0.1),
+    )
+    .with_offboard_faces([HexFace::Bottom, HexFace::LowerLeft])
+    .hide_tile_name()
 }
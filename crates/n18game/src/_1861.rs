@@ -5,16 +5,32 @@
 
 use std::collections::BTreeMap;
 
-use super::{Company, DividendKind, DividendOptions, Rounding};
+use super::{
+    Company, CompanyTier, DividendKind, DividendOptions, NationalFormation,
+    Rounding, TierRules,
+};
 use n18catalogue::{Builder, Catalogue, Kind};
 use n18hex::{
     Colour, Hex, HexColour, HexFace, HexPosition, Orientation, RotateCW,
 };
-use n18map::{Coordinates, FirstRow, HexAddress, Letters, Map};
-use n18route::{Bonus, ConflictRule, Train, TrainType};
+use n18map::{Coordinates, FirstRow, HexAddress, Letters, Map, TokenRule};
+use n18route::{
+    Bonus, ConflictRule, HighestStops, Scoring, Train, TrainType,
+};
 use n18tile::{Label, Tile};
 use n18token::{Token, TokenStyle};
 
+/// Returns the game phase (identified by its tile colour) that determines
+/// the revenue of each off-board location in the specified game phase.
+fn offboard_phase(phase: usize) -> HexColour {
+    match phase {
+        0 => HexColour::Yellow,
+        1 | 2 => HexColour::Green,
+        3 => HexColour::Brown,
+        _ => HexColour::Grey,
+    }
+}
+
 /// Defines the trains, tiles, and map for 1861: The Railways Of The Russian Empire.
 pub struct Game {
     companies: Vec<Company>,
@@ -78,7 +94,7 @@ impl Game {
             ("V", "Vladikavkaz"),
             // Major.
             ("GRR", "Grand Russian Railway"),
-            ("MK", "Moscow & Kazan Railway"),
+            ("MKR", "Moscow & Kazan Railway"),
             ("MKN", "Moscow, Kursk & Nizhnii Novgorod"),
             ("MKV", "Moscow, Kiev & Voronezh"),
             ("MVR", "Moscow, Vindava & Rybinsk Railway"),
@@ -90,9 +106,8 @@ impl Game {
         // Background colours for minor (yellow) and major (green) companies.
         let bg_yellow = Colour::from((223, 223, 0));
         let bg_green = Colour::from((0, 153, 63));
-        let bg_iter = std::iter::repeat(bg_yellow)
-            .take(16)
-            .chain(std::iter::repeat(bg_green).take(8));
+        let bg_iter = std::iter::repeat_n(bg_yellow, 16)
+            .chain(std::iter::repeat_n(bg_green, 8));
 
         let fg_colours = vec![
             Colour::from((0, 204, 204)), // Aqua
@@ -121,10 +136,17 @@ impl Game {
                     4 => TokenStyle::TopLines { bg, fg, text },
                     _ => TokenStyle::TopTriangles { bg, fg, text },
                 };
+                // The first 16 companies are minors, the rest are majors.
+                let tier = if ix < 16 {
+                    CompanyTier::Minor
+                } else {
+                    CompanyTier::Major
+                };
                 Company {
                     abbrev: company_names[ix].0.to_string(),
                     full_name: company_names[ix].1.to_string(),
                     token: Token::new(style),
+                    tier,
                 }
             })
             .collect();
@@ -141,6 +163,7 @@ impl Game {
                 bottom: Colour::from((213, 43, 30)),
                 text: Colour::WHITE,
             }),
+            tier: CompanyTier::Major,
         });
 
         let barriers = vec![];
@@ -182,10 +205,18 @@ impl super::Game for Game {
         &self.companies
     }
 
+    /// Returns how the Russian National Railway is formed from the minor
+    /// companies.
+    fn national_formation(&self) -> Option<NationalFormation> {
+        // NOTE: the first 16 companies are minors, and the national company
+        // is the last company.
+        NationalFormation::from_minors(&self.companies, 16, "6")
+    }
+
     /// Returns the options available to a company for distributing dividends
     /// to shareholders.
     fn dividend_options(&self, abbrev: &str) -> Option<DividendOptions> {
-        self.companies.iter().enumerate().find_map(|(ix, company)| {
+        self.companies.iter().find_map(|company| {
             if company.abbrev == abbrev {
                 // NOTE: half-pay means that 50% of the revenue --- rounded up
                 // to the nearest 10 --- goes to shareholders.
@@ -193,16 +224,19 @@ impl super::Game for Game {
                     rounding: Rounding::Up,
                     nearest: 10,
                 };
-                // The first 16 companies are minors, the rest are majors.
-                let is_minor = ix < 16;
+                let is_minor = company.tier == CompanyTier::Minor;
+                let rounding = self.share_rounding();
 
+                // NOTE: minor companies must pay half of their revenue to
+                // their owner, and cannot withhold.
                 let share_count = if is_minor { 1 } else { 10 };
                 let dividend_options = if is_minor {
-                    vec![(half_pay, Rounding::Exact)]
+                    vec![(half_pay, rounding)]
                 } else {
                     vec![
-                        (DividendKind::Full, Rounding::Exact),
-                        (half_pay, Rounding::Exact),
+                        (DividendKind::Full, rounding),
+                        (half_pay, rounding),
+                        (DividendKind::Withhold, rounding),
                     ]
                 };
 
@@ -216,6 +250,24 @@ impl super::Game for Game {
         })
     }
 
+    /// A company may place at most one token in each city.
+    fn token_rule(&self) -> TokenRule {
+        TokenRule::OnePerCity
+    }
+
+    /// Minor companies have a single token and can operate at most two
+    /// trains, while the limits for major companies depend on the company
+    /// and on the game phase.
+    fn tier_rules(&self, tier: CompanyTier) -> TierRules {
+        match tier {
+            CompanyTier::Minor => TierRules {
+                token_count: Some(1),
+                train_limit: Some(2),
+            },
+            CompanyTier::Major => TierRules::default(),
+        }
+    }
+
     /// Returns the named train types in this game, in the order that they
     /// become available (where applicable).
     fn trains(&self) -> &[(&str, Train)] {
@@ -280,6 +332,12 @@ impl super::Game for Game {
         bonuses
     }
 
+    /// The 5+5E train can visit any number of locations, but only earns
+    /// revenue from the five highest-value locations (which is doubled).
+    fn scoring(&self) -> Box<dyn Scoring> {
+        Box::new(HighestStops::new([*self.train("5+5E")]))
+    }
+
     /// Defines the elements that cannot be shared in a single route.
     ///
     /// A single route cannot reuse any track segment, any revenue centre
@@ -320,9 +378,21 @@ impl super::Game for Game {
             map.add_barrier(*addr, *face)
         }
         // TODO: mark tiles that are not modifiable.
+        map.set_revenue_phase(offboard_phase(self.phase));
+        map.record_initial_tiles();
         map
     }
 
+    /// Returns the starting tiles, and the tiles that are placed for each
+    /// game phase.
+    fn placed_tiles(&self) -> Vec<(HexAddress, String)> {
+        let starting = initial_tiles()
+            .into_iter()
+            .map(|(addr, (name, _rotn))| (addr, name.to_string()));
+        let phases = (0..self.phase_names.len()).flat_map(phase_tiles);
+        starting.chain(phases).collect()
+    }
+
     /// Returns all game tiles, including special tiles that players cannot
     /// place on the map.
     fn catalogue(&self) -> &Catalogue {
@@ -340,31 +410,12 @@ impl super::Game for Game {
             return false;
         }
         self.phase = phase;
-        let red_cities: Vec<(HexAddress, &str)> = vec![
-            ((7, 16).into(), "Central Asia"),
-            ((10, 1).into(), "Romania"),
-            ((11, 8).into(), "Caucasus"),
-            ((8, 0).into(), "Poland"),
-        ];
-        let suffix = match phase {
-            0 => "Yw",
-            1 | 2 => "Gn",
-            3 => "Bn",
-            _ => "Gy",
-        };
-        for (addr, city_name) in &red_cities {
-            let tile_name = format!("{} {}", city_name, suffix);
-            if !map.place_tile(*addr, &tile_name, RotateCW::Zero) {
+        map.set_revenue_phase(offboard_phase(phase));
+        for (addr, tile_name) in phase_tiles(phase) {
+            if !map.place_tile(addr, &tile_name, RotateCW::Zero) {
                 println!("Could not place tile {} at {}", tile_name, addr)
             }
         }
-        // Show a green token space in Ekaterinburg for phase 2,
-        // and an empty token space for all other phases.
-        let ekat_addr: HexAddress = (2, 16).into();
-        let ekat_tile = if phase == 0 { "Ekat Yw" } else { "Ekat Gr" };
-        if !map.place_tile(ekat_addr, ekat_tile, RotateCW::Zero) {
-            println!("Could not place tile {} at {}", ekat_tile, ekat_addr)
-        }
         true
     }
 
@@ -372,6 +423,17 @@ impl super::Game for Game {
     fn phase_names(&self) -> &[&str] {
         &self.phase_names
     }
+
+    /// Returns the tile colours that can be placed in each game phase.
+    fn phase_tile_colours(&self, phase: usize) -> Vec<HexColour> {
+        let last = match phase {
+            0 => HexColour::Yellow,
+            1 | 2 => HexColour::Green,
+            3 | 4 => HexColour::Brown,
+            _ => HexColour::Grey,
+        };
+        super::tile_colours_up_to(last)
+    }
 }
 
 fn addrs() -> Vec<(isize, isize)> {
@@ -608,6 +670,18 @@ fn player_tiles() -> Builder {
     Builder::with_available_tiles(tiles).unwrap()
 }
 
+/// Returns the tiles that change with the game phase: Ekaterinburg.
+///
+/// Note that the red off-board cities do not change, because their phase
+/// revenue labels highlight the revenue for the current game phase (see
+/// [n18map::Map::set_revenue_phase]).
+fn phase_tiles(phase: usize) -> Vec<(HexAddress, String)> {
+    // Show a green token space in Ekaterinburg for phase 2,
+    // and an empty token space for all other phases.
+    let ekat_tile = if phase == 0 { "Ekat Yw" } else { "Ekat Gr" };
+    vec![((2, 16).into(), ekat_tile.to_string())]
+}
+
 fn initial_tiles() -> BTreeMap<HexAddress, (&'static str, RotateCW)> {
     let tiles: Vec<(HexAddress, (&str, RotateCW))> = vec![
         // Grey off-board tiles.
@@ -664,16 +738,16 @@ fn initial_tiles() -> BTreeMap<HexAddress, (&'static str, RotateCW)> {
         ((4, 2).into(), ("8 initial", RotateCW::Three)),
         // Red off-board tiles.
         ((6, 16).into(), ("Central Asia2", RotateCW::Zero)),
-        ((7, 16).into(), ("Central Asia Yw", RotateCW::Zero)),
-        ((10, 1).into(), ("Romania Yw", RotateCW::Zero)),
+        ((7, 16).into(), ("Central Asia", RotateCW::Zero)),
+        ((10, 1).into(), ("Romania", RotateCW::Zero)),
         ((9, 1).into(), ("Romania2", RotateCW::Zero)),
-        ((11, 8).into(), ("Caucasus Yw", RotateCW::Zero)),
+        ((11, 8).into(), ("Caucasus", RotateCW::Zero)),
         ((10, 9).into(), ("Caucasus2", RotateCW::Zero)),
         ((11, 10).into(), ("Caucasus3", RotateCW::Zero)),
         ((5, 0).into(), ("Poland2", RotateCW::Zero)),
         ((6, 0).into(), ("Poland3", RotateCW::Zero)),
         ((7, 0).into(), ("Poland3", RotateCW::Zero)),
-        ((8, 0).into(), ("Poland Yw", RotateCW::Zero)),
+        ((8, 0).into(), ("Poland", RotateCW::Zero)),
     ];
     tiles.into_iter().collect()
 }
@@ -719,7 +793,7 @@ fn make_catalogue() -> Catalogue {
     builder.add_unavailable_tiles(city_tiles);
     builder.add_unavailable_tiles(offb_tiles);
     builder.add_unavailable_tiles(misc_tiles);
-    builder.build()
+    builder.try_build().expect("Duplicate tile names")
 }
 
 /// Position labels relative to the tile centre.
@@ -731,9 +805,9 @@ fn off_centre(dir: n18hex::Direction, frac: f64) -> HexPosition {
 }
 
 fn starting_city_tiles(hex: &Hex) -> Vec<Tile> {
+    use n18hex::HexColour::*;
     use n18hex::{Direction::*, *};
     use n18tile::*;
-    use HexColour::*;
     use HexCorner::*;
     use HexFace::*;
 
@@ -954,15 +1028,27 @@ fn starting_city_tiles(hex: &Hex) -> Vec<Tile> {
 }
 
 fn offboard_tiles(hex: &Hex) -> Vec<Tile> {
-    let suffixes = vec!["Yw", "Gn", "Bn", "Gy"];
     let mut tiles = vec![];
-    tiles.append(&mut central_asia(hex, &suffixes));
-    tiles.append(&mut romania(hex, &suffixes));
-    tiles.append(&mut caucasus(hex, &suffixes));
-    tiles.append(&mut poland(hex, &suffixes));
+    tiles.append(&mut central_asia(hex));
+    tiles.append(&mut romania(hex));
+    tiles.append(&mut caucasus(hex));
+    tiles.append(&mut poland(hex));
     tiles
 }
 
+/// Returns the phase revenue label for the yellow, green, brown, and grey
+/// phases.
+fn phase_revenue(revenues: [usize; 4]) -> Label {
+    use n18hex::HexColour::*;
+
+    Label::PhaseRevenue(vec![
+        (Yellow, revenues[0], true),
+        (Green, revenues[1], false),
+        (Brown, revenues[2], false),
+        (Grey, revenues[3], false),
+    ])
+}
+
 /// Position labels above the bottom hex face.
 ///
 /// The default nudge is 0.215 towards the tile centre.
@@ -971,86 +1057,62 @@ fn above_bottom_face<F: Into<Option<f64>>>(pos: F) -> HexPosition {
     HexFace::Bottom.to_centre(frac)
 }
 
-fn central_asia(hex: &Hex, suffixes: &[&str]) -> Vec<Tile> {
+fn central_asia(hex: &Hex) -> Vec<Tile> {
     use n18hex::{Direction::*, HexColour::*, HexCorner::*, HexFace::*};
     use n18tile::*;
 
     let name = "Central Asia";
     let revenues = [10, 20, 30, 40];
-    revenues
-        .iter()
-        .enumerate()
-        .map(|(ix, &revenue)| {
-            Tile::new(
-                Red,
-                format!("{} {}", name, suffixes[ix]),
-                vec![
-                    Track::hard_l(UpperLeft).with_span(0.0, 0.5),
-                    Track::hard_l(UpperLeft).with_span(0.5, 1.0),
-                ],
-                vec![
-                    City::single_at_corner(revenue, &TopLeft).with_fill(Red)
-                ],
-                hex,
-            )
-            .with_offboard_faces([UpperLeft])
-            .label(Label::MapLocation(name.to_string()), off_centre(S, 0.15))
-            .label(
-                Label::PhaseRevenue(vec![
-                    (Yellow, revenues[0], ix == 0),
-                    (Green, revenues[1], ix == 1),
-                    (Brown, revenues[2], ix == 2),
-                    (Grey, revenues[3], ix == 3),
-                ]),
-                above_bottom_face(0.15),
-            )
-        })
-        .chain(vec![Tile::new(
+    vec![
+        Tile::new(
+            Red,
+            name,
+            vec![
+                Track::hard_l(UpperLeft).with_span(0.0, 0.5),
+                Track::hard_l(UpperLeft).with_span(0.5, 1.0),
+            ],
+            vec![
+                City::single_at_corner(revenues[0], &TopLeft).with_fill(Red)
+            ],
+            hex,
+        )
+        .with_offboard_faces([UpperLeft])
+        .label(Label::MapLocation(name.to_string()), off_centre(S, 0.15))
+        .label(phase_revenue(revenues), above_bottom_face(0.15)),
+        Tile::new(
             Red,
             format!("{}2", name),
             vec![Track::gentle_l(Bottom), Track::hard_l(Bottom)],
             vec![],
             hex,
         )
-        .with_offboard_faces([LowerLeft, UpperLeft])])
-        .collect()
+        .with_offboard_faces([LowerLeft, UpperLeft]),
+    ]
 }
 
-fn romania(hex: &Hex, suffixes: &[&str]) -> Vec<Tile> {
+fn romania(hex: &Hex) -> Vec<Tile> {
     use n18hex::{Direction::*, HexColour::*, HexCorner::*, HexFace::*};
     use n18tile::*;
 
     let name = "Romania";
     let revenues = [10, 20, 30, 30];
-    revenues
-        .iter()
-        .enumerate()
-        .map(|(ix, &revenue)| {
-            Tile::new(
-                Red,
-                format!("{} {}", name, suffixes[ix]),
-                vec![
-                    Track::hard_r(UpperRight).with_span(0.0, 0.5),
-                    Track::hard_r(UpperRight).with_span(0.5, 1.0),
-                ],
-                vec![
-                    City::single_at_corner(revenue, &TopRight).with_fill(Red)
-                ],
-                hex,
-            )
-            .with_offboard_faces([UpperRight])
-            .label(Label::MapLocation(name.to_string()), off_centre(S, 0.12))
-            .label(
-                Label::PhaseRevenue(vec![
-                    (Yellow, revenues[0], ix == 0),
-                    (Green, revenues[1], ix == 1),
-                    (Brown, revenues[2], ix == 2),
-                    (Grey, revenues[3], ix == 3),
-                ]),
-                above_bottom_face(None),
-            )
-        })
-        .chain(vec![Tile::new(
+    vec![
+        Tile::new(
+            Red,
+            name,
+            vec![
+                Track::hard_r(UpperRight).with_span(0.0, 0.5),
+                Track::hard_r(UpperRight).with_span(0.5, 1.0),
+            ],
+            vec![
+                City::single_at_corner(revenues[0], &TopRight).with_fill(Red)
+            ],
+            hex,
+        )
+        .with_offboard_faces([UpperRight])
+        .label(Label::MapLocation(name.to_string()), off_centre(S, 0.12))
+        .label(phase_revenue(revenues), above_bottom_face(None)),
+        Tile::new(
             Red,
             format!("{}2", name),
             vec![
@@ -1061,135 +1123,104 @@ fn romania(hex: &Hex, suffixes: &[&str]) -> Vec<Tile> {
             vec![],
             hex,
         )
-        .with_offboard_faces([Top, UpperRight, LowerRight])])
-        .collect()
+        .with_offboard_faces([Top, UpperRight, LowerRight]),
+    ]
 }
 
-fn caucasus(hex: &Hex, suffixes: &[&str]) -> Vec<Tile> {
+fn caucasus(hex: &Hex) -> Vec<Tile> {
     use n18hex::{Direction::*, HexColour::*, HexCorner::*, HexFace::*};
     use n18tile::*;
 
     let name = "Caucasus";
     let revenues = [10, 20, 40, 60];
-    revenues
-        .iter()
-        .enumerate()
-        .map(|(ix, &revenue)| {
-            Tile::new(
-                Red,
-                format!("{} {}", name, suffixes[ix]),
-                vec![
-                    Track::hard_r(UpperRight).with_span(0.0, 0.5),
-                    Track::hard_r(UpperRight).with_span(0.5, 1.0),
-                ],
-                vec![
-                    City::single_at_corner(revenue, &TopRight).with_fill(Red)
-                ],
-                hex,
-            )
-            .with_offboard_faces([Top])
-            .label(Label::MapLocation(name.to_string()), off_centre(S, 0.12))
-            .label(
-                Label::PhaseRevenue(vec![
-                    (Yellow, revenues[0], ix == 0),
-                    (Green, revenues[1], ix == 1),
-                    (Brown, revenues[2], ix == 2),
-                    (Grey, revenues[3], ix == 3),
-                ]),
-                above_bottom_face(None),
-            )
-        })
-        .chain(vec![
-            Tile::new(
-                Red,
-                format!("{}2", name),
-                vec![
-                    Track::gentle_r(LowerLeft),
-                    Track::straight(LowerLeft),
-                    Track::gentle_l(LowerLeft),
-                    Track::hard_l(LowerLeft),
-                ],
-                vec![],
-                hex,
-            )
-            .with_offboard_faces([UpperLeft, Top, UpperRight]),
-            Tile::new(
-                Red,
-                format!("{}3", name),
-                vec![Track::hard_l(UpperLeft), Track::gentle_l(UpperLeft)],
-                vec![],
-                hex,
-            )
-            .with_offboard_faces([Top, UpperRight]),
-        ])
-        .collect()
+    vec![
+        Tile::new(
+            Red,
+            name,
+            vec![
+                Track::hard_r(UpperRight).with_span(0.0, 0.5),
+                Track::hard_r(UpperRight).with_span(0.5, 1.0),
+            ],
+            vec![
+                City::single_at_corner(revenues[0], &TopRight).with_fill(Red)
+            ],
+            hex,
+        )
+        .with_offboard_faces([Top])
+        .label(Label::MapLocation(name.to_string()), off_centre(S, 0.12))
+        .label(phase_revenue(revenues), above_bottom_face(None)),
+        Tile::new(
+            Red,
+            format!("{}2", name),
+            vec![
+                Track::gentle_r(LowerLeft),
+                Track::straight(LowerLeft),
+                Track::gentle_l(LowerLeft),
+                Track::hard_l(LowerLeft),
+            ],
+            vec![],
+            hex,
+        )
+        .with_offboard_faces([UpperLeft, Top, UpperRight]),
+        Tile::new(
+            Red,
+            format!("{}3", name),
+            vec![Track::hard_l(UpperLeft), Track::gentle_l(UpperLeft)],
+            vec![],
+            hex,
+        )
+        .with_offboard_faces([Top, UpperRight]),
+    ]
 }
 
-fn poland(hex: &Hex, suffixes: &[&str]) -> Vec<Tile> {
+fn poland(hex: &Hex) -> Vec<Tile> {
     use n18hex::{Direction::*, HexColour::*, HexFace::*};
     use n18tile::*;
 
     let name = "Poland";
     let revenues = [30, 40, 50, 70];
-    revenues
-        .iter()
-        .enumerate()
-        .map(|(ix, &revenue)| {
-            Tile::new(
-                Red,
-                format!("{} {}", name, suffixes[ix]),
-                vec![
-                    Track::hard_l(Top).with_span(0.0, 0.2),
-                    Track::hard_l(Top).with_span(0.3, 1.0),
-                    Track::gentle_l(Top).with_span(0.3, 1.0),
-                ],
-                vec![City::single_at_face(revenue, &Top).with_fill(Red)],
-                hex,
-            )
-            .with_offboard_faces([UpperRight, LowerRight])
-            .label(
-                Label::MapLocation(name.to_string()),
-                off_centre(S60W, 0.35),
-            )
-            .label(
-                Label::PhaseRevenue(vec![
-                    (Yellow, revenues[0], ix == 0),
-                    (Green, revenues[1], ix == 1),
-                    (Brown, revenues[2], ix == 2),
-                    (Grey, revenues[3], ix == 3),
-                ]),
-                above_bottom_face(0.15),
-            )
-        })
-        .chain(vec![
-            Tile::new(
-                Red,
-                format!("{}2", name),
-                vec![Track::gentle_r(Bottom), Track::hard_r(Bottom)],
-                vec![],
-                hex,
-            )
-            .with_offboard_faces([UpperRight, LowerRight]),
-            Tile::new(
-                Red,
-                format!("{}3", name),
-                vec![
-                    Track::straight(Bottom),
-                    Track::gentle_r(Bottom),
-                    Track::hard_r(Bottom),
-                ],
-                vec![],
-                hex,
-            )
-            .with_offboard_faces([UpperRight, LowerRight]),
-        ])
-        .collect()
+    vec![
+        Tile::new(
+            Red,
+            name,
+            vec![
+                Track::hard_l(Top).with_span(0.0, 0.2),
+                Track::hard_l(Top).with_span(0.3, 1.0),
+                Track::gentle_l(Top).with_span(0.3, 1.0),
+            ],
+            vec![City::single_at_face(revenues[0], &Top).with_fill(Red)],
+            hex,
+        )
+        .with_offboard_faces([UpperRight, LowerRight])
+        .label(Label::MapLocation(name.to_string()), off_centre(S60W, 0.35))
+        .label(phase_revenue(revenues), above_bottom_face(0.15)),
+        Tile::new(
+            Red,
+            format!("{}2", name),
+            vec![Track::gentle_r(Bottom), Track::hard_r(Bottom)],
+            vec![],
+            hex,
+        )
+        .with_offboard_faces([UpperRight, LowerRight]),
+        Tile::new(
+            Red,
+            format!("{}3", name),
+            vec![
+                Track::straight(Bottom),
+                Track::gentle_r(Bottom),
+                Track::hard_r(Bottom),
+            ],
+            vec![],
+            hex,
+        )
+        .with_offboard_faces([UpperRight, LowerRight]),
+    ]
 }
 
 fn miscellaneous_tiles(hex: &Hex) -> Vec<Tile> {
+    use n18hex::HexColour::*;
     use n18hex::*;
     use n18tile::*;
-    use HexColour::*;
     use HexCorner::*;
     use HexFace::*;
 
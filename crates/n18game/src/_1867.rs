@@ -5,13 +5,18 @@
 
 use std::collections::BTreeMap;
 
-use super::{Company, DividendKind, DividendOptions, Rounding};
+use super::{
+    Company, CompanyTier, DividendKind, DividendOptions, NationalFormation,
+    Rounding, TierRules, STANDARD_VARIANT,
+};
 use n18catalogue::{Builder, Catalogue, Kind};
 use n18hex::{
     Colour, Hex, HexColour, HexFace, HexPosition, Orientation, RotateCW,
 };
-use n18map::{Coordinates, FirstRow, HexAddress, Letters, Map};
-use n18route::{Bonus, ConflictRule, Train, TrainType};
+use n18map::{Coordinates, FirstRow, HexAddress, Letters, Map, TokenRule};
+use n18route::{
+    Bonus, ConflictRule, HighestStops, Scoring, Train, TrainType,
+};
 use n18tile::{Label, Tile};
 use n18token::{Token, TokenStyle};
 
@@ -21,6 +26,16 @@ const COORDS: Coordinates = Coordinates {
     letters: Letters::AsColumns,
 };
 
+/// The map variants: the standard map, and a short map that omits the
+/// northernmost hexes (rows 1 to 4), including Timmins.
+const VARIANTS: [&str; 2] = [STANDARD_VARIANT, "Short"];
+
+/// Returns `true` if a map hex is not included in the short map.
+fn omitted_from_short_map(addr: HexAddress) -> bool {
+    let (row, _col): (isize, isize) = (&addr).into();
+    row < 2
+}
+
 fn addrs() -> Vec<(isize, isize)> {
     vec![
         // Rows 1 and 2.
@@ -203,6 +218,16 @@ fn hex_labels() -> Vec<(HexAddress, Label)> {
     ]
 }
 
+/// Returns the colour of the tiles that are introduced in each game phase.
+fn phase_colour(phase: usize) -> HexColour {
+    match phase {
+        0 => HexColour::Yellow,
+        1 | 2 => HexColour::Green,
+        3 | 4 => HexColour::Brown,
+        _ => HexColour::Grey,
+    }
+}
+
 /// Defines the trains, tiles, and map for 1867: The Railways Of Canada.
 pub struct Game {
     companies: Vec<Company>,
@@ -211,6 +236,7 @@ pub struct Game {
     barriers: Vec<(HexAddress, HexFace)>,
     phase: usize,
     phase_names: Vec<&'static str>,
+    variant: usize,
 }
 
 impl Default for Game {
@@ -275,9 +301,8 @@ impl Game {
         // Background colours for minor (yellow) and major (green) companies.
         let bg_yellow = Colour::from((223, 223, 0));
         let bg_green = Colour::from((0, 153, 63));
-        let bg_iter = std::iter::repeat(bg_yellow)
-            .take(16)
-            .chain(std::iter::repeat(bg_green).take(8));
+        let bg_iter = std::iter::repeat_n(bg_yellow, 16)
+            .chain(std::iter::repeat_n(bg_green, 8));
 
         let fg_colours = vec![
             Colour::from((0, 204, 204)), // Aqua
@@ -288,7 +313,7 @@ impl Game {
         let fg_count = fg_colours.len();
         let fg_iter = fg_colours.into_iter().cycle();
 
-        let companies: Vec<Company> = bg_iter
+        let mut companies: Vec<Company> = bg_iter
             .zip(fg_iter)
             .enumerate()
             .map(|(ix, (bg, fg))| {
@@ -306,14 +331,34 @@ impl Game {
                     4 => TokenStyle::TopLines { bg, fg, text },
                     _ => TokenStyle::TopTriangles { bg, fg, text },
                 };
+                // The first 16 companies are minors, the rest are majors.
+                let tier = if ix < 16 {
+                    CompanyTier::Minor
+                } else {
+                    CompanyTier::Major
+                };
                 Company {
                     abbrev: company_names[ix].0.to_string(),
                     full_name: company_names[ix].1.to_string(),
                     token: Token::new(style),
+                    tier,
                 }
             })
             .collect();
 
+        // Add the Canadian National Railway as an extra company, which uses
+        // the colours of the Canadian flag.
+        companies.push(Company {
+            abbrev: "CN".to_string(),
+            full_name: "Canadian National Railway".to_string(),
+            token: Token::new(TokenStyle::TribandV {
+                sides: Colour::from((213, 43, 30)),
+                middle: Colour::WHITE,
+                text: Colour::BLACK,
+            }),
+            tier: CompanyTier::Major,
+        });
+
         let parse = |text| COORDS.parse(text);
         let barriers = vec![
             // The two ports.
@@ -344,8 +389,52 @@ impl Game {
             barriers,
             phase,
             phase_names,
+            variant: 0,
         }
     }
+
+    /// Returns `true` if the short map variant has been selected.
+    fn is_short_map(&self) -> bool {
+        VARIANTS[self.variant] != STANDARD_VARIANT
+    }
+
+    /// Returns `true` if a map hex is included in the current map variant.
+    fn includes_hex(&self, addr: HexAddress) -> bool {
+        !(self.is_short_map() && omitted_from_short_map(addr))
+    }
+
+    /// Returns the tiles that change with the game phase: the red off-board
+    /// cities, and Timmins (if it is part of the map).
+    fn phase_tiles(&self, phase: usize) -> Vec<(HexAddress, String)> {
+        let red_cities: Vec<(HexAddress, &str)> = vec![
+            ((3, 0).into(), "Sault Ste Marie"),
+            ((3, 15).into(), "Maritime Provinces"),
+            ((6, 14).into(), "Maine"),
+            ((7, 12).into(), "New England"),
+            ((8, 5).into(), "Buffalo"),
+            ((9, 0).into(), "Detroit"),
+        ];
+        let suffix = match phase_colour(phase) {
+            HexColour::Yellow => "Yw",
+            HexColour::Green => "Gn",
+            HexColour::Brown => "Bn",
+            _ => "Gy",
+        };
+        let mut tiles: Vec<(HexAddress, String)> = red_cities
+            .into_iter()
+            .map(|(addr, name)| (addr, format!("{} {}", name, suffix)))
+            .collect();
+        let timmins_addr: HexAddress = (0, 3).into();
+        if self.includes_hex(timmins_addr) {
+            let timmins_tile = if phase == 0 {
+                "Timmins Yw"
+            } else {
+                "Timmins Gr"
+            };
+            tiles.push((timmins_addr, timmins_tile.to_string()));
+        }
+        tiles
+    }
 }
 
 impl super::Game for Game {
@@ -369,10 +458,18 @@ impl super::Game for Game {
         &self.companies
     }
 
+    /// Returns how the Canadian National Railway is formed from the minor
+    /// companies.
+    fn national_formation(&self) -> Option<NationalFormation> {
+        // NOTE: the first 16 companies are minors, and the national company
+        // is the last company.
+        NationalFormation::from_minors(&self.companies, 16, "6")
+    }
+
     /// Returns the options available to a company for distributing dividends
     /// to shareholders.
     fn dividend_options(&self, abbrev: &str) -> Option<DividendOptions> {
-        self.companies.iter().enumerate().find_map(|(ix, company)| {
+        self.companies.iter().find_map(|company| {
             if company.abbrev == abbrev {
                 // NOTE: half-pay means that 50% of the revenue --- rounded up
                 // to the nearest 10 --- goes to shareholders.
@@ -380,16 +477,19 @@ impl super::Game for Game {
                     rounding: Rounding::Up,
                     nearest: 10,
                 };
-                // The first 16 companies are minors, the rest are majors.
-                let is_minor = ix < 16;
+                let is_minor = company.tier == CompanyTier::Minor;
+                let rounding = self.share_rounding();
 
+                // NOTE: minor companies must pay half of their revenue to
+                // their owner, and cannot withhold.
                 let share_count = if is_minor { 1 } else { 10 };
                 let dividend_options = if is_minor {
-                    vec![(half_pay, Rounding::Exact)]
+                    vec![(half_pay, rounding)]
                 } else {
                     vec![
-                        (DividendKind::Full, Rounding::Exact),
-                        (half_pay, Rounding::Exact),
+                        (DividendKind::Full, rounding),
+                        (half_pay, rounding),
+                        (DividendKind::Withhold, rounding),
                     ]
                 };
 
@@ -403,6 +503,24 @@ impl super::Game for Game {
         })
     }
 
+    /// A company may place at most one token in each city.
+    fn token_rule(&self) -> TokenRule {
+        TokenRule::OnePerCity
+    }
+
+    /// Minor companies have a single token and can operate at most two
+    /// trains, while the limits for major companies depend on the company
+    /// and on the game phase.
+    fn tier_rules(&self, tier: CompanyTier) -> TierRules {
+        match tier {
+            CompanyTier::Minor => TierRules {
+                token_count: Some(1),
+                train_limit: Some(2),
+            },
+            CompanyTier::Major => TierRules::default(),
+        }
+    }
+
     /// Returns the named train types in this game, in the order that they
     /// become available (where applicable).
     fn trains(&self) -> &[(&str, Train)] {
@@ -433,7 +551,10 @@ impl super::Game for Game {
             ],
             bonus: 40,
         };
-        let mut bonuses = vec![timmins];
+        let mut bonuses = vec![];
+        if !self.is_short_map() {
+            bonuses.push(timmins);
+        }
         if bonus_options.len() == 4 {
             if bonus_options[0] {
                 // Buffalo
@@ -473,6 +594,12 @@ impl super::Game for Game {
         bonuses
     }
 
+    /// The 5+5E train can visit any number of locations, but only earns
+    /// revenue from the five highest-value locations (which is doubled).
+    fn scoring(&self) -> Box<dyn Scoring> {
+        Box::new(HighestStops::new([*self.train("5+5E")]))
+    }
+
     /// Defines the elements that cannot be shared in a single route.
     ///
     /// A single route cannot reuse any track segment, any revenue centre
@@ -491,8 +618,11 @@ impl super::Game for Game {
     /// Create the initial map for 1867.
     fn create_map(&self, _hex: &Hex) -> Map {
         let tokens = self.create_tokens();
-        let hexes: Vec<HexAddress> =
-            addrs().iter().map(|coords| coords.into()).collect();
+        let hexes: Vec<HexAddress> = addrs()
+            .iter()
+            .map(|coords| coords.into())
+            .filter(|addr| self.includes_hex(*addr))
+            .collect();
         let mut map = Map::new(
             self.catalogue.clone(),
             tokens,
@@ -500,6 +630,9 @@ impl super::Game for Game {
             self.hex_orientation(),
         );
         for (addr, (tile_name, rotn)) in initial_tiles() {
+            if !self.includes_hex(addr) {
+                continue;
+            }
             if !map.place_tile(addr, tile_name, rotn) {
                 println!("Could not place tile {} at {}", tile_name, addr)
             }
@@ -508,12 +641,29 @@ impl super::Game for Game {
             map.add_label_at(addr, label);
         }
         for (addr, face) in &self.barriers {
-            map.add_barrier(*addr, *face)
+            if self.includes_hex(*addr) {
+                map.add_barrier(*addr, *face)
+            }
         }
+        map.set_phase_colour(phase_colour(self.phase));
+        map.set_revenue_phase(phase_colour(self.phase));
         // TODO: mark tiles that are not modifiable.
+        map.record_initial_tiles();
         map
     }
 
+    /// Returns the starting tiles, and the tiles that are placed for each
+    /// game phase.
+    fn placed_tiles(&self) -> Vec<(HexAddress, String)> {
+        let starting = initial_tiles()
+            .into_iter()
+            .filter(|(addr, _tile)| self.includes_hex(*addr))
+            .map(|(addr, (name, _rotn))| (addr, name.to_string()));
+        let phases = (0..self.phase_names.len())
+            .flat_map(|phase| self.phase_tiles(phase));
+        starting.chain(phases).collect()
+    }
+
     /// Returns all game tiles, including special tiles that players cannot
     /// place on the map.
     fn catalogue(&self) -> &Catalogue {
@@ -531,38 +681,13 @@ impl super::Game for Game {
             return false;
         }
         self.phase = phase;
-        let red_cities: Vec<(HexAddress, &str)> = vec![
-            ((3, 0).into(), "Sault Ste Marie"),
-            ((3, 15).into(), "Maritime Provinces"),
-            ((6, 14).into(), "Maine"),
-            ((7, 12).into(), "New England"),
-            ((8, 5).into(), "Buffalo"),
-            ((9, 0).into(), "Detroit"),
-        ];
-        let suffix = match phase {
-            0 => "Yw",
-            1 | 2 => "Gn",
-            3 | 4 => "Bn",
-            _ => "Gy",
-        };
-        for (addr, city_name) in &red_cities {
-            let tile_name = format!("{} {}", city_name, suffix);
-            if !map.place_tile(*addr, &tile_name, RotateCW::Zero) {
+        map.set_phase_colour(phase_colour(phase));
+        map.set_revenue_phase(phase_colour(phase));
+        for (addr, tile_name) in self.phase_tiles(phase) {
+            if !map.place_tile(addr, &tile_name, RotateCW::Zero) {
                 println!("Could not place tile {} at {}", tile_name, addr)
             }
         }
-        let timmins_addr: HexAddress = (0, 3).into();
-        let timmins_tile = if phase == 0 {
-            "Timmins Yw"
-        } else {
-            "Timmins Gr"
-        };
-        if !map.place_tile(timmins_addr, timmins_tile, RotateCW::Zero) {
-            println!(
-                "Could not place tile {} at {}",
-                timmins_tile, timmins_addr
-            )
-        }
         true
     }
 
@@ -570,6 +695,32 @@ impl super::Game for Game {
     fn phase_names(&self) -> &[&str] {
         &self.phase_names
     }
+
+    /// Returns the tile colours that can be placed in each game phase.
+    fn phase_tile_colours(&self, phase: usize) -> Vec<HexColour> {
+        super::tile_colours_up_to(phase_colour(phase))
+    }
+
+    /// Returns the name of each map variant.
+    fn variants(&self) -> &[&str] {
+        &VARIANTS
+    }
+
+    /// Returns the name of the current map variant.
+    fn variant(&self) -> &str {
+        VARIANTS[self.variant]
+    }
+
+    /// Selects the map variant that will be used by
+    /// [create_map](super::Game::create_map).
+    fn set_variant(&mut self, name: &str) -> bool {
+        if let Some(ix) = VARIANTS.iter().position(|v| *v == name) {
+            self.variant = ix;
+            true
+        } else {
+            false
+        }
+    }
 }
 
 /// Returns the tiles that are available to players at the start of the game.
@@ -670,7 +821,7 @@ fn make_catalogue() -> Catalogue {
     builder.add_unavailable_tiles(city_tiles);
     builder.add_unavailable_tiles(offb_tiles);
     builder.add_unavailable_tiles(misc_tiles);
-    builder.build()
+    builder.try_build().expect("Duplicate tile names")
 }
 
 /// Position labels relative to the tile centre.
@@ -691,9 +842,9 @@ fn above_bottom_face<F: Into<Option<f64>>>(pos: F) -> HexPosition {
 
 /// Tiles that are specific to 1867 and which cannot be placed by the player.
 fn starting_city_tiles(hex: &Hex) -> Vec<Tile> {
+    use n18hex::HexColour::*;
     use n18hex::{Direction::*, *};
     use n18tile::*;
-    use HexColour::*;
     use HexCorner::*;
     use HexFace::*;
 
@@ -765,7 +916,7 @@ fn starting_city_tiles(hex: &Hex) -> Vec<Tile> {
             Track::straight(LowerRight).with_span(0.0, 0.5),
             Track::straight(UpperRight).with_span(0.0, 0.5),
         ],
-        vec![City::single(40).with_fill(HexColour::Green)],
+        vec![City::single(40).with_available_from(HexColour::Green)],
         hex,
     )
     .label(Label::Revenue(0), BottomRight.to_centre(0.1))
@@ -815,10 +966,10 @@ fn starting_city_tiles(hex: &Hex) -> Vec<Tile> {
 
 /// Tiles that are specific to 1867 and which cannot be placed by the player.
 fn starting_town_tiles(hex: &Hex) -> Vec<Tile> {
+    use n18hex::HexColour::*;
     use n18hex::{Direction::*, *};
     use n18tile::DitShape::*;
     use n18tile::*;
-    use HexColour::*;
     use HexFace::*;
     use TrackEnd::*;
 
@@ -1062,10 +1213,10 @@ fn detroit(hex: &Hex, suffixes: &[&str]) -> Vec<Tile> {
 
 /// Tiles that are specific to 1867 and which cannot be placed by the player.
 fn offboard_tiles(hex: &Hex) -> Vec<Tile> {
+    use n18hex::HexColour::*;
     use n18hex::*;
     use n18tile::DitShape::*;
     use n18tile::*;
-    use HexColour::*;
     use HexFace::*;
     use TrackEnd::*;
 
@@ -1108,9 +1259,9 @@ fn offboard_tiles(hex: &Hex) -> Vec<Tile> {
 }
 
 fn miscellaneous_tiles(hex: &Hex) -> Vec<Tile> {
+    use n18hex::HexColour::*;
     use n18hex::*;
     use n18tile::*;
-    use HexColour::*;
     use HexFace::*;
 
     vec![
@@ -3,10 +3,10 @@
 //! Initial version of 1889 map and tiles.
 //!
 
-use super::{Company, DividendKind, DividendOptions, Rounding};
+use super::{Company, CompanyTier, DividendKind, DividendOptions};
 use n18catalogue::Catalogue;
-use n18hex::{self, Colour, Hex, Orientation, RotateCW};
-use n18map::{Coordinates, FirstRow, HexAddress, Letters, Map};
+use n18hex::{self, Colour, Hex, HexColour, Orientation, RotateCW};
+use n18map::{Coordinates, FirstRow, HexAddress, Letters, Map, TokenRule};
 use n18route::{Bonus, ConflictRule, Train, TrainType};
 use n18tile::Label;
 use n18token::{Token, TokenStyle};
@@ -29,6 +29,15 @@ mod locns;
 #[doc(inline)]
 pub use locns::Location;
 
+/// Returns the game phase (identified by its tile colour) that determines
+/// the revenue of each off-board location in the specified game phase.
+fn offboard_phase(phase: usize) -> HexColour {
+    match phase {
+        0..=2 => HexColour::Yellow,
+        _ => HexColour::Brown,
+    }
+}
+
 /// Defines the trains, tiles, and map for 1889: History of Shikoku Railways
 /// (Shikoku 1889).
 ///
@@ -118,6 +127,7 @@ impl Game {
             abbrev: abbrev.to_string(),
             full_name: full_name.to_string(),
             token: Token::new(TokenStyle::TopArcs { bg, fg, text }),
+            tier: CompanyTier::Major,
         })
         .collect();
 
@@ -164,17 +174,35 @@ impl super::Game for Game {
 
     /// Returns the options available to a company for distributing dividends
     /// to shareholders.
+    ///
+    /// A company either pays its full revenue to shareholders, or withholds
+    /// all of its revenue.
     fn dividend_options(&self, _abbrev: &str) -> Option<DividendOptions> {
+        let rounding = self.share_rounding();
         Some(DividendOptions {
             share_count: 10,
-            dividend_options: vec![(DividendKind::Full, Rounding::Exact)],
+            dividend_options: vec![
+                (DividendKind::Full, rounding),
+                (DividendKind::Withhold, rounding),
+            ],
         })
     }
 
+    /// A company may place at most one token in each city.
+    fn token_rule(&self) -> TokenRule {
+        TokenRule::OnePerCity
+    }
+
     fn trains(&self) -> &[(&str, Train)] {
         &self.trains
     }
 
+    /// The bank holds six 2-trains, five 3-trains, four 4-trains, three
+    /// 5-trains, two 6-trains, and an unlimited number of diesels.
+    fn train_counts(&self) -> Vec<Option<usize>> {
+        vec![Some(6), Some(5), Some(4), Some(3), Some(2), None]
+    }
+
     /// This game does not include any route bonuses.
     fn bonus_options(&self) -> Vec<&'static str> {
         vec![]
@@ -261,9 +289,21 @@ impl super::Game for Game {
             Label::City("T".to_string()),
         );
 
+        map.set_revenue_phase(offboard_phase(self.phase));
+        map.record_initial_tiles();
         map
     }
 
+    /// Returns the starting tiles.
+    fn placed_tiles(&self) -> Vec<(HexAddress, String)> {
+        initial_map()
+            .into_iter()
+            .filter_map(|(addr, tile)| {
+                tile.map(|(name, _rotn)| (addr, name.to_string()))
+            })
+            .collect()
+    }
+
     /// Returns all game tiles, including special tiles that players cannot
     /// place on the map.
     fn catalogue(&self) -> &Catalogue {
@@ -281,30 +321,7 @@ impl super::Game for Game {
             return false;
         }
         self.phase = phase;
-
-        let suffix = match phase {
-            0..=2 => "Yw",
-            _ => "Bn",
-        };
-
-        let offboard_locns = vec![
-            Location::Imabari,
-            Location::SakaideAndOkoyama,
-            Location::NarutoAndAwaji,
-        ];
-
-        for locn in offboard_locns {
-            let locn_addr = locn.address();
-            let locn_name = locn.as_str();
-            let tile_name = format!("{}_{}", locn_name, suffix);
-            if !map.place_tile(locn_addr, &tile_name, RotateCW::Zero) {
-                println!(
-                    "Could not place tile {} at {}",
-                    tile_name, locn_addr
-                )
-            }
-        }
-
+        map.set_revenue_phase(offboard_phase(phase));
         true
     }
 
@@ -312,6 +329,27 @@ impl super::Game for Game {
     fn phase_names(&self) -> &[&str] {
         &self.phase_names
     }
+
+    /// Returns the tile colours that can be placed in each game phase.
+    fn phase_tile_colours(&self, phase: usize) -> Vec<HexColour> {
+        let last = match phase {
+            0 => HexColour::Yellow,
+            1 | 2 => HexColour::Green,
+            3 | 4 => HexColour::Brown,
+            _ => HexColour::Grey,
+        };
+        super::tile_colours_up_to(last)
+    }
+
+    /// Companies can operate at most four trains in phases 2 and 3, three
+    /// trains in phase 4, and two trains from phase 5 onwards.
+    fn phase_train_limit(&self, phase: usize) -> Option<usize> {
+        match phase {
+            0 | 1 => Some(4),
+            2 => Some(3),
+            _ => Some(2),
+        }
+    }
 }
 
 /// Returns the address of each map hex, and the tile that should be placed
@@ -338,7 +376,7 @@ pub fn initial_map() -> Vec<(HexAddress, Option<(&'static str, RotateCW)>)> {
         ("E4", Some(("Mountain", Zero))),
         ("E6", Some(("Mountain", Zero))),
         ("E8", None),
-        ("F1", Some(("Imabari_Yw", Zero))),
+        ("F1", Some(("Imabari", Zero))),
         ("F3", Some(("Saijou", Zero))),
         ("F5", Some(("Mountain", Zero))),
         ("F7", Some(("Mountain", Zero))),
@@ -361,7 +399,7 @@ pub fn initial_map() -> Vec<(HexAddress, Option<(&'static str, RotateCW)>)> {
         ("I8", None),
         ("I10", None),
         ("I12", Some(("Muki", Zero))),
-        ("J1", Some(("Sakaide & Okoyama_Yw", Zero))),
+        ("J1", Some(("Sakaide & Okoyama", Zero))),
         ("J3", None),
         ("J5", Some(("Ritsurin Kouen", Zero))),
         ("J7", Some(("Grey_Gentle", Zero))),
@@ -370,7 +408,7 @@ pub fn initial_map() -> Vec<(HexAddress, Option<(&'static str, RotateCW)>)> {
         ("K4", Some(("Takamatsu", Zero))),
         ("K6", Some(("River", Zero))),
         ("K8", Some(("Tokushima", Zero))),
-        ("L7", Some(("Naruto & Awaji_Yw", Zero))),
+        ("L7", Some(("Naruto & Awaji", Zero))),
     ]
     .into_iter()
     .map(|(addr, tile_opt)| (COORDS.parse(addr).unwrap(), tile_opt))
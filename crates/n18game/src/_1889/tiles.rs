@@ -17,7 +17,7 @@ pub fn catalogue() -> Catalogue {
     let starting_tiles = initial_tiles(hex);
     builder.add_unavailable_tiles(offboard_tiles);
     builder.add_unavailable_tiles(starting_tiles);
-    builder.build()
+    builder.try_build().expect("Duplicate tile names")
 }
 
 /// Returns the tiles that are available to players at the start of the game.
@@ -264,110 +264,86 @@ pub fn initial_tiles(hex: &Hex) -> Vec<Tile> {
 }
 
 /// Returns the tiles for each off-board location.
+///
+/// Note that the phase revenue labels highlight the revenue for the current
+/// game phase (see [n18map::Map::set_revenue_phase]).
 pub fn offboard_tiles(hex: &Hex) -> Vec<Tile> {
-    let suffixes = ["Yw", "Bn"];
-
-    let imabari: Vec<Tile> = suffixes
-        .iter()
-        .map(|suffix| {
-            let locn_name = Location::Imabari.as_str();
-            let tile_name = format!("{}_{}", locn_name, suffix);
-            let is_yellow = *suffix == "Yw";
-            let revenue = if is_yellow { 30 } else { 60 };
-            Tile::new(
-                HexColour::Red,
-                tile_name,
-                vec![
-                    Track::mid(HexFace::LowerLeft),
-                    Track::mid(HexFace::Bottom),
-                ],
-                vec![City::single(revenue)],
-                hex,
-            )
-            .label(
-                Label::PhaseRevenueVert(vec![
-                    (HexColour::Yellow, 30, is_yellow),
-                    (HexColour::Brown, 60, !is_yellow),
-                    (HexColour::Grey, 100, false),
-                ]),
-                HexCorner::Right.to_centre(0.35),
-            )
-            .label(
-                Label::MapLocation(locn_name.to_string()),
-                HexFace::Top.to_centre(0.1),
-            )
-            .with_offboard_faces([HexFace::Bottom, HexFace::LowerLeft])
-        })
-        .collect();
+    let imabari = {
+        let locn_name = Location::Imabari.as_str();
+        Tile::new(
+            HexColour::Red,
+            locn_name,
+            vec![Track::mid(HexFace::LowerLeft), Track::mid(HexFace::Bottom)],
+            vec![City::single(30)],
+            hex,
+        )
+        .label(
+            Label::PhaseRevenueVert(vec![
+                (HexColour::Yellow, 30, true),
+                (HexColour::Brown, 60, false),
+                (HexColour::Grey, 100, false),
+            ]),
+            HexCorner::Right.to_centre(0.35),
+        )
+        .label(
+            Label::MapLocation(locn_name.to_string()),
+            HexFace::Top.to_centre(0.1),
+        )
+        .with_offboard_faces([HexFace::Bottom, HexFace::LowerLeft])
+    };
 
-    let sakaide: Vec<Tile> = suffixes
-        .iter()
-        .map(|suffix| {
-            let locn_name = Location::SakaideAndOkoyama.as_str();
-            let tile_name = format!("{}_{}", locn_name, suffix);
-            let is_yellow = *suffix == "Yw";
-            let revenue = if is_yellow { 20 } else { 40 };
-            Tile::new(
-                HexColour::Red,
-                tile_name,
-                vec![
-                    Track::mid(HexFace::LowerLeft),
-                    Track::mid(HexFace::Bottom),
-                ],
-                vec![City::single(revenue)],
-                hex,
-            )
-            .label(
-                Label::PhaseRevenueVert(vec![
-                    (HexColour::Yellow, 20, is_yellow),
-                    (HexColour::Brown, 40, !is_yellow),
-                    (HexColour::Grey, 80, false),
-                ]),
-                HexCorner::Right.in_dir(S60W, 0.5),
-            )
-            .label(
-                Label::MapLocation(locn_name.to_string()),
-                HexFace::Top.to_centre(0.1),
-            )
-            .with_offboard_faces([HexFace::Bottom, HexFace::LowerLeft])
-        })
-        .collect();
+    let sakaide = {
+        let locn_name = Location::SakaideAndOkoyama.as_str();
+        Tile::new(
+            HexColour::Red,
+            locn_name,
+            vec![Track::mid(HexFace::LowerLeft), Track::mid(HexFace::Bottom)],
+            vec![City::single(20)],
+            hex,
+        )
+        .label(
+            Label::PhaseRevenueVert(vec![
+                (HexColour::Yellow, 20, true),
+                (HexColour::Brown, 40, false),
+                (HexColour::Grey, 80, false),
+            ]),
+            HexCorner::Right.in_dir(S60W, 0.5),
+        )
+        .label(
+            Label::MapLocation(locn_name.to_string()),
+            HexFace::Top.to_centre(0.1),
+        )
+        .with_offboard_faces([HexFace::Bottom, HexFace::LowerLeft])
+    };
 
-    let naruto: Vec<Tile> = suffixes
-        .iter()
-        .map(|suffix| {
-            let locn_name = Location::NarutoAndAwaji.as_str();
-            let tile_name = format!("{}_{}", locn_name, suffix);
-            let is_yellow = *suffix == "Yw";
-            let revenue = if is_yellow { 20 } else { 40 };
-            Tile::new(
-                HexColour::Red,
-                tile_name,
-                vec![
-                    Track::mid(HexFace::LowerLeft),
-                    Track::mid(HexFace::UpperLeft),
-                ],
-                vec![City::single(revenue)],
-                hex,
-            )
-            .label(
-                Label::PhaseRevenueVert(vec![
-                    (HexColour::Yellow, 20, is_yellow),
-                    (HexColour::Brown, 40, !is_yellow),
-                    (HexColour::Grey, 80, false),
-                ]),
-                HexFace::Bottom.to_centre(0.1),
-            )
-            .label(
-                Label::MapLocation(locn_name.to_string()),
-                HexFace::Top.to_centre(0.1),
-            )
-            .with_offboard_faces([HexFace::UpperLeft, HexFace::LowerLeft])
-        })
-        .collect();
+    let naruto = {
+        let locn_name = Location::NarutoAndAwaji.as_str();
+        Tile::new(
+            HexColour::Red,
+            locn_name,
+            vec![
+                Track::mid(HexFace::LowerLeft),
+                Track::mid(HexFace::UpperLeft),
+            ],
+            vec![City::single(20)],
+            hex,
+        )
+        .label(
+            Label::PhaseRevenueVert(vec![
+                (HexColour::Yellow, 20, true),
+                (HexColour::Brown, 40, false),
+                (HexColour::Grey, 80, false),
+            ]),
+            HexFace::Bottom.to_centre(0.1),
+        )
+        .label(
+            Label::MapLocation(locn_name.to_string()),
+            HexFace::Top.to_centre(0.1),
+        )
+        .with_offboard_faces([HexFace::UpperLeft, HexFace::LowerLeft])
+    };
 
-    [imabari, sakaide, naruto]
-        .concat()
+    vec![imabari, sakaide, naruto]
         .into_iter()
         .map(|tile| tile.hide_tile_name())
         .collect()
@@ -0,0 +1,205 @@
+//! Evaluate where a company could place its next token.
+//!
+//! [rank_token_spaces] considers each empty token space in which a company
+//! is allowed to place a token, finds the routes that the company's trains
+//! could operate if the token was placed there, and ranks these token spaces
+//! by the best revenue that the trains could earn:
+//!
+//! ```rust
+//! # use n18game::Game;
+//! # use n18hex::Hex;
+//! # use n18route::Trains;
+//! let game = n18game::new_1830();
+//! let map = game.create_map(&Hex::default());
+//! let token = *game.try_token("PRR").unwrap();
+//! let trains: Trains = vec![*game.try_train("2").unwrap()].into();
+//! let ranked = n18game::analysis::rank_token_spaces(
+//!     &game,
+//!     &map,
+//!     token,
+//!     &trains,
+//!     vec![],
+//! );
+//! assert!(!ranked.is_empty());
+//! // The token spaces are ranked from most to least valuable.
+//! assert!(ranked.windows(2).all(|w| w[0].revenue >= w[1].revenue));
+//! ```
+//!
+//! Because this runs the route optimiser once for every candidate token
+//! space, [rank_token_spaces_closure] returns a closure that can be run in a
+//! separate thread.
+
+use n18map::{HexAddress, Map};
+use n18route::Trains;
+use n18tile::TokenSpace;
+use n18token::Token;
+
+use crate::Game;
+
+/// The best revenue that a company could earn if it placed a token in a
+/// specific token space.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenValue {
+    /// The map hex that contains the token space.
+    pub addr: HexAddress,
+    /// The token space.
+    pub space: TokenSpace,
+    /// The net revenue of the best routes for the company's trains, or zero
+    /// if the trains could not operate any routes.
+    pub revenue: usize,
+}
+
+/// Returns each empty token space in which `token` can be placed, according
+/// to the game's [TokenRule](n18map::TokenRule) and the current game phase.
+///
+/// This does not check whether the company has any tokens left to place.
+pub fn candidate_token_spaces<G: Game + ?Sized>(
+    game: &G,
+    map: &Map,
+    token: &Token,
+) -> Vec<(HexAddress, TokenSpace)> {
+    let rule = game.token_rule();
+    let mut candidates = vec![];
+    for addr in map.hex_address_iter() {
+        let (Some(tile), Some(hex_state)) =
+            (map.tile_at(*addr), map.hex_state(*addr))
+        else {
+            continue;
+        };
+        for space in tile.token_spaces() {
+            if hex_state.token_at(&space).is_some() {
+                continue;
+            }
+            if map
+                .check_token_placement(*addr, &space, token, rule)
+                .is_ok()
+            {
+                candidates.push((*addr, space))
+            }
+        }
+    }
+    candidates
+}
+
+/// Returns a closure that ranks each candidate token space (see
+/// [candidate_token_spaces]) by the best revenue that `trains` could earn if
+/// `token` was placed in that space, from highest to lowest revenue.
+///
+/// Token spaces that earn the same revenue are listed in map order.
+///
+/// The returned closure implements `Send`, so it can be sent to another
+/// thread.
+pub fn rank_token_spaces_closure<G: Game + ?Sized>(
+    game: &G,
+    map: Map,
+    token: Token,
+    trains: Trains,
+    bonus_options: Vec<bool>,
+) -> Box<dyn FnOnce() -> Vec<TokenValue> + Send> {
+    let rule = game.token_rule();
+    let searches: Vec<_> = candidate_token_spaces(game, &map, &token)
+        .into_iter()
+        .map(|(addr, space)| {
+            let mut candidate_map = map.clone();
+            // NOTE: this cannot fail, because the candidates are valid.
+            let _ = candidate_map.try_place_token(addr, &space, token, rule);
+            let search_fn = game.best_routes_closure(
+                candidate_map,
+                token,
+                trains.clone(),
+                bonus_options.clone(),
+            );
+            (addr, space, search_fn)
+        })
+        .collect();
+    Box::new(move || {
+        let mut values: Vec<TokenValue> = searches
+            .into_iter()
+            .map(|(addr, space, search_fn)| TokenValue {
+                addr,
+                space,
+                revenue: search_fn()
+                    .map(|routes| routes.net_revenue)
+                    .unwrap_or(0),
+            })
+            .collect();
+        values.sort_by_key(|value| std::cmp::Reverse(value.revenue));
+        values
+    })
+}
+
+/// Ranks each candidate token space (see [candidate_token_spaces]) by the
+/// best revenue that `trains` could earn if `token` was placed in that
+/// space, from highest to lowest revenue.
+///
+/// See [rank_token_spaces_closure] to perform this evaluation in a separate
+/// thread.
+pub fn rank_token_spaces<G: Game + ?Sized>(
+    game: &G,
+    map: &Map,
+    token: Token,
+    trains: &Trains,
+    bonus_options: Vec<bool>,
+) -> Vec<TokenValue> {
+    rank_token_spaces_closure(
+        game,
+        map.clone(),
+        token,
+        trains.clone(),
+        bonus_options,
+    )()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{candidate_token_spaces, rank_token_spaces};
+    use crate::Game;
+    use n18hex::Hex;
+    use n18route::Trains;
+
+    #[test]
+    fn test_candidates_exclude_occupied_spaces() {
+        let game = crate::new_1830();
+        let mut map = game.create_map(&Hex::default());
+        let prr = *game.try_token("PRR").unwrap();
+        let nyc = *game.try_token("NYC").unwrap();
+        let candidates = candidate_token_spaces(&game, &map, &prr);
+        assert!(!candidates.is_empty());
+        let (addr, space) = candidates[0];
+        let placed =
+            map.try_place_token(addr, &space, nyc, game.token_rule());
+        assert!(placed.is_ok());
+        let remaining = candidate_token_spaces(&game, &map, &prr);
+        assert_eq!(remaining.len(), candidates.len() - 1);
+        assert!(!remaining.contains(&(addr, space)));
+    }
+
+    #[test]
+    fn test_rank_token_spaces() {
+        let game = crate::new_1830();
+        let map = game.create_map(&Hex::default());
+        let token = *game.try_token("PRR").unwrap();
+        let trains: Trains = vec![*game.try_train("2").unwrap()].into();
+        let ranked = rank_token_spaces(&game, &map, token, &trains, vec![]);
+        let candidates = candidate_token_spaces(&game, &map, &token);
+        assert_eq!(ranked.len(), candidates.len());
+        assert!(ranked.windows(2).all(|w| w[0].revenue >= w[1].revenue));
+        assert!(ranked[0].revenue > 0);
+
+        // Check that the revenue for the best token space matches the best
+        // routes when a token is placed in that space.
+        let best = ranked[0];
+        let mut token_map = map.clone();
+        let placed = token_map.try_place_token(
+            best.addr,
+            &best.space,
+            token,
+            game.token_rule(),
+        );
+        assert!(placed.is_ok());
+        let routes = game
+            .best_routes(&token_map, token, &trains, vec![])
+            .unwrap();
+        assert_eq!(routes.net_revenue, best.revenue);
+    }
+}
@@ -0,0 +1,430 @@
+//! Track the trains that the bank holds and the trains that are available in
+//! the open market.
+//!
+//! Trains are sold by the bank in the order that they become available (see
+//! [Game::trains](crate::Game::trains)), and the first train of each type
+//! may begin a new game phase.
+//! Trains that companies discard are placed in the open market, from which
+//! any train can be bought.
+//!
+//! ```rust
+//! # use n18game::bank::{TrainStock, TrainSupply, TrainTransfer};
+//! let mut supply = TrainSupply::from(vec![
+//!     TrainStock::new("2", Some(1), None),
+//!     TrainStock::new("3", Some(2), Some(1)),
+//! ]);
+//! let buy_2 = TrainTransfer::FromBank("2".to_string());
+//! let buy_3 = TrainTransfer::FromBank("3".to_string());
+//! // The 3-trains are not available until every 2-train has been sold.
+//! assert!(supply.apply(&buy_3, 0).is_err());
+//! assert_eq!(supply.apply(&buy_2, 0), Ok(None));
+//! // The first 3-train begins the second game phase.
+//! assert_eq!(supply.apply(&buy_3, 0), Ok(Some(1)));
+//! assert_eq!(supply.apply(&buy_3, 1), Ok(None));
+//! assert_eq!(supply.bank_count("3"), Some(Some(0)));
+//! ```
+
+/// The number of trains of a single type that are held by the bank and are
+/// available in the open market.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrainStock {
+    /// The name of the train type.
+    pub name: String,
+    /// The number of trains held by the bank, where `None` indicates an
+    /// unlimited number of trains.
+    pub bank: Option<usize>,
+    /// The number of trains available in the open market.
+    pub pool: usize,
+    /// The number of trains that the bank has sold.
+    pub sold: usize,
+    /// The index of the game phase (if any) that begins when the first train
+    /// of this type is sold.
+    pub phase: Option<usize>,
+}
+
+impl TrainStock {
+    /// Returns the initial stock for a train type, before any trains have
+    /// been sold.
+    pub fn new(
+        name: &str,
+        bank: Option<usize>,
+        phase: Option<usize>,
+    ) -> Self {
+        TrainStock {
+            name: name.to_string(),
+            bank,
+            pool: 0,
+            sold: 0,
+            phase,
+        }
+    }
+
+    /// Returns `true` if the bank has no more trains of this type.
+    pub fn is_sold_out(&self) -> bool {
+        self.bank == Some(0)
+    }
+}
+
+/// The ways in which trains can move between the bank, the open market, and
+/// the companies.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TrainTransfer {
+    /// A company buys a new train from the bank.
+    FromBank(String),
+    /// A company buys a train from the open market.
+    FromPool(String),
+    /// A company discards a train to the open market.
+    ToPool(String),
+}
+
+impl TrainTransfer {
+    /// Returns the name of the train type.
+    pub fn train(&self) -> &str {
+        use TrainTransfer::*;
+        match self {
+            FromBank(name) | FromPool(name) | ToPool(name) => name,
+        }
+    }
+}
+
+impl std::fmt::Display for TrainTransfer {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> Result<(), std::fmt::Error> {
+        use TrainTransfer::*;
+        match self {
+            FromBank(name) => write!(f, "Buy a {}-train from the bank", name),
+            FromPool(name) => {
+                write!(f, "Buy a {}-train from the open market", name)
+            }
+            ToPool(name) => {
+                write!(f, "Discard a {}-train to the open market", name)
+            }
+        }
+    }
+}
+
+/// The reasons why a train transfer is not allowed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransferError {
+    /// There is no train type with this name.
+    UnknownTrain(String),
+    /// The bank has no more trains of this type.
+    SoldOut(String),
+    /// The bank is still selling an earlier train type.
+    NotAvailable(String),
+    /// There are no trains of this type in the open market.
+    NotInPool(String),
+    /// No company owns a train of this type.
+    NotOwned(String),
+}
+
+impl std::fmt::Display for TransferError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> Result<(), std::fmt::Error> {
+        use TransferError::*;
+        match self {
+            UnknownTrain(name) => write!(f, "There is no {}-train", name),
+            SoldOut(name) => {
+                write!(f, "The bank has no more {}-trains", name)
+            }
+            NotAvailable(name) => {
+                write!(f, "The {}-trains are not yet available", name)
+            }
+            NotInPool(name) => {
+                write!(f, "There are no {}-trains in the open market", name)
+            }
+            NotOwned(name) => write!(f, "No company owns a {}-train", name),
+        }
+    }
+}
+
+impl std::error::Error for TransferError {}
+
+/// The trains held by the bank and available in the open market.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrainSupply {
+    stocks: Vec<TrainStock>,
+}
+
+impl TrainSupply {
+    /// Returns the stock of each train type, in the order that they become
+    /// available.
+    pub fn stocks(&self) -> &[TrainStock] {
+        &self.stocks
+    }
+
+    /// Returns `true` if there are no train types.
+    pub fn is_empty(&self) -> bool {
+        self.stocks.is_empty()
+    }
+
+    /// Returns the stock for the named train type, if it exists.
+    pub fn stock(&self, name: &str) -> Option<&TrainStock> {
+        self.stocks.iter().find(|stock| stock.name == name)
+    }
+
+    /// Returns the number of trains of the named type that are held by the
+    /// bank, where `Some(None)` indicates an unlimited number of trains.
+    ///
+    /// Returns `None` if there is no train type with this name.
+    pub fn bank_count(&self, name: &str) -> Option<Option<usize>> {
+        self.stock(name).map(|stock| stock.bank)
+    }
+
+    /// Returns the number of trains of the named type that are available in
+    /// the open market.
+    ///
+    /// Returns `None` if there is no train type with this name.
+    pub fn pool_count(&self, name: &str) -> Option<usize> {
+        self.stock(name).map(|stock| stock.pool)
+    }
+
+    /// Returns the train types that the bank is currently selling.
+    ///
+    /// A train type is available once every earlier train type with a
+    /// limited number of trains has been sold out.
+    pub fn available(&self) -> Vec<&TrainStock> {
+        (0..self.stocks.len())
+            .filter(|ix| self.is_available(*ix))
+            .map(|ix| &self.stocks[ix])
+            .collect()
+    }
+
+    fn is_available(&self, ix: usize) -> bool {
+        !self.stocks[ix].is_sold_out()
+            && self.stocks[..ix]
+                .iter()
+                .all(|stock| stock.is_sold_out() || stock.bank.is_none())
+    }
+
+    /// Returns each transfer that is currently allowed.
+    pub fn transfers(&self) -> Vec<TrainTransfer> {
+        let from_bank = self
+            .available()
+            .into_iter()
+            .map(|stock| TrainTransfer::FromBank(stock.name.clone()));
+        let from_pool = self
+            .stocks
+            .iter()
+            .filter(|stock| stock.pool > 0)
+            .map(|stock| TrainTransfer::FromPool(stock.name.clone()));
+        let to_pool = self
+            .stocks
+            .iter()
+            .filter(|stock| stock.sold > stock.pool)
+            .map(|stock| TrainTransfer::ToPool(stock.name.clone()));
+        from_bank.chain(from_pool).chain(to_pool).collect()
+    }
+
+    /// Records a train transfer, where `phase_ix` is the index of the
+    /// current game phase.
+    ///
+    /// If this is the first train of its type to be sold by the bank and it
+    /// begins a later game phase, returns the index of the new game phase.
+    /// The caller is responsible for changing the game phase.
+    ///
+    /// If the transfer is not allowed, the supply is not modified.
+    pub fn apply(
+        &mut self,
+        transfer: &TrainTransfer,
+        phase_ix: usize,
+    ) -> Result<Option<usize>, TransferError> {
+        let name = transfer.train();
+        let ix = self
+            .stocks
+            .iter()
+            .position(|stock| stock.name == name)
+            .ok_or_else(|| TransferError::UnknownTrain(name.to_string()))?;
+        match transfer {
+            TrainTransfer::FromBank(_) => {
+                if self.stocks[ix].is_sold_out() {
+                    return Err(TransferError::SoldOut(name.to_string()));
+                }
+                if !self.is_available(ix) {
+                    return Err(TransferError::NotAvailable(
+                        name.to_string(),
+                    ));
+                }
+                let stock = &mut self.stocks[ix];
+                let first = stock.sold == 0;
+                stock.bank = stock.bank.map(|count| count - 1);
+                stock.sold += 1;
+                let new_phase =
+                    stock.phase.filter(|p| first && *p > phase_ix);
+                Ok(new_phase)
+            }
+            TrainTransfer::FromPool(_) => {
+                let stock = &mut self.stocks[ix];
+                if stock.pool == 0 {
+                    return Err(TransferError::NotInPool(name.to_string()));
+                }
+                stock.pool -= 1;
+                Ok(None)
+            }
+            TrainTransfer::ToPool(_) => {
+                let stock = &mut self.stocks[ix];
+                if stock.sold <= stock.pool {
+                    return Err(TransferError::NotOwned(name.to_string()));
+                }
+                stock.pool += 1;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Copies the number of trains held by the bank, held in the open
+    /// market, and sold for each train type in `saved`, such as a supply
+    /// that was loaded from a saved game.
+    ///
+    /// Train types that are not in this supply are ignored, and the game
+    /// phase associated with each train type is not modified.
+    pub fn restore_counts(&mut self, saved: &TrainSupply) {
+        for stock in &mut self.stocks {
+            if let Some(saved_stock) = saved.stock(&stock.name) {
+                stock.bank = saved_stock.bank;
+                stock.pool = saved_stock.pool;
+                stock.sold = saved_stock.sold;
+            }
+        }
+    }
+}
+
+impl From<Vec<TrainStock>> for TrainSupply {
+    fn from(stocks: Vec<TrainStock>) -> Self {
+        TrainSupply { stocks }
+    }
+}
+
+impl From<TrainSupply> for Vec<TrainStock> {
+    fn from(supply: TrainSupply) -> Self {
+        supply.stocks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TrainStock, TrainSupply, TrainTransfer, TransferError};
+
+    fn new_supply() -> TrainSupply {
+        vec![
+            TrainStock::new("2", Some(2), Some(0)),
+            TrainStock::new("3", Some(1), Some(1)),
+            TrainStock::new("D", None, Some(2)),
+        ]
+        .into()
+    }
+
+    #[test]
+    fn test_buy_from_bank() {
+        use TrainTransfer::*;
+        let mut supply = new_supply();
+        let two = FromBank("2".to_string());
+        let three = FromBank("3".to_string());
+        let diesel = FromBank("D".to_string());
+        assert_eq!(supply.transfers(), vec![two.clone()]);
+        // The first 2-train does not change the game phase.
+        assert_eq!(supply.apply(&two, 0), Ok(None));
+        assert_eq!(
+            supply.apply(&three, 0),
+            Err(TransferError::NotAvailable("3".to_string()))
+        );
+        assert_eq!(supply.apply(&two, 0), Ok(None));
+        assert_eq!(
+            supply.apply(&two, 0),
+            Err(TransferError::SoldOut("2".to_string()))
+        );
+        assert_eq!(supply.apply(&three, 0), Ok(Some(1)));
+        // The bank never runs out of diesels, and only the first diesel
+        // changes the game phase.
+        assert_eq!(supply.apply(&diesel, 1), Ok(Some(2)));
+        for _ in 0..10 {
+            assert_eq!(supply.apply(&diesel, 2), Ok(None));
+        }
+        assert_eq!(supply.bank_count("D"), Some(None));
+        assert_eq!(supply.stock("D").unwrap().sold, 11);
+        let available = supply.available();
+        assert_eq!(available.len(), 1);
+        assert_eq!(available[0].name, "D");
+    }
+
+    #[test]
+    fn test_open_market() {
+        use TrainTransfer::*;
+        let mut supply = new_supply();
+        let discard = ToPool("2".to_string());
+        let buy = FromPool("2".to_string());
+        assert_eq!(
+            supply.apply(&discard, 0),
+            Err(TransferError::NotOwned("2".to_string()))
+        );
+        assert_eq!(
+            supply.apply(&buy, 0),
+            Err(TransferError::NotInPool("2".to_string()))
+        );
+        supply.apply(&FromBank("2".to_string()), 0).unwrap();
+        assert_eq!(supply.apply(&discard, 0), Ok(None));
+        assert_eq!(supply.pool_count("2"), Some(1));
+        // The only 2-train that has been sold is in the open market.
+        assert_eq!(
+            supply.apply(&discard, 0),
+            Err(TransferError::NotOwned("2".to_string()))
+        );
+        assert!(supply.transfers().contains(&buy));
+        assert_eq!(supply.apply(&buy, 0), Ok(None));
+        assert_eq!(supply.pool_count("2"), Some(0));
+        assert_eq!(
+            supply.apply(&FromBank("4".to_string()), 0),
+            Err(TransferError::UnknownTrain("4".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_restore_counts() {
+        let mut saved = new_supply();
+        saved
+            .apply(&TrainTransfer::FromBank("2".into()), 0)
+            .unwrap();
+        saved.apply(&TrainTransfer::ToPool("2".into()), 0).unwrap();
+        // Saved supplies do not record the game phases.
+        let saved: TrainSupply = Vec::from(saved)
+            .into_iter()
+            .map(|stock| TrainStock {
+                phase: None,
+                ..stock
+            })
+            .collect::<Vec<_>>()
+            .into();
+        let mut supply = new_supply();
+        supply.restore_counts(&saved);
+        let stock = supply.stock("2").unwrap();
+        assert_eq!(stock.bank, Some(1));
+        assert_eq!(stock.pool, 1);
+        assert_eq!(stock.sold, 1);
+        assert_eq!(stock.phase, Some(0));
+    }
+
+    #[test]
+    fn test_unlimited_trains() {
+        // Train types with an unlimited number of trains do not prevent
+        // later train types from being bought.
+        let mut supply: TrainSupply = vec![
+            TrainStock::new("2", None, Some(0)),
+            TrainStock::new("3", None, Some(1)),
+            TrainStock::new("4", Some(1), Some(2)),
+            TrainStock::new("5", None, Some(3)),
+        ]
+        .into();
+        let names: Vec<&str> =
+            supply.available().iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["2", "3", "4"]);
+        let buy = TrainTransfer::FromBank("4".to_string());
+        assert_eq!(supply.apply(&buy, 0), Ok(Some(2)));
+        let names: Vec<&str> =
+            supply.available().iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["2", "3", "5"]);
+    }
+}
@@ -0,0 +1,148 @@
+//! Choose distinct colours for highlighting each company's routes.
+//!
+//! Each company's routes are highlighted in the colour returned by
+//! [Game::route_colour](crate::Game::route_colour), which defaults to the
+//! background colour of the company's tokens.
+//! Since several companies may have similar token colours, [distinct_colours]
+//! replaces colours that are nearly identical to those of preceding companies
+//! with high-contrast alternatives.
+
+use n18hex::Colour;
+
+/// The minimum [distance](Colour::distance) between two colours for them to
+/// be considered distinct.
+pub const MIN_DISTANCE: f64 = 64.0;
+
+/// High-contrast colours that replace nearly-identical company colours.
+///
+/// These are Kelly's colours of maximum contrast, excluding white and black
+/// so that they remain visible against track segments.
+const HIGH_CONTRAST: [(u8, u8, u8); 20] = [
+    (255, 179, 0),
+    (128, 62, 117),
+    (255, 104, 0),
+    (166, 189, 215),
+    (193, 0, 32),
+    (206, 162, 98),
+    (129, 112, 102),
+    (0, 125, 52),
+    (246, 118, 142),
+    (0, 83, 138),
+    (255, 122, 92),
+    (83, 55, 122),
+    (255, 142, 0),
+    (179, 40, 81),
+    (244, 200, 0),
+    (127, 24, 13),
+    (147, 170, 0),
+    (89, 51, 21),
+    (241, 58, 19),
+    (35, 44, 22),
+];
+
+/// Returns the distance between `colour` and the nearest of the `chosen`
+/// colours, or infinity if no colours have been chosen.
+fn nearest_distance(colour: &Colour, chosen: &[Colour]) -> f64 {
+    chosen
+        .iter()
+        .map(|other| colour.distance(other))
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Returns the high-contrast colours, followed by lighter and darker shades
+/// of these colours for games that have many similar company colours.
+fn candidate_colours() -> impl Iterator<Item = Colour> {
+    let shade =
+        |value: u8, target: u8| ((value as u16 + target as u16) / 2) as u8;
+    let lighter = move |(r, g, b): (u8, u8, u8)| {
+        (shade(r, u8::MAX), shade(g, u8::MAX), shade(b, u8::MAX))
+    };
+    let darker = move |(r, g, b): (u8, u8, u8)| {
+        (shade(r, 0), shade(g, 0), shade(b, 0))
+    };
+    HIGH_CONTRAST
+        .iter()
+        .copied()
+        .chain(HIGH_CONTRAST.iter().copied().map(lighter))
+        .chain(HIGH_CONTRAST.iter().copied().map(darker))
+        .map(Colour::from)
+}
+
+/// Returns the high-contrast colour that is furthest from all of the
+/// `chosen` colours.
+fn contrasting_colour(chosen: &[Colour]) -> Colour {
+    candidate_colours()
+        .max_by(|a, b| {
+            nearest_distance(a, chosen)
+                .total_cmp(&nearest_distance(b, chosen))
+        })
+        .expect("No high-contrast colours")
+}
+
+/// Returns the provided colours, in the same order, except that each colour
+/// that is within [MIN_DISTANCE] of a preceding colour is replaced by a
+/// high-contrast alternative.
+pub fn distinct_colours<K>(colours: Vec<(K, Colour)>) -> Vec<(K, Colour)> {
+    let mut chosen: Vec<Colour> = Vec::with_capacity(colours.len());
+    colours
+        .into_iter()
+        .map(|(key, colour)| {
+            let colour = if nearest_distance(&colour, &chosen) < MIN_DISTANCE
+            {
+                contrasting_colour(&chosen)
+            } else {
+                colour
+            };
+            chosen.push(colour);
+            (key, colour)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{distinct_colours, MIN_DISTANCE};
+    use crate::Game;
+    use n18hex::Colour;
+
+    #[test]
+    fn test_distinct_colours_are_retained() {
+        let colours = vec![
+            ("A", Colour::from((255, 0, 0))),
+            ("B", Colour::from((0, 255, 0))),
+            ("C", Colour::from((0, 0, 255))),
+        ];
+        assert_eq!(distinct_colours(colours.clone()), colours);
+    }
+
+    #[test]
+    fn test_similar_colours_are_replaced() {
+        let yellow = Colour::from((239, 239, 31));
+        let colours = vec![
+            ("A", yellow),
+            ("B", Colour::from((235, 235, 40))),
+            ("C", Colour::from((0, 0, 255))),
+        ];
+        let result = distinct_colours(colours);
+        assert_eq!(result[0], ("A", yellow));
+        assert_ne!(result[1].1, Colour::from((235, 235, 40)));
+        assert!(result[1].1.distance(&yellow) >= MIN_DISTANCE);
+        assert_eq!(result[2], ("C", Colour::from((0, 0, 255))));
+    }
+
+    #[test]
+    fn test_game_route_colours() {
+        // Many of the 1861 companies have tokens with the same background
+        // colour, but each company's routes should have a different colour.
+        let game = crate::new_1861();
+        let colours = game.route_colours();
+        let mut abbrevs = game.company_abbrevs();
+        abbrevs.sort();
+        abbrevs.dedup();
+        assert_eq!(colours.len(), abbrevs.len());
+        let mut values: Vec<Colour> = colours.values().copied().collect();
+        values.sort();
+        values.dedup();
+        assert_eq!(values.len(), colours.len());
+    }
+}
@@ -1,15 +1,37 @@
+use std::collections::BTreeMap;
+
 use log::info;
 use n18catalogue::Catalogue;
-use n18hex::{Hex, Orientation};
-use n18map::{Coordinates, Map};
-use n18route::{Bonus, ConflictRule, Routes, Train, Trains};
-use n18tile::Tile;
+use n18hex::{Colour, Hex, HexColour, Orientation};
+use n18map::{
+    Coordinates, HexAddress, Map, PlacementRules, TokenChange, TokenRule,
+};
+use n18route::{
+    Bonus, ConflictRule, PathCache, RouteConflictRules, RouteTokenRule,
+    Routes, Scoring, StandardScoring, Train, Trains,
+};
+use n18tile::{Label, Tile};
 use n18token::{Token, Tokens};
 
+use bank::{TrainStock, TrainSupply};
+use private::{Private, PrivateHoldings};
+use treasury::{TokenCost, Treasuries};
+
 pub mod _1830;
 pub mod _1861;
 pub mod _1867;
 pub mod _1889;
+pub mod analysis;
+pub mod bank;
+pub mod colours;
+pub mod private;
+pub mod setup;
+pub mod sim;
+pub mod treasury;
+pub mod validate;
+
+/// The name of the standard map variant, which every game provides.
+pub const STANDARD_VARIANT: &str = "Standard";
 
 /// Creates a new game of 1830: Railways and Robber Barons.
 pub fn new_1830() -> _1830::Game {
@@ -99,6 +121,8 @@ pub enum DividendKind {
     /// assert_eq!(net_dividend, 110);
     /// ```
     Half { rounding: Rounding, nearest: usize },
+    /// Withhold all of the earned revenue, so that no dividends are paid.
+    Withhold,
 }
 
 impl std::fmt::Display for DividendKind {
@@ -109,6 +133,7 @@ impl std::fmt::Display for DividendKind {
         let descr = match self {
             DividendKind::Full => "Full-pay",
             DividendKind::Half { .. } => "Half-pay",
+            DividendKind::Withhold => "Withhold",
         };
         write!(f, "{}", descr)
     }
@@ -123,6 +148,7 @@ impl DividendKind {
             Half { rounding, nearest } => {
                 nearest * rounding.round(revenue / 2, *nearest)
             }
+            Withhold => 0,
         }
     }
 }
@@ -150,12 +176,18 @@ pub struct Dividends {
 impl DividendOptions {
     /// Returns the per-share dividend to distribute the provided revenue, for
     /// each of the available dividend options.
+    ///
+    /// Any revenue that is not paid to shareholders is withheld.
+    /// If the per-share dividend is rounded up, the shareholders may be paid
+    /// more than the net dividend, but never more than the revenue.
     pub fn dividends(&self, revenue: usize) -> Vec<Dividends> {
         self.dividend_options
             .iter()
             .map(|&(kind, rounding)| {
                 let net = kind.net_dividend(revenue);
-                let per_share = rounding.round(net, self.share_count);
+                let per_share = rounding
+                    .round(net, self.share_count)
+                    .min(revenue / self.share_count);
                 let share_payments =
                     (1..=self.share_count).map(|n| per_share * n).collect();
                 let remainder = revenue - self.share_count * per_share;
@@ -184,6 +216,203 @@ pub struct Company {
     pub full_name: String,
     /// The visual characteristics of the company tokens.
     pub token: Token,
+    /// Whether this is a minor or a major company.
+    pub tier: CompanyTier,
+}
+
+/// Distinguishes minor companies from major companies, in games where they
+/// have different rules (such as 1861 and 1867).
+///
+/// The rules for each tier are defined by [Game::tier_rules].
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub enum CompanyTier {
+    /// A minor company, which typically has a single share, few tokens, and
+    /// can only operate a small number of trains.
+    Minor,
+    /// A major company.
+    #[default]
+    Major,
+}
+
+impl std::fmt::Display for CompanyTier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompanyTier::Minor => write!(f, "minor"),
+            CompanyTier::Major => write!(f, "major"),
+        }
+    }
+}
+
+/// The rules that apply to every company in a [CompanyTier].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TierRules {
+    /// The number of tokens that each company can place on the map, or
+    /// `None` if this is not limited (or differs between companies).
+    pub token_count: Option<usize>,
+    /// The maximum number of trains that each company can operate, or `None`
+    /// if this is not limited (or depends on the game phase).
+    pub train_limit: Option<usize>,
+}
+
+/// Describes how a national company is formed by converting other companies
+/// into shares of the national company, such as the Russian National Railway
+/// in 1861 and the Canadian National Railway in 1867.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NationalFormation {
+    /// The national company.
+    pub national: Company,
+    /// The name of the game phase in which the national company is formed.
+    pub phase: String,
+    /// The abbreviated names of the companies that are converted into
+    /// shares of the national company.
+    pub companies: Vec<String>,
+    /// The number of national company shares that each converted company is
+    /// exchanged for.
+    pub shares_per_company: usize,
+}
+
+impl NationalFormation {
+    /// Returns a national formation in which the national company is the
+    /// last company, and each of the first `minors` companies is exchanged
+    /// for a single share of the national company in the given phase.
+    ///
+    /// Returns `None` if there are no companies.
+    pub fn from_minors(
+        companies: &[Company],
+        minors: usize,
+        phase: &str,
+    ) -> Option<Self> {
+        let national = companies.last()?.clone();
+        let companies = companies
+            .iter()
+            .take(minors)
+            .map(|company| company.abbrev.clone())
+            .collect();
+        Some(NationalFormation {
+            national,
+            phase: phase.to_string(),
+            companies,
+            shares_per_company: 1,
+        })
+    }
+
+    /// Converts each company into shares of the national company, replacing
+    /// their placed tokens with the national company's token, and returns
+    /// the token changes for each company that had placed tokens.
+    ///
+    /// The national company's token is added to the map if it is not already
+    /// defined.
+    pub fn convert_companies(
+        &self,
+        map: &mut Map,
+    ) -> Vec<(String, Vec<TokenChange>)> {
+        let abbrev = &self.national.abbrev;
+        let national = match map.try_token(abbrev) {
+            Some(token) => token,
+            None => {
+                if !map.add_token(abbrev.clone(), self.national.token) {
+                    return vec![];
+                }
+                self.national.token
+            }
+        };
+        self.companies
+            .iter()
+            .filter_map(|name| {
+                let token = map.try_token(name)?;
+                let changes = map.replace_company_tokens(&token, &national);
+                if changes.is_empty() {
+                    None
+                } else {
+                    Some((name.clone(), changes))
+                }
+            })
+            .collect()
+    }
+}
+
+/// Describes the changes that occur when the game moves to a specific game
+/// phase, as returned by [Game::preview_phase].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhaseEffects {
+    /// The index of the game phase.
+    pub phase_ix: usize,
+    /// The name of the game phase.
+    pub name: String,
+    /// The tile colours that can be placed in this game phase.
+    pub tile_colours: Vec<HexColour>,
+    /// The tile colours that can be placed in this game phase, but not in
+    /// the current game phase.
+    pub new_tile_colours: Vec<HexColour>,
+    /// The map hexes whose revenues (e.g., off-board revenues) change.
+    pub revenue_changes: Vec<RevenueChange>,
+}
+
+impl PhaseEffects {
+    /// Returns `true` if moving to this game phase has no visible effects.
+    pub fn is_empty(&self) -> bool {
+        self.new_tile_colours.is_empty() && self.revenue_changes.is_empty()
+    }
+
+    /// Returns a human-readable description of each change, such as
+    /// "New tile colours: Green" and "Chicago (B6): 40 -> 70".
+    pub fn summary(&self) -> Vec<String> {
+        let join_revenues = |revenues: &[usize]| {
+            revenues
+                .iter()
+                .map(|r| r.to_string())
+                .collect::<Vec<_>>()
+                .join("/")
+        };
+        let mut lines = vec![];
+        if !self.new_tile_colours.is_empty() {
+            let colours: Vec<String> = self
+                .new_tile_colours
+                .iter()
+                .map(|c| format!("{:?}", c))
+                .collect();
+            lines.push(format!("New tile colours: {}", colours.join(", ")));
+        }
+        for change in &self.revenue_changes {
+            lines.push(format!(
+                "{}: {} -> {}",
+                change.name,
+                join_revenues(&change.before),
+                join_revenues(&change.after)
+            ))
+        }
+        lines
+    }
+}
+
+/// Describes a change in the revenues of a single map hex.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevenueChange {
+    /// The map hex.
+    pub addr: HexAddress,
+    /// The name of the map hex, such as "B3" or "Chicago (B6)".
+    pub name: String,
+    /// The revenues in the current game phase.
+    pub before: Vec<usize>,
+    /// The revenues in the previewed game phase.
+    pub after: Vec<usize>,
+}
+
+/// Returns the tile colours in the upgrade sequence, from yellow up to and
+/// including `last`.
+pub fn tile_colours_up_to(last: HexColour) -> Vec<HexColour> {
+    let mut colours = vec![];
+    let mut colour = HexColour::Empty;
+    while colour != last {
+        colour = match colour.next_phase() {
+            Some(colour) => colour,
+            None => break,
+        };
+        colours.push(colour);
+    }
+    colours
 }
 
 /// The methods that are required for a specific 18xx game implementation.
@@ -217,9 +446,78 @@ pub trait Game {
     /// The coordinate system used to identify map hexes.
     fn coordinate_system(&self) -> Coordinates;
 
-    /// Creates the initial map for this game.
+    /// Creates the initial map for this game, using the current map
+    /// variant.
+    ///
+    /// Implementations should call [Map::record_initial_tiles] once the
+    /// starting tiles have been placed, so that each map hex can be reset
+    /// to its starting tile (see [Map::reset_hex]).
     fn create_map(&self, hex: &Hex) -> Map;
 
+    /// Returns the name of each tile that this game places on the map, and
+    /// the hex where it is placed: the starting tiles for the current map
+    /// variant, and any tiles that are placed when the game phase changes.
+    ///
+    /// This is used to check that every tile is defined in the game
+    /// catalogue (see [Game::validate]).
+    ///
+    /// # Default implementation
+    ///
+    /// The default implementation returns the tiles on a newly-created map,
+    /// which will not include tiles that are missing from the catalogue.
+    fn placed_tiles(&self) -> Vec<(HexAddress, String)> {
+        let map = self.create_map(&Hex::default());
+        map.hex_address_iter()
+            .filter_map(|addr| {
+                map.tile_at(*addr).map(|tile| (*addr, tile.name.clone()))
+            })
+            .collect()
+    }
+
+    /// Checks that the map, tiles, and companies of this game are consistent
+    /// with each other, for the current map variant, and returns a report
+    /// that lists every inconsistency.
+    ///
+    /// This checks that every tile placed on the map is defined in the game
+    /// catalogue, that hex labels used for tile upgrades match at least one
+    /// catalogue tile, and that the map defines one token for each company.
+    fn validate(&self) -> validate::Report {
+        validate::validate(self)
+    }
+
+    /// Returns the name of each map variant for this game, where the first
+    /// variant is the standard map.
+    ///
+    /// Map variants may define different map hexes, starting tiles, or
+    /// companies.
+    ///
+    /// # Default implementation
+    ///
+    /// The default implementation only provides the standard map,
+    /// [STANDARD_VARIANT].
+    fn variants(&self) -> &[&str] {
+        &[STANDARD_VARIANT]
+    }
+
+    /// Returns the name of the current map variant.
+    ///
+    /// # Default implementation
+    ///
+    /// The default implementation returns [STANDARD_VARIANT].
+    fn variant(&self) -> &str {
+        STANDARD_VARIANT
+    }
+
+    /// Selects the map variant that will be used by [Game::create_map], and
+    /// returns `true` if this game has a variant with the given name.
+    ///
+    /// # Default implementation
+    ///
+    /// The default implementation only accepts [STANDARD_VARIANT].
+    fn set_variant(&mut self, name: &str) -> bool {
+        name == STANDARD_VARIANT
+    }
+
     /// Returns the companies in this game.
     fn companies(&self) -> &[Company];
 
@@ -306,10 +604,140 @@ pub trait Game {
             .unwrap_or_else(|| panic!("No company named '{}'", abbrev))
     }
 
+    /// Returns the private companies in this game.
+    ///
+    /// The default implementation returns no private companies.
+    fn privates(&self) -> &[Private] {
+        &[]
+    }
+
+    /// Returns the private company with the given abbreviated name, if it
+    /// exists.
+    fn try_private(&self, abbrev: &str) -> Option<&Private> {
+        self.privates().iter().find(|p| p.abbrev == abbrev)
+    }
+
+    /// Returns `true` if a private company exists and is open in the
+    /// current game phase.
+    fn private_is_open(
+        &self,
+        holdings: &PrivateHoldings,
+        abbrev: &str,
+    ) -> bool {
+        self.try_private(abbrev).is_some_and(|private| {
+            !holdings.is_closed(abbrev) && !private.closes_by(self.phase_ix())
+        })
+    }
+
+    /// Returns the open private company (if any) that prevents `company`
+    /// from placing a tile on the hex at `addr`.
+    ///
+    /// Hexes that are blocked by a private company (see
+    /// [private::Ability::BlocksHex]) are reserved for the company that owns
+    /// the private company, and are not reserved for any company once the
+    /// private company closes.
+    fn reserving_private(
+        &self,
+        holdings: &PrivateHoldings,
+        addr: HexAddress,
+        company: Option<&str>,
+    ) -> Option<&Private> {
+        self.privates().iter().find(|private| {
+            private.blocks(addr)
+                && self.private_is_open(holdings, &private.abbrev)
+                && (company.is_none()
+                    || holdings.owner(&private.abbrev) != company)
+        })
+    }
+
     /// Returns the options available to a company for distributing dividends
     /// to shareholders.
+    ///
+    /// Each option should round the per-share dividend with
+    /// [Game::share_rounding], unless the game defines other rules.
     fn dividend_options(&self, abbrev: &str) -> Option<DividendOptions>;
 
+    /// Returns how a company's per-share dividend is rounded when the net
+    /// dividend cannot be divided evenly between its shares.
+    ///
+    /// The default implementation rounds down, so that the company withholds
+    /// the remainder.
+    fn share_rounding(&self) -> Rounding {
+        Rounding::Down
+    }
+
+    /// Returns the rules that apply to every company in a tier.
+    ///
+    /// The default implementation does not limit the number of tokens or
+    /// trains for any tier.
+    fn tier_rules(&self, _tier: CompanyTier) -> TierRules {
+        TierRules::default()
+    }
+
+    /// Returns the rules that apply to a company, according to its tier, if
+    /// the company exists.
+    fn company_rules(&self, abbrev: &str) -> Option<TierRules> {
+        self.try_company(abbrev)
+            .map(|company| self.tier_rules(company.tier))
+    }
+
+    /// Returns the maximum number of trains that each company can operate
+    /// in the specified game phase, or `None` if this is not limited (or
+    /// differs between companies).
+    ///
+    /// The default implementation does not limit the number of trains in
+    /// any phase.
+    fn phase_train_limit(&self, _phase: usize) -> Option<usize> {
+        None
+    }
+
+    /// Returns the maximum number of trains that a company can operate in
+    /// the current game phase, or `None` if this is not limited.
+    ///
+    /// This is the smaller of the company's tier limit (see
+    /// [Game::company_rules]) and the limit for the current game phase (see
+    /// [Game::phase_train_limit]).
+    fn train_limit(&self, abbrev: &str) -> Option<usize> {
+        let tier_limit = self
+            .company_rules(abbrev)
+            .and_then(|rules| rules.train_limit);
+        let phase_limit = self.phase_train_limit(self.phase_ix());
+        match (tier_limit, phase_limit) {
+            (Some(tier), Some(phase)) => Some(tier.min(phase)),
+            (tier, phase) => tier.or(phase),
+        }
+    }
+
+    /// Returns the abbreviated names of the companies, in the order that
+    /// they operate in each operating round.
+    ///
+    /// The default implementation returns the companies in the order that
+    /// they are defined by [Game::companies].
+    fn operating_order(&self) -> Vec<&str> {
+        self.companies()
+            .iter()
+            .map(|company| company.abbrev.as_str())
+            .collect()
+    }
+
+    /// Returns whether a company may place more than one token in the same
+    /// city (see [Map::try_place_token]).
+    ///
+    /// The default implementation does not restrict token placement.
+    fn token_rule(&self) -> TokenRule {
+        TokenRule::Unrestricted
+    }
+
+    /// Returns how much a company must pay to place each of its tokens.
+    ///
+    /// This cost is only paid by companies whose treasury is being tracked
+    /// (see [GameState::treasuries]).
+    ///
+    /// The default implementation allows every token to be placed for free.
+    fn token_cost(&self, _abbrev: &str) -> TokenCost {
+        TokenCost::Free
+    }
+
     /// Returns the token with the given abbreviated name, if it exists.
     fn try_token(&self, abbrev: &str) -> Option<&Token> {
         self.try_company(abbrev).map(|c| &c.token)
@@ -325,6 +753,34 @@ pub trait Game {
             .unwrap_or_else(|| panic!("No company named '{}'", abbrev))
     }
 
+    /// Returns the colour used to highlight the routes operated by a company,
+    /// if it exists.
+    ///
+    /// By default, this is the background colour of the company's tokens.
+    /// Games can override this to choose more suitable colours.
+    fn route_colour(&self, abbrev: &str) -> Option<Colour> {
+        self.try_token(abbrev)
+            .map(|token| *token.style.background())
+    }
+
+    /// Returns the colour used to highlight the routes operated by each
+    /// company.
+    ///
+    /// Where a company's colour is nearly identical to that of a preceding
+    /// company, it is replaced by a high-contrast alternative (see
+    /// [colours::distinct_colours]).
+    fn route_colours(&self) -> BTreeMap<String, Colour> {
+        let colours: Vec<(String, Colour)> = self
+            .companies()
+            .iter()
+            .filter_map(|company| {
+                self.route_colour(&company.abbrev)
+                    .map(|colour| (company.abbrev.clone(), colour))
+            })
+            .collect();
+        colours::distinct_colours(colours).into_iter().collect()
+    }
+
     /// Returns the named train types in this game, in the order that they
     /// become available (where applicable).
     fn trains(&self) -> &[(&str, Train)];
@@ -362,20 +818,90 @@ pub trait Game {
         self.trains().iter().find(|t| &t.1 == train).map(|t| t.0)
     }
 
+    /// Returns the number of trains of each type that the bank holds at the
+    /// start of the game, in the same order as [Game::trains], where `None`
+    /// indicates an unlimited number of trains.
+    ///
+    /// The default implementation provides an unlimited number of each
+    /// train type.
+    fn train_counts(&self) -> Vec<Option<usize>> {
+        vec![None; self.trains().len()]
+    }
+
+    /// Returns the index of the game phase (if any) that begins when the
+    /// first train of each type is bought, in the same order as
+    /// [Game::trains].
+    ///
+    /// The default implementation selects the game phase that has the same
+    /// name as the train type.
+    fn train_phases(&self) -> Vec<Option<usize>> {
+        self.train_names()
+            .iter()
+            .map(|name| self.phase_names().iter().position(|p| p == name))
+            .collect()
+    }
+
+    /// Returns the trains that the bank holds at the start of the game.
+    fn train_supply(&self) -> TrainSupply {
+        self.train_names()
+            .into_iter()
+            .zip(self.train_counts())
+            .zip(self.train_phases())
+            .map(|((name, count), phase)| TrainStock::new(name, count, phase))
+            .collect::<Vec<_>>()
+            .into()
+    }
+
     /// Optional route bonuses that a company may hold.
     fn bonus_options(&self) -> Vec<&str>;
 
     /// Return the bonuses that may apply to the routes being operated by a
     /// company, given the bonus options (e.g., private company bonuses) that
     /// the company currently owns.
+    ///
+    /// These may include [Bonus::MarkerBonus] bonuses, which apply to each
+    /// map hex that has the named marker (e.g., a port marker).
     fn bonuses(&self, bonus_options: &[bool]) -> Vec<Bonus>;
 
+    /// Returns the rules for calculating the revenue that each train earns
+    /// by operating a route.
+    ///
+    /// The default implementation returns [StandardScoring], as per
+    /// [Train::revenue_for](n18route::Train::revenue_for).
+    fn scoring(&self) -> Box<dyn Scoring> {
+        Box::new(StandardScoring)
+    }
+
     /// Defines the elements that cannot be shared in a single route.
     fn single_route_conflicts(&self) -> ConflictRule;
 
     /// Defines the elements that cannot be shared between routes.
     fn multiple_routes_conflicts(&self) -> ConflictRule;
 
+    /// Defines the elements that cannot be shared between routes, for games
+    /// where this depends on the trains that operate these routes.
+    ///
+    /// The default implementation uses [Game::multiple_routes_conflicts] for
+    /// every train.
+    /// Each train's rule must be more general than
+    /// [Game::single_route_conflicts].
+    fn route_conflict_rules(&self) -> RouteConflictRules {
+        self.multiple_routes_conflicts().into()
+    }
+
+    /// Defines which city (or cities) every route operated by the company
+    /// `abbrev` must include, such as any city where the company has placed
+    /// a token, or the company's home city.
+    ///
+    /// # Default implementation
+    ///
+    /// The default implementation returns [RouteTokenRule::AnyToken], so
+    /// that every route must include at least one city in which the company
+    /// has placed a token.
+    fn route_token_rule(&self, _map: &Map, _abbrev: &str) -> RouteTokenRule {
+        RouteTokenRule::AnyToken
+    }
+
     /// Returns a closure that finds routes for the currently-selected
     /// token that yield the maximum revenue.
     ///
@@ -385,30 +911,67 @@ pub trait Game {
     /// unlike the [Game::best_routes] method where they are passed *by
     /// reference*.
     ///
+    /// This calls [Game::best_routes_closure_cached] with an empty
+    /// [PathCache].
+    fn best_routes_closure(
+        &self,
+        map: Map,
+        token: Token,
+        trains: Trains,
+        bonus_options: Vec<bool>,
+    ) -> Box<dyn FnOnce() -> Option<Routes> + Send> {
+        let cache =
+            std::sync::Arc::new(std::sync::Mutex::new(PathCache::new()));
+        self.best_routes_closure_cached(
+            map,
+            token,
+            trains,
+            bonus_options,
+            cache,
+        )
+    }
+
+    /// Returns a closure that finds routes for the currently-selected
+    /// token that yield the maximum revenue, as per
+    /// [Game::best_routes_closure], and which reuses the paths in `cache`
+    /// from previous searches of the same map.
+    ///
+    /// The cache is locked while the closure searches for routes, so that
+    /// concurrent searches do not repeat the same work.
+    ///
     /// # Default implementation
     ///
     /// The default implementation calls [default_best_routes].
     /// While this should be sufficient for many 18xx games, some games may
     /// need to override this method.
-    fn best_routes_closure(
+    fn best_routes_closure_cached(
         &self,
         map: Map,
         token: Token,
         trains: Trains,
         bonus_options: Vec<bool>,
+        cache: std::sync::Arc<std::sync::Mutex<PathCache>>,
     ) -> Box<dyn FnOnce() -> Option<Routes> + Send> {
         let bonuses = self.bonuses(&bonus_options);
         let conflict_rule = self.single_route_conflicts();
-        let route_conflict_rule = self.multiple_routes_conflicts();
+        let route_conflict_rules = self.route_conflict_rules();
+        let token_rule = token_rule_for(self, &map, &token);
+        let scoring = self.scoring();
 
         Box::new(move || {
-            default_best_routes(
-                &map,
-                token,
-                &trains,
-                bonuses,
+            // NOTE: the cached paths remain valid if a previous search
+            // panicked, because each search only adds complete results.
+            let mut cache = cache
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let rules = RouteRules {
+                token_rule,
                 conflict_rule,
-                route_conflict_rule,
+                route_conflict_rules: &route_conflict_rules,
+                scoring: scoring.as_ref(),
+            };
+            default_best_routes(
+                &map, token, &trains, bonuses, &rules, &mut cache,
             )
         })
     }
@@ -416,32 +979,132 @@ pub trait Game {
     /// Finds routes for the currently-selected token that yield the maximum
     /// revenue.
     ///
+    /// This calls [Game::best_routes_cached] with an empty [PathCache].
+    fn best_routes(
+        &self,
+        map: &Map,
+        token: Token,
+        trains: &Trains,
+        bonus_options: Vec<bool>,
+    ) -> Option<Routes> {
+        let mut cache = PathCache::new();
+        self.best_routes_cached(map, token, trains, bonus_options, &mut cache)
+    }
+
+    /// Finds routes for the currently-selected token that yield the maximum
+    /// revenue, reusing the paths in `cache` from previous searches of the
+    /// same map.
+    ///
     /// # Default implementation
     ///
     /// The default implementation calls [default_best_routes].
     /// While this should be sufficient for many 18xx games, some games may
     /// need to override this method.
-    fn best_routes(
+    fn best_routes_cached(
         &self,
         map: &Map,
         token: Token,
         trains: &Trains,
         bonus_options: Vec<bool>,
+        cache: &mut PathCache,
     ) -> Option<Routes> {
         let bonuses = self.bonuses(&bonus_options);
         let conflict_rule = self.single_route_conflicts();
-        let route_conflict_rule = self.multiple_routes_conflicts();
+        let route_conflict_rules = self.route_conflict_rules();
+        let token_rule = token_rule_for(self, map, &token);
+        let scoring = self.scoring();
 
-        default_best_routes(
-            map,
+        let rules = RouteRules {
+            token_rule,
+            conflict_rule,
+            route_conflict_rules: &route_conflict_rules,
+            scoring: scoring.as_ref(),
+        };
+        default_best_routes(map, token, trains, bonuses, &rules, cache)
+    }
+
+    /// Returns the criteria that [Game::best_routes] uses to enumerate the
+    /// paths that `trains` could operate for the currently-selected token.
+    ///
+    /// This allows the path search to be inspected separately from the
+    /// route selection (e.g., with [n18route::path_counts_with]).
+    fn path_criteria(
+        &self,
+        map: &Map,
+        token: Token,
+        trains: &Trains,
+    ) -> n18route::Criteria {
+        n18route::Criteria {
             token,
+            path_limit: trains.path_limit(),
+            conflict_rule: self.single_route_conflicts(),
+            route_conflict_rule: self
+                .route_conflict_rules()
+                .most_permissive(),
+            token_rule: token_rule_for(self, map, &token),
+        }
+    }
+
+    /// Finds the routes that yield the maximum revenue from the city
+    /// `city_ix` on the tile at `addr`, for a company that has no tokens on
+    /// the map (e.g., to evaluate a potential token location).
+    ///
+    /// # Default implementation
+    ///
+    /// The default implementation calls [default_open_routes].
+    fn open_routes(
+        &self,
+        map: &Map,
+        addr: HexAddress,
+        city_ix: usize,
+        trains: &Trains,
+    ) -> Option<Routes> {
+        let conflict_rule = self.single_route_conflicts();
+        let route_conflict_rules = self.route_conflict_rules();
+        let scoring = self.scoring();
+
+        default_open_routes(
+            map,
+            addr,
+            city_ix,
             trains,
-            bonuses,
             conflict_rule,
-            route_conflict_rule,
+            &route_conflict_rules,
+            scoring.as_ref(),
         )
     }
 
+    /// Returns a closure that finds the routes that yield the maximum
+    /// revenue from a single city, as per [Game::open_routes], so that the
+    /// search can be performed in a separate thread.
+    ///
+    /// # Default implementation
+    ///
+    /// The default implementation calls [default_open_routes].
+    fn open_routes_closure(
+        &self,
+        map: Map,
+        addr: HexAddress,
+        city_ix: usize,
+        trains: Trains,
+    ) -> Box<dyn FnOnce() -> Option<Routes> + Send> {
+        let conflict_rule = self.single_route_conflicts();
+        let route_conflict_rules = self.route_conflict_rules();
+        let scoring = self.scoring();
+
+        Box::new(move || {
+            default_open_routes(
+                &map,
+                addr,
+                city_ix,
+                &trains,
+                conflict_rule,
+                &route_conflict_rules,
+                scoring.as_ref(),
+            )
+        })
+    }
+
     /// Returns all game tiles, including special tiles that players cannot
     /// place on the map.
     fn catalogue(&self) -> &Catalogue;
@@ -492,6 +1155,115 @@ pub trait Game {
         ix_opt.map(|ix| self.set_phase_ix(map, ix)).unwrap_or(false)
     }
 
+    /// Returns the tile colours that can be placed in the specified game
+    /// phase.
+    ///
+    /// # Default implementation
+    ///
+    /// The default implementation returns every colour in the tile upgrade
+    /// sequence, from yellow to grey.
+    fn phase_tile_colours(&self, _phase: usize) -> Vec<HexColour> {
+        tile_colours_up_to(HexColour::Grey)
+    }
+
+    /// Returns the rules that determine which tile placements are legal in
+    /// the current game phase (see [Map::legal_placements]).
+    ///
+    /// # Default implementation
+    ///
+    /// The default implementation only allows the tile colours returned by
+    /// [Game::phase_tile_colours], and requires upgrade tiles to preserve
+    /// every track connection of the current tile.
+    fn placement_rules(&self) -> PlacementRules {
+        PlacementRules::for_phase(self.phase_tile_colours(self.phase_ix()))
+    }
+
+    /// Returns the changes that would occur if the game moved to the
+    /// specified game phase, without changing the current game phase or
+    /// modifying `map`.
+    ///
+    /// Returns `None` if there is no such game phase.
+    ///
+    /// # Default implementation
+    ///
+    /// The default implementation changes the game phase on a copy of `map`
+    /// with [Game::set_phase_ix], compares the revenues of each map hex, and
+    /// then restores the current game phase.
+    fn preview_phase(
+        &mut self,
+        map: &Map,
+        phase: usize,
+    ) -> Option<PhaseEffects> {
+        let name = self.phase_names().get(phase)?.to_string();
+        let curr = self.phase_ix();
+        let mut preview = map.clone();
+        if !self.set_phase_ix(&mut preview, phase) {
+            return None;
+        }
+        // NOTE: the phase change is applied to a copy of the map, so that
+        // the original map is not modified when the phase is restored.
+        self.set_phase_ix(&mut map.clone(), curr);
+
+        // NOTE: tiles with a phase revenue label (e.g., off-board tiles)
+        // earn the revenue for the map's revenue phase.
+        let revenues = |map: &Map, tile: &Tile| -> Vec<usize> {
+            let phase_revenue = map
+                .revenue_phase()
+                .and_then(|phase| tile.phase_revenue(phase));
+            match phase_revenue {
+                Some(revenue) => vec![revenue; tile.revenues().len()],
+                None => tile.revenues().to_vec(),
+            }
+        };
+        let coords = self.coordinate_system();
+        let revenue_changes = map
+            .hex_address_iter()
+            .filter_map(|addr| {
+                let before = revenues(map, map.tile_at(*addr)?);
+                let tile = preview.tile_at(*addr)?;
+                let after = revenues(&preview, tile);
+                if before == after {
+                    return None;
+                }
+                let hex_name =
+                    coords.format(addr).unwrap_or_else(|| addr.to_string());
+                let location =
+                    tile.labels().iter().find_map(|(label, _)| match label {
+                        Label::MapLocation(name) => Some(name),
+                        _ => None,
+                    });
+                // NOTE: location names may be split over multiple lines.
+                let name = match location {
+                    Some(locn) => {
+                        format!("{} ({})", locn.replace('\n', " "), hex_name)
+                    }
+                    None => hex_name,
+                };
+                Some(RevenueChange {
+                    addr: *addr,
+                    name,
+                    before,
+                    after,
+                })
+            })
+            .collect();
+
+        let current_colours = self.phase_tile_colours(curr);
+        let tile_colours = self.phase_tile_colours(phase);
+        let new_tile_colours = tile_colours
+            .iter()
+            .filter(|colour| !current_colours.contains(colour))
+            .copied()
+            .collect();
+        Some(PhaseEffects {
+            phase_ix: phase,
+            name,
+            tile_colours,
+            new_tile_colours,
+            revenue_changes,
+        })
+    }
+
     /// Advance to the next game phase, if it exists.
     fn next_phase(&mut self, map: &mut Map) -> bool {
         let curr = self.phase_ix();
@@ -512,22 +1284,124 @@ pub trait Game {
         }
     }
 
+    /// Returns how the national company is formed, for games that have a
+    /// national company.
+    ///
+    /// # Default implementation
+    ///
+    /// The default implementation returns `None`.
+    fn national_formation(&self) -> Option<NationalFormation> {
+        None
+    }
+
+    /// Returns `true` if the national company can be formed in the current
+    /// game phase (i.e., its formation phase or any later phase).
+    fn can_form_national(&self) -> bool {
+        self.national_formation()
+            .and_then(|nf| {
+                self.phase_names().iter().position(|name| *name == nf.phase)
+            })
+            .is_some_and(|ix| self.phase_ix() >= ix)
+    }
+
+    /// Forms the national company, if it can be formed in the current game
+    /// phase, and returns the token changes for each converted company.
+    ///
+    /// See [NationalFormation::convert_companies] for details.
+    fn form_national(
+        &self,
+        map: &mut Map,
+    ) -> Option<Vec<(String, Vec<TokenChange>)>> {
+        if !self.can_form_national() {
+            return None;
+        }
+        self.national_formation()
+            .map(|formation| formation.convert_companies(map))
+    }
+
+    /// Returns `true` if this game (or its current map variant) supports a
+    /// random setup (see [Game::randomise_setup]).
+    ///
+    /// # Default implementation
+    ///
+    /// The default implementation returns `false`.
+    fn has_random_setup(&self) -> bool {
+        false
+    }
+
+    /// Randomises the game setup, such as the private companies that are
+    /// available or the contents of the starting packet, and returns the
+    /// choices that were made.
+    ///
+    /// The same `seed` must always produce the same setup (see
+    /// [setup::SetupRng]), so that saved games can be reproduced.
+    /// This should be called before [Game::create_map], because the setup
+    /// may affect the starting map.
+    ///
+    /// # Default implementation
+    ///
+    /// The default implementation returns `None`, which indicates that this
+    /// game does not support a random setup.
+    fn randomise_setup(&mut self, _seed: u64) -> Option<setup::Setup> {
+        None
+    }
+
+    /// Restores the standard (non-random) game setup.
+    ///
+    /// # Default implementation
+    ///
+    /// The default implementation does nothing.
+    fn standard_setup(&mut self) {}
+
+    /// Returns the seed for the current game setup, or `None` if the game
+    /// uses the standard setup.
+    ///
+    /// # Default implementation
+    ///
+    /// The default implementation returns `None`.
+    fn setup_seed(&self) -> Option<u64> {
+        None
+    }
+
     /// Returns a snapshot of the game state.
+    ///
+    /// Note that the returned game state has an empty journal and train
+    /// supply; use [GameState::with_journal] to record the moves that have
+    /// been made, and [GameState::with_train_supply] to record the trains
+    /// that have been bought.
+    /// It also has no custom title; use [GameState::with_title] to add one.
+    /// No private companies are owned or closed; use
+    /// [GameState::with_privates] to record these.
+    /// No company treasuries are tracked; use [GameState::with_treasuries]
+    /// to record these.
     fn save(&self, map: &Map) -> GameState {
         GameState {
             game: self.name().to_string(),
+            variant: self.variant().to_string(),
             phase: self.current_phase_name().to_string(),
             map: map.into(),
+            journal: Journal::default(),
+            trains: TrainSupply::default(),
+            seed: self.setup_seed(),
+            title: None,
+            privates: PrivateHoldings::default(),
+            treasuries: Treasuries::default(),
         }
     }
 
     /// Loads a game state and returns the game map.
     ///
-    /// Note that this also updates the game phase.
+    /// Note that this also updates the map variant, the game setup, and the
+    /// game phase.
     fn load(&mut self, hex: &Hex, state: GameState) -> Option<Map> {
-        if state.game != self.name() {
+        if state.game != self.name() || !self.set_variant(&state.variant) {
             return None;
         }
+        if let Some(seed) = state.seed {
+            self.randomise_setup(seed)?;
+        } else {
+            self.standard_setup();
+        }
         let mut map = self.create_map(hex);
         if !self.set_phase_name(&mut map, &state.phase) {
             return None;
@@ -537,10 +1411,45 @@ pub trait Game {
     }
 }
 
+/// Returns the [RouteTokenRule] for the company that owns `token`, as defined
+/// by [Game::route_token_rule], or [RouteTokenRule::AnyToken] if `token` is
+/// not one of the map's tokens.
+fn token_rule_for<G: Game + ?Sized>(
+    game: &G,
+    map: &Map,
+    token: &Token,
+) -> RouteTokenRule {
+    map.try_token_name(token)
+        .map(|abbrev| game.route_token_rule(map, abbrev))
+        .unwrap_or_default()
+}
+
+/// The rules that determine which routes a company can operate, and how much
+/// revenue each route earns, as used by [default_best_routes].
+#[derive(Clone, Copy)]
+pub struct RouteRules<'a> {
+    /// Which city (or cities) every route must include.
+    pub token_rule: RouteTokenRule,
+    /// The elements that a single route cannot use more than once.
+    pub conflict_rule: ConflictRule,
+    /// The elements that routes operated by different trains cannot share.
+    pub route_conflict_rules: &'a RouteConflictRules,
+    /// Calculates the revenue earned by each train.
+    pub scoring: &'a dyn Scoring,
+}
+
 /// The default implementation for finding routes that earn the most revenue.
 ///
-/// This finds all valid paths with [n18route::paths_for_token] and selects
-/// the best combination with [n18route::Trains::select_routes].
+/// Any [marker bonuses](Bonus::MarkerBonus) are first resolved against the
+/// markers on the map, with [Bonus::resolve_markers].
+/// This finds all valid paths that satisfy the token rule in `rules` with
+/// [n18route::paths_for_token_cached], reusing the paths in `cache` from
+/// previous searches of the same map, and selects the best combination with
+/// [n18route::Trains::select_routes_with], where the revenue earned by each
+/// train is calculated by the scoring rule in `rules`.
+/// If the trains' routes are subject to different route conflict rules, the
+/// best combination is selected with
+/// [n18route::Trains::select_routes_with_rules].
 ///
 /// While this should be sufficient for many 18xx games, some games may
 /// need to use a different approach.
@@ -549,12 +1458,13 @@ pub fn default_best_routes(
     token: Token,
     trains: &Trains,
     bonuses: Vec<Bonus>,
-    conflict_rule: ConflictRule,
-    route_conflict_rule: ConflictRule,
+    rules: &RouteRules,
+    cache: &mut PathCache,
 ) -> Option<Routes> {
     if trains.is_empty() {
         return None;
     }
+    let bonuses = Bonus::resolve_markers(bonuses, map);
 
     let start = std::time::Instant::now();
     info!("");
@@ -564,23 +1474,86 @@ pub fn default_best_routes(
     let criteria = n18route::Criteria {
         token,
         path_limit,
-        conflict_rule,
-        route_conflict_rule,
+        conflict_rule: rules.conflict_rule,
+        route_conflict_rule: rules.route_conflict_rules.most_permissive(),
+        token_rule: rules.token_rule,
     };
 
-    let paths = n18route::paths_for_token(map, &criteria);
+    let paths = n18route::paths_for_token_cached(map, cache, &criteria);
     info!(
         "Enumerated {} routes in {}",
         paths.len(),
         start.elapsed().as_secs_f64()
     );
 
-    let now = std::time::Instant::now();
-    let routes = trains.select_routes(paths, bonuses);
+    let routes = select_best_routes(
+        map,
+        paths,
+        trains,
+        bonuses,
+        rules.route_conflict_rules,
+        rules.scoring,
+    );
+    info!(
+        "Searching for the best routes took {}",
+        start.elapsed().as_secs_f64()
+    );
+    routes
+}
+
+/// The default implementation for finding routes from a single city that
+/// earn the most revenue, for a company that has no tokens on the map.
+///
+/// This finds all valid paths that pass through the city `city_ix` on the
+/// tile at `addr` with [n18route::paths_from_city], and selects the best
+/// combination in the same way as [default_best_routes], without any
+/// bonuses.
+///
+/// Returns `None` if there are no trains, or no such city.
+pub fn default_open_routes(
+    map: &Map,
+    addr: HexAddress,
+    city_ix: usize,
+    trains: &Trains,
+    conflict_rule: ConflictRule,
+    route_conflict_rules: &RouteConflictRules,
+    scoring: &dyn Scoring,
+) -> Option<Routes> {
+    if trains.is_empty() {
+        return None;
+    }
+    // NOTE: the search criteria must identify a token, but this token is
+    // ignored when searching for paths from a specific city.
+    let token = map
+        .token_names()
+        .first()
+        .and_then(|name| map.try_token(name))?;
+
+    let start = std::time::Instant::now();
+    info!("");
+    info!("Searching for the best routes from {} ...", addr);
 
+    let criteria = n18route::Criteria {
+        token,
+        path_limit: trains.path_limit(),
+        conflict_rule,
+        route_conflict_rule: route_conflict_rules.most_permissive(),
+        token_rule: RouteTokenRule::AnyToken,
+    };
+    let paths = n18route::paths_from_city(map, &criteria, addr, city_ix);
     info!(
-        "Calculated (train, path) revenues in {}",
-        now.elapsed().as_secs_f64()
+        "Enumerated {} routes in {}",
+        paths.len(),
+        start.elapsed().as_secs_f64()
+    );
+
+    let routes = select_best_routes(
+        map,
+        paths,
+        trains,
+        vec![],
+        route_conflict_rules,
+        scoring,
     );
     info!(
         "Searching for the best routes took {}",
@@ -589,12 +1562,153 @@ pub fn default_best_routes(
     routes
 }
 
+/// Selects the combination of `paths` that earns the most revenue for the
+/// provided trains.
+fn select_best_routes(
+    map: &Map,
+    paths: Vec<n18route::Path>,
+    trains: &Trains,
+    bonuses: Vec<Bonus>,
+    route_conflict_rules: &RouteConflictRules,
+    scoring: &dyn Scoring,
+) -> Option<Routes> {
+    let now = std::time::Instant::now();
+    let routes = if route_conflict_rules.is_uniform() {
+        trains.select_routes_with(paths, bonuses, scoring)
+    } else {
+        trains.select_routes_with_rules(
+            map,
+            paths,
+            bonuses,
+            scoring,
+            route_conflict_rules,
+        )
+    };
+    info!(
+        "Calculated (train, path) revenues in {}",
+        now.elapsed().as_secs_f64()
+    );
+    routes
+}
+
 /// Describes the current game state.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct GameState {
     /// A unique identifier for the game.
     pub game: String,
+    /// The map variant, see [Game::variants].
+    pub variant: String,
     /// The current game phase.
     pub phase: String,
     /// The current map state.
     pub map: n18map::descr::Descr,
+    /// The moves that have been made in this game.
+    pub journal: Journal,
+    /// The trains held by the bank and available in the open market, which
+    /// is empty if no trains have been bought.
+    ///
+    /// Note that this does not record the game phase associated with each
+    /// train type; use [TrainSupply::restore_counts] to update the supply
+    /// returned by [Game::train_supply].
+    pub trains: TrainSupply,
+    /// The seed for the random game setup, or `None` if the game uses the
+    /// standard setup (see [Game::randomise_setup]).
+    pub seed: Option<u64>,
+    /// A custom title that identifies this game (e.g., "Tuesday club game"),
+    /// which is shown alongside the game name.
+    pub title: Option<String>,
+    /// The company that owns each private company, and the private companies
+    /// that have been closed.
+    pub privates: PrivateHoldings,
+    /// The cash held by each company whose treasury is being tracked.
+    pub treasuries: Treasuries,
+}
+
+impl GameState {
+    /// Replaces the journal of moves that have been made in this game.
+    pub fn with_journal(mut self, journal: Journal) -> Self {
+        self.journal = journal;
+        self
+    }
+
+    /// Replaces the trains held by the bank and available in the open
+    /// market.
+    pub fn with_train_supply(mut self, trains: TrainSupply) -> Self {
+        self.trains = trains;
+        self
+    }
+
+    /// Replaces the custom title of this game.
+    pub fn with_title(mut self, title: Option<String>) -> Self {
+        self.title = title;
+        self
+    }
+
+    /// Replaces the owners of each private company, and the private
+    /// companies that have been closed.
+    pub fn with_privates(mut self, privates: PrivateHoldings) -> Self {
+        self.privates = privates;
+        self
+    }
+
+    /// Replaces the cash held by each company whose treasury is being
+    /// tracked.
+    pub fn with_treasuries(mut self, treasuries: Treasuries) -> Self {
+        self.treasuries = treasuries;
+        self
+    }
+}
+
+/// A log of the moves that have been made in a game, such as placing tiles
+/// and tokens, and changing the game phase.
+///
+/// Each entry is a human-readable description of a single move, such as
+/// "Placed tile 57 at D10 (rotation 1)".
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Journal {
+    entries: Vec<String>,
+}
+
+impl Journal {
+    /// Returns an empty journal.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an entry to the end of the journal.
+    pub fn record<S: Into<String>>(&mut self, entry: S) {
+        self.entries.push(entry.into())
+    }
+
+    /// Returns the journal entries, from oldest to newest.
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Returns the number of journal entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the journal contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all journal entries.
+    pub fn clear(&mut self) {
+        self.entries.clear()
+    }
+}
+
+impl From<Vec<String>> for Journal {
+    fn from(entries: Vec<String>) -> Self {
+        Journal { entries }
+    }
+}
+
+impl From<Journal> for Vec<String> {
+    fn from(journal: Journal) -> Self {
+        journal.entries
+    }
 }
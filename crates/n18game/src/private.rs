@@ -0,0 +1,134 @@
+//! Private companies, which do not operate trains, but pay a fixed revenue
+//! to their owner and may have special abilities that affect the map.
+//!
+//! Each game defines its private companies (see
+//! [Game::privates](crate::Game::privates)), and the [PrivateHoldings]
+//! record which company (if any) owns each private company, and which
+//! private companies have closed.
+//!
+//! ```rust
+//! # use n18game::private::{Ability, PrivateHoldings};
+//! # use n18game::Game;
+//! let game = n18game::new_1830();
+//! let dh = game.try_private("D&H").unwrap();
+//! let addr = game.coordinate_system().parse("P6").unwrap();
+//! assert_eq!(dh.abilities[0], Ability::BlocksHex(addr));
+//!
+//! // Only the company that owns the D&H may lay a tile on its hex.
+//! let mut holdings = PrivateHoldings::default();
+//! assert!(game.reserving_private(&holdings, addr, Some("PRR")).is_some());
+//! holdings.set_owner("D&H", "PRR");
+//! assert!(game.reserving_private(&holdings, addr, Some("PRR")).is_none());
+//! assert!(game.reserving_private(&holdings, addr, Some("NYC")).is_some());
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use n18map::HexAddress;
+
+/// The special abilities of private companies that affect the map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Ability {
+    /// Tiles cannot be placed on this hex while the private company is open,
+    /// except by the company that owns it.
+    BlocksHex(HexAddress),
+    /// The company that owns the private company may place a tile on this
+    /// hex for free, in addition to its normal tile placement.
+    FreeTileLay(HexAddress),
+}
+
+/// The details that characterise a private company.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Private {
+    /// The abbreviated private company name, which must be unique for each
+    /// private company in the game.
+    pub abbrev: String,
+    /// The full private company name, used for display purposes.
+    pub full_name: String,
+    /// The face value of the private company.
+    pub cost: usize,
+    /// The revenue that the private company pays to its owner in each
+    /// operating round.
+    pub revenue: usize,
+    /// The special abilities of the private company.
+    pub abilities: Vec<Ability>,
+    /// The index of the game phase in which the private company closes, if
+    /// any.
+    pub closing_phase: Option<usize>,
+}
+
+impl Private {
+    /// Returns `true` if this private company has closed by the specified
+    /// game phase.
+    pub fn closes_by(&self, phase: usize) -> bool {
+        self.closing_phase.is_some_and(|closing| phase >= closing)
+    }
+
+    /// Returns `true` if this private company prevents other companies from
+    /// placing tiles on the hex at `addr`.
+    pub fn blocks(&self, addr: HexAddress) -> bool {
+        self.abilities.contains(&Ability::BlocksHex(addr))
+    }
+
+    /// Returns the hexes on which the company that owns this private
+    /// company may place a tile for free.
+    pub fn free_tile_lays(&self) -> impl Iterator<Item = HexAddress> + '_ {
+        self.abilities.iter().filter_map(|ability| match ability {
+            Ability::FreeTileLay(addr) => Some(*addr),
+            _ => None,
+        })
+    }
+}
+
+/// Records which company (if any) owns each private company, and which
+/// private companies have been closed.
+///
+/// Private companies that close at the start of a game phase (see
+/// [Private::closing_phase]) do not need to be closed explicitly.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PrivateHoldings {
+    owners: BTreeMap<String, String>,
+    closed: BTreeSet<String>,
+}
+
+impl PrivateHoldings {
+    /// Returns `true` if no private company is owned by a company or has
+    /// been closed.
+    pub fn is_empty(&self) -> bool {
+        self.owners.is_empty() && self.closed.is_empty()
+    }
+
+    /// Returns the company that owns a private company, if any.
+    pub fn owner(&self, private: &str) -> Option<&str> {
+        self.owners.get(private).map(|owner| owner.as_str())
+    }
+
+    /// Records that a company now owns a private company.
+    pub fn set_owner(&mut self, private: &str, company: &str) {
+        self.owners.insert(private.to_string(), company.to_string());
+    }
+
+    /// Returns each private company that is owned by a company, and the
+    /// company that owns it.
+    pub fn owners(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.owners
+            .iter()
+            .map(|(private, owner)| (private.as_str(), owner.as_str()))
+    }
+
+    /// Closes a private company, which no longer has an owner.
+    pub fn close(&mut self, private: &str) {
+        self.owners.remove(private);
+        self.closed.insert(private.to_string());
+    }
+
+    /// Returns `true` if a private company has been closed.
+    pub fn is_closed(&self, private: &str) -> bool {
+        self.closed.contains(private)
+    }
+
+    /// Returns each private company that has been closed.
+    pub fn closed(&self) -> impl Iterator<Item = &str> {
+        self.closed.iter().map(|private| private.as_str())
+    }
+}
@@ -0,0 +1,176 @@
+//! Randomise the setup of games that have variable starting conditions.
+//!
+//! Some games (or map variants) randomly select, e.g., the private companies
+//! that are available or the contents of the starting packet.
+//! Games that support this implement
+//! [Game::randomise_setup](crate::Game::randomise_setup), which uses a
+//! [SetupRng] to make each random choice, so that the same seed always
+//! produces the same setup.
+//! The seed is recorded in the [GameState](crate::GameState), and the setup
+//! is reproduced when a saved game is loaded.
+//!
+//! ```rust
+//! # use n18game::setup::SetupRng;
+//! let privates = ["SVNRR", "C&SL", "D&H", "M&H", "C&A", "B&O"];
+//! let mut rng = SetupRng::new(1830);
+//! let chosen = rng.choose_multiple(&privates, 4);
+//! assert_eq!(chosen.len(), 4);
+//! // The same seed always makes the same choices.
+//! assert_eq!(SetupRng::new(1830).choose_multiple(&privates, 4), chosen);
+//! ```
+
+/// A deterministic pseudo-random number generator for choosing a game setup.
+///
+/// This implements the SplitMix64 algorithm, which is simple, fast, and
+/// produces the same sequence of numbers on every platform.
+/// It is **not** suitable for cryptographic purposes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SetupRng {
+    state: u64,
+}
+
+impl SetupRng {
+    /// Returns a generator that is initialised with the provided seed.
+    pub fn new(seed: u64) -> Self {
+        SetupRng { state: seed }
+    }
+
+    /// Returns the next pseudo-random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a pseudo-random number in the range `0..bound`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bound` is zero.
+    pub fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "Cannot choose a number below zero");
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Shuffles the items into a pseudo-random order.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for ix in (1..items.len()).rev() {
+            let other = self.below(ix + 1);
+            items.swap(ix, other);
+        }
+    }
+
+    /// Returns `count` distinct items (or every item, if there are fewer
+    /// than `count` items), chosen in a pseudo-random order.
+    pub fn choose_multiple<T: Clone>(
+        &mut self,
+        items: &[T],
+        count: usize,
+    ) -> Vec<T> {
+        let mut items = items.to_vec();
+        self.shuffle(&mut items);
+        items.truncate(count);
+        items
+    }
+}
+
+/// Describes the random choices that were made when setting up a game.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Setup {
+    /// The seed from which the choices were made.
+    pub seed: u64,
+    /// The random choices that were made.
+    pub choices: Vec<SetupChoice>,
+}
+
+/// Describes a single random choice, such as the private companies that are
+/// available in this game.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SetupChoice {
+    /// What was chosen (e.g., "Private companies").
+    pub name: String,
+    /// The chosen items.
+    pub selected: Vec<String>,
+}
+
+impl Setup {
+    /// Returns a human-readable description of each random choice, which
+    /// can be recorded in the game [Journal](crate::Journal).
+    pub fn describe(&self) -> Vec<String> {
+        std::iter::once(format!(
+            "Randomised the game setup (seed {})",
+            self.seed
+        ))
+        .chain(self.choices.iter().map(|choice| {
+            format!("{}: {}", choice.name, choice.selected.join(", "))
+        }))
+        .collect()
+    }
+}
+
+/// Returns a seed for a new random setup, based on the current time.
+pub fn new_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Setup, SetupChoice, SetupRng};
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = SetupRng::new(42);
+        let mut b = SetupRng::new(42);
+        let seq_a: Vec<u64> = (0..8).map(|_| a.next_u64()).collect();
+        let seq_b: Vec<u64> = (0..8).map(|_| b.next_u64()).collect();
+        assert_eq!(seq_a, seq_b);
+        let mut c = SetupRng::new(43);
+        let seq_c: Vec<u64> = (0..8).map(|_| c.next_u64()).collect();
+        assert_ne!(seq_a, seq_c);
+    }
+
+    #[test]
+    fn test_shuffle_is_a_permutation() {
+        let mut rng = SetupRng::new(7);
+        let mut items: Vec<usize> = (0..20).collect();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..20).collect::<Vec<_>>());
+        items.sort();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_choose_multiple() {
+        let items = ["A", "B", "C"];
+        let mut rng = SetupRng::new(1);
+        let mut chosen = rng.choose_multiple(&items, 2);
+        assert_eq!(chosen.len(), 2);
+        chosen.sort();
+        chosen.dedup();
+        assert_eq!(chosen.len(), 2);
+        assert_eq!(rng.choose_multiple(&items, 5).len(), 3);
+    }
+
+    #[test]
+    fn test_describe_setup() {
+        let setup = Setup {
+            seed: 12,
+            choices: vec![SetupChoice {
+                name: "Private companies".to_string(),
+                selected: vec!["B&O".to_string(), "C&A".to_string()],
+            }],
+        };
+        assert_eq!(
+            setup.describe(),
+            vec![
+                "Randomised the game setup (seed 12)".to_string(),
+                "Private companies: B&O, C&A".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,1212 @@
+//! Apply moves to a saved game state, without a user interface.
+//!
+//! A [Move] describes a single change to the game, such as laying a tile,
+//! placing a token, buying a train, or advancing to the next game phase.
+//! [apply] checks that the move is valid, and returns a new [GameState] that
+//! includes the move and its journal entries, so that headless tools and
+//! tests can drive a game by replaying a sequence of moves:
+//!
+//! ```rust
+//! # use n18game::Game;
+//! # use n18game::sim::{apply, Move, MoveError};
+//! # use n18hex::{Hex, RotateCW};
+//! let mut game = n18game::new_1830();
+//! let map = game.create_map(&Hex::default());
+//! let state = game.save(&map);
+//! let addr = game.coordinate_system().parse("C7").unwrap();
+//! let lay_tile = Move::LayTile {
+//!     addr,
+//!     tile: "8".to_string(),
+//!     rotation: RotateCW::Zero,
+//!     company: None,
+//! };
+//! let state = apply(&mut game, &state, &lay_tile).unwrap();
+//! assert_eq!(state.journal.entries(), ["Placed tile 8 at C7 (rotation 0)"]);
+//! // Yellow tiles cannot be placed on top of each other.
+//! let result = apply(&mut game, &state, &lay_tile);
+//! assert!(matches!(result, Err(MoveError::Tile(_))));
+//! ```
+//!
+//! Use a [Simulation] to apply a sequence of moves without saving the game
+//! state after each move, and to retrieve the routes found by
+//! [Move::RunRoutes], and use a [Board] to apply moves to a game state that
+//! is owned by another type (e.g., a user interface).
+
+use n18hex::{Hex, RotateCW};
+use n18map::{HexAddress, Map, Placement, TokenPlacement};
+use n18route::{PathCache, Routes, Train, Trains};
+
+use crate::bank::{TrainSupply, TrainTransfer, TransferError};
+use crate::private::PrivateHoldings;
+use crate::treasury::Treasuries;
+use crate::{Game, GameState, Journal};
+
+/// A single move that changes the state of a game.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Move {
+    /// Places a tile on an empty hex, or upgrades the current tile.
+    ///
+    /// Only the company that owns a private company can place tiles on the
+    /// hexes that the private company blocks (see
+    /// [Game::reserving_private]).
+    LayTile {
+        addr: HexAddress,
+        tile: String,
+        rotation: RotateCW,
+        company: Option<String>,
+    },
+    /// Removes the tile (and any tokens) from a map hex.
+    RemoveTile { addr: HexAddress },
+    /// Changes the rotation of the tile on a map hex (e.g., to correct a
+    /// tile that was placed with the wrong rotation).
+    RotateTile {
+        addr: HexAddress,
+        rotation: RotateCW,
+    },
+    /// Places a company's token in a token space (identified by its index)
+    /// of the tile at `addr`.
+    ///
+    /// If the company's treasury is being tracked, the cost of the token
+    /// (see [Game::token_cost]) is removed from its treasury.
+    PlaceToken {
+        addr: HexAddress,
+        space: usize,
+        company: String,
+    },
+    /// Removes the token (if any) from a token space (identified by its
+    /// index) of the tile at `addr`.
+    RemoveToken { addr: HexAddress, space: usize },
+    /// Buys a train from the bank or the open market, or discards a train to
+    /// the open market.
+    /// Buying the first train of a new type may advance the game phase.
+    TransferTrain(TrainTransfer),
+    /// Advances to the next game phase.
+    AdvancePhase,
+    /// Changes to a specific game phase (identified by its index).
+    SetPhase(usize),
+    /// A company buys a private company.
+    BuyPrivate { private: String, company: String },
+    /// Closes a private company.
+    ClosePrivate(String),
+    /// Sets the cash held by a company, and begins tracking the company's
+    /// treasury.
+    SetCash { company: String, cash: usize },
+    /// Finds the optimal routes for a company's trains, and records the
+    /// revenue in the journal.
+    RunRoutes {
+        company: String,
+        trains: Vec<String>,
+        bonuses: Vec<bool>,
+    },
+}
+
+/// The reasons why a move cannot be applied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MoveError {
+    /// The game state could not be loaded for this game.
+    InvalidState,
+    /// The tile cannot be placed.
+    Tile(Placement),
+    /// There is no tile on this map hex.
+    NoTile(HexAddress),
+    /// The token cannot be placed.
+    Token(TokenPlacement),
+    /// The tile at this hex has no token space with this index.
+    NoTokenSpace { addr: HexAddress, space: usize },
+    /// There is already a token in this token space.
+    Occupied { addr: HexAddress, space: usize },
+    /// There is no token in this token space.
+    NoToken { addr: HexAddress, space: usize },
+    /// The company has already placed all of its tokens.
+    NoTokensLeft(String),
+    /// The company does not have enough cash to pay for the token.
+    InsufficientCash {
+        company: String,
+        cost: usize,
+        cash: usize,
+    },
+    /// There is no company with this name.
+    UnknownCompany(String),
+    /// There is no train type with this name.
+    UnknownTrain(String),
+    /// There is no private company with this name.
+    UnknownPrivate(String),
+    /// The private company has closed.
+    PrivateClosed(String),
+    /// The hex is reserved by a private company, which the company that
+    /// placed the tile does not own.
+    Reserved { addr: HexAddress, private: String },
+    /// The company cannot operate this many trains in the current game
+    /// phase (see [Game::train_limit]).
+    TooManyTrains {
+        company: String,
+        count: usize,
+        limit: usize,
+    },
+    /// The train transfer is not allowed.
+    Transfer(TransferError),
+    /// The game is already in its final phase.
+    FinalPhase,
+    /// There is no game phase with this index.
+    InvalidPhase(usize),
+}
+
+impl std::fmt::Display for MoveError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> Result<(), std::fmt::Error> {
+        use MoveError::*;
+        match self {
+            InvalidState => write!(f, "The game state is not valid"),
+            Tile(placement) => {
+                write!(f, "The tile cannot be placed: {:?}", placement)
+            }
+            Token(placement) => {
+                write!(f, "The token cannot be placed: {:?}", placement)
+            }
+            NoTile(addr) => write!(f, "There is no tile at {}", addr),
+            NoTokenSpace { addr, space } => {
+                write!(f, "There is no token space {} at {}", space, addr)
+            }
+            Occupied { addr, space } => {
+                write!(f, "Token space {} at {} is occupied", space, addr)
+            }
+            NoToken { addr, space } => {
+                write!(f, "There is no token in space {} at {}", space, addr)
+            }
+            NoTokensLeft(name) => {
+                write!(f, "{} has placed all of its tokens", name)
+            }
+            InsufficientCash {
+                company,
+                cost,
+                cash,
+            } => write!(
+                f,
+                "{} cannot pay ${} for a token, it only has ${}",
+                company, cost, cash
+            ),
+            UnknownCompany(name) => write!(f, "There is no company {}", name),
+            UnknownTrain(name) => write!(f, "There is no {}-train", name),
+            UnknownPrivate(name) => {
+                write!(f, "There is no private company {}", name)
+            }
+            PrivateClosed(name) => write!(f, "{} has closed", name),
+            Reserved { addr, private } => {
+                write!(f, "The hex {} is reserved by {}", addr, private)
+            }
+            TooManyTrains {
+                company,
+                count,
+                limit,
+            } => write!(
+                f,
+                "{} can operate at most {} trains, not {}",
+                company, limit, count
+            ),
+            Transfer(error) => write!(f, "{}", error),
+            FinalPhase => write!(f, "The game is in its final phase"),
+            InvalidPhase(ix) => write!(f, "Cannot change to phase #{}", ix),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+impl From<TransferError> for MoveError {
+    fn from(error: TransferError) -> Self {
+        MoveError::Transfer(error)
+    }
+}
+
+/// Applies a move to a saved game state, and returns the new game state.
+///
+/// The `game` is updated to match the new game state (e.g., its current
+/// phase).
+/// The saved game state must be valid for this game (see
+/// `n18io::check_map_descr`).
+pub fn apply<G: Game + ?Sized>(
+    game: &mut G,
+    state: &GameState,
+    mv: &Move,
+) -> Result<GameState, MoveError> {
+    let mut sim = Simulation::load(game, state)?;
+    sim.apply(mv)?;
+    Ok(sim.save())
+}
+
+/// A game in progress, to which moves can be applied.
+pub struct Simulation<'a, G: Game + ?Sized> {
+    game: &'a mut G,
+    map: Map,
+    journal: Journal,
+    trains: TrainSupply,
+    title: Option<String>,
+    privates: PrivateHoldings,
+    treasuries: Treasuries,
+    /// The paths found when running routes, which are reused until the map
+    /// is modified.
+    path_cache: PathCache,
+}
+
+impl<'a, G: Game + ?Sized> Simulation<'a, G> {
+    /// Loads a saved game state.
+    pub fn load(
+        game: &'a mut G,
+        state: &GameState,
+    ) -> Result<Self, MoveError> {
+        let map = game
+            .load(&Hex::default(), state.clone())
+            .ok_or(MoveError::InvalidState)?;
+        let mut trains = game.train_supply();
+        trains.restore_counts(&state.trains);
+        Ok(Simulation {
+            game,
+            map,
+            journal: state.journal.clone(),
+            trains,
+            title: state.title.clone(),
+            privates: state.privates.clone(),
+            treasuries: state.treasuries.clone(),
+            path_cache: PathCache::new(),
+        })
+    }
+
+    /// Returns the game.
+    pub fn game(&self) -> &G {
+        self.game
+    }
+
+    /// Returns the current map state.
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    /// Returns the moves that have been made in this game.
+    pub fn journal(&self) -> &Journal {
+        &self.journal
+    }
+
+    /// Returns the trains held by the bank and available in the open market.
+    pub fn trains(&self) -> &TrainSupply {
+        &self.trains
+    }
+
+    /// Returns the owner of each private company, and the private companies
+    /// that have been closed.
+    pub fn privates(&self) -> &PrivateHoldings {
+        &self.privates
+    }
+
+    /// Returns the cash held by each company whose treasury is being
+    /// tracked.
+    pub fn treasuries(&self) -> &Treasuries {
+        &self.treasuries
+    }
+
+    /// Saves the current game state.
+    pub fn save(&self) -> GameState {
+        self.game
+            .save(&self.map)
+            .with_journal(self.journal.clone())
+            .with_train_supply(self.trains.clone())
+            .with_title(self.title.clone())
+            .with_privates(self.privates.clone())
+            .with_treasuries(self.treasuries.clone())
+    }
+
+    /// Applies a move, and records it in the journal.
+    ///
+    /// Returns the optimal routes for [Move::RunRoutes], if any were found,
+    /// and `None` for every other move.
+    /// If the move is not valid, the game state is not modified.
+    pub fn apply(&mut self, mv: &Move) -> Result<Option<Routes>, MoveError> {
+        let mut board = Board {
+            game: &mut *self.game,
+            map: &mut self.map,
+            journal: &mut self.journal,
+            trains: &mut self.trains,
+            privates: &mut self.privates,
+            treasuries: &mut self.treasuries,
+            path_cache: &mut self.path_cache,
+            free_placement: false,
+        };
+        board.apply(mv)
+    }
+}
+
+/// The state of a game in progress that is owned by another type (e.g., a
+/// user interface), to which moves can be applied.
+pub struct Board<'b, G: Game + ?Sized> {
+    pub game: &'b mut G,
+    pub map: &'b mut Map,
+    pub journal: &'b mut Journal,
+    pub trains: &'b mut TrainSupply,
+    pub privates: &'b mut PrivateHoldings,
+    pub treasuries: &'b mut Treasuries,
+    pub path_cache: &'b mut PathCache,
+    /// Whether tiles can be placed on any map hex, ignoring the tile
+    /// placement rules and the hexes reserved by private companies (e.g.,
+    /// when editing a map).
+    pub free_placement: bool,
+}
+
+impl<G: Game + ?Sized> Board<'_, G> {
+    /// Returns the name of a map hex in the game's coordinate system.
+    fn hex_name(&self, addr: HexAddress) -> String {
+        self.game
+            .coordinate_system()
+            .format(&addr)
+            .unwrap_or_else(|| addr.to_string())
+    }
+
+    /// Applies a move, and records it in the journal.
+    ///
+    /// Returns the optimal routes for [Move::RunRoutes], if any were found,
+    /// and `None` for every other move.
+    /// If the move is not valid, the game state is not modified.
+    pub fn apply(&mut self, mv: &Move) -> Result<Option<Routes>, MoveError> {
+        match mv {
+            Move::LayTile {
+                addr,
+                tile,
+                rotation,
+                company,
+            } => {
+                let reserved = self
+                    .game
+                    .reserving_private(
+                        self.privates,
+                        *addr,
+                        company.as_deref(),
+                    )
+                    .filter(|_| !self.free_placement);
+                if let Some(private) = reserved {
+                    return Err(MoveError::Reserved {
+                        addr: *addr,
+                        private: private.abbrev.clone(),
+                    });
+                }
+                let previous =
+                    self.map.tile_at(*addr).map(|t| t.name.clone());
+                let placed = if self.free_placement {
+                    self.place_any_tile(*addr, tile, *rotation)
+                } else {
+                    self.map.try_place_tile(*addr, tile, *rotation, None)
+                };
+                if !placed.is_ok() {
+                    return Err(MoveError::Tile(placed));
+                }
+                let entry = tile_entry(
+                    previous.as_deref(),
+                    tile,
+                    &self.hex_name(*addr),
+                    *rotation,
+                );
+                self.journal.record(entry);
+            }
+            Move::RemoveTile { addr } => {
+                let tile = self
+                    .map
+                    .tile_at(*addr)
+                    .map(|t| t.name.clone())
+                    .ok_or(MoveError::NoTile(*addr))?;
+                self.map.remove_tile(*addr);
+                let entry = remove_tile_entry(&tile, &self.hex_name(*addr));
+                self.journal.record(entry);
+            }
+            Move::RotateTile { addr, rotation } => {
+                let tile = self
+                    .map
+                    .tile_at(*addr)
+                    .map(|t| t.name.clone())
+                    .ok_or(MoveError::NoTile(*addr))?;
+                if let Some(hex_state) = self.map.hex_state_mut(*addr) {
+                    // NOTE: a tile can be rotated by at most five turns.
+                    for _ in 0..5 {
+                        if hex_state.rotation() == rotation {
+                            break;
+                        }
+                        hex_state.rotate_cw()
+                    }
+                }
+                let hex_name = self.hex_name(*addr);
+                let entry = rotate_tile_entry(&tile, &hex_name, *rotation);
+                self.journal.record(entry);
+            }
+            Move::PlaceToken {
+                addr,
+                space,
+                company,
+            } => {
+                let token =
+                    *self.game.try_token(company).ok_or_else(|| {
+                        MoveError::UnknownCompany(company.clone())
+                    })?;
+                let limit = self
+                    .game
+                    .company_rules(company)
+                    .and_then(|rules| rules.token_count);
+                let placed = self.map.find_placed_tokens(&token).len();
+                if limit.is_some_and(|limit| placed >= limit) {
+                    return Err(MoveError::NoTokensLeft(company.clone()));
+                }
+                let token_space = self.token_space(*addr, *space)?;
+                let occupied = self
+                    .map
+                    .hex_state(*addr)
+                    .and_then(|hs| hs.token_at(&token_space))
+                    .is_some();
+                if occupied {
+                    return Err(MoveError::Occupied {
+                        addr: *addr,
+                        space: *space,
+                    });
+                }
+                let cost = self.token_cost(company, *addr, &token);
+                if let Some(cash) = self.treasuries.cash(company) {
+                    if cash < cost {
+                        return Err(MoveError::InsufficientCash {
+                            company: company.clone(),
+                            cost,
+                            cash,
+                        });
+                    }
+                }
+                let rule = self.game.token_rule();
+                self.map
+                    .try_place_token(*addr, &token_space, token, rule)
+                    .map_err(MoveError::Token)?;
+                let entry = place_token_entry(company, &self.hex_name(*addr));
+                self.journal.record(entry);
+                if cost > 0 && self.treasuries.debit(company, cost) {
+                    self.journal.record(token_cost_entry(company, cost));
+                }
+            }
+            Move::RemoveToken { addr, space } => {
+                let token_space = self.token_space(*addr, *space)?;
+                let hex_state = self.map.hex_state(*addr);
+                let token = hex_state
+                    .and_then(|hs| hs.token_at(&token_space))
+                    .copied()
+                    .ok_or(MoveError::NoToken {
+                        addr: *addr,
+                        space: *space,
+                    })?;
+                let name = self
+                    .map
+                    .try_token_name(&token)
+                    .unwrap_or("?")
+                    .to_string();
+                if let Some(hs) = self.map.hex_state_mut(*addr) {
+                    hs.remove_token_at(&token_space)
+                }
+                let entry = remove_token_entry(&name, &self.hex_name(*addr));
+                self.journal.record(entry);
+            }
+            Move::TransferTrain(transfer) => {
+                let phase_ix = self.game.phase_ix();
+                let new_phase = self.trains.apply(transfer, phase_ix)?;
+                self.journal.record(transfer_entry(transfer));
+                if let Some(phase) = new_phase {
+                    if self.game.set_phase_ix(self.map, phase) {
+                        let name = self.game.current_phase_name();
+                        self.journal.record(phase_entry(name));
+                    }
+                }
+            }
+            Move::AdvancePhase => {
+                if !self.game.next_phase(self.map) {
+                    return Err(MoveError::FinalPhase);
+                }
+                let name = self.game.current_phase_name();
+                self.journal.record(phase_entry(name));
+            }
+            Move::SetPhase(phase_ix) => {
+                if !self.game.set_phase_ix(self.map, *phase_ix) {
+                    return Err(MoveError::InvalidPhase(*phase_ix));
+                }
+                let name = self.game.current_phase_name();
+                self.journal.record(phase_entry(name));
+            }
+            Move::BuyPrivate { private, company } => {
+                let cost = self.open_private(private)?;
+                if self.game.try_company(company).is_none() {
+                    return Err(MoveError::UnknownCompany(company.clone()));
+                }
+                self.privates.set_owner(private, company);
+                let entry = buy_private_entry(company, private, cost);
+                self.journal.record(entry);
+            }
+            Move::ClosePrivate(private) => {
+                self.open_private(private)?;
+                self.privates.close(private);
+                self.journal.record(close_private_entry(private));
+            }
+            Move::SetCash { company, cash } => {
+                if self.game.try_company(company).is_none() {
+                    return Err(MoveError::UnknownCompany(company.clone()));
+                }
+                self.treasuries.set_cash(company, *cash);
+                self.journal.record(cash_entry(company, *cash));
+            }
+            Move::RunRoutes {
+                company,
+                trains,
+                bonuses,
+            } => {
+                let token =
+                    *self.game.try_token(company).ok_or_else(|| {
+                        MoveError::UnknownCompany(company.clone())
+                    })?;
+                if let Some(limit) = self.game.train_limit(company) {
+                    if trains.len() > limit {
+                        return Err(MoveError::TooManyTrains {
+                            company: company.clone(),
+                            count: trains.len(),
+                            limit,
+                        });
+                    }
+                }
+                let train_vec = trains
+                    .iter()
+                    .map(|name| {
+                        self.game.try_train(name).copied().ok_or_else(|| {
+                            MoveError::UnknownTrain(name.clone())
+                        })
+                    })
+                    .collect::<Result<Vec<Train>, MoveError>>()?;
+                let train_set: Trains = train_vec.into();
+                let routes = self.game.best_routes_cached(
+                    self.map,
+                    token,
+                    &train_set,
+                    bonuses.clone(),
+                    self.path_cache,
+                );
+                let revenue =
+                    routes.as_ref().map(|r| r.net_revenue).unwrap_or(0);
+                self.journal.record(routes_entry(company, trains, revenue));
+                return Ok(routes);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Places a tile without checking the tile placement rules.
+    fn place_any_tile(
+        &mut self,
+        addr: HexAddress,
+        tile: &str,
+        rotation: RotateCW,
+    ) -> Placement {
+        if self.map.tile(tile).is_none() {
+            Placement::UnknownTile
+        } else if !self.map.place_tile(addr, tile, rotation) {
+            Placement::NotReplaceable
+        } else {
+            Placement::Ok
+        }
+    }
+
+    /// Returns the cost of a private company, if it exists and is open.
+    fn open_private(&self, private: &str) -> Result<usize, MoveError> {
+        let cost =
+            self.game.try_private(private).map(|p| p.cost).ok_or_else(
+                || MoveError::UnknownPrivate(private.to_string()),
+            )?;
+        if !self.game.private_is_open(self.privates, private) {
+            return Err(MoveError::PrivateClosed(private.to_string()));
+        }
+        Ok(cost)
+    }
+
+    /// Returns the cost of placing a company's next token at `addr`.
+    fn token_cost(
+        &self,
+        company: &str,
+        addr: HexAddress,
+        token: &n18token::Token,
+    ) -> usize {
+        let placed = self.map.find_placed_tokens(token);
+        let orientation = self.map.orientation();
+        let distance = placed
+            .iter()
+            .map(|(placed_addr, _)| addr.distance(placed_addr, orientation))
+            .min()
+            .unwrap_or(0);
+        self.game.token_cost(company).cost(placed.len(), distance)
+    }
+
+    /// Returns the token space with index `space` on the tile at `addr`.
+    fn token_space(
+        &self,
+        addr: HexAddress,
+        space: usize,
+    ) -> Result<n18tile::TokenSpace, MoveError> {
+        self.map
+            .tile_at(addr)
+            .and_then(|tile| tile.token_spaces().get(space).copied())
+            .ok_or(MoveError::NoTokenSpace { addr, space })
+    }
+}
+
+/// Returns the journal entry for placing a tile on a map hex, where
+/// `previous` is the name of the tile that was replaced (if any).
+pub fn tile_entry(
+    previous: Option<&str>,
+    tile: &str,
+    hex_name: &str,
+    rotation: RotateCW,
+) -> String {
+    let turns = rotation.count_turns();
+    match previous {
+        Some(previous) => format!(
+            "Upgraded tile {} to {} at {} (rotation {})",
+            previous, tile, hex_name, turns
+        ),
+        None => {
+            format!(
+                "Placed tile {} at {} (rotation {})",
+                tile, hex_name, turns
+            )
+        }
+    }
+}
+
+/// Returns the journal entry for removing a tile from a map hex.
+pub fn remove_tile_entry(tile: &str, hex_name: &str) -> String {
+    format!("Removed tile {} from {}", tile, hex_name)
+}
+
+/// Returns the journal entry for changing the rotation of a tile.
+pub fn rotate_tile_entry(
+    tile: &str,
+    hex_name: &str,
+    rotation: RotateCW,
+) -> String {
+    format!(
+        "Rotated tile {} at {} to rotation {}",
+        tile,
+        hex_name,
+        rotation.count_turns()
+    )
+}
+
+/// Returns the journal entry for placing a company's token on a map hex.
+pub fn place_token_entry(company: &str, hex_name: &str) -> String {
+    format!("Placed {} token at {}", company, hex_name)
+}
+
+/// Returns the journal entry for a company paying for a token.
+pub fn token_cost_entry(company: &str, cost: usize) -> String {
+    format!("{} paid ${} for the token", company, cost)
+}
+
+/// Returns the journal entry for setting the cash held by a company.
+pub fn cash_entry(company: &str, cash: usize) -> String {
+    format!("Set {} cash to ${}", company, cash)
+}
+
+/// Returns the journal entry for removing a company's token from a map hex.
+pub fn remove_token_entry(company: &str, hex_name: &str) -> String {
+    format!("Removed {} token from {}", company, hex_name)
+}
+
+/// Returns the journal entry for changing the game phase.
+pub fn phase_entry(name: &str) -> String {
+    format!("Changed phase to {}", name)
+}
+
+/// Returns the journal entry for a company buying a private company.
+pub fn buy_private_entry(
+    company: &str,
+    private: &str,
+    cost: usize,
+) -> String {
+    format!("{} bought {} for ${}", company, private, cost)
+}
+
+/// Returns the journal entry for closing a private company.
+pub fn close_private_entry(private: &str) -> String {
+    format!("Closed {}", private)
+}
+
+/// Returns the journal entry for a train purchase (or discard).
+pub fn transfer_entry(transfer: &TrainTransfer) -> String {
+    use TrainTransfer::*;
+    match transfer {
+        FromBank(name) => format!("Bought a {}-train from the bank", name),
+        FromPool(name) => {
+            format!("Bought a {}-train from the open market", name)
+        }
+        ToPool(name) => {
+            format!("Discarded a {}-train to the open market", name)
+        }
+    }
+}
+
+/// Returns the journal entry for the revenue earned by a company's trains.
+pub fn routes_entry(
+    company: &str,
+    trains: &[String],
+    revenue: usize,
+) -> String {
+    if trains.is_empty() {
+        return format!("{} ran no trains for ${}", company, revenue);
+    }
+    let trains: Vec<String> = trains
+        .iter()
+        .map(|name| format!("{}-train", name))
+        .collect();
+    format!("{} ran {} for ${}", company, trains.join(", "), revenue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply, Board, Move, MoveError, Simulation};
+    use crate::bank::{TrainTransfer, TransferError};
+    use crate::{Game, GameState};
+    use n18hex::{Hex, RotateCW};
+    use n18map::{HexAddress, Placement};
+
+    fn new_game() -> (Box<dyn Game>, GameState) {
+        let game = crate::new_1830();
+        let map = game.create_map(&Hex::default());
+        let state = game.save(&map);
+        (Box::new(game), state)
+    }
+
+    fn addr(game: &dyn Game, name: &str) -> HexAddress {
+        game.coordinate_system().parse(name).unwrap()
+    }
+
+    #[test]
+    fn test_lay_tile() {
+        let (mut game, state) = new_game();
+        let c7 = addr(game.as_ref(), "C7");
+        let lay_tile = |tile: &str| Move::LayTile {
+            addr: c7,
+            tile: tile.to_string(),
+            rotation: RotateCW::One,
+            company: None,
+        };
+        let state = apply(game.as_mut(), &state, &lay_tile("8")).unwrap();
+        assert_eq!(
+            state.journal.entries(),
+            ["Placed tile 8 at C7 (rotation 1)"]
+        );
+        // A yellow tile cannot replace another yellow tile, and the state is
+        // not modified.
+        let result = apply(game.as_mut(), &state, &lay_tile("9"));
+        assert_eq!(result, Err(MoveError::Tile(Placement::IllegalColour)));
+        let result = apply(game.as_mut(), &state, &lay_tile("X99"));
+        assert!(matches!(result, Err(MoveError::Tile(_))));
+        let sim = Simulation::load(game.as_mut(), &state).unwrap();
+        assert_eq!(sim.save(), state);
+        assert_eq!(sim.map().tile_at(c7).unwrap().name, "8");
+    }
+
+    #[test]
+    fn test_place_and_remove_token() {
+        let (mut game, state) = new_game();
+        let f6 = addr(game.as_ref(), "F6");
+        let mut sim = Simulation::load(game.as_mut(), &state).unwrap();
+        let place = |company: &str, space: usize| Move::PlaceToken {
+            addr: f6,
+            space,
+            company: company.to_string(),
+        };
+        assert_eq!(
+            sim.apply(&place("XYZ", 0)),
+            Err(MoveError::UnknownCompany("XYZ".to_string()))
+        );
+        assert_eq!(
+            sim.apply(&place("PRR", 9)),
+            Err(MoveError::NoTokenSpace { addr: f6, space: 9 })
+        );
+        assert_eq!(sim.apply(&place("PRR", 0)), Ok(None));
+        assert_eq!(
+            sim.apply(&place("NYC", 0)),
+            Err(MoveError::Occupied { addr: f6, space: 0 })
+        );
+        let remove = Move::RemoveToken { addr: f6, space: 0 };
+        assert_eq!(sim.apply(&remove), Ok(None));
+        assert_eq!(
+            sim.apply(&remove),
+            Err(MoveError::NoToken { addr: f6, space: 0 })
+        );
+        assert_eq!(
+            sim.journal().entries(),
+            ["Placed PRR token at F6", "Removed PRR token from F6"]
+        );
+    }
+
+    #[test]
+    fn test_remove_tile() {
+        let (mut game, state) = new_game();
+        let c7 = addr(game.as_ref(), "C7");
+        let mut sim = Simulation::load(game.as_mut(), &state).unwrap();
+        let remove = Move::RemoveTile { addr: c7 };
+        assert_eq!(sim.apply(&remove), Err(MoveError::NoTile(c7)));
+        let lay_tile = Move::LayTile {
+            addr: c7,
+            tile: "8".to_string(),
+            rotation: RotateCW::Zero,
+            company: None,
+        };
+        assert_eq!(sim.apply(&lay_tile), Ok(None));
+        assert_eq!(sim.apply(&remove), Ok(None));
+        assert!(sim.map().tile_at(c7).is_none());
+        assert_eq!(
+            sim.journal().entries(),
+            ["Placed tile 8 at C7 (rotation 0)", "Removed tile 8 from C7"]
+        );
+    }
+
+    #[test]
+    fn test_rotate_tile() {
+        let (mut game, state) = new_game();
+        let c7 = addr(game.as_ref(), "C7");
+        let mut sim = Simulation::load(game.as_mut(), &state).unwrap();
+        let rotate = Move::RotateTile {
+            addr: c7,
+            rotation: RotateCW::Two,
+        };
+        assert_eq!(sim.apply(&rotate), Err(MoveError::NoTile(c7)));
+        let lay_tile = Move::LayTile {
+            addr: c7,
+            tile: "8".to_string(),
+            rotation: RotateCW::Zero,
+            company: None,
+        };
+        assert_eq!(sim.apply(&lay_tile), Ok(None));
+        assert_eq!(sim.apply(&rotate), Ok(None));
+        let rotation = sim.map().hex_state(c7).map(|hs| *hs.rotation());
+        assert_eq!(rotation, Some(RotateCW::Two));
+        assert_eq!(
+            sim.journal().entries(),
+            [
+                "Placed tile 8 at C7 (rotation 0)",
+                "Rotated tile 8 at C7 to rotation 2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_free_placement() {
+        let (mut game, state) = new_game();
+        let p6 = addr(game.as_ref(), "P6");
+        let mut sim = Simulation::load(game.as_mut(), &state).unwrap();
+        let lay_tile = |tile: &str| Move::LayTile {
+            addr: p6,
+            tile: tile.to_string(),
+            rotation: RotateCW::Zero,
+            company: None,
+        };
+        let mut privates = sim.privates.clone();
+        let mut board = Board {
+            game: &mut *sim.game,
+            map: &mut sim.map,
+            journal: &mut sim.journal,
+            trains: &mut sim.trains,
+            privates: &mut privates,
+            treasuries: &mut sim.treasuries,
+            path_cache: &mut sim.path_cache,
+            free_placement: true,
+        };
+        // Tiles can be placed on reserved hexes, and yellow tiles can
+        // replace other yellow tiles.
+        assert_eq!(board.apply(&lay_tile("8")), Ok(None));
+        assert_eq!(board.apply(&lay_tile("9")), Ok(None));
+        assert_eq!(
+            board.apply(&lay_tile("X99")),
+            Err(MoveError::Tile(Placement::UnknownTile))
+        );
+        assert_eq!(sim.map().tile_at(p6).unwrap().name, "9");
+        assert_eq!(
+            sim.journal().entries(),
+            [
+                "Upgraded tile Scranton to 8 at P6 (rotation 0)",
+                "Upgraded tile 8 to 9 at P6 (rotation 0)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_phase() {
+        let (mut game, state) = new_game();
+        let phases = game.phase_names().len();
+        let last = Move::SetPhase(phases - 1);
+        let state = apply(game.as_mut(), &state, &last).unwrap();
+        assert_eq!(game.phase_ix(), phases - 1);
+        let name = game.current_phase_name().to_string();
+        assert_eq!(
+            state.journal.entries(),
+            [format!("Changed phase to {}", name)]
+        );
+        let invalid = Move::SetPhase(phases);
+        assert_eq!(
+            apply(game.as_mut(), &state, &invalid),
+            Err(MoveError::InvalidPhase(phases))
+        );
+    }
+
+    #[test]
+    fn test_advance_phase() {
+        let (mut game, mut state) = new_game();
+        let phases = game.phase_names().len();
+        for _ in 1..phases {
+            state =
+                apply(game.as_mut(), &state, &Move::AdvancePhase).unwrap();
+        }
+        assert_eq!(game.phase_ix(), phases - 1);
+        assert_eq!(
+            apply(game.as_mut(), &state, &Move::AdvancePhase),
+            Err(MoveError::FinalPhase)
+        );
+        let entries = state.journal.entries();
+        assert_eq!(entries.len(), phases - 1);
+        assert_eq!(entries[0], "Changed phase to 3");
+    }
+
+    #[test]
+    fn test_buy_trains() {
+        let (mut game, state) = new_game();
+        let mut sim = Simulation::load(game.as_mut(), &state).unwrap();
+        let buy = |name: &str| {
+            Move::TransferTrain(TrainTransfer::FromBank(name.to_string()))
+        };
+        assert_eq!(
+            sim.apply(&buy("3")),
+            Err(MoveError::Transfer(TransferError::NotAvailable(
+                "3".to_string()
+            )))
+        );
+        for _ in 0..6 {
+            assert_eq!(sim.apply(&buy("2")), Ok(None));
+        }
+        assert_eq!(sim.game().phase_ix(), 0);
+        // The first 3-train begins the next game phase.
+        assert_eq!(sim.apply(&buy("3")), Ok(None));
+        assert_eq!(sim.game().phase_ix(), 1);
+        let entries = sim.journal().entries();
+        assert_eq!(entries.len(), 8);
+        assert_eq!(entries[6], "Bought a 3-train from the bank");
+        assert_eq!(entries[7], "Changed phase to 3");
+
+        // The train counts are preserved when the state is saved and loaded.
+        let state = sim.save();
+        let sim = Simulation::load(game.as_mut(), &state).unwrap();
+        assert_eq!(sim.trains(), &state.trains);
+        assert_eq!(sim.game().phase_ix(), 1);
+    }
+
+    #[test]
+    fn test_run_routes() {
+        let (mut game, state) = new_game();
+        let f6 = addr(game.as_ref(), "F6");
+        let mut sim = Simulation::load(game.as_mut(), &state).unwrap();
+        let place = Move::PlaceToken {
+            addr: f6,
+            space: 0,
+            company: "PRR".to_string(),
+        };
+        sim.apply(&place).unwrap();
+        let run = |train: &str| Move::RunRoutes {
+            company: "PRR".to_string(),
+            trains: vec![train.to_string()],
+            bonuses: vec![],
+        };
+        assert_eq!(
+            sim.apply(&run("9")),
+            Err(MoveError::UnknownTrain("9".to_string()))
+        );
+        // There is no track leaving Cleveland, so no revenue is earned.
+        let routes = sim.apply(&run("2")).unwrap();
+        let revenue = routes.map(|r| r.net_revenue).unwrap_or(0);
+        assert_eq!(revenue, 0);
+        assert_eq!(
+            sim.journal().entries().last().unwrap(),
+            "PRR ran 2-train for $0"
+        );
+    }
+
+    #[test]
+    fn test_train_limit() {
+        let (mut game, state) = new_game();
+        assert_eq!(game.train_limit("PRR"), Some(4));
+        assert_eq!(game.train_limit("XYZ"), Some(4));
+        let mut sim = Simulation::load(game.as_mut(), &state).unwrap();
+        let run = |count: usize| Move::RunRoutes {
+            company: "PRR".to_string(),
+            trains: vec!["2".to_string(); count],
+            bonuses: vec![],
+        };
+        assert_eq!(sim.apply(&run(4)), Ok(None));
+        assert_eq!(
+            sim.apply(&run(5)),
+            Err(MoveError::TooManyTrains {
+                company: "PRR".to_string(),
+                count: 5,
+                limit: 4,
+            })
+        );
+        // The train limit decreases in later phases.
+        sim.apply(&Move::SetPhase(3)).unwrap();
+        assert_eq!(sim.game().train_limit("PRR"), Some(2));
+        assert_eq!(
+            sim.apply(&run(3)).unwrap_err().to_string(),
+            "PRR can operate at most 2 trains, not 3"
+        );
+    }
+
+    #[test]
+    fn test_private_companies() {
+        let (mut game, state) = new_game();
+        let p6 = addr(game.as_ref(), "P6");
+        let mut sim = Simulation::load(game.as_mut(), &state).unwrap();
+        let lay_tile = |company: Option<&str>| Move::LayTile {
+            addr: p6,
+            tile: "57".to_string(),
+            rotation: RotateCW::Zero,
+            company: company.map(|c| c.to_string()),
+        };
+        let buy = |private: &str, company: &str| Move::BuyPrivate {
+            private: private.to_string(),
+            company: company.to_string(),
+        };
+        let reserved = Err(MoveError::Reserved {
+            addr: p6,
+            private: "D&H".to_string(),
+        });
+
+        // The D&H blocks its hex until a company buys it.
+        assert_eq!(sim.apply(&lay_tile(None)), reserved);
+        assert_eq!(sim.apply(&lay_tile(Some("PRR"))), reserved);
+        assert_eq!(
+            sim.apply(&buy("XYZ", "PRR")),
+            Err(MoveError::UnknownPrivate("XYZ".to_string()))
+        );
+        assert_eq!(
+            sim.apply(&buy("D&H", "XYZ")),
+            Err(MoveError::UnknownCompany("XYZ".to_string()))
+        );
+        assert_eq!(sim.apply(&buy("D&H", "PRR")), Ok(None));
+        assert_eq!(sim.privates().owner("D&H"), Some("PRR"));
+        assert_eq!(sim.apply(&lay_tile(Some("NYC"))), reserved);
+        assert_eq!(sim.apply(&lay_tile(Some("PRR"))), Ok(None));
+
+        // The owners are preserved when the state is saved and loaded.
+        let state = sim.save();
+        assert_eq!(state.privates.owner("D&H"), Some("PRR"));
+        let mut sim = Simulation::load(game.as_mut(), &state).unwrap();
+        assert_eq!(sim.privates(), &state.privates);
+
+        // Closed private companies no longer block their hex.
+        let o9 = addr(sim.game(), "O9");
+        assert!(sim
+            .game()
+            .reserving_private(sim.privates(), o9, None)
+            .is_some());
+        let close = Move::ClosePrivate("B&O".to_string());
+        assert_eq!(sim.apply(&close), Ok(None));
+        assert!(sim.privates().is_closed("B&O"));
+        assert!(sim
+            .game()
+            .reserving_private(sim.privates(), o9, None)
+            .is_none());
+        assert_eq!(
+            sim.apply(&close),
+            Err(MoveError::PrivateClosed("B&O".to_string()))
+        );
+
+        // Every private company closes in phase 5.
+        let r4 = addr(sim.game(), "R4");
+        assert!(sim
+            .game()
+            .reserving_private(sim.privates(), r4, None)
+            .is_some());
+        sim.apply(&Move::SetPhase(3)).unwrap();
+        assert!(sim
+            .game()
+            .reserving_private(sim.privates(), r4, None)
+            .is_none());
+        assert_eq!(
+            sim.apply(&buy("M&H", "NYC")).unwrap_err().to_string(),
+            "M&H has closed"
+        );
+        let entries = sim.journal().entries();
+        assert_eq!(entries[0], "PRR bought D&H for $70");
+        assert_eq!(entries[2], "Closed B&O");
+    }
+
+    #[test]
+    fn test_token_costs() {
+        let (mut game, state) = new_game();
+        let hexes = ["F6", "J8", "O9", "W5", "E5", "S7", "J4"];
+        let [f6, j8, o9, w5, e5, s7, j4] =
+            hexes.map(|name| addr(game.as_ref(), name));
+        let mut sim = Simulation::load(game.as_mut(), &state).unwrap();
+        let place = |company: &str, addr: HexAddress| Move::PlaceToken {
+            addr,
+            space: 0,
+            company: company.to_string(),
+        };
+        let set_cash = |company: &str, cash: usize| Move::SetCash {
+            company: company.to_string(),
+            cash,
+        };
+
+        // Companies whose treasuries are not tracked do not pay for tokens.
+        assert_eq!(sim.apply(&place("B&O", e5)), Ok(None));
+        assert_eq!(sim.apply(&place("B&O", o9)), Ok(None));
+        assert!(sim.treasuries().is_empty());
+        assert_eq!(
+            sim.apply(&set_cash("XYZ", 100)),
+            Err(MoveError::UnknownCompany("XYZ".to_string()))
+        );
+
+        // The home token is free, the second token costs $40, and every
+        // other token costs $100.
+        assert_eq!(sim.apply(&set_cash("PRR", 150)), Ok(None));
+        assert_eq!(sim.apply(&place("PRR", f6)), Ok(None));
+        assert_eq!(sim.treasuries().cash("PRR"), Some(150));
+        assert_eq!(sim.apply(&place("PRR", j8)), Ok(None));
+        assert_eq!(sim.treasuries().cash("PRR"), Some(110));
+        assert_eq!(sim.apply(&place("PRR", w5)), Ok(None));
+        assert_eq!(sim.treasuries().cash("PRR"), Some(10));
+
+        // A company cannot place a token that it cannot afford, and the map
+        // is not modified.
+        assert_eq!(sim.apply(&set_cash("NYC", 20)), Ok(None));
+        assert_eq!(sim.apply(&place("NYC", s7)), Ok(None));
+        let result = sim.apply(&place("NYC", j4));
+        assert_eq!(
+            result,
+            Err(MoveError::InsufficientCash {
+                company: "NYC".to_string(),
+                cost: 40,
+                cash: 20,
+            })
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "NYC cannot pay $40 for a token, it only has $20"
+        );
+        let nyc = *sim.game().try_token("NYC").unwrap();
+        assert_eq!(sim.map().find_placed_tokens(&nyc).len(), 1);
+
+        // The treasuries are preserved when the state is saved and loaded.
+        let state = sim.save();
+        assert_eq!(state.treasuries.cash("PRR"), Some(10));
+        assert_eq!(state.treasuries.cash("NYC"), Some(20));
+        let sim = Simulation::load(game.as_mut(), &state).unwrap();
+        assert_eq!(sim.treasuries(), &state.treasuries);
+        let entries = sim.journal().entries();
+        assert_eq!(entries[2], "Set PRR cash to $150");
+        assert_eq!(entries[5], "PRR paid $40 for the token");
+        assert_eq!(entries[7], "PRR paid $100 for the token");
+    }
+}
@@ -0,0 +1,117 @@
+//! Track the cash held by each company, and the cost of placing tokens.
+//!
+//! Treasuries are optional: a company's cash is only tracked once it has
+//! been set (e.g., by [Move::SetCash](crate::sim::Move::SetCash)), and
+//! companies whose cash is not tracked can place tokens without paying for
+//! them.
+//!
+//! ```rust
+//! # use n18game::treasury::{TokenCost, Treasuries};
+//! // The home token is free, the second token costs $40, and every other
+//! // token costs $100.
+//! let cost = TokenCost::Fixed(vec![40, 100]);
+//! assert_eq!(cost.cost(0, 5), 0);
+//! assert_eq!(cost.cost(1, 5), 40);
+//! assert_eq!(cost.cost(3, 5), 100);
+//!
+//! // Each token costs $20, plus $10 for each hex between the token and the
+//! // company's nearest placed token.
+//! let cost = TokenCost::Distance { base: 20, per_hex: 10 };
+//! assert_eq!(cost.cost(0, 5), 0);
+//! assert_eq!(cost.cost(2, 5), 70);
+//!
+//! let mut treasuries = Treasuries::default();
+//! assert_eq!(treasuries.cash("PRR"), None);
+//! treasuries.set_cash("PRR", 120);
+//! assert!(treasuries.debit("PRR", 100));
+//! assert!(!treasuries.debit("PRR", 40));
+//! assert_eq!(treasuries.cash("PRR"), Some(20));
+//! ```
+
+use std::collections::BTreeMap;
+
+/// Defines how much a company must pay to place a token.
+///
+/// A company's first token is its home token, which is always free.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum TokenCost {
+    /// Every token is free.
+    #[default]
+    Free,
+    /// The cost of each token after the home token, in the order that they
+    /// are placed; the final cost applies to every subsequent token.
+    Fixed(Vec<usize>),
+    /// Each token costs a fixed amount, plus an amount for each hex between
+    /// the token and the company's nearest placed token.
+    Distance { base: usize, per_hex: usize },
+}
+
+impl TokenCost {
+    /// Returns the cost of a company's next token, where `placed` is the
+    /// number of tokens that the company has already placed, and `distance`
+    /// is the number of hexes between the new token and the company's
+    /// nearest placed token.
+    pub fn cost(&self, placed: usize, distance: usize) -> usize {
+        if placed == 0 {
+            return 0;
+        }
+        match self {
+            TokenCost::Free => 0,
+            TokenCost::Fixed(costs) => costs
+                .get(placed - 1)
+                .or_else(|| costs.last())
+                .copied()
+                .unwrap_or(0),
+            TokenCost::Distance { base, per_hex } => {
+                base + per_hex * distance
+            }
+        }
+    }
+}
+
+/// Records the cash held by each company whose treasury is being tracked.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Treasuries {
+    cash: BTreeMap<String, usize>,
+}
+
+impl Treasuries {
+    /// Returns `true` if no company's treasury is being tracked.
+    pub fn is_empty(&self) -> bool {
+        self.cash.is_empty()
+    }
+
+    /// Returns the cash held by a company, or `None` if the company's
+    /// treasury is not being tracked.
+    pub fn cash(&self, company: &str) -> Option<usize> {
+        self.cash.get(company).copied()
+    }
+
+    /// Sets the cash held by a company, and begins tracking the company's
+    /// treasury if it was not already being tracked.
+    pub fn set_cash(&mut self, company: &str, cash: usize) {
+        self.cash.insert(company.to_string(), cash);
+    }
+
+    /// Removes an amount from a company's treasury, and returns `true` if
+    /// the company's treasury is being tracked and holds enough cash.
+    ///
+    /// The treasury is not modified if this returns `false`.
+    pub fn debit(&mut self, company: &str, amount: usize) -> bool {
+        match self.cash.get_mut(company) {
+            Some(cash) if *cash >= amount => {
+                *cash -= amount;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns each company whose treasury is being tracked, and the cash
+    /// that it holds.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.cash
+            .iter()
+            .map(|(company, cash)| (company.as_str(), *cash))
+    }
+}
@@ -0,0 +1,303 @@
+//! Check that a game's map, tiles, and companies are consistent with each
+//! other.
+//!
+//! Games are defined in code, and refer to tiles by name and to companies by
+//! their abbreviated names, so mistakes such as misspelled tile names are not
+//! caught by the compiler.
+//! Because [Map::place_tile] silently ignores unknown tiles, these mistakes
+//! can otherwise go unnoticed.
+//!
+//! [validate] checks every tile that the game places on the map (see
+//! [Game::placed_tiles]), the hex labels that determine valid tile upgrades,
+//! and the tokens defined for each company, and returns a [Report] that
+//! lists every [Inconsistency].
+//!
+//! ```rust
+//! # use n18game::Game;
+//! for game in n18game::games() {
+//!     let report = game.validate();
+//!     assert!(report.is_consistent(), "{}", report);
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use n18hex::Hex;
+use n18map::{HexAddress, Map};
+use n18tile::Label;
+
+use crate::{CompanyTier, Game};
+
+/// A single inconsistency between a game's map, tiles, and companies.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Inconsistency {
+    /// A tile that the game places on the map is not defined in the game
+    /// catalogue.
+    UnknownTile { addr: HexAddress, tile: String },
+    /// A map hex has a tile-restriction label that no other tile in the game
+    /// catalogue has, so the tile on this hex can never be upgraded.
+    UnmatchedLabel { addr: HexAddress, label: Label },
+    /// More than one company has the same abbreviated name.
+    DuplicateCompany { company: String },
+    /// Two companies have identical tokens, and so cannot be distinguished
+    /// on the map.
+    SharedToken { first: String, second: String },
+    /// The map does not define the token for a company.
+    MissingToken { company: String },
+    /// The number of tokens defined by the map differs from the number of
+    /// companies.
+    TokenCount { companies: usize, tokens: usize },
+    /// The rules for a company tier do not allow its companies to place any
+    /// tokens.
+    NoTokens { tier: CompanyTier },
+    /// The national company formation refers to a company that is not
+    /// defined by the game.
+    UnknownCompany { company: String },
+}
+
+impl std::fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Inconsistency::*;
+        match self {
+            UnknownTile { addr, tile } => {
+                write!(f, "Unknown tile '{}' at {}", tile, addr)
+            }
+            UnmatchedLabel { addr, label } => {
+                write!(f, "No tile has the label {:?} at {}", label, addr)
+            }
+            DuplicateCompany { company } => {
+                write!(f, "Multiple companies are called '{}'", company)
+            }
+            SharedToken { first, second } => {
+                write!(
+                    f,
+                    "Companies '{}' and '{}' share a token",
+                    first, second
+                )
+            }
+            MissingToken { company } => {
+                write!(f, "The map has no token for company '{}'", company)
+            }
+            TokenCount { companies, tokens } => write!(
+                f,
+                "The map defines {} tokens for {} companies",
+                tokens, companies
+            ),
+            NoTokens { tier } => {
+                write!(f, "{} companies cannot place any tokens", tier)
+            }
+            UnknownCompany { company } => write!(
+                f,
+                "The national company replaces unknown company '{}'",
+                company
+            ),
+        }
+    }
+}
+
+/// Lists every inconsistency in a game, as returned by [validate].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    /// The name of the game.
+    pub game: String,
+    /// The name of the map variant that was checked.
+    pub variant: String,
+    /// Every inconsistency that was found.
+    pub problems: Vec<Inconsistency>,
+}
+
+impl Report {
+    /// Returns `true` if no inconsistencies were found.
+    pub fn is_consistent(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}): {} problem(s)",
+            self.game,
+            self.variant,
+            self.problems.len()
+        )?;
+        for problem in &self.problems {
+            write!(f, "\n  {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that a game's map, tiles, and companies are consistent, for the
+/// current map variant.
+///
+/// See [Game::validate].
+pub fn validate<G: Game + ?Sized>(game: &G) -> Report {
+    let map = game.create_map(&Hex::default());
+    let mut problems = tile_problems(game, &map);
+    problems.extend(label_problems(game, &map));
+    problems.extend(company_problems(game, &map));
+    Report {
+        game: game.name().to_string(),
+        variant: game.variant().to_string(),
+        problems,
+    }
+}
+
+/// Returns each tile that the game places on the map and which is not
+/// defined in the game catalogue.
+fn tile_problems<G: Game + ?Sized>(
+    game: &G,
+    map: &Map,
+) -> Vec<Inconsistency> {
+    let mut problems: Vec<Inconsistency> = vec![];
+    for (addr, tile) in game.placed_tiles() {
+        if map.tile(&tile).is_some() {
+            continue;
+        }
+        let problem = Inconsistency::UnknownTile { addr, tile };
+        if !problems.contains(&problem) {
+            problems.push(problem)
+        }
+    }
+    problems
+}
+
+/// Returns each tile-restriction label on a map hex that does not appear on
+/// any catalogue tile other than the tile currently on that hex.
+fn label_problems<G: Game + ?Sized>(
+    game: &G,
+    map: &Map,
+) -> Vec<Inconsistency> {
+    let mut problems = vec![];
+    for addr in map.hex_address_iter() {
+        let current = map.tile_at(*addr).map(|tile| tile.name.as_str());
+        for label in map.labels_at(*addr) {
+            if !label.is_tile_restriction() {
+                continue;
+            }
+            let matched = game.catalogue().tile_iter().any(|tile| {
+                Some(tile.name.as_str()) != current
+                    && tile.labels().iter().any(|(l, _posn)| l == label)
+            });
+            if !matched {
+                problems.push(Inconsistency::UnmatchedLabel {
+                    addr: *addr,
+                    label: label.clone(),
+                })
+            }
+        }
+    }
+    problems
+}
+
+/// Returns each inconsistency between the game companies, the tokens defined
+/// by the map, and the rules for each company tier.
+fn company_problems<G: Game + ?Sized>(
+    game: &G,
+    map: &Map,
+) -> Vec<Inconsistency> {
+    let mut problems = vec![];
+    let companies = game.companies();
+    let mut seen: BTreeMap<&str, usize> = BTreeMap::new();
+    for (ix, company) in companies.iter().enumerate() {
+        let abbrev = company.abbrev.as_str();
+        if seen.insert(abbrev, ix).is_some() {
+            let problem = Inconsistency::DuplicateCompany {
+                company: abbrev.to_string(),
+            };
+            if !problems.contains(&problem) {
+                problems.push(problem)
+            }
+        }
+        if let Some(other) = companies[..ix].iter().find(|other| {
+            other.abbrev != company.abbrev && other.token == company.token
+        }) {
+            problems.push(Inconsistency::SharedToken {
+                first: other.abbrev.clone(),
+                second: company.abbrev.clone(),
+            })
+        }
+        if map.try_token(abbrev) != Some(company.token) {
+            problems.push(Inconsistency::MissingToken {
+                company: abbrev.to_string(),
+            })
+        }
+    }
+    let tokens = map.token_names().len();
+    if tokens != seen.len() {
+        problems.push(Inconsistency::TokenCount {
+            companies: seen.len(),
+            tokens,
+        })
+    }
+    for tier in [CompanyTier::Minor, CompanyTier::Major] {
+        let used = companies.iter().any(|company| company.tier == tier);
+        if used && game.tier_rules(tier).token_count == Some(0) {
+            problems.push(Inconsistency::NoTokens { tier })
+        }
+    }
+    if let Some(national) = game.national_formation() {
+        for company in national.companies {
+            if game.try_company(&company).is_none() {
+                problems.push(Inconsistency::UnknownCompany { company })
+            }
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Inconsistency;
+    use crate::Game;
+
+    #[test]
+    fn test_games_are_consistent() {
+        for mut game in crate::games() {
+            let variants: Vec<String> =
+                game.variants().iter().map(|v| v.to_string()).collect();
+            for variant in variants {
+                assert!(game.set_variant(&variant));
+                let report = game.validate();
+                assert_eq!(report.variant, variant);
+                assert!(report.is_consistent(), "{}", report);
+            }
+        }
+    }
+
+    #[test]
+    fn test_placed_tiles_include_every_phase() {
+        let game = crate::new_1861();
+        let tiles = game.placed_tiles();
+        for name in ["Ekat Yw", "Ekat Gr"] {
+            assert!(tiles.iter().any(|(_addr, tile)| tile == name));
+        }
+    }
+
+    #[test]
+    fn test_describe_report() {
+        let report = super::Report {
+            game: "1830".to_string(),
+            variant: "Standard".to_string(),
+            problems: vec![
+                Inconsistency::UnknownTile {
+                    addr: (0, 0).into(),
+                    tile: "X1".to_string(),
+                },
+                Inconsistency::TokenCount {
+                    companies: 8,
+                    tokens: 7,
+                },
+            ],
+        };
+        assert!(!report.is_consistent());
+        let text = format!("{}", report);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "1830 (Standard): 2 problem(s)");
+        assert!(lines[1].contains("Unknown tile 'X1'"));
+        assert_eq!(lines[2], "  The map defines 7 tokens for 8 companies");
+    }
+}
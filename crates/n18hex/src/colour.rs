@@ -0,0 +1,278 @@
+//! Defines colours for drawing strokes and fills.
+//!
+//! [Colour] values are available without the `cairo` feature, so that they
+//! can describe company and token colours; the `cairo` feature adds
+//! `Colour::apply_colour`, which uses a colour as the source for a
+//! `cairo::Context`.
+
+/// The scaling factor for converting u8 values to the unit interval.
+#[cfg(feature = "cairo")]
+static SCALE_U8_COLOUR: f64 = 1.0 / u8::MAX as f64;
+
+/// Defines colours for drawing strokes and fills.
+///
+/// Each colour comprises four channels: red, green, blue, and alpha.
+/// These values correspond to Cairo's 8-bit RGB colour space.
+///
+/// Colours can be constructed from 3-tuples (red, green, blue) and 4-tuples
+/// (red, green, blue, alpha) of `u8` values (`0..=255`) or `f64` values
+/// (`0.0..=1.0`).
+/// When constructed from 3-tuples, the alpha channel is set to 255 (opaque).
+///
+/// ```
+/// # use n18hex::Colour;
+/// let red = Colour::from((255, 0, 0));
+/// let green = Colour::from((0.0, 1.0, 0.0));
+/// let blue = Colour::from((0, 0, 255, 255));
+/// let black = Colour::from((0.0, 0.0, 0.0, 1.0));
+/// ```
+///
+/// Colours can also be constructed from valid hexadecimal strings:
+///
+/// ```
+/// # use n18hex::Colour;
+/// let red = "#ff0000".parse::<Colour>().unwrap();
+/// let translucent_blue = "#0000ff7f".parse::<Colour>().unwrap();
+/// ```
+///
+/// Attempts to parse invalid strings will return a [ParseColourError] value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Colour {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
+}
+
+impl Colour {
+    /// The colour white ("#FFFFFF").
+    pub const WHITE: Self = Self {
+        red: u8::MAX,
+        green: u8::MAX,
+        blue: u8::MAX,
+        alpha: u8::MAX,
+    };
+
+    /// The colour black ("#000000").
+    pub const BLACK: Self = Self {
+        red: 0,
+        green: 0,
+        blue: 0,
+        alpha: u8::MAX,
+    };
+
+    /// Entirely transparent.
+    pub const TRANSPARENT: Self = Self {
+        red: u8::MAX,
+        green: u8::MAX,
+        blue: u8::MAX,
+        alpha: 0,
+    };
+
+    /// Adjust the transparency of this colour.
+    pub fn with_alpha(mut self, alpha: u8) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Adjust the transparency of this colour (`0.0..=1.0`).
+    pub fn with_alpha_f64(mut self, alpha: f64) -> Self {
+        self.alpha = (alpha.clamp(0.0, 1.0) * u8::MAX as f64).round() as u8;
+        self
+    }
+
+    /// Returns the distance between this colour and another colour, ignoring
+    /// the alpha channel.
+    ///
+    /// This is the Euclidean distance in RGB space, and ranges from `0.0`
+    /// (identical colours) to approximately `441.7` (black and white).
+    pub fn distance(&self, other: &Colour) -> f64 {
+        let diff = |a: u8, b: u8| a as f64 - b as f64;
+        let dr = diff(self.red, other.red);
+        let dg = diff(self.green, other.green);
+        let db = diff(self.blue, other.blue);
+        (dr * dr + dg * dg + db * db).sqrt()
+    }
+
+    /// Use this colour as the source for the provided context.
+    #[cfg(feature = "cairo")]
+    pub fn apply_colour(&self, ctx: &cairo::Context) {
+        let r = self.red as f64 * SCALE_U8_COLOUR;
+        let g = self.green as f64 * SCALE_U8_COLOUR;
+        let b = self.blue as f64 * SCALE_U8_COLOUR;
+        let a = self.alpha as f64 * SCALE_U8_COLOUR;
+        ctx.set_source_rgba(r, g, b, a)
+    }
+
+    /// Returns the hexadecimal representation of this colour, without the
+    /// alpha channel.
+    pub fn as_rgb(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+
+    /// Returns the hexadecimal representation of this colour, including the
+    /// alpha channel.
+    pub fn as_rgba(&self) -> String {
+        format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            self.red, self.green, self.blue, self.alpha
+        )
+    }
+}
+
+impl std::fmt::Display for Colour {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_rgba())
+    }
+}
+
+impl From<&Colour> for Colour {
+    fn from(src: &Colour) -> Self {
+        *src
+    }
+}
+
+impl From<(f64, f64, f64)> for Colour {
+    fn from(src: (f64, f64, f64)) -> Self {
+        let fmax = u8::MAX as f64;
+        let r = (src.0 * fmax).round() as u8;
+        let g = (src.1 * fmax).round() as u8;
+        let b = (src.2 * fmax).round() as u8;
+        Colour::from((r, g, b))
+    }
+}
+
+impl From<(f64, f64, f64, f64)> for Colour {
+    fn from(src: (f64, f64, f64, f64)) -> Self {
+        let fmax = u8::MAX as f64;
+        let r = (src.0 * fmax).round() as u8;
+        let g = (src.1 * fmax).round() as u8;
+        let b = (src.2 * fmax).round() as u8;
+        let a = (src.3 * fmax).round() as u8;
+        Colour::from((r, g, b, a))
+    }
+}
+
+impl From<(u8, u8, u8)> for Colour {
+    fn from(src: (u8, u8, u8)) -> Self {
+        Colour {
+            red: src.0,
+            green: src.1,
+            blue: src.2,
+            alpha: u8::MAX,
+        }
+    }
+}
+
+impl From<(u8, u8, u8, u8)> for Colour {
+    fn from(src: (u8, u8, u8, u8)) -> Self {
+        Self::from((src.0, src.1, src.2)).with_alpha(src.3)
+    }
+}
+
+/// An error which can be returned when parsing colour strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColourError {
+    input: String,
+}
+
+impl std::error::Error for ParseColourError {}
+
+impl std::fmt::Display for ParseColourError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid colour string: {:?}", self.input)
+    }
+}
+
+impl From<std::num::ParseIntError> for ParseColourError {
+    fn from(src: std::num::ParseIntError) -> Self {
+        let input = format!("{}", src);
+        ParseColourError { input }
+    }
+}
+
+impl std::str::FromStr for Colour {
+    type Err = ParseColourError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let num_bytes = s.len();
+        let invalid_len = num_bytes != 7 && num_bytes != 9;
+        let invalid_start = &s[0..1] != "#";
+        if invalid_len || invalid_start {
+            return Err(ParseColourError {
+                input: s.to_string(),
+            });
+        }
+        let red = u8::from_str_radix(&s[1..3], 16)?;
+        let green = u8::from_str_radix(&s[3..5], 16)?;
+        let blue = u8::from_str_radix(&s[5..7], 16)?;
+        let alpha = if num_bytes == 9 {
+            u8::from_str_radix(&s[7..9], 16)?
+        } else {
+            u8::MAX
+        };
+        Ok(Colour::from((red, green, blue, alpha)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    /// Tests the round-trip between RGBA strings and Colours.
+    fn rgba_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let input = "#dbbf12aa";
+        let colour: Colour = input.parse()?;
+        let output = colour.as_rgba();
+        assert_eq!(input, output);
+        Ok(())
+    }
+
+    #[test]
+    /// Tests the round-trip between RGBA strings and Colours.
+    fn rgba_invalid_string() -> Result<(), Box<dyn Error>> {
+        let input = "#dbbf12AAA";
+        assert!(input.parse::<Colour>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    /// Tests parsing a range of valid RGB and RGBA strings.
+    ///
+    /// The original version of this test case covered every valid input
+    /// string, but this took 13 seconds to run when compiled in release mode.
+    fn rgb_rgba_inputs() -> Result<(), Box<dyn Error>> {
+        let in_vals: [u8; 8] = [0, 1, 63, 127, 128, 191, 254, 255];
+        for r in &in_vals {
+            for g in &in_vals {
+                for b in &in_vals {
+                    // Parse the #RRGGBB input string.
+                    let input = format!("#{:02x}{:02x}{:02x}", r, g, b);
+                    let parsed = input.parse::<Colour>();
+                    assert!(parsed.is_ok());
+                    let colour = parsed.unwrap();
+                    // Ensure we obtain the expected colour values.
+                    assert_eq!(*r, colour.red);
+                    assert_eq!(*g, colour.green);
+                    assert_eq!(*b, colour.blue);
+                    for a in &in_vals {
+                        // Parse the #RRGGBBAA input string.
+                        let input_a = format!("{}{:02x}", input, a);
+                        let parsed_a = input_a.parse::<Colour>();
+                        assert!(parsed_a.is_ok());
+                        let colour_a = parsed_a.unwrap();
+                        // Ensure we obtain the expected colour values.
+                        assert_eq!(colour.red, colour_a.red);
+                        assert_eq!(colour.green, colour_a.green);
+                        assert_eq!(colour.blue, colour_a.blue);
+                        assert_eq!(*a, colour_a.alpha);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
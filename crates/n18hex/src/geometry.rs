@@ -0,0 +1,78 @@
+//! Defines the lengths that determine the shape of track segments, dits,
+//! and cities, and the margins around the map edges.
+//!
+//! These lengths determine how the elements of a tile are connected to each
+//! other and where each hex is located on the map, and so they are available
+//! without the `cairo` feature.
+
+use crate::Hex;
+
+/// Defines relative and absolute lengths.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Length {
+    /// Lengths that are relative to the hexagon maximal diameter.
+    Relative(f64),
+    /// Lengths that are absolute (in pixels).
+    Absolute(f64),
+}
+
+impl Length {
+    /// Returns the absolute length in pixels.
+    pub fn absolute(&self, hex: &Hex) -> f64 {
+        match self {
+            Self::Relative(frac) => frac * hex.max_d,
+            Self::Absolute(length) => *length,
+        }
+    }
+
+    /// Returns the absolute length of a margin in pixels, which is also
+    /// scaled by the hexagon's margin scale factor.
+    pub fn margin(&self, hex: &Hex) -> f64 {
+        self.absolute(hex) * hex.scale().margin
+    }
+
+    /// Returns the absolute width of a line in pixels, which is also scaled
+    /// by the hexagon's stroke scale factor.
+    pub fn line_width(&self, hex: &Hex) -> f64 {
+        self.absolute(hex) * hex.scale().stroke
+    }
+}
+
+/// The lengths that define the shape of track segments, dits, and cities,
+/// and the margins around the map edges.
+///
+/// The default [Theme](crate::theme::Theme) uses these lengths for the
+/// corresponding theme elements, and [Hex::geometry] returns the lengths
+/// defined by the hexagon's theme (if any).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Geometry {
+    /// The width of the outer (background) track segment stroke.
+    pub track_width: Length,
+    /// The radius of gentle track curves.
+    pub track_gentle_radius: Length,
+    /// The radius of hard track curves.
+    pub track_hard_radius: Length,
+    /// The inner (foreground) length of (linear) dits.
+    pub dit_length: Length,
+    /// The radius of token spaces.
+    pub token_space_radius: Length,
+    /// The width of the margin around each map edge.
+    pub map_margin: Length,
+    /// The additional margin around each map edge that contains the row and
+    /// column labels, when these labels are shown.
+    pub border_margin: Length,
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Geometry {
+            track_width: Length::Relative(0.10),
+            track_gentle_radius: Length::Relative(0.75),
+            track_hard_radius: Length::Relative(0.25),
+            dit_length: Length::Relative(0.10),
+            token_space_radius: Length::Relative(0.125),
+            map_margin: Length::Absolute(10.0),
+            border_margin: Length::Relative(0.25),
+        }
+    }
+}
@@ -1,9 +1,12 @@
+#[cfg(feature = "cairo")]
 use cairo::Context;
 
 use crate::consts::PI;
 use crate::consts::{PI_1_4, PI_3_4};
 use crate::consts::{PI_1_6, PI_2_6, PI_3_6, PI_4_6, PI_5_6};
 use crate::coord::Coord;
+use crate::geometry::Geometry;
+#[cfg(feature = "cairo")]
 use crate::theme::Theme;
 
 /// The tile background colours for [Hex].
@@ -30,6 +33,19 @@ impl HexColour {
         }
     }
 
+    /// Returns the number of phases that precede tiles of this colour, where
+    /// empty hexes are phase zero, or `None` for colours that are not part of
+    /// the tile upgrade sequence (e.g., red off-board tiles).
+    pub fn phase_number(&self) -> Option<usize> {
+        let mut phase = HexColour::Empty;
+        let mut number = 0;
+        while phase != *self {
+            phase = phase.next_phase()?;
+            number += 1;
+        }
+        Some(number)
+    }
+
     /// Returns the next colour, in the order that the enum variants are
     ///defined, and cycling back to the start.
     pub fn next_colour(&self) -> Self {
@@ -546,12 +562,40 @@ impl HexPosition {
     }
 }
 
+/// Scale factors for line widths, text, and margins.
+///
+/// By default, these are all drawn in proportion to the hexagon diameter.
+/// These factors allow them to be scaled independently of the hexagon
+/// diameter, so that (for example) very small thumbnails can use thicker
+/// lines and larger text, and very large posters can use thinner lines and
+/// smaller margins.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HexScale {
+    /// The scale factor for line widths.
+    pub stroke: f64,
+    /// The scale factor for font sizes.
+    pub font: f64,
+    /// The scale factor for tile and map margins.
+    pub margin: f64,
+}
+
+impl Default for HexScale {
+    fn default() -> Self {
+        HexScale {
+            stroke: 1.0,
+            font: 1.0,
+            margin: 1.0,
+        }
+    }
+}
+
 /// The geometry of hexagonal tiles.
 ///
 /// The origin is defined to be the centre of the hexagon.
 #[derive(Debug)]
 pub struct Hex {
     /// The colours and drawing styles for this hexagon.
+    #[cfg(feature = "cairo")]
     pub theme: Theme,
     /// The maximal diameter (the length between opposite corners).
     pub max_d: f64,
@@ -559,12 +603,16 @@ pub struct Hex {
     pub min_d: f64,
     /// The hexagon orientation.
     orientation: Orientation,
+    /// The scale factors for line widths, text, and margins.
+    scale: HexScale,
     /// The coordinates of each hexagon corner, in anti-clockwise order,
     /// starting from the left corner ([Orientation::FlatTop]) or the
     /// upper-left corner ([Orientation::PointedTop]).
     corners: Vec<Coord>,
+    #[cfg(feature = "cairo")]
     #[allow(dead_code)]
     surface: cairo::ImageSurface,
+    #[cfg(feature = "cairo")]
     context: cairo::Context,
 }
 
@@ -592,17 +640,42 @@ impl Default for Hex {
 impl Hex {
     /// Constructs a hexagon for the given maximal diameter.
     pub fn new(max_d: f64) -> Self {
-        let theme = Theme::default();
-        Self::with_theme(max_d, theme)
-    }
-
-    /// Constructs a hexagon for the given maximal diameter and drawing theme.
-    pub fn with_theme(max_d: f64, theme: Theme) -> Self {
         // NOTE: the default orientation is FlatTop.
         let orientation = Orientation::FlatTop;
         let min_d = (3.0_f64).sqrt() * max_d / 2.0;
         let corners = Self::corner_coords(max_d, orientation);
+        #[cfg(feature = "cairo")]
+        let (surface, context) = Self::new_surface(max_d);
 
+        Self {
+            #[cfg(feature = "cairo")]
+            theme: Theme::default(),
+            max_d,
+            min_d,
+            orientation,
+            scale: HexScale::default(),
+            corners,
+            #[cfg(feature = "cairo")]
+            surface,
+            #[cfg(feature = "cairo")]
+            context,
+        }
+    }
+
+    /// Constructs a hexagon for the given maximal diameter and drawing theme.
+    #[cfg(feature = "cairo")]
+    pub fn with_theme(max_d: f64, theme: Theme) -> Self {
+        Self {
+            theme,
+            ..Self::new(max_d)
+        }
+    }
+
+    /// Returns a surface with sufficient dimensions for drawing a hexagon
+    /// with the given maximal diameter, and a context whose origin is the
+    /// centre of this surface.
+    #[cfg(feature = "cairo")]
+    fn new_surface(max_d: f64) -> (cairo::ImageSurface, cairo::Context) {
         let dim = (max_d * 2.0) as i32;
         let surface =
             cairo::ImageSurface::create(cairo::Format::ARgb32, dim, dim)
@@ -611,16 +684,48 @@ impl Hex {
             .expect("Can't create cairo::Context");
         // Move the origin to the centre of this surface.
         context.translate(max_d, max_d);
+        (surface, context)
+    }
 
-        Self {
-            theme,
-            max_d,
-            min_d,
-            orientation,
-            corners,
-            surface,
-            context,
-        }
+    /// Returns the lengths that define the shape of track segments, dits,
+    /// and cities.
+    ///
+    /// These are defined by the hexagon's theme, if the `cairo` feature is
+    /// enabled, and otherwise they are the [default](Geometry::default)
+    /// lengths.
+    pub fn geometry(&self) -> Geometry {
+        #[cfg(feature = "cairo")]
+        return self.theme.geometry();
+        #[cfg(not(feature = "cairo"))]
+        Geometry::default()
+    }
+
+    /// Returns the scale factors for line widths, text, and margins.
+    pub fn scale(&self) -> HexScale {
+        self.scale
+    }
+
+    /// Modifies the scale factors for line widths, text, and margins.
+    ///
+    /// Note that these factors do not affect the hexagon geometry, and are
+    /// retained when the hexagon is [resized](Self::resize).
+    pub fn set_scale(&mut self, scale: HexScale) {
+        self.scale = scale
+    }
+
+    /// Modifies the scale factor for line widths.
+    pub fn set_stroke_scale(&mut self, stroke: f64) {
+        self.scale.stroke = stroke
+    }
+
+    /// Modifies the scale factor for font sizes.
+    pub fn set_font_scale(&mut self, font: f64) {
+        self.scale.font = font
+    }
+
+    /// Modifies the scale factor for tile and map margins.
+    pub fn set_margin_scale(&mut self, margin: f64) {
+        self.scale.margin = margin
     }
 
     /// Returns the hexagon orientation.
@@ -687,18 +792,18 @@ impl Hex {
         self.min_d = min_d;
         self.corners = corners;
 
-        let dim = (max_d * 2.0) as i32;
-        let resize_surface =
-            self.surface.width() < dim || self.surface.height() < dim;
-        if resize_surface {
-            self.surface =
-                cairo::ImageSurface::create(cairo::Format::ARgb32, dim, dim)
-                    .expect("Can't create cairo::ImageSurface");
-            self.context = cairo::Context::new(&self.surface)
-                .expect("Can't create cairo::Context");
+        #[cfg(feature = "cairo")]
+        {
+            let dim = (max_d * 2.0) as i32;
+            let resize_surface =
+                self.surface.width() < dim || self.surface.height() < dim;
+            if resize_surface {
+                (self.surface, self.context) = Self::new_surface(max_d);
+            } else {
+                // Move the origin to the centre of this hexagon.
+                self.context.translate(max_d, max_d);
+            }
         }
-        // Move the origin to the centre of this hexagon.
-        self.context.translate(max_d, max_d);
     }
 
     /// Returns the ratio of the minimal diameter to the maximal diameter:
@@ -719,6 +824,7 @@ impl Hex {
     /// This context is intended for checking properties such as whether a
     /// specific coordinate is inside an area that would be affected by a
     /// stroke or fill operation.
+    #[cfg(feature = "cairo")]
     pub fn context(&self) -> &cairo::Context {
         &self.context
     }
@@ -739,6 +845,7 @@ impl Hex {
     }
 
     /// Defines the hexagon boundary as a path on the provided context.
+    #[cfg(feature = "cairo")]
     pub fn define_boundary(&self, ctx: &Context) {
         self.theme.hex_border.apply_line(ctx, self);
         ctx.new_path();
@@ -750,6 +857,7 @@ impl Hex {
     }
 
     /// Fills the hexagon with a specific colour on the provided context.
+    #[cfg(feature = "cairo")]
     pub fn draw_background(&self, colour: HexColour, ctx: &Context) {
         self.define_boundary(ctx);
         self.theme.apply_hex_colour(ctx, colour);
@@ -888,6 +996,30 @@ mod test {
         assert!(diff < epsilon);
     }
 
+    /// Tests that the scale factors are retained when resizing a hexagon,
+    /// and that they do not affect its geometry.
+    #[test]
+    fn scale_is_independent_of_size() {
+        let mut hex = Hex::new(125.0);
+        assert_eq!(hex.scale(), HexScale::default());
+        let corner = *hex.corner_coord(&HexCorner::Right);
+
+        hex.set_stroke_scale(2.0);
+        hex.set_font_scale(1.5);
+        hex.set_margin_scale(0.5);
+        let scale = HexScale {
+            stroke: 2.0,
+            font: 1.5,
+            margin: 0.5,
+        };
+        assert_eq!(hex.scale(), scale);
+        assert_eq!(*hex.corner_coord(&HexCorner::Right), corner);
+
+        hex.resize(250.0);
+        assert_eq!(hex.scale(), scale);
+        assert_eq!(hex.corner_coord(&HexCorner::Right).x, 2.0 * corner.x);
+    }
+
     /// Tests that adding a RotateCW to a HexFace returns the correct HexFace.
     #[test]
     fn hex_face_rotation() {
@@ -1,26 +1,40 @@
 /// Constant values used to define, e.g., angles in radians.
 pub mod consts;
 
+/// Colours for drawing strokes and fills.
+pub mod colour;
+
 /// Cartesian coordinates for use with hex tiles.
 pub mod coord;
 
+/// Lengths that define the shape of tile elements.
+pub mod geometry;
+
 /// Hexagonal tiles, and attributes such as faces, corners, and colours.
 pub mod hex;
 
 /// Define colours, line styles, and other drawing properties.
+#[cfg(feature = "cairo")]
 pub mod theme;
 
 #[doc(inline)]
 pub use consts::*;
 
+#[doc(inline)]
+pub use colour::Colour;
+
 #[doc(inline)]
 pub use coord::Coord;
 
+#[doc(inline)]
+pub use geometry::{Geometry, Length};
+
 #[doc(inline)]
 pub use hex::{
     Delta, Direction, Hex, HexColour, HexCorner, HexFace, HexPosition,
-    Orientation, RotateCW,
+    HexScale, Orientation, RotateCW,
 };
 
+#[cfg(feature = "cairo")]
 #[doc(inline)]
-pub use theme::{Colour, Theme};
+pub use theme::Theme;
@@ -14,6 +14,8 @@
 //!
 //! [Length::absolute()](theme::Length::absolute) returns lengths (in pixels)
 //! for the provided [Hex].
+//! Line widths, text, and margins are also scaled by the [Hex] scale factors
+//! (see [HexScale](crate::HexScale)).
 //!
 //! [Text::labeller()](theme::Text::labeller) creates
 //! [Labeller](theme::Labeller) values that can be used to draw text on a
@@ -21,29 +23,12 @@
 
 use std::collections::BTreeMap;
 
-use crate::{Coord, Hex, HexColour};
+pub use crate::colour::{Colour, ParseColourError};
+pub use crate::geometry::Length;
+use crate::{Coord, Geometry, Hex, HexColour};
 use cairo::Context;
 use pangocairo::functions::{create_layout, show_layout, update_layout};
 
-/// Defines relative and absolute lengths.
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub enum Length {
-    /// Lengths that are relative to the hexagon maximal diameter.
-    Relative(f64),
-    /// Lengths that are absolute (in pixels).
-    Absolute(f64),
-}
-
-impl Length {
-    /// Returns the absolute length in pixels.
-    pub fn absolute(&self, hex: &Hex) -> f64 {
-        match self {
-            Self::Relative(frac) => frac * hex.max_d,
-            Self::Absolute(length) => *length,
-        }
-    }
-}
-
 /// Defines the drawing properties for strokes and fills.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Draw {
@@ -61,6 +46,12 @@ pub struct Draw {
 }
 
 impl Draw {
+    /// Returns the absolute line width in pixels, which is also scaled by
+    /// the hexagon's stroke scale factor.
+    pub fn line_width(&self, hex: &Hex) -> f64 {
+        self.width.line_width(hex)
+    }
+
     /// Applies the line style and stroke colour to the provided context.
     pub fn apply_line_and_stroke(&self, ctx: &Context, hex: &Hex) {
         self.apply_line(ctx, hex);
@@ -69,7 +60,7 @@ impl Draw {
 
     /// Applies the line style to the provided context.
     pub fn apply_line(&self, ctx: &Context, hex: &Hex) {
-        ctx.set_line_width(self.width.absolute(hex));
+        ctx.set_line_width(self.line_width(hex));
         ctx.set_line_cap(self.cap);
         ctx.set_line_join(self.join);
     }
@@ -353,9 +344,16 @@ impl Text {
     }
 
     /// Returns a [Labeller] that can be used to draw text.
+    ///
+    /// If the hexagon's theme defines a [font
+    /// family](Theme::font_family), it is used instead of this style's
+    /// font family.
     pub fn labeller<'a>(&self, ctx: &'a Context, hex: &Hex) -> Labeller<'a> {
-        let scale = hex.max_d / 125.0;
-        let font_descr = self.describe(scale);
+        let scale = hex.max_d / 125.0 * hex.scale().font;
+        let mut font_descr = self.describe(scale);
+        if let Some(family) = &hex.theme.font_family {
+            font_descr.set_family(family);
+        }
         let layout = create_layout(ctx);
         layout.set_font_description(Some(&font_descr));
         layout.set_alignment(self.align);
@@ -416,6 +414,11 @@ impl Text {
         self
     }
 
+    /// Returns the font size (in points).
+    pub fn get_font_size(&self) -> f64 {
+        self.font_size
+    }
+
     /// Sets the bounding box horizontal alignment, relative to the specified
     /// anchor coordinates.
     pub fn halign(&mut self, align: AlignH) -> &mut Self {
@@ -561,12 +564,20 @@ pub struct Theme {
     pub hex_barrier: Draw,
     /// The border for highlighted hexagons.
     pub hex_highlight: Draw,
+    /// The style for mountain terrain drawn on map hexes.
+    pub terrain_mountain: Draw,
+    /// The style for water terrain drawn on map hexes.
+    pub terrain_water: Draw,
     /// The outer (background) style for track segments.
     pub track_outer: Draw,
     /// The inner (foreground) style for track segments.
     pub track_inner: Draw,
     /// The length of off-board track segments.
     pub track_offboard_length: Length,
+    /// The style for arrows that point out of a tile through a hex face.
+    pub face_arrow: Draw,
+    /// The length of arrows that point out of a tile through a hex face.
+    pub face_arrow_length: Length,
     /// The outer (background) style for token spaces.
     pub token_space_outer: Draw,
     /// The inner (foreground) style for token spaces.
@@ -605,6 +616,9 @@ pub struct Theme {
     pub map_border: Draw,
     /// The width of the margin around each map edge.
     pub map_margin: Length,
+    /// The additional margin around each map edge that contains the row and
+    /// column labels, when these labels are shown.
+    pub border_margin: Length,
     /// The text settings for tile name labels.
     pub tile_label: Text,
     /// The text settings for city name labels.
@@ -621,10 +635,23 @@ pub struct Theme {
     pub phase_revenue_label: Text,
     /// The text settings for token labels.
     pub token_label: Text,
+    /// The smallest font size (in points) to which token labels are shrunk,
+    /// so that they fit inside the token.
+    pub token_label_min_font_size: f64,
+    /// Whether token labels that do not fit inside the token on a single
+    /// line may be split over two lines.
+    pub token_label_two_lines: bool,
+    /// The text settings for hex coordinate labels.
+    pub coordinate_label: Text,
+    /// The text settings for row and column labels around the map border.
+    pub border_label: Text,
     /// The horizontal margin for phase revenue labels.
     pub phase_revenue_margin_x: Length,
     /// The vertical margin for phase revenue labels.
     pub phase_revenue_margin_y: Length,
+    /// The font family for all text, if any, which overrides the family of
+    /// each text style.
+    pub font_family: Option<String>,
 }
 
 impl Default for Theme {
@@ -647,6 +674,7 @@ impl Default for Theme {
         ]
         .into_iter()
         .collect();
+        let geometry = Geometry::default();
         let highlight_colours = vec![
             Colour::from((179, 25, 25)),
             Colour::from((25, 179, 25)),
@@ -671,8 +699,20 @@ impl Default for Theme {
                 stroke: Colour::from((255, 0, 0)),
                 ..Default::default()
             },
+            terrain_mountain: Draw {
+                width: Length::Relative(0.015),
+                stroke: Colour::from((115, 85, 55)),
+                cap: cairo::LineCap::Round,
+                ..Default::default()
+            },
+            terrain_water: Draw {
+                width: Length::Relative(0.015),
+                stroke: Colour::from((40, 110, 200)),
+                cap: cairo::LineCap::Round,
+                ..Default::default()
+            },
             track_outer: Draw {
-                width: Length::Relative(0.10),
+                width: geometry.track_width,
                 stroke: Colour::WHITE,
                 ..Default::default()
             },
@@ -682,6 +722,13 @@ impl Default for Theme {
                 ..Default::default()
             },
             track_offboard_length: Length::Relative(0.3),
+            face_arrow: Draw {
+                width: Length::Relative(0.02),
+                stroke: Colour::WHITE,
+                fill: Colour::BLACK,
+                ..Default::default()
+            },
+            face_arrow_length: Length::Relative(0.15),
             token_space_outer: Draw {
                 width: Length::Relative(0.03),
                 stroke: Colour::WHITE,
@@ -717,11 +764,11 @@ impl Default for Theme {
                 ..Default::default()
             },
             dit_outer_length: Length::Relative(0.11),
-            dit_inner_length: Length::Relative(0.10),
-            track_gentle_radius: Length::Relative(0.75),
-            track_hard_radius: Length::Relative(0.25),
+            dit_inner_length: geometry.dit_length,
+            track_gentle_radius: geometry.track_gentle_radius,
+            track_hard_radius: geometry.track_hard_radius,
             dit_circle_radius: Length::Relative(0.085),
-            token_space_radius: Length::Relative(0.125),
+            token_space_radius: geometry.token_space_radius,
             label_circle: Draw {
                 width: Length::Relative(0.01),
                 stroke: Colour::BLACK,
@@ -735,7 +782,8 @@ impl Default for Theme {
                 stroke: Colour::BLACK,
                 ..Default::default()
             },
-            map_margin: Length::Absolute(10.0),
+            map_margin: geometry.map_margin,
+            border_margin: geometry.border_margin,
             tile_label: Text {
                 family: FontFamily::Sans,
                 font_size: 8.0,
@@ -790,13 +838,61 @@ impl Default for Theme {
                 max_width: Some(30.0),
                 ..Default::default()
             },
+            token_label_min_font_size: 6.0,
+            token_label_two_lines: true,
+            coordinate_label: Text {
+                family: FontFamily::Sans,
+                font_size: 8.0,
+                colour: Colour::from((95, 95, 95)),
+                horiz: AlignH::Centre,
+                vert: AlignV::Middle,
+                ..Default::default()
+            },
+            border_label: Text {
+                family: FontFamily::Sans,
+                font_size: 12.0,
+                weight: pango::Weight::Bold,
+                horiz: AlignH::Centre,
+                vert: AlignV::Middle,
+                ..Default::default()
+            },
             phase_revenue_margin_x: Length::Absolute(2.0),
             phase_revenue_margin_y: Length::Absolute(1.0),
+            font_family: None,
         }
     }
 }
 
+/// The names of the predefined themes, which can be retrieved with
+/// [Theme::named].
+/// The first name refers to the default theme.
+pub const THEME_NAMES: [&str; 2] = ["Default", "High contrast"];
+
 impl Theme {
+    /// Returns the predefined theme with this name (see [THEME_NAMES]), if
+    /// any.
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "Default" => Some(Theme::default()),
+            "High contrast" => Some(Theme::high_contrast()),
+            _ => None,
+        }
+    }
+
+    /// Returns a theme with darker hex borders, thicker track segments and
+    /// map borders, and black hex coordinate labels, which is easier to
+    /// read on small or low-quality displays.
+    pub fn high_contrast() -> Self {
+        let mut theme = Theme::default();
+        theme.hex_border.width = Length::Relative(0.02);
+        theme.hex_border.stroke = Colour::from((63, 63, 63));
+        theme.hex_highlight.width = Length::Relative(0.04);
+        theme.track_inner.width = Length::Relative(0.09);
+        theme.map_border.width = Length::Relative(0.07);
+        theme.coordinate_label.colour = Colour::BLACK;
+        theme
+    }
+
     /// Sets a hexagon colour as the source pattern for the provided context.
     pub fn apply_hex_colour(&self, ctx: &Context, hc: HexColour) {
         let colour = self
@@ -821,6 +917,20 @@ impl Theme {
         self.highlight_colours[ix]
     }
 
+    /// Returns the lengths that define the shape of track segments, dits,
+    /// and cities for this theme.
+    pub fn geometry(&self) -> Geometry {
+        Geometry {
+            track_width: self.track_outer.width,
+            track_gentle_radius: self.track_gentle_radius,
+            track_hard_radius: self.track_hard_radius,
+            dit_length: self.dit_inner_length,
+            token_space_radius: self.token_space_radius,
+            map_margin: self.map_margin,
+            border_margin: self.border_margin,
+        }
+    }
+
     /// Retrieves the colour associated with the provided hexagon background.
     pub fn hex_colour(&self, hc: HexColour) -> Option<Colour> {
         self.hex_colours.get(&hc).copied()
@@ -845,206 +955,10 @@ impl Theme {
     }
 }
 
-/// The scaling factor for converting u8 values to the unit interval.
-static SCALE_U8_COLOUR: f64 = 1.0 / u8::MAX as f64;
-
-/// Defines colours for drawing strokes and fills.
-///
-/// Each colour comprises four channels: red, green, blue, and alpha.
-/// These values correspond to Cairo's 8-bit RGB colour space.
-///
-/// Colours can be constructed from 3-tuples (red, green, blue) and 4-tuples
-/// (red, green, blue, alpha) of `u8` values (`0..=255`) or `f64` values
-/// (`0.0..=1.0`).
-/// When constructed from 3-tuples, the alpha channel is set to 255 (opaque).
-///
-/// ```
-/// # use n18hex::theme::Colour;
-/// let red = Colour::from((255, 0, 0));
-/// let green = Colour::from((0.0, 1.0, 0.0));
-/// let blue = Colour::from((0, 0, 255, 255));
-/// let black = Colour::from((0.0, 0.0, 0.0, 1.0));
-/// ```
-///
-/// Colours can also be constructed from valid hexadecimal strings:
-///
-/// ```
-/// # use n18hex::theme::Colour;
-/// let red = "#ff0000".parse::<Colour>().unwrap();
-/// let translucent_blue = "#0000ff7f".parse::<Colour>().unwrap();
-/// ```
-///
-/// Attempts to parse invalid strings will return a [ParseColourError] value.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Colour {
-    pub red: u8,
-    pub green: u8,
-    pub blue: u8,
-    pub alpha: u8,
-}
-
-impl Colour {
-    /// The colour white ("#FFFFFF").
-    pub const WHITE: Self = Self {
-        red: u8::MAX,
-        green: u8::MAX,
-        blue: u8::MAX,
-        alpha: u8::MAX,
-    };
-
-    /// The colour black ("#000000").
-    pub const BLACK: Self = Self {
-        red: 0,
-        green: 0,
-        blue: 0,
-        alpha: u8::MAX,
-    };
-
-    /// Entirely transparent.
-    pub const TRANSPARENT: Self = Self {
-        red: u8::MAX,
-        green: u8::MAX,
-        blue: u8::MAX,
-        alpha: 0,
-    };
-
-    /// Adjust the transparency of this colour.
-    pub fn with_alpha(mut self, alpha: u8) -> Self {
-        self.alpha = alpha;
-        self
-    }
-
-    /// Adjust the transparency of this colour (`0.0..=1.0`).
-    pub fn with_alpha_f64(mut self, alpha: f64) -> Self {
-        self.alpha = (alpha.clamp(0.0, 1.0) * u8::MAX as f64).round() as u8;
-        self
-    }
-
-    /// Use this colour as the source for the provided context.
-    pub fn apply_colour(&self, ctx: &Context) {
-        let r = self.red as f64 * SCALE_U8_COLOUR;
-        let g = self.green as f64 * SCALE_U8_COLOUR;
-        let b = self.blue as f64 * SCALE_U8_COLOUR;
-        let a = self.alpha as f64 * SCALE_U8_COLOUR;
-        ctx.set_source_rgba(r, g, b, a)
-    }
-
-    /// Returns the hexadecimal representation of this colour, without the
-    /// alpha channel.
-    pub fn as_rgb(&self) -> String {
-        format!("#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
-    }
-
-    /// Returns the hexadecimal representation of this colour, including the
-    /// alpha channel.
-    pub fn as_rgba(&self) -> String {
-        format!(
-            "#{:02x}{:02x}{:02x}{:02x}",
-            self.red, self.green, self.blue, self.alpha
-        )
-    }
-}
-
-impl std::fmt::Display for Colour {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.as_rgba())
-    }
-}
-
-impl From<&Colour> for Colour {
-    fn from(src: &Colour) -> Self {
-        *src
-    }
-}
-
-impl From<(f64, f64, f64)> for Colour {
-    fn from(src: (f64, f64, f64)) -> Self {
-        let fmax = u8::MAX as f64;
-        let r = (src.0 * fmax).round() as u8;
-        let g = (src.1 * fmax).round() as u8;
-        let b = (src.2 * fmax).round() as u8;
-        Colour::from((r, g, b))
-    }
-}
-
-impl From<(f64, f64, f64, f64)> for Colour {
-    fn from(src: (f64, f64, f64, f64)) -> Self {
-        let fmax = u8::MAX as f64;
-        let r = (src.0 * fmax).round() as u8;
-        let g = (src.1 * fmax).round() as u8;
-        let b = (src.2 * fmax).round() as u8;
-        let a = (src.3 * fmax).round() as u8;
-        Colour::from((r, g, b, a))
-    }
-}
-
-impl From<(u8, u8, u8)> for Colour {
-    fn from(src: (u8, u8, u8)) -> Self {
-        Colour {
-            red: src.0,
-            green: src.1,
-            blue: src.2,
-            alpha: u8::MAX,
-        }
-    }
-}
-
-impl From<(u8, u8, u8, u8)> for Colour {
-    fn from(src: (u8, u8, u8, u8)) -> Self {
-        Self::from((src.0, src.1, src.2)).with_alpha(src.3)
-    }
-}
-
-/// An error which can be returned when parsing colour strings.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ParseColourError {
-    input: String,
-}
-
-impl std::error::Error for ParseColourError {}
-
-impl std::fmt::Display for ParseColourError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Invalid colour string: {:?}", self.input)
-    }
-}
-
-impl From<std::num::ParseIntError> for ParseColourError {
-    fn from(src: std::num::ParseIntError) -> Self {
-        let input = format!("{}", src);
-        ParseColourError { input }
-    }
-}
-
-impl std::str::FromStr for Colour {
-    type Err = ParseColourError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let num_bytes = s.len();
-        let invalid_len = num_bytes != 7 && num_bytes != 9;
-        let invalid_start = &s[0..1] != "#";
-        if invalid_len || invalid_start {
-            return Err(ParseColourError {
-                input: s.to_string(),
-            });
-        }
-        let red = u8::from_str_radix(&s[1..3], 16)?;
-        let green = u8::from_str_radix(&s[3..5], 16)?;
-        let blue = u8::from_str_radix(&s[5..7], 16)?;
-        let alpha = if num_bytes == 9 {
-            u8::from_str_radix(&s[7..9], 16)?
-        } else {
-            u8::MAX
-        };
-        Ok(Colour::from((red, green, blue, alpha)))
-    }
-}
-
 #[cfg(test)]
 mod test {
 
     use super::*;
-    use std::error::Error;
 
     /// Converts a HexColour into a Colour and prints this Colour as a
     /// hexadecimal triplet.
@@ -1071,57 +985,40 @@ mod test {
     }
 
     #[test]
-    /// Tests the round-trip between RGBA strings and Colours.
-    fn rgba_round_trip() -> Result<(), Box<dyn std::error::Error>> {
-        let input = "#dbbf12aa";
-        let colour: Colour = input.parse()?;
-        let output = colour.as_rgba();
-        assert_eq!(input, output);
-        Ok(())
-    }
+    /// Tests that line widths and margins are scaled by the hexagon scale
+    /// factors, independently of the hexagon diameter.
+    fn scaled_widths_and_margins() {
+        let mut hex = Hex::new(100.0);
+        let theme = Theme::default();
+        assert_eq!(theme.track_inner.line_width(&hex), 8.0);
+        assert_eq!(theme.tile_margin.margin(&hex), 2.5);
+        assert_eq!(theme.map_margin.margin(&hex), 10.0);
 
-    #[test]
-    /// Tests the round-trip between RGBA strings and Colours.
-    fn rgba_invalid_string() -> Result<(), Box<dyn Error>> {
-        let input = "#dbbf12AAA";
-        assert!(input.parse::<Colour>().is_err());
-        Ok(())
+        hex.set_stroke_scale(0.5);
+        hex.set_margin_scale(2.0);
+        assert_eq!(theme.track_inner.line_width(&hex), 4.0);
+        assert_eq!(theme.tile_margin.margin(&hex), 5.0);
+        assert_eq!(theme.map_margin.margin(&hex), 20.0);
+        // Other lengths are not affected by these scale factors.
+        assert_eq!(theme.track_inner.width.absolute(&hex), 8.0);
+        assert_eq!(theme.token_space_radius.absolute(&hex), 12.5);
     }
 
     #[test]
-    /// Tests parsing a range of valid RGB and RGBA strings.
-    ///
-    /// The original version of this test case covered every valid input
-    /// string, but this took 13 seconds to run when compiled in release mode.
-    fn rgb_rgba_inputs() -> Result<(), Box<dyn Error>> {
-        let in_vals: [u8; 8] = [0, 1, 63, 127, 128, 191, 254, 255];
-        for r in &in_vals {
-            for g in &in_vals {
-                for b in &in_vals {
-                    // Parse the #RRGGBB input string.
-                    let input = format!("#{:02x}{:02x}{:02x}", r, g, b);
-                    let parsed = input.parse::<Colour>();
-                    assert!(parsed.is_ok());
-                    let colour = parsed.unwrap();
-                    // Ensure we obtain the expected colour values.
-                    assert_eq!(*r, colour.red);
-                    assert_eq!(*g, colour.green);
-                    assert_eq!(*b, colour.blue);
-                    for a in &in_vals {
-                        // Parse the #RRGGBBAA input string.
-                        let input_a = format!("{}{:02x}", input, a);
-                        let parsed_a = input_a.parse::<Colour>();
-                        assert!(parsed_a.is_ok());
-                        let colour_a = parsed_a.unwrap();
-                        // Ensure we obtain the expected colour values.
-                        assert_eq!(colour.red, colour_a.red);
-                        assert_eq!(colour.green, colour_a.green);
-                        assert_eq!(colour.blue, colour_a.blue);
-                        assert_eq!(*a, colour_a.alpha);
-                    }
-                }
-            }
-        }
-        Ok(())
+    /// Tests that each predefined theme can be retrieved by name, and that
+    /// the first name refers to the default theme.
+    fn named_themes() {
+        assert_eq!(Theme::named(THEME_NAMES[0]), Some(Theme::default()));
+        assert_eq!(
+            Theme::named(THEME_NAMES[1]),
+            Some(Theme::high_contrast())
+        );
+        assert_ne!(Theme::high_contrast(), Theme::default());
+        // Themes must not change the shape of tiles.
+        assert_eq!(
+            Theme::high_contrast().geometry(),
+            Theme::default().geometry()
+        );
+        assert_eq!(Theme::named("Unknown"), None);
     }
 }
@@ -0,0 +1,287 @@
+//! Package a game state and an image of the map into a single file, so that
+//! games can be played by email.
+//!
+//! A bundle is a self-contained JSON file that records the game name, map
+//! variant, and schema version, followed by the game state (including the
+//! game journal) and a PNG image of the map, encoded as base64 text.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::migrate::{self, MigrationError};
+use crate::{write_json, Error, GameState, SCHEMA_VERSION};
+
+/// The first eight bytes of every PNG image.
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// The characters used to encode binary data as base64 text.
+const BASE64_CHARS: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// A game state and an image of the map, as exchanged between players.
+pub struct Bundle {
+    /// The game state, including the game journal.
+    pub state: n18game::GameState,
+    /// The contents of a PNG image of the map.
+    pub image: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BundleFile {
+    /// The schema version, see [SCHEMA_VERSION].
+    version: u32,
+    /// A unique identifier for the game.
+    game: String,
+    /// The map variant.
+    variant: String,
+    /// The game state.
+    state: GameState,
+    /// The PNG image of the map, encoded as base64 text.
+    image: String,
+}
+
+/// Encodes binary data as base64 text, with padding.
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(4 * bytes.len().div_ceil(3));
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let ix = (n >> (18 - 6 * i)) & 0x3f;
+                text.push(BASE64_CHARS[ix as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+/// Decodes base64 text, with or without padding, and returns `None` if the
+/// text is not valid base64.
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=');
+    if text.len() % 4 == 1 {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(3 * text.len() / 4);
+    for chunk in text.as_bytes().chunks(4) {
+        let mut n: u32 = 0;
+        for (i, c) in chunk.iter().enumerate() {
+            let ix = BASE64_CHARS.iter().position(|b| b == c)? as u32;
+            n |= ix << (18 - 6 * i);
+        }
+        let decoded = n.to_be_bytes();
+        bytes.extend_from_slice(&decoded[1..chunk.len()]);
+    }
+    Some(bytes)
+}
+
+/// Returns an [Error::Json] value that describes an invalid bundle.
+fn invalid(path: &Path, msg: &str) -> Error {
+    Error::json(Some(path), serde::de::Error::custom(msg))
+}
+
+/// Checks that a value in the bundle header matches the game state.
+fn check_match(
+    path: &Path,
+    field: &'static str,
+    bundle: String,
+    state: String,
+) -> Result<(), Error> {
+    if bundle == state {
+        Ok(())
+    } else {
+        Err(Error::BundleMismatch {
+            path: path.to_path_buf(),
+            field,
+            bundle,
+            state,
+        })
+    }
+}
+
+/// Reads a game state and an image of the map from a bundle.
+///
+/// Bundles saved with an older schema version are upgraded to the current
+/// version, and those saved with a newer version are rejected.
+/// The game name, map variant, and schema version recorded in the bundle
+/// must match those of the game state that it contains.
+///
+/// Note that the map configuration should be checked with
+/// [check_map_descr](crate::check_map_descr) before it is used to update a
+/// map.
+pub fn read_bundle<P: AsRef<Path>>(path: P) -> Result<Bundle, Error> {
+    let path = path.as_ref();
+    let text =
+        std::fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+    let mut value: serde_json::Value =
+        crate::from_json_str(&text, Some(path))?;
+    let version_of = |value: &serde_json::Value| {
+        value
+            .as_object()
+            .ok_or(MigrationError::Invalid("expected a JSON object"))
+            .and_then(migrate::version_of)
+            .map_err(|e| crate::migration_error(e, Some(path)))
+    };
+    let version = version_of(&value)?;
+    if version > SCHEMA_VERSION {
+        return Err(crate::migration_error(
+            MigrationError::Unsupported(version),
+            Some(path),
+        ));
+    }
+    let state = value
+        .get_mut("state")
+        .ok_or_else(|| invalid(path, "missing field `state`"))?;
+    let state_version = version_of(state)?;
+    check_match(
+        path,
+        "version",
+        version.to_string(),
+        state_version.to_string(),
+    )?;
+    migrate::migrate_game_state(state)
+        .map_err(|e| crate::migration_error(e, Some(path)))?;
+
+    let bundle: BundleFile = serde_json::from_value(value)
+        .map_err(|e| Error::json(Some(path), e))?;
+    check_match(path, "game", bundle.game, bundle.state.game.clone())?;
+    check_match(
+        path,
+        "variant",
+        bundle.variant,
+        bundle.state.variant.clone(),
+    )?;
+    let image = decode_base64(&bundle.image)
+        .filter(|image| image.starts_with(PNG_SIGNATURE))
+        .ok_or_else(|| invalid(path, "the map image is not a PNG image"))?;
+    Ok(Bundle {
+        state: bundle.state.try_into()?,
+        image,
+    })
+}
+
+/// Writes a game state and a PNG image of the map to a bundle.
+pub fn write_bundle<P: AsRef<Path>>(
+    path: P,
+    game_state: n18game::GameState,
+    image: &[u8],
+    pretty: bool,
+) -> Result<(), Error> {
+    let state: GameState = game_state.into();
+    let bundle = BundleFile {
+        version: SCHEMA_VERSION,
+        game: state.game.clone(),
+        variant: state.variant.clone(),
+        state,
+        image: encode_base64(image),
+    };
+    write_json(path, &bundle, pretty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use n18game::{Game, Journal};
+    use n18hex::Hex;
+
+    static OUT_DIR: &str = "../../tests/output";
+
+    fn output_path(file: &'static str) -> std::path::PathBuf {
+        std::path::Path::new(OUT_DIR).join(file)
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        let cases: [(&[u8], &str); 5] = [
+            (b"", ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foobar", "Zm9vYmFy"),
+        ];
+        for (bytes, text) in cases {
+            assert_eq!(encode_base64(bytes), text);
+            assert_eq!(decode_base64(text).as_deref(), Some(bytes));
+        }
+        assert_eq!(decode_base64("Zm8"), Some(b"fo".to_vec()));
+        assert!(decode_base64("Zm9v!").is_none());
+        assert!(decode_base64("Zm9vY").is_none());
+    }
+
+    #[test]
+    fn bundle_round_trip() {
+        let hex = Hex::default();
+        let game = n18game::_1867::Game::new();
+        let map = game.create_map(&hex);
+        let filename = output_path("test-bundle_round_trip.bundle");
+
+        // Check that the game state, journal, and image are restored.
+        let mut journal = Journal::new();
+        journal.record("Placed tile 57 at D10 (rotation 1)");
+        let state_in = game.save(&map).with_journal(journal.clone());
+        let image: Vec<u8> =
+            PNG_SIGNATURE.iter().copied().chain(0..=255).collect();
+        let write_res = write_bundle(&filename, state_in, &image, true);
+        assert!(write_res.is_ok(), "Could not write {}", filename.display());
+        let bundle = read_bundle(&filename).unwrap();
+        assert_eq!(bundle.state.game, game.name());
+        assert_eq!(bundle.state.journal, journal);
+        assert_eq!(bundle.image, image);
+
+        // Check that the game name must match the game state.
+        let text = std::fs::read_to_string(&filename).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        let mut renamed = value.clone();
+        renamed["game"] = serde_json::json!("1861");
+        std::fs::write(&filename, renamed.to_string()).unwrap();
+        match read_bundle(&filename) {
+            Err(Error::BundleMismatch {
+                field,
+                bundle,
+                state,
+                ..
+            }) => {
+                assert_eq!(field, "game");
+                assert_eq!(bundle, "1861");
+                assert_eq!(state, game.name());
+            }
+            _ => panic!("Expected a bundle mismatch error"),
+        }
+
+        // Check that the schema versions must match.
+        let mut unversioned = value.clone();
+        unversioned["state"]
+            .as_object_mut()
+            .unwrap()
+            .remove("version");
+        std::fs::write(&filename, unversioned.to_string()).unwrap();
+        match read_bundle(&filename) {
+            Err(Error::BundleMismatch { field, .. }) => {
+                assert_eq!(field, "version")
+            }
+            _ => panic!("Expected a bundle mismatch error"),
+        }
+
+        // Check that bundles with a newer version number are rejected.
+        let mut newer = value.clone();
+        newer["version"] = serde_json::json!(SCHEMA_VERSION + 1);
+        std::fs::write(&filename, newer.to_string()).unwrap();
+        assert!(matches!(
+            read_bundle(&filename),
+            Err(Error::UnsupportedVersion { .. })
+        ));
+
+        // Check that the image must be a PNG image.
+        let mut no_image = value;
+        no_image["image"] = serde_json::json!(encode_base64(b"image"));
+        std::fs::write(&filename, no_image.to_string()).unwrap();
+        assert!(matches!(read_bundle(&filename), Err(Error::Json { .. })));
+    }
+}
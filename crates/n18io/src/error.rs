@@ -0,0 +1,314 @@
+//! The error type for reading and writing tiles, routes, maps, and games.
+
+use std::path::{Path, PathBuf};
+
+/// The errors that can occur when reading or writing files, or when checking
+/// that a map description is consistent with a map.
+#[derive(Debug)]
+pub enum Error {
+    /// A file, reader, or writer could not be opened, created, read, or
+    /// written.
+    Io {
+        path: Option<PathBuf>,
+        source: std::io::Error,
+    },
+    /// A file or JSON text did not contain valid JSON, or did not match the
+    /// expected structure.
+    Json {
+        path: Option<PathBuf>,
+        line: usize,
+        column: usize,
+        source: serde_json::Error,
+    },
+    /// A map hex refers to a tile that is not defined.
+    UnknownTile {
+        addr: n18map::HexAddress,
+        tile: String,
+    },
+    /// A map hex refers to a token that is not defined.
+    UnknownToken {
+        addr: n18map::HexAddress,
+        token: String,
+    },
+    /// A map hex refers to a token space that the tile does not contain.
+    InvalidTokenSpace {
+        addr: n18map::HexAddress,
+        tile: String,
+        space_ix: usize,
+    },
+    /// A map hex has an invalid tile rotation.
+    InvalidRotation {
+        addr: n18map::HexAddress,
+        rotation: String,
+    },
+    /// A map description or a token list contains more than one problem,
+    /// such as unknown tiles or tokens, or invalid token placements.
+    Invalid {
+        what: &'static str,
+        problems: Vec<Error>,
+    },
+    /// A file could not be read as an image.
+    #[cfg(feature = "cairo")]
+    Image { path: PathBuf, source: cairo::Error },
+    /// A file defines tiles whose names are already used by other tiles.
+    DuplicateTiles { path: PathBuf, names: Vec<String> },
+    /// A file or JSON text was written using a schema version that is not
+    /// supported.
+    UnsupportedVersion {
+        path: Option<PathBuf>,
+        found: u32,
+        supported: u32,
+    },
+    /// A game bundle records a game name, map variant, or schema version
+    /// that differs from that of the game state it contains.
+    BundleMismatch {
+        path: PathBuf,
+        field: &'static str,
+        bundle: String,
+        state: String,
+    },
+    /// A line of a CSV file or CSV text did not contain the expected fields.
+    Csv {
+        path: Option<PathBuf>,
+        line: usize,
+        message: String,
+    },
+    /// A token placement could not be applied to the map.
+    InvalidTokenPlacement {
+        entry: usize,
+        company: String,
+        hex: String,
+        city: usize,
+        reason: &'static str,
+    },
+    /// A tile defines a dit revenue for a colour that is not a game phase.
+    InvalidPhaseRevenue {
+        tile: String,
+        colour: n18hex::HexColour,
+    },
+}
+
+impl Error {
+    /// Returns an [Error::Io] value for the file `path`.
+    pub(crate) fn io<P: AsRef<Path>>(
+        path: P,
+        source: std::io::Error,
+    ) -> Self {
+        Error::Io {
+            path: Some(path.as_ref().to_path_buf()),
+            source,
+        }
+    }
+
+    /// Returns an [Error::Image] value for the file `path`, or an
+    /// [Error::Io] value if `source` was caused by an I/O error.
+    #[cfg(feature = "cairo")]
+    pub(crate) fn image<P: AsRef<Path>>(
+        path: P,
+        source: cairo::IoError,
+    ) -> Self {
+        match source {
+            cairo::IoError::Io(source) => Error::io(path, source),
+            cairo::IoError::Cairo(source) => Error::Image {
+                path: path.as_ref().to_path_buf(),
+                source,
+            },
+        }
+    }
+
+    /// Returns an [Error::Json] value for the file `path` (if any), or an
+    /// [Error::Io] value if `source` was caused by an I/O error.
+    pub(crate) fn json(
+        path: Option<&Path>,
+        source: serde_json::Error,
+    ) -> Self {
+        let path = path.map(|path| path.to_path_buf());
+        if source.is_io() {
+            return Error::Io {
+                path,
+                source: source.into(),
+            };
+        }
+        Error::Json {
+            path,
+            line: source.line(),
+            column: source.column(),
+            source,
+        }
+    }
+
+    /// Returns `Ok(())` if there are no `problems`, the only problem if there
+    /// is exactly one, and an [Error::Invalid] value otherwise.
+    ///
+    /// The `what` argument describes where the problems were found (e.g.,
+    /// `"map"`).
+    pub(crate) fn from_problems(
+        what: &'static str,
+        mut problems: Vec<Error>,
+    ) -> Result<(), Self> {
+        match problems.len() {
+            0 => Ok(()),
+            1 => Err(problems.remove(0)),
+            _ => Err(Error::Invalid { what, problems }),
+        }
+    }
+
+    /// Returns the path of the file associated with this error, if any.
+    pub fn path(&self) -> Option<&Path> {
+        use Error::*;
+        match self {
+            Io { path, .. } => path.as_deref(),
+            Json { path, .. } => path.as_deref(),
+            #[cfg(feature = "cairo")]
+            Image { path, .. } => Some(path),
+            DuplicateTiles { path, .. } => Some(path),
+            UnsupportedVersion { path, .. } => path.as_deref(),
+            BundleMismatch { path, .. } => Some(path),
+            Csv { path, .. } => path.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use Error::*;
+        match self {
+            Io {
+                path: Some(path),
+                source,
+            } => {
+                write!(f, "Could not access '{}': {}", path.display(), source)
+            }
+            Io { path: None, source } => {
+                write!(f, "Could not read or write: {}", source)
+            }
+            Json {
+                path,
+                line,
+                column,
+                source,
+            } => {
+                write!(f, "Invalid JSON")?;
+                if let Some(path) = path {
+                    write!(f, " in '{}'", path.display())?;
+                }
+                // NOTE: serde_json reports a line number of zero for errors
+                // that do not occur at a specific location in the input.
+                if *line > 0 {
+                    write!(f, " at line {}, column {}", line, column)?;
+                }
+                write!(f, ": {}", source)
+            }
+            UnknownTile { addr, tile } => {
+                write!(f, "Unknown tile '{}' at hex {}", tile, addr)
+            }
+            UnknownToken { addr, token } => {
+                write!(f, "Unknown token '{}' at hex {}", token, addr)
+            }
+            InvalidTokenSpace {
+                addr,
+                tile,
+                space_ix,
+            } => write!(
+                f,
+                "Tile '{}' at hex {} has no token space #{}",
+                tile, addr, space_ix
+            ),
+            InvalidRotation { addr, rotation } => {
+                write!(f, "Invalid rotation '{}' at hex {}", rotation, addr)
+            }
+            Invalid { what, problems } => {
+                write!(f, "Found {} problems in the {}:", problems.len(), what)?;
+                for problem in problems {
+                    write!(f, "\n- {}", problem)?;
+                }
+                Ok(())
+            }
+            #[cfg(feature = "cairo")]
+            Image { path, source } => {
+                write!(f, "Invalid image '{}': {}", path.display(), source)
+            }
+            DuplicateTiles { path, names } => write!(
+                f,
+                "'{}' defines duplicate tiles: {}",
+                path.display(),
+                names.join(", ")
+            ),
+            UnsupportedVersion {
+                path: Some(path),
+                found,
+                supported,
+            } => write!(
+                f,
+                "'{}' has version {}, but only versions up to {} are supported",
+                path.display(),
+                found,
+                supported
+            ),
+            UnsupportedVersion {
+                path: None,
+                found,
+                supported,
+            } => write!(
+                f,
+                "Found version {}, but only versions up to {} are supported",
+                found, supported
+            ),
+            BundleMismatch {
+                path,
+                field,
+                bundle,
+                state,
+            } => write!(
+                f,
+                "'{}' has {} '{}', but contains a game state with {} '{}'",
+                path.display(),
+                field,
+                bundle,
+                field,
+                state
+            ),
+            Csv {
+                path,
+                line,
+                message,
+            } => {
+                write!(f, "Invalid CSV")?;
+                if let Some(path) = path {
+                    write!(f, " in '{}'", path.display())?;
+                }
+                write!(f, " at line {}: {}", line, message)
+            }
+            InvalidTokenPlacement {
+                entry,
+                company,
+                hex,
+                city,
+                reason,
+            } => write!(
+                f,
+                "Entry {}: cannot place a {} token in city #{} at {}: {}",
+                entry, company, city, hex, reason
+            ),
+            InvalidPhaseRevenue { tile, colour } => write!(
+                f,
+                "Tile '{}' has a dit revenue for {:?}, which is not a game phase",
+                tile, colour
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        use Error::*;
+        match self {
+            Io { source, .. } => Some(source),
+            Json { source, .. } => Some(source),
+            #[cfg(feature = "cairo")]
+            Image { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
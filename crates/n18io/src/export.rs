@@ -0,0 +1,200 @@
+//! Export game states in a format that follows the conventions of the
+//! open-source [18xx.games](https://github.com/tobymao/18xx) project (see
+//! [read_imported_map](crate::read_imported_map)).
+//!
+//! The output is a JSON object with the following fields:
+//!
+//! - `"title"`: the game name, or the custom title of the game;
+//! - `"game"` and `"variant"`: the game name and map variant;
+//! - `"phase"`: the current game phase;
+//! - `"layout"`: either `"flat"` or `"pointy"`; and
+//! - `"hexes"`: the tile laid on each hex and the tokens placed on each
+//!   hex, identified by their hex coordinates.
+//!
+//! Each tile is identified by its name and its rotation, which is the number
+//! of clockwise turns (0 to 5) from its default orientation.
+//! Pre-printed map tiles are not listed, unless they contain tokens.
+//! Each token is identified by the company name, the city index, and the
+//! token space (slot) index within that city.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use n18catalogue::Availability;
+use n18game::{Game, GameState};
+use n18hex::Orientation;
+use n18map::descr::TileDescr;
+use n18map::HexAddress;
+
+use super::{to_json_string, write_json, Error};
+
+#[derive(Serialize)]
+struct ExportedGame {
+    title: String,
+    game: String,
+    variant: String,
+    phase: String,
+    layout: &'static str,
+    hexes: BTreeMap<String, ExportedHex>,
+}
+
+#[derive(Serialize)]
+struct ExportedHex {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rotation: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tokens: Vec<ExportedToken>,
+}
+
+#[derive(Serialize)]
+struct ExportedToken {
+    corporation: String,
+    city: usize,
+    slot: usize,
+}
+
+/// Writes a game state to disk, in the format described in the
+/// [module documentation](self).
+pub fn write_exported_game<G: Game + ?Sized, P: AsRef<Path>>(
+    path: P,
+    game: &G,
+    state: &GameState,
+    pretty: bool,
+) -> Result<(), Error> {
+    let exported = export(game, state)?;
+    write_json(path, &exported, pretty)
+}
+
+/// Writes a game state as JSON text, in the format described in the
+/// [module documentation](self).
+///
+/// # Errors
+///
+/// Returns [Error::UnknownTile] if the map contains a tile that is not in
+/// the game's catalogue, and [Error::InvalidTokenSpace] if a token is placed
+/// in a token space that the tile does not contain.
+pub fn exported_game_to_string<G: Game + ?Sized>(
+    game: &G,
+    state: &GameState,
+    pretty: bool,
+) -> Result<String, Error> {
+    let exported = export(game, state)?;
+    to_json_string(&exported, pretty)
+}
+
+fn export<G: Game + ?Sized>(
+    game: &G,
+    state: &GameState,
+) -> Result<ExportedGame, Error> {
+    let (orientation, tiles): (
+        Orientation,
+        &BTreeMap<HexAddress, Option<TileDescr>>,
+    ) = (&state.map).into();
+    let coords = game.coordinate_system();
+    let catalogue = game.catalogue();
+    let mut hexes = BTreeMap::new();
+    for (addr, descr) in tiles {
+        let Some(descr) = descr else {
+            continue;
+        };
+        let (tile, availability) = catalogue
+            .tile_and_availability(&descr.tile)
+            .ok_or_else(|| Error::UnknownTile {
+                addr: *addr,
+                tile: descr.tile.clone(),
+            })?;
+        let spaces = tile.token_spaces();
+        let tokens = descr
+            .tokens
+            .iter()
+            .map(|(space_ix, name)| {
+                let space = spaces.get(*space_ix).ok_or_else(|| {
+                    Error::InvalidTokenSpace {
+                        addr: *addr,
+                        tile: descr.tile.clone(),
+                        space_ix: *space_ix,
+                    }
+                })?;
+                // NOTE: token spaces are ordered by city, so the slot is the
+                // number of preceding token spaces in the same city.
+                let city = space.city_ix();
+                let slot = spaces[..*space_ix]
+                    .iter()
+                    .filter(|other| other.city_ix() == city)
+                    .count();
+                Ok(ExportedToken {
+                    corporation: name.clone(),
+                    city,
+                    slot,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let laid = *availability != Availability::Unavailable;
+        if !laid && tokens.is_empty() {
+            continue;
+        }
+        let coord = coords.format(addr).unwrap_or_else(|| addr.to_string());
+        hexes.insert(
+            coord,
+            ExportedHex {
+                tile: laid.then(|| descr.tile.clone()),
+                rotation: laid.then(|| descr.rotation.count_turns()),
+                tokens,
+            },
+        );
+    }
+    let layout = match orientation {
+        Orientation::FlatTop => "flat",
+        Orientation::PointedTop => "pointy",
+    };
+    Ok(ExportedGame {
+        title: state.title.clone().unwrap_or_else(|| state.game.clone()),
+        game: state.game.clone(),
+        variant: state.variant.clone(),
+        phase: state.phase.clone(),
+        layout,
+        hexes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::exported_game_to_string;
+    use n18game::Game;
+    use n18hex::{Hex, RotateCW};
+
+    #[test]
+    fn export_1830_game() {
+        let game = n18game::new_1830();
+        let hex = Hex::default();
+        let mut map = game.create_map(&hex);
+        let coords = game.coordinate_system();
+
+        // Lay a yellow tile on an empty hex, and place a token on it.
+        let b4 = coords.parse("B4").unwrap();
+        assert!(map.place_tile(b4, "57", RotateCW::One));
+        let prr = *game.try_token("PRR").unwrap();
+        let space = map.tile_at(b4).unwrap().token_spaces()[0];
+        map.hex_state_mut(b4).unwrap().set_token_at(&space, prr);
+
+        let state = game.save(&map);
+        let text = exported_game_to_string(&game, &state, false).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(value["game"], state.game.as_str());
+        assert_eq!(value["phase"], state.phase.as_str());
+        assert_eq!(value["layout"], "pointy");
+        let b4 = &value["hexes"]["B4"];
+        assert_eq!(b4["tile"], "57");
+        assert_eq!(b4["rotation"], 1);
+        assert_eq!(b4["tokens"][0]["corporation"], "PRR");
+        assert_eq!(b4["tokens"][0]["city"], 0);
+        assert_eq!(b4["tokens"][0]["slot"], 0);
+
+        // Check that pre-printed tiles without tokens are not listed.
+        let hexes = value["hexes"].as_object().unwrap();
+        assert_eq!(hexes.len(), 1);
+    }
+}
@@ -0,0 +1,724 @@
+//! Import maps and tiles from the JSON format used by the open-source
+//! [18xx.games](https://github.com/tobymao/18xx) project.
+//!
+//! The input is a JSON object with the following fields:
+//!
+//! - `"title"`: the game title (optional);
+//! - `"layout"`: either `"flat"` (the default) or `"pointy"`;
+//! - `"location_names"`: the location name for each hex coordinate;
+//! - `"tiles"`: the number of copies of each tile, which may be a number, the
+//!   string `"unlimited"`, or an object with `"count"`, `"color"`, and
+//!   `"code"` fields that defines a game-specific tile; and
+//! - `"hexes"`: the hexes of each colour (`"white"`, `"yellow"`, `"green"`,
+//!   `"brown"`, `"gray"`, `"red"`, or `"blue"`), grouped by their tile code.
+//!
+//! Tile codes are sequences of parts separated by semicolons, such as
+//! `"city=revenue:20;path=a:0,b:_0;label=Y"`.
+//! Cities, towns, off-board locations, paths, labels, and upgrade costs are
+//! supported; every other part is listed in [ImportedMap::problems], as are
+//! any parts that could not be converted.
+//! Off-board locations whose revenue depends on the game phase earn the
+//! revenue for the map's current revenue phase (see
+//! [n18map::Map::set_revenue_phase]), and earn the revenue for the first
+//! phase until the revenue phase is set.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use n18catalogue::Availability;
+use n18hex::{Hex, HexColour, HexCorner, HexFace, Orientation, RotateCW};
+use n18map::descr::{Descr, TileDescr};
+use n18map::{Coordinates, FirstRow, HexAddress, Letters};
+use n18tile::{City, DitShape, Label, PhaseRevenues, Tile, Track, TrackEnd};
+
+use super::{from_json_str, read_json, Error};
+
+/// A map and its tiles, as defined in the 18xx.games JSON format.
+#[derive(Debug)]
+pub struct ImportedMap {
+    /// The game title, if one was provided.
+    pub title: Option<String>,
+    /// The scheme used to identify each map hex.
+    pub coordinates: Coordinates,
+    /// The tiles that were defined by tile codes, which includes a tile for
+    /// each pre-printed map hex.
+    pub tiles: Vec<Tile>,
+    /// The number of copies of each tile that players can place, including
+    /// tiles from the standard catalogue (see [n18catalogue::Kind]).
+    pub tile_counts: BTreeMap<String, Availability>,
+    /// The tile on each map hex.
+    pub descr: Descr,
+    /// The features that could not be imported.
+    pub problems: Vec<ImportProblem>,
+}
+
+/// A feature that could not be imported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportProblem {
+    /// The hex coordinate or tile name where the feature was defined, if
+    /// any.
+    pub location: Option<String>,
+    /// A description of the feature.
+    pub message: String,
+}
+
+impl std::fmt::Display for ImportProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{}: {}", location, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Config {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    layout: Layout,
+    #[serde(default)]
+    location_names: BTreeMap<String, String>,
+    #[serde(default)]
+    tiles: BTreeMap<String, TileEntry>,
+    #[serde(default)]
+    hexes: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum Layout {
+    #[default]
+    Flat,
+    Pointy,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum TileEntry {
+    Custom {
+        count: Count,
+        color: String,
+        code: String,
+    },
+    Count(Count),
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+enum Count {
+    Number(usize),
+    Text(String),
+}
+
+/// Reads a map and its tiles from a file in the 18xx.games JSON format.
+pub fn read_imported_map<P: AsRef<Path>>(
+    path: P,
+) -> Result<ImportedMap, Error> {
+    let config: Config = read_json(path)?;
+    Ok(import(config))
+}
+
+/// Reads a map and its tiles from JSON text in the 18xx.games format.
+pub fn imported_map_from_str(text: &str) -> Result<ImportedMap, Error> {
+    let config: Config = from_json_str(text, None)?;
+    Ok(import(config))
+}
+
+fn import(config: Config) -> ImportedMap {
+    let hex = Hex::default();
+    let mut problems = vec![];
+    let mut tiles = vec![];
+    let mut tile_counts = BTreeMap::new();
+
+    let standard: Vec<String> = n18catalogue::tile_catalogue()
+        .into_iter()
+        .map(|tile| tile.name)
+        .collect();
+    for (name, entry) in &config.tiles {
+        let problem = |message: String| ImportProblem {
+            location: Some(name.clone()),
+            message,
+        };
+        let count = match entry {
+            TileEntry::Count(count) => count,
+            TileEntry::Custom { count, .. } => count,
+        };
+        let availability = match count {
+            Count::Number(n) => Availability::Limited(*n),
+            Count::Text(text) if text == "unlimited" => {
+                Availability::Unlimited
+            }
+            Count::Text(text) => {
+                problems.push(problem(format!("invalid count '{}'", text)));
+                continue;
+            }
+        };
+        match entry {
+            TileEntry::Custom { color, code, .. } => {
+                let Some(colour) = parse_colour(color) else {
+                    problems.push(problem(format!(
+                        "unsupported colour '{}'",
+                        color
+                    )));
+                    continue;
+                };
+                let (tile, messages) = build_tile(&hex, colour, name, code);
+                problems.extend(messages.into_iter().map(problem));
+                tiles.push(tile);
+            }
+            TileEntry::Count(_) => {
+                if !standard.contains(name) {
+                    problems.push(problem(
+                        "unknown tile with no code".to_string(),
+                    ));
+                    continue;
+                }
+            }
+        }
+        tile_counts.insert(name.clone(), availability);
+    }
+
+    let (orientation, letters) = match config.layout {
+        Layout::Flat => (Orientation::FlatTop, Letters::AsColumns),
+        Layout::Pointy => (Orientation::PointedTop, Letters::AsRows),
+    };
+    let coords: Vec<&String> = config
+        .hexes
+        .values()
+        .flat_map(|codes| codes.values().flatten())
+        .collect();
+    // NOTE: 18xx.games does not record which columns the first row
+    // contains, so choose the first scheme that accepts every coordinate.
+    let coordinates = [FirstRow::OddColumns, FirstRow::EvenColumns]
+        .into_iter()
+        .map(|first_row| Coordinates::from((orientation, letters, first_row)))
+        .find(|coordinates| {
+            coords.iter().all(|coord| coordinates.parse(coord).is_ok())
+        })
+        .unwrap_or_else(|| {
+            Coordinates::from((orientation, letters, FirstRow::OddColumns))
+        });
+
+    let mut descr_tiles: BTreeMap<HexAddress, Option<TileDescr>> =
+        BTreeMap::new();
+    let mut max_colours = BTreeMap::new();
+    for (color, codes) in &config.hexes {
+        let Some(colour) = parse_colour(color) else {
+            problems.push(ImportProblem {
+                location: None,
+                message: format!("unsupported hex colour '{}'", color),
+            });
+            continue;
+        };
+        for (code, coords) in codes {
+            for coord in coords {
+                let Ok(addr) = coordinates.parse(coord) else {
+                    problems.push(ImportProblem {
+                        location: Some(coord.clone()),
+                        message: "invalid hex coordinate".to_string(),
+                    });
+                    continue;
+                };
+                let location = config.location_names.get(coord);
+                if colour == HexColour::Empty
+                    && code.is_empty()
+                    && location.is_none()
+                {
+                    descr_tiles.insert(addr, None);
+                    continue;
+                }
+                let (mut tile, messages) =
+                    build_tile(&hex, colour, coord, code);
+                problems.extend(messages.into_iter().map(|message| {
+                    ImportProblem {
+                        location: Some(coord.clone()),
+                        message,
+                    }
+                }));
+                if let Some(name) = location {
+                    tile = tile.label(
+                        Label::MapLocation(name.clone()),
+                        HexFace::Top.to_centre(0.1),
+                    );
+                }
+                tiles.push(tile.hide_tile_name());
+                tile_counts.insert(coord.clone(), Availability::Unavailable);
+                // NOTE: only white hexes and pre-printed yellow, green, and
+                // brown tiles can be upgraded.
+                if matches!(
+                    colour,
+                    HexColour::Grey | HexColour::Red | HexColour::Blue
+                ) {
+                    max_colours.insert(addr, HexColour::Empty);
+                }
+                let (row, col) = (&addr).into();
+                descr_tiles.insert(
+                    addr,
+                    Some(TileDescr {
+                        row,
+                        col,
+                        tile: coord.clone(),
+                        rotation: RotateCW::Zero,
+                        tokens: vec![],
+                    }),
+                );
+            }
+        }
+    }
+    let descr =
+        Descr::from((orientation, descr_tiles)).with_max_colours(max_colours);
+
+    ImportedMap {
+        title: config.title,
+        coordinates,
+        tiles,
+        tile_counts,
+        descr,
+        problems,
+    }
+}
+
+/// Returns the tile colour that corresponds to an 18xx.games colour name.
+fn parse_colour(color: &str) -> Option<HexColour> {
+    match color {
+        "white" => Some(HexColour::Empty),
+        "yellow" => Some(HexColour::Yellow),
+        "green" => Some(HexColour::Green),
+        "brown" => Some(HexColour::Brown),
+        "gray" => Some(HexColour::Grey),
+        "red" => Some(HexColour::Red),
+        "blue" => Some(HexColour::Blue),
+        _ => None,
+    }
+}
+
+/// Returns the hexagon face that corresponds to an 18xx.games edge number.
+fn parse_edge(text: &str) -> Option<HexFace> {
+    match text {
+        "0" => Some(HexFace::Bottom),
+        "1" => Some(HexFace::LowerLeft),
+        "2" => Some(HexFace::UpperLeft),
+        "3" => Some(HexFace::Top),
+        "4" => Some(HexFace::UpperRight),
+        "5" => Some(HexFace::LowerRight),
+        _ => None,
+    }
+}
+
+/// A revenue centre, identified in paths by its index (e.g., `_0`).
+enum Node {
+    City { revenue: usize, slots: usize },
+    Town { revenue: usize },
+    Offboard { revenues: Vec<(HexColour, usize)> },
+}
+
+/// One end of a path.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum End {
+    Edge(HexFace),
+    Node(usize),
+}
+
+/// Parses the `key:value` arguments of a tile code part.
+fn parse_args(args: &str) -> Vec<(&str, &str)> {
+    args.split(',')
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| arg.split_once(':').unwrap_or((arg, "")))
+        .collect()
+}
+
+/// Parses an off-board revenue, which is either a single number or a
+/// revenue for each phase, such as `yellow_30|brown_60`.
+fn parse_phase_revenues(text: &str) -> Option<Vec<(HexColour, usize)>> {
+    if let Ok(revenue) = text.parse() {
+        return Some(vec![(HexColour::Yellow, revenue)]);
+    }
+    text.split('|')
+        .map(|item| {
+            let (color, revenue) = item.split_once('_')?;
+            Some((parse_colour(color)?, revenue.parse().ok()?))
+        })
+        .collect()
+}
+
+/// Returns a track segment that connects two different hexagon faces.
+fn track_between(from: HexFace, to: HexFace) -> Track {
+    if to == from.clockwise() {
+        Track::hard_l(from)
+    } else if to == from.anti_clockwise() {
+        Track::hard_r(from)
+    } else if to == from.clockwise().clockwise() {
+        Track::gentle_l(from)
+    } else if to == from.anti_clockwise().anti_clockwise() {
+        Track::gentle_r(from)
+    } else {
+        Track::straight(from)
+    }
+}
+
+/// Builds a tile from an 18xx.games tile code, and returns the tile and a
+/// description of each part that could not be converted.
+fn build_tile(
+    hex: &Hex,
+    colour: HexColour,
+    name: &str,
+    code: &str,
+) -> (Tile, Vec<String>) {
+    let mut problems = vec![];
+    let mut nodes = vec![];
+    let mut paths = vec![];
+    let mut labels = vec![];
+    for part in code.split(';').filter(|part| !part.is_empty()) {
+        let (kind, args) = part.split_once('=').unwrap_or((part, ""));
+        let args = parse_args(args);
+        let arg =
+            |key: &str| args.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+        let revenue = arg("revenue").and_then(|r| r.parse().ok());
+        match kind {
+            "city" => nodes.push(Node::City {
+                revenue: revenue.unwrap_or(0),
+                slots: arg("slots").and_then(|s| s.parse().ok()).unwrap_or(1),
+            }),
+            "town" => nodes.push(Node::Town {
+                revenue: revenue.unwrap_or(0),
+            }),
+            "offboard" => {
+                match arg("revenue").and_then(parse_phase_revenues) {
+                    Some(revenues) if !revenues.is_empty() => {
+                        nodes.push(Node::Offboard { revenues })
+                    }
+                    _ => problems
+                        .push(format!("invalid revenue in '{}'", part)),
+                }
+            }
+            "path" => {
+                let end = |key: &str| match arg(key)? {
+                    node if node.starts_with('_') => {
+                        node[1..].parse().ok().map(End::Node)
+                    }
+                    edge => parse_edge(edge).map(End::Edge),
+                };
+                match (end("a"), end("b")) {
+                    (Some(a), Some(b)) if a != b => paths.push((a, b)),
+                    _ => problems.push(format!("invalid path '{}'", part)),
+                }
+                let extra: Vec<&str> = args
+                    .iter()
+                    .map(|(k, _)| *k)
+                    .filter(|k| *k != "a" && *k != "b")
+                    .collect();
+                if !extra.is_empty() {
+                    problems.push(format!(
+                        "unsupported path options in '{}'",
+                        part
+                    ));
+                }
+            }
+            "label" if !args.is_empty() => labels.push((
+                Label::CityKind(args[0].0.to_string()),
+                HexFace::Top.to_centre(0.25),
+            )),
+            "upgrade" => match arg("cost") {
+                Some(cost) => labels.push((
+                    Label::Note(format!("${}", cost)),
+                    HexFace::LowerLeft.to_centre(0.25),
+                )),
+                None => problems.push(format!("invalid upgrade '{}'", part)),
+            },
+            _ => problems.push(format!("unsupported feature '{}'", part)),
+        }
+    }
+
+    // Identify the faces that are connected to each node, and the track
+    // segments that connect two faces.
+    let mut node_faces: Vec<Vec<HexFace>> = vec![vec![]; nodes.len()];
+    let mut tracks = vec![];
+    for (a, b) in paths {
+        match (a, b) {
+            (End::Edge(f), End::Edge(g)) => tracks.push(track_between(f, g)),
+            (End::Edge(face), End::Node(ix))
+            | (End::Node(ix), End::Edge(face)) => {
+                match node_faces.get_mut(ix) {
+                    Some(faces) => faces.push(face),
+                    None => problems.push(format!("no node _{}", ix)),
+                }
+            }
+            (End::Node(_), End::Node(_)) => problems.push(
+                "paths between two nodes are not supported".to_string(),
+            ),
+        }
+    }
+
+    let city_count = nodes
+        .iter()
+        .filter(|node| !matches!(node, Node::Town { .. }))
+        .count();
+    let mut cities = vec![];
+    let mut offboard_faces = vec![];
+    for (node, faces) in nodes.iter().zip(&node_faces) {
+        match node {
+            Node::Town { revenue } => match faces.as_slice() {
+                [] => tracks.push(
+                    Track::straight(HexFace::Bottom)
+                        .with_span(0.5, 0.5)
+                        .with_dit(TrackEnd::End, *revenue, DitShape::Circle),
+                ),
+                [face] => tracks.push(Track::mid(*face).with_dit(
+                    TrackEnd::End,
+                    *revenue,
+                    DitShape::Bar,
+                )),
+                [f, g] => {
+                    let track = track_between(*f, *g);
+                    tracks.push(track.with_span(0.0, 0.5).with_dit(
+                        TrackEnd::End,
+                        *revenue,
+                        DitShape::Bar,
+                    ));
+                    tracks.push(track.with_span(0.5, 1.0));
+                }
+                _ => problems.push(
+                    "towns with more than two paths are not supported"
+                        .to_string(),
+                ),
+            },
+            Node::City { revenue, slots } if city_count == 1 => {
+                let city = match slots {
+                    1 => City::single(*revenue),
+                    2 => City::double(*revenue),
+                    3 => City::triple(*revenue),
+                    4 => City::quad(*revenue),
+                    _ => {
+                        problems.push(format!(
+                            "cities with {} slots are not supported",
+                            slots
+                        ));
+                        City::single(*revenue)
+                    }
+                };
+                cities.push(city);
+                tracks.extend(faces.iter().map(|face| Track::mid(*face)));
+            }
+            Node::City { revenue, slots } => {
+                // NOTE: place each city next to its first face.
+                if *slots != 1 {
+                    problems.push(format!(
+                        "cities with {} slots are only supported when the \
+                         tile has a single city",
+                        slots
+                    ));
+                }
+                let Some(face) = faces.first() else {
+                    problems.push(
+                        "cities with no paths are only supported when the \
+                         tile has a single city"
+                            .to_string(),
+                    );
+                    continue;
+                };
+                if faces.len() > 1 {
+                    problems.push(
+                        "cities with several paths are only supported when \
+                         the tile has a single city"
+                            .to_string(),
+                    );
+                }
+                cities.push(
+                    City::single_at_face(*revenue, face).to_centre(0.2),
+                );
+                tracks.push(Track::straight(*face).with_span(0.0, 0.2));
+            }
+            Node::Offboard { revenues } => {
+                if city_count > 1 {
+                    problems.push(
+                        "off-board locations are only supported when the \
+                         tile has a single city"
+                            .to_string(),
+                    );
+                    continue;
+                }
+                cities.push(City::single(revenues[0].1));
+                tracks.extend(faces.iter().map(|face| Track::mid(*face)));
+                offboard_faces.extend(faces.iter().copied());
+                if revenues.len() > 1 {
+                    // NOTE: the phase revenue label defines the revenue in
+                    // each phase (see Tile::phase_revenue), so it can only
+                    // contain colours from the tile upgrade sequence.
+                    match PhaseRevenues::try_new(revenues) {
+                        Ok(_) => {
+                            let revenues = revenues
+                                .iter()
+                                .enumerate()
+                                .map(|(ix, (colour, revenue))| {
+                                    (*colour, *revenue, ix == 0)
+                                })
+                                .collect();
+                            labels.push((
+                                Label::PhaseRevenueVert(revenues),
+                                HexCorner::Right.to_centre(0.35),
+                            ));
+                        }
+                        Err(colour) => problems.push(format!(
+                            "off-board revenues for {:?} phases are not \
+                             supported",
+                            colour
+                        )),
+                    }
+                }
+            }
+        }
+    }
+    let has_revenue = nodes.iter().any(|node| match node {
+        Node::City { revenue, .. } | Node::Town { revenue } => *revenue > 0,
+        Node::Offboard { revenues } => revenues.len() == 1,
+    });
+    if has_revenue {
+        labels.push((Label::Revenue(0), HexFace::UpperLeft.to_centre(0.2)));
+    }
+
+    let tile = Tile::new(colour, name, tracks, cities, hex);
+    let tile = labels
+        .into_iter()
+        .fold(tile, |tile, (label, posn)| tile.label(label, posn));
+    let tile = if offboard_faces.is_empty() {
+        tile
+    } else {
+        tile.with_offboard_faces(offboard_faces)
+    };
+    (tile, problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::imported_map_from_str;
+    use n18catalogue::Availability;
+    use n18hex::{HexColour, HexFace};
+    use n18tile::Connection;
+    use n18token::Tokens;
+
+    static MAP: &str = r#"{
+        "title": "Sample",
+        "layout": "flat",
+        "location_names": { "B2": "Northville", "C3": "Eastport" },
+        "tiles": {
+            "57": 4,
+            "9": "unlimited",
+            "X1": {
+                "count": 1,
+                "color": "green",
+                "code": "city=revenue:40,slots:2;path=a:0,b:_0;path=a:3,b:_0;label=OO"
+            },
+            "X99": 2
+        },
+        "hexes": {
+            "white": {
+                "": ["A1", "B4"],
+                "city=revenue:0": ["B2"],
+                "town=revenue:0;border=edge:1": ["A3"]
+            },
+            "yellow": {
+                "city=revenue:20;path=a:1,b:_0;path=a:4,b:_0": ["C3"]
+            },
+            "red": {
+                "offboard=revenue:yellow_30|brown_60;path=a:2,b:_0": ["C1"]
+            },
+            "gray": {
+                "path=a:0,b:3": ["B6"]
+            }
+        }
+    }"#;
+
+    #[test]
+    fn import_sample_map() {
+        let imported = imported_map_from_str(MAP).unwrap();
+        assert_eq!(imported.title.as_deref(), Some("Sample"));
+
+        // Check that the tile counts include standard and custom tiles, but
+        // not tiles that are undefined.
+        let counts = &imported.tile_counts;
+        assert_eq!(counts.get("57"), Some(&Availability::Limited(4)));
+        assert_eq!(counts.get("9"), Some(&Availability::Unlimited));
+        assert_eq!(counts.get("X1"), Some(&Availability::Limited(1)));
+        assert_eq!(counts.get("C3"), Some(&Availability::Unavailable));
+        assert!(!counts.contains_key("X99"));
+
+        // Check that unsupported features are reported.
+        let problems: Vec<String> =
+            imported.problems.iter().map(|p| p.to_string()).collect();
+        assert_eq!(problems.len(), 2);
+        assert!(problems.contains(&"X99: unknown tile with no code".into()));
+        assert!(problems
+            .contains(&"A3: unsupported feature 'border=edge:1'".into()));
+
+        // Check the custom tile and the pre-printed tiles.
+        let tile = |name: &str| {
+            imported.tiles.iter().find(|t| t.name == name).unwrap()
+        };
+        let x1 = tile("X1");
+        assert_eq!(x1.colour, HexColour::Green);
+        assert_eq!(x1.token_spaces().len(), 2);
+        let city = Connection::City { ix: 0 };
+        assert!(
+            x1.connected_faces_are(city, &[HexFace::Bottom, HexFace::Top])
+        );
+        let c3 = tile("C3");
+        assert!(c3.connected_faces_are(
+            city,
+            &[HexFace::LowerLeft, HexFace::UpperRight]
+        ));
+        assert_eq!(
+            tile("C1").offboard_faces(),
+            Some(vec![HexFace::UpperLeft])
+        );
+        assert!(
+            tile("B6").connected_faces_are(HexFace::Bottom, &[HexFace::Top])
+        );
+
+        // Check that the map can be constructed.
+        let mut tiles = n18catalogue::tile_catalogue();
+        tiles.extend(imported.tiles.iter().cloned());
+        let map = imported.descr.build_map(tiles, Tokens::new(vec![]));
+        let addr = |coord: &str| imported.coordinates.parse(coord).unwrap();
+        assert_eq!(map.hex_address_iter().count(), 7);
+        assert!(map.tile_at(addr("A1")).is_none());
+        assert_eq!(map.tile_at(addr("C3")).unwrap().name, "C3");
+        assert_eq!(map.max_colour(addr("B6")), Some(HexColour::Empty));
+    }
+
+    #[test]
+    fn import_phase_revenues() {
+        let map = r#"{
+            "hexes": {
+                "red": {
+                    "offboard=revenue:yellow_30|brown_60;path=a:2,b:_0": ["A1"],
+                    "offboard=revenue:yellow_30|red_60;path=a:2,b:_0": ["A3"]
+                }
+            }
+        }"#;
+        let imported = imported_map_from_str(map).unwrap();
+        let tile = |name: &str| {
+            imported.tiles.iter().find(|t| t.name == name).unwrap()
+        };
+
+        // Off-board revenues are defined for each phase, and the revenue for
+        // the first phase applies until the revenue phase is set.
+        let a1 = tile("A1");
+        assert_eq!(a1.cities()[0].revenue, 30);
+        assert_eq!(a1.phase_revenue(HexColour::Yellow), Some(30));
+        assert_eq!(a1.phase_revenue(HexColour::Green), Some(30));
+        assert_eq!(a1.phase_revenue(HexColour::Brown), Some(60));
+        assert_eq!(a1.phase_revenue(HexColour::Grey), Some(60));
+
+        // Phases that are not part of the tile upgrade sequence are reported.
+        let a3 = tile("A3");
+        assert_eq!(a3.phase_revenue(HexColour::Yellow), None);
+        let problems: Vec<String> =
+            imported.problems.iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            problems,
+            ["A3: off-board revenues for Red phases are not supported"]
+        );
+    }
+}
@@ -1,15 +1,51 @@
 /// Load tile catalogues from disk.
 use n18hex::Hex;
 
+use migrate::MigrationError;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 use std::collections::BTreeMap;
-use std::error::Error;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufWriter, Read, Write};
 use std::path::Path;
 
+mod bundle;
+mod error;
+mod export;
+mod import;
+mod migrate;
+mod moves;
+mod placements;
 mod routes;
+mod table;
+mod tokens;
+
+#[doc(inline)]
+pub use bundle::{read_bundle, write_bundle, Bundle};
+
+#[doc(inline)]
+pub use error::Error;
+
+#[doc(inline)]
+pub use export::{exported_game_to_string, write_exported_game};
+
+#[doc(inline)]
+pub use import::{
+    imported_map_from_str, read_imported_map, ImportProblem, ImportedMap,
+};
+
+#[doc(inline)]
+pub use migrate::SCHEMA_VERSION;
+
+#[doc(inline)]
+pub use table::{hex_table_to_string, write_hex_table, TableFormat};
+
+#[doc(inline)]
+pub use tokens::{
+    apply_token_placements, read_token_placements, token_placements_from_str,
+    TokenListFormat, TokenPlacement,
+};
 
 #[derive(Serialize, Deserialize, Debug, Default)]
 struct Tiles {
@@ -96,6 +132,8 @@ struct Tile {
     pub show_tile_name: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub offboard_faces: Option<Vec<HexFace>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub arrows: Vec<Arrow>,
 }
 
 /// By default, show tile names on the tile.
@@ -120,6 +158,7 @@ impl std::convert::From<&n18tile::Tile> for Tile {
             offboard_faces: src
                 .offboard_faces()
                 .map(|faces| faces.into_iter().map(|f| f.into()).collect()),
+            arrows: src.arrows().iter().map(|arrow| arrow.into()).collect(),
         }
     }
 }
@@ -134,6 +173,38 @@ impl Default for Tile {
             labels: vec![],
             show_tile_name: true,
             offboard_faces: None,
+            arrows: vec![],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Arrow {
+    pub face: HexFace,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_centre: Option<f64>,
+}
+
+impl std::convert::From<&n18tile::Arrow> for Arrow {
+    fn from(src: &n18tile::Arrow) -> Self {
+        Self {
+            face: src.face.into(),
+            to_centre: if src.offset == 0.0 {
+                None
+            } else {
+                Some(src.offset)
+            },
+        }
+    }
+}
+
+impl From<&Arrow> for n18tile::Arrow {
+    fn from(a: &Arrow) -> n18tile::Arrow {
+        let arrow = n18tile::Arrow::new((&a.face).into());
+        if let Some(frac) = a.to_centre {
+            arrow.to_centre(frac)
+        } else {
+            arrow
         }
     }
 }
@@ -210,6 +281,8 @@ struct Track {
     pub track_type: TrackType,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub dit: Option<(TrackEnd, usize, DitShape)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dit_revenues: Vec<(HexColour, usize)>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub clip: Option<(f64, f64)>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -220,7 +293,7 @@ impl std::convert::From<&n18tile::Track> for Track {
     fn from(src: &n18tile::Track) -> Self {
         use n18tile::TrackCurve::*;
 
-        let eps = std::f64::EPSILON;
+        let eps = f64::EPSILON;
         let span = if src.x0 == 0.0 && (src.x1 - 1.0).abs() < eps {
             None
         } else if src.x0 >= 0.0 && src.x1 <= 1.0 {
@@ -247,6 +320,11 @@ impl std::convert::From<&n18tile::Track> for Track {
             dit: src.dit.map(|(end, revenue, shape)| {
                 (end.into(), revenue, shape.into())
             }),
+            dit_revenues: src
+                .dit_revenues
+                .iter()
+                .map(|(colour, revenue)| (colour.into(), revenue))
+                .collect(),
             clip: src.clip,
             span,
         }
@@ -258,6 +336,7 @@ impl Default for Track {
         Self {
             track_type: TrackType::Straight(HexFace::Bottom),
             dit: None,
+            dit_revenues: vec![],
             clip: None,
             span: None,
         }
@@ -421,6 +500,8 @@ struct City {
     pub rotate: Option<CityRotation>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub fill_colour: Option<HexColour>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available_from: Option<HexColour>,
 }
 
 impl std::convert::From<&n18tile::City> for City {
@@ -472,6 +553,7 @@ impl std::convert::From<&n18tile::City> for City {
         };
         let rotate = CityRotation::from_rot(src.angle);
         let fill_colour = src.fill_colour.map(|colour| colour.into());
+        let available_from = src.available_from.map(|colour| colour.into());
         Self {
             city_type,
             revenue,
@@ -479,6 +561,7 @@ impl std::convert::From<&n18tile::City> for City {
             to_centre,
             rotate,
             fill_colour,
+            available_from,
         }
     }
 }
@@ -492,6 +575,7 @@ impl Default for City {
             to_centre: None,
             rotate: None,
             fill_colour: None,
+            available_from: None,
         }
     }
 }
@@ -697,25 +781,181 @@ impl std::convert::From<&Direction> for n18hex::Direction {
     }
 }
 
-/// Reads a single tile from disk.
-pub fn read_tile<P: AsRef<Path>>(
+/// Reads all of the text from a reader.
+fn read_text<R: Read>(mut reader: R) -> Result<String, Error> {
+    let mut text = String::new();
+    reader
+        .read_to_string(&mut text)
+        .map_err(|source| Error::Io { path: None, source })?;
+    Ok(text)
+}
+
+/// Reads a value from JSON text, which was read from the file `path` (if
+/// any).
+fn from_json_str<T: DeserializeOwned>(
+    text: &str,
+    path: Option<&Path>,
+) -> Result<T, Error> {
+    serde_json::from_str(text).map_err(|e| Error::json(path, e))
+}
+
+/// Returns the error that describes why a value, which was read from the
+/// file `path` (if any), could not be upgraded to the current schema
+/// version.
+fn migration_error(error: MigrationError, path: Option<&Path>) -> Error {
+    match error {
+        MigrationError::Invalid(msg) => {
+            Error::json(path, serde::de::Error::custom(msg))
+        }
+        MigrationError::Unsupported(found) => Error::UnsupportedVersion {
+            path: path.map(|path| path.to_path_buf()),
+            found,
+            supported: SCHEMA_VERSION,
+        },
+    }
+}
+
+/// Reads a versioned value from JSON text, which was read from the file
+/// `path` (if any), and upgrades it to the current schema version if
+/// necessary.
+fn from_versioned_json_str<T: DeserializeOwned>(
+    text: &str,
+    migrate: fn(&mut serde_json::Value) -> Result<(), MigrationError>,
+    path: Option<&Path>,
+) -> Result<T, Error> {
+    let mut value: serde_json::Value = from_json_str(text, path)?;
+    let is_current = value.as_object().map(migrate::version_of)
+        == Some(Ok(SCHEMA_VERSION));
+    migrate(&mut value).map_err(|e| migration_error(e, path))?;
+    // NOTE: deserialise current values from the original text, so that any
+    // errors will report the line and column where they occurred.
+    let result = if is_current {
+        serde_json::from_str(text)
+    } else {
+        serde_json::from_value(value)
+    };
+    result.map_err(|e| Error::json(path, e))
+}
+
+/// Writes a value as JSON text.
+fn to_json_string<T: Serialize>(
+    value: &T,
+    pretty: bool,
+) -> Result<String, Error> {
+    let result = if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    };
+    result.map_err(|e| Error::json(None, e))
+}
+
+/// Writes a value as JSON to a writer, which writes to the file `path` (if
+/// any).
+fn to_json_writer<T: Serialize, W: Write>(
+    writer: W,
+    value: &T,
+    pretty: bool,
+    path: Option<&Path>,
+) -> Result<(), Error> {
+    let result = if pretty {
+        serde_json::to_writer_pretty(writer, value)
+    } else {
+        serde_json::to_writer(writer, value)
+    };
+    result.map_err(|e| Error::json(path, e))
+}
+
+/// Reads a value from a JSON file.
+fn read_json<T: DeserializeOwned, P: AsRef<Path>>(
+    path: P,
+) -> Result<T, Error> {
+    let path = path.as_ref();
+    let text =
+        std::fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+    from_json_str(&text, Some(path))
+}
+
+/// Reads a versioned value from a JSON file, and upgrades it to the current
+/// schema version if necessary.
+fn read_versioned_json<T: DeserializeOwned, P: AsRef<Path>>(
     path: P,
-) -> Result<n18tile::Tile, Box<dyn Error>> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let tile: Tile = serde_json::from_reader(reader)?;
+    migrate: fn(&mut serde_json::Value) -> Result<(), MigrationError>,
+) -> Result<T, Error> {
+    let path = path.as_ref();
+    let text =
+        std::fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+    from_versioned_json_str(&text, migrate, Some(path))
+}
+
+/// Writes a value to a JSON file.
+fn write_json<T: Serialize, P: AsRef<Path>>(
+    path: P,
+    value: &T,
+    pretty: bool,
+) -> Result<(), Error> {
+    let path = path.as_ref();
+    let file = File::create(path).map_err(|e| Error::io(path, e))?;
+    to_json_writer(BufWriter::new(file), value, pretty, Some(path))
+}
+
+/// Reads a single tile from disk.
+pub fn read_tile<P: AsRef<Path>>(path: P) -> Result<n18tile::Tile, Error> {
+    let tile: Tile = read_json(path)?;
     let hex = Hex::default();
-    Ok(tile.build(&hex))
+    tile.build(&hex)
 }
 
 /// Reads multiple tiles from disk.
 pub fn read_tiles<P: AsRef<Path>>(
     path: P,
-) -> Result<Vec<n18tile::Tile>, Box<dyn Error>> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let tiles: Tiles = serde_json::from_reader(reader)?;
-    Ok(tiles.build())
+) -> Result<Vec<n18tile::Tile>, Error> {
+    let tiles: Tiles = read_json(path)?;
+    tiles.build()
+}
+
+/// Reads a single tile from JSON text.
+pub fn tile_from_str(text: &str) -> Result<n18tile::Tile, Error> {
+    let tile: Tile = from_json_str(text, None)?;
+    tile.build(&Hex::default())
+}
+
+/// Reads a single tile from a reader that provides JSON text.
+pub fn tile_from_reader<R: Read>(reader: R) -> Result<n18tile::Tile, Error> {
+    tile_from_str(&read_text(reader)?)
+}
+
+/// Reads multiple tiles from JSON text.
+pub fn tiles_from_str(text: &str) -> Result<Vec<n18tile::Tile>, Error> {
+    let tiles: Tiles = from_json_str(text, None)?;
+    tiles.build()
+}
+
+/// Reads multiple tiles from a reader that provides JSON text.
+pub fn tiles_from_reader<R: Read>(
+    reader: R,
+) -> Result<Vec<n18tile::Tile>, Error> {
+    tiles_from_str(&read_text(reader)?)
+}
+
+/// Reads multiple tiles from disk and adds them to a tile catalogue, with
+/// the given availability, and returns the index of each new tile.
+///
+/// If any of these tiles has the same name as a tile in the catalogue, or as
+/// another tile in the file, the catalogue is not modified and this returns
+/// an [Error::DuplicateTiles] value.
+pub fn extend_catalogue<P: AsRef<Path>>(
+    catalogue: &mut n18catalogue::Catalogue,
+    path: P,
+    availability: n18catalogue::Availability,
+) -> Result<Vec<usize>, Error> {
+    let tiles = read_tiles(&path)?;
+    catalogue
+        .extend(tiles.into_iter().map(|tile| (tile, availability)))
+        .map_err(|names| Error::DuplicateTiles {
+            path: path.as_ref().to_path_buf(),
+            names,
+        })
 }
 
 /// Writes a single tile to disk.
@@ -723,15 +963,9 @@ pub fn write_tile<P: AsRef<Path>>(
     path: P,
     tile: &n18tile::Tile,
     pretty: bool,
-) -> Result<(), Box<dyn Error>> {
-    let file = File::create(path)?;
+) -> Result<(), Error> {
     let tile: Tile = tile.into();
-    if pretty {
-        serde_json::to_writer_pretty(file, &tile)?;
-    } else {
-        serde_json::to_writer(file, &tile)?;
-    }
-    Ok(())
+    write_json(path, &tile, pretty)
 }
 
 /// Writes multiple tiles to disk.
@@ -743,24 +977,58 @@ pub fn write_tiles<
     path: P,
     tiles: T,
     pretty: bool,
-) -> Result<(), Box<dyn Error>> {
-    let file = File::create(path)?;
+) -> Result<(), Error> {
     let tiles: Tiles = tiles.into();
-    if pretty {
-        serde_json::to_writer_pretty(file, &tiles)?;
-    } else {
-        serde_json::to_writer(file, &tiles)?;
-    }
-    Ok(())
+    write_json(path, &tiles, pretty)
+}
+
+/// Writes a single tile as JSON text.
+pub fn tile_to_string(
+    tile: &n18tile::Tile,
+    pretty: bool,
+) -> Result<String, Error> {
+    let tile: Tile = tile.into();
+    to_json_string(&tile, pretty)
+}
+
+/// Writes a single tile as JSON text to a writer.
+pub fn tile_to_writer<W: Write>(
+    writer: W,
+    tile: &n18tile::Tile,
+    pretty: bool,
+) -> Result<(), Error> {
+    let tile: Tile = tile.into();
+    to_json_writer(writer, &tile, pretty, None)
+}
+
+/// Writes multiple tiles as JSON text.
+pub fn tiles_to_string<'a, T: IntoIterator<Item = &'a n18tile::Tile>>(
+    tiles: T,
+    pretty: bool,
+) -> Result<String, Error> {
+    let tiles: Tiles = tiles.into();
+    to_json_string(&tiles, pretty)
+}
+
+/// Writes multiple tiles as JSON text to a writer.
+pub fn tiles_to_writer<
+    'a,
+    W: Write,
+    T: IntoIterator<Item = &'a n18tile::Tile>,
+>(
+    writer: W,
+    tiles: T,
+    pretty: bool,
+) -> Result<(), Error> {
+    let tiles: Tiles = tiles.into();
+    to_json_writer(writer, &tiles, pretty, None)
 }
 
 /// Reads train routes from disk.
 pub fn read_routes<P: AsRef<Path>>(
     path: P,
-) -> Result<n18route::Routes, Box<dyn Error>> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let routes: routes::Routes = serde_json::from_reader(reader)?;
+) -> Result<n18route::Routes, Error> {
+    let routes: routes::Routes = read_json(path)?;
     Ok(routes.into())
 }
 
@@ -769,15 +1037,138 @@ pub fn write_routes<P: AsRef<Path>>(
     path: P,
     routes: &n18route::Routes,
     pretty: bool,
-) -> Result<(), Box<dyn Error>> {
-    let file = File::create(path)?;
+) -> Result<(), Error> {
     let routes: routes::Routes = routes.into();
-    if pretty {
-        serde_json::to_writer_pretty(file, &routes)?;
-    } else {
-        serde_json::to_writer(file, &routes)?;
-    }
-    Ok(())
+    write_json(path, &routes, pretty)
+}
+
+/// Reads train routes from JSON text.
+pub fn routes_from_str(text: &str) -> Result<n18route::Routes, Error> {
+    let routes: routes::Routes = from_json_str(text, None)?;
+    Ok(routes.into())
+}
+
+/// Reads train routes from a reader that provides JSON text.
+pub fn routes_from_reader<R: Read>(
+    reader: R,
+) -> Result<n18route::Routes, Error> {
+    routes_from_str(&read_text(reader)?)
+}
+
+/// Writes train routes as JSON text.
+pub fn routes_to_string(
+    routes: &n18route::Routes,
+    pretty: bool,
+) -> Result<String, Error> {
+    let routes: routes::Routes = routes.into();
+    to_json_string(&routes, pretty)
+}
+
+/// Writes train routes as JSON text to a writer.
+pub fn routes_to_writer<W: Write>(
+    writer: W,
+    routes: &n18route::Routes,
+    pretty: bool,
+) -> Result<(), Error> {
+    let routes: routes::Routes = routes.into();
+    to_json_writer(writer, &routes, pretty, None)
+}
+
+/// Writes the tiles that can be placed on a map hex, and the rotations with
+/// which each tile can be placed, as JSON text.
+///
+/// The `placements` are typically returned by
+/// [n18map::Map::legal_placements].
+pub fn placements_to_string(
+    placements: &[(String, Vec<n18hex::RotateCW>)],
+    pretty: bool,
+) -> Result<String, Error> {
+    let placements: Vec<placements::TilePlacement> = placements
+        .iter()
+        .map(|placement| placement.into())
+        .collect();
+    to_json_string(&placements, pretty)
+}
+
+/// Reads a game move from JSON text.
+pub fn move_from_str(text: &str) -> Result<n18game::sim::Move, Error> {
+    let mv: moves::Move = from_json_str(text, None)?;
+    mv.try_into()
+}
+
+/// Reads a game move from a reader that provides JSON text.
+pub fn move_from_reader<R: Read>(
+    reader: R,
+) -> Result<n18game::sim::Move, Error> {
+    move_from_str(&read_text(reader)?)
+}
+
+/// Writes a game move as JSON text.
+pub fn move_to_string(
+    mv: &n18game::sim::Move,
+    pretty: bool,
+) -> Result<String, Error> {
+    let mv: moves::Move = mv.into();
+    to_json_string(&mv, pretty)
+}
+
+/// Writes a game move as JSON text to a writer.
+pub fn move_to_writer<W: Write>(
+    writer: W,
+    mv: &n18game::sim::Move,
+    pretty: bool,
+) -> Result<(), Error> {
+    let mv: moves::Move = mv.into();
+    to_json_writer(writer, &mv, pretty, None)
+}
+
+/// Reads a collection of tokens from disk, and loads the image for each
+/// emblem that the file defines.
+///
+/// Emblem images must be PNG files (SVG images are not supported), and their
+/// paths are interpreted relative to the directory that contains the tokens
+/// file.
+#[cfg(feature = "cairo")]
+pub fn read_tokens<P: AsRef<Path>>(
+    path: P,
+) -> Result<n18token::Tokens, Error> {
+    let tokens: Tokens = read_json(&path)?;
+    let base_dir = path.as_ref().parent().unwrap_or_else(|| Path::new(""));
+    for (name, image) in &tokens.emblems {
+        let image_path = base_dir.join(image);
+        n18token::Emblem::load_png(name, &image_path)
+            .map_err(|e| Error::image(&image_path, e))?;
+    }
+    Ok(n18token::Tokens::new(
+        tokens
+            .tokens
+            .iter()
+            .map(|(name, token)| (name.clone(), token.into()))
+            .collect(),
+    ))
+}
+
+/// Writes a collection of tokens to disk.
+///
+/// The `emblems` argument identifies the image file for each emblem, relative
+/// to the tokens file; emblems that are not included will be drawn without an
+/// image when the tokens are read.
+pub fn write_tokens<P: AsRef<Path>>(
+    path: P,
+    tokens: &n18token::Tokens,
+    emblems: &BTreeMap<String, String>,
+    pretty: bool,
+) -> Result<(), Error> {
+    let tokens = Tokens {
+        emblems: emblems.clone(),
+        tokens: tokens
+            .names()
+            .iter()
+            .zip(tokens.tokens())
+            .map(|(name, token)| (name.clone(), token.into()))
+            .collect(),
+    };
+    write_json(path, &tokens, pretty)
 }
 
 // NOTE: need hex and ctx to construct tiles!
@@ -785,7 +1176,10 @@ pub fn write_routes<P: AsRef<Path>>(
 impl Tiles {
     /// Returns a collection of [n18tile::Tile] values equivalent to these
     /// tiles.
-    pub fn build(&self) -> Vec<n18tile::Tile> {
+    ///
+    /// Returns an [Error::InvalidPhaseRevenue] value if any tile defines a
+    /// dit revenue for a colour that is not a game phase.
+    pub fn build(&self) -> Result<Vec<n18tile::Tile>, Error> {
         let hex = Hex::default();
         self.tiles.iter().map(|t| t.build(&hex)).collect()
     }
@@ -801,11 +1195,23 @@ impl Tile {
     /// It is accepted as an explicit argument so that it can be provided by
     /// [Tiles::build], rather than requiring a new [Hex] to be constructed
     /// for each call to this method.
-    pub fn build(&self, hex: &Hex) -> n18tile::Tile {
+    ///
+    /// Returns an [Error::InvalidPhaseRevenue] value if any dit has a
+    /// revenue for a colour that is not a game phase.
+    pub fn build(&self, hex: &Hex) -> Result<n18tile::Tile, Error> {
+        let track = self
+            .track
+            .iter()
+            .map(n18tile::Track::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|colour| Error::InvalidPhaseRevenue {
+                tile: self.name.clone(),
+                colour,
+            })?;
         let tile = n18tile::Tile::new(
             (&self.colour).into(),
             self.name.clone(),
-            self.track.iter().map(|t| t.into()).collect(),
+            track,
             self.cities.iter().map(|c| c.build()).collect(),
             hex,
         );
@@ -819,11 +1225,14 @@ impl Tile {
         } else {
             tile
         };
+        let tile = self.arrows.iter().fold(tile, |tile, arrow| {
+            tile.arrow(n18tile::Arrow::from(arrow))
+        });
         // Hide the tile name label if it should not be displayed.
         if !self.show_tile_name {
-            tile.hide_tile_name()
+            Ok(tile.hide_tile_name())
         } else {
-            tile
+            Ok(tile)
         }
     }
 }
@@ -943,8 +1352,12 @@ impl From<&HexFace> for n18hex::HexFace {
     }
 }
 
-impl From<&Track> for n18tile::Track {
-    fn from(t: &Track) -> n18tile::Track {
+impl TryFrom<&Track> for n18tile::Track {
+    type Error = n18hex::HexColour;
+
+    /// Returns the equivalent track segment, or the first phase-dependent
+    /// dit revenue colour that is not a game phase.
+    fn try_from(t: &Track) -> Result<n18tile::Track, n18hex::HexColour> {
         let track = match t.track_type {
             TrackType::Mid(ref face) => n18tile::Track::mid(face.into()),
             TrackType::Straight(ref face) => {
@@ -960,7 +1373,17 @@ impl From<&Track> for n18tile::Track {
             TrackType::HardR(ref face) => n18tile::Track::hard_r(face.into()),
         };
         let track = if let Some((posn, revenue, shape)) = t.dit {
-            track.with_dit(posn.into(), revenue, shape.into())
+            let revenues: Vec<(n18hex::HexColour, usize)> = t
+                .dit_revenues
+                .iter()
+                .map(|(colour, revenue)| (colour.into(), *revenue))
+                .collect();
+            track.with_phase_dit(
+                posn.into(),
+                revenue,
+                shape.into(),
+                n18tile::PhaseRevenues::try_new(&revenues)?,
+            )
         } else {
             track
         };
@@ -970,9 +1393,9 @@ impl From<&Track> for n18tile::Track {
             track
         };
         if let Some((x0, x1)) = t.span {
-            track.with_span(x0, x1)
+            Ok(track.with_span(x0, x1))
         } else {
-            track
+            Ok(track)
         }
     }
 }
@@ -1066,15 +1489,24 @@ impl City {
                 .unwrap_or(n18tile::Rotation::Zero),
         );
         // Apply the optional fill colour.
-        if let Some(ref colour) = self.fill_colour {
+        let city = if let Some(ref colour) = self.fill_colour {
             city.with_fill(colour.into())
         } else {
             city
+        };
+        // Apply the optional phase from which the token spaces are available.
+        if let Some(ref colour) = self.available_from {
+            city.with_available_from(colour.into())
+        } else {
+            city
         }
     }
 }
 
-#[derive(Default, Serialize, Deserialize, PartialEq, Eq)]
+/// Tile rotations are (de)serialised as strings, so that invalid rotations
+/// can be reported with the address of the affected hex.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
 enum TileRotation {
     #[default]
     Zero,
@@ -1083,6 +1515,39 @@ enum TileRotation {
     Half,
     Acw2,
     Acw1,
+    Invalid(String),
+}
+
+impl std::convert::From<String> for TileRotation {
+    fn from(src: String) -> Self {
+        use TileRotation::*;
+
+        match src.as_str() {
+            "Zero" => Zero,
+            "Cw1" => Cw1,
+            "Cw2" => Cw2,
+            "Half" => Half,
+            "Acw2" => Acw2,
+            "Acw1" => Acw1,
+            _ => Invalid(src),
+        }
+    }
+}
+
+impl std::convert::From<TileRotation> for String {
+    fn from(src: TileRotation) -> Self {
+        use TileRotation::*;
+
+        match src {
+            Zero => "Zero".to_string(),
+            Cw1 => "Cw1".to_string(),
+            Cw2 => "Cw2".to_string(),
+            Half => "Half".to_string(),
+            Acw2 => "Acw2".to_string(),
+            Acw1 => "Acw1".to_string(),
+            Invalid(text) => text,
+        }
+    }
 }
 
 impl TileRotation {
@@ -1091,6 +1556,16 @@ impl TileRotation {
     }
 }
 
+/// A collection of named tokens, and the images for any emblems that they
+/// contain.
+#[derive(Serialize, Deserialize)]
+struct Tokens {
+    /// The image file for each emblem, relative to the tokens file.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    emblems: BTreeMap<String, String>,
+    tokens: Vec<(String, Token)>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct Token {
     pub style: TokenStyle,
@@ -1152,6 +1627,12 @@ enum TokenStyle {
         bottom: Colour,
         text: Colour,
     },
+    /// Refers to the emblem image by name; see [n18token::Emblem].
+    Emblem {
+        bg: Colour,
+        emblem: String,
+        text: Colour,
+    },
 }
 
 #[derive(Serialize, Deserialize)]
@@ -1175,15 +1656,137 @@ struct TileDescr {
 struct HexAddress {
     row: isize,
     col: isize,
+    /// The latest tile colour that can be placed on this hex, where `Empty`
+    /// indicates that no tiles can be placed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_colour: Option<HexColour>,
+    /// The names of the markers (e.g., port and mine markers) that have
+    /// been placed on this hex.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    markers: Vec<String>,
+    /// The decorative terrain (e.g., mountains or water) drawn on this hex.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    terrain: Option<Terrain>,
+    /// The name of the location that this hex is part of, if the location
+    /// spans several hexes (e.g., an off-board area).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    location: Option<String>,
+    /// The half of this hex that is present, if the hex is only partially
+    /// present (e.g., along the edges of the 1825 and 1829 maps).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    partial: Option<HexHalf>,
+    /// The tile that is planned for this hex but has not been placed, if
+    /// any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    planned: Option<PlannedTile>,
     #[serde(flatten)]
     tile: Option<TileDescr>,
 }
 
+/// The decorative terrain drawn on a map hex; see [n18map::Terrain].
+#[derive(Serialize, Deserialize, Copy, Clone)]
+enum Terrain {
+    Mountain,
+    Water,
+}
+
+impl From<n18map::Terrain> for Terrain {
+    fn from(src: n18map::Terrain) -> Self {
+        match src {
+            n18map::Terrain::Mountain => Terrain::Mountain,
+            n18map::Terrain::Water => Terrain::Water,
+        }
+    }
+}
+
+impl From<Terrain> for n18map::Terrain {
+    fn from(src: Terrain) -> Self {
+        match src {
+            Terrain::Mountain => n18map::Terrain::Mountain,
+            Terrain::Water => n18map::Terrain::Water,
+        }
+    }
+}
+
+/// The half of a partial map hex that is present; see [n18map::HexHalf].
+#[derive(Serialize, Deserialize, Copy, Clone)]
+enum HexHalf {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl From<n18map::HexHalf> for HexHalf {
+    fn from(src: n18map::HexHalf) -> Self {
+        match src {
+            n18map::HexHalf::Top => HexHalf::Top,
+            n18map::HexHalf::Bottom => HexHalf::Bottom,
+            n18map::HexHalf::Left => HexHalf::Left,
+            n18map::HexHalf::Right => HexHalf::Right,
+        }
+    }
+}
+
+impl From<HexHalf> for n18map::HexHalf {
+    fn from(src: HexHalf) -> Self {
+        match src {
+            HexHalf::Top => n18map::HexHalf::Top,
+            HexHalf::Bottom => n18map::HexHalf::Bottom,
+            HexHalf::Left => n18map::HexHalf::Left,
+            HexHalf::Right => n18map::HexHalf::Right,
+        }
+    }
+}
+
+/// A tile that is planned for a map hex, but has not been placed.
+#[derive(Serialize, Deserialize)]
+struct PlannedTile {
+    tile: String,
+    #[serde(default, skip_serializing_if = "TileRotation::is_default")]
+    rotation: TileRotation,
+}
+
 impl HexAddress {
     fn with_tile(mut self, tile: Option<TileDescr>) -> Self {
         self.tile = tile;
         self
     }
+
+    fn with_max_colour(mut self, colour: Option<n18hex::HexColour>) -> Self {
+        self.max_colour = colour.map(|c| c.into());
+        self
+    }
+
+    fn with_markers(mut self, markers: Option<&Vec<n18map::Marker>>) -> Self {
+        self.markers = markers
+            .map(|ms| ms.iter().map(|m| m.name.clone()).collect())
+            .unwrap_or_default();
+        self
+    }
+
+    fn with_terrain(mut self, terrain: Option<n18map::Terrain>) -> Self {
+        self.terrain = terrain.map(|t| t.into());
+        self
+    }
+
+    fn with_location(mut self, location: Option<&String>) -> Self {
+        self.location = location.cloned();
+        self
+    }
+
+    fn with_partial(mut self, half: Option<n18map::HexHalf>) -> Self {
+        self.partial = half.map(|h| h.into());
+        self
+    }
+
+    fn with_planned(mut self, planned: Option<&n18map::PlannedTile>) -> Self {
+        self.planned = planned.map(|planned| PlannedTile {
+            tile: planned.tile.clone(),
+            rotation: (&planned.rotation).into(),
+        });
+        self
+    }
 }
 
 #[derive(Serialize, Deserialize, Copy, Clone)]
@@ -1216,6 +1819,9 @@ impl std::convert::From<Orientation> for n18hex::Orientation {
 
 #[derive(Serialize, Deserialize)]
 struct Descr {
+    /// The schema version, see [SCHEMA_VERSION].
+    #[serde(default)]
+    version: u32,
     orientation: Orientation,
     tiles: Vec<HexAddress>,
 }
@@ -1235,18 +1841,27 @@ impl std::convert::From<&n18hex::RotateCW> for TileRotation {
     }
 }
 
-impl std::convert::From<&TileRotation> for n18hex::RotateCW {
-    fn from(src: &TileRotation) -> Self {
+impl TileRotation {
+    /// Returns the equivalent rotation, or an [Error::InvalidRotation] for
+    /// the tile at hex `addr` if this rotation is not valid.
+    fn rotation(
+        &self,
+        addr: n18map::HexAddress,
+    ) -> Result<n18hex::RotateCW, Error> {
         use self::TileRotation::*;
         use n18hex::RotateCW;
 
-        match src {
-            Zero => RotateCW::Zero,
-            Cw1 => RotateCW::One,
-            Cw2 => RotateCW::Two,
-            Half => RotateCW::Three,
-            Acw2 => RotateCW::Four,
-            Acw1 => RotateCW::Five,
+        match self {
+            Zero => Ok(RotateCW::Zero),
+            Cw1 => Ok(RotateCW::One),
+            Cw2 => Ok(RotateCW::Two),
+            Half => Ok(RotateCW::Three),
+            Acw2 => Ok(RotateCW::Four),
+            Acw1 => Ok(RotateCW::Five),
+            Invalid(text) => Err(Error::InvalidRotation {
+                addr,
+                rotation: text.clone(),
+            }),
         }
     }
 }
@@ -1348,6 +1963,11 @@ impl std::convert::From<&n18token::TokenStyle> for TokenStyle {
                 bottom: bottom.into(),
                 text: text.into(),
             },
+            Emblem { bg, emblem, text } => Self::Emblem {
+                bg: bg.into(),
+                emblem: emblem.name(),
+                text: text.into(),
+            },
         }
     }
 }
@@ -1427,6 +2047,11 @@ impl std::convert::From<&TokenStyle> for n18token::TokenStyle {
                 bottom: bottom.into(),
                 text: text.into(),
             },
+            Emblem { bg, emblem, text } => Self::Emblem {
+                bg: bg.into(),
+                emblem: n18token::Emblem::named(emblem),
+                text: text.into(),
+            },
         }
     }
 }
@@ -1457,6 +2082,12 @@ impl std::convert::From<&n18map::HexAddress> for HexAddress {
         HexAddress {
             row,
             col,
+            max_colour: None,
+            markers: vec![],
+            terrain: None,
+            location: None,
+            partial: None,
+            planned: None,
             tile: None,
         }
     }
@@ -1483,18 +2114,21 @@ impl std::convert::From<&n18map::descr::TileDescr> for TileDescr {
 }
 
 // NOTE: cannot implement From<(&HexAddress, &TileDescr)> for TileDescr.
-fn tile_descr(addr: &HexAddress, descr: &TileDescr) -> n18map::TileDescr {
-    n18map::TileDescr {
+fn tile_descr(
+    addr: &HexAddress,
+    descr: &TileDescr,
+) -> Result<n18map::TileDescr, Error> {
+    Ok(n18map::TileDescr {
         row: addr.row,
         col: addr.col,
         tile: descr.tile.clone(),
-        rotation: (&descr.rotation).into(),
+        rotation: descr.rotation.rotation(addr.into())?,
         tokens: descr
             .tokens
             .iter()
             .map(|(ix, tok)| (*ix, tok.into()))
             .collect(),
-    }
+    })
 }
 
 impl std::convert::From<&n18map::descr::Descr> for Descr {
@@ -1503,39 +2137,126 @@ impl std::convert::From<&n18map::descr::Descr> for Descr {
         let tiles: Vec<HexAddress> = tiles
             .iter()
             .map(|(k, v)| {
-                HexAddress::from(k).with_tile(v.as_ref().map(|td| td.into()))
+                HexAddress::from(k)
+                    .with_tile(v.as_ref().map(|td| td.into()))
+                    .with_max_colour(src.max_colours().get(k).copied())
+                    .with_markers(src.markers().get(k))
+                    .with_terrain(src.terrain().get(k).copied())
+                    .with_location(src.locations().get(k))
+                    .with_partial(src.partial_hexes().get(k).copied())
+                    .with_planned(src.plan().get(*k))
             })
             .collect();
         let orientation = orientation.into();
-        Descr { tiles, orientation }
+        Descr {
+            version: SCHEMA_VERSION,
+            tiles,
+            orientation,
+        }
     }
 }
 
-impl std::convert::From<&Descr> for n18map::descr::Descr {
-    fn from(src: &Descr) -> Self {
+impl std::convert::TryFrom<&Descr> for n18map::descr::Descr {
+    type Error = Error;
+
+    fn try_from(src: &Descr) -> Result<Self, Error> {
+        // Report every invalid rotation, rather than only the first.
+        let mut problems = vec![];
         let tiles: BTreeMap<_, _> = src
             .tiles
             .iter()
+            .filter_map(|addr| {
+                let descr = addr.tile.as_ref().map(|td| tile_descr(addr, td));
+                match descr.transpose() {
+                    Ok(descr) => Some((addr.into(), descr)),
+                    Err(e) => {
+                        problems.push(e);
+                        None
+                    }
+                }
+            })
+            .collect();
+        let plan = src
+            .tiles
+            .iter()
+            .filter_map(|addr| {
+                let planned = addr.planned.as_ref()?;
+                match planned.rotation.rotation(addr.into()) {
+                    Ok(rotation) => Some((
+                        addr.into(),
+                        n18map::PlannedTile {
+                            tile: planned.tile.clone(),
+                            rotation,
+                        },
+                    )),
+                    Err(e) => {
+                        problems.push(e);
+                        None
+                    }
+                }
+            })
+            .collect();
+        Error::from_problems("map", problems)?;
+        let max_colours = src
+            .tiles
+            .iter()
+            .filter_map(|addr| {
+                addr.max_colour.as_ref().map(|c| (addr.into(), c.into()))
+            })
+            .collect();
+        let markers = src
+            .tiles
+            .iter()
+            .filter(|addr| !addr.markers.is_empty())
             .map(|addr| {
-                (
-                    addr.into(),
-                    addr.tile.as_ref().map(|td| tile_descr(addr, td)),
-                )
+                let markers = addr
+                    .markers
+                    .iter()
+                    .map(|name| n18map::Marker::new(name.as_str()))
+                    .collect();
+                (addr.into(), markers)
             })
             .collect();
-        let orientation = src.orientation.into();
-        (orientation, tiles).into()
-    }
-}
-
-/// Reads a map configuration from disk.
-pub fn read_map_descr<P: AsRef<Path>>(
-    path: P,
-) -> Result<n18map::descr::Descr, Box<dyn Error>> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let descr: Descr = serde_json::from_reader(reader)?;
-    Ok((&descr).into())
+        let terrain = src
+            .tiles
+            .iter()
+            .filter_map(|addr| addr.terrain.map(|t| (addr.into(), t.into())))
+            .collect();
+        let locations = src
+            .tiles
+            .iter()
+            .filter_map(|addr| {
+                addr.location
+                    .as_ref()
+                    .map(|name| (addr.into(), name.clone()))
+            })
+            .collect();
+        let partial_hexes = src
+            .tiles
+            .iter()
+            .filter_map(|addr| addr.partial.map(|h| (addr.into(), h.into())))
+            .collect();
+        let orientation = src.orientation.into();
+        let descr: n18map::descr::Descr = (orientation, tiles).into();
+        Ok(descr
+            .with_max_colours(max_colours)
+            .with_markers(markers)
+            .with_terrain(terrain)
+            .with_locations(locations)
+            .with_partial_hexes(partial_hexes)
+            .with_plan(plan))
+    }
+}
+
+/// Reads a map configuration from disk.
+///
+/// Configurations saved with an older schema version are upgraded to the
+/// current version, and those saved with a newer version are rejected.
+pub fn read_map_descr<P: AsRef<Path>>(
+    path: P,
+) -> Result<n18map::descr::Descr, Error> {
+    let descr: Descr = read_versioned_json(path, migrate::migrate_descr)?;
+    (&descr).try_into()
 }
 
 /// Writes a map configuration to disk.
@@ -1543,55 +2264,312 @@ pub fn write_map_descr<P: AsRef<Path>>(
     path: P,
     descr: &n18map::descr::Descr,
     pretty: bool,
-) -> Result<(), Box<dyn Error>> {
-    let file = File::create(path)?;
+) -> Result<(), Error> {
     let descr: Descr = descr.into();
-    if pretty {
-        serde_json::to_writer_pretty(file, &descr)?;
-    } else {
-        serde_json::to_writer(file, &descr)?;
+    write_json(path, &descr, pretty)
+}
+
+/// Reads a map configuration from JSON text.
+///
+/// Map configurations saved with an older schema version are upgraded to
+/// the current version, and those saved with a newer version are rejected.
+pub fn map_descr_from_str(text: &str) -> Result<n18map::descr::Descr, Error> {
+    let descr: Descr =
+        from_versioned_json_str(text, migrate::migrate_descr, None)?;
+    (&descr).try_into()
+}
+
+/// Reads a map configuration from a reader that provides JSON text.
+///
+/// See [map_descr_from_str] for details.
+pub fn map_descr_from_reader<R: Read>(
+    reader: R,
+) -> Result<n18map::descr::Descr, Error> {
+    map_descr_from_str(&read_text(reader)?)
+}
+
+/// Writes a map configuration as JSON text.
+pub fn map_descr_to_string(
+    descr: &n18map::descr::Descr,
+    pretty: bool,
+) -> Result<String, Error> {
+    let descr: Descr = descr.into();
+    to_json_string(&descr, pretty)
+}
+
+/// Writes a map configuration as JSON text to a writer.
+pub fn map_descr_to_writer<W: Write>(
+    writer: W,
+    descr: &n18map::descr::Descr,
+    pretty: bool,
+) -> Result<(), Error> {
+    let descr: Descr = descr.into();
+    to_json_writer(writer, &descr, pretty, None)
+}
+
+/// Checks that every tile and token in a map configuration is defined by
+/// `map`, and that every token is placed in a valid token space.
+///
+/// This should be used to validate configurations that were read from disk,
+/// because [n18map::descr::Descr::update_map] panics if any of these checks
+/// would fail.
+/// If there is more than one problem, this returns an [Error::Invalid]
+/// value that lists every problem (see [validate_map_descr]).
+pub fn check_map_descr(
+    descr: &n18map::descr::Descr,
+    map: &n18map::Map,
+) -> Result<(), Error> {
+    Error::from_problems("map", validate_map_descr(descr, map))
+}
+
+/// Returns every problem in a map configuration, in order of hex address:
+/// tiles and tokens that are not defined by `map`, and tokens that are not
+/// placed in a valid token space.
+///
+/// Returns an empty vector if the configuration is consistent with `map`.
+pub fn validate_map_descr(
+    descr: &n18map::descr::Descr,
+    map: &n18map::Map,
+) -> Vec<Error> {
+    let (_orientation, tiles) = descr.into();
+    let mut problems = vec![];
+    for (addr, tile_descr) in tiles.iter() {
+        let tile_descr = if let Some(tile_descr) = tile_descr {
+            tile_descr
+        } else {
+            continue;
+        };
+        let tile = map.tile(&tile_descr.tile);
+        if tile.is_none() {
+            problems.push(Error::UnknownTile {
+                addr: *addr,
+                tile: tile_descr.tile.clone(),
+            });
+        }
+        // NOTE: we can only check token spaces if the tile is defined.
+        let num_spaces = tile.map(|tile| tile.token_spaces().len());
+        for (space_ix, token_name) in &tile_descr.tokens {
+            if num_spaces.is_some_and(|count| *space_ix >= count) {
+                problems.push(Error::InvalidTokenSpace {
+                    addr: *addr,
+                    tile: tile_descr.tile.clone(),
+                    space_ix: *space_ix,
+                });
+            }
+            if map.try_token(token_name).is_none() {
+                problems.push(Error::UnknownToken {
+                    addr: *addr,
+                    token: token_name.clone(),
+                });
+            }
+        }
     }
-    Ok(())
+    problems
 }
 
 #[derive(Serialize, Deserialize)]
 struct GameState {
+    /// The schema version, see [SCHEMA_VERSION].
+    #[serde(default)]
+    version: u32,
     /// A unique identifier for the game.
     game: String,
+    /// The map variant.
+    #[serde(default = "standard_variant")]
+    variant: String,
     /// The current game phase.
     phase: String,
     /// The current map state.
     map: Descr,
+    /// The moves that have been made in this game.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    journal: Vec<String>,
+    /// The trains held by the bank and available in the open market.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    trains: Vec<TrainStock>,
+    /// The seed for the random game setup, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    /// The custom title of the game, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    /// The private companies that are owned by a company, or have closed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    privates: Vec<PrivateHolding>,
+    /// The cash held by each company whose treasury is being tracked.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    treasuries: BTreeMap<String, usize>,
+}
+
+/// The company that owns a private company, and whether the private company
+/// has closed.
+#[derive(Serialize, Deserialize)]
+struct PrivateHolding {
+    /// The abbreviated name of the private company.
+    name: String,
+    /// The company that owns the private company, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    owner: Option<String>,
+    /// Whether the private company has closed.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    closed: bool,
+}
+
+/// Returns the owner of each private company, and the private companies
+/// that have closed.
+fn private_holdings(
+    src: Vec<PrivateHolding>,
+) -> n18game::private::PrivateHoldings {
+    let mut holdings = n18game::private::PrivateHoldings::default();
+    for holding in src {
+        if holding.closed {
+            holdings.close(&holding.name);
+        } else if let Some(owner) = holding.owner {
+            holdings.set_owner(&holding.name, &owner);
+        }
+    }
+    holdings
+}
+
+/// Describes the owner of each private company, and the private companies
+/// that have closed.
+fn describe_private_holdings(
+    src: &n18game::private::PrivateHoldings,
+) -> Vec<PrivateHolding> {
+    let owned = src.owners().map(|(name, owner)| PrivateHolding {
+        name: name.to_string(),
+        owner: Some(owner.to_string()),
+        closed: false,
+    });
+    let closed = src.closed().map(|name| PrivateHolding {
+        name: name.to_string(),
+        owner: None,
+        closed: true,
+    });
+    owned.chain(closed).collect()
+}
+
+/// Returns the cash held by each company whose treasury is being tracked.
+fn treasuries(src: BTreeMap<String, usize>) -> n18game::treasury::Treasuries {
+    let mut treasuries = n18game::treasury::Treasuries::default();
+    for (company, cash) in src {
+        treasuries.set_cash(&company, cash);
+    }
+    treasuries
+}
+
+/// The number of trains of a single type that are held by the bank and are
+/// available in the open market.
+#[derive(Serialize, Deserialize)]
+struct TrainStock {
+    /// The name of the train type.
+    name: String,
+    /// The number of trains held by the bank, which is omitted if the bank
+    /// holds an unlimited number of trains.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bank: Option<usize>,
+    /// The number of trains available in the open market.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pool: usize,
+    /// The number of trains that the bank has sold.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    sold: usize,
+}
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+impl From<TrainStock> for n18game::bank::TrainStock {
+    fn from(src: TrainStock) -> Self {
+        n18game::bank::TrainStock {
+            name: src.name,
+            bank: src.bank,
+            pool: src.pool,
+            sold: src.sold,
+            phase: None,
+        }
+    }
 }
 
-impl From<GameState> for n18game::GameState {
-    fn from(src: GameState) -> Self {
-        n18game::GameState {
+impl From<n18game::bank::TrainStock> for TrainStock {
+    fn from(src: n18game::bank::TrainStock) -> Self {
+        TrainStock {
+            name: src.name,
+            bank: src.bank,
+            pool: src.pool,
+            sold: src.sold,
+        }
+    }
+}
+
+/// Returns the map variant for game states that do not specify a variant.
+fn standard_variant() -> String {
+    n18game::STANDARD_VARIANT.to_string()
+}
+
+impl TryFrom<GameState> for n18game::GameState {
+    type Error = Error;
+
+    fn try_from(src: GameState) -> Result<Self, Error> {
+        Ok(n18game::GameState {
             game: src.game,
+            variant: src.variant,
             phase: src.phase,
-            map: (&src.map).into(),
-        }
+            map: (&src.map).try_into()?,
+            journal: src.journal.into(),
+            trains: src
+                .trains
+                .into_iter()
+                .map(|stock| stock.into())
+                .collect::<Vec<n18game::bank::TrainStock>>()
+                .into(),
+            seed: src.seed,
+            title: src.title,
+            privates: private_holdings(src.privates),
+            treasuries: treasuries(src.treasuries),
+        })
     }
 }
 
 impl From<n18game::GameState> for GameState {
     fn from(src: n18game::GameState) -> Self {
         GameState {
+            version: SCHEMA_VERSION,
             game: src.game,
+            variant: src.variant,
             phase: src.phase,
             map: (&src.map).into(),
+            journal: src.journal.into(),
+            trains: Vec::from(src.trains)
+                .into_iter()
+                .map(|stock| stock.into())
+                .collect(),
+            seed: src.seed,
+            title: src.title,
+            privates: describe_private_holdings(&src.privates),
+            treasuries: src
+                .treasuries
+                .iter()
+                .map(|(company, cash)| (company.to_string(), cash))
+                .collect(),
         }
     }
 }
 
 /// Reads a game state from disk.
+///
+/// Game states saved with an older schema version are upgraded to the
+/// current version, and those saved with a newer version are rejected.
+///
+/// Note that the map configuration should be checked with
+/// [check_map_descr] before it is used to update a map.
 pub fn read_game_state<P: AsRef<Path>>(
     path: P,
-) -> Result<n18game::GameState, Box<dyn Error>> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let game_state: GameState = serde_json::from_reader(reader)?;
-    Ok(game_state.into())
+) -> Result<n18game::GameState, Error> {
+    let game_state: GameState =
+        read_versioned_json(path, migrate::migrate_game_state)?;
+    game_state.try_into()
 }
 
 /// Writes a game state to disk.
@@ -1599,35 +2577,62 @@ pub fn write_game_state<P: AsRef<Path>>(
     path: P,
     game_state: n18game::GameState,
     pretty: bool,
-) -> Result<(), Box<dyn Error>> {
-    let file = File::create(path)?;
+) -> Result<(), Error> {
     let game_state: GameState = game_state.into();
-    if pretty {
-        serde_json::to_writer_pretty(file, &game_state)?;
-    } else {
-        serde_json::to_writer(file, &game_state)?;
-    }
-    Ok(())
+    write_json(path, &game_state, pretty)
+}
+
+/// Reads a game state from JSON text.
+///
+/// Game states saved with an older schema version are upgraded to the
+/// current version, and those saved with a newer version are rejected.
+///
+/// Note that the map configuration should be checked with
+/// [check_map_descr] before it is used to update a map.
+pub fn game_state_from_str(text: &str) -> Result<n18game::GameState, Error> {
+    let game_state: GameState =
+        from_versioned_json_str(text, migrate::migrate_game_state, None)?;
+    game_state.try_into()
+}
+
+/// Reads a game state from a reader that provides JSON text.
+///
+/// See [game_state_from_str] for details.
+pub fn game_state_from_reader<R: Read>(
+    reader: R,
+) -> Result<n18game::GameState, Error> {
+    game_state_from_str(&read_text(reader)?)
+}
+
+/// Writes a game state as JSON text.
+pub fn game_state_to_string(
+    game_state: n18game::GameState,
+    pretty: bool,
+) -> Result<String, Error> {
+    let game_state: GameState = game_state.into();
+    to_json_string(&game_state, pretty)
+}
+
+/// Writes a game state as JSON text to a writer.
+pub fn game_state_to_writer<W: Write>(
+    writer: W,
+    game_state: n18game::GameState,
+    pretty: bool,
+) -> Result<(), Error> {
+    let game_state: GameState = game_state.into();
+    to_json_writer(writer, &game_state, pretty, None)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn read<P: AsRef<Path>>(path: P) -> Result<Tiles, Box<dyn Error>> {
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        let tiles = serde_json::from_reader(reader)?;
-        Ok(tiles)
+    fn read<P: AsRef<Path>>(path: P) -> Result<Tiles, Error> {
+        read_json(path)
     }
 
-    fn write<P: AsRef<Path>>(
-        path: P,
-        tiles: &Tiles,
-    ) -> Result<(), Box<dyn Error>> {
-        let file = File::create(path)?;
-        serde_json::to_writer(file, tiles)?;
-        Ok(())
+    fn write<P: AsRef<Path>>(path: P, tiles: &Tiles) -> Result<(), Error> {
+        write_json(path, tiles, false)
     }
 
     static OUT_DIR: &str = "../../tests/output";
@@ -1663,12 +2668,63 @@ mod tests {
         assert_eq!(cat_in, cat_out);
     }
 
+    #[test]
+    fn json_round_trip_phase_dit() {
+        use n18hex::HexColour::*;
+        use n18tile::{DitShape, PhaseRevenues, TrackEnd};
+
+        let hex = Hex::default();
+        let revenues = PhaseRevenues::new(&[(Green, 20), (Grey, 40)]);
+        let tile = n18tile::Tile::new(
+            Yellow,
+            "Phase dit",
+            vec![n18tile::Track::straight(n18hex::HexFace::Top)
+                .with_phase_dit(TrackEnd::End, 10, DitShape::Bar, revenues)],
+            vec![],
+            &hex,
+        );
+        let text = super::tile_to_string(&tile, false).unwrap();
+        assert!(text.contains("dit_revenues"));
+        let tile_out = super::tile_from_str(&text).unwrap();
+        assert_eq!(tile, tile_out);
+        assert_eq!(tile_out.dits()[0].revenue_in(None), 10);
+        assert_eq!(tile_out.dits()[0].revenue_in(Some(Brown)), 20);
+        assert_eq!(tile_out.dits()[0].revenue_in(Some(Grey)), 40);
+
+        // Dits without phase-dependent revenue are written as before.
+        let tiles = n18catalogue::tile_catalogue();
+        let text = super::tiles_to_string(&tiles, false).unwrap();
+        assert!(!text.contains("dit_revenues"));
+    }
+
+    #[test]
+    fn invalid_dit_revenue_phase() {
+        let text = r#"{
+            "name": "Bad dit",
+            "colour": "Yellow",
+            "track": [{
+                "Straight": "Top",
+                "dit": ["End", 10, "Bar"],
+                "dit_revenues": [["Red", 30]]
+            }],
+            "cities": []
+        }"#;
+        let result = super::tile_from_str(text);
+        assert!(matches!(
+            result,
+            Err(super::Error::InvalidPhaseRevenue {
+                ref tile,
+                colour: n18hex::HexColour::Red,
+            }) if tile == "Bad dit"
+        ));
+    }
+
     #[test]
     fn json_round_trip_1867() {
         // The 1867 game includes starting tiles (part of the map) and
         // off-board tiles, which make use of features such as hiding the tile
-        // names and marking unavailable token spaces with fill colours, that
-        // are not used by any of the tiles in n18catalogue.
+        // names and marking token spaces that are unavailable until a later
+        // phase, that are not used by any of the tiles in n18catalogue.
         // This test case ensures these features are correctly (de)serialised.
         use n18game::Game;
         let game = n18game::_1867::Game::new();
@@ -1690,7 +2746,7 @@ mod tests {
         let catalogue = n18catalogue::tile_catalogue();
         for cat_tile in catalogue {
             let de_tile = Tile::from(&cat_tile);
-            let new_tile = de_tile.build(&hex);
+            let new_tile = de_tile.build(&hex).unwrap();
             assert_eq!(cat_tile, new_tile, "Tiles differ: {}", cat_tile.name);
             let de_tile2 = Tile::from(&new_tile);
             assert_eq!(de_tile, de_tile2, "Tiles differ: {}", de_tile.name);
@@ -1705,7 +2761,7 @@ mod tests {
         let map = game.create_map(&hex);
         for tile in map.tile_iter() {
             let de_tile = Tile::from(tile);
-            let new_tile = de_tile.build(&hex);
+            let new_tile = de_tile.build(&hex).unwrap();
             assert_eq!(*tile, new_tile, "Tiles differ: {}", tile.name);
             let de_tile2 = Tile::from(&new_tile);
             assert_eq!(de_tile, de_tile2, "Tiles differ: {}", de_tile.name);
@@ -1715,7 +2771,7 @@ mod tests {
             let json_str = serde_json::to_string(&de_tile).unwrap();
             let de_tile3: Tile = serde_json::from_str(&json_str).unwrap();
             assert_eq!(de_tile, de_tile3, "Tiles differ: {}", de_tile.name);
-            let new_tile2 = de_tile3.build(&hex);
+            let new_tile2 = de_tile3.build(&hex).unwrap();
             assert_eq!(*tile, new_tile2, "Tiles differ: {}", tile.name);
         }
     }
@@ -1729,4 +2785,920 @@ mod tests {
     fn compare_1867_tiles() {
         compare_all_game_tiles(n18game::new_1867())
     }
+
+    #[test]
+    fn malformed_game_state() {
+        let filename = output_path("test-malformed_game_state.json");
+        std::fs::write(&filename, "{\n  \"game\": \"1867\",\n  phase\n}")
+            .unwrap();
+        match super::read_game_state(&filename) {
+            Err(Error::Json { line, column, .. }) => {
+                assert_eq!(line, 3);
+                assert_eq!(column, 3);
+            }
+            _ => panic!("Expected a JSON error"),
+        }
+    }
+
+    #[test]
+    fn missing_game_state() {
+        let filename = output_path("test-missing_game_state.json");
+        let _ = std::fs::remove_file(&filename);
+        match super::read_game_state(&filename) {
+            Err(err @ Error::Io { .. }) => {
+                assert_eq!(err.path(), Some(filename.as_path()))
+            }
+            _ => panic!("Expected an I/O error"),
+        }
+    }
+
+    #[test]
+    fn in_memory_round_trip() {
+        use n18game::{Game, Journal};
+        let hex = Hex::default();
+        let game = n18game::_1867::Game::new();
+        let mut map = game.create_map(&hex);
+        let addr = *map.hex_address_iter().next().unwrap();
+        assert!(map.place_tile(addr, "5", n18hex::RotateCW::Zero));
+
+        // Check that a game state is unchanged by writing it to a string or
+        // a writer, and reading it back.
+        let mut journal = Journal::new();
+        journal.record("Placed tile 5 at A1");
+        let state = || game.save(&map).with_journal(journal.clone());
+        let text = super::game_state_to_string(state(), true).unwrap();
+        let state_out = super::game_state_from_str(&text).unwrap();
+        assert_eq!(state_out.journal, journal);
+        assert_eq!(
+            super::game_state_to_string(state_out, true).unwrap(),
+            text
+        );
+        let mut bytes: Vec<u8> = vec![];
+        super::game_state_to_writer(&mut bytes, state(), true).unwrap();
+        assert_eq!(bytes, text.as_bytes());
+        let state_out = super::game_state_from_reader(&bytes[..]).unwrap();
+        assert_eq!(state_out.game, game.name());
+
+        // Check that map configurations are also unchanged.
+        let descr = n18map::descr::Descr::from(&map);
+        let text = super::map_descr_to_string(&descr, false).unwrap();
+        let descr_out = super::map_descr_from_str(&text).unwrap();
+        let text_out = super::map_descr_to_string(&descr_out, false).unwrap();
+        assert_eq!(text, text_out);
+        let mut bytes: Vec<u8> = vec![];
+        super::map_descr_to_writer(&mut bytes, &descr, false).unwrap();
+        assert!(super::map_descr_from_reader(&bytes[..]).is_ok());
+
+        // Check that tiles are also unchanged.
+        let tiles = n18catalogue::tile_catalogue();
+        let text = super::tile_to_string(&tiles[0], false).unwrap();
+        assert_eq!(super::tile_from_str(&text).unwrap(), tiles[0]);
+        let text = super::tiles_to_string(tiles.iter(), false).unwrap();
+        assert_eq!(super::tiles_from_str(&text).unwrap(), tiles);
+        let mut bytes: Vec<u8> = vec![];
+        super::tiles_to_writer(&mut bytes, tiles.iter(), false).unwrap();
+        assert_eq!(super::tiles_from_reader(&bytes[..]).unwrap(), tiles);
+
+        // Check that routes are also unchanged.
+        let routes = n18route::Routes {
+            net_revenue: 0,
+            train_routes: vec![],
+        };
+        let text = super::routes_to_string(&routes, false).unwrap();
+        assert_eq!(super::routes_from_str(&text).unwrap(), routes);
+
+        // Check that game moves are also unchanged.
+        use n18game::bank::TrainTransfer;
+        use n18game::sim::Move;
+        let addr = n18map::HexAddress::from((2, 3));
+        let moves = vec![
+            Move::LayTile {
+                addr,
+                tile: "8".to_string(),
+                rotation: n18hex::RotateCW::Two,
+                company: None,
+            },
+            Move::LayTile {
+                addr,
+                tile: "57".to_string(),
+                rotation: n18hex::RotateCW::Zero,
+                company: Some("PRR".to_string()),
+            },
+            Move::RemoveTile { addr },
+            Move::RotateTile {
+                addr,
+                rotation: n18hex::RotateCW::Four,
+            },
+            Move::PlaceToken {
+                addr,
+                space: 1,
+                company: "PRR".to_string(),
+            },
+            Move::RemoveToken { addr, space: 1 },
+            Move::TransferTrain(TrainTransfer::FromPool("3".to_string())),
+            Move::AdvancePhase,
+            Move::SetPhase(2),
+            Move::BuyPrivate {
+                private: "D&H".to_string(),
+                company: "PRR".to_string(),
+            },
+            Move::ClosePrivate("D&H".to_string()),
+            Move::SetCash {
+                company: "PRR".to_string(),
+                cash: 680,
+            },
+            Move::RunRoutes {
+                company: "PRR".to_string(),
+                trains: vec!["2".to_string(), "3".to_string()],
+                bonuses: vec![true],
+            },
+        ];
+        for mv in &moves {
+            let text = super::move_to_string(mv, false).unwrap();
+            assert_eq!(&super::move_from_str(&text).unwrap(), mv);
+            let mut bytes: Vec<u8> = vec![];
+            super::move_to_writer(&mut bytes, mv, true).unwrap();
+            assert_eq!(&super::move_from_reader(&bytes[..]).unwrap(), mv);
+        }
+        let text = r#"{"LayTile": {"addr": [0, 0], "tile": "8",
+                       "rotation": "Cw9"}}"#;
+        assert!(matches!(
+            super::move_from_str(text),
+            Err(Error::InvalidRotation { .. })
+        ));
+    }
+
+    #[test]
+    fn in_memory_errors() {
+        // Check that invalid JSON text reports where the error occurred.
+        // NOTE: only current versions are read directly from the text.
+        let text =
+            format!("{{\"version\": {},\n\"game\": 1867}}", SCHEMA_VERSION);
+        match super::game_state_from_str(&text) {
+            Err(Error::Json {
+                path: None, line, ..
+            }) => assert_eq!(line, 2),
+            _ => panic!("Expected an invalid JSON error"),
+        }
+        match super::tiles_from_str("[") {
+            Err(err) => assert_eq!(err.path(), None),
+            _ => panic!("Expected an invalid JSON error"),
+        }
+
+        // Check that newer schema versions are rejected.
+        let text = format!(
+            r#"{{"version": {}, "orientation": "FlatTop", "tiles": []}}"#,
+            SCHEMA_VERSION + 1
+        );
+        match super::map_descr_from_str(&text) {
+            Err(Error::UnsupportedVersion {
+                path: None,
+                found,
+                supported,
+            }) => {
+                assert_eq!(found, SCHEMA_VERSION + 1);
+                assert_eq!(supported, SCHEMA_VERSION);
+            }
+            _ => panic!("Expected an unsupported schema error"),
+        }
+    }
+
+    #[test]
+    fn max_colour_round_trip() {
+        use n18game::Game;
+        use n18hex::HexColour;
+        let hex = Hex::default();
+        let game = n18game::_1867::Game::new();
+        let mut map = game.create_map(&hex);
+        let addrs: Vec<n18map::HexAddress> =
+            map.hex_address_iter().take(3).copied().collect();
+        assert!(map.set_max_colour(addrs[0], HexColour::Green));
+        assert!(map.set_max_colour(addrs[1], HexColour::Empty));
+
+        // Check that the limits are saved only for the limited hexes, and
+        // are restored when the map configuration is read.
+        let descr = n18map::descr::Descr::from(&map);
+        let text = super::map_descr_to_string(&descr, false).unwrap();
+        assert_eq!(text.matches("max_colour").count(), 2);
+        let descr_out = super::map_descr_from_str(&text).unwrap();
+        assert_eq!(descr_out.max_colours(), descr.max_colours());
+        let mut new_map = game.create_map(&hex);
+        descr_out.update_map(&mut new_map);
+        assert_eq!(new_map.max_colour(addrs[0]), Some(HexColour::Green));
+        assert_eq!(new_map.max_colour(addrs[1]), Some(HexColour::Empty));
+        assert_eq!(new_map.max_colour(addrs[2]), None);
+
+        // Check that hexes without tiles can still define a limit.
+        let json = r#"{
+            "orientation": "FlatTop",
+            "tiles": [
+                {"row": 0, "col": 0, "max_colour": "Empty"},
+                {"row": 0, "col": 1, "tile": "5", "max_colour": "Yellow"}
+            ]
+        }"#;
+        let descr = super::map_descr_from_str(json).unwrap();
+        let (_orientation, tiles) = (&descr).into();
+        assert!(tiles[&(0, 0).into()].is_none());
+        assert_eq!(tiles[&(0, 1).into()].as_ref().unwrap().tile, "5");
+        assert_eq!(descr.max_colours().len(), 2);
+        assert_eq!(descr.max_colours()[&(0, 1).into()], HexColour::Yellow);
+    }
+
+    #[test]
+    fn plan_round_trip() {
+        use n18game::Game;
+        let hex = Hex::default();
+        let game = n18game::_1867::Game::new();
+        let mut map = game.create_map(&hex);
+        let addrs: Vec<n18map::HexAddress> = map
+            .hex_address_iter()
+            .filter(|addr| map.tile_at(**addr).is_none())
+            .take(2)
+            .copied()
+            .collect();
+        assert!(map.plan_tile(addrs[0], "8", n18hex::RotateCW::Two));
+        assert!(map.plan_tile(addrs[1], "57", n18hex::RotateCW::Zero));
+
+        // Check that planned tiles are saved only for the planned hexes, and
+        // are restored when the map configuration is read.
+        let descr = n18map::descr::Descr::from(&map);
+        let text = super::map_descr_to_string(&descr, false).unwrap();
+        assert_eq!(text.matches("planned").count(), 2);
+        let descr_out = super::map_descr_from_str(&text).unwrap();
+        assert_eq!(descr_out.plan(), map.plan());
+        let mut new_map = game.create_map(&hex);
+        descr_out.update_map(&mut new_map);
+        assert_eq!(new_map.plan(), map.plan());
+        assert!(new_map.tile_at(addrs[0]).is_none());
+
+        // Check that an invalid planned rotation is reported.
+        let json = r#"{
+            "orientation": "FlatTop",
+            "tiles": [
+                {"row": 0, "col": 0, "planned": {"tile": "8", "rotation": "Cw9"}}
+            ]
+        }"#;
+        let result = super::map_descr_from_str(json);
+        assert!(matches!(result, Err(Error::InvalidRotation { .. })));
+    }
+
+    #[test]
+    fn marker_round_trip() {
+        use n18game::Game;
+        let hex = Hex::default();
+        let game = n18game::_1867::Game::new();
+        let mut map = game.create_map(&hex);
+        let addrs: Vec<n18map::HexAddress> =
+            map.hex_address_iter().take(3).copied().collect();
+        assert!(map.place_marker(addrs[0], "Port"));
+        assert!(map.place_marker(addrs[0], "Mine"));
+        assert!(map.place_marker(addrs[1], "Port"));
+
+        // Check that markers are saved only for the marked hexes, and are
+        // restored when the map configuration is read.
+        let descr = n18map::descr::Descr::from(&map);
+        let text = super::map_descr_to_string(&descr, false).unwrap();
+        assert_eq!(text.matches("markers").count(), 2);
+        let descr_out = super::map_descr_from_str(&text).unwrap();
+        assert_eq!(descr_out.markers(), descr.markers());
+        let mut new_map = game.create_map(&hex);
+        assert!(new_map.place_marker(addrs[2], "Bridge"));
+        descr_out.update_map(&mut new_map);
+        assert_eq!(new_map.markers_at(addrs[0]), map.markers_at(addrs[0]));
+        assert_eq!(new_map.markers_at(addrs[1]), map.markers_at(addrs[1]));
+        assert!(new_map.markers_at(addrs[2]).is_empty());
+
+        // Check that map configurations without markers can be read.
+        let json = r#"{
+            "orientation": "FlatTop",
+            "tiles": [{"row": 0, "col": 0, "tile": "5"}]
+        }"#;
+        let descr = super::map_descr_from_str(json).unwrap();
+        assert!(descr.markers().is_empty());
+    }
+
+    #[test]
+    fn terrain_round_trip() {
+        use n18game::Game;
+        use n18map::Terrain;
+        let hex = Hex::default();
+        let game = n18game::_1867::Game::new();
+        let mut map = game.create_map(&hex);
+        let addrs: Vec<n18map::HexAddress> =
+            map.hex_address_iter().take(3).copied().collect();
+        assert!(map.set_terrain(addrs[0], Terrain::Mountain));
+        assert!(map.set_terrain(addrs[1], Terrain::Water));
+
+        // Check that terrain is saved only for hexes that have terrain, and
+        // is restored when the map configuration is read.
+        let descr = n18map::descr::Descr::from(&map);
+        let text = super::map_descr_to_string(&descr, false).unwrap();
+        assert_eq!(text.matches("terrain").count(), 2);
+        let descr_out = super::map_descr_from_str(&text).unwrap();
+        assert_eq!(descr_out.terrain(), descr.terrain());
+        let mut new_map = game.create_map(&hex);
+        assert!(new_map.set_terrain(addrs[2], Terrain::Water));
+        descr_out.update_map(&mut new_map);
+        assert_eq!(new_map.terrain_at(addrs[0]), Some(Terrain::Mountain));
+        assert_eq!(new_map.terrain_at(addrs[1]), Some(Terrain::Water));
+        assert_eq!(new_map.terrain_at(addrs[2]), None);
+
+        // Check that map configurations without terrain can be read.
+        let json = r#"{
+            "orientation": "FlatTop",
+            "tiles": [{"row": 0, "col": 0, "tile": "5"}]
+        }"#;
+        let descr = super::map_descr_from_str(json).unwrap();
+        assert!(descr.terrain().is_empty());
+    }
+
+    #[test]
+    fn partial_hex_round_trip() {
+        use n18game::Game;
+        use n18map::HexHalf;
+        let hex = Hex::default();
+        let game = n18game::_1867::Game::new();
+        let mut map = game.create_map(&hex);
+        let addrs: Vec<n18map::HexAddress> =
+            map.hex_address_iter().take(3).copied().collect();
+        assert!(map.set_partial_hex(addrs[0], HexHalf::Bottom));
+        assert!(map.set_partial_hex(addrs[1], HexHalf::Right));
+
+        // Check that partial hexes are saved only for hexes that are
+        // partially present, and are restored when the map configuration is
+        // read.
+        let descr = n18map::descr::Descr::from(&map);
+        let text = super::map_descr_to_string(&descr, false).unwrap();
+        assert_eq!(text.matches("partial").count(), 2);
+        let descr_out = super::map_descr_from_str(&text).unwrap();
+        assert_eq!(descr_out.partial_hexes(), descr.partial_hexes());
+        let mut new_map = game.create_map(&hex);
+        assert!(new_map.set_partial_hex(addrs[2], HexHalf::Top));
+        descr_out.update_map(&mut new_map);
+        assert_eq!(new_map.partial_hex(addrs[0]), Some(HexHalf::Bottom));
+        assert_eq!(new_map.partial_hex(addrs[1]), Some(HexHalf::Right));
+        assert_eq!(new_map.partial_hex(addrs[2]), None);
+        assert_eq!(new_map.drawing_size(&hex), map.drawing_size(&hex));
+
+        // Check that map configurations without partial hexes can be read.
+        let json = r#"{
+            "orientation": "FlatTop",
+            "tiles": [{"row": 0, "col": 0, "tile": "5"}]
+        }"#;
+        let descr = super::map_descr_from_str(json).unwrap();
+        assert!(descr.partial_hexes().is_empty());
+    }
+
+    #[test]
+    fn location_round_trip() {
+        use n18game::Game;
+        let hex = Hex::default();
+        let game = n18game::_1867::Game::new();
+        let mut map = game.create_map(&hex);
+        let addrs: Vec<n18map::HexAddress> =
+            map.hex_address_iter().take(3).copied().collect();
+        assert!(map.set_location(addrs[0], "West"));
+        assert!(map.set_location(addrs[1], "West"));
+
+        // Check that locations are saved only for the grouped hexes, and are
+        // restored when the map configuration is read.
+        let descr = n18map::descr::Descr::from(&map);
+        let text = super::map_descr_to_string(&descr, false).unwrap();
+        assert_eq!(text.matches("location").count(), 2);
+        let descr_out = super::map_descr_from_str(&text).unwrap();
+        assert_eq!(descr_out.locations(), descr.locations());
+        let mut new_map = game.create_map(&hex);
+        assert!(new_map.set_location(addrs[2], "East"));
+        descr_out.update_map(&mut new_map);
+        assert_eq!(new_map.location_hexes("West"), addrs[0..2].to_vec());
+        assert_eq!(new_map.location(addrs[2]), None);
+    }
+
+    #[test]
+    fn arrow_round_trip() {
+        use n18hex::HexFace::*;
+        let hex = Hex::default();
+        let tile = n18tile::Tile::new(
+            n18hex::HexColour::Red,
+            "Offboard",
+            vec![],
+            vec![],
+            &hex,
+        )
+        .arrow(Top)
+        .arrow(n18tile::Arrow::new(LowerLeft).to_centre(0.1));
+        let text = super::tile_to_string(&tile, false).unwrap();
+        assert_eq!(text.matches("to_centre").count(), 1);
+        let tile_out = super::tile_from_str(&text).unwrap();
+        assert_eq!(tile_out.arrows(), tile.arrows());
+        assert_eq!(tile_out, tile);
+
+        // Check that arrows can be defined without an offset.
+        let json = r#"{
+            "name": "Arrows",
+            "colour": "Red",
+            "arrows": [{"face": "Bottom"}, {"face": "Top", "to_centre": 0.2}]
+        }"#;
+        let tile = super::tile_from_str(json).unwrap();
+        assert_eq!(
+            tile.arrows(),
+            &[
+                n18tile::Arrow::new(Bottom),
+                n18tile::Arrow::new(Top).to_centre(0.2)
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_rotation() {
+        let json = r#"{
+            "orientation": "FlatTop",
+            "tiles": [{"row": 0, "col": 0, "tile": "5", "rotation": "Cw7"}]
+        }"#;
+        let descr: Descr = serde_json::from_str(json).unwrap();
+        let result: Result<n18map::descr::Descr, Error> = (&descr).try_into();
+        match result {
+            Err(Error::InvalidRotation { addr, rotation }) => {
+                assert_eq!(addr, (0, 0).into());
+                assert_eq!(rotation, "Cw7");
+            }
+            _ => panic!("Expected an invalid rotation error"),
+        }
+    }
+
+    #[test]
+    fn unknown_tile_and_token() {
+        use n18game::Game;
+        let hex = Hex::default();
+        let game = n18game::_1867::Game::new();
+        let map = game.create_map(&hex);
+        let (row, col): (isize, isize) =
+            (*map.hex_address_iter().next().unwrap()).into();
+        let orientation = map.orientation();
+        let tile_descr = |tile: &str, tokens: Vec<(usize, String)>| {
+            let td = n18map::TileDescr {
+                row,
+                col,
+                tile: tile.to_string(),
+                rotation: n18hex::RotateCW::Zero,
+                tokens,
+            };
+            n18map::descr::Descr::from((orientation, vec![td]))
+        };
+
+        let descr = tile_descr("5", vec![]);
+        assert!(super::check_map_descr(&descr, &map).is_ok());
+
+        let descr = tile_descr("No such tile", vec![]);
+        let result = super::check_map_descr(&descr, &map);
+        assert!(matches!(result, Err(Error::UnknownTile { .. })));
+
+        let descr = tile_descr("5", vec![(0, "No such token".to_string())]);
+        let result = super::check_map_descr(&descr, &map);
+        assert!(matches!(result, Err(Error::UnknownToken { .. })));
+
+        let token = map.token_names()[0].clone();
+        let descr = tile_descr("5", vec![(1, token)]);
+        let result = super::check_map_descr(&descr, &map);
+        assert!(matches!(result, Err(Error::InvalidTokenSpace { .. })));
+    }
+
+    #[test]
+    fn report_all_map_problems() {
+        use n18game::Game;
+        let hex = Hex::default();
+        let game = n18game::_1867::Game::new();
+        let map = game.create_map(&hex);
+        let mut addrs = map.hex_address_iter();
+        let addr_a = *addrs.next().unwrap();
+        let addr_b = *addrs.next().unwrap();
+        let tile_descr =
+            |addr: n18map::HexAddress, tile: &str, token: &str| {
+                let (row, col): (isize, isize) = addr.into();
+                n18map::TileDescr {
+                    row,
+                    col,
+                    tile: tile.to_string(),
+                    rotation: n18hex::RotateCW::Zero,
+                    tokens: vec![(0, token.to_string())],
+                }
+            };
+        let descr = n18map::descr::Descr::from((
+            map.orientation(),
+            vec![
+                tile_descr(addr_a, "X9", "ZZ"),
+                tile_descr(addr_b, "57", "ZZ"),
+            ],
+        ));
+
+        let problems = super::validate_map_descr(&descr, &map);
+        assert_eq!(problems.len(), 3);
+        assert!(matches!(
+            &problems[0],
+            Error::UnknownTile { addr, tile } if *addr == addr_a && tile == "X9"
+        ));
+        assert!(matches!(
+            &problems[1],
+            Error::UnknownToken { addr, token } if *addr == addr_a && token == "ZZ"
+        ));
+        assert!(matches!(
+            &problems[2],
+            Error::UnknownToken { addr, token } if *addr == addr_b && token == "ZZ"
+        ));
+
+        let result = super::check_map_descr(&descr, &map);
+        match result {
+            Err(Error::Invalid { problems, .. }) => {
+                assert_eq!(problems.len(), 3)
+            }
+            _ => panic!("Expected an invalid map error"),
+        }
+        let message = super::check_map_descr(&descr, &map)
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("Unknown tile 'X9' at hex"));
+    }
+
+    #[test]
+    fn report_all_invalid_rotations() {
+        let json = r#"{
+            "orientation": "FlatTop",
+            "tiles": [
+                {"row": 0, "col": 0, "tile": "5", "rotation": "Cw7"},
+                {"row": 0, "col": 1, "tile": "5", "rotation": "Cw8"}
+            ]
+        }"#;
+        let descr: Descr = serde_json::from_str(json).unwrap();
+        let result: Result<n18map::descr::Descr, Error> = (&descr).try_into();
+        match result {
+            Err(Error::Invalid { problems, .. }) => {
+                assert_eq!(problems.len(), 2);
+                assert!(problems
+                    .iter()
+                    .all(|p| matches!(p, Error::InvalidRotation { .. })));
+            }
+            _ => panic!("Expected an invalid map error"),
+        }
+    }
+
+    #[cfg(feature = "cairo")]
+    #[test]
+    fn token_emblems() {
+        let filename = output_path("test-token_emblems.json");
+        let image_name = "test-token_emblems.png";
+        let image_path = output_path(image_name);
+        let surface =
+            cairo::ImageSurface::create(cairo::Format::ARgb32, 20, 20)
+                .unwrap();
+        let mut file = std::fs::File::create(&image_path).unwrap();
+        surface.write_to_png(&mut file).unwrap();
+
+        let emblem = n18token::Emblem::named("test-token_emblems");
+        assert!(!emblem.has_image());
+        let style = n18token::TokenStyle::Emblem {
+            bg: n18hex::Colour::WHITE,
+            emblem,
+            text: n18hex::Colour::BLACK,
+        };
+        let tokens_in = n18token::Tokens::new(vec![(
+            "EMB".to_string(),
+            n18token::Token::new(style),
+        )]);
+        let mut emblems = BTreeMap::new();
+        emblems.insert(emblem.name(), image_name.to_string());
+        let write_res = write_tokens(&filename, &tokens_in, &emblems, true);
+        assert!(write_res.is_ok());
+        let tokens_out = read_tokens(&filename).unwrap();
+        assert_eq!(tokens_in, tokens_out);
+        assert!(emblem.has_image());
+
+        // Check that missing images are reported.
+        emblems.insert(emblem.name(), "missing.png".to_string());
+        let write_res = write_tokens(&filename, &tokens_in, &emblems, true);
+        assert!(write_res.is_ok());
+        let result = read_tokens(&filename);
+        assert!(matches!(result, Err(Error::Io { .. })));
+    }
+
+    #[test]
+    fn extend_catalogue() {
+        use n18catalogue::{Availability, Builder, Kind};
+        let filename = output_path("test-extend_catalogue.json");
+        let hex = Hex::default();
+        let extra: Vec<n18tile::Tile> = [Kind::_57, Kind::_9]
+            .iter()
+            .map(|kind| kind.build(&hex))
+            .collect();
+        let write_res = super::write_tiles(&filename, &extra, true);
+        assert!(write_res.is_ok(), "Could not write {}", filename.display());
+
+        // Check that the tiles are added to the end of the catalogue.
+        let mut catalogue =
+            Builder::with_unlimited_tiles([Kind::_3, Kind::_4])
+                .unwrap()
+                .build();
+        let avail = Availability::Limited(2);
+        let result =
+            super::extend_catalogue(&mut catalogue, &filename, avail);
+        assert_eq!(result.unwrap(), vec![2, 3]);
+        assert_eq!(catalogue.index_of("57"), Some(2));
+        assert_eq!(catalogue.availability("9"), Some(&avail));
+
+        // Check that adding the same tiles again is rejected, and that the
+        // catalogue is not modified.
+        let before = catalogue.clone();
+        let result =
+            super::extend_catalogue(&mut catalogue, &filename, avail);
+        match result {
+            Err(Error::DuplicateTiles { names, .. }) => {
+                assert_eq!(names, vec!["57".to_string(), "9".to_string()])
+            }
+            _ => panic!("Expected a duplicate tiles error"),
+        }
+        assert_eq!(catalogue, before);
+    }
+
+    #[test]
+    fn game_state_versions() {
+        use n18game::Game;
+        let hex = Hex::default();
+        let game = n18game::_1867::Game::new();
+        let map = game.create_map(&hex);
+        let filename = output_path("test-game_state_versions.json");
+
+        // Save the game state and check that it records the schema version.
+        let write_res =
+            super::write_game_state(&filename, game.save(&map), true);
+        assert!(write_res.is_ok(), "Could not write {}", filename.display());
+        let text = std::fs::read_to_string(&filename).unwrap();
+        let mut value: serde_json::Value =
+            serde_json::from_str(&text).unwrap();
+        assert_eq!(value["version"], serde_json::json!(SCHEMA_VERSION));
+        assert!(super::read_game_state(&filename).is_ok());
+
+        // Check that game states without a version number can be loaded.
+        value.as_object_mut().unwrap().remove("version");
+        value["map"].as_object_mut().unwrap().remove("version");
+        std::fs::write(&filename, value.to_string()).unwrap();
+        assert!(super::read_game_state(&filename).is_ok());
+
+        // Check that game states with a newer version number are rejected.
+        value["version"] = serde_json::json!(SCHEMA_VERSION + 1);
+        std::fs::write(&filename, value.to_string()).unwrap();
+        match super::read_game_state(&filename) {
+            Err(Error::UnsupportedVersion {
+                found, supported, ..
+            }) => {
+                assert_eq!(found, SCHEMA_VERSION + 1);
+                assert_eq!(supported, SCHEMA_VERSION);
+            }
+            _ => panic!("Expected an unsupported version error"),
+        }
+    }
+
+    #[test]
+    fn load_unversioned_offboard_tiles() {
+        use n18game::Game;
+        let hex = Hex::default();
+        let mut game = n18game::_1830::Game::new();
+        let coords = game.coordinate_system();
+        let hex_json = |name: &str, tile: &str| {
+            let (row, col): (isize, isize) =
+                coords.parse(name).unwrap().into();
+            format!(
+                r#"{{"row": {}, "col": {}, "tile": "{}"}}"#,
+                row, col, tile
+            )
+        };
+        // A game state saved before schema versions were introduced, when
+        // each off-board tile was replaced in each game phase.
+        let text = format!(
+            r#"{{
+                "game": "{}",
+                "phase": "4",
+                "map": {{
+                    "orientation": "FlatTop",
+                    "tiles": [{}, {}, {}]
+                }}
+            }}"#,
+            game.name(),
+            hex_json("B6", "Chicago_Gn"),
+            hex_json("K1", "Canadian West_Gn"),
+            hex_json("F4", "57"),
+        );
+        let state = super::game_state_from_str(&text).unwrap();
+        let map = game.create_map(&hex);
+        assert!(super::check_map_descr(&state.map, &map).is_ok());
+        let map = game.load(&hex, state).unwrap();
+        let tile_at = |name: &str| {
+            let addr = coords.parse(name).unwrap();
+            map.tile_at(addr).map(|tile| tile.name.as_str())
+        };
+        assert_eq!(tile_at("B6"), Some("Chicago"));
+        assert_eq!(tile_at("K1"), Some("Canadian West"));
+        assert_eq!(tile_at("F4"), Some("57"));
+    }
+
+    #[test]
+    fn game_state_variant() {
+        use n18game::{Game, STANDARD_VARIANT};
+        let hex = Hex::default();
+        let mut game = n18game::_1867::Game::new();
+        assert_eq!(game.variants(), &[STANDARD_VARIANT, "Short"]);
+        let standard_map = game.create_map(&hex);
+        assert!(game.set_variant("Short"));
+        let map = game.create_map(&hex);
+        assert!(
+            map.hex_address_iter().count()
+                < standard_map.hex_address_iter().count()
+        );
+        let filename = output_path("test-game_state_variant.json");
+
+        // Check that the map variant is saved and restored.
+        let write_res =
+            super::write_game_state(&filename, game.save(&map), true);
+        assert!(write_res.is_ok(), "Could not write {}", filename.display());
+        let state = super::read_game_state(&filename).unwrap();
+        assert_eq!(state.variant, "Short");
+        let mut game = n18game::_1867::Game::new();
+        let loaded = game.load(&hex, state).unwrap();
+        assert_eq!(game.variant(), "Short");
+        assert!(loaded.hex_address_iter().eq(map.hex_address_iter()));
+
+        // Check that game states without a map variant use the standard map.
+        let text = std::fs::read_to_string(&filename).unwrap();
+        let mut value: serde_json::Value =
+            serde_json::from_str(&text).unwrap();
+        value.as_object_mut().unwrap().remove("variant");
+        std::fs::write(&filename, value.to_string()).unwrap();
+        let state = super::read_game_state(&filename).unwrap();
+        assert_eq!(state.variant, STANDARD_VARIANT);
+    }
+
+    #[test]
+    fn game_state_journal() {
+        use n18game::{Game, Journal};
+        let hex = Hex::default();
+        let game = n18game::_1867::Game::new();
+        let map = game.create_map(&hex);
+        let filename = output_path("test-game_state_journal.json");
+
+        // Check that the journal is saved and loaded.
+        let mut journal = Journal::new();
+        journal.record("Placed tile 57 at D10 (rotation 1)");
+        journal.record("Changed phase to 3");
+        let state_in = game.save(&map).with_journal(journal.clone());
+        let write_res = super::write_game_state(&filename, state_in, true);
+        assert!(write_res.is_ok(), "Could not write {}", filename.display());
+        let state_out = super::read_game_state(&filename).unwrap();
+        assert_eq!(state_out.journal, journal);
+
+        // Check that game states without a journal can be loaded.
+        let text = std::fs::read_to_string(&filename).unwrap();
+        let mut value: serde_json::Value =
+            serde_json::from_str(&text).unwrap();
+        value.as_object_mut().unwrap().remove("journal");
+        std::fs::write(&filename, value.to_string()).unwrap();
+        let state_out = super::read_game_state(&filename).unwrap();
+        assert!(state_out.journal.is_empty());
+    }
+
+    #[test]
+    fn game_state_seed() {
+        use n18game::Game;
+        let hex = Hex::default();
+        let mut game = n18game::_1867::Game::new();
+        let map = game.create_map(&hex);
+
+        // Check that the seed is omitted for games with a standard setup.
+        let state_in = game.save(&map);
+        assert_eq!(state_in.seed, None);
+        let text = super::game_state_to_string(state_in, false).unwrap();
+        assert!(!text.contains("seed"));
+
+        // Check that the seed is saved and loaded.
+        let mut state_in = game.save(&map);
+        state_in.seed = Some(1867);
+        let text = super::game_state_to_string(state_in, false).unwrap();
+        let state_out = super::game_state_from_str(&text).unwrap();
+        assert_eq!(state_out.seed, Some(1867));
+
+        // This game does not support a random setup, so it cannot load a
+        // game state that has a seed.
+        assert!(game.load(&hex, state_out).is_none());
+    }
+
+    #[test]
+    fn game_state_title() {
+        use n18game::Game;
+        let hex = Hex::default();
+        let game = n18game::_1889::Game::new();
+        let map = game.create_map(&hex);
+
+        // Check that the title is omitted for games without a custom title.
+        let state_in = game.save(&map);
+        assert_eq!(state_in.title, None);
+        let text = super::game_state_to_string(state_in, false).unwrap();
+        assert!(!text.contains("title"));
+
+        // Check that the title is saved and loaded.
+        let title = Some("Tuesday club game".to_string());
+        let state_in = game.save(&map).with_title(title.clone());
+        let text = super::game_state_to_string(state_in, false).unwrap();
+        let state_out = super::game_state_from_str(&text).unwrap();
+        assert_eq!(state_out.title, title);
+    }
+
+    #[test]
+    fn game_state_trains() {
+        use n18game::bank::TrainTransfer;
+        use n18game::Game;
+        let hex = Hex::default();
+        let game = n18game::_1889::Game::new();
+        let map = game.create_map(&hex);
+        let filename = output_path("test-game_state_trains.json");
+
+        // Check that the train supply is saved and loaded.
+        let mut supply = game.train_supply();
+        let buy = TrainTransfer::FromBank("2".to_string());
+        let discard = TrainTransfer::ToPool("2".to_string());
+        supply.apply(&buy, 0).unwrap();
+        supply.apply(&buy, 0).unwrap();
+        supply.apply(&discard, 0).unwrap();
+        let state_in = game.save(&map).with_train_supply(supply.clone());
+        let write_res = super::write_game_state(&filename, state_in, true);
+        assert!(write_res.is_ok(), "Could not write {}", filename.display());
+        let state_out = super::read_game_state(&filename).unwrap();
+        let mut restored = game.train_supply();
+        restored.restore_counts(&state_out.trains);
+        assert_eq!(restored, supply);
+        assert_eq!(restored.bank_count("2"), Some(Some(4)));
+        assert_eq!(restored.pool_count("2"), Some(1));
+        assert_eq!(restored.bank_count("D"), Some(None));
+
+        // Check that game states without a train supply can be loaded.
+        let text = std::fs::read_to_string(&filename).unwrap();
+        let mut value: serde_json::Value =
+            serde_json::from_str(&text).unwrap();
+        value.as_object_mut().unwrap().remove("trains");
+        std::fs::write(&filename, value.to_string()).unwrap();
+        let state_out = super::read_game_state(&filename).unwrap();
+        assert!(state_out.trains.is_empty());
+    }
+
+    #[test]
+    fn game_state_privates() {
+        use n18game::private::PrivateHoldings;
+        use n18game::Game;
+        let hex = Hex::default();
+        let game = n18game::_1830::Game::new();
+        let map = game.create_map(&hex);
+
+        // Check that private companies are omitted if none are owned.
+        let state_in = game.save(&map);
+        assert!(state_in.privates.is_empty());
+        let text = super::game_state_to_string(state_in, false).unwrap();
+        assert!(!text.contains("privates"));
+
+        // Check that the owners and closed private companies are saved and
+        // loaded.
+        let mut holdings = PrivateHoldings::default();
+        holdings.set_owner("D&H", "PRR");
+        holdings.set_owner("C&SL", "NYC");
+        holdings.close("C&SL");
+        holdings.close("B&O");
+        let state_in = game.save(&map).with_privates(holdings.clone());
+        let text = super::game_state_to_string(state_in, false).unwrap();
+        let state_out = super::game_state_from_str(&text).unwrap();
+        assert_eq!(state_out.privates, holdings);
+        assert_eq!(state_out.privates.owner("D&H"), Some("PRR"));
+        assert_eq!(state_out.privates.owner("C&SL"), None);
+        assert!(state_out.privates.is_closed("B&O"));
+    }
+
+    #[test]
+    fn game_state_treasuries() {
+        use n18game::treasury::Treasuries;
+        use n18game::Game;
+        let hex = Hex::default();
+        let game = n18game::_1830::Game::new();
+        let map = game.create_map(&hex);
+
+        // Check that treasuries are omitted if none are tracked.
+        let state_in = game.save(&map);
+        assert!(state_in.treasuries.is_empty());
+        let text = super::game_state_to_string(state_in, false).unwrap();
+        assert!(!text.contains("treasuries"));
+
+        // Check that the tracked treasuries are saved and loaded.
+        let mut treasuries = Treasuries::default();
+        treasuries.set_cash("PRR", 420);
+        treasuries.set_cash("NYC", 0);
+        let state_in = game.save(&map).with_treasuries(treasuries.clone());
+        let text = super::game_state_to_string(state_in, false).unwrap();
+        let state_out = super::game_state_from_str(&text).unwrap();
+        assert_eq!(state_out.treasuries, treasuries);
+        assert_eq!(state_out.treasuries.cash("NYC"), Some(0));
+        assert_eq!(state_out.treasuries.cash("B&O"), None);
+    }
 }
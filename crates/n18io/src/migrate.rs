@@ -0,0 +1,293 @@
+//! Upgrade saved map configurations and game states to the current schema.
+//!
+//! Each saved map configuration and game state records the version of the
+//! schema that was used to save it.
+//! Files that were saved before this version number was introduced are
+//! treated as version 0.
+//!
+//! To change the schema, increment [SCHEMA_VERSION] and add a migration
+//! function to the end of [DESCR_MIGRATIONS] and/or [GAME_STATE_MIGRATIONS]
+//! that upgrades the previous version to the new version.
+
+use serde_json::{Map, Value};
+
+/// The current version of the schema for map configurations and game
+/// states.
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// A function that upgrades a JSON object by one schema version.
+type Migration = fn(&mut Map<String, Value>);
+
+/// The migrations for map configurations, where the `n`th function upgrades
+/// version `n` to version `n + 1`.
+const DESCR_MIGRATIONS: [Migration; SCHEMA_VERSION as usize] =
+    [no_changes, no_changes, descr_v3];
+
+/// The migrations for game states, where the `n`th function upgrades version
+/// `n` to version `n + 1`.
+///
+/// Note that the map configuration in each game state is upgraded separately.
+const GAME_STATE_MIGRATIONS: [Migration; SCHEMA_VERSION as usize] =
+    [no_changes, game_state_v2, no_changes];
+
+/// The ways in which a migration can fail.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum MigrationError {
+    /// The value is not a JSON object, or the version is not an integer.
+    Invalid(&'static str),
+    /// The value was saved with a newer version of the schema.
+    Unsupported(u32),
+}
+
+/// A migration that only requires updating the version number.
+fn no_changes(_value: &mut Map<String, Value>) {}
+
+/// Adds the game state fields that were introduced in version 2: the map
+/// variant, journal, train supply, random seed, title, private companies,
+/// and company treasuries.
+fn game_state_v2(value: &mut Map<String, Value>) {
+    let defaults = [
+        ("variant", Value::from(n18game::STANDARD_VARIANT)),
+        ("journal", Value::Array(vec![])),
+        ("trains", Value::Array(vec![])),
+        ("seed", Value::Null),
+        ("title", Value::Null),
+        ("privates", Value::Array(vec![])),
+        ("treasuries", Value::Object(Map::new())),
+    ];
+    for (field, default) in defaults {
+        value.entry(field).or_insert(default);
+    }
+}
+
+/// The off-board tiles that, prior to version 3, were replaced by a
+/// separate tile in each game phase.
+/// Each entry defines the separator and phase suffixes that were appended to
+/// the tile names (e.g., `"Chicago_Gn"` and `"Central Asia Gn"`) and the
+/// names of the tiles.
+const PHASE_OFFBOARD_TILES: [(&str, &[&str], &[&str]); 3] = [
+    // The 1830 off-board tiles.
+    (
+        "_",
+        &["Yw", "Gn", "Bn"],
+        &[
+            "Chicago",
+            "Gulf of Mexico",
+            "Canadian West",
+            "Deep South",
+            "Maritime Provinces",
+        ],
+    ),
+    // The 1861 off-board tiles.
+    (
+        " ",
+        &["Yw", "Gn", "Bn", "Gy"],
+        &["Central Asia", "Romania", "Caucasus", "Poland"],
+    ),
+    // The 1889 off-board tiles.
+    (
+        "_",
+        &["Yw", "Bn"],
+        &["Imabari", "Sakaide & Okoyama", "Naruto & Awaji"],
+    ),
+];
+
+/// Returns the current name of an off-board tile whose name included a game
+/// phase prior to version 3, or `None` if the tile was not renamed.
+fn offboard_tile_name(name: &str) -> Option<&'static str> {
+    PHASE_OFFBOARD_TILES
+        .iter()
+        .find_map(|(separator, suffixes, tiles)| {
+            tiles.iter().copied().find(|tile| {
+                name.strip_prefix(tile)
+                    .and_then(|rest| rest.strip_prefix(separator))
+                    .is_some_and(|suffix| suffixes.contains(&suffix))
+            })
+        })
+}
+
+/// Renames the off-board tiles that were replaced by a separate tile in
+/// each game phase prior to version 3, and which are now a single tile that
+/// shows the revenue for each phase.
+fn descr_v3(value: &mut Map<String, Value>) {
+    let Some(Value::Array(hexes)) = value.get_mut("tiles") else {
+        return;
+    };
+    for hex in hexes {
+        let Some(Value::String(tile)) = hex.get_mut("tile") else {
+            continue;
+        };
+        if let Some(name) = offboard_tile_name(tile) {
+            *tile = name.to_string()
+        }
+    }
+}
+
+/// Returns the schema version of a JSON object.
+pub(crate) fn version_of(
+    value: &Map<String, Value>,
+) -> Result<u32, MigrationError> {
+    match value.get("version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or(MigrationError::Invalid("invalid schema version")),
+    }
+}
+
+/// Upgrades a JSON object to the current schema version by applying each of
+/// the necessary migrations in turn.
+fn migrate(
+    value: &mut Value,
+    migrations: &[Migration],
+) -> Result<(), MigrationError> {
+    let obj = value
+        .as_object_mut()
+        .ok_or(MigrationError::Invalid("expected a JSON object"))?;
+    let version = version_of(obj)?;
+    if version > SCHEMA_VERSION {
+        return Err(MigrationError::Unsupported(version));
+    }
+    for migration in &migrations[version as usize..] {
+        migration(obj);
+    }
+    obj.insert("version".to_string(), SCHEMA_VERSION.into());
+    Ok(())
+}
+
+/// Upgrades a map configuration to the current schema version.
+pub(crate) fn migrate_descr(value: &mut Value) -> Result<(), MigrationError> {
+    migrate(value, &DESCR_MIGRATIONS)
+}
+
+/// Upgrades a game state, including its map configuration, to the current
+/// schema version.
+pub(crate) fn migrate_game_state(
+    value: &mut Value,
+) -> Result<(), MigrationError> {
+    // NOTE: the map configuration has the same version as the game state.
+    let version = value.as_object().map(version_of).transpose()?;
+    if let Some(map) = value.get_mut("map").and_then(|m| m.as_object_mut()) {
+        if let Some(version) = version {
+            if !map.contains_key("version") {
+                map.insert("version".to_string(), version.into());
+            }
+        }
+    }
+    migrate(value, &GAME_STATE_MIGRATIONS)?;
+    if let Some(map) = value.get_mut("map") {
+        migrate_descr(map)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unversioned_game_state() {
+        let mut value = json!({
+            "game": "1867",
+            "phase": "2",
+            "map": {"orientation": "FlatTop", "tiles": []}
+        });
+        assert!(migrate_game_state(&mut value).is_ok());
+        assert_eq!(value["version"], json!(SCHEMA_VERSION));
+        assert_eq!(value["map"]["version"], json!(SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn version_1_game_state() {
+        let mut value = json!({
+            "version": 1,
+            "game": "1867",
+            "phase": "2",
+            "map": {"version": 1, "orientation": "FlatTop", "tiles": []}
+        });
+        assert!(migrate_game_state(&mut value).is_ok());
+        assert_eq!(value["version"], json!(SCHEMA_VERSION));
+        assert_eq!(value["variant"], json!(n18game::STANDARD_VARIANT));
+        assert_eq!(value["journal"], json!([]));
+        assert_eq!(value["trains"], json!([]));
+        assert_eq!(value["seed"], Value::Null);
+        assert_eq!(value["title"], Value::Null);
+        assert_eq!(value["privates"], json!([]));
+        assert_eq!(value["treasuries"], json!({}));
+    }
+
+    #[test]
+    fn version_1_fields_are_kept() {
+        let mut value = json!({
+            "version": 1,
+            "game": "1867",
+            "variant": "Short",
+            "phase": "2",
+            "map": {"version": 1, "orientation": "FlatTop", "tiles": []}
+        });
+        assert!(migrate_game_state(&mut value).is_ok());
+        assert_eq!(value["variant"], json!("Short"));
+    }
+
+    #[test]
+    fn version_2_offboard_tiles() {
+        let mut value = json!({
+            "version": 2,
+            "orientation": "FlatTop",
+            "tiles": [
+                {"row": 0, "col": 0, "tile": "Chicago_Gn"},
+                {"row": 0, "col": 1, "tile": "Central Asia Gy"},
+                {"row": 0, "col": 2, "tile": "Naruto & Awaji_Bn"},
+                {"row": 0, "col": 3, "tile": "Caucasus2"},
+                {"row": 0, "col": 4, "tile": "Maritime Provinces Yw"},
+                {"row": 0, "col": 5}
+            ]
+        });
+        assert!(migrate_descr(&mut value).is_ok());
+        let tiles: Vec<&Value> = value["tiles"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hex| &hex["tile"])
+            .collect();
+        // NOTE: the 1867 off-board tiles were not renamed.
+        let expected = [
+            json!("Chicago"),
+            json!("Central Asia"),
+            json!("Naruto & Awaji"),
+            json!("Caucasus2"),
+            json!("Maritime Provinces Yw"),
+            Value::Null,
+        ];
+        assert_eq!(tiles, expected.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn newer_game_state() {
+        let version = SCHEMA_VERSION + 1;
+        let mut value = json!({
+            "version": version,
+            "game": "1867",
+            "phase": "2",
+            "map": {"orientation": "FlatTop", "tiles": []}
+        });
+        let result = migrate_game_state(&mut value);
+        assert_eq!(result, Err(MigrationError::Unsupported(version)));
+    }
+
+    #[test]
+    fn invalid_versions() {
+        let mut value = json!({"version": "1", "tiles": []});
+        assert!(matches!(
+            migrate_descr(&mut value),
+            Err(MigrationError::Invalid(_))
+        ));
+        let mut value = json!([]);
+        assert!(matches!(
+            migrate_descr(&mut value),
+            Err(MigrationError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,207 @@
+//! Serialise and deserialise game moves (see [n18game::sim::Move]).
+
+use serde::{Deserialize, Serialize};
+
+use super::{Error, TileRotation};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) enum TrainTransfer {
+    FromBank(String),
+    FromPool(String),
+    ToPool(String),
+}
+
+impl std::convert::From<&n18game::bank::TrainTransfer> for TrainTransfer {
+    fn from(src: &n18game::bank::TrainTransfer) -> Self {
+        use n18game::bank::TrainTransfer::*;
+
+        match src {
+            FromBank(name) => TrainTransfer::FromBank(name.clone()),
+            FromPool(name) => TrainTransfer::FromPool(name.clone()),
+            ToPool(name) => TrainTransfer::ToPool(name.clone()),
+        }
+    }
+}
+
+impl std::convert::From<TrainTransfer> for n18game::bank::TrainTransfer {
+    fn from(src: TrainTransfer) -> Self {
+        use n18game::bank::TrainTransfer::*;
+
+        match src {
+            TrainTransfer::FromBank(name) => FromBank(name),
+            TrainTransfer::FromPool(name) => FromPool(name),
+            TrainTransfer::ToPool(name) => ToPool(name),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) enum Move {
+    LayTile {
+        addr: (isize, isize),
+        tile: String,
+        #[serde(default, skip_serializing_if = "TileRotation::is_default")]
+        rotation: TileRotation,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        company: Option<String>,
+    },
+    RemoveTile {
+        addr: (isize, isize),
+    },
+    RotateTile {
+        addr: (isize, isize),
+        rotation: TileRotation,
+    },
+    PlaceToken {
+        addr: (isize, isize),
+        space: usize,
+        company: String,
+    },
+    RemoveToken {
+        addr: (isize, isize),
+        space: usize,
+    },
+    TransferTrain(TrainTransfer),
+    AdvancePhase,
+    SetPhase(usize),
+    BuyPrivate {
+        private: String,
+        company: String,
+    },
+    ClosePrivate(String),
+    SetCash {
+        company: String,
+        cash: usize,
+    },
+    RunRoutes {
+        company: String,
+        trains: Vec<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        bonuses: Vec<bool>,
+    },
+}
+
+impl std::convert::From<&n18game::sim::Move> for Move {
+    fn from(src: &n18game::sim::Move) -> Self {
+        use n18game::sim::Move::*;
+
+        match src {
+            LayTile {
+                addr,
+                tile,
+                rotation,
+                company,
+            } => Move::LayTile {
+                addr: (*addr).into(),
+                tile: tile.clone(),
+                rotation: rotation.into(),
+                company: company.clone(),
+            },
+            RemoveTile { addr } => Move::RemoveTile {
+                addr: (*addr).into(),
+            },
+            RotateTile { addr, rotation } => Move::RotateTile {
+                addr: (*addr).into(),
+                rotation: rotation.into(),
+            },
+            PlaceToken {
+                addr,
+                space,
+                company,
+            } => Move::PlaceToken {
+                addr: (*addr).into(),
+                space: *space,
+                company: company.clone(),
+            },
+            RemoveToken { addr, space } => Move::RemoveToken {
+                addr: (*addr).into(),
+                space: *space,
+            },
+            TransferTrain(transfer) => Move::TransferTrain(transfer.into()),
+            AdvancePhase => Move::AdvancePhase,
+            SetPhase(phase_ix) => Move::SetPhase(*phase_ix),
+            BuyPrivate { private, company } => Move::BuyPrivate {
+                private: private.clone(),
+                company: company.clone(),
+            },
+            ClosePrivate(private) => Move::ClosePrivate(private.clone()),
+            SetCash { company, cash } => Move::SetCash {
+                company: company.clone(),
+                cash: *cash,
+            },
+            RunRoutes {
+                company,
+                trains,
+                bonuses,
+            } => Move::RunRoutes {
+                company: company.clone(),
+                trains: trains.clone(),
+                bonuses: bonuses.clone(),
+            },
+        }
+    }
+}
+
+impl std::convert::TryFrom<Move> for n18game::sim::Move {
+    type Error = Error;
+
+    fn try_from(src: Move) -> Result<Self, Self::Error> {
+        use n18game::sim::Move::*;
+
+        let mv = match src {
+            Move::LayTile {
+                addr,
+                tile,
+                rotation,
+                company,
+            } => {
+                let addr = addr.into();
+                LayTile {
+                    addr,
+                    tile,
+                    rotation: rotation.rotation(addr)?,
+                    company,
+                }
+            }
+            Move::RemoveTile { addr } => RemoveTile { addr: addr.into() },
+            Move::RotateTile { addr, rotation } => {
+                let addr = addr.into();
+                RotateTile {
+                    addr,
+                    rotation: rotation.rotation(addr)?,
+                }
+            }
+            Move::PlaceToken {
+                addr,
+                space,
+                company,
+            } => PlaceToken {
+                addr: addr.into(),
+                space,
+                company,
+            },
+            Move::RemoveToken { addr, space } => RemoveToken {
+                addr: addr.into(),
+                space,
+            },
+            Move::TransferTrain(transfer) => TransferTrain(transfer.into()),
+            Move::AdvancePhase => AdvancePhase,
+            Move::SetPhase(phase_ix) => SetPhase(phase_ix),
+            Move::BuyPrivate { private, company } => {
+                BuyPrivate { private, company }
+            }
+            Move::ClosePrivate(private) => ClosePrivate(private),
+            Move::SetCash { company, cash } => SetCash { company, cash },
+            Move::RunRoutes {
+                company,
+                trains,
+                bonuses,
+            } => RunRoutes {
+                company,
+                trains,
+                bonuses,
+            },
+        };
+        Ok(mv)
+    }
+}
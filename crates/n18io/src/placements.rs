@@ -0,0 +1,22 @@
+//! Serialise the tiles that can be placed on a map hex (see
+//! [n18map::Map::legal_placements]).
+
+use serde::{Deserialize, Serialize};
+
+use super::TileRotation;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) struct TilePlacement {
+    tile: String,
+    rotations: Vec<TileRotation>,
+}
+
+impl std::convert::From<&(String, Vec<n18hex::RotateCW>)> for TilePlacement {
+    fn from(src: &(String, Vec<n18hex::RotateCW>)) -> Self {
+        let (tile, rotations) = src;
+        TilePlacement {
+            tile: tile.clone(),
+            rotations: rotations.iter().map(|rot| rot.into()).collect(),
+        }
+    }
+}
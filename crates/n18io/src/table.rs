@@ -0,0 +1,230 @@
+//! Export the tiles on a map as a table of comma-separated values (CSV) or
+//! tab-separated values (TSV), for analysis in spreadsheets and other tools.
+//!
+//! The table has one row for each map hex that contains a tile, and the
+//! following columns:
+//!
+//! - `hex`: the hex coordinates (e.g., `"C7"`);
+//! - `tile`: the tile name;
+//! - `rotation`: the number of clockwise turns (0 to 5) from the tile's
+//!   default orientation;
+//! - `colour`: the tile colour (e.g., `"Yellow"`);
+//! - `revenues`: the revenue of each city in the current revenue phase (see
+//!   [Map::describe_hex]), separated by semicolons; and
+//! - `tokens`: the name of each token placed on the tile, separated by
+//!   semicolons.
+//!
+//! Values that contain the format, double quotes, or line breaks are
+//! enclosed in double quotes, and double quotes are escaped by doubling them.
+//!
+//! ```rust
+//! # use n18game::Game;
+//! # use n18hex::{Hex, RotateCW};
+//! # use n18io::{hex_table_to_string, TableFormat};
+//! let game = n18game::new_1830();
+//! let mut map = game.create_map(&Hex::default());
+//! let addr = game.coordinate_system().parse("C7").unwrap();
+//! assert!(map.place_tile(addr, "57", RotateCW::One));
+//! let table = hex_table_to_string(&game, &map, TableFormat::Csv);
+//! let mut lines = table.lines();
+//! assert_eq!(lines.next(), Some("hex,tile,rotation,colour,revenues,tokens"));
+//! assert!(lines.any(|line| line == "C7,57,1,Yellow,20,"));
+//! ```
+
+use std::path::Path;
+
+use n18game::Game;
+use n18hex::HexColour;
+use n18map::Map;
+
+use super::Error;
+
+/// The column names of the exported table.
+const COLUMNS: [&str; 6] =
+    ["hex", "tile", "rotation", "colour", "revenues", "tokens"];
+
+/// The supported table formats, which differ in the character that
+/// separates values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableFormat {
+    /// Write comma-separated values (CSV).
+    Csv,
+    /// Write tab-separated values (TSV).
+    Tsv,
+}
+
+impl TableFormat {
+    /// Returns the filename extension associated with the table format.
+    pub fn extension(&self) -> &str {
+        match self {
+            TableFormat::Csv => "csv",
+            TableFormat::Tsv => "tsv",
+        }
+    }
+
+    /// Returns the table format associated with a filename extension, if
+    /// any.
+    /// The extension is not case-sensitive.
+    ///
+    /// ```rust
+    /// # use n18io::TableFormat;
+    /// assert_eq!(TableFormat::from_extension("TSV"), Some(TableFormat::Tsv));
+    /// assert!(TableFormat::from_extension("json").is_none());
+    /// ```
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        [TableFormat::Csv, TableFormat::Tsv]
+            .into_iter()
+            .find(|delim| ext.eq_ignore_ascii_case(delim.extension()))
+    }
+
+    fn as_char(&self) -> char {
+        match self {
+            TableFormat::Csv => ',',
+            TableFormat::Tsv => '\t',
+        }
+    }
+
+    /// Returns a single row of the table, including the line ending.
+    fn row<S: AsRef<str>>(&self, values: &[S]) -> String {
+        let delim = self.as_char();
+        let mut row = values
+            .iter()
+            .map(|value| self.quote(value.as_ref()))
+            .collect::<Vec<_>>()
+            .join(&delim.to_string());
+        row.push('\n');
+        row
+    }
+
+    /// Encloses a value in double quotes, if necessary.
+    fn quote(&self, value: &str) -> String {
+        let special = |c: char| {
+            c == self.as_char() || c == '"' || c == '\n' || c == '\r'
+        };
+        if value.contains(special) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+}
+
+/// Returns the name of a tile colour.
+fn colour_name(colour: HexColour) -> &'static str {
+    match colour {
+        HexColour::Yellow => "Yellow",
+        HexColour::Green => "Green",
+        HexColour::Brown => "Brown",
+        HexColour::Grey => "Grey",
+        HexColour::Red => "Red",
+        HexColour::Blue => "Blue",
+        HexColour::Empty => "Empty",
+    }
+}
+
+/// Writes the tiles on a map to disk, in the format described in the
+/// [module documentation](self).
+pub fn write_hex_table<G: Game + ?Sized, P: AsRef<Path>>(
+    path: P,
+    game: &G,
+    map: &Map,
+    format: TableFormat,
+) -> Result<(), Error> {
+    let path = path.as_ref();
+    let table = hex_table_to_string(game, map, format);
+    std::fs::write(path, table).map_err(|e| Error::io(path, e))
+}
+
+/// Writes the tiles on a map as text, in the format described in the
+/// [module documentation](self).
+pub fn hex_table_to_string<G: Game + ?Sized>(
+    game: &G,
+    map: &Map,
+    format: TableFormat,
+) -> String {
+    let coords = game.coordinate_system();
+    let mut table = format.row(&COLUMNS);
+    for &addr in map.hex_address_iter() {
+        let Some(descr) = map.describe_hex(addr) else {
+            continue;
+        };
+        let (Some(tile), Some(rotation)) = (descr.tile, descr.rotation)
+        else {
+            continue;
+        };
+        let revenues: Vec<String> = descr
+            .cities
+            .iter()
+            .map(|city| city.revenue.to_string())
+            .collect();
+        let tokens: Vec<String> = descr
+            .cities
+            .into_iter()
+            .flat_map(|city| city.tokens)
+            .flatten()
+            .collect();
+        let hex = coords.format(&addr).unwrap_or_else(|| addr.to_string());
+        table.push_str(&format.row(&[
+            hex,
+            tile,
+            rotation.count_turns().to_string(),
+            colour_name(descr.colour).to_string(),
+            revenues.join(";"),
+            tokens.join(";"),
+        ]));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hex_table_to_string, TableFormat};
+    use n18game::Game;
+    use n18hex::{Hex, RotateCW};
+
+    #[test]
+    fn export_1830_table() {
+        let game = n18game::new_1830();
+        let hex = Hex::default();
+        let mut map = game.create_map(&hex);
+        let coords = game.coordinate_system();
+
+        // Lay a yellow tile on an empty hex, and place two tokens on a
+        // pre-printed tile with two cities.
+        let b4 = coords.parse("B4").unwrap();
+        assert!(map.place_tile(b4, "57", RotateCW::Two));
+        let e5 = coords.parse("E5").unwrap();
+        let prr = *game.try_token("PRR").unwrap();
+        let nyc = *game.try_token("NYC").unwrap();
+        let spaces = map.tile_at(e5).unwrap().token_spaces();
+        let hex_state = map.hex_state_mut(e5).unwrap();
+        hex_state.set_token_at(&spaces[0], prr);
+        hex_state.set_token_at(&spaces[1], nyc);
+
+        let csv = hex_table_to_string(&game, &map, TableFormat::Csv);
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], "hex,tile,rotation,colour,revenues,tokens");
+        assert!(rows.contains(&"B4,57,2,Yellow,20,"));
+        let e5_row = rows.iter().find(|row| row.starts_with("E5,")).unwrap();
+        assert!(e5_row.ends_with(",PRR;NYC"), "{}", e5_row);
+
+        // Check that each row has the same number of values, and that
+        // empty hexes are not listed.
+        let tiles = map
+            .hex_address_iter()
+            .filter(|a| map.tile_at(**a).is_some());
+        assert_eq!(rows.len(), 1 + tiles.count());
+        let tsv = hex_table_to_string(&game, &map, TableFormat::Tsv);
+        for row in tsv.lines() {
+            assert_eq!(row.split('\t').count(), 6);
+        }
+    }
+
+    #[test]
+    fn quote_values() {
+        let row = TableFormat::Csv.row(&["a,b", "say \"hi\"", "plain"]);
+        assert_eq!(row, "\"a,b\",\"say \"\"hi\"\"\",plain\n");
+        let row = TableFormat::Tsv.row(&["a,b", "c\td"]);
+        assert_eq!(row, "a,b\t\"c\td\"\n");
+    }
+}
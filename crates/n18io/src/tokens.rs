@@ -0,0 +1,329 @@
+//! Import lists of company token placements, to quickly reconstruct a game
+//! that is in progress on a physical board.
+//!
+//! Each token placement identifies a company (e.g., `"PRR"`), a map hex
+//! (e.g., `"E5"`), and the index of a city on the tile in that hex, starting
+//! from zero.
+//! Lists can be written as a JSON array of objects with `"company"`,
+//! `"hex"`, and `"city"` fields (the city defaults to zero), or as
+//! comma-separated values (CSV) with one placement per line.
+//! A CSV list may begin with the header line `company,hex,city`, and blank
+//! lines and lines that begin with `#` are ignored.
+//!
+//! ```rust
+//! # use n18game::Game;
+//! # use n18hex::Hex;
+//! # use n18io::{apply_token_placements, token_placements_from_str};
+//! # use n18io::TokenListFormat;
+//! let game = n18game::new_1830();
+//! let mut map = game.create_map(&Hex::default());
+//! let text = "company,hex,city\nPRR,E5,0\nNYC,E5,1\n";
+//! let placements =
+//!     token_placements_from_str(text, TokenListFormat::Csv).unwrap();
+//! let coords = game.coordinate_system();
+//! let changes =
+//!     apply_token_placements(&mut map, &coords, &placements).unwrap();
+//! assert_eq!(changes.len(), 2);
+//! ```
+
+use serde::Deserialize;
+use std::path::Path;
+
+use n18map::{Coordinates, Map, TokenChange};
+
+use super::{from_json_str, read_json, Error};
+
+/// A company token that should be placed in a city on the map.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct TokenPlacement {
+    /// The company name (e.g., `"PRR"`).
+    pub company: String,
+    /// The hex coordinates (e.g., `"E5"`).
+    pub hex: String,
+    /// The index of the city on the tile.
+    #[serde(default)]
+    pub city: usize,
+}
+
+/// The supported token list formats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenListFormat {
+    /// A JSON array of token placements.
+    Json,
+    /// Comma-separated values (CSV), with one token placement per line.
+    Csv,
+}
+
+impl TokenListFormat {
+    /// Returns the token list format associated with a file, which is CSV
+    /// if the file has the extension `.csv` (which is not case-sensitive),
+    /// and JSON otherwise.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        let is_csv = path
+            .as_ref()
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        if is_csv {
+            TokenListFormat::Csv
+        } else {
+            TokenListFormat::Json
+        }
+    }
+}
+
+/// Reads a list of token placements from a file, whose format is determined
+/// by the file extension (see [TokenListFormat::from_path]).
+pub fn read_token_placements<P: AsRef<Path>>(
+    path: P,
+) -> Result<Vec<TokenPlacement>, Error> {
+    let path = path.as_ref();
+    match TokenListFormat::from_path(path) {
+        TokenListFormat::Json => read_json(path),
+        TokenListFormat::Csv => {
+            let text = std::fs::read_to_string(path)
+                .map_err(|e| Error::io(path, e))?;
+            from_csv_str(&text, Some(path))
+        }
+    }
+}
+
+/// Reads a list of token placements from text in the given format.
+pub fn token_placements_from_str(
+    text: &str,
+    format: TokenListFormat,
+) -> Result<Vec<TokenPlacement>, Error> {
+    match format {
+        TokenListFormat::Json => from_json_str(text, None),
+        TokenListFormat::Csv => from_csv_str(text, None),
+    }
+}
+
+/// Reads a list of token placements from CSV text, which was read from the
+/// file `path` (if any).
+fn from_csv_str(
+    text: &str,
+    path: Option<&Path>,
+) -> Result<Vec<TokenPlacement>, Error> {
+    let mut placements = vec![];
+    let mut first = true;
+    for (ix, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+        let is_header = first && fields[0].eq_ignore_ascii_case("company");
+        first = false;
+        if is_header {
+            continue;
+        }
+        let invalid = |message: String| Error::Csv {
+            path: path.map(|path| path.to_path_buf()),
+            line: ix + 1,
+            message,
+        };
+        let (company, hex, city) = match fields[..] {
+            [company, hex] => (company, hex, "0"),
+            [company, hex, city] => (company, hex, city),
+            _ => {
+                return Err(invalid(format!(
+                    "expected 2 or 3 values, found {}",
+                    fields.len()
+                )))
+            }
+        };
+        let city = city
+            .parse()
+            .map_err(|_| invalid(format!("invalid city index '{}'", city)))?;
+        placements.push(TokenPlacement {
+            company: company.to_string(),
+            hex: hex.to_string(),
+            city,
+        })
+    }
+    Ok(placements)
+}
+
+/// Places each company token on the map, in the first empty token space of
+/// the chosen city that is available in the current phase, and returns the
+/// changes that were made.
+///
+/// If any placement is invalid, no tokens are placed and the returned error
+/// describes every invalid placement.
+pub fn apply_token_placements(
+    map: &mut Map,
+    coords: &Coordinates,
+    placements: &[TokenPlacement],
+) -> Result<Vec<TokenChange>, Error> {
+    let mut changes = vec![];
+    let mut problems = vec![];
+    for (ix, placement) in placements.iter().enumerate() {
+        match place_token(map, coords, placement) {
+            Ok(change) => changes.push(change),
+            Err(reason) => problems.push(Error::InvalidTokenPlacement {
+                entry: ix + 1,
+                company: placement.company.clone(),
+                hex: placement.hex.clone(),
+                city: placement.city,
+                reason,
+            }),
+        }
+    }
+    if let Err(error) = Error::from_problems("token list", problems) {
+        map.revert_token_changes(&changes);
+        return Err(error);
+    }
+    Ok(changes)
+}
+
+/// Places a single company token, or returns the reason why it could not
+/// be placed.
+fn place_token(
+    map: &mut Map,
+    coords: &Coordinates,
+    placement: &TokenPlacement,
+) -> Result<TokenChange, &'static str> {
+    let addr = coords
+        .parse(&placement.hex)
+        .ok()
+        .filter(|addr| map.contains_hex(*addr))
+        .ok_or("there is no such hex")?;
+    let token = map
+        .try_token(&placement.company)
+        .ok_or("there is no such company")?;
+    let tile = map.tile_at(addr).ok_or("the hex has no tile")?;
+    if placement.city >= tile.cities().len() {
+        return Err("the tile has no such city");
+    }
+    let spaces = tile.token_spaces();
+    let hex_state = map.hex_state(addr).ok_or("the hex has no tile")?;
+    if spaces.iter().any(|s| hex_state.token_at(s) == Some(&token)) {
+        return Err("the company already has a token on this tile");
+    }
+    let space = spaces
+        .into_iter()
+        .find(|s| {
+            s.city_ix() == placement.city
+                && hex_state.token_at(s).is_none()
+                && map.token_space_available(addr, s)
+        })
+        .ok_or("the city has no empty token spaces")?;
+    map.hex_state_mut(addr)
+        .ok_or("the hex has no tile")?
+        .set_token_at(&space, token);
+    Ok(TokenChange {
+        addr,
+        space,
+        before: None,
+        after: Some(token),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        apply_token_placements, token_placements_from_str, TokenListFormat,
+        TokenPlacement,
+    };
+    use crate::Error;
+    use n18game::Game;
+    use n18hex::Hex;
+
+    fn placement(company: &str, hex: &str, city: usize) -> TokenPlacement {
+        TokenPlacement {
+            company: company.to_string(),
+            hex: hex.to_string(),
+            city,
+        }
+    }
+
+    #[test]
+    fn parse_token_lists() {
+        let csv = "# Tokens\nCompany, Hex, City\n\nPRR, E5, 1\nNYC,G19\n";
+        let placements =
+            token_placements_from_str(csv, TokenListFormat::Csv).unwrap();
+        let expected =
+            vec![placement("PRR", "E5", 1), placement("NYC", "G19", 0)];
+        assert_eq!(placements, expected);
+
+        let json = r#"[
+            {"company": "PRR", "hex": "E5", "city": 1},
+            {"company": "NYC", "hex": "G19"}
+        ]"#;
+        let placements =
+            token_placements_from_str(json, TokenListFormat::Json).unwrap();
+        assert_eq!(placements, expected);
+
+        // Report the line number of invalid CSV lines.
+        let csv = "PRR,E5,0\nNYC,G19,first\n";
+        let result = token_placements_from_str(csv, TokenListFormat::Csv);
+        assert!(matches!(result, Err(Error::Csv { line: 2, .. })));
+        let csv = "PRR,E5,0,extra\n";
+        let result = token_placements_from_str(csv, TokenListFormat::Csv);
+        assert!(matches!(result, Err(Error::Csv { line: 1, .. })));
+
+        assert_eq!(
+            TokenListFormat::from_path("tokens.CSV"),
+            TokenListFormat::Csv
+        );
+        assert_eq!(
+            TokenListFormat::from_path("tokens.json"),
+            TokenListFormat::Json
+        );
+    }
+
+    #[test]
+    fn apply_1830_tokens() {
+        let game = n18game::new_1830();
+        let mut map = game.create_map(&Hex::default());
+        let coords = game.coordinate_system();
+        let e5 = coords.parse("E5").unwrap();
+        let prr = map.try_token("PRR").unwrap();
+        let nyc = map.try_token("NYC").unwrap();
+
+        let placements =
+            vec![placement("PRR", "E5", 1), placement("NYC", "E5", 0)];
+        let changes =
+            apply_token_placements(&mut map, &coords, &placements).unwrap();
+        assert_eq!(changes.len(), 2);
+        let spaces = map.tile_at(e5).unwrap().token_spaces();
+        let hex_state = map.hex_state(e5).unwrap();
+        assert_eq!(hex_state.token_at(&spaces[0]), Some(&nyc));
+        assert_eq!(hex_state.token_at(&spaces[1]), Some(&prr));
+
+        // Invalid placements are reported, and no tokens are placed.
+        let original = map.clone();
+        let placements = vec![
+            placement("B&O", "O9", 0),
+            placement("PRR", "E5", 0),
+            placement("XYZ", "O9", 0),
+            placement("B&O", "Z99", 0),
+            placement("B&O", "C7", 0),
+            placement("B&O", "E5", 2),
+        ];
+        let result = apply_token_placements(&mut map, &coords, &placements);
+        let Err(Error::Invalid { problems, .. }) = result else {
+            panic!("Expected invalid placements")
+        };
+        let entries: Vec<usize> = problems
+            .iter()
+            .map(|problem| match problem {
+                Error::InvalidTokenPlacement { entry, .. } => *entry,
+                _ => panic!("Unexpected error {}", problem),
+            })
+            .collect();
+        assert_eq!(entries, vec![2, 3, 4, 5, 6]);
+        let o9 = coords.parse("O9").unwrap();
+        assert_eq!(map.hex_state(e5), original.hex_state(e5));
+        assert_eq!(map.hex_state(o9), original.hex_state(o9));
+        assert!(map.hex_state(o9).unwrap().tokens().is_empty());
+
+        // A single invalid placement is reported directly.
+        let placements = vec![placement("NYC", "E5", 1)];
+        let result = apply_token_placements(&mut map, &coords, &placements);
+        assert!(matches!(
+            result,
+            Err(Error::InvalidTokenPlacement { entry: 1, .. })
+        ));
+    }
+}
@@ -138,6 +138,8 @@
 //! assert!(valid_addr.is_some());
 //! let invalid_addr = HexAddress::logical(1, 4);
 //! assert!(invalid_addr.is_none());
+//! let negative_addr = HexAddress::logical(-1, 3);
+//! assert!(negative_addr.is_some());
 //! ```
 //!
 
@@ -759,7 +761,9 @@ impl HexAddress {
     /// Returns a new `HexAddress` with the specified `row` and `column`
     /// (logical coordinates) if the coordinates are valid.
     pub fn logical(row: isize, column: isize) -> Option<Self> {
-        if row % 2 != column % 2 {
+        // NOTE: use rem_euclid() so that negative odd numbers are handled
+        // correctly (e.g., `-1 % 2 == -1` but `-1_isize.rem_euclid(2) == 1`).
+        if row.rem_euclid(2) != column.rem_euclid(2) {
             None
         } else {
             Some(Self { row, col: column })
@@ -790,6 +794,35 @@ impl HexAddress {
         orientation.adjacent(*self, face)
     }
 
+    /// Returns the number of steps required to move from this hex to the
+    /// `other` hex, where each step moves to an adjacent hex.
+    pub fn distance(
+        &self,
+        other: &HexAddress,
+        orientation: Orientation,
+    ) -> usize {
+        let (q0, r0) = self.axial(orientation);
+        let (q1, r1) = other.axial(orientation);
+        let (dq, dr) = (q1 - q0, r1 - r0);
+        (dq.unsigned_abs() + dr.unsigned_abs() + (dq + dr).unsigned_abs()) / 2
+    }
+
+    /// Returns the axial coordinates of this hex.
+    fn axial(&self, orientation: Orientation) -> (isize, isize) {
+        // NOTE: use rem_euclid() so that negative odd numbers are handled
+        // correctly.
+        match orientation {
+            Orientation::FlatTop => {
+                let q = self.col;
+                (q, self.row - (q - q.rem_euclid(2)) / 2)
+            }
+            Orientation::PointedTop => {
+                let r = self.row;
+                (self.col - (r - r.rem_euclid(2)) / 2, r)
+            }
+        }
+    }
+
     /// Calls a closure on this hex address, and returns the hex address.
     pub fn do_here<F>(&self, mut f: F) -> &HexAddress
     where
@@ -1562,4 +1595,36 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_distance() {
+        use n18hex::HexFace;
+
+        for orientation in [FlatTop, PointedTop] {
+            for row in -2..3 {
+                for col in -2..3 {
+                    let addr = HexAddress::new(row, col);
+                    assert_eq!(addr.distance(&addr, orientation), 0);
+                    let mut face = HexFace::Top;
+                    for _ in 0..6 {
+                        face = face.clockwise();
+                        let adj = addr.adjacent(face, orientation);
+                        assert_eq!(addr.distance(&adj, orientation), 1);
+                        assert_eq!(adj.distance(&addr, orientation), 1);
+                        let next = adj.adjacent(face, orientation);
+                        assert_eq!(addr.distance(&next, orientation), 2);
+                    }
+                }
+            }
+        }
+
+        // Check the distances between hexes in different rows and columns.
+        let system = Coordinates::from((FlatTop, AsColumns, OddColumns));
+        let a1 = system.parse("A1").unwrap();
+        let b4 = system.parse("B4").unwrap();
+        let e5 = system.parse("E5").unwrap();
+        assert_eq!(a1.distance(&b4, FlatTop), 2);
+        assert_eq!(a1.distance(&e5, FlatTop), 4);
+        assert_eq!(b4.distance(&e5, FlatTop), 3);
+    }
 }
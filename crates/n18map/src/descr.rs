@@ -3,8 +3,8 @@
 use std::collections::BTreeMap;
 
 use crate::map::MapTile;
-use crate::{HexAddress, Map};
-use n18hex::{Orientation, RotateCW};
+use crate::{HexAddress, HexHalf, Layer, Map, Marker, Terrain};
+use n18hex::{HexColour, Orientation, RotateCW};
 use n18tile::Tile;
 use n18token::Tokens;
 
@@ -24,9 +24,27 @@ pub struct TileDescr {
 }
 
 /// A description of each tile's configuration on a map.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Descr {
     orientation: Orientation,
     tiles: BTreeMap<HexAddress, Option<TileDescr>>,
+    /// The latest tile colour that can be placed on each map hex (see
+    /// [Map::set_max_colour]).
+    max_colours: BTreeMap<HexAddress, HexColour>,
+    /// The markers that have been placed on each map hex (see
+    /// [Map::place_marker]).
+    markers: BTreeMap<HexAddress, Vec<Marker>>,
+    /// The terrain that is drawn on each map hex (see [Map::set_terrain]).
+    terrain: BTreeMap<HexAddress, Terrain>,
+    /// The location names of map hexes that form part of a larger location
+    /// (see [Map::set_location]).
+    locations: BTreeMap<HexAddress, String>,
+    /// The map hexes that are only partially present (see
+    /// [Map::set_partial_hex]).
+    partial_hexes: BTreeMap<HexAddress, HexHalf>,
+    /// The tiles that are planned but have not been placed (see
+    /// [Map::plan]).
+    plan: Layer,
 }
 
 impl<'a> From<&'a Descr>
@@ -42,7 +60,20 @@ impl From<(Orientation, BTreeMap<HexAddress, Option<TileDescr>>)> for Descr {
         src: (Orientation, BTreeMap<HexAddress, Option<TileDescr>>),
     ) -> Self {
         let (orientation, tiles) = src;
-        Self { tiles, orientation }
+        let max_colours = BTreeMap::new();
+        let markers = BTreeMap::new();
+        let terrain = BTreeMap::new();
+        let locations = BTreeMap::new();
+        Self {
+            tiles,
+            orientation,
+            max_colours,
+            markers,
+            terrain,
+            locations,
+            partial_hexes: BTreeMap::new(),
+            plan: Layer::default(),
+        }
     }
 }
 
@@ -55,8 +86,21 @@ impl From<(Orientation, Vec<TileDescr>)> for Descr {
             .into_iter()
             .map(|td| ((td.row, td.col).into(), Some(td)))
             .collect();
-
-        Descr { tiles, orientation }
+        let max_colours = BTreeMap::new();
+        let markers = BTreeMap::new();
+        let terrain = BTreeMap::new();
+        let locations = BTreeMap::new();
+
+        Descr {
+            tiles,
+            orientation,
+            max_colours,
+            markers,
+            terrain,
+            locations,
+            partial_hexes: BTreeMap::new(),
+            plan: Layer::default(),
+        }
     }
 }
 
@@ -102,21 +146,185 @@ impl From<&Map> for Descr {
             })
             .collect();
         let orientation = map.orientation();
-        Descr { tiles, orientation }
+        let max_colours = map
+            .hex_address_iter()
+            .filter_map(|addr| map.max_colour(*addr).map(|c| (*addr, c)))
+            .collect();
+        let markers = map
+            .marker_iter()
+            .map(|(addr, markers)| (addr, markers.to_vec()))
+            .collect();
+        let terrain = map.terrain_iter().collect();
+        let locations = map
+            .location_iter()
+            .map(|(addr, name)| (addr, name.to_string()))
+            .collect();
+        Descr {
+            tiles,
+            orientation,
+            max_colours,
+            markers,
+            terrain,
+            locations,
+            partial_hexes: map.partial_hex_iter().collect(),
+            plan: map.plan().clone(),
+        }
     }
 }
 
 impl Descr {
+    /// Limits the tile colours that can be placed on each map hex (see
+    /// [Map::set_max_colour]).
+    pub fn with_max_colours(
+        mut self,
+        max_colours: BTreeMap<HexAddress, HexColour>,
+    ) -> Self {
+        self.max_colours = max_colours;
+        self
+    }
+
+    /// Returns the latest tile colour that can be placed on each map hex,
+    /// for those hexes that are limited to certain tile colours.
+    pub fn max_colours(&self) -> &BTreeMap<HexAddress, HexColour> {
+        &self.max_colours
+    }
+
+    /// Places markers on map hexes (see [Map::place_marker]).
+    pub fn with_markers(
+        mut self,
+        markers: BTreeMap<HexAddress, Vec<Marker>>,
+    ) -> Self {
+        self.markers = markers;
+        self
+    }
+
+    /// Returns the markers that have been placed on each map hex.
+    pub fn markers(&self) -> &BTreeMap<HexAddress, Vec<Marker>> {
+        &self.markers
+    }
+
+    /// Draws terrain on map hexes (see [Map::set_terrain]).
+    pub fn with_terrain(
+        mut self,
+        terrain: BTreeMap<HexAddress, Terrain>,
+    ) -> Self {
+        self.terrain = terrain;
+        self
+    }
+
+    /// Returns the terrain that is drawn on each map hex.
+    pub fn terrain(&self) -> &BTreeMap<HexAddress, Terrain> {
+        &self.terrain
+    }
+
+    /// Groups map hexes into locations that span several hexes (see
+    /// [Map::set_location]).
+    pub fn with_locations(
+        mut self,
+        locations: BTreeMap<HexAddress, String>,
+    ) -> Self {
+        self.locations = locations;
+        self
+    }
+
+    /// Returns the location name of each map hex that is part of a location
+    /// that spans several hexes.
+    pub fn locations(&self) -> &BTreeMap<HexAddress, String> {
+        &self.locations
+    }
+
+    /// Marks map hexes as only partially present (see
+    /// [Map::set_partial_hex]).
+    pub fn with_partial_hexes(
+        mut self,
+        partial_hexes: BTreeMap<HexAddress, HexHalf>,
+    ) -> Self {
+        self.partial_hexes = partial_hexes;
+        self
+    }
+
+    /// Returns the half of each map hex that is present, for those hexes
+    /// that are only partially present.
+    pub fn partial_hexes(&self) -> &BTreeMap<HexAddress, HexHalf> {
+        &self.partial_hexes
+    }
+
+    /// Plans tiles that have not been placed (see [Map::plan_tile]).
+    pub fn with_plan(mut self, plan: Layer) -> Self {
+        self.plan = plan;
+        self
+    }
+
+    /// Returns the tiles that are planned but have not been placed.
+    pub fn plan(&self) -> &Layer {
+        &self.plan
+    }
+
     /// Constructs a map whose state reflects the tile configurations.
+    ///
+    /// These tiles are recorded as the initial state of each map hex (see
+    /// [Map::record_initial_tiles]).
     pub fn build_map(&self, tiles: Vec<Tile>, tokens: Tokens) -> Map {
         let addrs = self.tiles.keys().copied().collect::<Vec<_>>();
         let mut map = Map::new(tiles.into(), tokens, addrs, self.orientation);
         self.update_map(&mut map);
+        map.record_initial_tiles();
         map
     }
 
     /// Updates the state of an existing map.
+    ///
+    /// Any maximum tile colours in this description are applied to the map,
+    /// but existing limits on other hexes are retained, since these are
+    /// typically defined by the game rather than by the map state.
+    ///
+    /// The markers on each hex are replaced by the markers (if any) in this
+    /// description.
+    /// Locations that span several hexes, terrain, and partial hexes, are
+    /// only replaced if this description defines any locations, terrain, or
+    /// partial hexes, respectively, since these are also typically defined
+    /// by the game.
+    ///
+    /// The planned tiles are replaced by the planned tiles (if any) in this
+    /// description.
     pub fn update_map(&self, map: &mut Map) {
+        for (addr, colour) in self.max_colours.iter() {
+            map.set_max_colour(*addr, *colour);
+        }
+        let addrs: Vec<HexAddress> =
+            map.hex_address_iter().copied().collect();
+        for addr in &addrs {
+            map.clear_markers(*addr);
+        }
+        if !self.locations.is_empty() {
+            for addr in &addrs {
+                map.clear_location(*addr);
+            }
+            for (addr, name) in self.locations.iter() {
+                map.set_location(*addr, name);
+            }
+        }
+        if !self.terrain.is_empty() {
+            for addr in &addrs {
+                map.clear_terrain(*addr);
+            }
+            for (addr, terrain) in self.terrain.iter() {
+                map.set_terrain(*addr, *terrain);
+            }
+        }
+        if !self.partial_hexes.is_empty() {
+            for addr in &addrs {
+                map.clear_partial_hex(*addr);
+            }
+            for (addr, half) in self.partial_hexes.iter() {
+                map.set_partial_hex(*addr, *half);
+            }
+        }
+        for (addr, markers) in self.markers.iter() {
+            for marker in markers {
+                map.place_marker(*addr, marker.clone());
+            }
+        }
         for (addr, tile_descr) in self.tiles.iter() {
             if let Some(tile_descr) = tile_descr {
                 map.place_tile(
@@ -156,27 +364,204 @@ impl Descr {
                 map.remove_tile(*addr);
             }
         }
+        map.discard_plan();
+        for (addr, planned) in self.plan.iter() {
+            map.plan_tile(addr, &planned.tile, planned.rotation);
+        }
+    }
+}
+
+/// A group of map hexes that have been copied from a map, so that they can
+/// be pasted elsewhere on the map.
+///
+/// Each hex is identified by its position relative to an anchor hex, so
+/// that the group retains its shape when it is pasted, and can be rotated as
+/// a block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clipboard {
+    orientation: Orientation,
+    /// The contents of each copied hex, identified by its position relative
+    /// to the anchor hex in axial coordinates.
+    hexes: BTreeMap<(isize, isize), Option<TileDescr>>,
+}
+
+/// Returns the axial coordinates `(q, r)` of a map hex.
+fn to_axial(addr: HexAddress, orientation: Orientation) -> (isize, isize) {
+    match orientation {
+        Orientation::FlatTop => (addr.col, addr.row - addr.col.div_euclid(2)),
+        Orientation::PointedTop => {
+            (addr.col - addr.row.div_euclid(2), addr.row)
+        }
+    }
+}
+
+/// Returns the map hex located at the axial coordinates `(q, r)`.
+fn from_axial(q: isize, r: isize, orientation: Orientation) -> HexAddress {
+    match orientation {
+        Orientation::FlatTop => (r + q.div_euclid(2), q).into(),
+        Orientation::PointedTop => (r, q + r.div_euclid(2)).into(),
+    }
+}
+
+impl Clipboard {
+    /// Copies the contents of each map hex in `hexes`, relative to the
+    /// `anchor` hex.
+    ///
+    /// Addresses in `hexes` that are not valid for this map are ignored.
+    pub fn copy<'a, T>(map: &Map, anchor: HexAddress, hexes: T) -> Self
+    where
+        T: IntoIterator<Item = &'a HexAddress>,
+    {
+        let orientation = map.orientation();
+        let (q0, r0) = to_axial(anchor, orientation);
+        let hexes = hexes
+            .into_iter()
+            .filter(|addr| map.contains_hex(**addr))
+            .map(|addr| {
+                let (q, r) = to_axial(*addr, orientation);
+                let tile_opt = map
+                    .hex_state(*addr)
+                    .map(|map_hex| (map, *addr, map_hex).into());
+                ((q - q0, r - r0), tile_opt)
+            })
+            .collect();
+        Clipboard { orientation, hexes }
+    }
+
+    /// Returns `true` if no hexes were copied.
+    pub fn is_empty(&self) -> bool {
+        self.hexes.is_empty()
+    }
+
+    /// Returns the number of copied hexes.
+    pub fn len(&self) -> usize {
+        self.hexes.len()
+    }
+
+    /// Rotates the copied hexes clockwise around the anchor hex, and rotates
+    /// each copied tile accordingly.
+    pub fn rotate_cw(&mut self) {
+        // NOTE: in cube coordinates, (q, r, s) becomes (-r, -s, -q).
+        self.rotate(|q, r| (-r, q + r), |rot| rot.rotate_cw())
+    }
+
+    /// Rotates the copied hexes anti-clockwise around the anchor hex, and
+    /// rotates each copied tile accordingly.
+    pub fn rotate_anti_cw(&mut self) {
+        // NOTE: in cube coordinates, (q, r, s) becomes (-s, -q, -r).
+        self.rotate(|q, r| (q + r, -q), |rot| rot.rotate_anti_cw())
+    }
+
+    fn rotate<F, G>(&mut self, posn: F, rotn: G)
+    where
+        F: Fn(isize, isize) -> (isize, isize),
+        G: Fn(&RotateCW) -> RotateCW,
+    {
+        let hexes = std::mem::take(&mut self.hexes);
+        self.hexes = hexes
+            .into_iter()
+            .map(|((q, r), mut tile_opt)| {
+                if let Some(tile_descr) = tile_opt.as_mut() {
+                    tile_descr.rotation = rotn(&tile_descr.rotation)
+                }
+                (posn(q, r), tile_opt)
+            })
+            .collect();
+    }
+
+    /// Returns the map hexes that would be affected by pasting the copied
+    /// hexes so that the anchor hex is placed at `anchor`.
+    ///
+    /// Note that these addresses may not all be valid for the map.
+    pub fn targets(&self, anchor: HexAddress) -> Vec<HexAddress> {
+        let (q0, r0) = to_axial(anchor, self.orientation);
+        self.hexes
+            .keys()
+            .map(|(q, r)| from_axial(q0 + q, r0 + r, self.orientation))
+            .collect()
+    }
+
+    /// Pastes the copied hexes so that the anchor hex is placed at `anchor`,
+    /// and returns the addresses of the map hexes that were updated.
+    ///
+    /// Copied hexes that would lie outside of the map are ignored, as are
+    /// tokens that are not defined by the map.
+    pub fn paste(
+        &self,
+        map: &mut Map,
+        anchor: HexAddress,
+    ) -> Vec<HexAddress> {
+        let mut updated = vec![];
+        let targets = self.targets(anchor);
+        for (addr, tile_opt) in targets.into_iter().zip(self.hexes.values()) {
+            if !map.contains_hex(addr) {
+                continue;
+            }
+            if let Some(tile_descr) = tile_opt {
+                if !paste_tile(map, addr, tile_descr) {
+                    continue;
+                }
+            } else {
+                map.remove_tile(addr);
+            }
+            updated.push(addr);
+        }
+        updated
+    }
+}
+
+/// Places the described tile and its tokens on the map hex `addr`, and
+/// returns whether the tile was placed.
+fn paste_tile(
+    map: &mut Map,
+    addr: HexAddress,
+    tile_descr: &TileDescr,
+) -> bool {
+    if !map.place_tile(addr, &tile_descr.tile, tile_descr.rotation) {
+        return false;
+    }
+    let spaces = map.tile_at(addr).map(|t| t.token_spaces());
+    let spaces = spaces.unwrap_or_default();
+    let tokens = tile_descr
+        .tokens
+        .iter()
+        .filter_map(|(space_ix, name)| {
+            let space = spaces.get(*space_ix)?;
+            let token = map.try_token(name)?;
+            Some((*space, token))
+        })
+        .collect();
+    if let Some(hex_state) = map.hex_state_mut(addr) {
+        hex_state.set_tokens(tokens)
     }
+    true
 }
 
 #[cfg(test)]
 pub mod tests {
+    #[cfg(feature = "cairo")]
     use cairo::{Context, Format, ImageSurface};
 
     use super::*;
+    #[cfg(feature = "cairo")]
     use crate::map::TileHexState;
 
+    #[cfg(feature = "cairo")]
     use n18hex::Hex;
     use n18token::Token;
 
+    #[cfg(feature = "cairo")]
     static HEX_DIAMETER: f64 = 150.0;
 
+    #[cfg(feature = "cairo")]
     static OUT_DIR: &str = "../../tests/output";
 
+    #[cfg(feature = "cairo")]
     fn output_path(file: &'static str) -> std::path::PathBuf {
         std::path::Path::new(OUT_DIR).join(file)
     }
 
+    #[cfg(feature = "cairo")]
     fn new_context(width: i32, height: i32) -> (Context, ImageSurface) {
         let surface = ImageSurface::create(Format::ARgb32, width, height)
             .expect("Can't create surface");
@@ -185,6 +570,7 @@ pub mod tests {
         (context, surface)
     }
 
+    #[cfg(feature = "cairo")]
     fn draw_tiles(map: &Map, hex: &Hex, ctx: &Context) {
         for hex_state in map.hex_iter(hex, ctx) {
             match hex_state.tile_state {
@@ -291,6 +677,7 @@ pub mod tests {
             .into()
     }
 
+    #[cfg(feature = "cairo")]
     #[test]
     fn simple_two_by_two() {
         let hex = Hex::new(HEX_DIAMETER);
@@ -385,6 +772,7 @@ pub mod tests {
             .expect("Couldn't write to output PNG file");
     }
 
+    #[cfg(feature = "cairo")]
     #[test]
     fn simple_two_by_two_with_empty_hexes() {
         let hex = Hex::new(HEX_DIAMETER);
@@ -429,4 +817,83 @@ pub mod tests {
         surf.write_to_png(&mut file)
             .expect("Couldn't write to output PNG file");
     }
+
+    #[test]
+    fn built_maps_record_initial_tiles() {
+        let mut map = map_2x2_tiles_5_6_58_63();
+        let addr: HexAddress = (0, 0).into();
+        assert_eq!(map.initial_tile(addr).unwrap().0.name, "5");
+        assert!(map.is_initial_state(addr));
+
+        // The tiles in the description are restored when a hex is reset.
+        map.remove_tile(addr);
+        assert!(map.can_reset_hex(addr));
+        assert!(map.reset_hex(addr));
+        assert_eq!(map.tile_at(addr).unwrap().name, "5");
+    }
+
+    #[test]
+    fn clipboard_copy_and_paste() {
+        let mut map = map_2x2_tiles_5_6_58_63();
+        let anchor: HexAddress = (0, 0).into();
+        let hexes: Vec<HexAddress> = vec![(0, 0).into(), (0, 1).into()];
+        let clipboard = Clipboard::copy(&map, anchor, &hexes);
+        assert_eq!(clipboard.len(), 2);
+
+        // Paste the tiles one row down, which replaces tiles 58 and 63.
+        let dest: HexAddress = (1, 0).into();
+        let updated = clipboard.paste(&mut map, dest);
+        assert_eq!(updated, vec![(1, 0).into(), (1, 1).into()]);
+        let before = Descr::from(&map_2x2_tiles_5_6_58_63());
+        let after = Descr::from(&map);
+        for col in 0..2 {
+            let src = before.tiles[&(0, col).into()].as_ref().unwrap();
+            let dst = after.tiles[&(1, col).into()].as_ref().unwrap();
+            assert_eq!(src.tile, dst.tile);
+            assert_eq!(src.rotation, dst.rotation);
+            assert_eq!(src.tokens, dst.tokens);
+        }
+
+        // Pasting the tiles one row further down should only affect hexes
+        // that are on the map, and there are none.
+        let updated = clipboard.paste(&mut map, (2, 0).into());
+        assert!(updated.is_empty());
+    }
+
+    #[test]
+    fn clipboard_rotation() {
+        use n18hex::HexFace;
+
+        for orientation in [Orientation::FlatTop, Orientation::PointedTop] {
+            // NOTE: use an anchor with odd-numbered row and column, to check
+            // that the offset coordinates are handled correctly.
+            let anchor: HexAddress = (3, 5).into();
+            for face in [
+                HexFace::Top,
+                HexFace::UpperRight,
+                HexFace::LowerRight,
+                HexFace::Bottom,
+                HexFace::LowerLeft,
+                HexFace::UpperLeft,
+            ] {
+                let mut clipboard = Clipboard {
+                    orientation,
+                    hexes: BTreeMap::new(),
+                };
+                let (q0, r0) = to_axial(anchor, orientation);
+                let (q, r) =
+                    to_axial(anchor.adjacent(face, orientation), orientation);
+                clipboard.hexes.insert((q - q0, r - r0), None);
+
+                clipboard.rotate_cw();
+                let expect = anchor.adjacent(face.clockwise(), orientation);
+                assert_eq!(clipboard.targets(anchor), vec![expect]);
+                clipboard.rotate_anti_cw();
+                clipboard.rotate_anti_cw();
+                let expect =
+                    anchor.adjacent(face.anti_clockwise(), orientation);
+                assert_eq!(clipboard.targets(anchor), vec![expect]);
+            }
+        }
+    }
 }
@@ -0,0 +1,188 @@
+//! Describe the contents of individual map hexes.
+//!
+//! [Map::describe_hex] returns a [HexDescription], which records the tile
+//! placed on a map hex, the revenue and tokens of each city, and the tiles
+//! that could replace the current tile.
+//! This is intended for user interfaces that inspect a single hex, and for
+//! scripted tools that need to report the state of a hex.
+
+use crate::{HexAddress, Map, PlacementRules, UpgradeRule};
+use n18hex::{HexColour, RotateCW};
+
+/// A description of a single map hex.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HexDescription {
+    /// The map hex.
+    pub addr: HexAddress,
+    /// The name of the tile placed on this hex, if any.
+    pub tile: Option<String>,
+    /// The rotation of the tile placed on this hex, if any.
+    pub rotation: Option<RotateCW>,
+    /// The colour of the tile placed on this hex, or [HexColour::Empty] if
+    /// there is no tile.
+    pub colour: HexColour,
+    /// The cities on this tile, in the order defined by the tile.
+    pub cities: Vec<CityDescription>,
+    /// The revenue of each dit (town) on this tile.
+    pub dits: Vec<usize>,
+    /// The location (see [Map::set_location]) that this hex is part of, if
+    /// any.
+    pub location: Option<String>,
+    /// The names of the available tiles that could be placed on this hex.
+    pub upgrades: Vec<String>,
+}
+
+/// A description of a single city on a map hex.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CityDescription {
+    /// The revenue that a train earns by stopping at this city.
+    pub revenue: usize,
+    /// The name of the token (if any) in each of this city's token spaces.
+    pub tokens: Vec<Option<String>>,
+}
+
+impl Map {
+    /// Describes the tile (if any) placed on a map hex, its cities and
+    /// tokens, and the tiles that could be placed on this hex.
+    ///
+    /// If the revenue phase has been set (see [Map::revenue_phase]) and the
+    /// tile has a phase revenue label, the revenue for this phase is
+    /// reported for each city and dit.
+    /// Dits that have their own phase-dependent revenue (see
+    /// [n18tile::Dit::revenue_in]) report their revenue for this phase.
+    ///
+    /// Returns `None` if `addr` is not a valid hex address for this map.
+    pub fn describe_hex(&self, addr: HexAddress) -> Option<HexDescription> {
+        if !self.contains_hex(addr) {
+            return None;
+        }
+        let location = self.location(addr).map(|name| name.to_string());
+        let upgrades = self.upgrade_candidates(addr);
+        let (tile, hex_state) =
+            match (self.tile_at(addr), self.hex_state(addr)) {
+                (Some(tile), Some(hex_state)) => (tile, hex_state),
+                _ => {
+                    return Some(HexDescription {
+                        addr,
+                        tile: None,
+                        rotation: None,
+                        colour: HexColour::Empty,
+                        cities: vec![],
+                        dits: vec![],
+                        location,
+                        upgrades,
+                    })
+                }
+            };
+        let phase_revenue = self
+            .revenue_phase()
+            .and_then(|phase| tile.phase_revenue(phase));
+        let cities = tile
+            .cities()
+            .iter()
+            .enumerate()
+            .map(|(city_ix, city)| {
+                let tokens = tile
+                    .city_token_spaces(city_ix)
+                    .iter()
+                    .map(|space| {
+                        hex_state
+                            .token_at(space)
+                            .and_then(|token| self.try_token_name(token))
+                            .map(|name| name.to_string())
+                    })
+                    .collect();
+                CityDescription {
+                    revenue: phase_revenue.unwrap_or(city.revenue),
+                    tokens,
+                }
+            })
+            .collect();
+        let dits = tile
+            .dits()
+            .iter()
+            .map(|dit| {
+                self.revenue_phase()
+                    .and_then(|phase| dit.phase_revenues.revenue(phase))
+                    .or(phase_revenue)
+                    .unwrap_or(dit.revenue)
+            })
+            .collect();
+        Some(HexDescription {
+            addr,
+            tile: Some(tile.name.clone()),
+            rotation: Some(*hex_state.rotation()),
+            colour: tile.colour,
+            cities,
+            dits,
+            location,
+            upgrades,
+        })
+    }
+
+    /// Returns the names of the available tiles that could be placed on a
+    /// map hex, either by upgrading the current tile or, for empty hexes,
+    /// by placing a new tile.
+    fn upgrade_candidates(&self, addr: HexAddress) -> Vec<String> {
+        let rules = PlacementRules::default().with_upgrades(UpgradeRule::Any);
+        self.legal_placements(addr, &rules)
+            .into_iter()
+            .map(|(name, _rotations)| name)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use n18hex::{HexColour, Orientation, RotateCW};
+    use n18token::{Token, TokenStyle};
+
+    use crate::{HexAddress, Map};
+
+    #[test]
+    fn test_describe_hex() {
+        let token = Token::new(TokenStyle::SideArcs {
+            fg: (63, 153, 153).into(),
+            bg: (255, 127, 127).into(),
+            text: (0, 0, 0).into(),
+        });
+        let hexes: Vec<HexAddress> = vec![(0, 0).into(), (0, 1).into()];
+        let mut map = Map::new(
+            n18catalogue::tile_catalogue().into(),
+            vec![("A".to_string(), token)].into(),
+            hexes,
+            Orientation::FlatTop,
+        );
+        let (addr_57, addr_empty) = ((0, 0).into(), (0, 1).into());
+        assert!(map.place_tile(addr_57, "57", RotateCW::One));
+        let space = map.tile_at(addr_57).unwrap().token_spaces()[0];
+        map.hex_state_mut(addr_57)
+            .unwrap()
+            .set_token_at(&space, token);
+
+        let descr = map.describe_hex(addr_57).unwrap();
+        assert_eq!(descr.tile.as_deref(), Some("57"));
+        assert_eq!(descr.rotation, Some(RotateCW::One));
+        assert_eq!(descr.colour, HexColour::Yellow);
+        assert_eq!(descr.cities.len(), 1);
+        assert_eq!(descr.cities[0].revenue, 20);
+        assert_eq!(descr.cities[0].tokens, vec![Some("A".to_string())]);
+        assert!(descr.dits.is_empty());
+        // Tile 57 can be upgraded to green tiles such as 14 and 15.
+        assert!(descr.upgrades.iter().any(|name| name == "14"));
+        assert!(descr.upgrades.iter().any(|name| name == "15"));
+        assert!(descr.upgrades.iter().all(|name| {
+            map.tile(name).map(|t| t.colour) == Some(HexColour::Green)
+        }));
+
+        // Only yellow track tiles can be placed on empty hexes.
+        let descr = map.describe_hex(addr_empty).unwrap();
+        assert_eq!(descr.tile, None);
+        assert_eq!(descr.colour, HexColour::Empty);
+        assert!(descr.upgrades.iter().any(|name| name == "8"));
+        assert!(!descr.upgrades.iter().any(|name| name == "57"));
+
+        // Hexes that are not part of the map cannot be described.
+        assert!(map.describe_hex((5, 5).into()).is_none());
+    }
+}
@@ -0,0 +1,227 @@
+//! Tentative tile placements that are kept separate from the map hexes.
+//!
+//! Each [Map] has a planning layer (see [Map::plan]), in which tiles can be
+//! sketched without modifying the map itself, so they do not affect the
+//! map's [revision](Map::revision) or the routes that companies can run.
+//! Planned tiles can then be placed on the map as a batch
+//! ([Map::commit_plan]) or discarded ([Map::discard_plan]).
+//!
+//! ```rust
+//! # use n18hex::{Orientation, RotateCW};
+//! # use n18map::{HexAddress, Map};
+//! # let tiles = n18catalogue::tile_catalogue();
+//! # let hexes = vec![HexAddress::new(0, 0), HexAddress::new(0, 1)];
+//! # let orientation = Orientation::FlatTop;
+//! let mut map = Map::new(tiles.into(), vec![].into(), hexes, orientation);
+//! let addr = HexAddress::new(0, 0);
+//! let revision = map.revision();
+//! assert!(map.plan_tile(addr, "5", RotateCW::One));
+//! assert_eq!(map.plan().len(), 1);
+//! // The planned tile has not been placed on the map.
+//! assert!(map.tile_at(addr).is_none());
+//! assert_eq!(map.revision(), revision);
+//!
+//! assert_eq!(map.commit_plan(), vec![addr]);
+//! assert!(map.plan().is_empty());
+//! assert_eq!(map.tile_at(addr).unwrap().name, "5");
+//! ```
+
+use std::collections::BTreeMap;
+
+use n18hex::RotateCW;
+
+use crate::{HexAddress, Map};
+
+/// A tile that is planned for a map hex, but has not been placed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlannedTile {
+    /// The tile name.
+    pub tile: String,
+    /// The tile rotation.
+    pub rotation: RotateCW,
+}
+
+/// The tiles that are planned for each map hex (see [Map::plan]).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Layer {
+    tiles: BTreeMap<HexAddress, PlannedTile>,
+}
+
+impl Layer {
+    /// Returns `true` if no tiles are planned.
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Returns the number of planned tiles.
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Returns the tile planned for a map hex, if any.
+    pub fn get(&self, addr: HexAddress) -> Option<&PlannedTile> {
+        self.tiles.get(&addr)
+    }
+
+    /// Returns each map hex for which a tile is planned, in order, and the
+    /// planned tile.
+    pub fn iter(&self) -> impl Iterator<Item = (HexAddress, &PlannedTile)> {
+        self.tiles.iter().map(|(addr, tile)| (*addr, tile))
+    }
+
+    pub(crate) fn insert(&mut self, addr: HexAddress, tile: PlannedTile) {
+        self.tiles.insert(addr, tile);
+    }
+
+    pub(crate) fn remove(&mut self, addr: HexAddress) -> bool {
+        self.tiles.remove(&addr).is_some()
+    }
+}
+
+impl FromIterator<(HexAddress, PlannedTile)> for Layer {
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (HexAddress, PlannedTile)>,
+    {
+        Layer {
+            tiles: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Map {
+    /// Returns the tiles in the planning layer, which are not placed on the
+    /// map.
+    pub fn plan(&self) -> &Layer {
+        &self.plan
+    }
+
+    /// Plans to place a tile on a map hex, replacing any tile that was
+    /// previously planned for this hex, without modifying the map hex.
+    ///
+    /// Returns `false` if the hex is not part of the map, if there is no
+    /// such tile, or if the current tile cannot be replaced.
+    pub fn plan_tile(
+        &mut self,
+        addr: HexAddress,
+        tile: &str,
+        rotation: RotateCW,
+    ) -> bool {
+        if !self.contains_hex(addr) || self.tile(tile).is_none() {
+            return false;
+        }
+        if self.hex_state(addr).is_some_and(|hs| !hs.is_replaceable()) {
+            return false;
+        }
+        let tile = tile.to_string();
+        self.plan.insert(addr, PlannedTile { tile, rotation });
+        true
+    }
+
+    /// Removes the tile planned for a map hex, and returns `false` if no
+    /// tile was planned for this hex.
+    pub fn unplan_tile(&mut self, addr: HexAddress) -> bool {
+        self.plan.remove(addr)
+    }
+
+    /// Removes every planned tile, and returns the number of tiles that were
+    /// removed.
+    pub fn discard_plan(&mut self) -> usize {
+        std::mem::take(&mut self.plan).len()
+    }
+
+    /// Removes and returns every planned tile, without placing any of them
+    /// on the map.
+    pub fn take_plan(&mut self) -> Layer {
+        std::mem::take(&mut self.plan)
+    }
+
+    /// Places every planned tile on the map (see [Map::place_tile]), removes
+    /// them from the planning layer, and returns the map hexes on which a
+    /// tile was placed.
+    pub fn commit_plan(&mut self) -> Vec<HexAddress> {
+        let plan = self.take_plan();
+        plan.iter()
+            .filter(|(addr, planned)| {
+                self.place_tile(*addr, &planned.tile, planned.rotation)
+            })
+            .map(|(addr, _)| addr)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Layer;
+    use crate::descr::tests::map_2x2_tiles_5_6_58_63;
+    use crate::{Descr, HexAddress};
+    use n18hex::RotateCW;
+
+    #[test]
+    fn test_plan_and_discard() {
+        let mut map = map_2x2_tiles_5_6_58_63();
+        let original = map.clone();
+        let revision = map.revision();
+        let addr = HexAddress::new(0, 1);
+
+        // Tiles can only be planned for valid hexes, using defined tiles.
+        assert!(!map.plan_tile(HexAddress::new(5, 5), "8", RotateCW::Zero));
+        assert!(!map.plan_tile(addr, "no such tile", RotateCW::Zero));
+        assert!(map.plan().is_empty());
+
+        // Planning a tile for the same hex replaces the earlier plan.
+        assert!(map.plan_tile(addr, "8", RotateCW::Zero));
+        assert!(map.plan_tile(addr, "15", RotateCW::Two));
+        assert!(map.plan_tile(HexAddress::new(1, 0), "9", RotateCW::One));
+        assert_eq!(map.plan().len(), 2);
+        let planned = map.plan().get(addr).unwrap();
+        assert_eq!(planned.tile, "15");
+        assert_eq!(planned.rotation, RotateCW::Two);
+        assert_eq!(map.revision(), revision);
+        assert_eq!(map.tile_at(addr).unwrap().name, "6");
+
+        assert!(map.unplan_tile(addr));
+        assert!(!map.unplan_tile(addr));
+        assert_eq!(map.discard_plan(), 1);
+        assert!(map.plan().is_empty());
+        assert_eq!(map, original);
+    }
+
+    #[test]
+    fn test_plan_descr() {
+        let mut map = map_2x2_tiles_5_6_58_63();
+        let addr = HexAddress::new(1, 1);
+        assert!(map.plan_tile(addr, "66", RotateCW::Three));
+        let descr = Descr::from(&map);
+        assert_eq!(descr.plan(), map.plan());
+
+        // Updating a map restores the planned tiles, and discards any tiles
+        // that were planned for other hexes.
+        let mut other = map_2x2_tiles_5_6_58_63();
+        assert!(other.plan_tile(HexAddress::new(0, 0), "14", RotateCW::Zero));
+        descr.update_map(&mut other);
+        assert_eq!(other.plan(), map.plan());
+        assert_eq!(other, map);
+
+        // A description without planned tiles removes all planned tiles.
+        let descr = descr.with_plan(Layer::default());
+        descr.update_map(&mut other);
+        assert!(other.plan().is_empty());
+    }
+
+    #[test]
+    fn test_commit_plan() {
+        let mut map = map_2x2_tiles_5_6_58_63();
+        let addr = HexAddress::new(0, 0);
+        let empty = HexAddress::new(1, 0);
+        map.remove_tile(empty);
+        assert!(map.plan_tile(addr, "14", RotateCW::Zero));
+        assert!(map.plan_tile(empty, "9", RotateCW::One));
+        let placed = map.commit_plan();
+        assert_eq!(placed, vec![addr, empty]);
+        assert!(map.plan().is_empty());
+        assert_eq!(map.tile_at(addr).unwrap().name, "14");
+        assert_eq!(map.tile_at(empty).unwrap().name, "9");
+        assert_eq!(*map.hex_state(empty).unwrap().rotation(), RotateCW::One);
+    }
+}
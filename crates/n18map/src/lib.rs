@@ -5,11 +5,42 @@ pub mod address;
 
 pub mod descr;
 
+pub mod placement;
+
+pub mod marker;
+
+pub mod terrain;
+
+pub mod inspect;
+
+pub mod layer;
+
 #[doc(inline)]
-pub use descr::{Descr, TileDescr};
+pub use descr::{Clipboard, Descr, TileDescr};
 
 #[doc(inline)]
 pub use address::*;
 
 #[doc(inline)]
-pub use map::{EmptyHexIter, HexIter, Map, TileHexIter, TokensTable};
+pub use marker::Marker;
+
+#[doc(inline)]
+pub use terrain::Terrain;
+
+#[doc(inline)]
+pub use layer::{Layer, PlannedTile};
+
+#[doc(inline)]
+pub use inspect::{CityDescription, HexDescription};
+
+#[doc(inline)]
+pub use placement::{
+    Placement, PlacementRules, TokenPlacement, TokenRule, UpgradeRule,
+};
+
+#[doc(inline)]
+pub use map::{HexHalf, Map, Neighbour, TokenChange, TokensTable};
+
+#[cfg(feature = "cairo")]
+#[doc(inline)]
+pub use map::{EmptyHexIter, HexIter, TileHexIter};
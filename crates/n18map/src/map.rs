@@ -1,12 +1,16 @@
+#[cfg(feature = "cairo")]
 use cairo::Context;
 use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use n18catalogue::{Availability, Catalogue};
-use n18hex::{Hex, HexColour, HexFace, Orientation, RotateCW};
-use n18tile::{Label, Tile, TokenSpace};
+use n18hex::{Coord, Hex, HexColour, HexFace, Orientation, RotateCW};
+use n18tile::{Connectivity, Label, Tile, TokenSpace};
 use n18token::{Token, Tokens};
 
-use crate::{Adjacency, HexAddress};
+use crate::{
+    Adjacency, Coordinates, HexAddress, Layer, Letters, Marker, Terrain,
+};
 
 /// A grid of hexes, each of which may contain a [Tile].
 #[derive(Debug, PartialEq, Clone)]
@@ -21,17 +25,169 @@ pub struct Map {
     /// The map state: the tile (if any) placed on each map hex, and other
     /// tile-related details, such as the tile's rotation and placed tokens.
     hexes: BTreeMap<HexAddress, Option<MapTile>>,
+    /// The tiles that were placed on map hexes at the start of the game, or
+    /// `None` if these were never recorded (see [Map::record_initial_tiles]).
+    initial_tiles: Option<BTreeMap<HexAddress, MapTile>>,
     /// City labels that apply to map hexes.
     labels_tbl: BTreeMap<HexAddress, Vec<Label>>,
-    /// The minimum row number for which there is a hex.
-    min_row: isize,
-    /// The minimum column number for which there is a hex.
-    min_col: isize,
+    /// Markers (e.g., port and mine markers) placed on map hexes.
+    pub(crate) markers_tbl: BTreeMap<HexAddress, Vec<Marker>>,
+    /// Decorative terrain (e.g., mountains and lakes) drawn on map hexes.
+    pub(crate) terrain_tbl: BTreeMap<HexAddress, Terrain>,
+    /// Map hexes that are only partially present, such as the half-hexes
+    /// along the edges of the 1825 and 1829 maps.
+    partial_hexes: BTreeMap<HexAddress, HexHalf>,
+    /// The location names of map hexes that form part of a larger location,
+    /// such as an off-board area that spans several hexes.
+    locations: BTreeMap<HexAddress, String>,
+    /// The latest tile colour that can ever be placed on each map hex, where
+    /// [HexColour::Empty] indicates that no tiles can be placed (e.g., on
+    /// pre-printed hexes).
+    max_colours: BTreeMap<HexAddress, HexColour>,
+    /// The tile colour of the current game phase, which determines whether
+    /// token spaces are available (see [n18tile::City::available_from]), or
+    /// `None` if all token spaces are available.
+    phase_colour: Option<HexColour>,
+    /// The game phase that determines the revenue of tiles that have a
+    /// phase revenue label (see [n18tile::Tile::phase_revenue]), or `None`
+    /// if each revenue centre earns its own revenue.
+    revenue_phase: Option<HexColour>,
+    /// The upper-left corner of the map's bounding box, relative to the
+    /// centre of the hex at `(0, 0)`, in units of the maximal hex diameter.
+    origin: (f64, f64),
     /// The orientation of the hexagonal grid.
     orientation: Orientation,
+    /// The coordinate system used to label the rows and columns around the
+    /// map border, or `None` if there are no border labels.
+    border_coords: Option<Coordinates>,
+    /// Identifies the current state of the map (see [Map::revision]).
+    revision: Revision,
+    /// Identifies the current tile definitions (see
+    /// [Map::catalogue_revision]).
+    catalogue_revision: Revision,
+    /// Tiles that are planned but have not been placed (see [Map::plan]).
+    pub(crate) plan: Layer,
+}
+
+/// The source of map revision numbers, which is shared by all maps so that
+/// each revision number is only ever used once.
+static NEXT_REVISION: AtomicU64 = AtomicU64::new(0);
+
+/// Identifies the current state of a map, and changes whenever the map is
+/// modified.
+///
+/// NOTE: revision numbers are ignored when comparing maps, so that maps
+/// with the same contents are equal regardless of how they were modified.
+#[derive(Clone, Copy, Debug)]
+struct Revision(u64);
+
+impl Revision {
+    fn next() -> Self {
+        Revision(NEXT_REVISION.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl PartialEq for Revision {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+/// Identifies the half of a partial map hex that is present on the map.
+///
+/// Partial hexes are drawn clipped to this half, and only this half is used
+/// to determine the map dimensions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HexHalf {
+    /// Only the upper half of the hex is present.
+    Top,
+    /// Only the lower half of the hex is present.
+    Bottom,
+    /// Only the left half of the hex is present.
+    Left,
+    /// Only the right half of the hex is present.
+    Right,
+}
+
+impl HexHalf {
+    /// Returns the bounding box `(x0, y0, x1, y1)` of a (partial) hex,
+    /// relative to the hex centre, in units of the maximal hex diameter.
+    fn unit_extents(
+        half: Option<HexHalf>,
+        orientation: Orientation,
+    ) -> (f64, f64, f64, f64) {
+        let (dx, dy) = match orientation {
+            Orientation::FlatTop => (0.5, 0.5 * Hex::ratio_min_d()),
+            Orientation::PointedTop => (0.5 * Hex::ratio_min_d(), 0.5),
+        };
+        match half {
+            None => (-dx, -dy, dx, dy),
+            Some(HexHalf::Top) => (-dx, -dy, dx, 0.0),
+            Some(HexHalf::Bottom) => (-dx, 0.0, dx, dy),
+            Some(HexHalf::Left) => (-dx, -dy, 0.0, dy),
+            Some(HexHalf::Right) => (0.0, -dy, dx, dy),
+        }
+    }
+
+    /// Restricts drawing to this half of the hex, assuming that the origin
+    /// of `ctx` is the hex centre and that `ctx` has not been rotated.
+    #[cfg(feature = "cairo")]
+    fn clip(&self, hex: &Hex, ctx: &Context) {
+        let d = hex.max_d;
+        match self {
+            HexHalf::Top => ctx.rectangle(-d, -d, 2.0 * d, d),
+            HexHalf::Bottom => ctx.rectangle(-d, 0.0, 2.0 * d, d),
+            HexHalf::Left => ctx.rectangle(-d, -d, d, 2.0 * d),
+            HexHalf::Right => ctx.rectangle(0.0, -d, d, 2.0 * d),
+        }
+        ctx.clip();
+    }
+}
+
+/// Describes where the tile on a map hex meets the tile on an adjacent map
+/// hex; see [Map::neighbours].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Neighbour {
+    /// The face of the tile, relative to the tile's orientation.
+    pub tile_face: HexFace,
+    /// The face of the tile, relative to the map.
+    pub map_face: HexFace,
+    /// The address of the adjacent map hex.
+    pub addr: HexAddress,
+    /// The face of the adjacent tile, relative to its orientation.
+    pub adj_face: HexFace,
+    /// Whether both tiles have track on these faces, so that trains can run
+    /// from one tile to the other.
+    pub connected: bool,
 }
 
 impl Map {
+    /// Returns the revision number of the map, which changes whenever the
+    /// map is modified, and which is never shared by maps with different
+    /// histories.
+    ///
+    /// This allows results that depend on the map state (e.g., optimal
+    /// routes) to be reused until the map is modified.
+    /// Note that any mutable access to a map hex (see [Map::hex_state_mut])
+    /// is treated as a modification.
+    pub fn revision(&self) -> u64 {
+        self.revision.0
+    }
+
+    /// Records that the map has been modified (see [Map::revision]).
+    pub(crate) fn touch(&mut self) {
+        self.revision = Revision::next()
+    }
+
+    /// Returns the revision number of the map catalogue, which changes
+    /// whenever the tile definitions are updated (see [Map::update_tiles]).
+    ///
+    /// This allows results that depend on the tile definitions (e.g., the
+    /// connectivity of each tile) to be reused until the catalogue changes.
+    pub fn catalogue_revision(&self) -> u64 {
+        self.catalogue_revision.0
+    }
+
     /// Returns an iterator over all tiles in the map catalogue.
     ///
     /// This includes tiles that are not available to the player.
@@ -39,6 +195,34 @@ impl Map {
         self.tiles.tile_iter()
     }
 
+    /// Returns the map catalogue.
+    pub fn catalogue(&self) -> &Catalogue {
+        &self.tiles
+    }
+
+    /// Returns the tile in the map catalogue with the given name, if any.
+    ///
+    /// This includes tiles that are not available to the player.
+    pub fn tile(&self, name: &str) -> Option<&Tile> {
+        self.tiles.tile(name)
+    }
+
+    /// Updates the definitions of tiles in the map catalogue, and adds any
+    /// new tiles with unlimited availability.
+    /// Returns the catalogue index of each tile.
+    ///
+    /// Tiles that have been placed on the map are drawn using their updated
+    /// definitions.
+    /// See [Catalogue::update] for details.
+    pub fn update_tiles<T>(&mut self, tiles: T) -> Vec<usize>
+    where
+        T: IntoIterator<Item = Tile>,
+    {
+        self.touch();
+        self.catalogue_revision = Revision::next();
+        self.tiles.update(tiles, Availability::Unlimited)
+    }
+
     /// Returns an iterator over all tiles available to the player.
     ///
     /// Note that this returns all tiles that indicated as being available to
@@ -94,6 +278,15 @@ impl Map {
         }
     }
 
+    /// Returns how many copies of the named tile are currently placed on the
+    /// map.
+    pub fn placed_count(&self, tile_name: &str) -> usize {
+        self.tiles
+            .index_of(tile_name)
+            .map(|ix| self.number_placed(ix))
+            .unwrap_or(0)
+    }
+
     /// Returns `true` if the tile is available to be placed on the map in its
     /// current state, respecting any limits on tile availability.
     pub fn tile_is_available(&self, tile_name: &str) -> bool {
@@ -169,6 +362,14 @@ impl Map {
         })
     }
 
+    /// Adds a token with the given abbreviated name, such as the token of
+    /// a national company that is formed during the game, and returns
+    /// `true` if neither the name nor the token were already defined.
+    pub fn add_token(&mut self, name: String, token: Token) -> bool {
+        self.touch();
+        self.tokens.add(name, token)
+    }
+
     /// Returns the abbreviated name of the given token, if it exists.
     pub fn try_token_name(&self, token: &Token) -> Option<&str> {
         self.tokens.name(token)
@@ -198,6 +399,7 @@ impl Map {
 
     /// Adds a new barrier to a single face of a specific map hex.
     pub fn add_barrier(&mut self, addr: HexAddress, face: HexFace) {
+        self.touch();
         self.barriers.push((addr, face))
     }
 
@@ -206,6 +408,11 @@ impl Map {
         self.hexes.keys()
     }
 
+    /// Returns `true` if `addr` is a valid hex address for this map.
+    pub fn contains_hex(&self, addr: HexAddress) -> bool {
+        self.hexes.contains_key(&addr)
+    }
+
     /// Returns a valid hex address, since all maps contain at least one hex.
     pub fn default_hex(&self) -> HexAddress {
         self.hexes.keys().next().copied().unwrap()
@@ -238,6 +445,7 @@ impl Map {
         &mut self,
         addr: HexAddress,
     ) -> Option<&mut MapTile> {
+        self.touch();
         self.hexes
             .get_mut(&addr)
             .unwrap_or_else(|| panic!("Invalid address {:#?}", addr))
@@ -278,6 +486,98 @@ impl Map {
         placed
     }
 
+    /// Replaces each placed `from` token with the `to` token (e.g., when one
+    /// company merges into another) and returns the changes that were made,
+    /// so that they can be reverted with [Map::revert_token_changes].
+    ///
+    /// A company may only place one token on each tile, so if the `to` token
+    /// has already been placed on the same tile, the `from` token is removed
+    /// instead.
+    pub fn replace_company_tokens(
+        &mut self,
+        from: &Token,
+        to: &Token,
+    ) -> Vec<TokenChange> {
+        self.touch();
+        let mut changes = vec![];
+        if from == to {
+            return changes;
+        }
+        self.hexes.iter_mut().for_each(|(addr, state_opt)| {
+            if let Some(state) = state_opt {
+                let mut has_to = state.tokens.values().any(|t| t == to);
+                let spaces: Vec<TokenSpace> = state
+                    .tokens
+                    .iter()
+                    .filter(|(_space, token)| *token == from)
+                    .map(|(space, _token)| *space)
+                    .collect();
+                for space in spaces {
+                    let after = if has_to {
+                        state.remove_token_at(&space);
+                        None
+                    } else {
+                        state.set_token_at(&space, *to);
+                        Some(*to)
+                    };
+                    has_to = true;
+                    changes.push(TokenChange {
+                        addr: *addr,
+                        space,
+                        before: Some(*from),
+                        after,
+                    });
+                }
+            }
+        });
+        changes
+    }
+
+    /// Removes the token (if any) that has been placed in a token space on
+    /// the specified map hex, and returns the change that was made.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `addr` is not a valid hex address for this map.
+    pub fn remove_token(
+        &mut self,
+        addr: HexAddress,
+        space: &TokenSpace,
+    ) -> Option<TokenChange> {
+        self.touch();
+        let state = self.hex_state_mut(addr)?;
+        let token = state.tokens.remove(space)?;
+        Some(TokenChange {
+            addr,
+            space: *space,
+            before: Some(token),
+            after: None,
+        })
+    }
+
+    /// Reverts token changes, in the reverse order to which they were made.
+    ///
+    /// Changes are ignored for hexes whose current tile no longer contains
+    /// the corresponding token space (e.g., the tile has been removed).
+    pub fn revert_token_changes(&mut self, changes: &[TokenChange]) {
+        self.touch();
+        for change in changes.iter().rev() {
+            let has_space = self
+                .tile_at(change.addr)
+                .map(|tile| tile.token_spaces().contains(&change.space))
+                .unwrap_or(false);
+            if !has_space {
+                continue;
+            }
+            if let Some(state) = self.hex_state_mut(change.addr) {
+                match change.before {
+                    Some(token) => state.set_token_at(&change.space, token),
+                    None => state.remove_token_at(&change.space),
+                }
+            }
+        }
+    }
+
     /// Returns the hex face **relative to the map** that corresponds to the
     /// the specified hex face **relative to the tile's orientation**.
     pub fn map_face_from_tile_face(
@@ -341,6 +641,51 @@ impl Map {
         Some((adj_addr, adj_tile_face, adj_tile))
     }
 
+    /// Returns the connectivity between the hex faces, cities, and dits of
+    /// the tile placed on a map hex, where the hex faces are relative to the
+    /// map, or `None` if there is no tile on this hex.
+    pub fn connectivity_at(&self, addr: HexAddress) -> Option<Connectivity> {
+        let tile = self.tile_at(addr)?;
+        let rotation = self.hex_state(addr)?.rotation;
+        Some(tile.connectivity_at(rotation))
+    }
+
+    /// Returns each face of the tile placed on a map hex that is adjacent to
+    /// a tile on another map hex, ordered clockwise from the top face of the
+    /// tile.
+    ///
+    /// This returns no neighbours if there is no tile on this hex.
+    pub fn neighbours(&self, addr: HexAddress) -> Vec<Neighbour> {
+        let tile = if let Some(tile) = self.tile_at(addr) {
+            tile
+        } else {
+            return vec![];
+        };
+        let conn = tile.connectivity();
+        let mut faces = vec![HexFace::Top];
+        while faces.len() < 6 {
+            faces.push(faces[faces.len() - 1].clockwise());
+        }
+        faces
+            .into_iter()
+            .filter_map(|tile_face| {
+                let map_face =
+                    self.map_face_from_tile_face(addr, tile_face)?;
+                let (adj_addr, adj_face, adj_tile) =
+                    self.adjacent_face(addr, tile_face)?;
+                let connected = conn.has_track(tile_face)
+                    && adj_tile.connectivity().has_track(adj_face);
+                Some(Neighbour {
+                    tile_face,
+                    map_face,
+                    addr: adj_addr,
+                    adj_face,
+                    connected,
+                })
+            })
+            .collect()
+    }
+
     /// Returns the hexagon orientation.
     pub fn orientation(&self) -> Orientation {
         self.orientation
@@ -365,21 +710,300 @@ impl Map {
 
         let barriers = vec![];
         let labels_tbl = BTreeMap::new();
-        let min_col = hexes.keys().map(|hc| hc.col).min().unwrap();
-        let min_row = hexes.keys().map(|hc| hc.row).min().unwrap();
+        let markers_tbl = BTreeMap::new();
+        let terrain_tbl = BTreeMap::new();
+        let partial_hexes = BTreeMap::new();
+        let locations = BTreeMap::new();
+        let max_colours = BTreeMap::new();
 
-        Map {
+        let mut map = Map {
             tokens,
             barriers,
             tiles,
             hexes,
+            initial_tiles: None,
             labels_tbl,
-            min_row,
-            min_col,
+            markers_tbl,
+            terrain_tbl,
+            partial_hexes,
+            locations,
+            max_colours,
+            phase_colour: None,
+            revenue_phase: None,
+            origin: (0.0, 0.0),
             orientation,
+            border_coords: None,
+            revision: Revision::next(),
+            catalogue_revision: Revision::next(),
+            plan: Layer::default(),
+        };
+        map.update_origin();
+        map
+    }
+
+    /// Marks a map hex as only partially present, so that only the
+    /// specified half of the hex will be drawn.
+    ///
+    /// This allows maps with half-hexes along their edges (such as 1825 and
+    /// 1829) to be drawn without any empty space beyond the map edges.
+    ///
+    /// Returns `false` if `addr` is not a valid hex address for this map.
+    pub fn set_partial_hex(
+        &mut self,
+        addr: HexAddress,
+        half: HexHalf,
+    ) -> bool {
+        self.touch();
+        if !self.hexes.contains_key(&addr) {
+            return false;
+        }
+        self.partial_hexes.insert(addr, half);
+        self.update_origin();
+        true
+    }
+
+    /// Marks a map hex as being entirely present.
+    pub fn clear_partial_hex(&mut self, addr: HexAddress) {
+        self.touch();
+        if self.partial_hexes.remove(&addr).is_some() {
+            self.update_origin();
+        }
+    }
+
+    /// Returns the half of the map hex that is present, if the hex is only
+    /// partially present.
+    pub fn partial_hex(&self, addr: HexAddress) -> Option<HexHalf> {
+        self.partial_hexes.get(&addr).copied()
+    }
+
+    /// Returns an iterator over the map hexes that are only partially
+    /// present, and the half of each hex that is present.
+    pub fn partial_hex_iter(
+        &self,
+    ) -> impl Iterator<Item = (HexAddress, HexHalf)> + '_ {
+        self.partial_hexes.iter().map(|(addr, half)| (*addr, *half))
+    }
+
+    /// Marks a map hex as part of a location that spans several hexes, such
+    /// as an off-board area, where all hexes that share the same location
+    /// name form a single location.
+    ///
+    /// Trains can stop at each location at most once, and earn revenue for
+    /// only one of its hexes.
+    ///
+    /// Returns `false` if `addr` is not a valid hex address for this map.
+    pub fn set_location(&mut self, addr: HexAddress, name: &str) -> bool {
+        self.touch();
+        if !self.hexes.contains_key(&addr) {
+            return false;
+        }
+        self.locations.insert(addr, name.to_string());
+        true
+    }
+
+    /// Removes a map hex from the location (if any) that it is part of.
+    pub fn clear_location(&mut self, addr: HexAddress) {
+        self.touch();
+        self.locations.remove(&addr);
+    }
+
+    /// Returns the name of the location that this map hex is part of, if it
+    /// is part of a location that spans several hexes.
+    pub fn location(&self, addr: HexAddress) -> Option<&str> {
+        self.locations.get(&addr).map(|name| name.as_str())
+    }
+
+    /// Returns the map hexes that are part of the named location.
+    pub fn location_hexes(&self, name: &str) -> Vec<HexAddress> {
+        self.locations
+            .iter()
+            .filter(|(_addr, loc)| *loc == name)
+            .map(|(addr, _loc)| *addr)
+            .collect()
+    }
+
+    /// Returns an iterator over the map hexes that are part of a location
+    /// that spans several hexes, and the name of each location.
+    pub fn location_iter(&self) -> impl Iterator<Item = (HexAddress, &str)> {
+        self.locations
+            .iter()
+            .map(|(addr, name)| (*addr, name.as_str()))
+    }
+
+    /// Returns the map hex that identifies the location that contains
+    /// `addr`: the first hex of its location, or `addr` itself if it is not
+    /// part of a location that spans several hexes.
+    pub fn location_anchor(&self, addr: HexAddress) -> HexAddress {
+        self.location(addr)
+            .and_then(|name| self.location_hexes(name).first().copied())
+            .unwrap_or(addr)
+    }
+
+    /// Limits the tiles that can ever be placed on a map hex to those whose
+    /// colour is no later than `colour` (e.g., hexes that can only be
+    /// upgraded to green tiles).
+    /// If `colour` is [HexColour::Empty], no tiles can be placed on this hex
+    /// (e.g., pre-printed hexes that can never be upgraded).
+    ///
+    /// Returns `false` if `addr` is not a valid hex address for this map.
+    pub fn set_max_colour(
+        &mut self,
+        addr: HexAddress,
+        colour: HexColour,
+    ) -> bool {
+        self.touch();
+        if !self.hexes.contains_key(&addr) {
+            return false;
+        }
+        self.max_colours.insert(addr, colour);
+        true
+    }
+
+    /// Allows tiles of any colour to be placed on a map hex.
+    pub fn clear_max_colour(&mut self, addr: HexAddress) {
+        self.touch();
+        self.max_colours.remove(&addr);
+    }
+
+    /// Returns the latest tile colour that can be placed on a map hex, if
+    /// this hex is limited to certain tile colours.
+    pub fn max_colour(&self, addr: HexAddress) -> Option<HexColour> {
+        self.max_colours.get(&addr).copied()
+    }
+
+    /// Returns `true` if tiles of the specified colour can be placed on a map
+    /// hex, according to the hex's [maximum colour](Map::max_colour).
+    pub fn colour_allowed_at(
+        &self,
+        addr: HexAddress,
+        colour: HexColour,
+    ) -> bool {
+        let max_colour = match self.max_colour(addr) {
+            Some(max_colour) => max_colour,
+            None => return true,
+        };
+        match (colour.phase_number(), max_colour.phase_number()) {
+            (Some(phase), Some(max_phase)) => phase <= max_phase,
+            _ => false,
         }
     }
 
+    /// Sets the tile colour of the current game phase, which determines
+    /// whether token spaces are available (see
+    /// [n18tile::City::available_from]).
+    pub fn set_phase_colour(&mut self, colour: HexColour) {
+        self.touch();
+        self.phase_colour = Some(colour);
+    }
+
+    /// Returns the tile colour of the current game phase, if it has been
+    /// set.
+    pub fn phase_colour(&self) -> Option<HexColour> {
+        self.phase_colour
+    }
+
+    /// Sets the game phase that determines the revenue of tiles that have a
+    /// phase revenue label (see [n18tile::Tile::phase_revenue]), such as
+    /// off-board tiles, and highlights this revenue on each of these tiles
+    /// (see [n18tile::Tile::set_revenue_phase]).
+    pub fn set_revenue_phase(&mut self, colour: HexColour) {
+        self.touch();
+        self.revenue_phase = Some(colour);
+        self.tiles.set_revenue_phase(colour);
+    }
+
+    /// Returns the game phase that determines the revenue of tiles that have
+    /// a phase revenue label, if it has been set.
+    pub fn revenue_phase(&self) -> Option<HexColour> {
+        self.revenue_phase
+    }
+
+    /// Returns `true` if the token space `space` on the tile at map hex
+    /// `addr` is available in the current game phase (see
+    /// [n18tile::City::available_from]).
+    ///
+    /// All token spaces are available if the phase colour has not been set.
+    pub fn token_space_available(
+        &self,
+        addr: HexAddress,
+        space: &TokenSpace,
+    ) -> bool {
+        let Some(phase) = self.phase_colour else {
+            return true;
+        };
+        self.tile_at(addr)
+            .and_then(|tile| tile.cities().get(space.city_ix()))
+            .map(|city| city.available_in(phase))
+            .unwrap_or(true)
+    }
+
+    /// Returns the centre of a map hex, relative to the centre of the hex at
+    /// `(0, 0)`, in units of the maximal hex diameter.
+    ///
+    /// Note that this accepts any valid row and column, so it can be used to
+    /// locate the centre of hexes that are not part of the map itself.
+    fn unit_offset(&self, row: isize, col: isize) -> (f64, f64) {
+        let min_d = Hex::ratio_min_d();
+        // NOTE: use rem_euclid() so that negative odd-numbered rows and
+        // columns are correctly identified.
+        match self.orientation {
+            Orientation::FlatTop => {
+                let x = (col as f64) * 0.75;
+                let y = if col.rem_euclid(2) == 1 {
+                    (row as f64 + 0.5) * min_d
+                } else {
+                    (row as f64) * min_d
+                };
+                (x, y)
+            }
+            Orientation::PointedTop => {
+                let x = if row.rem_euclid(2) == 1 {
+                    (col as f64 + 0.5) * min_d
+                } else {
+                    (col as f64) * min_d
+                };
+                let y = (row as f64) * 0.75;
+                (x, y)
+            }
+        }
+    }
+
+    /// Recalculates the upper-left corner of the map's bounding box.
+    ///
+    /// This accounts for offset layouts where the first row or column is
+    /// "shoved" down or right, and for partial hexes along the map edges.
+    fn update_origin(&mut self) {
+        let (x0, y0, _x1, _y1) = self.unit_bounds();
+        self.origin = (x0, y0);
+    }
+
+    /// Returns the map's bounding box `(x0, y0, x1, y1)`, relative to the
+    /// centre of the hex at `(0, 0)`, in units of the maximal hex diameter.
+    ///
+    /// An empty map has an empty bounding box at `(0, 0)`.
+    fn unit_bounds(&self) -> (f64, f64, f64, f64) {
+        if self.hexes.is_empty() {
+            return (0.0, 0.0, 0.0, 0.0);
+        }
+        let init = (
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        );
+        self.hexes
+            .keys()
+            .map(|addr| {
+                let (x, y) = self.unit_offset(addr.row, addr.col);
+                let half = self.partial_hex(*addr);
+                let exts = HexHalf::unit_extents(half, self.orientation);
+                (x + exts.0, y + exts.1, x + exts.2, y + exts.3)
+            })
+            .fold(init, |acc, (x0, y0, x1, y1)| {
+                (acc.0.min(x0), acc.1.min(y0), acc.2.max(x1), acc.3.max(y1))
+            })
+    }
+
     /// Returns the current tile, if any, placed at the specified hex address.
     ///
     /// # Panics
@@ -406,6 +1030,7 @@ impl Map {
         new: &str,
         rot: RotateCW,
     ) -> Option<bool> {
+        self.touch();
         let do_replace = if let Some(hs) = self.hex_state(addr) {
             if !hs.replaceable {
                 // This tile cannot be replaced.
@@ -435,6 +1060,7 @@ impl Map {
         tile: &str,
         rot: RotateCW,
     ) -> bool {
+        self.touch();
         let tile_ix = if let Some(ix) = self.tiles.index_of(tile) {
             ix
         } else {
@@ -483,6 +1109,7 @@ impl Map {
 
     /// Removes the current tile, if any, from the specified map hex.
     pub fn remove_tile(&mut self, addr: HexAddress) {
+        self.touch();
         // NOTE: must ensure that this is a valid hex address.
         // Otherwise, this would add a new hex to the map.
         if self.hexes.contains_key(&addr) {
@@ -490,6 +1117,79 @@ impl Map {
         }
     }
 
+    /// Records the tile (if any) on each map hex as that hex's initial
+    /// state, which can be restored with [Map::reset_hex].
+    ///
+    /// This should be called once the starting tiles have been placed on a
+    /// new map (e.g., pre-printed cities and off-board areas).
+    /// Hexes cannot be reset until the initial tiles have been recorded.
+    pub fn record_initial_tiles(&mut self) {
+        self.initial_tiles = Some(
+            self.hexes
+                .iter()
+                .filter_map(|(addr, hex_state)| {
+                    hex_state.as_ref().map(|hs| (*addr, hs.clone()))
+                })
+                .collect(),
+        );
+    }
+
+    /// Returns the tile and rotation that were initially placed on a map
+    /// hex (see [Map::record_initial_tiles]), or `None` if the hex was
+    /// initially empty.
+    pub fn initial_tile(
+        &self,
+        addr: HexAddress,
+    ) -> Option<(&Tile, RotateCW)> {
+        self.initial_tiles
+            .as_ref()?
+            .get(&addr)
+            .map(|hs| (&self.tiles[hs.tile_ix].0, hs.rotation))
+    }
+
+    /// Returns `true` if a map hex contains its initial tile, rotation, and
+    /// tokens (see [Map::record_initial_tiles]).
+    ///
+    /// Returns `false` if the initial tiles were never recorded.
+    pub fn is_initial_state(&self, addr: HexAddress) -> bool {
+        let Some(initial_tiles) = &self.initial_tiles else {
+            return false;
+        };
+        match self.hexes.get(&addr) {
+            Some(hex_state) => hex_state.as_ref() == initial_tiles.get(&addr),
+            None => false,
+        }
+    }
+
+    /// Returns `true` if a map hex can be restored to its initial state
+    /// with [Map::reset_hex], because the initial tiles have been recorded
+    /// and the hex is not already in its initial state.
+    pub fn can_reset_hex(&self, addr: HexAddress) -> bool {
+        self.initial_tiles.is_some()
+            && self.hexes.contains_key(&addr)
+            && !self.is_initial_state(addr)
+    }
+
+    /// Restores a map hex to its initial state (see
+    /// [Map::record_initial_tiles]), replacing the current tile and removing
+    /// any tokens that have been placed on it.
+    ///
+    /// Returns `false`, and does not modify the map, if `addr` is not a
+    /// valid hex address for this map or if the initial tiles were never
+    /// recorded.
+    pub fn reset_hex(&mut self, addr: HexAddress) -> bool {
+        let Some(initial_tiles) = &self.initial_tiles else {
+            return false;
+        };
+        if !self.hexes.contains_key(&addr) {
+            return false;
+        }
+        let initial = initial_tiles.get(&addr).cloned();
+        self.touch();
+        self.hexes.insert(addr, initial);
+        true
+    }
+
     /// Defines labels for a specific map hex, such as [Label::CityKind] and
     /// [Label::City], which are used to identify valid upgrade tiles.
     ///
@@ -502,6 +1202,7 @@ impl Map {
     /// (i.e., you can place an "O" tile on top of a "Y" tile, if the hex has
     /// both "O" and "Y" labels).
     pub fn add_label_at(&mut self, addr: HexAddress, label: Label) {
+        self.touch();
         self.labels_tbl.entry(addr).or_default().push(label)
     }
 
@@ -538,27 +1239,54 @@ impl Map {
     }
 
     /// Check whether a tile can be upgraded to another tile, given the
-    /// current map state and respecting any limits on tile availability.
+    /// current map state and respecting any limits on tile availability and
+    /// tile colour.
     pub fn can_upgrade_to(&self, addr: HexAddress, tile: &Tile) -> bool {
         // Check if the tile is available.
         if !self.tile_is_available(&tile.name) {
             return false;
         }
 
-        if let Some(hex_labels) = self.labels_tbl.get(&addr) {
-            // Check that the tile has at least one tile-restriction label in
-            // common with this hex.
+        // Check that tiles of this colour can be placed on this hex.
+        if !self.colour_allowed_at(addr, tile.colour) {
+            return false;
+        }
+
+        self.tile_labels_allowed_at(addr, tile)
+    }
+
+    /// Returns `true` if the tile-restriction labels of `tile` (see
+    /// [Label::is_tile_restriction]) allow it to be placed on a map hex.
+    ///
+    /// If the hex has labels (see [Map::add_label_at]), the tile must have
+    /// at least one tile-restriction label in common with the hex.
+    /// Otherwise, the tile-restriction labels of the current tile (if any)
+    /// apply, so that a labelled tile can only be replaced by a tile with a
+    /// matching label.
+    /// If neither the hex nor its current tile have any tile-restriction
+    /// labels, the tile must not have any tile-restriction labels.
+    pub fn tile_labels_allowed_at(
+        &self,
+        addr: HexAddress,
+        tile: &Tile,
+    ) -> bool {
+        let restrictions = |tile: &Tile| -> Vec<Label> {
             tile.labels()
                 .iter()
-                .filter(|(label, _posn)| label.is_tile_restriction())
-                .any(|(label, _posn)| hex_labels.contains(label))
+                .map(|(label, _posn)| label)
+                .filter(|label| label.is_tile_restriction())
+                .cloned()
+                .collect()
+        };
+        let hex_labels: Vec<Label> = match self.labels_tbl.get(&addr) {
+            Some(labels) => labels.clone(),
+            None => self.tile_at(addr).map(restrictions).unwrap_or_default(),
+        };
+        let tile_labels = restrictions(tile);
+        if hex_labels.is_empty() {
+            tile_labels.is_empty()
         } else {
-            // Check that this tile has no tile-restriction labels.
-            tile.labels()
-                .iter()
-                .filter(|(label, _posn)| label.is_tile_restriction())
-                .count()
-                == 0
+            tile_labels.iter().any(|label| hex_labels.contains(label))
         }
     }
 
@@ -574,49 +1302,126 @@ impl Map {
         y0: f64,
         hex: &Hex,
     ) -> (f64, f64) {
-        let row = row - self.min_row;
-        let col = col - self.min_col;
+        let (x, y) = self.unit_offset(row, col);
+        (x0 + x * hex.max_d, y0 + y * hex.max_d)
+    }
 
-        match self.orientation {
-            Orientation::FlatTop => {
-                let x = x0 + (col as f64) * hex.max_d * 0.75;
-                let y = if (col + self.min_col) % 2 == 1 {
-                    y0 + (row as f64 + 0.5) * hex.min_d
-                } else {
-                    y0 + (row as f64) * hex.min_d
-                };
-                (x, y)
-            }
-            Orientation::PointedTop => {
-                let x = if (row + self.min_row) % 2 == 1 {
-                    x0 + (col as f64 + 0.5) * hex.min_d
-                } else {
-                    x0 + (col as f64) * hex.min_d
-                };
-                let y = y0 + (row as f64) * hex.max_d * 0.75;
-                (x, y)
-            }
+    /// Returns the width of the margin between the map edges and the
+    /// drawing surface, which includes space for any border labels.
+    fn margin(&self, hex: &Hex) -> f64 {
+        let geometry = hex.geometry();
+        let margin = geometry.map_margin.margin(hex);
+        if self.border_coords.is_some() {
+            margin + geometry.border_margin.margin(hex)
+        } else {
+            margin
         }
     }
 
-    /// Returns the hexagon x-origin coordinate for the map.
+    /// Returns the x coordinate of the centre of the hex at `(0, 0)`.
     fn hex_x0(&self, hex: &Hex) -> f64 {
-        use Orientation::*;
-        let margin = hex.theme.map_margin.absolute(hex);
-        match self.orientation {
-            FlatTop => 0.5 * hex.max_d + margin,
-            PointedTop => 0.5 * hex.min_d + margin,
-        }
+        self.margin(hex) - self.origin.0 * hex.max_d
     }
 
-    /// Returns the hexagon y-origin coordinate for the map.
+    /// Returns the y coordinate of the centre of the hex at `(0, 0)`.
     fn hex_y0(&self, hex: &Hex) -> f64 {
-        use Orientation::*;
-        let margin = hex.theme.map_margin.absolute(hex);
-        match self.orientation {
-            FlatTop => 0.5 * hex.min_d + margin,
-            PointedTop => 0.5 * hex.max_d + margin,
+        self.margin(hex) - self.origin.1 * hex.max_d
+    }
+
+    /// Returns the bounding box `(x0, y0, width, height)` of the map hexes,
+    /// in pixels, for the hexagon size defined by `hex`.
+    ///
+    /// This excludes the margin around the map edges (which includes space
+    /// for any border labels), so `(x0, y0)` is offset from the origin by the
+    /// width of this margin.
+    /// Empty regions outside of the map hexes, such as the corner of an
+    /// L-shaped map, are included in the bounding box.
+    pub fn bounding_box(&self, hex: &Hex) -> (f64, f64, f64, f64) {
+        let margin = self.margin(hex);
+        let (x0, y0, x1, y1) = self.unit_bounds();
+        (margin, margin, (x1 - x0) * hex.max_d, (y1 - y0) * hex.max_d)
+    }
+
+    /// Returns the width and height, in pixels, of the surface required to
+    /// draw the map, including the margin around the map edges.
+    pub fn drawing_size(&self, hex: &Hex) -> (f64, f64) {
+        let (x0, y0, width, height) = self.bounding_box(hex);
+        (width + 2.0 * x0, height + 2.0 * y0)
+    }
+
+    /// Returns the coordinate system used to label the rows and columns
+    /// around the map border, if any.
+    pub fn border_coordinates(&self) -> Option<Coordinates> {
+        self.border_coords
+    }
+
+    /// Sets the coordinate system used to label the rows and columns around
+    /// the map border, or removes the border labels if `coords` is `None`.
+    ///
+    /// Note that border labels increase the margin around the map edges by
+    /// the width of [Theme::border_margin](n18hex::Theme::border_margin).
+    pub fn set_border_coordinates(&mut self, coords: Option<Coordinates>) {
+        self.touch();
+        self.border_coords = coords
+    }
+
+    /// Returns the text and location of each label around the map border,
+    /// in the user coordinates of `hex.context()`.
+    ///
+    /// Each column is labelled above and below the map, and each row is
+    /// labelled to the left and right of the map.
+    /// This returns no labels if [Map::border_coordinates] is `None`.
+    pub fn border_labels(&self, hex: &Hex) -> Vec<(String, Coord)> {
+        let coords = if let Some(coords) = self.border_coords {
+            coords
+        } else {
+            return vec![];
+        };
+
+        // Identify the column and row labels, and their locations.
+        let x0 = self.hex_x0(hex);
+        let y0 = self.hex_y0(hex);
+        let mut columns: BTreeMap<String, f64> = BTreeMap::new();
+        let mut rows: BTreeMap<String, f64> = BTreeMap::new();
+        for addr in self.hexes.keys() {
+            let text = if let Some(text) = coords.format(addr) {
+                text
+            } else {
+                continue;
+            };
+            let ix = text
+                .find(|ch: char| !ch.is_ascii_uppercase())
+                .unwrap_or(text.len());
+            let (letters, digits) = text.split_at(ix);
+            let (column, row) = match coords.letters {
+                Letters::AsColumns => (letters, digits),
+                Letters::AsRows => (digits, letters),
+            };
+            let (x, y) = self.hex_centre(addr.row, addr.col, x0, y0, hex);
+            columns.entry(column.to_string()).or_insert(x);
+            rows.entry(row.to_string()).or_insert(y);
         }
+
+        // Place the labels in the middle of the border margin.
+        let (ux0, uy0, ux1, uy1) = self.unit_bounds();
+        let offset = 0.5 * hex.geometry().border_margin.margin(hex);
+        let left = x0 + ux0 * hex.max_d - offset;
+        let right = x0 + ux1 * hex.max_d + offset;
+        let top = y0 + uy0 * hex.max_d - offset;
+        let bottom = y0 + uy1 * hex.max_d + offset;
+        let column_labels = columns.into_iter().flat_map(|(text, x)| {
+            [
+                (text.clone(), Coord::from((x, top))),
+                (text, Coord::from((x, bottom))),
+            ]
+        });
+        let row_labels = rows.into_iter().flat_map(|(text, y)| {
+            [
+                (text.clone(), Coord::from((left, y))),
+                (text, Coord::from((right, y))),
+            ]
+        });
+        column_labels.chain(row_labels).collect()
     }
 
     /// Translates and rotates the provided context `ctx` in preparation for
@@ -647,6 +1452,7 @@ impl Map {
     ///     ctx.set_matrix(m);
     /// }
     /// ```
+    #[cfg(feature = "cairo")]
     pub fn prepare_to_draw(
         &self,
         addr: HexAddress,
@@ -677,6 +1483,30 @@ impl Map {
         m
     }
 
+    /// Returns the token space on the tile placed at the specified map hex
+    /// that contains the point `(x, y)`, if any.
+    ///
+    /// The point is defined in the user coordinates of `hex.context()`, and
+    /// the location and rotation of the tile are taken into account.
+    /// See [Tile::token_space_at_point] for further details.
+    #[cfg(feature = "cairo")]
+    pub fn token_space_at_point(
+        &self,
+        addr: HexAddress,
+        x: f64,
+        y: f64,
+        hex: &Hex,
+    ) -> Option<TokenSpace> {
+        let tile = self.tile_at(addr)?;
+        let ctx = hex.context();
+        let m = self.prepare_to_draw(addr, hex, ctx);
+        let (dev_x, dev_y) = m.transform_point(x, y);
+        let tile_coords = ctx.device_to_user(dev_x, dev_y);
+        ctx.set_matrix(m);
+        let (tile_x, tile_y) = tile_coords.ok()?;
+        tile.token_space_at_point(tile_x, tile_y, hex)
+    }
+
     /// Returns an iterator over all map hexes.
     ///
     /// At each iteration, the transformation matrix will be updated to
@@ -708,6 +1538,7 @@ impl Map {
     ///     ctx.stroke().unwrap();
     /// }
     /// ```
+    #[cfg(feature = "cairo")]
     pub fn hex_iter<'a>(
         &'a self,
         hex: &'a Hex,
@@ -721,6 +1552,7 @@ impl Map {
     ///
     /// At each iteration, the transformation matrix will be updated to
     /// account for the current hex's location and orientation.
+    #[cfg(feature = "cairo")]
     pub fn hex_subset_iter<'a, P: FnMut(&HexAddress) -> bool>(
         &'a self,
         hex: &'a Hex,
@@ -767,6 +1599,7 @@ impl Map {
     ///     ctx.fill();
     /// }
     /// ```
+    #[cfg(feature = "cairo")]
     pub fn empty_hex_iter<'a>(
         &'a self,
         hex: &'a Hex,
@@ -809,6 +1642,7 @@ impl Map {
     ///     }
     /// }
     /// ```
+    #[cfg(feature = "cairo")]
     pub fn tile_hex_iter<'a>(
         &'a self,
         hex: &'a Hex,
@@ -905,6 +1739,7 @@ pub fn try_placing_tokens(
 pub type TileState<'a> = (&'a Tile, &'a TokensTable);
 
 /// An iterator over each hex in a `Map`.
+#[cfg(feature = "cairo")]
 pub struct HexIter<'a> {
     hex: &'a Hex,
     ctx: &'a Context,
@@ -914,14 +1749,26 @@ pub struct HexIter<'a> {
     iter: std::collections::btree_map::Iter<'a, HexAddress, Option<MapTile>>,
     m: cairo::Matrix,
     include: Option<BTreeSet<HexAddress>>,
+    /// Whether the context has been clipped to draw a partial hex.
+    clipped: bool,
 }
 
+#[cfg(feature = "cairo")]
 impl<'a> HexIter<'a> {
     pub fn restart(&mut self) {
+        self.release_clip();
         self.ctx.set_matrix(self.m);
         self.iter = self.map.hexes.iter();
     }
 
+    /// Removes the clip region (if any) that was applied for a partial hex.
+    fn release_clip(&mut self) {
+        if self.clipped {
+            self.ctx.restore().unwrap();
+            self.clipped = false;
+        }
+    }
+
     /// Returns a reference to the map associated with this hex iterator.
     ///
     /// Call this method with `HexIter::map(hex_iter)` in order to distinguish
@@ -944,6 +1791,7 @@ impl<'a> HexIter<'a> {
             iter,
             m: ctx.matrix(),
             include: None,
+            clipped: false,
         }
     }
 
@@ -966,6 +1814,7 @@ impl<'a> HexIter<'a> {
             iter,
             m: ctx.matrix(),
             include: Some(include),
+            clipped: false,
         }
     }
 
@@ -975,6 +1824,15 @@ impl<'a> HexIter<'a> {
     }
 }
 
+#[cfg(feature = "cairo")]
+impl Drop for HexIter<'_> {
+    fn drop(&mut self) {
+        // NOTE: ensure that the context is not left clipped if the iterator
+        // is dropped before it has been exhausted.
+        self.release_clip();
+    }
+}
+
 /// The state of a map hex that may, or may not, contain a tile.
 pub struct HexState<'a> {
     pub addr: HexAddress,
@@ -983,6 +1841,7 @@ pub struct HexState<'a> {
     pub tile_rotation: f64,
 }
 
+#[cfg(feature = "cairo")]
 impl<'a> Iterator for HexIter<'a> {
     type Item = HexState<'a>;
 
@@ -999,6 +1858,7 @@ impl<'a> Iterator for HexIter<'a> {
             }
             entry_opt = self.iter.next();
         }
+        self.release_clip();
         let (&addr, hex_state_opt) = if let Some(entry) = entry_opt {
             (entry.0, entry.1)
         } else {
@@ -1012,6 +1872,14 @@ impl<'a> Iterator for HexIter<'a> {
         self.ctx.set_matrix(self.m);
         self.ctx.translate(x, y);
 
+        // NOTE: clip partial hexes before rotating the context, because the
+        // clip region is defined relative to the map, not the tile.
+        if let Some(half) = self.map.partial_hex(addr) {
+            self.ctx.save().unwrap();
+            half.clip(self.hex, self.ctx);
+            self.clipped = true;
+        }
+
         if let Some(hex_state) = hex_state_opt {
             self.ctx.rotate(hex_state.rotation.radians());
             let tile_state = Some((
@@ -1036,10 +1904,12 @@ impl<'a> Iterator for HexIter<'a> {
 }
 
 /// An iterator over each hex in a `Map` that does not contain a `Tile`.
+#[cfg(feature = "cairo")]
 pub struct EmptyHexIter<'a> {
     iter: HexIter<'a>,
 }
 
+#[cfg(feature = "cairo")]
 impl<'a> EmptyHexIter<'a> {
     fn new(iter: HexIter<'a>) -> Self {
         EmptyHexIter { iter }
@@ -1050,6 +1920,7 @@ impl<'a> EmptyHexIter<'a> {
     }
 }
 
+#[cfg(feature = "cairo")]
 impl<'a> Iterator for EmptyHexIter<'a> {
     type Item = HexAddress;
 
@@ -1065,6 +1936,7 @@ impl<'a> Iterator for EmptyHexIter<'a> {
     }
 }
 
+#[cfg(feature = "cairo")]
 impl<'a> From<HexIter<'a>> for EmptyHexIter<'a> {
     fn from(src: HexIter<'a>) -> Self {
         Self::new(src)
@@ -1072,10 +1944,12 @@ impl<'a> From<HexIter<'a>> for EmptyHexIter<'a> {
 }
 
 /// An iterator over each hex in a `Map` that contains a `Tile`.
+#[cfg(feature = "cairo")]
 pub struct TileHexIter<'a> {
     iter: HexIter<'a>,
 }
 
+#[cfg(feature = "cairo")]
 impl<'a> TileHexIter<'a> {
     fn new(iter: HexIter<'a>) -> Self {
         TileHexIter { iter }
@@ -1096,6 +1970,7 @@ pub struct TileHexState<'a> {
     pub tile_rotation: f64,
 }
 
+#[cfg(feature = "cairo")]
 impl<'a> Iterator for TileHexIter<'a> {
     type Item = TileHexState<'a>;
 
@@ -1117,12 +1992,26 @@ impl<'a> Iterator for TileHexIter<'a> {
     }
 }
 
+#[cfg(feature = "cairo")]
 impl<'a> From<HexIter<'a>> for TileHexIter<'a> {
     fn from(src: HexIter<'a>) -> Self {
         Self::new(src)
     }
 }
 
+/// Records a change to the token (if any) placed in a token space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenChange {
+    /// The map hex that contains the token space.
+    pub addr: HexAddress,
+    /// The token space whose token was changed.
+    pub space: TokenSpace,
+    /// The token that was placed in this space before the change.
+    pub before: Option<Token>,
+    /// The token that was placed in this space after the change.
+    pub after: Option<Token>,
+}
+
 /// Describes the placement of a specific tile on a map hex.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MapTile {
@@ -1155,6 +2044,11 @@ impl MapTile {
         &self.rotation
     }
 
+    /// Returns `true` if this tile can be replaced by another tile.
+    pub fn is_replaceable(&self) -> bool {
+        self.replaceable
+    }
+
     pub fn token_at(&self, space: &TokenSpace) -> Option<&Token> {
         self.tokens.get(space)
     }
@@ -1178,9 +2072,416 @@ impl MapTile {
 
 #[cfg(test)]
 mod tests {
-    use super::HexAddress;
+    #[cfg(feature = "cairo")]
+    use super::HexHalf;
+    use super::{HexAddress, Map};
+    #[cfg(feature = "cairo")]
+    use n18hex::Hex;
+    use n18hex::Orientation;
     use n18tile::{Connection, TrackEnd};
 
+    /// Returns the translation applied by [Map::prepare_to_draw] for the
+    /// specified map hex.
+    #[cfg(feature = "cairo")]
+    fn hex_centre(map: &Map, addr: HexAddress, hex: &Hex) -> (f64, f64) {
+        let ctx = hex.context();
+        let m = map.prepare_to_draw(addr, hex, ctx);
+        let (x, y) = ctx.matrix().transform_point(0.0, 0.0);
+        let (x_orig, y_orig) = m.transform_point(0.0, 0.0);
+        ctx.set_matrix(m);
+        (x - x_orig, y - y_orig)
+    }
+
+    fn new_map(hexes: &[(isize, isize)]) -> Map {
+        let hexes: Vec<HexAddress> = hexes.iter().map(|a| a.into()).collect();
+        Map::new(
+            n18catalogue::tile_catalogue().into(),
+            vec![].into(),
+            hexes,
+            Orientation::FlatTop,
+        )
+    }
+
+    #[test]
+    fn test_placed_count() {
+        use n18hex::RotateCW;
+
+        let mut map = new_map(&[(0, 0), (0, 1), (1, 0)]);
+        assert_eq!(map.placed_count("8"), 0);
+        map.place_tile((0, 0).into(), "8", RotateCW::Zero);
+        map.place_tile((0, 1).into(), "8", RotateCW::One);
+        map.place_tile((1, 0).into(), "9", RotateCW::Zero);
+        assert_eq!(map.placed_count("8"), 2);
+        assert_eq!(map.placed_count("9"), 1);
+        assert_eq!(map.placed_count("no such tile"), 0);
+    }
+
+    #[test]
+    fn test_reset_hex() {
+        use n18hex::RotateCW;
+
+        let mut map = new_map(&[(0, 0), (0, 1), (1, 0)]);
+        let (a, b, c) = ((0, 0).into(), (0, 1).into(), (1, 0).into());
+        assert!(map.place_tile(a, "5", RotateCW::Two));
+
+        // Hexes cannot be reset until the initial tiles are recorded.
+        assert!(!map.can_reset_hex(a));
+        assert!(!map.reset_hex(a));
+        assert_eq!(map.tile_at(a).unwrap().name, "5");
+        assert!(map.initial_tile(a).is_none());
+
+        map.record_initial_tiles();
+        assert_eq!(map.initial_tile(a).unwrap().0.name, "5");
+        assert_eq!(map.initial_tile(a).unwrap().1, RotateCW::Two);
+        assert!(map.initial_tile(b).is_none());
+        assert!(map.is_initial_state(a) && map.is_initial_state(b));
+
+        // Upgrade the initial tile and place a tile on an empty hex.
+        assert!(map.place_tile(a, "15", RotateCW::Zero));
+        assert!(map.place_tile(b, "8", RotateCW::One));
+        assert!(!map.is_initial_state(a) && !map.is_initial_state(b));
+        assert!(map.is_initial_state(c));
+        assert!(map.can_reset_hex(a) && !map.can_reset_hex(c));
+
+        // Resetting each hex restores its initial tile, or removes the tile.
+        assert!(map.reset_hex(a));
+        assert_eq!(map.tile_at(a).unwrap().name, "5");
+        assert_eq!(*map.hex_state(a).unwrap().rotation(), RotateCW::Two);
+        assert!(map.reset_hex(b));
+        assert!(map.tile_at(b).is_none());
+        assert!(map.is_initial_state(a) && map.is_initial_state(b));
+        assert!(!map.reset_hex((5, 5).into()));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        use n18hex::{HexFace, RotateCW};
+
+        let mut map = new_map(&[(0, 0), (0, 1), (1, 0)]);
+        assert!(map.neighbours((0, 0).into()).is_empty());
+        assert!(map.connectivity_at((0, 0).into()).is_none());
+        map.place_tile((0, 0).into(), "9", RotateCW::Zero);
+        map.place_tile((0, 1).into(), "9", RotateCW::Zero);
+        map.place_tile((1, 0).into(), "9", RotateCW::Three);
+
+        // The straight track runs between the top and bottom faces.
+        let conn = map.connectivity_at((0, 0).into()).unwrap();
+        assert_eq!(
+            conn.faces_from_face(HexFace::Top),
+            [HexFace::Bottom].into()
+        );
+
+        // The tile below is connected, but the tile to the lower right has
+        // no track on the adjacent face.
+        let nbrs = map.neighbours((0, 0).into());
+        assert_eq!(nbrs.len(), 2);
+        assert_eq!(nbrs[0].tile_face, HexFace::LowerRight);
+        assert_eq!(nbrs[0].addr, (0, 1).into());
+        assert_eq!(nbrs[0].adj_face, HexFace::UpperLeft);
+        assert!(!nbrs[0].connected);
+        assert_eq!(nbrs[1].tile_face, HexFace::Bottom);
+        assert_eq!(nbrs[1].addr, (1, 0).into());
+        assert!(nbrs[1].connected);
+
+        // Faces are reported relative to each tile's orientation.
+        let nbrs = map.neighbours((1, 0).into());
+        assert_eq!(nbrs.len(), 2);
+        assert_eq!(nbrs[0].map_face, HexFace::Top);
+        assert_eq!(nbrs[0].tile_face, HexFace::Bottom);
+        assert_eq!(nbrs[0].adj_face, HexFace::Bottom);
+        assert!(nbrs[0].connected);
+        assert_eq!(nbrs[1].map_face, HexFace::UpperRight);
+        assert_eq!(nbrs[1].tile_face, HexFace::LowerLeft);
+        assert_eq!(nbrs[1].addr, (0, 1).into());
+        assert!(!nbrs[1].connected);
+    }
+
+    #[cfg(feature = "cairo")]
+    #[test]
+    fn test_offset_layout() {
+        let hex = Hex::new(100.0);
+        let margin = hex.geometry().map_margin.margin(&hex);
+
+        // The first hex is in the first row and is not "shoved down".
+        let map = new_map(&[(0, 0), (0, 1), (1, 0), (1, 1)]);
+        let (x, y) = hex_centre(&map, (0, 0).into(), &hex);
+        assert!((x - margin - 0.5 * hex.max_d).abs() < 1e-8);
+        assert!((y - margin - 0.5 * hex.min_d).abs() < 1e-8);
+
+        // The first row only contains odd-numbered columns, which are
+        // "shoved down", so there should be no empty space above them.
+        let map = new_map(&[(0, 1), (0, 3), (1, 0), (1, 1), (1, 2)]);
+        let (_x, y) = hex_centre(&map, (0, 1).into(), &hex);
+        assert!((y - margin - 0.5 * hex.min_d).abs() < 1e-8);
+
+        // Negative odd-numbered columns should also be "shoved down".
+        let map = new_map(&[(0, -1), (1, -2), (1, -1)]);
+        let (_x, y) = hex_centre(&map, (0, -1).into(), &hex);
+        assert!((y - margin - 0.5 * hex.min_d).abs() < 1e-8);
+    }
+
+    #[cfg(feature = "cairo")]
+    #[test]
+    fn test_border_labels() {
+        use crate::{FirstRow, Letters};
+
+        let hex = Hex::new(100.0);
+        let margin = hex.geometry().map_margin.margin(&hex);
+        let border = hex.geometry().border_margin.margin(&hex);
+        let mut map = new_map(&[(0, 0), (0, 1), (1, 0), (1, 1)]);
+        assert!(map.border_labels(&hex).is_empty());
+
+        // Border labels should increase the margin around the map.
+        let (x_before, y_before) = hex_centre(&map, (0, 0).into(), &hex);
+        let coords =
+            (Orientation::FlatTop, Letters::AsRows, FirstRow::OddColumns)
+                .into();
+        map.set_border_coordinates(Some(coords));
+        assert_eq!(map.border_coordinates(), Some(coords));
+        let (x, y) = hex_centre(&map, (0, 0).into(), &hex);
+        assert!((x - x_before - border).abs() < 1e-8);
+        assert!((y - y_before - border).abs() < 1e-8);
+
+        // The hexes are "A1", "B2", "C1", and "D2", so there should be two
+        // column labels and four row labels, each drawn on opposite sides.
+        let labels = map.border_labels(&hex);
+        assert_eq!(labels.len(), 12);
+        let texts: Vec<&str> =
+            labels.iter().map(|(text, _)| text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec!["1", "1", "2", "2", "A", "A", "B", "B", "C", "C", "D", "D"]
+        );
+
+        // The first column labels are aligned with the first column, and
+        // are centred in the border margin above and below the map.
+        let (_, top) = &labels[0];
+        let (_, bottom) = &labels[1];
+        assert!((top.x - x).abs() < 1e-8);
+        assert!((bottom.x - x).abs() < 1e-8);
+        assert!((top.y - margin - 0.5 * border).abs() < 1e-8);
+        assert!(bottom.y > y + 1.5 * hex.min_d);
+
+        // The first row labels are aligned with the first row, and are
+        // centred in the border margin to the left and right of the map.
+        let (_, left) = &labels[4];
+        let (_, right) = &labels[5];
+        assert!((left.y - y).abs() < 1e-8);
+        assert!((right.y - y).abs() < 1e-8);
+        assert!((left.x - margin - 0.5 * border).abs() < 1e-8);
+        assert!(right.x > x + hex.max_d);
+
+        // Removing the border labels should restore the original margin.
+        map.set_border_coordinates(None);
+        assert!(map.border_labels(&hex).is_empty());
+        assert_eq!(
+            hex_centre(&map, (0, 0).into(), &hex),
+            (x_before, y_before)
+        );
+    }
+
+    #[cfg(feature = "cairo")]
+    #[test]
+    fn test_bounding_box() {
+        let hex = Hex::new(100.0);
+        let min_d = Hex::ratio_min_d() * hex.max_d;
+        let margin = hex.geometry().map_margin.margin(&hex);
+
+        // An L-shaped map, where the odd-numbered column is shoved down.
+        let map = new_map(&[(0, 0), (0, 1), (0, 2), (1, 0), (2, 0)]);
+        let (x0, y0, width, height) = map.bounding_box(&hex);
+        assert_eq!((x0, y0), (margin, margin));
+        assert!((width - 2.5 * hex.max_d).abs() < 1e-8);
+        assert!((height - 3.0 * min_d).abs() < 1e-8);
+        let (draw_width, draw_height) = map.drawing_size(&hex);
+        assert!((draw_width - width - 2.0 * margin).abs() < 1e-8);
+        assert!((draw_height - height - 2.0 * margin).abs() < 1e-8);
+
+        // The first hex is drawn in the top-left corner of the bounding box.
+        let (x, y) = hex_centre(&map, (0, 0).into(), &hex);
+        assert!((x - x0 - 0.5 * hex.max_d).abs() < 1e-8);
+        assert!((y - y0 - 0.5 * min_d).abs() < 1e-8);
+
+        // The bounding box scales with the hexagon size.
+        let big_hex = Hex::new(200.0);
+        let (_, _, big_width, big_height) = map.bounding_box(&big_hex);
+        assert!((big_width - 2.0 * width).abs() < 1e-8);
+        assert!((big_height - 2.0 * height).abs() < 1e-8);
+    }
+
+    #[cfg(feature = "cairo")]
+    #[test]
+    fn test_token_space_at_point() {
+        use n18hex::RotateCW;
+
+        let hex = Hex::new(100.0);
+        let radius = hex.theme.token_space_radius.absolute(&hex);
+        let addr: HexAddress = (0, 0).into();
+        let mut map = new_map(&[(0, 0), (0, 1), (1, 0), (1, 1)]);
+        let (x, y) = hex_centre(&map, addr, &hex);
+
+        // Check that points on an empty hex do not match any token space.
+        assert_eq!(map.token_space_at_point(addr, x, y, &hex), None);
+
+        // Place a double city that is rotated by one turn, so the token
+        // spaces lie along the lower-right to upper-left axis.
+        assert!(map.place_tile(addr, "14", RotateCW::One));
+        let spaces = map.tile_at(addr).unwrap().token_spaces();
+        let (dx, dy) = (radius * 0.5, radius * 0.75_f64.sqrt());
+        assert_eq!(
+            map.token_space_at_point(addr, x + dx, y + dy, &hex),
+            Some(spaces[0])
+        );
+        assert_eq!(
+            map.token_space_at_point(addr, x - dx, y - dy, &hex),
+            Some(spaces[1])
+        );
+        // The un-rotated token space locations are only partially covered.
+        assert_eq!(
+            map.token_space_at_point(
+                addr,
+                x + radius,
+                y - 0.5 * radius,
+                &hex
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_replace_company_tokens() {
+        use n18hex::RotateCW;
+        use n18token::{Token, TokenStyle};
+
+        let new_token = |bg: (u8, u8, u8)| {
+            Token::new(TokenStyle::SideArcs {
+                fg: (63, 153, 153).into(),
+                bg: bg.into(),
+                text: (0, 0, 0).into(),
+            })
+        };
+        let token_a = new_token((255, 127, 127));
+        let token_b = new_token((127, 255, 127));
+        let hexes: Vec<HexAddress> = vec![(0, 0).into(), (0, 1).into()];
+        let mut map = Map::new(
+            n18catalogue::tile_catalogue().into(),
+            vec![("A".to_string(), token_a), ("B".to_string(), token_b)]
+                .into(),
+            hexes,
+            Orientation::FlatTop,
+        );
+        let (addr_63, addr_57) = ((0, 0).into(), (0, 1).into());
+        assert!(map.place_tile(addr_63, "63", RotateCW::Zero));
+        assert!(map.place_tile(addr_57, "57", RotateCW::Zero));
+        let spaces_63 = map.tile_at(addr_63).unwrap().token_spaces();
+        let spaces_57 = map.tile_at(addr_57).unwrap().token_spaces();
+        let hs = map.hex_state_mut(addr_63).unwrap();
+        hs.set_token_at(&spaces_63[0], token_a);
+        hs.set_token_at(&spaces_63[1], token_b);
+        let hs = map.hex_state_mut(addr_57).unwrap();
+        hs.set_token_at(&spaces_57[0], token_a);
+        let original = map.clone();
+
+        // Company B already has a token on tile 63, so company A's token
+        // should be removed from this tile and replaced on tile 57.
+        let changes = map.replace_company_tokens(&token_a, &token_b);
+        assert_eq!(changes.len(), 2);
+        assert!(map.find_placed_tokens(&token_a).is_empty());
+        assert_eq!(map.find_placed_tokens(&token_b).len(), 2);
+        let hs = map.hex_state(addr_63).unwrap();
+        assert_eq!(hs.token_at(&spaces_63[0]), None);
+        assert_eq!(hs.token_at(&spaces_63[1]), Some(&token_b));
+        let hs = map.hex_state(addr_57).unwrap();
+        assert_eq!(hs.token_at(&spaces_57[0]), Some(&token_b));
+
+        // Reverting these changes should restore the original tokens.
+        map.revert_token_changes(&changes);
+        assert_eq!(map, original);
+
+        // Remove a specific token, and then restore it.
+        let change = map.remove_token(addr_63, &spaces_63[1]).unwrap();
+        assert_eq!(change.before, Some(token_b));
+        assert_eq!(change.after, None);
+        assert!(map.find_placed_tokens(&token_b).is_empty());
+        assert!(map.remove_token(addr_63, &spaces_63[1]).is_none());
+        map.revert_token_changes(&[change]);
+        assert_eq!(map, original);
+    }
+
+    #[cfg(feature = "cairo")]
+    #[test]
+    fn test_partial_hexes() {
+        let hex = Hex::new(100.0);
+        let margin = hex.geometry().map_margin.margin(&hex);
+        let mut map = new_map(&[(0, 0), (0, 1), (1, 0), (1, 1)]);
+
+        // Only the lower half of hex (0, 0) is present, so its centre should
+        // lie on the top edge of the map.
+        // NOTE: hex (0, 1) is shoved down, so its top edge is also aligned
+        // with the centre of hex (0, 0).
+        assert!(map.set_partial_hex((0, 0).into(), HexHalf::Bottom));
+        let (_x, y) = hex_centre(&map, (0, 0).into(), &hex);
+        assert!((y - margin).abs() < 1e-8);
+        assert_eq!(map.partial_hex((0, 0).into()), Some(HexHalf::Bottom));
+
+        // Only the upper half of hex (0, 1) is present, which should not
+        // affect the top edge of the map.
+        assert!(map.set_partial_hex((0, 1).into(), HexHalf::Top));
+        let (_x, y) = hex_centre(&map, (0, 0).into(), &hex);
+        assert!((y - margin).abs() < 1e-8);
+        map.clear_partial_hex((0, 1).into());
+        assert_eq!(map.partial_hex((0, 1).into()), None);
+
+        // Only the right half of the left-most hexes are present.
+        assert!(map.set_partial_hex((0, 0).into(), HexHalf::Right));
+        assert!(map.set_partial_hex((1, 0).into(), HexHalf::Right));
+        let (x, _y) = hex_centre(&map, (0, 0).into(), &hex);
+        assert!((x - margin).abs() < 1e-8);
+
+        // Hexes that are not part of the map cannot be marked as partial.
+        assert!(!map.set_partial_hex((5, 5).into(), HexHalf::Top));
+        assert_eq!(map.partial_hex((5, 5).into()), None);
+    }
+
+    #[cfg(feature = "cairo")]
+    #[test]
+    fn test_empty_map_bounds() {
+        let hex = Hex::new(100.0);
+        let margin = hex.geometry().map_margin.margin(&hex);
+        // NOTE: Map::new() does not accept an empty set of hexes.
+        let mut map = new_map(&[(1, 1)]);
+        map.hexes.clear();
+        map.update_origin();
+        assert_eq!(map.origin, (0.0, 0.0));
+        assert_eq!(map.bounding_box(&hex), (margin, margin, 0.0, 0.0));
+        assert_eq!(map.drawing_size(&hex), (2.0 * margin, 2.0 * margin));
+    }
+
+    #[test]
+    fn test_locations() {
+        let mut map = new_map(&[(0, 0), (0, 1), (1, 0), (1, 1)]);
+        let (a, b, c) = ((0, 1).into(), (1, 1).into(), (1, 0).into());
+        assert!(map.set_location(b, "West"));
+        assert!(map.set_location(a, "West"));
+        assert_eq!(map.location(a), Some("West"));
+        assert_eq!(map.location(c), None);
+        assert_eq!(map.location_hexes("West"), vec![a, b]);
+
+        // Each hex is identified by the first hex of its location.
+        assert_eq!(map.location_anchor(b), a);
+        assert_eq!(map.location_anchor(c), c);
+
+        map.clear_location(a);
+        assert_eq!(map.location_anchor(b), b);
+        assert_eq!(
+            map.location_iter().collect::<Vec<_>>(),
+            vec![(b, "West")]
+        );
+
+        // Hexes that are not part of the map cannot be part of a location.
+        assert!(!map.set_location((5, 5).into(), "West"));
+    }
+
     #[test]
     fn test_simple_two_by_two() {
         let map = crate::descr::tests::map_2x2_tiles_5_6_58_63();
@@ -1209,11 +2510,10 @@ mod tests {
             // Check that each city is connected to the end of a different
             // track segment.
             for j in 0..conns.len() {
-                assert!(conns.iter().any(|&c| c
-                    == Connection::Track {
-                        ix: j,
-                        end: TrackEnd::End
-                    }));
+                assert!(conns.contains(&Connection::Track {
+                    ix: j,
+                    end: TrackEnd::End
+                }));
             }
 
             // Check that the other end of each track segment is connected to
@@ -1260,4 +2560,53 @@ mod tests {
             assert_eq!(track_to_track, 2);
         }
     }
+
+    #[test]
+    fn revision_changes_when_modified() {
+        use n18hex::RotateCW;
+
+        let mut map = new_map(&[(0, 0), (0, 1)]);
+        let other = new_map(&[(0, 0), (0, 1)]);
+        // Maps with different histories never share a revision number, but
+        // revision numbers do not affect equality.
+        assert_ne!(map.revision(), other.revision());
+        assert_eq!(map, other);
+
+        // Reading from the map does not change its revision.
+        let addr = HexAddress::new(0, 0);
+        let rev = map.revision();
+        assert!(map.tile_at(addr).is_none());
+        assert!(map.hex_state(addr).is_none());
+        assert_eq!(map.revision(), rev);
+        let copy = map.clone();
+        assert_eq!(copy.revision(), rev);
+
+        // Modifying the map changes its revision.
+        assert!(map.place_tile(addr, "7", RotateCW::Zero));
+        let placed = map.revision();
+        assert_ne!(placed, rev);
+        assert!(map.place_marker(addr, "Port"));
+        assert_ne!(map.revision(), placed);
+        let marked = map.revision();
+        map.set_revenue_phase(n18hex::HexColour::Green);
+        assert_ne!(map.revision(), marked);
+        assert_eq!(copy.revision(), rev);
+    }
+
+    #[test]
+    fn catalogue_revision_changes_when_tiles_updated() {
+        use n18hex::RotateCW;
+
+        let mut map = new_map(&[(0, 0), (0, 1)]);
+        let rev = map.catalogue_revision();
+
+        // Placing tiles does not change the catalogue revision.
+        assert!(map.place_tile(HexAddress::new(0, 0), "7", RotateCW::Zero));
+        assert_eq!(map.catalogue_revision(), rev);
+
+        // Updating the tile definitions changes the catalogue revision.
+        let tile = map.tile("7").unwrap().clone();
+        map.update_tiles(vec![tile]);
+        assert_ne!(map.catalogue_revision(), rev);
+    }
 }
@@ -0,0 +1,176 @@
+//! Markers that are placed on map hexes, separately from tiles and company
+//! tokens.
+//!
+//! Some games have special markers (e.g., port, mine, and bridge markers)
+//! that are placed on map hexes and which may affect route revenue (see
+//! `n18route::Bonus::MarkerBonus`).
+//! Unlike company tokens, markers do not occupy token spaces, do not block
+//! routes, and are not removed when the hex's tile is upgraded.
+
+use crate::{HexAddress, Map};
+
+/// A marker that has been placed on a map hex.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Marker {
+    /// The marker name (e.g., "Port"), which is also drawn on the map.
+    pub name: String,
+}
+
+impl Marker {
+    /// Creates a new marker with the given name.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Marker { name: name.into() }
+    }
+}
+
+impl From<&str> for Marker {
+    fn from(name: &str) -> Self {
+        Marker::new(name)
+    }
+}
+
+impl From<String> for Marker {
+    fn from(name: String) -> Self {
+        Marker { name }
+    }
+}
+
+impl std::fmt::Display for Marker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Map {
+    /// Places a marker on a map hex.
+    ///
+    /// Returns `false` if `addr` is not a valid hex address for this map, or
+    /// if the hex already has a marker with the same name.
+    pub fn place_marker<M: Into<Marker>>(
+        &mut self,
+        addr: HexAddress,
+        marker: M,
+    ) -> bool {
+        self.touch();
+        if !self.contains_hex(addr) {
+            return false;
+        }
+        let marker = marker.into();
+        let markers = self.markers_tbl.entry(addr).or_default();
+        if markers.contains(&marker) {
+            return false;
+        }
+        markers.push(marker);
+        true
+    }
+
+    /// Removes the named marker from a map hex, and returns `true` if the
+    /// hex had such a marker.
+    pub fn remove_marker(&mut self, addr: HexAddress, name: &str) -> bool {
+        self.touch();
+        let Some(markers) = self.markers_tbl.get_mut(&addr) else {
+            return false;
+        };
+        let Some(ix) = markers.iter().position(|m| m.name == name) else {
+            return false;
+        };
+        markers.remove(ix);
+        if markers.is_empty() {
+            self.markers_tbl.remove(&addr);
+        }
+        true
+    }
+
+    /// Removes all markers from a map hex.
+    pub fn clear_markers(&mut self, addr: HexAddress) {
+        self.touch();
+        self.markers_tbl.remove(&addr);
+    }
+
+    /// Returns the markers that have been placed on a map hex.
+    pub fn markers_at(&self, addr: HexAddress) -> &[Marker] {
+        self.markers_tbl
+            .get(&addr)
+            .map(|v| v.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Returns `true` if the named marker has been placed on a map hex.
+    pub fn has_marker(&self, addr: HexAddress, name: &str) -> bool {
+        self.markers_at(addr).iter().any(|m| m.name == name)
+    }
+
+    /// Returns an iterator over each map hex that has at least one marker,
+    /// and the markers on that hex.
+    pub fn marker_iter(
+        &self,
+    ) -> impl Iterator<Item = (HexAddress, &[Marker])> + '_ {
+        self.markers_tbl
+            .iter()
+            .map(|(addr, markers)| (*addr, markers.as_slice()))
+    }
+
+    /// Returns the address of each map hex on which the named marker has
+    /// been placed.
+    pub fn marker_hexes(&self, name: &str) -> Vec<HexAddress> {
+        self.marker_iter()
+            .filter(|(_addr, markers)| markers.iter().any(|m| m.name == name))
+            .map(|(addr, _markers)| addr)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Marker;
+    use crate::{HexAddress, Map};
+    use n18hex::Orientation;
+
+    fn new_map() -> Map {
+        let addrs: Vec<HexAddress> = (0..3)
+            .flat_map(|row| (0..3).map(move |col| (row, col).into()))
+            .collect();
+        Map::new(
+            n18catalogue::tile_catalogue().into(),
+            vec![].into(),
+            addrs,
+            Orientation::FlatTop,
+        )
+    }
+
+    #[test]
+    fn place_and_remove_markers() {
+        let mut map = new_map();
+        let a: HexAddress = (0, 1).into();
+        let b: HexAddress = (2, 2).into();
+        assert!(map.markers_at(a).is_empty());
+
+        assert!(map.place_marker(a, "Port"));
+        assert!(map.place_marker(a, "Mine"));
+        assert!(map.place_marker(b, "Port"));
+        // Each hex can only have one marker with the same name.
+        assert!(!map.place_marker(a, "Port"));
+        // Markers cannot be placed on hexes that are not on the map.
+        assert!(!map.place_marker((5, 5).into(), "Port"));
+
+        assert_eq!(
+            map.markers_at(a),
+            &[Marker::new("Port"), Marker::new("Mine")]
+        );
+        assert!(map.has_marker(a, "Mine"));
+        assert!(!map.has_marker(b, "Mine"));
+        assert_eq!(map.marker_hexes("Port"), vec![a, b]);
+        assert_eq!(map.marker_hexes("Mine"), vec![a]);
+
+        // Markers are not removed when the tile is removed.
+        map.remove_tile(a);
+        assert_eq!(map.markers_at(a).len(), 2);
+
+        assert!(map.remove_marker(a, "Port"));
+        assert!(!map.remove_marker(a, "Port"));
+        assert_eq!(map.marker_hexes("Port"), vec![b]);
+        map.clear_markers(a);
+        assert!(map.markers_at(a).is_empty());
+        assert_eq!(map.marker_iter().count(), 1);
+    }
+}
@@ -0,0 +1,849 @@
+//! Place tiles on a map by name, checking that each placement is valid.
+//!
+//! Unlike [Map::place_tile], which will replace any tile with any other
+//! tile, [Map::try_place_tile] checks that the new tile is a valid upgrade
+//! of the current tile (if any), and reports why a tile cannot be placed.
+//! This is intended for scripted tools and tests, which should not need to
+//! manipulate the map hex state directly.
+//!
+//! Similarly, [Map::try_place_token] checks that a token can be placed in a
+//! token space, respecting the game's [TokenRule].
+//!
+//! [Map::legal_placements] applies the same checks to every available tile
+//! and rotation, and returns the legal placements for a map hex, subject to
+//! the [PlacementRules] of the game and its current phase.
+
+use std::collections::BTreeSet;
+
+use n18hex::{HexColour, HexFace, RotateCW};
+use n18tile::{Tile, TokenSpace};
+use n18token::Token;
+
+use crate::{HexAddress, Map, TokenChange, TokensTable};
+
+/// The outcome of placing a tile with [Map::try_place_tile].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Placement {
+    /// The tile was placed.
+    Ok,
+    /// The map does not contain this hex.
+    HexNotOnMap,
+    /// The map does not contain a tile with this name.
+    UnknownTile,
+    /// There are no copies of this tile available.
+    Unavailable,
+    /// The current tile cannot be replaced (e.g., off-board locations).
+    NotReplaceable,
+    /// The tile is not the next colour after the current tile (or, for empty
+    /// hexes, is not a yellow tile).
+    IllegalColour,
+    /// The tile's colour is later than the latest colour that can be placed
+    /// on this hex (see [Map::set_max_colour]).
+    ExceedsMaxColour,
+    /// The tile's city labels (e.g., "Y", "B") do not match those of this
+    /// hex or its current tile (see [Map::tile_labels_allowed_at]).
+    IllegalLabel,
+    /// The tile's revenue centres (dits and cities) are not consistent with
+    /// this hex.
+    IllegalTile,
+    /// The tile does not preserve the track connections of the current tile.
+    BrokenConnectivity,
+    /// The tile's colour cannot be placed in the current game phase (see
+    /// [PlacementRules::phase_colours]).
+    IllegalPhase,
+    /// The tile does not contain this token space.
+    InvalidTokenSpace(TokenSpace),
+    /// The token placed in this token space is not one of the map's tokens.
+    UnknownToken(TokenSpace),
+}
+
+impl Placement {
+    /// Returns `true` if the tile was placed.
+    pub fn is_ok(&self) -> bool {
+        *self == Placement::Ok
+    }
+}
+
+/// Defines whether a company may place more than one token in the same
+/// city.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TokenRule {
+    /// A company may place tokens in any number of a city's token spaces.
+    #[default]
+    Unrestricted,
+    /// A company may place at most one token in each city.
+    OnePerCity,
+}
+
+/// The reasons why a token cannot be placed with [Map::try_place_token].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenPlacement {
+    /// The token can be placed.
+    Ok,
+    /// The map does not contain this hex.
+    HexNotOnMap,
+    /// There is no tile on this hex.
+    NoTile,
+    /// The tile does not contain this token space.
+    InvalidTokenSpace,
+    /// The token is not one of the map's tokens.
+    UnknownToken,
+    /// The token space is not available in the current game phase (see
+    /// [Map::token_space_available]).
+    SpaceUnavailable,
+    /// The company has already placed a token in this city, and the
+    /// [TokenRule] only allows one token per city.
+    DuplicateInCity,
+}
+
+impl TokenPlacement {
+    /// Returns `true` if the token can be placed.
+    pub fn is_ok(&self) -> bool {
+        *self == TokenPlacement::Ok
+    }
+}
+
+/// Defines which track an upgrade tile must preserve, relative to the
+/// current tile.
+///
+/// See the
+/// [different upgrade criteria](https://www.railsonboards.com/2020/12/26/permissive-restrictive-semi-restrictive-what-it-means/)
+/// used by 18xx games.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UpgradeRule {
+    /// The upgrade tile may contain any track.
+    Any,
+    /// The upgrade tile must contain track on every hex face that contains
+    /// track on the current tile.
+    Permissive,
+    /// The upgrade tile must preserve every track connection of the current
+    /// tile.
+    #[default]
+    Restrictive,
+}
+
+/// The rules that determine which tile placements are legal (see
+/// [Map::legal_placements]).
+///
+/// The default rules allow tiles of any colour to be placed, and require
+/// upgrade tiles to preserve every track connection of the current tile, as
+/// per [Map::try_place_tile].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlacementRules {
+    /// The tile colours that can be placed in the current game phase, or
+    /// `None` if tiles of any colour can be placed.
+    pub phase_colours: Option<Vec<HexColour>>,
+    /// The track that upgrade tiles must preserve.
+    pub upgrades: UpgradeRule,
+}
+
+impl PlacementRules {
+    /// Returns the rules for a game phase in which only tiles of the
+    /// provided colours can be placed.
+    pub fn for_phase(colours: Vec<HexColour>) -> Self {
+        PlacementRules {
+            phase_colours: Some(colours),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the track that upgrade tiles must preserve.
+    pub fn with_upgrades(mut self, upgrades: UpgradeRule) -> Self {
+        self.upgrades = upgrades;
+        self
+    }
+}
+
+/// The six hex faces.
+const FACES: [HexFace; 6] = [
+    HexFace::Top,
+    HexFace::UpperRight,
+    HexFace::LowerRight,
+    HexFace::Bottom,
+    HexFace::LowerLeft,
+    HexFace::UpperLeft,
+];
+
+/// The six tile rotations.
+const ROTATIONS: [RotateCW; 6] = [
+    RotateCW::Zero,
+    RotateCW::One,
+    RotateCW::Two,
+    RotateCW::Three,
+    RotateCW::Four,
+    RotateCW::Five,
+];
+
+/// Returns the map faces that contain track, and each pair of map faces that
+/// are connected to each other, for a tile placed with rotation `rot`.
+fn track_connections(
+    tile: &Tile,
+    rot: &RotateCW,
+) -> (BTreeSet<HexFace>, BTreeSet<(HexFace, HexFace)>) {
+    let faces = tile
+        .tracks()
+        .iter()
+        .flat_map(|track| track.connected_to_faces())
+        .map(|(_end, face)| face + rot)
+        .collect();
+    let pairs = FACES
+        .iter()
+        .flat_map(|&from| {
+            tile.connected_faces(from)
+                .into_iter()
+                .filter(move |&to| to != from)
+                .map(move |to| (from + rot, to + rot))
+        })
+        .collect();
+    (faces, pairs)
+}
+
+impl Map {
+    /// Places the tile called `tile_name` at the map hex `addr` with
+    /// rotation `rot`, if it is a valid placement.
+    ///
+    /// A tile can be placed on an empty hex if it is a yellow tile with no
+    /// dits or cities.
+    /// A tile can replace the current tile if it is the next colour, has the
+    /// same number of dits and at least as many token spaces, and preserves
+    /// all of the current tile's track connections.
+    /// In both cases the tile must be available, no later than the hex's
+    /// maximum colour (if any), and consistent with the hex labels.
+    ///
+    /// If `tokens` is `None`, the current tokens are moved to the new tile
+    /// (as per [Map::place_tile]); otherwise, the new tile will contain only
+    /// the provided tokens.
+    ///
+    /// If the tile cannot be placed, the map is not modified.
+    ///
+    /// ```rust
+    /// # use n18hex::{Orientation, RotateCW};
+    /// # use n18map::{HexAddress, Map, Placement};
+    /// # let tiles = n18catalogue::tile_catalogue();
+    /// # let hexes: Vec<HexAddress> = vec![(0, 0).into()];
+    /// # let orient = Orientation::FlatTop;
+    /// # let mut map = Map::new(tiles.into(), vec![].into(), hexes, orient);
+    /// let addr: HexAddress = (0, 0).into();
+    /// let placed = map.try_place_tile(addr, "8", RotateCW::One, None);
+    /// assert_eq!(placed, Placement::Ok);
+    /// let placed = map.try_place_tile(addr, "8", RotateCW::Two, None);
+    /// assert_eq!(placed, Placement::IllegalColour);
+    /// ```
+    pub fn try_place_tile(
+        &mut self,
+        addr: HexAddress,
+        tile_name: &str,
+        rot: RotateCW,
+        tokens: Option<TokensTable>,
+    ) -> Placement {
+        let rules = PlacementRules::default();
+        let result =
+            self.check_placement(addr, tile_name, &rot, &tokens, &rules);
+        if !result.is_ok() {
+            return result;
+        }
+        self.place_tile(addr, tile_name, rot);
+        if let Some(tokens) = tokens {
+            if let Some(hex_state) = self.hex_state_mut(addr) {
+                hex_state.set_tokens(tokens)
+            }
+        }
+        Placement::Ok
+    }
+
+    /// Returns each available tile that can legally be placed on the map hex
+    /// `addr`, and the rotations with which it can be placed, subject to
+    /// `rules`.
+    ///
+    /// This applies the same checks as [Map::try_place_tile] (tile colour,
+    /// hex labels, revenue centres, track connectivity, and tile supply) to
+    /// every tile and rotation, so that user interfaces and other tools
+    /// (e.g., move validators) agree on which placements are legal.
+    /// Tiles are returned in the order of the map catalogue, and tiles that
+    /// cannot be placed with any rotation are omitted.
+    ///
+    /// ```rust
+    /// # use n18hex::{HexColour, Orientation, RotateCW};
+    /// # use n18map::{HexAddress, Map, PlacementRules};
+    /// # let tiles = n18catalogue::tile_catalogue();
+    /// # let hexes: Vec<HexAddress> = vec![(0, 0).into()];
+    /// # let orient = Orientation::FlatTop;
+    /// # let mut map = Map::new(tiles.into(), vec![].into(), hexes, orient);
+    /// let addr: HexAddress = (0, 0).into();
+    /// let rules = PlacementRules::for_phase(vec![HexColour::Yellow]);
+    /// let placements = map.legal_placements(addr, &rules);
+    /// // Tile 8 can be placed on an empty hex with any rotation.
+    /// let (_name, rotations) =
+    ///     placements.iter().find(|(name, _)| name == "8").unwrap();
+    /// assert_eq!(rotations.len(), 6);
+    /// ```
+    pub fn legal_placements(
+        &self,
+        addr: HexAddress,
+        rules: &PlacementRules,
+    ) -> Vec<(String, Vec<RotateCW>)> {
+        self.available_tiles_iter()
+            .filter_map(|tile| {
+                let rotations: Vec<RotateCW> = ROTATIONS
+                    .iter()
+                    .filter(|rot| {
+                        self.check_placement(
+                            addr, &tile.name, rot, &None, rules,
+                        )
+                        .is_ok()
+                    })
+                    .copied()
+                    .collect();
+                if rotations.is_empty() {
+                    None
+                } else {
+                    Some((tile.name.clone(), rotations))
+                }
+            })
+            .collect()
+    }
+
+    /// Checks whether a tile can be placed, without modifying the map.
+    fn check_placement(
+        &self,
+        addr: HexAddress,
+        tile_name: &str,
+        rot: &RotateCW,
+        tokens: &Option<TokensTable>,
+        rules: &PlacementRules,
+    ) -> Placement {
+        if !self.contains_hex(addr) {
+            return Placement::HexNotOnMap;
+        }
+        let tile = match self.tile(tile_name) {
+            Some(tile) => tile,
+            None => return Placement::UnknownTile,
+        };
+        if !self.tile_is_available(tile_name) {
+            return Placement::Unavailable;
+        }
+        if !self.colour_allowed_at(addr, tile.colour) {
+            return Placement::ExceedsMaxColour;
+        }
+        if let Some(colours) = &rules.phase_colours {
+            if !colours.contains(&tile.colour) {
+                return Placement::IllegalPhase;
+            }
+        }
+
+        if let Some(hex_state) = self.hex_state(addr) {
+            if !hex_state.is_replaceable() {
+                return Placement::NotReplaceable;
+            }
+            let current = hex_state.tile(self);
+            if current.colour.next_phase() != Some(tile.colour) {
+                return Placement::IllegalColour;
+            }
+            if !self.tile_labels_allowed_at(addr, tile) {
+                return Placement::IllegalLabel;
+            }
+            if !current.can_upgrade_to(tile)
+                || !self.can_upgrade_to(addr, tile)
+            {
+                return Placement::IllegalTile;
+            }
+            let (old_faces, old_pairs) =
+                track_connections(current, hex_state.rotation());
+            let (new_faces, new_pairs) = track_connections(tile, rot);
+            let preserved = match rules.upgrades {
+                UpgradeRule::Any => true,
+                UpgradeRule::Permissive => old_faces.is_subset(&new_faces),
+                UpgradeRule::Restrictive => {
+                    old_faces.is_subset(&new_faces)
+                        && old_pairs.is_subset(&new_pairs)
+                }
+            };
+            if !preserved {
+                return Placement::BrokenConnectivity;
+            }
+        } else {
+            if HexColour::Empty.next_phase() != Some(tile.colour) {
+                return Placement::IllegalColour;
+            }
+            if !self.tile_labels_allowed_at(addr, tile) {
+                return Placement::IllegalLabel;
+            }
+            if !self.can_place_on_empty(addr, tile) {
+                return Placement::IllegalTile;
+            }
+        }
+
+        if let Some(tokens) = tokens {
+            let spaces = tile.token_spaces();
+            for (space, token) in tokens {
+                if !spaces.contains(space) {
+                    return Placement::InvalidTokenSpace(*space);
+                }
+                if self.try_token_name(token).is_none() {
+                    return Placement::UnknownToken(*space);
+                }
+            }
+        }
+
+        Placement::Ok
+    }
+
+    /// Places `token` in the token space `space` at the map hex `addr`, if
+    /// it is a valid placement, and returns the change that was made.
+    ///
+    /// The token must be one of the map's tokens, and if `rule` is
+    /// [TokenRule::OnePerCity], the company must not have already placed a
+    /// token in another token space of the same city.
+    /// Any token already placed in this token space is replaced.
+    ///
+    /// If the token cannot be placed, the map is not modified.
+    ///
+    /// ```rust
+    /// # use n18hex::{Orientation, RotateCW};
+    /// # use n18map::{HexAddress, Map, TokenPlacement, TokenRule};
+    /// # use n18token::{Token, TokenStyle, Tokens};
+    /// # let tiles = n18catalogue::tile_catalogue();
+    /// # let hexes: Vec<HexAddress> = vec![(0, 0).into()];
+    /// # let orient = Orientation::FlatTop;
+    /// # let style = TokenStyle::SideArcs {
+    /// #     bg: (255, 255, 255).into(),
+    /// #     fg: (0, 0, 0).into(),
+    /// #     text: (0, 0, 0).into(),
+    /// # };
+    /// # let token = Token::new(style);
+    /// # let tokens = Tokens::new(vec![("A".to_string(), token)]);
+    /// # let mut map = Map::new(tiles.into(), tokens, hexes, orient);
+    /// let addr: HexAddress = (0, 0).into();
+    /// // Tile 14 contains a single city with two token spaces.
+    /// map.place_tile(addr, "14", RotateCW::Zero);
+    /// let spaces = map.tile_at(addr).unwrap().token_spaces();
+    /// let rule = TokenRule::OnePerCity;
+    /// let placed = map.try_place_token(addr, &spaces[0], token, rule);
+    /// assert!(placed.is_ok());
+    /// let placed = map.try_place_token(addr, &spaces[1], token, rule);
+    /// assert_eq!(placed, Err(TokenPlacement::DuplicateInCity));
+    /// ```
+    pub fn try_place_token(
+        &mut self,
+        addr: HexAddress,
+        space: &TokenSpace,
+        token: Token,
+        rule: TokenRule,
+    ) -> Result<TokenChange, TokenPlacement> {
+        let result = self.check_token_placement(addr, space, &token, rule);
+        if !result.is_ok() {
+            return Err(result);
+        }
+        let hex_state =
+            self.hex_state_mut(addr).ok_or(TokenPlacement::NoTile)?;
+        let before = hex_state.token_at(space).copied();
+        hex_state.set_token_at(space, token);
+        Ok(TokenChange {
+            addr,
+            space: *space,
+            before,
+            after: Some(token),
+        })
+    }
+
+    /// Checks whether `token` can be placed in the token space `space` at
+    /// the map hex `addr`, without modifying the map; see
+    /// [Map::try_place_token].
+    pub fn check_token_placement(
+        &self,
+        addr: HexAddress,
+        space: &TokenSpace,
+        token: &Token,
+        rule: TokenRule,
+    ) -> TokenPlacement {
+        if !self.contains_hex(addr) {
+            return TokenPlacement::HexNotOnMap;
+        }
+        let (tile, hex_state) =
+            match (self.tile_at(addr), self.hex_state(addr)) {
+                (Some(tile), Some(hex_state)) => (tile, hex_state),
+                _ => return TokenPlacement::NoTile,
+            };
+        if !tile.token_spaces().contains(space) {
+            return TokenPlacement::InvalidTokenSpace;
+        }
+        if self.try_token_name(token).is_none() {
+            return TokenPlacement::UnknownToken;
+        }
+        if !self.token_space_available(addr, space) {
+            return TokenPlacement::SpaceUnavailable;
+        }
+        if rule == TokenRule::OnePerCity {
+            let duplicate = hex_state.tokens().iter().any(|(other, tok)| {
+                other != space
+                    && other.city_ix() == space.city_ix()
+                    && tok == token
+            });
+            if duplicate {
+                return TokenPlacement::DuplicateInCity;
+            }
+        }
+        TokenPlacement::Ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Placement, PlacementRules, TokenPlacement, TokenRule, UpgradeRule,
+        ROTATIONS,
+    };
+    use crate::{HexAddress, Map, TokensTable};
+    use n18hex::{HexColour, Orientation, RotateCW};
+    use n18tile::Label;
+    use n18token::{Token, TokenStyle, Tokens};
+
+    fn new_tokens() -> Tokens {
+        let style = TokenStyle::SideArcs {
+            bg: (255, 255, 255).into(),
+            fg: (0, 0, 0).into(),
+            text: (0, 0, 0).into(),
+        };
+        Tokens::new(vec![("A".to_string(), Token::new(style))])
+    }
+
+    fn new_map() -> Map {
+        let hexes: Vec<HexAddress> = vec![(0, 0).into(), (0, 1).into()];
+        Map::new(
+            n18catalogue::tile_catalogue().into(),
+            new_tokens(),
+            hexes,
+            Orientation::FlatTop,
+        )
+    }
+
+    /// Returns a token with the provided background colour.
+    fn coloured_token(red: u8) -> Token {
+        Token::new(TokenStyle::SideArcs {
+            bg: (red, 255, 255).into(),
+            fg: (0, 0, 0).into(),
+            text: (0, 0, 0).into(),
+        })
+    }
+
+    #[test]
+    fn test_token_rule_one_per_city() {
+        let names = ["A", "B", "C"];
+        let tokens = Tokens::new(
+            names
+                .iter()
+                .enumerate()
+                .map(|(ix, name)| {
+                    (name.to_string(), coloured_token(ix as u8))
+                })
+                .collect(),
+        );
+        let [a, b, c] = [0, 1, 2].map(coloured_token);
+        let unknown = coloured_token(3);
+        let addr: HexAddress = (0, 0).into();
+        let mut map = Map::new(
+            n18catalogue::tile_catalogue().into(),
+            tokens,
+            vec![addr],
+            Orientation::FlatTop,
+        );
+        let one = TokenRule::OnePerCity;
+
+        // Tokens can only be placed in the token spaces of placed tiles.
+        assert!(map.place_tile(addr, "8", RotateCW::Zero));
+        let other_spaces = map.tile("14").unwrap().token_spaces();
+        let result = map.try_place_token(addr, &other_spaces[0], a, one);
+        assert_eq!(result, Err(TokenPlacement::InvalidTokenSpace));
+        assert!(map.place_tile(addr, "14", RotateCW::Zero));
+        let spaces = map.tile_at(addr).unwrap().token_spaces();
+        assert_eq!(spaces.len(), 2);
+        let result = map.try_place_token((5, 5).into(), &spaces[0], a, one);
+        assert_eq!(result, Err(TokenPlacement::HexNotOnMap));
+        let result = map.try_place_token(addr, &spaces[0], unknown, one);
+        assert_eq!(result, Err(TokenPlacement::UnknownToken));
+
+        // A valid placement returns the change that was made.
+        let change = map.try_place_token(addr, &spaces[0], a, one).unwrap();
+        assert_eq!((change.before, change.after), (None, Some(a)));
+        assert_eq!(
+            map.hex_state(addr).unwrap().token_at(&spaces[0]),
+            Some(&a)
+        );
+
+        // A company may only place one token in the city, unless the rule
+        // is unrestricted.
+        let result = map.try_place_token(addr, &spaces[1], a, one);
+        assert_eq!(result, Err(TokenPlacement::DuplicateInCity));
+        assert_eq!(map.hex_state(addr).unwrap().token_at(&spaces[1]), None);
+        let unrestricted = TokenRule::Unrestricted;
+        let result =
+            map.check_token_placement(addr, &spaces[1], &a, unrestricted);
+        assert_eq!(result, TokenPlacement::Ok);
+
+        // Once the city is full, a company that has a token in the city
+        // cannot move it to another token space, but placing a token in an
+        // occupied space replaces the existing token.
+        assert!(map.try_place_token(addr, &spaces[1], b, one).is_ok());
+        let result = map.check_token_placement(addr, &spaces[0], &b, one);
+        assert_eq!(result, TokenPlacement::DuplicateInCity);
+        let change = map.try_place_token(addr, &spaces[0], c, one).unwrap();
+        assert_eq!((change.before, change.after), (Some(a), Some(c)));
+        let hex_state = map.hex_state(addr).unwrap();
+        assert_eq!(hex_state.token_at(&spaces[0]), Some(&c));
+        assert_eq!(hex_state.token_at(&spaces[1]), Some(&b));
+    }
+
+    #[test]
+    fn test_invalid_placements() {
+        let mut map = new_map();
+        let addr: HexAddress = (0, 0).into();
+        let rot = RotateCW::Zero;
+        let placed = map.try_place_tile((5, 5).into(), "8", rot, None);
+        assert_eq!(placed, Placement::HexNotOnMap);
+        let placed = map.try_place_tile(addr, "no such tile", rot, None);
+        assert_eq!(placed, Placement::UnknownTile);
+        // Green tiles cannot be placed on empty hexes.
+        let placed = map.try_place_tile(addr, "24", rot, None);
+        assert_eq!(placed, Placement::IllegalColour);
+        // City tiles cannot be placed on empty hexes.
+        let placed = map.try_place_tile(addr, "57", rot, None);
+        assert_eq!(placed, Placement::IllegalTile);
+        assert!(map.tile_at(addr).is_none());
+    }
+
+    #[test]
+    fn test_legal_placements() {
+        let mut map = new_map();
+        let addr: HexAddress = (0, 0).into();
+        let yellow = PlacementRules::for_phase(vec![HexColour::Yellow]);
+
+        // Tile 8 can be placed on an empty hex with any rotation, and only
+        // yellow tiles can be placed on an empty hex.
+        let placements = map.legal_placements(addr, &yellow);
+        let (_, rotations) =
+            placements.iter().find(|(name, _)| name == "8").unwrap();
+        assert_eq!(rotations.len(), 6);
+        assert!(placements.iter().all(|(name, _)| {
+            map.tile(name).unwrap().colour == HexColour::Yellow
+        }));
+        assert!(!placements.iter().any(|(name, _)| name == "57"));
+
+        // Green tiles cannot be placed in a yellow phase.
+        assert!(map.try_place_tile(addr, "8", RotateCW::Zero, None).is_ok());
+        assert!(map.legal_placements(addr, &yellow).is_empty());
+
+        // Each legal rotation of each upgrade tile must preserve the
+        // existing track.
+        let rules = PlacementRules::default();
+        let placements = map.legal_placements(addr, &rules);
+        assert!(!placements.is_empty());
+        for (name, rotations) in &placements {
+            assert!(!rotations.is_empty());
+            for rot in &ROTATIONS {
+                let result =
+                    map.check_placement(addr, name, rot, &None, &rules);
+                assert_eq!(result.is_ok(), rotations.contains(rot));
+            }
+        }
+
+        // Relaxing the upgrade rule allows more rotations.
+        let permissive = rules.clone().with_upgrades(UpgradeRule::Permissive);
+        let any = rules.clone().with_upgrades(UpgradeRule::Any);
+        let count = |rules: &PlacementRules| -> usize {
+            map.legal_placements(addr, rules)
+                .iter()
+                .map(|(_, rotations)| rotations.len())
+                .sum()
+        };
+        assert!(count(&rules) <= count(&permissive));
+        assert!(count(&permissive) < count(&any));
+        assert!(map
+            .legal_placements(addr, &any)
+            .iter()
+            .all(|(_, rotations)| rotations.len() == 6));
+    }
+
+    #[test]
+    fn test_upgrade_connectivity() {
+        let mut map = new_map();
+        let addr: HexAddress = (0, 0).into();
+        let placed = map.try_place_tile(addr, "8", RotateCW::Zero, None);
+        assert!(placed.is_ok());
+
+        // Only some rotations of a green tile preserve the existing track.
+        let rotations = [
+            RotateCW::Zero,
+            RotateCW::One,
+            RotateCW::Two,
+            RotateCW::Three,
+            RotateCW::Four,
+            RotateCW::Five,
+        ];
+        let results: Vec<Placement> = rotations
+            .iter()
+            .map(|rot| map.clone().try_place_tile(addr, "24", *rot, None))
+            .collect();
+        assert!(results.contains(&Placement::Ok));
+        assert!(results.contains(&Placement::BrokenConnectivity));
+        assert!(results
+            .iter()
+            .all(|r| r.is_ok() || *r == Placement::BrokenConnectivity));
+        assert_eq!(map.tile_at(addr).unwrap().name, "8");
+    }
+
+    #[test]
+    fn test_max_colour() {
+        let mut map = new_map();
+        let addr: HexAddress = (0, 0).into();
+        let other: HexAddress = (0, 1).into();
+        assert!(map.set_max_colour(addr, HexColour::Yellow));
+        assert!(map.set_max_colour(other, HexColour::Empty));
+        assert!(!map.set_max_colour((5, 5).into(), HexColour::Green));
+
+        // Yellow tiles can be placed, but not upgraded to green tiles.
+        let rotations = [
+            RotateCW::Zero,
+            RotateCW::One,
+            RotateCW::Two,
+            RotateCW::Three,
+            RotateCW::Four,
+            RotateCW::Five,
+        ];
+        let placed = map.try_place_tile(addr, "8", RotateCW::Zero, None);
+        assert!(placed.is_ok());
+        let green = map.tile("24").unwrap();
+        assert!(!map.can_upgrade_to(addr, green));
+        let results: Vec<Placement> = rotations
+            .iter()
+            .map(|rot| map.clone().try_place_tile(addr, "24", *rot, None))
+            .collect();
+        assert!(results.iter().all(|r| *r == Placement::ExceedsMaxColour));
+
+        // No tiles can be placed on the other hex.
+        let yellow = map.tile("8").unwrap();
+        assert!(!map.can_place_on_empty(other, yellow));
+        let placed = map.try_place_tile(other, "8", RotateCW::Zero, None);
+        assert_eq!(placed, Placement::ExceedsMaxColour);
+
+        // Removing the limit allows green tiles to be placed.
+        map.clear_max_colour(addr);
+        let results: Vec<Placement> = rotations
+            .iter()
+            .map(|rot| map.clone().try_place_tile(addr, "24", *rot, None))
+            .collect();
+        assert!(results.contains(&Placement::Ok));
+    }
+
+    #[test]
+    fn test_city_labels() {
+        let mut map = new_map();
+        let labelled: HexAddress = (0, 0).into();
+        let unlabelled: HexAddress = (0, 1).into();
+        let rotations = [
+            RotateCW::Zero,
+            RotateCW::One,
+            RotateCW::Two,
+            RotateCW::Three,
+            RotateCW::Four,
+            RotateCW::Five,
+        ];
+        let any_ok = |map: &Map, addr: HexAddress, name: &str| {
+            rotations.iter().any(|rot| {
+                map.clone().try_place_tile(addr, name, *rot, None).is_ok()
+            })
+        };
+
+        // Only "Y" tiles can be placed on a hex with a "Y" label.
+        map.add_label_at(labelled, Label::y());
+        map.place_tile(labelled, "201", RotateCW::Zero);
+        let placed =
+            map.clone()
+                .try_place_tile(labelled, "14", RotateCW::Zero, None);
+        assert_eq!(placed, Placement::IllegalLabel);
+        assert!(any_ok(&map, labelled, "208"));
+
+        // Tiles with a "Y" label cannot replace a label-free tile.
+        map.place_tile(unlabelled, "57", RotateCW::Zero);
+        let placed = map.clone().try_place_tile(
+            unlabelled,
+            "208",
+            RotateCW::Zero,
+            None,
+        );
+        assert_eq!(placed, Placement::IllegalLabel);
+        assert!(any_ok(&map, unlabelled, "14"));
+
+        // The labels of the current tile apply to hexes without labels, so
+        // label-free tiles cannot replace a "Y" tile.
+        map.place_tile(unlabelled, "201", RotateCW::Zero);
+        let green = map.tile("14").unwrap();
+        assert!(!map.can_upgrade_to(unlabelled, green));
+        let placed = map.clone().try_place_tile(
+            unlabelled,
+            "14",
+            RotateCW::Zero,
+            None,
+        );
+        assert_eq!(placed, Placement::IllegalLabel);
+        assert!(any_ok(&map, unlabelled, "208"));
+    }
+
+    #[test]
+    fn test_token_placement() {
+        let mut map = new_map();
+        let addr: HexAddress = (0, 0).into();
+        let token = map.token("A");
+        let city_tile = map.tile("57").unwrap();
+        let space = city_tile.token_spaces()[0];
+        let mut tokens = TokensTable::new();
+        tokens.insert(space, token);
+
+        // Tile 8 has no token spaces.
+        let placed =
+            map.try_place_tile(addr, "8", RotateCW::Zero, Some(tokens));
+        assert_eq!(placed, Placement::InvalidTokenSpace(space));
+        assert!(map.tile_at(addr).is_none());
+    }
+
+    #[test]
+    fn test_token_space_availability() {
+        use n18hex::Hex;
+        use n18tile::{City, Tile};
+
+        // Add a tile whose token space is only available from green phase.
+        let hex = Hex::default();
+        let city = City::single(20).with_available_from(HexColour::Green);
+        let reserved =
+            Tile::new(HexColour::Grey, "Reserved", vec![], vec![city], &hex);
+        let mut tiles = n18catalogue::tile_catalogue();
+        tiles.push(reserved);
+        let mut map = Map::new(
+            tiles.into(),
+            new_tokens(),
+            vec![(0, 0).into()],
+            Orientation::FlatTop,
+        );
+        let addr: HexAddress = (0, 0).into();
+        let token = map.token("A");
+        assert!(map.place_tile(addr, "Reserved", RotateCW::Zero));
+        let space = map.tile_at(addr).unwrap().token_spaces()[0];
+        let rule = TokenRule::Unrestricted;
+
+        // All token spaces are available if the phase is not defined.
+        assert!(map.token_space_available(addr, &space));
+        map.set_phase_colour(HexColour::Yellow);
+        assert!(!map.token_space_available(addr, &space));
+        let placed = map.try_place_token(addr, &space, token, rule);
+        assert_eq!(placed, Err(TokenPlacement::SpaceUnavailable));
+        assert!(map.hex_state(addr).unwrap().tokens().is_empty());
+
+        for colour in [HexColour::Green, HexColour::Brown] {
+            map.set_phase_colour(colour);
+            let result =
+                map.check_token_placement(addr, &space, &token, rule);
+            assert!(result.is_ok());
+        }
+        let placed = map.try_place_token(addr, &space, token, rule);
+        assert!(placed.is_ok());
+    }
+}
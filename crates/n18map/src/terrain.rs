@@ -0,0 +1,124 @@
+//! Decorative terrain (e.g., mountains and lakes) that is printed on map
+//! hexes.
+//!
+//! Terrain is drawn beneath the tiles, so that empty and pre-printed hexes
+//! resemble the hexes on a physical game board.
+//! Terrain is purely decorative: it does not affect track-building or route
+//! revenue, and it is hidden when a tile is placed on the hex.
+
+use crate::{HexAddress, Map};
+
+/// The kinds of terrain that can be drawn on map hexes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Terrain {
+    /// Mountains, which are drawn as hatched peaks.
+    Mountain,
+    /// Lakes and other bodies of water, which are drawn as waves.
+    Water,
+}
+
+impl Terrain {
+    /// Returns every kind of terrain.
+    pub fn all() -> [Terrain; 2] {
+        [Terrain::Mountain, Terrain::Water]
+    }
+}
+
+impl std::fmt::Display for Terrain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Terrain::Mountain => write!(f, "Mountain"),
+            Terrain::Water => write!(f, "Water"),
+        }
+    }
+}
+
+impl Map {
+    /// Defines the terrain that is drawn on a map hex, replacing any
+    /// existing terrain.
+    ///
+    /// Returns `false` if `addr` is not a valid hex address for this map.
+    pub fn set_terrain(
+        &mut self,
+        addr: HexAddress,
+        terrain: Terrain,
+    ) -> bool {
+        self.touch();
+        if !self.contains_hex(addr) {
+            return false;
+        }
+        self.terrain_tbl.insert(addr, terrain);
+        true
+    }
+
+    /// Removes the terrain (if any) from a map hex.
+    pub fn clear_terrain(&mut self, addr: HexAddress) {
+        self.touch();
+        self.terrain_tbl.remove(&addr);
+    }
+
+    /// Returns the terrain that is drawn on a map hex, if any.
+    pub fn terrain_at(&self, addr: HexAddress) -> Option<Terrain> {
+        self.terrain_tbl.get(&addr).copied()
+    }
+
+    /// Returns an iterator over each map hex that has terrain, and the
+    /// terrain on that hex.
+    pub fn terrain_iter(
+        &self,
+    ) -> impl Iterator<Item = (HexAddress, Terrain)> + '_ {
+        self.terrain_tbl
+            .iter()
+            .map(|(addr, terrain)| (*addr, *terrain))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Terrain;
+    use crate::{HexAddress, Map};
+    use n18hex::{Orientation, RotateCW};
+
+    fn new_map() -> Map {
+        let addrs: Vec<HexAddress> = (0..3)
+            .flat_map(|row| (0..3).map(move |col| (row, col).into()))
+            .collect();
+        Map::new(
+            n18catalogue::tile_catalogue().into(),
+            vec![].into(),
+            addrs,
+            Orientation::FlatTop,
+        )
+    }
+
+    #[test]
+    fn set_and_clear_terrain() {
+        let mut map = new_map();
+        let a: HexAddress = (0, 1).into();
+        let b: HexAddress = (2, 2).into();
+        assert_eq!(map.terrain_at(a), None);
+
+        assert!(map.set_terrain(a, Terrain::Mountain));
+        assert!(map.set_terrain(b, Terrain::Water));
+        // Terrain cannot be defined for hexes that are not on the map.
+        assert!(!map.set_terrain((5, 5).into(), Terrain::Water));
+        assert_eq!(map.terrain_at(a), Some(Terrain::Mountain));
+
+        // Each hex has at most one kind of terrain.
+        assert!(map.set_terrain(a, Terrain::Water));
+        assert_eq!(map.terrain_at(a), Some(Terrain::Water));
+        assert_eq!(
+            map.terrain_iter().collect::<Vec<_>>(),
+            vec![(a, Terrain::Water), (b, Terrain::Water)]
+        );
+
+        // Terrain is retained when tiles are placed and removed.
+        assert!(map.place_tile(a, "3", RotateCW::Zero));
+        map.remove_tile(a);
+        assert_eq!(map.terrain_at(a), Some(Terrain::Water));
+
+        map.clear_terrain(a);
+        assert_eq!(map.terrain_at(a), None);
+        assert_eq!(map.terrain_iter().count(), 1);
+    }
+}
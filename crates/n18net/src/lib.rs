@@ -0,0 +1,995 @@
+//! Share a game between players over a network, so that several players can
+//! edit the same map at the same time.
+//!
+//! One player hosts a [Session], which listens for other players on a TCP
+//! port, and the other players join this session.
+//! Each player proposes the moves that they make (see
+//! [n18game::sim::Move]), and the host decides the order in which moves are
+//! accepted.
+//! Each accepted move is given a sequence number and is sent to every
+//! player, including the player who proposed it.
+//! The host does not check moves against the game rules, and so every player
+//! must apply each accepted move in the same way (e.g., with a
+//! [n18game::sim::Board] that allows free placement), so that their copies
+//! of the game remain identical.
+//!
+//! ```rust,no_run
+//! # use n18game::Game;
+//! # use n18game::sim::Move;
+//! # use n18hex::Hex;
+//! # type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+//! # fn example() -> Result {
+//! let game = n18game::new_1830();
+//! let map = game.create_map(&Hex::default());
+//! let state = game.save(&map);
+//! let mut host = n18net::Session::host("0.0.0.0:8019", state, || {})?;
+//! // Another player joins the session, and receives the saved game state.
+//! let mut player = n18net::Session::join(host.local_addr(), || {})?;
+//! assert_eq!(player.initial_state(), host.initial_state());
+//! player.propose(Move::AdvancePhase)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Resolving conflicts
+//!
+//! Players apply their own moves immediately, before the host has accepted
+//! them.
+//! When a player proposes a move, they include the number of accepted moves
+//! that they have received (see [Session::seq]).
+//! If the host has since accepted a move from a different player, the
+//! proposed move may conflict with that move, and so the host rejects it
+//! (see [Event::Rejected]).
+//! The player should then discard their own changes, and restore the game
+//! from [Session::initial_state] and [Session::moves].
+//!
+//! # Protocol
+//!
+//! Each message is a single line of JSON text.
+//! The host sends a `Welcome` message to each new player, which contains
+//! the player's identifier, the game state when the session began, and the
+//! moves that have been accepted so far.
+//! Players send `Propose` messages, and the host sends `Applied` messages to
+//! every player and `Rejected` messages to the proposing player.
+//! Moves are encoded as per [n18io::move_to_string].
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use n18game::sim::Move;
+use n18game::GameState;
+
+/// The identifier of the player who hosts the session.
+const HOST: usize = 0;
+
+/// How long to wait when sending a message before disconnecting a player
+/// (or the host) who is not receiving messages.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The maximum length (in bytes) of a message that proposes, accepts, or
+/// rejects a single move.
+const MAX_MOVE_LEN: u64 = 64 * 1024;
+
+/// The maximum length (in bytes) of the message that welcomes a player,
+/// which contains the initial game state and every accepted move.
+const MAX_WELCOME_LEN: u64 = 16 * 1024 * 1024;
+
+/// The errors that can occur when hosting, joining, or sending moves to a
+/// session.
+#[derive(Debug)]
+pub enum Error {
+    /// A network connection failed.
+    Io(std::io::Error),
+    /// A game state or a move could not be encoded or decoded.
+    Encoding(n18io::Error),
+    /// A message did not contain valid JSON, or was not expected.
+    Protocol(String),
+    /// The host closed the connection.
+    Closed,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> Result<(), std::fmt::Error> {
+        use Error::*;
+        match self {
+            Io(error) => write!(f, "{}", error),
+            Encoding(error) => write!(f, "{}", error),
+            Protocol(msg) => write!(f, "Invalid message: {}", msg),
+            Closed => write!(f, "The host closed the connection"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            Error::Encoding(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<n18io::Error> for Error {
+    fn from(error: n18io::Error) -> Self {
+        Error::Encoding(error)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::Protocol(error.to_string())
+    }
+}
+
+/// The changes to a session that are reported by [Session::try_recv].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// The host accepted a move, which is the `seq`-th move of the session.
+    ///
+    /// If `own` is `true`, this player proposed the move and has already
+    /// applied it.
+    Applied { seq: usize, mv: Move, own: bool },
+    /// The host rejected a move that this player proposed, because another
+    /// player's move was accepted first.
+    Rejected(Move),
+    /// A player (identified by a number) joined the session.
+    PlayerJoined(usize),
+    /// A player (identified by a number) left the session.
+    PlayerLeft(usize),
+    /// The connection to the host was closed.
+    Disconnected,
+}
+
+/// The messages that are sent between the host and the other players.
+#[derive(Serialize, Deserialize)]
+enum Message {
+    Welcome {
+        player: usize,
+        state: serde_json::Value,
+        moves: Vec<serde_json::Value>,
+    },
+    Propose {
+        base: usize,
+        mv: serde_json::Value,
+    },
+    Applied {
+        seq: usize,
+        origin: usize,
+        mv: serde_json::Value,
+    },
+    Rejected {
+        mv: serde_json::Value,
+    },
+}
+
+/// The messages that are passed from the network threads to a [Session].
+enum Incoming {
+    Applied { seq: usize, origin: usize, mv: Move },
+    Rejected(Move),
+    Joined(usize),
+    Left(usize),
+    Closed,
+}
+
+/// A function that is called whenever a session receives a message.
+type Notify = Arc<dyn Fn() + Send + Sync>;
+
+/// A player who has joined a hosted session.
+struct Peer {
+    player: usize,
+    /// The connection to the player, which is only used to close it.
+    stream: TcpStream,
+    /// The messages to be sent to the player, which are written by a
+    /// separate thread (see [send_messages]) so that a slow player cannot
+    /// delay the host or the other players.
+    outbox: Sender<String>,
+}
+
+/// The accepted moves and connected players of a hosted session.
+struct Log {
+    state: serde_json::Value,
+    moves: Vec<(usize, Move)>,
+    players: Vec<Peer>,
+    next_player: usize,
+    /// Whether the host has left the session.
+    closed: bool,
+}
+
+impl Log {
+    /// Returns `true` if a move proposed by `origin`, after receiving `base`
+    /// accepted moves, does not conflict with any other player's moves.
+    fn is_current(&self, base: usize, origin: usize) -> bool {
+        base <= self.moves.len()
+            && self.moves[base..]
+                .iter()
+                .all(|(player, _)| *player == origin)
+    }
+
+    /// Accepts a move, and sends it to every player.
+    fn accept(&mut self, origin: usize, mv: Move, sender: &Sender<Incoming>) {
+        self.moves.push((origin, mv.clone()));
+        let seq = self.moves.len();
+        match encode_move(&mv) {
+            Ok(value) => {
+                let msg = Message::Applied {
+                    seq,
+                    origin,
+                    mv: value,
+                };
+                match encode_message(&msg) {
+                    Ok(text) => {
+                        // NOTE: the message is only queued here, so that
+                        // the log is not locked while it is being sent.
+                        self.players.retain(|peer| {
+                            peer.outbox.send(text.clone()).is_ok()
+                        });
+                    }
+                    Err(e) => warn!("Could not encode move: {}", e),
+                }
+            }
+            Err(e) => warn!("Could not encode move: {}", e),
+        }
+        let _ = sender.send(Incoming::Applied { seq, origin, mv });
+    }
+
+    /// Sends a rejected move to the player who proposed it.
+    fn reject(&mut self, origin: usize, mv: Move, sender: &Sender<Incoming>) {
+        if origin == HOST {
+            let _ = sender.send(Incoming::Rejected(mv));
+            return;
+        }
+        let peer = self.players.iter().find(|peer| peer.player == origin);
+        if let Some(peer) = peer {
+            let result = encode_move(&mv).and_then(|value| {
+                encode_message(&Message::Rejected { mv: value })
+            });
+            match result {
+                Ok(text) => {
+                    let _ = peer.outbox.send(text);
+                }
+                Err(e) => warn!(
+                    "Could not reject move from player {}: {}",
+                    origin, e
+                ),
+            }
+        }
+    }
+}
+
+/// The connection between a player and the rest of the session.
+enum Link {
+    Host {
+        addr: SocketAddr,
+        log: Arc<Mutex<Log>>,
+        sender: Sender<Incoming>,
+        notify: Notify,
+        /// Whether the thread that listens for players should stop.
+        shutdown: Arc<AtomicBool>,
+        /// The thread that listens for players.
+        listener: Option<JoinHandle<()>>,
+    },
+    Player {
+        stream: TcpStream,
+    },
+}
+
+/// A game that is shared between several players.
+///
+/// The network connections are managed by separate threads, which call the
+/// `notify` function provided to [Session::host] and [Session::join]
+/// whenever a message is received, so that the user interface can then call
+/// [Session::try_recv].
+pub struct Session {
+    player: usize,
+    link: Link,
+    receiver: Receiver<Incoming>,
+    initial: GameState,
+    moves: Vec<Move>,
+}
+
+impl Session {
+    /// Hosts a new session for a saved game state, and listens for other
+    /// players in a separate thread.
+    ///
+    /// Use port `0` to have the operating system choose an available port,
+    /// and [Session::local_addr] to identify the chosen port.
+    pub fn host<A, F>(
+        addr: A,
+        state: GameState,
+        notify: F,
+    ) -> Result<Self, Error>
+    where
+        A: ToSocketAddrs,
+        F: Fn() + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let text = n18io::game_state_to_string(state.clone(), false)?;
+        let log = Arc::new(Mutex::new(Log {
+            state: serde_json::from_str(&text)?,
+            moves: vec![],
+            players: vec![],
+            next_player: HOST + 1,
+            closed: false,
+        }));
+        let notify: Notify = Arc::new(notify);
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        let shared = Arc::clone(&log);
+        let player_tx = sender.clone();
+        let player_notify = Arc::clone(&notify);
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let stop = Arc::clone(&shutdown);
+        let listener = std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                // NOTE: the session connects to the listener when it is
+                // dropped, so that this loop stops and the port is closed.
+                if stop.load(Ordering::SeqCst) {
+                    return;
+                }
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        warn!("Could not accept player: {}", e);
+                        continue;
+                    }
+                };
+                let log = Arc::clone(&shared);
+                let sender = player_tx.clone();
+                let notify = Arc::clone(&player_notify);
+                std::thread::spawn(move || {
+                    serve_player(stream, &log, &sender, &notify)
+                });
+            }
+        });
+        info!("Hosting a shared game at {}", addr);
+
+        Ok(Session {
+            player: HOST,
+            link: Link::Host {
+                addr,
+                log,
+                sender,
+                notify,
+                shutdown,
+                listener: Some(listener),
+            },
+            receiver,
+            initial: state,
+            moves: vec![],
+        })
+    }
+
+    /// Joins a session, and receives the initial game state and the moves
+    /// that have been accepted so far.
+    pub fn join<A, F>(addr: A, notify: F) -> Result<Self, Error>
+    where
+        A: ToSocketAddrs,
+        F: Fn() + Send + Sync + 'static,
+    {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let Some(line) = read_message(&mut reader, MAX_WELCOME_LEN)? else {
+            return Err(Error::Closed);
+        };
+        let Message::Welcome {
+            player,
+            state,
+            moves,
+        } = serde_json::from_str(&line)?
+        else {
+            return Err(Error::Protocol("expected a welcome".to_string()));
+        };
+        let initial = n18io::game_state_from_str(&state.to_string())?;
+        let moves = moves
+            .into_iter()
+            .map(decode_move)
+            .collect::<Result<Vec<Move>, Error>>()?;
+
+        let notify: Notify = Arc::new(notify);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            if let Err(e) = receive_moves(reader, &sender, &notify) {
+                warn!("Disconnected from the host: {}", e)
+            }
+            let _ = sender.send(Incoming::Closed);
+            notify()
+        });
+        info!("Joined a shared game at {}", stream.peer_addr()?);
+
+        Ok(Session {
+            player,
+            link: Link::Player { stream },
+            receiver,
+            initial,
+            moves,
+        })
+    }
+
+    /// Returns `true` if this player is hosting the session.
+    pub fn is_host(&self) -> bool {
+        self.player == HOST
+    }
+
+    /// Returns the number that identifies this player, which is zero for
+    /// the host.
+    pub fn player(&self) -> usize {
+        self.player
+    }
+
+    /// Returns the address on which the host is listening for players.
+    pub fn local_addr(&self) -> SocketAddr {
+        match &self.link {
+            Link::Host { addr, .. } => *addr,
+            Link::Player { stream } => stream
+                .peer_addr()
+                .unwrap_or_else(|_| SocketAddr::from(([0, 0, 0, 0], 0))),
+        }
+    }
+
+    /// Returns the game state when the session began.
+    pub fn initial_state(&self) -> &GameState {
+        &self.initial
+    }
+
+    /// Returns the accepted moves that this player has received, in the
+    /// order that they were accepted.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// Returns the number of accepted moves that this player has received.
+    pub fn seq(&self) -> usize {
+        self.moves.len()
+    }
+
+    /// Proposes a move, which this player has already applied.
+    ///
+    /// The host will either accept this move and send it to every player
+    /// (see [Event::Applied]) or reject it (see [Event::Rejected]).
+    pub fn propose(&mut self, mv: Move) -> Result<(), Error> {
+        let base = self.seq();
+        match &mut self.link {
+            Link::Host {
+                log,
+                sender,
+                notify,
+                ..
+            } => {
+                let mut log = log
+                    .lock()
+                    .map_err(|_| Error::Protocol("no log".to_string()))?;
+                if log.is_current(base, HOST) {
+                    log.accept(HOST, mv, sender)
+                } else {
+                    log.reject(HOST, mv, sender)
+                }
+                drop(log);
+                notify();
+                Ok(())
+            }
+            Link::Player { stream } => {
+                let mv = encode_move(&mv)?;
+                write_message(stream, &Message::Propose { base, mv })
+            }
+        }
+    }
+
+    /// Returns the next change to the session, if any, without waiting.
+    pub fn try_recv(&mut self) -> Option<Event> {
+        let incoming = self.receiver.try_recv().ok()?;
+        Some(self.handle(incoming))
+    }
+
+    /// Waits for the next change to the session, and returns `None` if
+    /// there were no changes before the `timeout` elapsed.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Option<Event> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(incoming) => Some(self.handle(incoming)),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => Some(Event::Disconnected),
+        }
+    }
+
+    fn handle(&mut self, incoming: Incoming) -> Event {
+        match incoming {
+            Incoming::Applied { seq, origin, mv } => {
+                if seq != self.moves.len() + 1 {
+                    warn!("Expected move #{}, received #{}", self.seq(), seq)
+                }
+                self.moves.push(mv.clone());
+                Event::Applied {
+                    seq,
+                    mv,
+                    own: origin == self.player,
+                }
+            }
+            Incoming::Rejected(mv) => Event::Rejected(mv),
+            Incoming::Joined(player) => Event::PlayerJoined(player),
+            Incoming::Left(player) => Event::PlayerLeft(player),
+            Incoming::Closed => Event::Disconnected,
+        }
+    }
+}
+
+impl Drop for Session {
+    /// Closes the connections to the other players and stops listening for
+    /// players, if this player is the host, or closes the connection to the
+    /// host otherwise.
+    fn drop(&mut self) {
+        match &mut self.link {
+            Link::Host {
+                addr,
+                log,
+                shutdown,
+                listener,
+                ..
+            } => {
+                if let Ok(mut log) = log.lock() {
+                    log.closed = true;
+                    for peer in log.players.drain(..) {
+                        let _ = peer.stream.shutdown(Shutdown::Both);
+                    }
+                }
+                shutdown.store(true, Ordering::SeqCst);
+                // NOTE: only wait for the listening thread if it will accept
+                // this connection, otherwise it would never stop.
+                if TcpStream::connect(wake_addr(*addr)).is_ok() {
+                    if let Some(listener) = listener.take() {
+                        let _ = listener.join();
+                    }
+                }
+            }
+            Link::Player { stream } => {
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+        }
+    }
+}
+
+/// Welcomes a new player to a hosted session, and handles each move that
+/// they propose until they leave.
+fn serve_player(
+    stream: TcpStream,
+    log: &Mutex<Log>,
+    sender: &Sender<Incoming>,
+    notify: &Notify,
+) {
+    let player = match welcome(stream, log) {
+        Ok((player, reader)) => {
+            let _ = sender.send(Incoming::Joined(player));
+            notify();
+            if let Err(e) =
+                receive_proposals(player, reader, log, sender, notify)
+            {
+                warn!("Disconnected from player {}: {}", player, e)
+            }
+            player
+        }
+        Err(e) => {
+            warn!("Could not welcome player: {}", e);
+            return;
+        }
+    };
+    if let Ok(mut log) = log.lock() {
+        log.players.retain(|peer| peer.player != player)
+    }
+    let _ = sender.send(Incoming::Left(player));
+    notify()
+}
+
+/// Sends the initial game state and the accepted moves to a new player, and
+/// returns the player's identifier and a reader for their messages.
+fn welcome(
+    stream: TcpStream,
+    log: &Mutex<Log>,
+) -> Result<(usize, BufReader<TcpStream>), Error> {
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let reader = BufReader::new(stream.try_clone()?);
+    let writer = stream.try_clone()?;
+    let mut log = log
+        .lock()
+        .map_err(|_| Error::Protocol("no log".to_string()))?;
+    if log.closed {
+        return Err(Error::Closed);
+    }
+    let player = log.next_player;
+    log.next_player += 1;
+    let moves = log
+        .moves
+        .iter()
+        .map(|(_, mv)| encode_move(mv))
+        .collect::<Result<Vec<_>, Error>>()?;
+    let msg = Message::Welcome {
+        player,
+        state: log.state.clone(),
+        moves,
+    };
+    // NOTE: queue the welcome before the player is added to the session, so
+    // that it is sent before any subsequent moves.
+    let (outbox, queue) = std::sync::mpsc::channel();
+    outbox
+        .send(encode_message(&msg)?)
+        .map_err(|_| Error::Closed)?;
+    std::thread::spawn(move || send_messages(player, writer, queue));
+    log.players.push(Peer {
+        player,
+        stream,
+        outbox,
+    });
+    Ok((player, reader))
+}
+
+/// Sends each queued message to a player, and closes the connection if a
+/// message cannot be sent (e.g., if the player stops receiving messages and
+/// the [WRITE_TIMEOUT] elapses).
+fn send_messages(
+    player: usize,
+    mut stream: TcpStream,
+    queue: Receiver<String>,
+) {
+    for text in queue {
+        let result = stream
+            .write_all(text.as_bytes())
+            .and_then(|_| stream.flush());
+        if let Err(e) = result {
+            warn!("Could not send message to player {}: {}", player, e);
+            // NOTE: this also ends the thread that receives the player's
+            // messages, which removes the player from the session.
+            let _ = stream.shutdown(Shutdown::Both);
+            return;
+        }
+    }
+}
+
+/// Accepts or rejects each move that a player proposes.
+fn receive_proposals(
+    player: usize,
+    mut reader: BufReader<TcpStream>,
+    log: &Mutex<Log>,
+    sender: &Sender<Incoming>,
+    notify: &Notify,
+) -> Result<(), Error> {
+    while let Some(line) = read_message(&mut reader, MAX_MOVE_LEN)? {
+        let Message::Propose { base, mv } = serde_json::from_str(&line)?
+        else {
+            return Err(Error::Protocol("expected a move".to_string()));
+        };
+        let mv = decode_move(mv)?;
+        let mut log = log
+            .lock()
+            .map_err(|_| Error::Protocol("no log".to_string()))?;
+        if log.is_current(base, player) {
+            log.accept(player, mv, sender)
+        } else {
+            log.reject(player, mv, sender)
+        }
+        drop(log);
+        notify();
+    }
+    Ok(())
+}
+
+/// Receives each accepted or rejected move from the host.
+fn receive_moves(
+    mut reader: BufReader<TcpStream>,
+    sender: &Sender<Incoming>,
+    notify: &Notify,
+) -> Result<(), Error> {
+    while let Some(line) = read_message(&mut reader, MAX_MOVE_LEN)? {
+        let incoming = match serde_json::from_str(&line)? {
+            Message::Applied { seq, origin, mv } => Incoming::Applied {
+                seq,
+                origin,
+                mv: decode_move(mv)?,
+            },
+            Message::Rejected { mv } => Incoming::Rejected(decode_move(mv)?),
+            _ => return Err(Error::Protocol("expected a move".to_string())),
+        };
+        if sender.send(incoming).is_err() {
+            // The session no longer exists.
+            return Ok(());
+        }
+        notify();
+    }
+    Ok(())
+}
+
+/// Returns the address with which to connect to a listener that is bound to
+/// `addr`, which may be an unspecified address (e.g., `0.0.0.0`).
+fn wake_addr(addr: SocketAddr) -> SocketAddr {
+    match addr {
+        SocketAddr::V4(v4) if v4.ip().is_unspecified() => {
+            SocketAddr::from((Ipv4Addr::LOCALHOST, v4.port()))
+        }
+        SocketAddr::V6(v6) if v6.ip().is_unspecified() => {
+            SocketAddr::from((Ipv6Addr::LOCALHOST, v6.port()))
+        }
+        _ => addr,
+    }
+}
+
+/// Reads a single message, which may be no longer than `max_len` bytes, and
+/// returns `None` if the connection was closed.
+///
+/// NOTE: messages are read with a length limit, so that a peer cannot make
+/// this player buffer an unbounded amount of text.
+fn read_message<R: BufRead>(
+    reader: &mut R,
+    max_len: u64,
+) -> Result<Option<String>, Error> {
+    let mut line = String::new();
+    let len = reader.by_ref().take(max_len).read_line(&mut line)?;
+    if len == 0 {
+        Ok(None)
+    } else if !line.ends_with('\n') && len as u64 == max_len {
+        Err(Error::Protocol("the message is too long".to_string()))
+    } else {
+        Ok(Some(line))
+    }
+}
+
+/// Encodes a message as a single line of JSON text.
+fn encode_message(msg: &Message) -> Result<String, Error> {
+    let mut text = serde_json::to_string(msg)?;
+    text.push('\n');
+    Ok(text)
+}
+
+/// Writes a message as a single line of JSON text.
+fn write_message<W: Write>(
+    writer: &mut W,
+    msg: &Message,
+) -> Result<(), Error> {
+    writer.write_all(encode_message(msg)?.as_bytes())?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn encode_move(mv: &Move) -> Result<serde_json::Value, Error> {
+    let text = n18io::move_to_string(mv, false)?;
+    Ok(serde_json::from_str(&text)?)
+}
+
+fn decode_move(value: serde_json::Value) -> Result<Move, Error> {
+    Ok(n18io::move_from_str(&value.to_string())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Event, Session};
+    use n18game::sim::Move;
+    use n18game::{Game, GameState};
+    use n18hex::{Hex, RotateCW};
+    use std::time::Duration;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn new_state() -> GameState {
+        let game = n18game::new_1830();
+        let map = game.create_map(&Hex::default());
+        game.save(&map)
+    }
+
+    fn lay_tile(tile: &str) -> Move {
+        Move::LayTile {
+            addr: (2, 3).into(),
+            tile: tile.to_string(),
+            rotation: RotateCW::One,
+            company: None,
+        }
+    }
+
+    /// Returns the next event that is not a player joining or leaving.
+    fn next_move(session: &mut Session) -> Event {
+        loop {
+            match session.recv_timeout(TIMEOUT) {
+                Some(Event::PlayerJoined(_)) | Some(Event::PlayerLeft(_)) => {
+                }
+                Some(event) => return event,
+                None => panic!("No event was received"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_share_moves() {
+        let state = new_state();
+        let mut host = Session::host("127.0.0.1:0", state, || {}).unwrap();
+        assert!(host.is_host());
+        let mut player = Session::join(host.local_addr(), || {}).unwrap();
+        assert!(!player.is_host());
+        assert_eq!(player.player(), 1);
+        assert_eq!(player.initial_state(), host.initial_state());
+        assert!(player.moves().is_empty());
+        assert_eq!(host.recv_timeout(TIMEOUT), Some(Event::PlayerJoined(1)));
+
+        // Each move is sent to every player, including the player who
+        // proposed it.
+        player.propose(lay_tile("8")).unwrap();
+        let applied = Event::Applied {
+            seq: 1,
+            mv: lay_tile("8"),
+            own: false,
+        };
+        assert_eq!(next_move(&mut host), applied);
+        host.propose(Move::AdvancePhase).unwrap();
+        assert_eq!(
+            next_move(&mut host),
+            Event::Applied {
+                seq: 2,
+                mv: Move::AdvancePhase,
+                own: true
+            }
+        );
+        assert_eq!(
+            next_move(&mut player),
+            Event::Applied {
+                seq: 1,
+                mv: lay_tile("8"),
+                own: true
+            }
+        );
+        assert_eq!(
+            next_move(&mut player),
+            Event::Applied {
+                seq: 2,
+                mv: Move::AdvancePhase,
+                own: false
+            }
+        );
+        assert_eq!(player.moves(), host.moves());
+
+        // A player who joins later receives every accepted move.
+        let late = Session::join(host.local_addr(), || {}).unwrap();
+        assert_eq!(late.player(), 2);
+        assert_eq!(late.moves(), host.moves());
+        assert_eq!(host.recv_timeout(TIMEOUT), Some(Event::PlayerJoined(2)));
+        drop(late);
+        assert_eq!(host.recv_timeout(TIMEOUT), Some(Event::PlayerLeft(2)));
+    }
+
+    #[test]
+    fn test_reject_conflicting_moves() {
+        let mut host =
+            Session::host("127.0.0.1:0", new_state(), || {}).unwrap();
+        let mut player = Session::join(host.local_addr(), || {}).unwrap();
+
+        // The host makes a move before the player has received it, so the
+        // player's move is rejected.
+        host.propose(lay_tile("8")).unwrap();
+        player.propose(lay_tile("9")).unwrap();
+        player.propose(Move::AdvancePhase).unwrap();
+        assert_eq!(
+            next_move(&mut player),
+            Event::Applied {
+                seq: 1,
+                mv: lay_tile("8"),
+                own: false
+            }
+        );
+        assert_eq!(next_move(&mut player), Event::Rejected(lay_tile("9")));
+        assert_eq!(
+            next_move(&mut player),
+            Event::Rejected(Move::AdvancePhase)
+        );
+
+        // Once the player has received the host's move, their moves are
+        // accepted, and subsequent moves do not conflict with their own
+        // moves.
+        player.propose(lay_tile("9")).unwrap();
+        player.propose(Move::AdvancePhase).unwrap();
+        for (seq, mv) in [(2, lay_tile("9")), (3, Move::AdvancePhase)] {
+            let own = Event::Applied {
+                seq,
+                mv: mv.clone(),
+                own: true,
+            };
+            assert_eq!(next_move(&mut player), own);
+        }
+
+        // The host's own moves are rejected if the host has not yet received
+        // the player's moves.
+        host.propose(Move::SetPhase(0)).unwrap();
+        assert_eq!(
+            next_move(&mut host),
+            Event::Applied {
+                seq: 1,
+                mv: lay_tile("8"),
+                own: true
+            }
+        );
+        assert!(matches!(next_move(&mut host), Event::Applied { .. }));
+        assert!(matches!(next_move(&mut host), Event::Applied { .. }));
+        assert_eq!(next_move(&mut host), Event::Rejected(Move::SetPhase(0)));
+        assert_eq!(host.seq(), 3);
+        assert_eq!(host.moves(), player.moves());
+    }
+
+    #[test]
+    fn test_unresponsive_player() {
+        let mut host =
+            Session::host("127.0.0.1:0", new_state(), || {}).unwrap();
+        // This player never reads any messages, so the host cannot send
+        // them any further moves once the connection's buffers are full.
+        let _idle = std::net::TcpStream::connect(host.local_addr()).unwrap();
+        assert_eq!(host.recv_timeout(TIMEOUT), Some(Event::PlayerJoined(1)));
+        let count = 20_000;
+        for _ in 0..count {
+            host.propose(Move::AdvancePhase).unwrap();
+        }
+        for seq in 1..=count {
+            let applied = Event::Applied {
+                seq,
+                mv: Move::AdvancePhase,
+                own: true,
+            };
+            assert_eq!(next_move(&mut host), applied);
+        }
+    }
+
+    #[test]
+    fn test_message_too_long() {
+        use std::io::Write;
+
+        let mut host =
+            Session::host("127.0.0.1:0", new_state(), || {}).unwrap();
+        let mut stream =
+            std::net::TcpStream::connect(host.local_addr()).unwrap();
+        assert_eq!(host.recv_timeout(TIMEOUT), Some(Event::PlayerJoined(1)));
+        // A player who sends a message that never ends is disconnected.
+        let text = "x".repeat(super::MAX_MOVE_LEN as usize + 1);
+        // NOTE: the host may close the connection before the whole text has
+        // been sent.
+        let _ = stream.write_all(text.as_bytes());
+        assert_eq!(host.recv_timeout(TIMEOUT), Some(Event::PlayerLeft(1)));
+
+        // Other players can still join the session.
+        let _player = Session::join(host.local_addr(), || {}).unwrap();
+        assert_eq!(host.recv_timeout(TIMEOUT), Some(Event::PlayerJoined(2)));
+    }
+
+    #[test]
+    fn test_disconnected() {
+        let host = Session::host("127.0.0.1:0", new_state(), || {}).unwrap();
+        let addr = host.local_addr();
+        let mut player = Session::join(addr, || {}).unwrap();
+        assert_eq!(player.try_recv(), None);
+        // Players are disconnected when the host leaves, and can no longer
+        // join the session.
+        drop(host);
+        assert_eq!(player.recv_timeout(TIMEOUT), Some(Event::Disconnected));
+        assert!(Session::join(addr, || {}).is_err());
+    }
+
+    #[test]
+    fn test_host_again() {
+        let host = Session::host("127.0.0.1:0", new_state(), || {}).unwrap();
+        let addr = host.local_addr();
+        // The port is closed when the host leaves, so that a new session
+        // can be hosted at the same address.
+        drop(host);
+        let host = Session::host(addr, new_state(), || {}).unwrap();
+        assert_eq!(host.local_addr(), addr);
+        let player = Session::join(addr, || {}).unwrap();
+        assert_eq!(player.player(), 1);
+    }
+}
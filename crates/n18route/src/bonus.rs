@@ -1,7 +1,7 @@
 //! Route bonuses that can increase revenue.
 
 use crate::Train;
-use n18map::HexAddress;
+use n18map::{HexAddress, Map};
 
 /// The different types of route bonus that may be applied.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -20,4 +20,94 @@ pub enum Bonus {
         to_any: Vec<HexAddress>,
         bonus: usize,
     },
+    /// A bonus for operating a route that starts at any of the first
+    /// locations and ends at any of the second locations, or vice versa
+    /// (e.g., an east-west bonus).
+    ///
+    /// Unlike [Bonus::ConnectionBonus], these locations must be the first
+    /// and last stops of the route.
+    TerminusBonus {
+        endpoints: (Vec<HexAddress>, Vec<HexAddress>),
+        bonus: usize,
+    },
+    /// A bonus for visiting any location that has the named marker (see
+    /// [Map::place_marker]), such as a port or mine marker.
+    ///
+    /// These bonuses depend on the map state, and must be converted into
+    /// [Bonus::VisitBonus] bonuses with [Bonus::resolve_markers] before
+    /// routes are selected; unresolved marker bonuses are ignored.
+    MarkerBonus { marker: String, bonus: usize },
+}
+
+impl Bonus {
+    /// Replaces each [Bonus::MarkerBonus] with a [Bonus::VisitBonus] for
+    /// each map hex that has the named marker, and returns the resulting
+    /// bonuses.
+    pub fn resolve_markers(bonuses: Vec<Bonus>, map: &Map) -> Vec<Bonus> {
+        bonuses
+            .into_iter()
+            .flat_map(|b| match b {
+                Bonus::MarkerBonus { marker, bonus } => map
+                    .marker_hexes(&marker)
+                    .into_iter()
+                    .map(|locn| Bonus::VisitBonus { locn, bonus })
+                    .collect(),
+                b => vec![b],
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bonus;
+    use n18hex::Orientation;
+    use n18map::{HexAddress, Map};
+
+    #[test]
+    fn resolve_marker_bonuses() {
+        let addrs: Vec<HexAddress> =
+            vec![(0, 0).into(), (0, 1).into(), (1, 0).into()];
+        let mut map = Map::new(
+            n18catalogue::tile_catalogue().into(),
+            vec![].into(),
+            addrs.clone(),
+            Orientation::FlatTop,
+        );
+        assert!(map.place_marker(addrs[0], "Port"));
+        assert!(map.place_marker(addrs[2], "Port"));
+        assert!(map.place_marker(addrs[2], "Mine"));
+
+        let connect = Bonus::ConnectionBonus {
+            from: addrs[0],
+            to_any: vec![addrs[1]],
+            bonus: 20,
+        };
+        let bonuses = vec![
+            Bonus::MarkerBonus {
+                marker: "Port".to_string(),
+                bonus: 10,
+            },
+            connect.clone(),
+            Bonus::MarkerBonus {
+                marker: "Bridge".to_string(),
+                bonus: 40,
+            },
+        ];
+        let resolved = Bonus::resolve_markers(bonuses, &map);
+        assert_eq!(
+            resolved,
+            vec![
+                Bonus::VisitBonus {
+                    locn: addrs[0],
+                    bonus: 10
+                },
+                Bonus::VisitBonus {
+                    locn: addrs[2],
+                    bonus: 10
+                },
+                connect,
+            ]
+        );
+    }
 }
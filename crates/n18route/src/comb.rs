@@ -63,15 +63,32 @@ impl Iterator for Combinations {
 /// By providing the filtering function as part of this iterator, instead of
 /// calling `iter.filter()`, this prunes *k*-combinations where, e.g., the
 /// first two values should be excluded.
-pub struct CombinationsFilter<F: Fn(usize, usize) -> bool> {
+///
+/// A pruning function can also be provided (see
+/// [CombinationsFilter::with_pruning]), which excludes a combination and
+/// every larger combination that begins with it.
+pub struct CombinationsFilter<
+    F: Fn(usize, usize) -> bool,
+    P: Fn(&[usize]) -> bool = NoPruning,
+> {
     item_count: usize,
     max_len: usize,
     items: Vec<usize>,
     current_ix: usize,
     item_filter: F,
+    prune: P,
     filter_calls: usize,
 }
 
+/// The type of the default pruning function, which never prunes any
+/// combinations.
+pub type NoPruning = fn(&[usize]) -> bool;
+
+/// A pruning function that never prunes any combinations.
+fn never_prune(_items: &[usize]) -> bool {
+    false
+}
+
 impl<F: Fn(usize, usize) -> bool> CombinationsFilter<F> {
     /// Create an iterator over *k*-combinations of a set of size *n*, for all
     /// *k* up to the limit *k_max*, filtering out combinations for which
@@ -83,11 +100,37 @@ impl<F: Fn(usize, usize) -> bool> CombinationsFilter<F> {
             items: Vec::with_capacity(k_max),
             current_ix: 0,
             item_filter: ignore,
+            prune: never_prune,
             filter_calls: 0,
         }
     }
 }
 
+impl<F: Fn(usize, usize) -> bool, P: Fn(&[usize]) -> bool>
+    CombinationsFilter<F, P>
+{
+    /// Also filter out each combination for which `prune` returns `true`,
+    /// and every larger combination that begins with this combination.
+    ///
+    /// This allows a branch-and-bound search to skip entire branches of the
+    /// depth-first search, when no combination in that branch can be better
+    /// than the best combination found so far.
+    pub fn with_pruning<Q: Fn(&[usize]) -> bool>(
+        self,
+        prune: Q,
+    ) -> CombinationsFilter<F, Q> {
+        CombinationsFilter {
+            item_count: self.item_count,
+            max_len: self.max_len,
+            items: self.items,
+            current_ix: self.current_ix,
+            item_filter: self.item_filter,
+            prune,
+            filter_calls: self.filter_calls,
+        }
+    }
+}
+
 /// Implement a parallel iterator for filtered subsets of *k*-combinations.
 ///
 /// While this is conceptually simple — divide the interval `[0, n-1]` for
@@ -106,18 +149,25 @@ impl<F: Fn(usize, usize) -> bool> CombinationsFilter<F> {
 /// split into multiple producers, and also implements `Iterator`, so that it
 /// can yield each valid *k*-combination and feed them to the folder.
 pub mod par {
-    pub struct CombFilt<F: Fn(usize, usize) -> bool + Send + Sync> {
+    pub struct CombFilt<
+        F: Fn(usize, usize) -> bool + Send + Sync,
+        P: Fn(&[usize]) -> bool + Send + Sync = super::NoPruning,
+    > {
         item_count: usize,
         max_len: usize,
         current_ix: usize,
         ix0_max: usize,
         item_filter: F,
+        prune: P,
     }
 
-    impl<F: Fn(usize, usize) -> bool + Send + Sync>
-        rayon::iter::IntoParallelIterator for super::CombinationsFilter<F>
+    impl<
+            F: Fn(usize, usize) -> bool + Send + Sync,
+            P: Fn(&[usize]) -> bool + Send + Sync,
+        > rayon::iter::IntoParallelIterator
+        for super::CombinationsFilter<F, P>
     {
-        type Iter = CombFilt<F>;
+        type Iter = CombFilt<F, P>;
         type Item = Vec<usize>;
 
         fn into_par_iter(self) -> Self::Iter {
@@ -131,22 +181,30 @@ pub mod par {
                 max_len: self.max_len,
                 current_ix: self.current_ix,
                 item_filter: self.item_filter,
+                prune: self.prune,
                 ix0_max: self.item_count,
             }
         }
     }
 
-    pub struct CombFiltProducer<'a, F: Fn(usize, usize) -> bool + Send + Sync> {
+    pub struct CombFiltProducer<
+        'a,
+        F: Fn(usize, usize) -> bool + Send + Sync,
+        P: Fn(&[usize]) -> bool + Send + Sync,
+    > {
         item_count: usize,
         max_len: usize,
         items: Vec<usize>,
         current_ix: usize,
         ix0_max: usize,
         item_filter: &'a F,
+        prune: &'a P,
     }
 
-    impl<F: Fn(usize, usize) -> bool + Send + Sync>
-        rayon::iter::ParallelIterator for CombFilt<F>
+    impl<
+            F: Fn(usize, usize) -> bool + Send + Sync,
+            P: Fn(&[usize]) -> bool + Send + Sync,
+        > rayon::iter::ParallelIterator for CombFilt<F, P>
     {
         type Item = Vec<usize>;
 
@@ -161,12 +219,18 @@ pub mod par {
                 current_ix: self.current_ix,
                 ix0_max: self.ix0_max,
                 item_filter: &self.item_filter,
+                prune: &self.prune,
             };
             rayon::iter::plumbing::bridge_unindexed(producer, consumer)
         }
     }
 
-    impl<'a, F: Fn(usize, usize) -> bool + Send + Sync> CombFiltProducer<'a, F> {
+    impl<
+            'a,
+            F: Fn(usize, usize) -> bool + Send + Sync,
+            P: Fn(&[usize]) -> bool + Send + Sync,
+        > CombFiltProducer<'a, F, P>
+    {
         /// Determine where to split the interval `[a, b]` of values for the
         /// first element of each *k*-combination, such that each partition
         /// will contain about the same number of *k*-combinations.
@@ -210,8 +274,11 @@ pub mod par {
         }
     }
 
-    impl<'a, F: Fn(usize, usize) -> bool + Send + Sync> Iterator
-        for CombFiltProducer<'a, F>
+    impl<
+            'a,
+            F: Fn(usize, usize) -> bool + Send + Sync,
+            P: Fn(&[usize]) -> bool + Send + Sync,
+        > Iterator for CombFiltProducer<'a, F, P>
     {
         type Item = Vec<usize>;
 
@@ -234,6 +301,13 @@ pub mod par {
                     continue;
                 }
                 self.items.push(self.current_ix);
+                if (self.prune)(&self.items) {
+                    // NOTE: this prunes all sub-branches of the depth-first
+                    // search that begin with this combination.
+                    self.items.pop();
+                    self.current_ix += 1;
+                    continue;
+                }
                 let item = Some(self.items.clone());
                 if self.items.len() < self.max_len {
                     // Prepare to descend, starting at the smallest value that
@@ -258,8 +332,12 @@ pub mod par {
         }
     }
 
-    impl<'a, F: Fn(usize, usize) -> bool + Send + Sync>
-        rayon::iter::plumbing::UnindexedProducer for CombFiltProducer<'a, F>
+    impl<
+            'a,
+            F: Fn(usize, usize) -> bool + Send + Sync,
+            P: Fn(&[usize]) -> bool + Send + Sync,
+        > rayon::iter::plumbing::UnindexedProducer
+        for CombFiltProducer<'a, F, P>
     {
         type Item = Vec<usize>;
 
@@ -271,6 +349,7 @@ pub mod par {
                     items: vec![],
                     current_ix: self.current_ix,
                     item_filter: self.item_filter,
+                    prune: self.prune,
                     ix0_max: split_at,
                 };
                 let high = Self {
@@ -279,6 +358,7 @@ pub mod par {
                     items: vec![],
                     current_ix: split_at,
                     item_filter: self.item_filter,
+                    prune: self.prune,
                     ix0_max: self.ix0_max,
                 };
                 (low, Some(high))
@@ -370,10 +450,31 @@ pub mod par {
             assert_eq!(expected_count, pcombs.len());
             assert_eq!(combs, pcombs)
         }
+
+        #[test]
+        /// Check that pruning the 25 *{1,2,3}*-combinations for a set of
+        /// size 5 returns the same results when using the serial and parallel
+        /// iterators.
+        fn test_par_comb_prune() {
+            init();
+            let prune = |items: &[usize]| items.starts_with(&[0, 1]);
+            let comb = CombinationsFilter::new(5, 3, |_x, _y| false)
+                .with_pruning(prune);
+            let combs: Vec<_> = comb.collect();
+            let pcombs: Vec<_> =
+                CombinationsFilter::new(5, 3, |_x, _y| false)
+                    .with_pruning(prune)
+                    .into_par_iter()
+                    .collect();
+            assert_eq!(combs.len(), 25 - 4);
+            assert_eq!(combs, pcombs)
+        }
     }
 }
 
-impl<F: Fn(usize, usize) -> bool> Iterator for CombinationsFilter<F> {
+impl<F: Fn(usize, usize) -> bool, P: Fn(&[usize]) -> bool> Iterator
+    for CombinationsFilter<F, P>
+{
     type Item = Vec<usize>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -393,6 +494,13 @@ impl<F: Fn(usize, usize) -> bool> Iterator for CombinationsFilter<F> {
                 continue;
             }
             self.items.push(self.current_ix);
+            if (self.prune)(&self.items) {
+                // NOTE: this prunes all sub-branches of the depth-first
+                // search that begin with this combination.
+                self.items.pop();
+                self.current_ix += 1;
+                continue;
+            }
             let item = Some(self.items.clone());
             if self.items.len() < self.max_len {
                 // Prepare to descend, starting at the smallest value that
@@ -523,6 +631,27 @@ mod tests {
         }
     }
 
+    #[test]
+    /// Check that pruning a combination also prunes every larger combination
+    /// that begins with it.
+    ///
+    /// Of the 25 *{1,2,3}*-combinations, 5 should be pruned: ``[0 2]``,
+    /// ``[0 2 3]``, ``[0 2 4]``, ``[3]``, and ``[3 4]``.
+    /// Combinations that contain these values but do not begin with them,
+    /// such as ``[2 3]`` and ``[0 3 4]``, should not be pruned.
+    fn test_combinations_prune_1() {
+        init();
+        let prune = |items: &[usize]| items == [0, 2] || items == [3];
+        let comb =
+            CombinationsFilter::new(5, 3, |_i, _j| false).with_pruning(prune);
+        let combs: Vec<_> = comb.collect();
+        assert_eq!(combs.len(), 25 - 5);
+        assert!(!combs.contains(&vec![0, 2, 4]));
+        assert!(!combs.contains(&vec![3, 4]));
+        assert!(combs.contains(&vec![2, 3]));
+        assert!(combs.contains(&vec![0, 3, 4]));
+    }
+
     #[test]
     /// Check that for a set of size 5 there are:
     ///
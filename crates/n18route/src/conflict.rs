@@ -1,9 +1,13 @@
 //! Paths and routes may not share certain features.
 
+use std::collections::BTreeMap;
+
 use n18hex::HexFace;
-use n18map::HexAddress;
+use n18map::{HexAddress, Map};
 use n18tile::Connection;
 
+use crate::Train;
+
 /// A rule defines which elements of a path or route may not be shared.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ConflictRule {
@@ -17,6 +21,89 @@ pub enum ConflictRule {
     Hex,
 }
 
+/// The rules that define which elements of a train's route may not be
+/// shared with other routes, for games where these rules depend on the
+/// train.
+///
+/// When two routes are combined, each route must satisfy the rule for its
+/// own train, and so the routes may only share elements that are allowed by
+/// **both** rules.
+///
+/// ```rust
+/// # use n18route::{ConflictRule, RouteConflictRules, Train};
+/// let express = Train::new().with_unlimited_stops();
+/// let rules = RouteConflictRules::new(ConflictRule::TrackOnly)
+///     .with_train_rule(express, ConflictRule::TrackOrCity);
+/// assert_eq!(rules.rule_for(&Train::new()), ConflictRule::TrackOnly);
+/// assert_eq!(rules.rule_for(&express), ConflictRule::TrackOrCity);
+/// assert_eq!(rules.most_permissive(), ConflictRule::TrackOnly);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouteConflictRules {
+    default_rule: ConflictRule,
+    train_rules: BTreeMap<Train, ConflictRule>,
+}
+
+impl From<ConflictRule> for RouteConflictRules {
+    fn from(rule: ConflictRule) -> Self {
+        RouteConflictRules::new(rule)
+    }
+}
+
+impl RouteConflictRules {
+    /// Returns the rules where every train uses the same rule.
+    pub fn new(default_rule: ConflictRule) -> Self {
+        RouteConflictRules {
+            default_rule,
+            train_rules: BTreeMap::new(),
+        }
+    }
+
+    /// Defines a different rule for routes operated by the given train.
+    pub fn with_train_rule(
+        mut self,
+        train: Train,
+        rule: ConflictRule,
+    ) -> Self {
+        self.train_rules.insert(train, rule);
+        self
+    }
+
+    /// Returns the rule for routes operated by the given train.
+    pub fn rule_for(&self, train: &Train) -> ConflictRule {
+        self.train_rules
+            .get(train)
+            .copied()
+            .unwrap_or(self.default_rule)
+    }
+
+    /// Returns the rule that allows the most elements to be shared.
+    ///
+    /// This should be used as the
+    /// [route conflict rule](crate::Criteria::route_conflict_rule) when
+    /// searching for paths, so that no valid combination of routes is
+    /// excluded.
+    pub fn most_permissive(&self) -> ConflictRule {
+        self.train_rules
+            .values()
+            .copied()
+            .fold(self.default_rule, |a, b| a.min(b))
+    }
+
+    /// Returns `true` if every train uses the same rule.
+    pub fn is_uniform(&self) -> bool {
+        self.train_rules
+            .values()
+            .all(|rule| *rule == self.default_rule)
+    }
+
+    /// Returns the rule that applies when combining routes operated by two
+    /// trains, which is the more restrictive of the two trains' rules.
+    pub fn rule_between(&self, a: &Train, b: &Train) -> ConflictRule {
+        self.rule_for(a).max(self.rule_for(b))
+    }
+}
+
 /// A specific element of a path or route that cannot be shared.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Conflict {
@@ -266,6 +353,28 @@ impl ConflictRule {
             },
         }
     }
+
+    /// Returns the conflict that this connection adds to a path or route,
+    /// where the cities and dits of a location that spans several map hexes
+    /// (see [Map::location]) are treated as a single city.
+    ///
+    /// This ensures that trains can stop at such locations at most once.
+    pub fn maybe_conflict_on(
+        &self,
+        map: &Map,
+        addr: &HexAddress,
+        conn: &Connection,
+    ) -> Option<Conflict> {
+        match conn {
+            Connection::City { .. } | Connection::Dit { .. }
+                if map.location(*addr).is_some() =>
+            {
+                let anchor = map.location_anchor(*addr);
+                self.maybe_conflict(&anchor, &Connection::City { ix: 0 })
+            }
+            _ => self.maybe_conflict(addr, conn),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -53,9 +53,13 @@ Optimal pairings of trains to routes is implemented by
 [`Trains::select_routes`](crate::train::Trains::select_routes), and supports
 different types of [route bonuses](crate::Bonus):
 
-- Bonuses for [visiting a specific location](crate::Bonus::VisitBonus); and
+- Bonuses for [visiting a specific location](crate::Bonus::VisitBonus);
 - Bonuses for [connecting one location to another
-  location](crate::Bonus::ConnectionBonus).
+  location](crate::Bonus::ConnectionBonus);
+- Bonuses for [routes that start and end at specific
+  locations](crate::Bonus::TerminusBonus), such as east-west bonuses; and
+- Bonuses for [visiting any location that has a specific
+  marker](crate::Bonus::MarkerBonus), such as a port or mine marker.
 
 Once we have collected all of the possible paths for a company, we need to
 find the allocation of trains to routes that yields the greatest revenue.
@@ -100,6 +104,21 @@ There are a number of complications to consider:
    be a substantial overlap in these routes, such as for a `2` train and a `3`
    train).
 
+To avoid evaluating every pairing of trains to every combination of paths,
+the search also calculates optimistic upper bounds on the revenue that each
+combination of paths can earn:
+
+- The highest revenue that any of the company's trains can earn on each path;
+  and
+- The highest revenue that each train can earn on any path, so that a
+  combination of `K` paths can earn no more than the `K` trains with the
+  highest revenues.
+
+A combination of paths is skipped if its upper bound is smaller than the
+revenue of the best combination that has been found so far.
+This does not change the selected routes, and pruning can be disabled with
+[`Trains::with_pruning`](crate::train::Trains::with_pruning).
+
 # Train types
 
   There are many different train types in the 18xx family of board games; see
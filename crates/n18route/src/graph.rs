@@ -0,0 +1,656 @@
+//! Cache the connectivity between map hexes, so that path searches don't
+//! need to recalculate it at every step.
+//!
+//! A [MapGraph] records details that only depend on the tile type (such as
+//! whether a tile is an off-board location) once for each tile type, and
+//! records the connections between adjacent tile faces once for each map
+//! hex.
+//!
+//! When a tile is placed, rotated, upgraded, or removed, the graph must be
+//! updated by calling [MapGraph::update_hex]:
+//!
+//! ```rust
+//! # use n18hex::{HexFace, Orientation, RotateCW};
+//! # use n18map::{HexAddress, Map};
+//! # use n18route::graph::MapGraph;
+//! # use n18token::Tokens;
+//! # let tiles = n18catalogue::tile_catalogue();
+//! # let hexes: Vec<HexAddress> = vec![(0, 0).into(), (0, 1).into()];
+//! # let orient = Orientation::FlatTop;
+//! # let mut map = Map::new(tiles.into(), Tokens::new(vec![]), hexes, orient);
+//! let addr: HexAddress = (0, 0).into();
+//! let mut graph = MapGraph::new(&map);
+//! map.place_tile(addr, "8", RotateCW::One);
+//! graph.update_hex(&map, addr);
+//! assert_eq!(graph.map_face(addr, HexFace::Top), Some(HexFace::UpperRight));
+//! ```
+//!
+//! Alternatively, [MapGraph::refresh] finds and updates every map hex whose
+//! tile has changed, which is useful when the changes were not recorded.
+//! The graph is rebuilt if the tile definitions have changed (see
+//! [Map::catalogue_revision]).
+//!
+//! The recorded map hexes and track connections can be inspected with
+//! [MapGraph::nodes] and [MapGraph::edges] (e.g., to draw the graph on top of
+//! the map when diagnosing why an expected route was not found).
+
+use std::collections::BTreeMap;
+
+use n18hex::{HexColour, HexFace, RotateCW};
+use n18map::{HexAddress, Map, Neighbour};
+use n18tile::Tile;
+
+/// The hex faces, in the order that they are stored in each [HexNode].
+const FACES: [HexFace; 6] = [
+    HexFace::Top,
+    HexFace::UpperRight,
+    HexFace::LowerRight,
+    HexFace::Bottom,
+    HexFace::LowerLeft,
+    HexFace::UpperLeft,
+];
+
+/// Returns the index of a hex face in [FACES].
+fn face_ix(face: HexFace) -> usize {
+    face as usize
+}
+
+/// The details of a tile type that are relevant to path searches, which do
+/// not depend on where (or how) the tile is placed on the map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct TileNode {
+    /// Whether trains must stop at this tile (i.e., an off-board location).
+    off_board: bool,
+    /// The number of token spaces in each city.
+    city_spaces: Vec<usize>,
+    /// The number of dits.
+    dits: usize,
+}
+
+impl TileNode {
+    fn new(tile: &Tile) -> Self {
+        let off_board =
+            tile.colour == HexColour::Red || tile.colour == HexColour::Blue;
+        let city_spaces = (0..tile.cities().len())
+            .map(|city_ix| tile.city_token_spaces(city_ix).len())
+            .collect();
+        TileNode {
+            off_board,
+            city_spaces,
+            dits: tile.dits().len(),
+        }
+    }
+}
+
+/// The connections between a map hex and its neighbours.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct HexNode {
+    /// The name of the tile placed on this hex.
+    tile: String,
+    /// The rotation of the tile placed on this hex.
+    rotation: RotateCW,
+    /// The map face that corresponds to each tile face.
+    map_faces: [HexFace; 6],
+    /// The adjacent map hex and tile face (if any) for each tile face.
+    adjacent: [Option<Neighbour>; 6],
+}
+
+/// A map hex that contains a tile, as recorded by a [MapGraph].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GraphNode {
+    /// The address of the map hex.
+    pub addr: HexAddress,
+    /// The number of cities on the tile.
+    pub cities: usize,
+    /// The number of dits on the tile.
+    pub dits: usize,
+    /// Whether the tile is an off-board location.
+    pub off_board: bool,
+}
+
+/// A track connection between two adjacent map hexes, as recorded by a
+/// [MapGraph].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GraphEdge {
+    /// The address of the first map hex.
+    pub addr: HexAddress,
+    /// The face of the first map hex (in terms of map orientation, not tile
+    /// orientation) that is connected to the second map hex.
+    pub map_face: HexFace,
+    /// The address of the second map hex.
+    pub adj_addr: HexAddress,
+}
+
+/// The cached connectivity of a map.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MapGraph {
+    tiles: BTreeMap<String, TileNode>,
+    hexes: BTreeMap<HexAddress, HexNode>,
+    /// The [catalogue revision](Map::catalogue_revision) of the recorded
+    /// tile definitions.
+    catalogue: Option<u64>,
+}
+
+impl MapGraph {
+    /// Records the connectivity of every tile on the map.
+    pub fn new(map: &Map) -> Self {
+        let mut graph = MapGraph {
+            catalogue: Some(map.catalogue_revision()),
+            ..Default::default()
+        };
+        for addr in map.hex_address_iter() {
+            graph.update_node(map, *addr);
+        }
+        graph
+    }
+
+    /// Updates the connectivity of a map hex and its neighbours, and should
+    /// be called whenever the tile on this hex is placed, rotated, upgraded,
+    /// or removed.
+    ///
+    /// This only records the connectivity of this map hex, and then patches
+    /// the single connection that each neighbouring hex has with this hex,
+    /// rather than recording the connectivity of each neighbour from
+    /// scratch.
+    /// The resulting graph is identical to the graph returned by
+    /// [MapGraph::new], except that details of each tile type are only
+    /// recorded once, and are retained when a tile is removed from the map.
+    ///
+    /// If the tile definitions have changed since they were recorded (see
+    /// [Map::catalogue_revision]), the entire graph is rebuilt.
+    pub fn update_hex(&mut self, map: &Map, addr: HexAddress) {
+        if !self.is_current_catalogue(map) {
+            *self = MapGraph::new(map);
+            return;
+        }
+        self.update_node(map, addr);
+        let orientation = map.orientation();
+        for map_face in FACES {
+            let adj_addr = addr.adjacent(map_face, orientation);
+            self.update_edge(adj_addr, map_face.opposite(), addr);
+        }
+    }
+
+    /// Updates the connectivity of each map hex in `addrs` and their
+    /// neighbours, such as after pasting or removing multiple tiles.
+    pub fn update_hexes<'a, I>(&mut self, map: &Map, addrs: I)
+    where
+        I: IntoIterator<Item = &'a HexAddress>,
+    {
+        for addr in addrs {
+            self.update_hex(map, *addr)
+        }
+    }
+
+    /// Updates the connectivity of each map hex whose tile has been placed,
+    /// rotated, upgraded, or removed since it was last recorded, and returns
+    /// the addresses of these hexes.
+    ///
+    /// If the tile definitions have changed since they were recorded (see
+    /// [Map::catalogue_revision]), the entire graph is rebuilt and this
+    /// returns the addresses of every hex that contains (or contained) a
+    /// tile.
+    pub fn refresh(&mut self, map: &Map) -> Vec<HexAddress> {
+        if !self.is_current_catalogue(map) {
+            let changed: Vec<HexAddress> = map
+                .hex_address_iter()
+                .filter(|addr| {
+                    map.tile_at(**addr).is_some()
+                        || self.hexes.contains_key(addr)
+                })
+                .copied()
+                .collect();
+            *self = MapGraph::new(map);
+            return changed;
+        }
+        let changed: Vec<HexAddress> = map
+            .hex_address_iter()
+            .filter(|addr| {
+                let placed = map.hex_state(**addr).map(|hex_state| {
+                    (&map.tile_at(**addr).unwrap().name, hex_state.rotation())
+                });
+                let recorded = self
+                    .hexes
+                    .get(addr)
+                    .map(|node| (&node.tile, &node.rotation));
+                placed != recorded
+            })
+            .copied()
+            .collect();
+        self.update_hexes(map, &changed);
+        changed
+    }
+
+    /// Returns the number of map hexes that contain a tile.
+    pub fn hex_count(&self) -> usize {
+        self.hexes.len()
+    }
+
+    /// Returns the number of distinct tile types on the map (including tile
+    /// types that have since been removed from the map).
+    pub fn tile_type_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Returns each map hex that contains a tile, in order.
+    pub fn nodes(&self) -> Vec<GraphNode> {
+        self.hexes
+            .iter()
+            .filter_map(|(addr, node)| {
+                self.tiles.get(&node.tile).map(|tile| GraphNode {
+                    addr: *addr,
+                    cities: tile.city_spaces.len(),
+                    dits: tile.dits,
+                    off_board: tile.off_board,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns each track connection between adjacent map hexes.
+    ///
+    /// Each connection is only returned once, from the map hex with the
+    /// smaller address.
+    pub fn edges(&self) -> Vec<GraphEdge> {
+        self.hexes
+            .iter()
+            .flat_map(|(addr, node)| {
+                node.adjacent
+                    .iter()
+                    .flatten()
+                    .filter(move |nbr| nbr.connected && *addr < nbr.addr)
+                    .map(move |nbr| GraphEdge {
+                        addr: *addr,
+                        map_face: nbr.map_face,
+                        adj_addr: nbr.addr,
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns the map face that corresponds to the specified face of the
+    /// tile placed on a map hex, or `None` if there is no tile on this hex.
+    pub fn map_face(
+        &self,
+        addr: HexAddress,
+        tile_face: HexFace,
+    ) -> Option<HexFace> {
+        self.hexes
+            .get(&addr)
+            .map(|node| node.map_faces[face_ix(tile_face)])
+    }
+
+    /// Returns the address of the adjacent tile, and the face **relative to
+    /// the adjacent tile's orientation**, that is connected to the specified
+    /// face of the tile placed on a map hex.
+    ///
+    /// If there is no adjacent tile, returns `None`.
+    /// This is equivalent to [Map::adjacent_face].
+    pub fn adjacent_face(
+        &self,
+        addr: HexAddress,
+        tile_face: HexFace,
+    ) -> Option<(HexAddress, HexFace)> {
+        self.hexes
+            .get(&addr)
+            .and_then(|node| node.adjacent[face_ix(tile_face)])
+            .map(|nbr| (nbr.addr, nbr.adj_face))
+    }
+
+    /// Returns the address of the adjacent tile, and the face **relative to
+    /// the adjacent tile's orientation**, that is connected by track to the
+    /// specified face of the tile placed on a map hex.
+    ///
+    /// If there is no adjacent tile, or if either tile has no track on these
+    /// faces, returns `None`.
+    pub fn connected_face(
+        &self,
+        addr: HexAddress,
+        tile_face: HexFace,
+    ) -> Option<(HexAddress, HexFace)> {
+        self.hexes
+            .get(&addr)
+            .and_then(|node| node.adjacent[face_ix(tile_face)])
+            .filter(|nbr| nbr.connected)
+            .map(|nbr| (nbr.addr, nbr.adj_face))
+    }
+
+    /// Returns `true` if the tile placed on a map hex is an off-board
+    /// location, past which trains cannot continue.
+    pub(crate) fn is_off_board(&self, addr: HexAddress) -> bool {
+        self.tile_node(addr)
+            .map(|tile| tile.off_board)
+            .unwrap_or(false)
+    }
+
+    /// Returns the number of token spaces in a city on the tile placed on a
+    /// map hex.
+    pub(crate) fn city_space_count(
+        &self,
+        addr: HexAddress,
+        city_ix: usize,
+    ) -> usize {
+        self.tile_node(addr)
+            .and_then(|tile| tile.city_spaces.get(city_ix).copied())
+            .unwrap_or(0)
+    }
+
+    fn tile_node(&self, addr: HexAddress) -> Option<&TileNode> {
+        self.hexes
+            .get(&addr)
+            .and_then(|node| self.tiles.get(&node.tile))
+    }
+
+    /// Updates the connection between the map face `adj_map_face` of the
+    /// tile placed on `adj_addr` and the map hex `addr`, whose connectivity
+    /// has already been recorded.
+    fn update_edge(
+        &mut self,
+        adj_addr: HexAddress,
+        adj_map_face: HexFace,
+        addr: HexAddress,
+    ) {
+        // NOTE: the connection is symmetric, so we can use the connection
+        // that was recorded for `addr`, which is `None` if there is no tile
+        // on this hex.
+        let edge = self.hexes.get(&addr).and_then(|node| {
+            node.adjacent
+                .iter()
+                .flatten()
+                .find(|nbr| nbr.addr == adj_addr)
+                .copied()
+        });
+        let adj_node = match self.hexes.get_mut(&adj_addr) {
+            Some(node) => node,
+            None => return,
+        };
+        let adj_tile_face = match FACES
+            .into_iter()
+            .find(|face| adj_node.map_faces[face_ix(*face)] == adj_map_face)
+        {
+            Some(face) => face,
+            None => return,
+        };
+        adj_node.adjacent[face_ix(adj_tile_face)] =
+            edge.map(|nbr| Neighbour {
+                tile_face: adj_tile_face,
+                map_face: adj_map_face,
+                addr,
+                adj_face: nbr.tile_face,
+                connected: nbr.connected,
+            });
+    }
+
+    /// Returns whether the recorded tile definitions are those of the map
+    /// catalogue.
+    fn is_current_catalogue(&self, map: &Map) -> bool {
+        self.catalogue == Some(map.catalogue_revision())
+    }
+
+    /// Records the connectivity of a single map hex.
+    fn update_node(&mut self, map: &Map, addr: HexAddress) {
+        let (tile, rotation) = match (map.tile_at(addr), map.hex_state(addr))
+        {
+            (Some(tile), Some(hex_state)) => (tile, *hex_state.rotation()),
+            _ => {
+                self.hexes.remove(&addr);
+                return;
+            }
+        };
+        if !self.tiles.contains_key(&tile.name) {
+            self.tiles.insert(tile.name.clone(), TileNode::new(tile));
+        }
+        let mut adjacent = [None; 6];
+        for nbr in map.neighbours(addr) {
+            adjacent[face_ix(nbr.tile_face)] = Some(nbr);
+        }
+        let node = HexNode {
+            tile: tile.name.clone(),
+            rotation,
+            map_faces: FACES.map(|face| face + rotation),
+            adjacent,
+        };
+        self.hexes.insert(addr, node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GraphEdge, MapGraph, FACES};
+    use n18hex::{Orientation, RotateCW};
+    use n18map::{HexAddress, Map};
+    use n18token::Tokens;
+
+    fn new_map() -> Map {
+        let tiles = n18catalogue::tile_catalogue();
+        let hexes: Vec<HexAddress> = (0..3)
+            .flat_map(|row| (0..3).map(move |col| (row, col).into()))
+            .collect();
+        let mut map = Map::new(
+            tiles.into(),
+            Tokens::new(vec![]),
+            hexes,
+            Orientation::FlatTop,
+        );
+        map.place_tile((0, 0).into(), "5", RotateCW::Zero);
+        map.place_tile((1, 1).into(), "8", RotateCW::Two);
+        map.place_tile((2, 2).into(), "5", RotateCW::One);
+        map
+    }
+
+    /// Checks that the cached connectivity matches the map.
+    fn check_graph(graph: &MapGraph, map: &Map) {
+        for addr in map.hex_address_iter() {
+            for face in FACES {
+                let expect = map
+                    .adjacent_face(*addr, face)
+                    .map(|(adj_addr, adj_face, _tile)| (adj_addr, adj_face));
+                assert_eq!(graph.adjacent_face(*addr, face), expect);
+                let connected = map
+                    .neighbours(*addr)
+                    .into_iter()
+                    .find(|nbr| nbr.tile_face == face && nbr.connected)
+                    .map(|nbr| (nbr.addr, nbr.adj_face));
+                assert_eq!(graph.connected_face(*addr, face), connected);
+                if let Some((adj_addr, adj_face)) = connected {
+                    let conn = map.connectivity_at(*addr).unwrap();
+                    let map_face = graph.map_face(*addr, face).unwrap();
+                    assert!(conn.has_track(map_face));
+                    let adj_conn = map.connectivity_at(adj_addr).unwrap();
+                    let adj_map_face = map_face.opposite();
+                    assert!(adj_conn.has_track(adj_map_face));
+                    assert_eq!(
+                        graph.map_face(adj_addr, adj_face),
+                        Some(adj_map_face)
+                    );
+                }
+                assert_eq!(
+                    graph.map_face(*addr, face),
+                    map.map_face_from_tile_face(*addr, face)
+                );
+            }
+        }
+    }
+
+    /// Checks that the graph is equivalent to a graph that is constructed
+    /// from scratch, ignoring tile types that are no longer on the map.
+    fn check_rebuild(graph: &MapGraph, map: &Map) {
+        let rebuilt = MapGraph::new(map);
+        assert_eq!(graph.hexes, rebuilt.hexes);
+        for (name, tile) in &rebuilt.tiles {
+            assert_eq!(graph.tiles.get(name), Some(tile));
+        }
+    }
+
+    #[test]
+    fn test_update_hex() {
+        let mut map = new_map();
+        let mut graph = MapGraph::new(&map);
+        assert_eq!(graph.hex_count(), 3);
+        assert_eq!(graph.tile_type_count(), 2);
+        check_graph(&graph, &map);
+
+        // Fill the gaps between the existing tiles.
+        for (addr, tile) in [((0, 1), "9"), ((1, 0), "57"), ((1, 2), "6")] {
+            let addr: HexAddress = addr.into();
+            map.place_tile(addr, tile, RotateCW::One);
+            graph.update_hex(&map, addr);
+            check_graph(&graph, &map);
+            check_rebuild(&graph, &map);
+        }
+        assert_eq!(graph.hex_count(), 6);
+        assert_eq!(graph.tile_type_count(), 5);
+
+        // Rotate a tile, and then remove it.
+        let addr: HexAddress = (1, 1).into();
+        map.hex_state_mut(addr).unwrap().rotate_cw();
+        graph.update_hex(&map, addr);
+        check_graph(&graph, &map);
+        check_rebuild(&graph, &map);
+        map.remove_tile(addr);
+        graph.update_hex(&map, addr);
+        check_graph(&graph, &map);
+        check_rebuild(&graph, &map);
+        assert_eq!(graph.hex_count(), 5);
+        assert_eq!(graph.tile_type_count(), 5);
+    }
+
+    #[test]
+    fn test_refresh() {
+        let mut map = new_map();
+        let mut graph = MapGraph::new(&map);
+        assert!(graph.refresh(&map).is_empty());
+
+        // Place, rotate, and remove tiles without updating the graph.
+        map.place_tile((0, 1).into(), "9", RotateCW::One);
+        map.place_tile((1, 0).into(), "57", RotateCW::Zero);
+        map.hex_state_mut((1, 1).into()).unwrap().rotate_cw();
+        map.remove_tile((2, 2).into());
+        let changed = graph.refresh(&map);
+        let expect: Vec<HexAddress> =
+            vec![(0, 1).into(), (1, 0).into(), (1, 1).into(), (2, 2).into()];
+        assert_eq!(changed, expect);
+        check_graph(&graph, &map);
+        check_rebuild(&graph, &map);
+        assert!(graph.refresh(&map).is_empty());
+    }
+
+    #[test]
+    fn test_refresh_tile_definitions() {
+        let mut map = new_map();
+        let mut graph = MapGraph::new(&map);
+        let addr: HexAddress = (1, 1).into();
+        let node = |graph: &MapGraph| {
+            graph.nodes().into_iter().find(|node| node.addr == addr)
+        };
+        assert_eq!(node(&graph).map(|node| node.cities), Some(0));
+
+        // Replace the definition of tile "8" with a tile that has a city.
+        let mut tile = map.tile("57").unwrap().clone();
+        tile.name = "8".to_string();
+        map.update_tiles(vec![tile]);
+        let changed = graph.refresh(&map);
+        let expect: Vec<HexAddress> =
+            vec![(0, 0).into(), (1, 1).into(), (2, 2).into()];
+        assert_eq!(changed, expect);
+        assert_eq!(node(&graph).map(|node| node.cities), Some(1));
+        check_graph(&graph, &map);
+        assert_eq!(graph, MapGraph::new(&map));
+        assert!(graph.refresh(&map).is_empty());
+    }
+
+    #[test]
+    fn test_nodes_and_edges() {
+        let map = new_map();
+        let graph = MapGraph::new(&map);
+        let nodes = graph.nodes();
+        assert_eq!(nodes.len(), 3);
+        let addrs: Vec<HexAddress> = nodes.iter().map(|n| n.addr).collect();
+        assert_eq!(addrs, [(0, 0).into(), (1, 1).into(), (2, 2).into()]);
+        // Tile 5 contains a single city, and tile 8 contains only track.
+        assert_eq!((nodes[0].cities, nodes[0].dits), (1, 0));
+        assert_eq!((nodes[1].cities, nodes[1].dits), (0, 0));
+        assert!(nodes.iter().all(|node| !node.off_board));
+
+        // Check that each edge is a connection between two map hexes, and
+        // that each connection is only returned once.
+        let mut map = map;
+        for (addr, tile) in [((0, 1), "9"), ((1, 0), "57"), ((1, 2), "6")] {
+            map.place_tile(addr.into(), tile, RotateCW::One);
+        }
+        let graph = MapGraph::new(&map);
+        let edges = graph.edges();
+        assert!(!edges.is_empty());
+        for edge in &edges {
+            assert!(edge.addr < edge.adj_addr);
+            let nbr = map
+                .neighbours(edge.addr)
+                .into_iter()
+                .find(|nbr| nbr.map_face == edge.map_face)
+                .unwrap();
+            assert!(nbr.connected);
+            assert_eq!(nbr.addr, edge.adj_addr);
+        }
+        let connections: usize = map
+            .hex_address_iter()
+            .map(|addr| {
+                map.neighbours(*addr)
+                    .into_iter()
+                    .filter(|nbr| nbr.connected)
+                    .count()
+            })
+            .sum();
+        assert_eq!(2 * edges.len(), connections);
+        let expect: Vec<GraphEdge> = vec![];
+        assert_eq!(MapGraph::default().edges(), expect);
+    }
+
+    /// Checks that incremental updates are equivalent to full rebuilds over
+    /// a long sequence of tile placements, rotations, upgrades, and removals
+    /// on a larger map, for both hex orientations.
+    #[test]
+    fn test_update_hex_matches_rebuild() {
+        let names = ["5", "6", "7", "8", "9", "57", "14", "15", "63"];
+        let hexes: Vec<HexAddress> = (0..8)
+            .flat_map(|row| (0..10).map(move |col| (row, col).into()))
+            .collect();
+        for orientation in [Orientation::FlatTop, Orientation::PointedTop] {
+            let tiles = n18catalogue::tile_catalogue();
+            let mut map = Map::new(
+                tiles.into(),
+                Tokens::new(vec![]),
+                hexes.clone(),
+                orientation,
+            );
+            let mut graph = MapGraph::new(&map);
+            // Visit the hexes in a fixed but irregular order, so that tiles
+            // are placed next to existing tiles and in isolation.
+            let mut ix = 0;
+            for step in 0..200 {
+                ix = (ix * 37 + 11) % hexes.len();
+                let addr = hexes[ix];
+                if step % 7 == 6 {
+                    map.remove_tile(addr);
+                } else if step % 5 == 4 && map.tile_at(addr).is_some() {
+                    map.hex_state_mut(addr).unwrap().rotate_anti_cw();
+                } else {
+                    let name = names[step % names.len()];
+                    let rotn = (0..step % 6)
+                        .fold(RotateCW::Zero, |rotn, _| rotn.rotate_cw());
+                    assert!(map.place_tile(addr, name, rotn));
+                }
+                graph.update_hex(&map, addr);
+                check_rebuild(&graph, &map);
+            }
+            check_graph(&graph, &map);
+
+            // Update several hexes at once.
+            let addrs = [hexes[0], hexes[1], hexes[11]];
+            for addr in &addrs {
+                map.place_tile(*addr, "8", RotateCW::Three);
+            }
+            graph.update_hexes(&map, &addrs);
+            check_rebuild(&graph, &map);
+        }
+    }
+}
@@ -11,6 +11,7 @@
 //!
 //! ```rust
 //! use n18route::{paths_for_token, Bonus, Criteria, ConflictRule, Trains, Routes};
+//! use n18route::RouteTokenRule;
 //! use n18map::Map;
 //! use n18token::Token;
 //!
@@ -24,6 +25,7 @@
 //!         conflict_rule: ConflictRule::TrackOrCityHex,
 //!         // NOTE: game-specific rule.
 //!         route_conflict_rule: ConflictRule::TrackOnly,
+//!         token_rule: RouteTokenRule::AnyToken,
 //!     };
 //!     let paths = paths_for_token(&map, &criteria);
 //!
@@ -50,21 +52,40 @@ pub mod comb;
 
 pub mod train;
 
+pub mod scoring;
+
 pub mod builder;
 
+pub mod graph;
+
+pub mod reach;
+
 pub mod doc;
 
 #[doc(inline)]
-pub use conflict::{Conflict, ConflictRule};
+pub use conflict::{Conflict, ConflictRule, RouteConflictRules};
 
 #[doc(inline)]
 pub use path::{Path, Step, StopLocation, Visit};
 
 #[doc(inline)]
-pub use search::{paths_for_token, Criteria, PathLimit, Query};
+pub use search::{
+    path_counts_with, paths_for_token, paths_for_token_cached,
+    paths_for_token_with, paths_from_city, paths_from_city_with, Criteria,
+    PathCache, PathCounts, PathLimit, Query, RouteTokenRule,
+};
+
+#[doc(inline)]
+pub use graph::{GraphEdge, GraphNode, MapGraph};
+
+#[doc(inline)]
+pub use reach::{reachable_from_tokens, Reach};
 
 #[doc(inline)]
 pub use train::{Route, Routes, Train, TrainRoute, TrainType, Trains};
 
 #[doc(inline)]
 pub use bonus::Bonus;
+
+#[doc(inline)]
+pub use scoring::{HighestStops, Scoring, StandardScoring};
@@ -7,11 +7,11 @@
 
 use std::collections::BTreeSet;
 
-use n18map::HexAddress;
+use n18map::{HexAddress, Map};
 use n18tile::Connection;
 
 use crate::conflict::RouteConflicts;
-use crate::Conflict;
+use crate::{Conflict, ConflictRule};
 
 /// A single step in a path.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -73,6 +73,35 @@ impl Path {
         self.visits.last().unwrap()
     }
 
+    /// Returns the elements of this path that may not be shared with other
+    /// routes, according to the provided rule.
+    ///
+    /// This allows paths to be combined according to a different rule than
+    /// the rule used to find these paths (see
+    /// [Criteria::route_conflict_rule](crate::Criteria::route_conflict_rule)).
+    pub fn route_conflicts_with(
+        &self,
+        map: &Map,
+        rule: ConflictRule,
+    ) -> RouteConflicts {
+        let conflicts: BTreeSet<Conflict> = self
+            .steps
+            .iter()
+            .filter_map(|step| {
+                // NOTE: hex face conflicts are defined according to the map
+                // orientation, not the tile orientation.
+                let conn = match step.conn {
+                    Connection::Face { face } => Connection::Face {
+                        face: map.map_face_from_tile_face(step.addr, face)?,
+                    },
+                    conn => conn,
+                };
+                rule.maybe_conflict_on(map, &step.addr, &conn)
+            })
+            .collect();
+        (&conflicts).into()
+    }
+
     /// Joins two paths, which must start from the same location.
     pub(crate) fn append(&self, other: &Path) -> Path {
         // NOTE: ensure that the first step of both paths is the same.
@@ -115,3 +144,31 @@ impl Path {
         }
     }
 }
+
+#[cfg(test)]
+impl Path {
+    /// Returns a path whose visits earn the provided revenues, for testing
+    /// how routes are scored.
+    pub(crate) fn with_revenues(revenues: &[usize]) -> Self {
+        let visits: Vec<Visit> = revenues
+            .iter()
+            .enumerate()
+            .map(|(ix, revenue)| Visit {
+                addr: (0, ix as isize).into(),
+                revenue: *revenue,
+                visits: StopLocation::City { ix: 0 },
+            })
+            .collect();
+        Path {
+            steps: vec![],
+            conflicts: BTreeSet::new(),
+            route_conflicts: RouteConflicts::new(),
+            num_visits: visits.len(),
+            num_cities: visits.len(),
+            num_dits: 0,
+            num_hexes: visits.len(),
+            revenue: revenues.iter().sum(),
+            visits,
+        }
+    }
+}
@@ -0,0 +1,294 @@
+//! Find the track that a company can reach from its placed tokens.
+//!
+//! Unlike the path searches in [crate::search], this does not enumerate
+//! individual paths or calculate revenue, and is not limited by any trains.
+//! It instead returns every track segment, city, and dit that is connected
+//! to the company's tokens, so that the company's network can be drawn.
+//!
+//! As for path searches, trains cannot continue past an off-board location,
+//! or through a city whose token spaces are all occupied by other companies.
+//!
+//! ```rust
+//! # use n18hex::{Orientation, RotateCW};
+//! # use n18map::{HexAddress, Map};
+//! # use n18route::{reachable_from_tokens, MapGraph};
+//! # use n18token::{Token, TokenStyle, Tokens};
+//! # use n18hex::Colour;
+//! # let style = TokenStyle::SideArcs {
+//! #     fg: Colour::from((176, 176, 176)),
+//! #     bg: Colour::from((66, 0, 0)),
+//! #     text: Colour::WHITE,
+//! # };
+//! # let tokens = Tokens::new(vec![("LP".to_string(), Token::new(style))]);
+//! # let tiles = n18catalogue::tile_catalogue();
+//! # let hexes: Vec<HexAddress> = vec![(0, 0).into(), (0, 1).into()];
+//! # let orient = Orientation::FlatTop;
+//! let mut map = Map::new(tiles.into(), tokens, hexes, orient);
+//! let city: HexAddress = (0, 0).into();
+//! map.place_tile(city, "6", RotateCW::Zero);
+//! let token = map.token("LP");
+//! let space = map.tile_at(city).unwrap().token_spaces()[0];
+//! map.hex_state_mut(city).unwrap().set_token_at(&space, token);
+//! let graph = MapGraph::new(&map);
+//! let reach = reachable_from_tokens(&map, &graph, token);
+//! assert!(reach.contains_hex(city));
+//! assert!(!reach.contains_hex((0, 1).into()));
+//! ```
+
+use std::collections::BTreeSet;
+
+use n18map::{HexAddress, Map};
+use n18tile::Connection;
+use n18token::Token;
+
+use super::graph::MapGraph;
+use super::Step;
+
+/// The map hexes and track that a company can reach from its tokens.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Reach {
+    /// The map hexes that contain the company's tokens.
+    token_hexes: BTreeSet<HexAddress>,
+    /// The map hexes that contain reachable track, cities, or dits.
+    hexes: BTreeSet<HexAddress>,
+    /// The reachable track segments, cities, dits, and hex faces.
+    steps: BTreeSet<Step>,
+}
+
+impl Reach {
+    /// Returns `true` if the company has not placed any tokens.
+    pub fn is_empty(&self) -> bool {
+        self.token_hexes.is_empty()
+    }
+
+    /// Returns the map hexes that contain the company's tokens.
+    pub fn token_hexes(&self) -> &BTreeSet<HexAddress> {
+        &self.token_hexes
+    }
+
+    /// Returns the map hexes that contain the company's tokens or any
+    /// reachable track, cities, or dits.
+    pub fn hexes(&self) -> &BTreeSet<HexAddress> {
+        &self.hexes
+    }
+
+    /// Returns `true` if a map hex contains the company's tokens or any
+    /// reachable track, cities, or dits.
+    pub fn contains_hex(&self, addr: HexAddress) -> bool {
+        self.hexes.contains(&addr)
+    }
+
+    /// Returns each reachable track segment, city, dit, and hex face, in
+    /// order.
+    ///
+    /// Track segments are returned once for each end from which they can be
+    /// reached.
+    pub fn steps(&self) -> impl Iterator<Item = &Step> {
+        self.steps.iter()
+    }
+
+    /// Returns the reachable cities, as `(addr, city_ix)` pairs.
+    pub fn cities(&self) -> impl Iterator<Item = (HexAddress, usize)> + '_ {
+        self.steps.iter().filter_map(|step| match step.conn {
+            Connection::City { ix } => Some((step.addr, ix)),
+            _ => None,
+        })
+    }
+}
+
+/// Returns the map hexes and track that a company can reach from the cities
+/// in which it has placed a token, using the cached connectivity in `graph`.
+pub fn reachable_from_tokens(
+    map: &Map,
+    graph: &MapGraph,
+    token: Token,
+) -> Reach {
+    let mut reach = Reach::default();
+    let mut queue: Vec<Step> = vec![];
+    for (addr, space) in map.find_placed_tokens(&token) {
+        reach.token_hexes.insert(*addr);
+        queue.push(Step {
+            addr: *addr,
+            conn: Connection::City {
+                ix: space.city_ix(),
+            },
+        });
+    }
+    while let Some(step) = queue.pop() {
+        if !reach.steps.insert(step) {
+            continue;
+        }
+        reach.hexes.insert(step.addr);
+        let addr = step.addr;
+        let Some(tile) = map.tile_at(addr) else {
+            continue;
+        };
+        if !can_continue(map, graph, token, step) {
+            continue;
+        }
+        // If this is a track connection, switch to the other end.
+        let conn = step.conn.other_end().unwrap_or(step.conn);
+        let Some(conns) = tile.connections(&conn) else {
+            continue;
+        };
+        for next_conn in conns {
+            let Connection::Face { face } = next_conn else {
+                queue.push(Step {
+                    addr,
+                    conn: *next_conn,
+                });
+                continue;
+            };
+            // Cross to the adjacent tile, if it has track on this face.
+            let Some((adj_addr, adj_face)) =
+                graph.connected_face(addr, *face)
+            else {
+                continue;
+            };
+            let Some(adj_tile) = map.tile_at(adj_addr) else {
+                continue;
+            };
+            reach.steps.insert(Step {
+                addr,
+                conn: *next_conn,
+            });
+            let adj_conn = Connection::Face { face: adj_face };
+            reach.steps.insert(Step {
+                addr: adj_addr,
+                conn: adj_conn,
+            });
+            reach.hexes.insert(adj_addr);
+            for adj_next in adj_tile.connections(&adj_conn).unwrap_or(&[]) {
+                queue.push(Step {
+                    addr: adj_addr,
+                    conn: *adj_next,
+                });
+            }
+        }
+    }
+    reach
+}
+
+/// Returns `true` if trains can continue past a step, which is only `false`
+/// for off-board locations and for cities whose token spaces are all
+/// occupied by other companies.
+fn can_continue(
+    map: &Map,
+    graph: &MapGraph,
+    token: Token,
+    step: Step,
+) -> bool {
+    let city_ix = match step.conn {
+        Connection::City { ix } => ix,
+        Connection::Dit { .. } => return !graph.is_off_board(step.addr),
+        _ => return true,
+    };
+    if graph.is_off_board(step.addr) {
+        return false;
+    }
+    let token_spaces = graph.city_space_count(step.addr, city_ix);
+    let city_tokens: Vec<&Token> = map
+        .hex_state(step.addr)
+        .map(|hex_state| {
+            hex_state
+                .tokens()
+                .iter()
+                .filter(|(space, _tok)| space.city_ix() == city_ix)
+                .map(|(_space, tok)| tok)
+                .collect()
+        })
+        .unwrap_or_default();
+    token_spaces == 0
+        || city_tokens.len() < token_spaces
+        || city_tokens.iter().any(|tok| **tok == token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::reachable_from_tokens;
+    use crate::graph::MapGraph;
+    use crate::search::tests::map_2x2_tiles_5_6_58_63;
+    use n18map::HexAddress;
+    use n18tile::Connection;
+    use n18token::{Token, TokenStyle, Tokens};
+
+    fn define_tokens() -> Tokens {
+        let token = |bg: (u8, u8, u8)| {
+            Token::new(TokenStyle::SideArcs {
+                fg: (63, 153, 153).into(),
+                bg: bg.into(),
+                text: (0, 0, 0).into(),
+            })
+        };
+        vec![
+            ("LP".to_string(), token((255, 127, 127))),
+            ("PO".to_string(), token((127, 255, 127))),
+            ("MK".to_string(), token((127, 127, 255))),
+        ]
+        .into()
+    }
+
+    /// Returns the number of reachable track segments on a map hex.
+    fn track_count(reach: &super::Reach, addr: HexAddress) -> usize {
+        let tracks: std::collections::BTreeSet<usize> = reach
+            .steps()
+            .filter(|step| step.addr == addr)
+            .filter_map(|step| match step.conn {
+                Connection::Track { ix, .. } => Some(ix),
+                _ => None,
+            })
+            .collect();
+        tracks.len()
+    }
+
+    #[test]
+    fn test_2x2_reach() {
+        let map = map_2x2_tiles_5_6_58_63(define_tokens());
+        let graph = MapGraph::new(&map);
+        let lp = map.token("LP");
+        let reach = reachable_from_tokens(&map, &graph, lp);
+        let token_hexes: Vec<HexAddress> =
+            reach.token_hexes().iter().copied().collect();
+        assert_eq!(token_hexes, [(0, 0).into(), (1, 1).into()]);
+
+        // Every tile on this map is connected to the LP tokens, and every
+        // city can be reached.
+        assert_eq!(reach.hexes().len(), 4);
+        assert_eq!(reach.cities().count(), 3);
+        assert!(reach.steps().all(|step| reach.contains_hex(step.addr)));
+
+        // A company that has not placed any tokens cannot reach anything.
+        let mk = map.token("MK");
+        let reach = reachable_from_tokens(&map, &graph, mk);
+        assert!(reach.is_empty());
+        assert!(reach.hexes().is_empty());
+        assert_eq!(reach.steps().count(), 0);
+    }
+
+    #[test]
+    fn test_2x2_blocked_reach() {
+        let mut map = map_2x2_tiles_5_6_58_63(define_tokens());
+        let lp = map.token("LP");
+        let mk = map.token("MK");
+        let addr_63: HexAddress = (1, 1).into();
+        let graph = MapGraph::new(&map);
+        let before = reachable_from_tokens(&map, &graph, lp);
+
+        // Replace the LP token on tile 63 with an MK token, so that LP can
+        // only start from tile 5, and all of the token spaces on tile 63
+        // are occupied by other companies.
+        let spaces = map.tile_at(addr_63).unwrap().token_spaces();
+        let hex_state = map.hex_state_mut(addr_63).unwrap();
+        for space in &spaces {
+            if hex_state.token_at(space) == Some(&lp) {
+                hex_state.set_token_at(space, mk);
+            }
+        }
+        let after = reachable_from_tokens(&map, &graph, lp);
+        assert_eq!(after.token_hexes().len(), 1);
+
+        // LP can reach the city on tile 63, but cannot continue past it.
+        assert!(after.cities().any(|(addr, _ix)| addr == addr_63));
+        assert!(track_count(&after, addr_63) < track_count(&before, addr_63));
+    }
+}
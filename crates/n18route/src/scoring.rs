@@ -0,0 +1,202 @@
+//! Rules for calculating the revenue that a train earns by operating a path.
+//!
+//! By default, each train earns revenue as per [Train::revenue_for]: the
+//! train must stop at the first and last visits along the path, and can only
+//! skip the intermediate visits that its [TrainType](crate::TrainType)
+//! allows it to skip.
+//!
+//! Games whose trains earn revenue in other ways can provide a different
+//! [Scoring] implementation to [Trains::select_routes_with].
+//! For example, [HighestStops] allows some trains to visit any number of
+//! locations, and earn revenue from only the `N` highest-value locations.
+//!
+//! ```rust
+//! # use n18route::{HighestStops, Scoring, TrainType};
+//! let express = TrainType::SkipAny.with_max_stops(5).with_multiplier(2);
+//! let scoring = HighestStops::new(vec![express]);
+//! assert!(scoring.is_highest_stops(&express));
+//! ```
+//!
+//! [Trains::select_routes_with]: crate::Trains::select_routes_with
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use n18map::HexAddress;
+
+use crate::train::{revenue_for_stops, visit_bonus, TrainStop};
+use crate::{Path, Train};
+
+/// Calculates the revenue that a train earns by operating a path.
+///
+/// Implementations must be [Send] and [Sync], so that they can be used when
+/// searching for routes in a separate thread.
+pub trait Scoring: Send + Sync {
+    /// Determine the revenue earned and stops made when the train operates
+    /// the given path, if it can operate this path.
+    ///
+    /// The revenue includes the visit bonuses and connection bonuses for the
+    /// stops that are made, and the train's revenue multiplier.
+    fn revenue_for(
+        &self,
+        train: &Train,
+        path: &Path,
+        visit_bonuses: &BTreeMap<HexAddress, usize>,
+        conn_bonuses: &BTreeMap<HexAddress, (Vec<HexAddress>, usize)>,
+    ) -> Option<(usize, Vec<TrainStop>)>;
+}
+
+/// The default scoring rules, as per [Train::revenue_for].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StandardScoring;
+
+impl Scoring for StandardScoring {
+    fn revenue_for(
+        &self,
+        train: &Train,
+        path: &Path,
+        visit_bonuses: &BTreeMap<HexAddress, usize>,
+        conn_bonuses: &BTreeMap<HexAddress, (Vec<HexAddress>, usize)>,
+    ) -> Option<(usize, Vec<TrainStop>)> {
+        train.revenue_for(path, visit_bonuses, conn_bonuses)
+    }
+}
+
+/// Scoring rules where some trains (such as "E" trains) can visit any number
+/// of locations, but only earn revenue from the highest-value locations
+/// along the path, up to the train's maximum number of stops.
+///
+/// Unlike the default rules, these trains do not have to earn revenue from
+/// the first and last visits along the path.
+/// All other trains use the default rules, as per [Train::revenue_for].
+///
+/// Note that these trains should be able to skip any location (i.e.,
+/// [TrainType::SkipAny](crate::TrainType::SkipAny)), so that the route
+/// search is not limited to paths with at most `N` visits.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HighestStops {
+    trains: BTreeSet<Train>,
+}
+
+impl HighestStops {
+    /// Returns the scoring rules where each of the provided trains earns
+    /// revenue from only its highest-value stops.
+    pub fn new<T: IntoIterator<Item = Train>>(trains: T) -> Self {
+        HighestStops {
+            trains: trains.into_iter().collect(),
+        }
+    }
+
+    /// Returns `true` if this train earns revenue from only its
+    /// highest-value stops.
+    pub fn is_highest_stops(&self, train: &Train) -> bool {
+        self.trains.contains(train)
+    }
+}
+
+impl Scoring for HighestStops {
+    fn revenue_for(
+        &self,
+        train: &Train,
+        path: &Path,
+        visit_bonuses: &BTreeMap<HexAddress, usize>,
+        conn_bonuses: &BTreeMap<HexAddress, (Vec<HexAddress>, usize)>,
+    ) -> Option<(usize, Vec<TrainStop>)> {
+        if self.is_highest_stops(train) {
+            highest_stops_revenue(train, path, visit_bonuses, conn_bonuses)
+        } else {
+            train.revenue_for(path, visit_bonuses, conn_bonuses)
+        }
+    }
+}
+
+/// Determine the revenue earned and stops made when the train operates the
+/// given path, and earns revenue from only the highest-value visits, up to
+/// the train's maximum number of stops.
+///
+/// The visits are ranked by their base revenue plus any visit bonus.
+/// Connection bonuses are only applied if both locations are among the
+/// selected stops.
+pub fn highest_stops_revenue(
+    train: &Train,
+    path: &Path,
+    visit_bonuses: &BTreeMap<HexAddress, usize>,
+    conn_bonuses: &BTreeMap<HexAddress, (Vec<HexAddress>, usize)>,
+) -> Option<(usize, Vec<TrainStop>)> {
+    let num_visits = path.visits.len();
+    let num_stops = train.max_stops.unwrap_or(num_visits).min(num_visits);
+    let mut ranked: Vec<(usize, usize)> = path
+        .visits
+        .iter()
+        .enumerate()
+        .map(|(ix, visit)| {
+            (ix, visit.revenue + visit_bonus(&visit.addr, visit_bonuses))
+        })
+        .collect();
+    // NOTE: prefer earlier visits when visits earn the same revenue, so that
+    // the selected stops are deterministic.
+    ranked.sort_by_key(|&(ix, revenue)| (std::cmp::Reverse(revenue), ix));
+    let mut stop_ixs: Vec<usize> =
+        ranked.iter().take(num_stops).map(|&(ix, _)| ix).collect();
+    stop_ixs.sort_unstable();
+    Some(revenue_for_stops(
+        path,
+        train,
+        &stop_ixs,
+        visit_bonuses,
+        conn_bonuses,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HighestStops, Scoring, StandardScoring};
+    use crate::{Path, TrainType};
+    use n18map::HexAddress;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_highest_stops() {
+        let path = Path::with_revenues(&[10, 40, 20, 50, 30]);
+        let express = TrainType::SkipAny.with_max_stops(2).with_multiplier(2);
+        let no_bonuses = BTreeMap::new();
+        let no_conns = BTreeMap::new();
+
+        // The default rules require stopping at both ends of the path.
+        let (revenue, stops) = StandardScoring
+            .revenue_for(&express, &path, &no_bonuses, &no_conns)
+            .unwrap();
+        assert_eq!(revenue, 2 * (10 + 30));
+        assert_eq!(stops.len(), 2);
+
+        // Express trains only count the two highest-value stops.
+        let scoring = HighestStops::new([express]);
+        let (revenue, stops) = scoring
+            .revenue_for(&express, &path, &no_bonuses, &no_conns)
+            .unwrap();
+        assert_eq!(revenue, 2 * (50 + 40));
+        let stop_ixs: Vec<usize> = stops.iter().map(|s| s.visit_ix).collect();
+        assert_eq!(stop_ixs, vec![1, 3]);
+
+        // Visit bonuses can change which stops earn the most revenue.
+        let addr: HexAddress = (0, 0).into();
+        let bonuses: BTreeMap<HexAddress, usize> = [(addr, 60)].into();
+        let (revenue, _stops) = scoring
+            .revenue_for(&express, &path, &bonuses, &no_conns)
+            .unwrap();
+        assert_eq!(revenue, 2 * (70 + 50));
+    }
+
+    #[test]
+    fn test_other_trains_use_standard_scoring() {
+        let path = Path::with_revenues(&[10, 40, 20]);
+        let express = TrainType::SkipAny.with_max_stops(2);
+        let train = TrainType::SkipAny.with_max_stops(2).with_multiplier(3);
+        let scoring = HighestStops::new([express]);
+        let no_bonuses = BTreeMap::new();
+        let no_conns = BTreeMap::new();
+        assert_eq!(
+            scoring.revenue_for(&train, &path, &no_bonuses, &no_conns),
+            train.revenue_for(&path, &no_bonuses, &no_conns)
+        );
+    }
+}
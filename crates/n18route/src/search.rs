@@ -2,13 +2,13 @@
 
 use log::info;
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use super::conflict::{Conflict, ConflictRule};
+use super::graph::MapGraph;
 use super::{Path, Step, StopLocation, Visit};
-use n18hex::HexColour;
 use n18map::{HexAddress, Map};
-use n18tile::{Connection, Tile, TokenSpace};
+use n18tile::{Connection, Dit, Tile};
 use n18token::Token;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -36,6 +36,69 @@ pub struct Criteria {
     pub conflict_rule: ConflictRule,
     /// The rule that governs what elements can be shared between routes.
     pub route_conflict_rule: ConflictRule,
+    /// The rule that defines which city (or cities) every path must include.
+    pub token_rule: RouteTokenRule,
+}
+
+/// Defines which city (or cities) every route must include.
+///
+/// Note that [paths_from_city] ignores this rule, because it searches for
+/// paths from a specific city.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RouteTokenRule {
+    /// Every route must include at least one city in which the company has
+    /// placed a token.
+    #[default]
+    AnyToken,
+    /// Every route must include the city `city_ix` on the tile at `addr`,
+    /// and the company must have placed a token in this city.
+    /// There are no valid routes if the company has no token in this city.
+    Token { addr: HexAddress, city_ix: usize },
+    /// Every route must include the company's home city, which is the city
+    /// `city_ix` on the tile at `addr`, whether or not the company has placed
+    /// a token in this city.
+    HomeCity { addr: HexAddress, city_ix: usize },
+}
+
+/// The map and search criteria, which remain fixed during path exploration.
+struct Search<'a> {
+    map: &'a Map,
+    graph: &'a MapGraph,
+    query: &'a Query,
+    /// Whether to ignore the tokens that match `query.criteria.token`, as
+    /// if the company had no tokens on the map.
+    open: bool,
+}
+
+impl Search<'_> {
+    /// Returns `true` if `token` belongs to the company whose paths are
+    /// being searched.
+    fn is_company_token(&self, token: &Token) -> bool {
+        !self.open && *token == self.query.criteria.token
+    }
+}
+
+/// Returns the revenue earned by visiting a city or dit on `tile`, which
+/// would otherwise earn `revenue`.
+///
+/// If the revenue phase has been set (see [Map::revenue_phase]) and the tile
+/// has a phase revenue label (e.g., an off-board tile), the revenue for this
+/// phase is used (see [Tile::phase_revenue]).
+fn visit_revenue(map: &Map, tile: &Tile, revenue: usize) -> usize {
+    map.revenue_phase()
+        .and_then(|phase| tile.phase_revenue(phase))
+        .unwrap_or(revenue)
+}
+
+/// Returns the revenue earned by visiting `dit` on `tile`.
+///
+/// If the revenue phase has been set (see [Map::revenue_phase]) and the dit
+/// has a revenue for this phase (see [Dit::revenue_in]), this revenue is
+/// used; otherwise the revenue is determined by [visit_revenue].
+fn visit_dit_revenue(map: &Map, tile: &Tile, dit: &Dit) -> usize {
+    map.revenue_phase()
+        .and_then(|phase| dit.phase_revenues.revenue(phase))
+        .unwrap_or_else(|| visit_revenue(map, tile, dit.revenue))
 }
 
 /// The current state of the path-exploration algorithm.
@@ -68,7 +131,7 @@ impl Context {
         if let Some(conflict) = query
             .criteria
             .conflict_rule
-            .maybe_conflict(&query.addr, &query.from)
+            .maybe_conflict_on(map, &query.addr, &query.from)
         {
             conflicts.insert(conflict);
         }
@@ -76,7 +139,7 @@ impl Context {
         if let Some(conflict) = query
             .criteria
             .route_conflict_rule
-            .maybe_conflict(&query.addr, &query.from)
+            .maybe_conflict_on(map, &query.addr, &query.from)
         {
             route_conflicts.insert(conflict);
         }
@@ -94,7 +157,7 @@ impl Context {
                 (
                     Visit {
                         addr: query.addr,
-                        revenue: city.revenue,
+                        revenue: visit_revenue(map, tile, city.revenue),
                         visits: StopLocation::City { ix: city_ix },
                     },
                     1,
@@ -106,7 +169,7 @@ impl Context {
                 (
                     Visit {
                         addr: query.addr,
-                        revenue: dit.revenue,
+                        revenue: visit_dit_revenue(map, tile, &dit),
                         visits: StopLocation::Dit { ix: dit_ix },
                     },
                     0,
@@ -160,26 +223,35 @@ impl Context {
 /// Returns all valid paths that match the provided criteria and which pass
 /// through any matching token on the map.
 pub fn paths_for_token(map: &Map, criteria: &Criteria) -> Vec<Path> {
-    let locations: Vec<(HexAddress, TokenSpace)> = map
-        .find_placed_tokens(&criteria.token)
-        .iter()
-        .map(|(addr, token_space)| (**addr, **token_space))
-        .collect();
+    let graph = MapGraph::new(map);
+    paths_for_token_with(map, &graph, criteria)
+}
+
+/// Returns all valid paths that match the provided criteria and which pass
+/// through any matching token on the map, using the cached connectivity in
+/// `graph`.
+///
+/// This avoids recalculating the map connectivity when searching for the
+/// paths of multiple companies, but `graph` must be updated whenever a tile
+/// is placed, rotated, upgraded, or removed (see [MapGraph::update_hex]).
+pub fn paths_for_token_with(
+    map: &Map,
+    graph: &MapGraph,
+    criteria: &Criteria,
+) -> Vec<Path> {
+    let queries = token_queries(map, criteria);
     // Allow the search from each token to proceed in parallel.
     use rayon::prelude::*;
-    info!("Searching for paths from {} locations", locations.len());
-    let paths = locations
+    info!("Searching for paths from {} locations", queries.len());
+    let paths = queries
         .par_iter()
-        .flat_map(|(addr, token_space)| {
-            let query = Query {
-                addr: *addr,
-                from: Connection::City {
-                    ix: token_space.city_ix(),
-                },
-                criteria: *criteria,
-            };
-            let paths = paths_through(map, &query);
-            info!("Found {} paths that pass through {}", paths.len(), addr);
+        .flat_map(|query| {
+            let paths = paths_through_with(map, graph, query);
+            info!(
+                "Found {} paths that pass through {}",
+                paths.len(),
+                query.addr
+            );
             paths
         })
         .collect::<Vec<Path>>();
@@ -187,10 +259,263 @@ pub fn paths_for_token(map: &Map, criteria: &Criteria) -> Vec<Path> {
     paths
 }
 
+/// Returns all valid paths that match the provided criteria and which pass
+/// through any matching token on the map, reusing the connectivity and the
+/// paths in `cache` from previous searches.
+///
+/// The cache is first updated to reflect any changes to the map (see
+/// [PathCache::sync]), and the paths found by any new searches are added to
+/// `cache`.
+pub fn paths_for_token_cached(
+    map: &Map,
+    cache: &mut PathCache,
+    criteria: &Criteria,
+) -> Vec<Path> {
+    cache.sync(map);
+    let queries = token_queries(map, criteria);
+    let new_queries: Vec<&Query> = queries
+        .iter()
+        .filter(|query| !cache.paths.contains_key(query))
+        .collect();
+    info!(
+        "Searching for paths from {} locations ({} cached)",
+        queries.len(),
+        queries.len() - new_queries.len()
+    );
+    use rayon::prelude::*;
+    let graph = &cache.graph;
+    let new_paths: Vec<(Query, Vec<Path>)> = new_queries
+        .par_iter()
+        .map(|query| (**query, paths_through_with(map, graph, query)))
+        .collect();
+    cache.paths.extend(new_paths);
+    let paths: Vec<Path> = queries
+        .iter()
+        .flat_map(|query| cache.paths[query].iter().cloned())
+        .collect();
+    info!("Found {} paths in total", paths.len());
+    paths
+}
+
+/// Returns the queries for each distinct location where a matching token has
+/// been placed, subject to the [RouteTokenRule] in `criteria`.
+///
+/// Note that a company may have multiple tokens in the same city (i.e., in
+/// different token spaces), but we only need to search from this city once.
+fn token_queries(map: &Map, criteria: &Criteria) -> BTreeSet<Query> {
+    let query_at = |addr: HexAddress, city_ix: usize| Query {
+        addr,
+        from: Connection::City { ix: city_ix },
+        criteria: *criteria,
+    };
+    let token_cities = map
+        .find_placed_tokens(&criteria.token)
+        .into_iter()
+        .map(|(addr, token_space)| (*addr, token_space.city_ix()));
+    match criteria.token_rule {
+        RouteTokenRule::AnyToken => token_cities
+            .map(|(addr, city_ix)| query_at(addr, city_ix))
+            .collect(),
+        RouteTokenRule::Token { addr, city_ix } => token_cities
+            .filter(|&city| city == (addr, city_ix))
+            .map(|(addr, city_ix)| query_at(addr, city_ix))
+            .collect(),
+        RouteTokenRule::HomeCity { addr, city_ix } => {
+            let has_city = map
+                .tile_at(addr)
+                .is_some_and(|tile| city_ix < tile.cities().len());
+            if has_city {
+                [query_at(addr, city_ix)].into_iter().collect()
+            } else {
+                BTreeSet::new()
+            }
+        }
+    }
+}
+
+/// Caches the connectivity of a map (see [MapGraph]) and the paths that
+/// match each [Query], so that identical searches (e.g., when finding routes
+/// for the same company several times) are only performed once.
+///
+/// The cache records the [revision](Map::revision) of the map that it
+/// describes, and [PathCache::sync] discards the cached paths whenever the
+/// map has been modified (e.g., when a tile or token is placed, rotated,
+/// upgraded, or removed, or when the tile definitions are updated).
+#[derive(Clone, Debug, Default)]
+pub struct PathCache {
+    graph: MapGraph,
+    revision: Option<u64>,
+    paths: BTreeMap<Query, Vec<Path>>,
+}
+
+impl PathCache {
+    /// Returns an empty path cache.
+    pub fn new() -> Self {
+        PathCache::default()
+    }
+
+    /// Updates the cached connectivity for each map hex whose tile has
+    /// changed, and discards the cached paths, if the map has been modified
+    /// since the cache was last updated.
+    pub fn sync(&mut self, map: &Map) {
+        if self.revision == Some(map.revision()) {
+            return;
+        }
+        if self.revision.is_none() {
+            self.graph = MapGraph::new(map);
+        } else {
+            self.graph.refresh(map);
+        }
+        self.paths.clear();
+        self.revision = Some(map.revision());
+    }
+
+    /// Returns the cached connectivity of the map, as of the most recent
+    /// call to [PathCache::sync].
+    pub fn graph(&self) -> &MapGraph {
+        &self.graph
+    }
+
+    /// Returns the number of queries whose paths are cached.
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Returns `true` if there are no cached paths.
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Returns `true` if the paths that match `query` are cached.
+    pub fn contains(&self, query: &Query) -> bool {
+        self.paths.contains_key(query)
+    }
+
+    /// Discards all of the cached paths, and the cached connectivity.
+    pub fn clear(&mut self) {
+        *self = PathCache::default()
+    }
+}
+
+/// The number of paths that pass through a single starting location, grouped
+/// by the number of locations that each path visits.
+///
+/// These counts can help to diagnose why an expected route was not found
+/// (e.g., if no paths from a city visit enough locations for a train).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathCounts {
+    /// The map hex that contains the starting location.
+    pub addr: HexAddress,
+    /// The starting location.
+    pub from: Connection,
+    /// The number of paths for each number of visited locations.
+    pub by_visits: BTreeMap<usize, usize>,
+}
+
+impl PathCounts {
+    /// Returns the total number of paths.
+    pub fn total(&self) -> usize {
+        self.by_visits.values().sum()
+    }
+
+    /// Returns the largest number of locations visited by any path, or
+    /// `None` if there are no paths.
+    pub fn longest(&self) -> Option<usize> {
+        self.by_visits.keys().next_back().copied()
+    }
+}
+
+/// Returns the number of paths that match the provided criteria and which
+/// pass through each matching token on the map, using the cached
+/// connectivity in `graph`.
+///
+/// See [PathCounts] for details.
+pub fn path_counts_with(
+    map: &Map,
+    graph: &MapGraph,
+    criteria: &Criteria,
+) -> Vec<PathCounts> {
+    token_queries(map, criteria)
+        .iter()
+        .map(|query| {
+            let mut by_visits = BTreeMap::new();
+            for path in paths_through_with(map, graph, query) {
+                *by_visits.entry(path.num_visits).or_insert(0) += 1;
+            }
+            PathCounts {
+                addr: query.addr,
+                from: query.from,
+                by_visits,
+            }
+        })
+        .collect()
+}
+
+/// Returns all valid paths that match the provided criteria and which pass
+/// through the city `city_ix` on the tile at `addr`, for a company that has
+/// no tokens on the map.
+///
+/// This answers questions such as "what routes could a train run from this
+/// city?", which can help to decide where to place a token.
+/// Because the company has no tokens on the map, `criteria.token` is
+/// ignored, and paths cannot pass through cities whose token spaces are all
+/// occupied.
+///
+/// Returns an empty vector if there is no such city.
+pub fn paths_from_city(
+    map: &Map,
+    criteria: &Criteria,
+    addr: HexAddress,
+    city_ix: usize,
+) -> Vec<Path> {
+    let graph = MapGraph::new(map);
+    paths_from_city_with(map, &graph, criteria, addr, city_ix)
+}
+
+/// Returns all valid paths that match the provided criteria and which pass
+/// through the city `city_ix` on the tile at `addr`, for a company that has
+/// no tokens on the map, using the cached connectivity in `graph`.
+///
+/// See [paths_from_city] for details.
+pub fn paths_from_city_with(
+    map: &Map,
+    graph: &MapGraph,
+    criteria: &Criteria,
+    addr: HexAddress,
+    city_ix: usize,
+) -> Vec<Path> {
+    let has_city = map
+        .tile_at(addr)
+        .is_some_and(|tile| city_ix < tile.cities().len());
+    if !has_city {
+        return vec![];
+    }
+    let query = Query {
+        addr,
+        from: Connection::City { ix: city_ix },
+        criteria: *criteria,
+    };
+    let mut paths = search_from(map, graph, &query, true);
+    let mut extra_paths = path_combinations(&query, &paths);
+    paths.append(&mut extra_paths);
+    paths
+}
+
 /// Returns all valid paths that match the provided criteria, passing through
 /// the specified token.
 pub fn paths_through(map: &Map, query: &Query) -> Vec<Path> {
-    let mut paths = paths_from(map, query);
+    let graph = MapGraph::new(map);
+    paths_through_with(map, &graph, query)
+}
+
+/// Returns all valid paths that match the provided criteria, passing through
+/// the specified token, using the cached connectivity in `graph`.
+pub fn paths_through_with(
+    map: &Map,
+    graph: &MapGraph,
+    query: &Query,
+) -> Vec<Path> {
+    let mut paths = paths_from_with(map, graph, query);
     let mut extra_paths = path_combinations(query, &paths);
     paths.append(&mut extra_paths);
     paths
@@ -199,6 +524,35 @@ pub fn paths_through(map: &Map, query: &Query) -> Vec<Path> {
 /// Returns all valid paths that match the provided criteria, starting from
 /// the specified token.
 pub fn paths_from(map: &Map, query: &Query) -> Vec<Path> {
+    let graph = MapGraph::new(map);
+    paths_from_with(map, &graph, query)
+}
+
+/// Returns all valid paths that match the provided criteria, starting from
+/// the specified token, using the cached connectivity in `graph`.
+pub fn paths_from_with(
+    map: &Map,
+    graph: &MapGraph,
+    query: &Query,
+) -> Vec<Path> {
+    search_from(map, graph, query, false)
+}
+
+/// Returns all valid paths that match the provided criteria, starting from
+/// the specified location, and optionally ignoring the company's tokens (see
+/// [paths_from_city]).
+fn search_from(
+    map: &Map,
+    graph: &MapGraph,
+    query: &Query,
+    open: bool,
+) -> Vec<Path> {
+    let search = Search {
+        map,
+        graph,
+        query,
+        open,
+    };
     let mut context = Context::new(map, query);
     let mut paths: Vec<Path> = vec![];
     let start_tile = map.tile_at(query.addr).unwrap();
@@ -214,8 +568,7 @@ pub fn paths_from(map: &Map, query: &Query) -> Vec<Path> {
     };
     for conn in connections.iter() {
         depth_first_search(
-            map,
-            query,
+            &search,
             &mut context,
             &mut paths,
             query.addr,
@@ -277,14 +630,14 @@ fn path_combinations(query: &Query, paths: &[Path]) -> Vec<Path> {
 }
 
 fn dfs_over(
-    map: &Map,
-    query: &Query,
+    search: &Search,
     ctx: &mut Context,
     paths: &mut Vec<Path>,
     addr: HexAddress,
     conns: Option<&[Connection]>,
     tile: &Tile,
 ) {
+    let query = search.query;
     if let Some(connections) = conns {
         for next_conn in connections.iter() {
             match next_conn {
@@ -294,7 +647,12 @@ fn dfs_over(
                     // NOTE: record this face and the adjacent face, so that
                     // routes that don't share any track segments but do
                     // share a hex face will be detected!!!
-                    let adj = map.adjacent_face(addr, *face);
+                    // NOTE: only consider adjacent faces that have track.
+                    let adj = search.graph.connected_face(addr, *face);
+                    let adj = adj.and_then(|(new_addr, new_face)| {
+                        let new_tile = search.map.tile_at(new_addr)?;
+                        Some((new_addr, new_face, new_tile))
+                    });
                     if let Some((new_addr, new_face, new_tile)) = adj {
                         let first_face = Step {
                             addr,
@@ -309,11 +667,13 @@ fn dfs_over(
                         // the map orientation, so that we always have an
                         // upper face and a lower face, and only need to
                         // record one of these.
-                        let map_face_1 = map
-                            .map_face_from_tile_face(addr, *face)
+                        let map_face_1 = search
+                            .graph
+                            .map_face(addr, *face)
                             .expect("No map face for current tile");
-                        let map_face_2 = map
-                            .map_face_from_tile_face(new_addr, new_face)
+                        let map_face_2 = search
+                            .graph
+                            .map_face(new_addr, new_face)
                             .expect("No map face for adjacent tile");
                         let map_conn_1 =
                             Connection::Face { face: map_face_1 };
@@ -371,8 +731,8 @@ fn dfs_over(
                             for new_conn in new_conns.iter() {
                                 // NOTE: we can skip any Face connection here!
                                 depth_first_search(
-                                    map, query, ctx, paths, new_addr,
-                                    *new_conn, new_tile,
+                                    search, ctx, paths, new_addr, *new_conn,
+                                    new_tile,
                                 );
                             }
                         }
@@ -398,7 +758,7 @@ fn dfs_over(
                 }
                 _ => {
                     depth_first_search(
-                        map, query, ctx, paths, addr, *next_conn, tile,
+                        search, ctx, paths, addr, *next_conn, tile,
                     );
                 }
             }
@@ -407,14 +767,14 @@ fn dfs_over(
 }
 
 fn depth_first_search(
-    map: &Map,
-    query: &Query,
+    search: &Search,
     ctx: &mut Context,
     paths: &mut Vec<Path>,
     addr: HexAddress,
     conn: Connection,
     tile: &Tile,
 ) {
+    let (map, query) = (search.map, search.query);
     if ctx
         .path
         .iter()
@@ -425,7 +785,12 @@ fn depth_first_search(
     }
 
     // Check if this connection conflicts with an earlier connection.
-    let conflict = query.criteria.conflict_rule.maybe_conflict(&addr, &conn);
+    // NOTE: a location that spans several hexes is treated as a single city,
+    // so that the train can only stop there once.
+    let conflict = query
+        .criteria
+        .conflict_rule
+        .maybe_conflict_on(map, &addr, &conn);
     if let Some(conflict) = conflict {
         if ctx.conflicts.contains(&conflict) {
             return;
@@ -436,7 +801,7 @@ fn depth_first_search(
     let route_conflict = query
         .criteria
         .route_conflict_rule
-        .maybe_conflict(&addr, &conn);
+        .maybe_conflict_on(map, &addr, &conn);
     if let Some(conflict) = route_conflict {
         ctx.route_conflicts.insert(conflict);
     }
@@ -449,7 +814,7 @@ fn depth_first_search(
     if let Connection::City { ix: city_ix } = conn {
         let token_tbl = map.hex_state(addr).unwrap().tokens();
         let has_token = token_tbl.iter().any(|(&space, &tok)| {
-            space.city_ix() == city_ix && tok == query.criteria.token
+            space.city_ix() == city_ix && search.is_company_token(&tok)
         });
         if has_token {
             let start_ix = if let Connection::City { ix } = query.from {
@@ -484,7 +849,7 @@ fn depth_first_search(
             let city = tile.cities()[city_ix];
             let visit = Visit {
                 addr,
-                revenue: city.revenue,
+                revenue: visit_revenue(map, tile, city.revenue),
                 visits: StopLocation::City { ix: city_ix },
             };
             ctx.num_visits += 1;
@@ -493,10 +858,10 @@ fn depth_first_search(
             paths.push(ctx.current_path());
             // NOTE: if we can continue travelling past this city, then do so.
             // NOTE: trains cannot continue past an off-board city/town.
-            let off_board = tile.colour == HexColour::Red
-                || tile.colour == HexColour::Blue;
+            let off_board = search.graph.is_off_board(addr);
             if !off_board {
-                let token_spaces = tile.city_token_spaces(city_ix);
+                let token_spaces =
+                    search.graph.city_space_count(addr, city_ix);
                 // NOTE: we must only check tokens associated with this city.
                 let city_tokens: Vec<_> = map
                     .hex_state(addr)
@@ -505,15 +870,15 @@ fn depth_first_search(
                     .iter()
                     .filter(|(&space, &_tok)| space.city_ix() == city_ix)
                     .collect();
-                let can_continue = token_spaces.is_empty()
-                    || (city_tokens.len() < token_spaces.len())
+                let can_continue = token_spaces == 0
+                    || (city_tokens.len() < token_spaces)
                     || city_tokens
                         .iter()
-                        .any(|(&_space, &tok)| tok == query.criteria.token);
+                        .any(|(&_space, &tok)| search.is_company_token(&tok));
                 let more_visits_allowed =
                     ctx.can_continue(&query.criteria.path_limit);
                 if can_continue && more_visits_allowed {
-                    dfs_over(map, query, ctx, paths, addr, conns, tile);
+                    dfs_over(search, ctx, paths, addr, conns, tile);
                 }
             }
             ctx.visits.pop();
@@ -525,7 +890,7 @@ fn depth_first_search(
             let dit = tile.dits()[dit_ix];
             let visit = Visit {
                 addr,
-                revenue: dit.revenue,
+                revenue: visit_dit_revenue(map, tile, &dit),
                 visits: StopLocation::Dit { ix: dit_ix },
             };
             ctx.num_visits += 1;
@@ -533,12 +898,11 @@ fn depth_first_search(
             ctx.visits.push(visit);
             paths.push(ctx.current_path());
             // NOTE: if we can continue travelling past this dit, then do so.
-            let off_board = tile.colour == HexColour::Red
-                || tile.colour == HexColour::Blue;
+            let off_board = search.graph.is_off_board(addr);
             let more_visits_allowed =
                 ctx.can_continue(&query.criteria.path_limit);
             if !off_board && more_visits_allowed {
-                dfs_over(map, query, ctx, paths, addr, conns, tile);
+                dfs_over(search, ctx, paths, addr, conns, tile);
             }
             ctx.visits.pop();
             ctx.num_dits -= 1;
@@ -546,7 +910,7 @@ fn depth_first_search(
         }
         _ => {
             // NOTE: no path to save, just visit subsequent connections.
-            dfs_over(map, query, ctx, paths, addr, conns, tile);
+            dfs_over(search, ctx, paths, addr, conns, tile);
         }
     }
 
@@ -561,9 +925,13 @@ fn depth_first_search(
 }
 
 #[cfg(test)]
-mod tests {
-    use super::{Criteria, PathLimit, Query};
+pub(crate) mod tests {
+    use super::{
+        path_counts_with, paths_for_token_cached, Criteria, PathCache,
+        PathLimit, Query, RouteTokenRule,
+    };
     use crate::conflict::ConflictRule;
+    use crate::graph::MapGraph;
     use n18hex::{Orientation, RotateCW};
     use n18map::{Descr, HexAddress, Map, TileDescr};
     use n18tile::Connection;
@@ -679,6 +1047,7 @@ mod tests {
                 path_limit: Some(PathLimit::CitiesAndTowns { count: 2 }),
                 conflict_rule: ConflictRule::TrackOrCityHex,
                 route_conflict_rule: ConflictRule::TrackOnly,
+                token_rule: RouteTokenRule::AnyToken,
             },
         };
         let from_len2 = super::paths_from(&map, &query);
@@ -696,6 +1065,7 @@ mod tests {
                 path_limit: Some(PathLimit::CitiesAndTowns { count: 3 }),
                 conflict_rule: ConflictRule::TrackOrCityHex,
                 route_conflict_rule: ConflictRule::TrackOnly,
+                token_rule: RouteTokenRule::AnyToken,
             },
         };
         let from_len3 = super::paths_from(&map, &query);
@@ -713,6 +1083,7 @@ mod tests {
                 path_limit: Some(PathLimit::CitiesAndTowns { count: 4 }),
                 conflict_rule: ConflictRule::TrackOrCityHex,
                 route_conflict_rule: ConflictRule::TrackOnly,
+                token_rule: RouteTokenRule::AnyToken,
             },
         };
         let from_len4 = super::paths_from(&map, &query);
@@ -730,6 +1101,7 @@ mod tests {
                 path_limit: None,
                 conflict_rule: ConflictRule::TrackOrCityHex,
                 route_conflict_rule: ConflictRule::TrackOnly,
+                token_rule: RouteTokenRule::AnyToken,
             },
         };
         let from_any = super::paths_from(&map, &query);
@@ -739,4 +1111,321 @@ mod tests {
         assert_eq!(rev_from_any, Some(90));
         assert_eq!(rev_via_any, Some(90));
     }
+
+    /// Test that paths from a city for a company with no tokens cannot pass
+    /// through cities whose token spaces are all occupied, and that there
+    /// are no paths from locations that are not cities.
+    #[test]
+    fn test_2x2_open_paths() {
+        let tokens = define_tokens();
+        let token_lp = *tokens.token("LP").unwrap();
+        let map = map_2x2_tiles_5_6_58_63(tokens);
+        let criteria = Criteria {
+            token: token_lp,
+            path_limit: None,
+            conflict_rule: ConflictRule::TrackOrCityHex,
+            route_conflict_rule: ConflictRule::TrackOnly,
+            token_rule: RouteTokenRule::AnyToken,
+        };
+        let addr = HexAddress::new(0, 0);
+
+        let open = super::paths_from_city(&map, &criteria, addr, 0);
+        let rev_open = open.iter().map(|path| path.revenue).max();
+        assert_eq!(rev_open, Some(90));
+        assert_eq!(open.len(), 5);
+        // Tiles 6 and 63 are full, so paths can only end at these tiles.
+        let full = [HexAddress::new(0, 1), HexAddress::new(1, 1)];
+        for path in &open {
+            let n = path.visits.len();
+            assert!(path.visits[1..n - 1]
+                .iter()
+                .all(|visit| !full.contains(&visit.addr)));
+        }
+        // The "LP" company can pass through tile 63, which contains an "LP"
+        // token, and so there are more paths than for a company that has no
+        // tokens.
+        let query = Query {
+            addr,
+            from: Connection::City { ix: 0 },
+            criteria,
+        };
+        assert!(super::paths_through(&map, &query).len() > open.len());
+
+        // Tile 58 contains dits, but no cities.
+        let dits = HexAddress::new(1, 0);
+        assert!(super::paths_from_city(&map, &criteria, dits, 0).is_empty());
+        // Tile 5 only contains a single city.
+        assert!(super::paths_from_city(&map, &criteria, addr, 1).is_empty());
+    }
+
+    /// Test that cached paths are identical to the paths found by a new
+    /// search, and that each query is only searched once.
+    #[test]
+    fn test_2x2_path_cache() {
+        let tokens = define_tokens();
+        let token_lp = *tokens.token("LP").unwrap();
+        let mut map = map_2x2_tiles_5_6_58_63(tokens);
+        let mut cache = PathCache::new();
+
+        let criteria = Criteria {
+            token: token_lp,
+            path_limit: Some(PathLimit::CitiesAndTowns { count: 3 }),
+            conflict_rule: ConflictRule::TrackOrCityHex,
+            route_conflict_rule: ConflictRule::TrackOnly,
+            token_rule: RouteTokenRule::AnyToken,
+        };
+        let expected = super::paths_for_token(&map, &criteria);
+        assert!(!expected.is_empty());
+        let paths = paths_for_token_cached(&map, &mut cache, &criteria);
+        assert_eq!(paths, expected);
+        // NOTE: there are two "LP" tokens on this map.
+        assert_eq!(cache.len(), 2);
+        let paths = paths_for_token_cached(&map, &mut cache, &criteria);
+        assert_eq!(paths, expected);
+        assert_eq!(cache.len(), 2);
+
+        // Searching with different criteria should add new cache entries.
+        let criteria = Criteria {
+            path_limit: Some(PathLimit::CitiesAndTowns { count: 2 }),
+            ..criteria
+        };
+        let expected = super::paths_for_token(&map, &criteria);
+        let paths = paths_for_token_cached(&map, &mut cache, &criteria);
+        assert_eq!(paths, expected);
+        assert_eq!(cache.len(), 4);
+
+        // Modifying the map should discard the cached paths and update the
+        // cached connectivity.
+        let addr = HexAddress::new(1, 0);
+        map.hex_state_mut(addr).unwrap().rotate_cw();
+        let expected = super::paths_for_token(&map, &criteria);
+        let paths = paths_for_token_cached(&map, &mut cache, &criteria);
+        assert_eq!(paths, expected);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.graph(), &MapGraph::new(&map));
+        map.remove_tile(addr);
+        let expected = super::paths_for_token(&map, &criteria);
+        let paths = paths_for_token_cached(&map, &mut cache, &criteria);
+        assert_eq!(paths, expected);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.graph().hex_count(), 3);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    /// Test that the path counts for each token match the paths found by a
+    /// search from that token.
+    #[test]
+    fn test_2x2_path_counts() {
+        let tokens = define_tokens();
+        let token_lp = *tokens.token("LP").unwrap();
+        let map = map_2x2_tiles_5_6_58_63(tokens);
+        let graph = MapGraph::new(&map);
+        let criteria = Criteria {
+            token: token_lp,
+            path_limit: Some(PathLimit::CitiesAndTowns { count: 3 }),
+            conflict_rule: ConflictRule::TrackOrCityHex,
+            route_conflict_rule: ConflictRule::TrackOnly,
+            token_rule: RouteTokenRule::AnyToken,
+        };
+        let counts = path_counts_with(&map, &graph, &criteria);
+        // NOTE: there are two "LP" tokens on this map.
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[0].addr, HexAddress::new(0, 0));
+        for count in &counts {
+            let query = Query {
+                addr: count.addr,
+                from: count.from,
+                criteria,
+            };
+            let paths = super::paths_through(&map, &query);
+            assert_eq!(count.total(), paths.len());
+            let longest = paths.iter().map(|path| path.num_visits).max();
+            assert_eq!(count.longest(), longest);
+            assert!(count.by_visits.keys().all(|&n| n <= 3));
+        }
+        let total: usize = counts.iter().map(|count| count.total()).sum();
+        assert_eq!(total, super::paths_for_token(&map, &criteria).len());
+
+        // There are no paths for a company that has no tokens.
+        let criteria = Criteria {
+            token_rule: RouteTokenRule::Token {
+                addr: HexAddress::new(1, 0),
+                city_ix: 0,
+            },
+            ..criteria
+        };
+        assert!(path_counts_with(&map, &graph, &criteria).is_empty());
+    }
+
+    /// Test that paths can only stop at a location that spans several hexes
+    /// once, and earn revenue for only one of its hexes.
+    #[test]
+    fn test_2x2_location_paths() {
+        let tokens = define_tokens();
+        let token_lp = *tokens.token("LP").unwrap();
+        let mut map = map_2x2_tiles_5_6_58_63(tokens);
+        let criteria = Criteria {
+            token: token_lp,
+            path_limit: None,
+            conflict_rule: ConflictRule::TrackOrCityHex,
+            route_conflict_rule: ConflictRule::TrackOnly,
+            token_rule: RouteTokenRule::AnyToken,
+        };
+        let grouped = [HexAddress::new(0, 1), HexAddress::new(1, 0)];
+        let visits_both = |path: &crate::Path| {
+            grouped
+                .iter()
+                .all(|addr| path.visits.iter().any(|v| v.addr == *addr))
+        };
+
+        // Without grouping these hexes, some paths visit both of them.
+        let paths = super::paths_for_token(&map, &criteria);
+        assert!(paths.iter().any(visits_both));
+        let max_revenue = paths.iter().map(|path| path.revenue).max();
+        assert_eq!(max_revenue, Some(90));
+
+        // Once they are grouped, each path visits at most one of them.
+        assert!(map.set_location(grouped[0], "Area"));
+        assert!(map.set_location(grouped[1], "Area"));
+        let paths = super::paths_for_token(&map, &criteria);
+        assert!(!paths.is_empty());
+        assert!(!paths.iter().any(visits_both));
+        let max_revenue = paths.iter().map(|path| path.revenue).max();
+        assert!(max_revenue < Some(90));
+    }
+
+    /// Test that the route token rule restricts paths to those that include
+    /// a specific city.
+    ///
+    /// "LP" tokens are placed on tiles 5 and 63, but not on tile 6.
+    #[test]
+    fn test_2x2_token_rules() {
+        let tokens = define_tokens();
+        let token_lp = *tokens.token("LP").unwrap();
+        let map = map_2x2_tiles_5_6_58_63(tokens);
+        let criteria = |token_rule| Criteria {
+            token: token_lp,
+            path_limit: None,
+            conflict_rule: ConflictRule::TrackOrCityHex,
+            route_conflict_rule: ConflictRule::TrackOnly,
+            token_rule,
+        };
+        let includes = |path: &crate::Path, addr: HexAddress| {
+            path.visits.iter().any(|visit| visit.addr == addr)
+        };
+        let (addr_5, addr_6) = (HexAddress::new(0, 0), HexAddress::new(0, 1));
+        let any_token = criteria(RouteTokenRule::AnyToken);
+        let all_paths = super::paths_for_token(&map, &any_token);
+        assert!(all_paths.iter().any(|path| !includes(path, addr_5)));
+        assert!(all_paths.iter().any(|path| !includes(path, addr_6)));
+
+        // Paths must include the token on tile 5.
+        let token_5 = criteria(RouteTokenRule::Token {
+            addr: addr_5,
+            city_ix: 0,
+        });
+        let paths = super::paths_for_token(&map, &token_5);
+        assert!(!paths.is_empty());
+        assert!(paths.len() < all_paths.len());
+        assert!(paths.iter().all(|path| includes(path, addr_5)));
+
+        // There are no paths if there is no token in this city.
+        let token_6 = criteria(RouteTokenRule::Token {
+            addr: addr_6,
+            city_ix: 0,
+        });
+        assert!(super::paths_for_token(&map, &token_6).is_empty());
+
+        // Paths must include the home city, even if it has no token.
+        let home_6 = criteria(RouteTokenRule::HomeCity {
+            addr: addr_6,
+            city_ix: 0,
+        });
+        let paths = super::paths_for_token(&map, &home_6);
+        assert!(!paths.is_empty());
+        assert!(paths.iter().all(|path| includes(path, addr_6)));
+
+        // There are no paths if there is no such city.
+        let home_none = criteria(RouteTokenRule::HomeCity {
+            addr: addr_6,
+            city_ix: 1,
+        });
+        assert!(super::paths_for_token(&map, &home_none).is_empty());
+    }
+
+    /// Test that dits with phase-dependent revenue earn the revenue for the
+    /// current revenue phase.
+    #[test]
+    fn test_dit_phase_revenue() {
+        use crate::StopLocation;
+        use n18hex::{Hex, HexColour, HexFace};
+        use n18tile::{DitShape, PhaseRevenues, Tile, Track, TrackEnd};
+
+        let hex = Hex::default();
+        let revenues = PhaseRevenues::new(&[(HexColour::Brown, 30)]);
+        let tile_58 = Tile::new(
+            HexColour::Yellow,
+            "58",
+            vec![
+                Track::gentle_r(HexFace::Bottom)
+                    .with_span(0.0, 0.5)
+                    .with_phase_dit(
+                        TrackEnd::End,
+                        10,
+                        DitShape::Bar,
+                        revenues,
+                    ),
+                Track::gentle_r(HexFace::Bottom).with_span(0.5, 1.0),
+            ],
+            vec![],
+            &hex,
+        );
+        let tiles: Vec<_> = n18catalogue::tile_catalogue()
+            .into_iter()
+            .map(|tile| {
+                if tile.name == "58" {
+                    tile_58.clone()
+                } else {
+                    tile
+                }
+            })
+            .collect();
+        let tokens = define_tokens();
+        let token_lp = *tokens.token("LP").unwrap();
+        let mut map = descr_2x2_tiles_5_6_58_63().build_map(tiles, tokens);
+
+        let query = Query {
+            addr: HexAddress::new(0, 0),
+            from: Connection::City { ix: 0 },
+            criteria: Criteria {
+                token: token_lp,
+                path_limit: Some(PathLimit::CitiesAndTowns { count: 3 }),
+                conflict_rule: ConflictRule::TrackOrCityHex,
+                route_conflict_rule: ConflictRule::TrackOnly,
+                token_rule: RouteTokenRule::AnyToken,
+            },
+        };
+        let dit_revenues = |map: &Map| -> Vec<usize> {
+            super::paths_from(map, &query)
+                .iter()
+                .flat_map(|path| &path.visits)
+                .filter(|visit| {
+                    matches!(visit.visits, StopLocation::Dit { ix: 0 })
+                })
+                .map(|visit| visit.revenue)
+                .collect()
+        };
+
+        let revenues = dit_revenues(&map);
+        assert!(!revenues.is_empty());
+        assert!(revenues.iter().all(|&revenue| revenue == 10));
+        map.set_revenue_phase(HexColour::Green);
+        assert!(dit_revenues(&map).iter().all(|&revenue| revenue == 10));
+        map.set_revenue_phase(HexColour::Brown);
+        assert!(dit_revenues(&map).iter().all(|&revenue| revenue == 30));
+        map.set_revenue_phase(HexColour::Grey);
+        assert!(dit_revenues(&map).iter().all(|&revenue| revenue == 30));
+    }
 }
@@ -47,6 +47,7 @@
 //!     path_limit: path_limit,
 //!     conflict_rule: ConflictRule::TrackOrCityHex,
 //!     route_conflict_rule: ConflictRule::TrackOnly,
+//!     token_rule: RouteTokenRule::AnyToken,
 //! };
 //!
 //! // Find all paths for which at least one of the company's trains can run.
@@ -66,14 +67,18 @@
 
 use super::bonus::Bonus;
 use super::comb::CombinationsFilter;
+use super::conflict::{ConflictRule, RouteConflictRules, RouteConflicts};
 use super::perm::KPermutationsFilter;
+use super::scoring::{Scoring, StandardScoring};
 use super::search::PathLimit;
-use super::{Path, Step, Visit};
+use super::{Path, Step, StopLocation, Visit};
 use log::info;
-use n18map::HexAddress;
+use n18map::{HexAddress, Map};
+use n18tile::Connection;
 use rayon::prelude::*;
 use std::collections::{BTreeMap, BTreeSet};
 use std::iter::FromIterator;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 
 /// The types of trains that can operate routes to earn revenue.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -271,7 +276,7 @@ impl Train {
 }
 
 /// Calculate the revenue bonus for stopping at a location.
-fn visit_bonus(
+pub(crate) fn visit_bonus(
     addr: &HexAddress,
     visit_bonuses: &BTreeMap<HexAddress, usize>,
 ) -> usize {
@@ -313,6 +318,41 @@ fn connection_bonus(
         .unwrap_or(0)
 }
 
+/// The locations at either end of a route, and the bonus for connecting
+/// them (see [Bonus::TerminusBonus]).
+type TerminusBonus = (Vec<HexAddress>, Vec<HexAddress>, usize);
+
+/// Adds the bonus for each pair of locations that are connected by the first
+/// and last stops along a path to the revenue earned at the final stop; this
+/// includes the train's revenue multiplier, if any.
+fn add_terminus_bonuses(
+    train: &Train,
+    path: &Path,
+    terminus_bonuses: &[TerminusBonus],
+    (revenue, mut stops): (usize, Vec<TrainStop>),
+) -> (usize, Vec<TrainStop>) {
+    let final_ix = path.visits.len() - 1;
+    let first_stop = stops.first().map(|stop| stop.visit_ix);
+    let last_stop = stops.last().map(|stop| stop.visit_ix);
+    if first_stop != Some(0) || last_stop != Some(final_ix) {
+        return (revenue, stops);
+    }
+    let start = path.visits[0].addr;
+    let end = path.visits[final_ix].addr;
+    let bonus: usize = terminus_bonuses
+        .iter()
+        .filter(|(a, b, _bonus)| {
+            (a.contains(&start) && b.contains(&end))
+                || (a.contains(&end) && b.contains(&start))
+        })
+        .map(|(_a, _b, bonus)| bonus * train.revenue_multiplier)
+        .sum();
+    if let Some(stop) = stops.last_mut() {
+        stop.revenue += bonus;
+    }
+    (revenue + bonus, stops)
+}
+
 fn revenue_for_stop(
     path: &Path,
     stop_ixs: &[usize],
@@ -369,7 +409,7 @@ fn best_ix_and_base_revenue(
 
 /// Calculate the revenue, including bonuses, for stopping at a subset of
 /// visits along a path; this includes the train's revenue multiplier, if any.
-fn revenue_for_stops(
+pub(crate) fn revenue_for_stops(
     path: &Path,
     train: &Train,
     stop_ixs: &[usize],
@@ -572,7 +612,7 @@ fn best_stop_ixs(
 }
 
 /// Pairings of trains to routes.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Routes {
     /// The total revenue earned from these routes.
     pub net_revenue: usize,
@@ -590,7 +630,7 @@ impl Routes {
 ///
 /// Note that the train may not earn revenue from every location along the
 /// path.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct TrainRoute {
     /// The train.
     pub train: Train,
@@ -607,7 +647,7 @@ impl AsRef<Route> for TrainRoute {
 }
 
 /// A route operated by a train.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Route {
     /// The steps that form the entire route.
     pub steps: Vec<Step>,
@@ -624,6 +664,33 @@ impl AsRef<Route> for Route {
     }
 }
 
+impl Route {
+    /// Returns the part of this route that ends at the `visit_ix`th visit,
+    /// or `None` if there is no such visit.
+    ///
+    /// This can be used to step through a route one visit at a time.
+    pub fn up_to_visit(&self, visit_ix: usize) -> Option<Route> {
+        if visit_ix >= self.visits.len() {
+            return None;
+        }
+        let mut end_ix = 0;
+        for visit in &self.visits[..=visit_ix] {
+            let conn = match visit.visits {
+                StopLocation::City { ix } => Connection::City { ix },
+                StopLocation::Dit { ix } => Connection::Dit { ix },
+            };
+            let offset = self.steps[end_ix..].iter().position(|step| {
+                step.addr == visit.addr && step.conn == conn
+            })?;
+            end_ix += offset + 1;
+        }
+        Some(Route {
+            steps: self.steps[..end_ix].to_vec(),
+            visits: self.visits[..=visit_ix].to_vec(),
+        })
+    }
+}
+
 impl From<Path> for Route {
     fn from(path: Path) -> Route {
         Route {
@@ -648,6 +715,7 @@ pub struct Trains {
     trains: BTreeMap<Train, usize>,
     train_vec: Vec<Train>,
     train_classes: Vec<usize>,
+    prune: bool,
 }
 
 impl From<Vec<Train>> for Trains {
@@ -675,6 +743,7 @@ impl From<Vec<Train>> for Trains {
             trains,
             train_vec: src,
             train_classes,
+            prune: true,
         }
     }
 }
@@ -698,6 +767,16 @@ impl Trains {
         trains.into()
     }
 
+    /// Enables or disables pruning path combinations that cannot earn more
+    /// revenue than the best combination found so far (enabled by default).
+    ///
+    /// Pruning does not change the selected routes, so it only needs to be
+    /// disabled in order to measure its effect on the search time.
+    pub fn with_pruning(mut self, prune: bool) -> Self {
+        self.prune = prune;
+        self
+    }
+
     /// Returns `true` if there are no trains in this collection.
     pub fn is_empty(&self) -> bool {
         self.train_count() == 0
@@ -741,6 +820,73 @@ impl Trains {
         &self,
         path_tbl: Vec<Path>,
         bonuses: Vec<Bonus>,
+    ) -> Option<Routes> {
+        self.select_routes_with(path_tbl, bonuses, &StandardScoring)
+    }
+
+    /// Returns a pairing of trains to routes that earns the most revenue,
+    /// where the revenue earned by each train is calculated by `scoring`.
+    pub fn select_routes_with(
+        &self,
+        path_tbl: Vec<Path>,
+        bonuses: Vec<Bonus>,
+        scoring: &dyn Scoring,
+    ) -> Option<Routes> {
+        self.select_routes_checked(path_tbl, bonuses, scoring, None)
+    }
+
+    /// Returns a pairing of trains to routes that earns the most revenue,
+    /// where the revenue earned by each train is calculated by `scoring`,
+    /// and the elements that each train's route may share with other routes
+    /// are defined by `rules`.
+    ///
+    /// The paths must have been found using the most permissive of these
+    /// rules (see [RouteConflictRules::most_permissive]), so that no valid
+    /// combination of routes is excluded.
+    pub fn select_routes_with_rules(
+        &self,
+        map: &Map,
+        path_tbl: Vec<Path>,
+        bonuses: Vec<Bonus>,
+        scoring: &dyn Scoring,
+        rules: &RouteConflictRules,
+    ) -> Option<Routes> {
+        // Record the elements of each path that may not be shared with other
+        // routes, according to each train's rule.
+        let train_rules: BTreeSet<ConflictRule> = self
+            .trains
+            .keys()
+            .map(|train| rules.rule_for(train))
+            .collect();
+        let conflicts = path_tbl
+            .iter()
+            .map(|path| {
+                train_rules
+                    .iter()
+                    .map(|rule| {
+                        (*rule, path.route_conflicts_with(map, *rule))
+                    })
+                    .collect()
+            })
+            .collect();
+        let pairing_conflicts = PairingConflicts { rules, conflicts };
+        self.select_routes_checked(
+            path_tbl,
+            bonuses,
+            scoring,
+            Some(&pairing_conflicts),
+        )
+    }
+
+    /// Returns a pairing of trains to routes that earns the most revenue,
+    /// where each pairing must be allowed by `pairing_conflicts` (if
+    /// provided).
+    fn select_routes_checked(
+        &self,
+        path_tbl: Vec<Path>,
+        bonuses: Vec<Bonus>,
+        scoring: &dyn Scoring,
+        pairing_conflicts: Option<&PairingConflicts>,
     ) -> Option<Routes> {
         use std::cmp::Ordering;
 
@@ -765,6 +911,8 @@ impl Trains {
                                 }
                                 Bonus::VisitWithTrainBonus { .. } => None,
                                 Bonus::ConnectionBonus { .. } => None,
+                                Bonus::TerminusBonus { .. } => None,
+                                Bonus::MarkerBonus { .. } => None,
                             })
                             .collect();
                     // Add any train-specific bonuses that exceed a matching
@@ -789,6 +937,17 @@ impl Trains {
                 })
                 .collect();
 
+        // Collect the terminus bonuses.
+        let terminus_bonuses: Vec<TerminusBonus> = bonuses
+            .iter()
+            .filter_map(|b| match b {
+                Bonus::TerminusBonus { endpoints, bonus } => {
+                    Some((endpoints.0.clone(), endpoints.1.clone(), *bonus))
+                }
+                _ => None,
+            })
+            .collect();
+
         // Index connection bonuses by location.
         let connect_bonuses: BTreeMap<HexAddress, (Vec<HexAddress>, usize)> =
             bonuses
@@ -796,6 +955,8 @@ impl Trains {
                 .filter_map(|b| match b {
                     Bonus::VisitBonus { .. } => None,
                     Bonus::VisitWithTrainBonus { .. } => None,
+                    Bonus::TerminusBonus { .. } => None,
+                    Bonus::MarkerBonus { .. } => None,
                     Bonus::ConnectionBonus {
                         from,
                         to_any,
@@ -813,18 +974,45 @@ impl Trains {
                 self.trains
                     .keys()
                     .filter_map(|train| {
-                        train
+                        scoring
                             .revenue_for(
+                                train,
                                 &path_tbl[path_ix],
                                 visit_bonuses.get(train).unwrap(),
                                 &connect_bonuses,
                             )
+                            .map(|revenue| {
+                                add_terminus_bonuses(
+                                    train,
+                                    &path_tbl[path_ix],
+                                    &terminus_bonuses,
+                                    revenue,
+                                )
+                            })
                             .map(|revenue| (*train, revenue))
                     })
                     .collect()
             })
             .collect();
 
+        // Record the highest revenue found so far, and skip each path
+        // combination whose upper bound is smaller than this revenue.
+        // NOTE: operating any single path earns at least as much as the
+        // best single path, so this is a lower bound on the best revenue.
+        // NOTE: combinations whose upper bound equals this revenue must be
+        // checked, because they may earn the same revenue.
+        let bounds = RevenueBounds::new(&rev, &self.train_vec);
+        let incumbent = AtomicUsize::new(bounds.best_single_path());
+        let pruned = AtomicUsize::new(0);
+        let prune_branch = |path_ixs: &[usize]| {
+            let best = incumbent.load(AtomicOrdering::Relaxed);
+            let prune = self.prune && bounds.branch_bound(path_ixs) < best;
+            if prune {
+                pruned.fetch_add(1, AtomicOrdering::Relaxed);
+            }
+            prune
+        };
+
         info!("Searching for best path combination");
         let best_pairing: Option<(usize, Vec<_>)> =
             CombinationsFilter::new(num_paths, num_trains, |a, b| {
@@ -832,10 +1020,29 @@ impl Trains {
                     .route_conflicts
                     .is_disjoint(&path_tbl[b].route_conflicts)
             })
+            .with_pruning(prune_branch)
             .into_par_iter()
+            .filter(|path_ixs| {
+                if !self.prune {
+                    return true;
+                }
+                let best = incumbent.load(AtomicOrdering::Relaxed);
+                let keep = bounds.upper_bound(path_ixs) >= best;
+                if !keep {
+                    pruned.fetch_add(1, AtomicOrdering::Relaxed);
+                }
+                keep
+            })
             // NOTE: best_pairing_for iterates over a
             // KPermutationsFilter to match trains to paths.
-            .filter_map(|path_ixs| self.best_pairing_for(&rev, &path_ixs))
+            .filter_map(|path_ixs| {
+                let pairing =
+                    self.best_pairing_for(&rev, &path_ixs, pairing_conflicts);
+                if let Some((revenue, _)) = &pairing {
+                    incumbent.fetch_max(*revenue, AtomicOrdering::Relaxed);
+                }
+                pairing
+            })
             // NOTE: instead of simply returning the first pairing that earns
             // the most revenue, also count how many pairings earn this much.
             // .max_by_key(|&(revenue, _)| revenue);
@@ -861,6 +1068,10 @@ impl Trains {
                 },
             )
             .map(|best| {
+                info!(
+                    "Pruned {} path combinations and their branches",
+                    pruned.load(AtomicOrdering::Relaxed)
+                );
                 info!("Number of solutions: {}", best.2);
                 (best.0, best.1)
             });
@@ -922,6 +1133,7 @@ impl Trains {
         &self,
         revenue: &[BTreeMap<Train, (usize, Vec<TrainStop>)>],
         path_ixs: &[usize],
+        pairing_conflicts: Option<&PairingConflicts>,
     ) -> Option<(usize, Vec<TrainStops>)> {
         let num_paths = path_ixs.len();
         // NOTE: we only need to consider pairings that allocate a train to
@@ -948,6 +1160,18 @@ impl Trains {
                 if revenues.len() < train_ixs.len() {
                     // Some trains could not operate the corresponding path.
                     None
+                } else if pairing_conflicts.is_some_and(|pc| {
+                    let pairs: Vec<(Train, usize)> = train_ixs
+                        .iter()
+                        .enumerate()
+                        .map(|(path_ixs_ix, train_ix)| {
+                            (self.train_vec[*train_ix], path_ixs[path_ixs_ix])
+                        })
+                        .collect();
+                    !pc.allows(&pairs)
+                }) {
+                    // Some routes conflict according to their trains' rules.
+                    None
                 } else {
                     Some((
                         net_revenue,
@@ -971,3 +1195,224 @@ impl Trains {
             .max_by_key(|(rev, _)| *rev)
     }
 }
+
+/// Optimistic upper bounds on the revenue that can be earned by operating
+/// a combination of paths.
+struct RevenueBounds {
+    /// The highest revenue that any train can earn on each path.
+    path_max: Vec<usize>,
+    /// The highest revenue that can be earned by the `n` trains that earn
+    /// the most revenue on their best paths, for each `n` from zero to the
+    /// number of trains.
+    train_max: Vec<usize>,
+    /// The highest revenue that can be earned by the `m` paths with the
+    /// highest bounds, out of the paths from the `i`th path onward, for each
+    /// `m` from zero to the number of trains (indexed as `[i][m]`).
+    suffix_max: Vec<Vec<usize>>,
+}
+
+impl RevenueBounds {
+    /// Calculates the revenue bounds for each path and for each train, from
+    /// the revenue that each train can earn on each path.
+    fn new(
+        revenue: &[BTreeMap<Train, (usize, Vec<TrainStop>)>],
+        trains: &[Train],
+    ) -> Self {
+        let path_max: Vec<usize> = revenue
+            .iter()
+            .map(|tbl| tbl.values().map(|(r, _)| *r).max().unwrap_or(0))
+            .collect();
+        let mut best_for_train: Vec<usize> = trains
+            .iter()
+            .map(|train| {
+                revenue
+                    .iter()
+                    .filter_map(|tbl| tbl.get(train).map(|(r, _)| *r))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        best_for_train.sort_unstable_by(|a, b| b.cmp(a));
+        let train_max = cumulative_sums(&best_for_train);
+
+        // Calculate the bounds for each suffix of the path table, starting
+        // from the final (empty) suffix.
+        let mut suffix_max = vec![vec![0; trains.len() + 1]];
+        let mut top_paths: Vec<usize> = Vec::with_capacity(trains.len() + 1);
+        for max in path_max.iter().rev() {
+            let ix = top_paths.partition_point(|r| r >= max);
+            top_paths.insert(ix, *max);
+            top_paths.truncate(trains.len());
+            let mut sums = cumulative_sums(&top_paths);
+            sums.resize(trains.len() + 1, *sums.last().unwrap());
+            suffix_max.push(sums);
+        }
+        suffix_max.reverse();
+
+        RevenueBounds {
+            path_max,
+            train_max,
+            suffix_max,
+        }
+    }
+
+    /// Returns the highest revenue that can be earned by operating a single
+    /// path.
+    fn best_single_path(&self) -> usize {
+        self.path_max.iter().max().copied().unwrap_or(0)
+    }
+
+    /// Returns an upper bound on the revenue that can be earned by pairing
+    /// each path in a combination with a different train.
+    fn upper_bound(&self, path_ixs: &[usize]) -> usize {
+        let by_path: usize =
+            path_ixs.iter().map(|ix| self.path_max[*ix]).sum();
+        let by_train = self
+            .train_max
+            .get(path_ixs.len())
+            .or(self.train_max.last())
+            .copied()
+            .unwrap_or(0);
+        by_path.min(by_train)
+    }
+
+    /// Returns an upper bound on the revenue that can be earned by a
+    /// combination of paths, and by every larger combination that begins
+    /// with these paths.
+    fn branch_bound(&self, path_ixs: &[usize]) -> usize {
+        let num_trains = self.train_max.len() - 1;
+        let by_path: usize =
+            path_ixs.iter().map(|ix| self.path_max[*ix]).sum();
+        let extra = path_ixs
+            .last()
+            .and_then(|ix| self.suffix_max.get(ix + 1))
+            .and_then(|sums| {
+                sums.get(num_trains.saturating_sub(path_ixs.len()))
+            })
+            .copied()
+            .unwrap_or(0);
+        (by_path + extra).min(self.train_max[num_trains])
+    }
+}
+
+/// Returns the sum of the first `n` values, for each `n` from zero to the
+/// number of values.
+fn cumulative_sums(values: &[usize]) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(values.iter().scan(0, |sum, r| {
+            *sum += r;
+            Some(*sum)
+        }))
+        .collect()
+}
+
+/// The elements of each path that may not be shared with other routes,
+/// according to the rule for each train.
+struct PairingConflicts<'a> {
+    rules: &'a RouteConflictRules,
+    conflicts: Vec<BTreeMap<ConflictRule, RouteConflicts>>,
+}
+
+impl PairingConflicts<'_> {
+    /// Returns `true` if no pair of (train, path index) pairings share any
+    /// elements that are prohibited by the rules for these two trains.
+    fn allows(&self, pairs: &[(Train, usize)]) -> bool {
+        pairs.iter().enumerate().all(|(ix, (train_a, path_a))| {
+            pairs[ix + 1..].iter().all(|(train_b, path_b)| {
+                let rule = self.rules.rule_between(train_a, train_b);
+                self.conflicts[*path_a][&rule]
+                    .is_disjoint(&self.conflicts[*path_b][&rule])
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Route, Trains};
+    use crate::{Bonus, Path, Step, StopLocation, TrainType, Visit};
+    use n18hex::HexFace;
+    use n18map::HexAddress;
+    use n18tile::Connection;
+
+    /// Returns a route that visits a city, passes through a dit without
+    /// stopping, and then visits a second city.
+    fn city_dit_city_route() -> Route {
+        let a = HexAddress::new(0, 0);
+        let b = HexAddress::new(1, 0);
+        let c = HexAddress::new(2, 0);
+        let step = |addr, conn| Step { addr, conn };
+        let steps = vec![
+            step(a, Connection::City { ix: 0 }),
+            step(a, HexFace::Bottom.into()),
+            step(b, HexFace::Top.into()),
+            step(b, Connection::Dit { ix: 0 }),
+            step(b, HexFace::Bottom.into()),
+            step(c, HexFace::Top.into()),
+            step(c, Connection::City { ix: 0 }),
+        ];
+        let visits = vec![
+            Visit {
+                addr: a,
+                revenue: 20,
+                visits: StopLocation::City { ix: 0 },
+            },
+            Visit {
+                addr: b,
+                revenue: 0,
+                visits: StopLocation::Dit { ix: 0 },
+            },
+            Visit {
+                addr: c,
+                revenue: 30,
+                visits: StopLocation::City { ix: 0 },
+            },
+        ];
+        Route { steps, visits }
+    }
+
+    #[test]
+    fn route_up_to_each_visit() {
+        let route = city_dit_city_route();
+        let step_counts = [1, 4, 7];
+        for (visit_ix, num_steps) in step_counts.iter().enumerate() {
+            let part = route.up_to_visit(visit_ix).unwrap();
+            assert_eq!(part.steps, route.steps[..*num_steps]);
+            assert_eq!(part.visits, route.visits[..=visit_ix]);
+        }
+        assert_eq!(route.up_to_visit(3), None);
+    }
+
+    #[test]
+    fn terminus_bonus() {
+        let path = Path::with_revenues(&[10, 20, 30]);
+        let train = TrainType::MustStop.with_max_stops(3).with_multiplier(2);
+        let trains: Trains = vec![train].into();
+        let west: HexAddress = (0, 0).into();
+        let middle: HexAddress = (0, 1).into();
+        let east: HexAddress = (0, 2).into();
+        let bonus = |a: HexAddress, b: HexAddress| Bonus::TerminusBonus {
+            endpoints: (vec![a], vec![b]),
+            bonus: 50,
+        };
+
+        let routes = trains.select_routes(vec![path.clone()], vec![]);
+        assert_eq!(routes.unwrap().net_revenue, 2 * 60);
+
+        // The bonus applies in either direction, and is earned at the final
+        // stop of the route.
+        for endpoints in [(west, east), (east, west)] {
+            let bonuses = vec![bonus(endpoints.0, endpoints.1)];
+            let routes =
+                trains.select_routes(vec![path.clone()], bonuses).unwrap();
+            assert_eq!(routes.net_revenue, 2 * (60 + 50));
+            let visits = &routes.train_routes[0].route.visits;
+            assert_eq!(visits[2].revenue, 2 * (30 + 50));
+        }
+
+        // The bonus does not apply to intermediate stops.
+        let bonuses = vec![bonus(west, middle)];
+        let routes = trains.select_routes(vec![path], bonuses).unwrap();
+        assert_eq!(routes.net_revenue, 2 * 60);
+    }
+}
@@ -0,0 +1,80 @@
+#[cfg(feature = "cairo")]
+use crate::draw::Draw;
+#[cfg(feature = "cairo")]
+use cairo::Context;
+use n18hex::HexFace;
+#[cfg(feature = "cairo")]
+use n18hex::{Coord, Hex};
+
+/// An arrow that points out of a tile through one of its hex faces.
+///
+/// These are typically used by off-board and pre-printed tiles to show the
+/// map edges to which they connect, without requiring any track segments.
+/// Arrows do not affect track connectivity.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Arrow {
+    /// The hex face through which the arrow points.
+    pub face: HexFace,
+    /// The distance between the arrow tip and the hex face, as a fraction of
+    /// the distance from the hex face to the hex centre.
+    pub offset: f64,
+}
+
+impl Arrow {
+    /// Creates a new arrow that points through the hex face `face`, and
+    /// whose tip touches this face.
+    pub fn new(face: HexFace) -> Self {
+        Arrow { face, offset: 0.0 }
+    }
+
+    /// Moves the arrow towards the hex centre.
+    pub fn to_centre(mut self, frac: f64) -> Self {
+        self.offset = frac;
+        self
+    }
+
+    /// Returns the coordinates of the arrow tip and the two corners of the
+    /// arrow base.
+    #[cfg(feature = "cairo")]
+    fn coords(&self, hex: &Hex) -> (Coord, Coord, Coord) {
+        let length = hex.theme.face_arrow_length.absolute(hex);
+        let midpoint = hex.midpoint(&self.face);
+        let inwards = (&midpoint * -1.0).normalise();
+        let tip = midpoint.scale_by(1.0 - self.offset);
+        let base = &tip + &(&inwards * length);
+        let along_face = Coord::unit_normal(&tip, &base);
+        let base_1 = &base + &(&along_face * (0.5 * length));
+        let base_2 = &base - &(&along_face * (0.5 * length));
+        (tip, base_1, base_2)
+    }
+}
+
+impl From<HexFace> for Arrow {
+    fn from(face: HexFace) -> Self {
+        Arrow::new(face)
+    }
+}
+
+#[cfg(feature = "cairo")]
+impl Draw for Arrow {
+    fn define_boundary(&self, hex: &Hex, ctx: &Context) {
+        let (tip, base_1, base_2) = self.coords(hex);
+        ctx.new_path();
+        ctx.move_to(tip.x, tip.y);
+        ctx.line_to(base_1.x, base_1.y);
+        ctx.line_to(base_2.x, base_2.y);
+        ctx.close_path();
+    }
+
+    fn draw_bg(&self, hex: &Hex, ctx: &Context) {
+        self.define_boundary(hex, ctx);
+        hex.theme.face_arrow.apply_line_and_stroke(ctx, hex);
+        ctx.stroke().unwrap();
+    }
+
+    fn draw_fg(&self, hex: &Hex, ctx: &Context) {
+        self.define_boundary(hex, ctx);
+        hex.theme.face_arrow.apply_fill(ctx);
+        ctx.fill().unwrap();
+    }
+}
@@ -1,4 +1,7 @@
+#[cfg(feature = "cairo")]
 use crate::draw::Draw;
+use crate::geometry;
+#[cfg(feature = "cairo")]
 use cairo::Context;
 use n18hex::{
     Coord, Delta, Direction, Hex, HexColour, HexCorner, HexFace, HexPosition,
@@ -54,6 +57,10 @@ pub struct City {
     pub angle: Rotation,
     /// Mark unavailable token space(s) with a solid colour.
     pub fill_colour: Option<HexColour>,
+    /// The tile colour of the earliest game phase in which tokens can be
+    /// placed in this city's token spaces, or `None` if they are always
+    /// available.
+    pub available_from: Option<HexColour>,
 }
 
 impl City {
@@ -129,18 +136,62 @@ impl City {
         angle
     }
 
+    #[cfg(feature = "cairo")]
     pub fn translate_begin(&self, hex: &Hex, ctx: &Context) {
         let coord = self.translate_coords(hex);
         ctx.translate(coord.x, coord.y);
         ctx.rotate(self.rotate_angle(hex));
     }
 
+    #[cfg(feature = "cairo")]
     pub fn translate_end(&self, hex: &Hex, ctx: &Context) {
         let coord = self.translate_coords(hex);
         ctx.rotate(-self.rotate_angle(hex));
         ctx.translate(-coord.x, -coord.y);
     }
 
+    /// Returns `true` if `coord` lies inside the boundary of this city.
+    pub fn fill_contains(&self, hex: &Hex, coord: &Coord) -> bool {
+        // Transform the coordinate into the city's frame of reference.
+        let offset = coord - &self.translate_coords(hex);
+        let angle = -self.rotate_angle(hex);
+        let (sin, cos) = angle.sin_cos();
+        let pt = Coord {
+            x: offset.x * cos - offset.y * sin,
+            y: offset.x * sin + offset.y * cos,
+        };
+        let radius = hex.geometry().token_space_radius.absolute(hex);
+        // NOTE: each boundary contains every point that is within one radius
+        // of the centre(s) of the token spaces.
+        let distance = match self.tokens {
+            Tokens::Single => pt.magnitude(),
+            Tokens::Double => {
+                let left = Coord::from((-radius, 0.0));
+                let right = Coord::from((radius, 0.0));
+                geometry::distance_to_segment(&left, &right, &pt)
+            }
+            Tokens::Triple => {
+                let half_height = radius * (3.0_f64).sqrt() / 2.0;
+                let centres = [
+                    Coord::from((-radius, half_height)),
+                    Coord::from((radius, half_height)),
+                    Coord::from((0.0, -half_height)),
+                ];
+                geometry::distance_to_polygon(&centres, &pt)
+            }
+            Tokens::Quadruple => {
+                let corners = [
+                    Coord::from((-radius, -radius)),
+                    Coord::from((radius, -radius)),
+                    Coord::from((radius, radius)),
+                    Coord::from((-radius, radius)),
+                ];
+                geometry::distance_to_polygon(&corners, &pt)
+            }
+        };
+        geometry::within(distance, radius)
+    }
+
     pub fn in_dir(mut self, dir: Direction, frac: f64) -> Self {
         self.position = self.position.in_dir(dir, frac);
         self
@@ -160,6 +211,25 @@ impl City {
         self
     }
 
+    /// Prevents tokens from being placed in this city's token spaces until
+    /// the game phase whose tiles have the specified colour.
+    pub fn with_available_from(mut self, colour: HexColour) -> Self {
+        self.available_from = Some(colour);
+        self
+    }
+
+    /// Returns `true` if tokens can be placed in this city's token spaces in
+    /// the game phase whose tiles have the specified colour.
+    pub fn available_in(&self, phase: HexColour) -> bool {
+        let Some(from) = self.available_from else {
+            return true;
+        };
+        match (phase.phase_number(), from.phase_number()) {
+            (Some(phase), Some(from)) => phase >= from,
+            _ => false,
+        }
+    }
+
     pub fn single(revenue: usize) -> City {
         City {
             tokens: Tokens::Single,
@@ -167,6 +237,7 @@ impl City {
             position: HexPosition::Centre(None),
             angle: Rotation::Zero,
             fill_colour: None,
+            available_from: None,
         }
     }
 
@@ -177,6 +248,7 @@ impl City {
             position: HexPosition::Face(*face, None),
             angle: Rotation::Zero,
             fill_colour: None,
+            available_from: None,
         }
     }
 
@@ -187,6 +259,7 @@ impl City {
             position: HexPosition::Corner(*corner, None),
             angle: Rotation::Zero,
             fill_colour: None,
+            available_from: None,
         }
     }
 
@@ -197,6 +270,7 @@ impl City {
             position: HexPosition::Centre(None),
             angle: Rotation::Zero,
             fill_colour: None,
+            available_from: None,
         }
     }
 
@@ -207,6 +281,7 @@ impl City {
             position: HexPosition::Corner(*corner, None),
             angle: Rotation::Zero,
             fill_colour: None,
+            available_from: None,
         }
     }
 
@@ -218,6 +293,7 @@ impl City {
             position: HexPosition::Centre(None),
             angle: Rotation::Zero,
             fill_colour: None,
+            available_from: None,
         }
     }
 
@@ -228,6 +304,7 @@ impl City {
             position: HexPosition::Centre(None),
             angle: Rotation::Zero,
             fill_colour: None,
+            available_from: None,
         }
     }
 
@@ -235,8 +312,9 @@ impl City {
     // See tiles 8887 and 8888 for the game 1880:
     // http://www.fwtwr.com/18xx/tiles/tiles.asp?xGame=1880
 
+    #[cfg(feature = "cairo")]
     fn define_fg_path(&self, hex: &Hex, ctx: &Context) {
-        let radius = hex.theme.token_space_radius.absolute(hex);
+        let radius = hex.geometry().token_space_radius.absolute(hex);
         self.define_bg_path(hex, ctx);
 
         match self.tokens {
@@ -275,8 +353,9 @@ impl City {
         }
     }
 
+    #[cfg(feature = "cairo")]
     fn define_bg_path(&self, hex: &Hex, ctx: &Context) {
-        let radius = hex.theme.token_space_radius.absolute(hex);
+        let radius = hex.geometry().token_space_radius.absolute(hex);
         ctx.new_path();
 
         match self.tokens {
@@ -380,6 +459,7 @@ impl City {
         (0..self.tokens.count()).collect()
     }
 
+    #[cfg(feature = "cairo")]
     pub fn define_token_path(
         &self,
         ix: usize,
@@ -391,7 +471,7 @@ impl City {
         }
 
         self.translate_begin(hex, ctx);
-        let radius = hex.theme.token_space_radius.absolute(hex);
+        let radius = hex.geometry().token_space_radius.absolute(hex);
         ctx.new_path();
 
         match self.tokens {
@@ -429,6 +509,7 @@ impl City {
     }
 }
 
+#[cfg(feature = "cairo")]
 impl Draw for City {
     fn define_boundary(&self, hex: &Hex, ctx: &Context) {
         self.translate_begin(hex, ctx);
@@ -462,5 +543,22 @@ impl Draw for City {
         ctx.stroke().unwrap();
 
         self.translate_end(hex, ctx);
+
+        // Mark token spaces that are not always available with a ring in the
+        // colour of the phase in which they become available.
+        if let Some(colour) = self.available_from {
+            let radius = hex.geometry().token_space_radius.absolute(hex);
+            for ix in self.token_ixs() {
+                ctx.save().unwrap();
+                self.define_token_path(ix, hex, ctx);
+                ctx.clip_preserve();
+                hex.theme.apply_hex_colour(ctx, colour);
+                ctx.set_line_width(0.5 * radius);
+                ctx.stroke_preserve().unwrap();
+                ctx.restore().unwrap();
+                hex.theme.token_space_inner.apply_line_and_stroke(ctx, hex);
+                ctx.stroke().unwrap();
+            }
+        }
     }
 }
@@ -1,6 +1,6 @@
 use crate::city::City;
-use crate::track::{Track, TrackEnd};
-use n18hex::{Hex, HexFace};
+use crate::track::{PhaseRevenues, Track, TrackEnd};
+use n18hex::{Hex, HexColour, HexFace, RotateCW};
 use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -56,6 +56,19 @@ pub struct Dit {
     pub track_ix: usize,
     end: TrackEnd,
     pub revenue: usize,
+    /// The phase-dependent revenue of this dit, if any.
+    pub phase_revenues: PhaseRevenues,
+}
+
+impl Dit {
+    /// Returns the revenue of this dit in the game phase in which tiles of
+    /// colour `phase` are available, or the dit's base revenue if `phase` is
+    /// `None` or the dit has no revenue for this phase.
+    pub fn revenue_in(&self, phase: Option<HexColour>) -> usize {
+        phase
+            .and_then(|phase| self.phase_revenues.revenue(phase))
+            .unwrap_or(self.revenue)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -93,8 +106,6 @@ impl Connections {
         let mut dit_conns = BTreeMap::new();
         let mut city_conns = BTreeMap::new();
 
-        let ctx = hex.context();
-
         for i in 0..tracks.len() {
             let track = tracks[i];
 
@@ -118,6 +129,7 @@ impl Connections {
                     track_ix: i,
                     end: dit_end,
                     revenue,
+                    phase_revenues: track.dit_revenues,
                 });
                 dit_conns.entry(dit_ix).or_insert_with(Vec::new).push(
                     Connection::Track {
@@ -136,7 +148,7 @@ impl Connections {
                     if j == i {
                         continue;
                     }
-                    let conn_opt = track.connected_at(other, hex, ctx);
+                    let conn_opt = track.connected_at(other, hex);
                     if let Some((conn_end, other_end)) = conn_opt {
                         if conn_end == dit_end {
                             dit_conns
@@ -158,7 +170,7 @@ impl Connections {
 
         for (cx, city) in cities.iter().enumerate() {
             for (i, track) in tracks.iter().enumerate() {
-                let end_opt = track.connected_to_fill_at(city, hex, ctx);
+                let end_opt = track.connected_to_fill_at(city, hex);
                 if let Some(end) = end_opt {
                     city_conns
                         .entry(cx)
@@ -185,7 +197,7 @@ impl Connections {
             let end_conns = track_conns.contains_key(&(i, TrackEnd::End));
             if !(start_conns && end_conns) {
                 for (j, other) in tracks.iter().enumerate().skip(i + 1) {
-                    if track.connected(other, hex, ctx) {
+                    if track.connected(other, hex) {
                         println!("WARNING: tracks {} and {} connect", i, j);
                     }
                 }
@@ -265,6 +277,124 @@ impl Connections {
         }
         visited
     }
+
+    /// Returns the connectivity between hex faces, cities, and dits.
+    pub fn connectivity(&self) -> Connectivity {
+        let mut connectivity = Connectivity::default();
+        for face in self.face.keys() {
+            let start = Connection::Face { face: *face };
+            let mut faces = BTreeSet::new();
+            let mut cities = BTreeSet::new();
+            let mut dits = BTreeSet::new();
+            for conn in self.connections_from(&start) {
+                match conn {
+                    Connection::Face { face: other } if other != *face => {
+                        faces.insert(other);
+                    }
+                    Connection::City { ix } => {
+                        cities.insert(ix);
+                    }
+                    Connection::Dit { ix } => {
+                        dits.insert(ix);
+                    }
+                    _ => {}
+                }
+            }
+            connectivity.faces.insert(*face, faces);
+            connectivity.cities.insert(*face, cities);
+            connectivity.dits.insert(*face, dits);
+        }
+        connectivity
+    }
+}
+
+/// Records which hex faces, cities, and dits of a tile are connected by
+/// track, without reference to the individual track segments.
+///
+/// This only describes the tile's connectivity, and so it can be queried
+/// without a drawing context.
+/// Hex faces are relative to the tile's orientation, unless the connectivity
+/// was obtained from [Tile::connectivity_at](crate::Tile::connectivity_at)
+/// or [Connectivity::rotate].
+///
+/// Note that connections may pass through cities and dits; for example, both
+/// faces of tile 57 are connected to each other, and to its only city.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Connectivity {
+    /// The other hex faces that are connected to each hex face.
+    faces: BTreeMap<HexFace, BTreeSet<HexFace>>,
+    /// The cities that are connected to each hex face.
+    cities: BTreeMap<HexFace, BTreeSet<usize>>,
+    /// The dits that are connected to each hex face.
+    dits: BTreeMap<HexFace, BTreeSet<usize>>,
+}
+
+impl Connectivity {
+    /// Returns the hex faces that are connected to a track segment.
+    pub fn track_faces(&self) -> BTreeSet<HexFace> {
+        self.faces.keys().copied().collect()
+    }
+
+    /// Returns `true` if a track segment is connected to the hex face.
+    pub fn has_track(&self, face: HexFace) -> bool {
+        self.faces.contains_key(&face)
+    }
+
+    /// Returns the other hex faces that are connected to a hex face.
+    pub fn faces_from_face(&self, face: HexFace) -> BTreeSet<HexFace> {
+        self.faces.get(&face).cloned().unwrap_or_default()
+    }
+
+    /// Returns the index of each city that is connected to a hex face.
+    pub fn cities_from_face(&self, face: HexFace) -> BTreeSet<usize> {
+        self.cities.get(&face).cloned().unwrap_or_default()
+    }
+
+    /// Returns the index of each dit that is connected to a hex face.
+    pub fn dits_from_face(&self, face: HexFace) -> BTreeSet<usize> {
+        self.dits.get(&face).cloned().unwrap_or_default()
+    }
+
+    /// Returns the hex faces that are connected to a city.
+    pub fn faces_from_city(&self, city_ix: usize) -> BTreeSet<HexFace> {
+        self.cities
+            .iter()
+            .filter(|(_face, cities)| cities.contains(&city_ix))
+            .map(|(face, _cities)| *face)
+            .collect()
+    }
+
+    /// Returns the hex faces that are connected to a dit.
+    pub fn faces_from_dit(&self, dit_ix: usize) -> BTreeSet<HexFace> {
+        self.dits
+            .iter()
+            .filter(|(_face, dits)| dits.contains(&dit_ix))
+            .map(|(face, _dits)| *face)
+            .collect()
+    }
+
+    /// Returns the connectivity of the tile after it has been rotated.
+    pub fn rotate(&self, rotation: RotateCW) -> Self {
+        let rotate_keys = |map: &BTreeMap<HexFace, BTreeSet<usize>>| {
+            map.iter()
+                .map(|(face, ixs)| (*face + rotation, ixs.clone()))
+                .collect()
+        };
+        let faces = self
+            .faces
+            .iter()
+            .map(|(face, others)| {
+                let others =
+                    others.iter().map(|other| *other + rotation).collect();
+                (*face + rotation, others)
+            })
+            .collect();
+        Connectivity {
+            faces,
+            cities: rotate_keys(&self.cities),
+            dits: rotate_keys(&self.dits),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -330,4 +460,53 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    /// Check the connectivity of tile 5, and of a tile with a dit, at
+    /// different rotations.
+    fn test_connectivity() {
+        use n18hex::RotateCW;
+
+        let hex = Hex::new(HEX_DIAMETER);
+        let tile = Tile::new(
+            Yellow,
+            "5",
+            vec![Track::mid(Bottom), Track::mid(LowerRight)],
+            vec![City::single(20)],
+            &hex,
+        );
+        let conn = tile.connectivity();
+        assert_eq!(conn.track_faces(), [LowerRight, Bottom].into());
+        assert!(conn.has_track(Bottom));
+        assert!(!conn.has_track(Top));
+        assert_eq!(conn.faces_from_face(Bottom), [LowerRight].into());
+        assert_eq!(conn.cities_from_face(Bottom), [0].into());
+        assert!(conn.dits_from_face(Bottom).is_empty());
+        assert_eq!(conn.faces_from_city(0), [LowerRight, Bottom].into());
+        assert!(conn.faces_from_face(Top).is_empty());
+
+        // Rotating the tile clockwise should rotate each connected face.
+        let conn = tile.connectivity_at(RotateCW::One);
+        assert_eq!(conn.track_faces(), [Bottom, LowerLeft].into());
+        assert_eq!(conn.faces_from_face(LowerLeft), [Bottom].into());
+        assert_eq!(conn.faces_from_city(0), [Bottom, LowerLeft].into());
+        assert_eq!(tile.connectivity().rotate(RotateCW::One), conn);
+
+        // Track segments that meet at a dit are connected via the dit.
+        let tile = Tile::new(
+            Yellow,
+            "dit",
+            vec![
+                Track::mid(Bottom).with_dit(TrackEnd::End, 10, DitShape::Bar),
+                Track::mid(Top),
+            ],
+            vec![],
+            &hex,
+        );
+        let conn = tile.connectivity_at(RotateCW::Two);
+        assert_eq!(conn.faces_from_face(UpperLeft), [LowerRight].into());
+        assert_eq!(conn.dits_from_face(UpperLeft), [0].into());
+        assert_eq!(conn.faces_from_dit(0), [LowerRight, UpperLeft].into());
+        assert!(conn.cities_from_face(UpperLeft).is_empty());
+    }
 }
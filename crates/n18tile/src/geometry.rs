@@ -0,0 +1,267 @@
+//! Determines whether points lie inside the strokes of track segments and
+//! the boundaries of cities, without requiring a drawing context.
+//!
+//! These tests mirror the paths that are drawn with Cairo: lines and arcs
+//! are stroked with butt caps and round joins, and points that lie on the
+//! boundary of a stroke or fill are considered to be inside it.
+
+use n18hex::{Coord, PI};
+
+/// The tolerance for points that lie on the boundary of a stroke or fill.
+const EPSILON: f64 = 1e-6;
+
+/// A single piece of a stroked path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Piece {
+    /// A straight line from `start` to `end`.
+    Line { start: Coord, end: Coord },
+    /// A circular arc that begins at `angle_0` and ends at `angle_1`, where
+    /// the angle increases along the arc unless `negative` is `true`.
+    Arc {
+        centre: Coord,
+        radius: f64,
+        angle_0: f64,
+        angle_1: f64,
+        negative: bool,
+    },
+    /// A round join between two pieces of the same sub-path.
+    Join { at: Coord },
+}
+
+impl Piece {
+    /// Returns the start of this piece.
+    pub fn start(&self) -> Coord {
+        match self {
+            Piece::Line { start, .. } => *start,
+            Piece::Arc {
+                centre,
+                radius,
+                angle_0,
+                ..
+            } => arc_point(centre, *radius, *angle_0),
+            Piece::Join { at } => *at,
+        }
+    }
+
+    /// Returns the end of this piece.
+    pub fn end(&self) -> Coord {
+        match self {
+            Piece::Line { end, .. } => *end,
+            Piece::Arc {
+                centre,
+                radius,
+                angle_0,
+                angle_1,
+                negative,
+            } => {
+                let sweep = arc_sweep(*angle_0, *angle_1, *negative);
+                arc_point(centre, *radius, angle_0 + sweep)
+            }
+            Piece::Join { at } => *at,
+        }
+    }
+
+    /// Returns `true` if `pt` lies inside the stroke of this piece, for the
+    /// given line width.
+    pub fn stroke_contains(&self, width: f64, pt: &Coord) -> bool {
+        let half_width = 0.5 * width + EPSILON;
+        match self {
+            Piece::Line { start, end } => {
+                let dir = end - start;
+                let length = dir.magnitude();
+                if length == 0.0 {
+                    // NOTE: butt caps mean that nothing is drawn.
+                    return false;
+                }
+                let unit = dir.scale_by(1.0 / length);
+                let rel = pt - start;
+                let along = rel.x * unit.x + rel.y * unit.y;
+                let across = rel.x * unit.y - rel.y * unit.x;
+                along >= -EPSILON
+                    && along <= length + EPSILON
+                    && across.abs() <= half_width
+            }
+            Piece::Arc {
+                centre,
+                radius,
+                angle_0,
+                angle_1,
+                negative,
+            } => {
+                let rel = pt - centre;
+                if (rel.magnitude() - radius).abs() > half_width {
+                    return false;
+                }
+                let sweep = arc_sweep(*angle_0, *angle_1, *negative);
+                let angle = rel.y.atan2(rel.x);
+                // Measure the angle from the start of the arc, in the
+                // direction of the arc.
+                let offset = if *negative {
+                    (angle_0 - angle).rem_euclid(2.0 * PI)
+                } else {
+                    (angle - angle_0).rem_euclid(2.0 * PI)
+                };
+                let tolerance = EPSILON / radius;
+                offset <= sweep.abs() + tolerance
+                    || offset >= 2.0 * PI - tolerance
+            }
+            Piece::Join { at } => (pt - at).magnitude() <= half_width,
+        }
+    }
+}
+
+/// Returns the point on a circle at the given angle.
+fn arc_point(centre: &Coord, radius: f64, angle: f64) -> Coord {
+    Coord {
+        x: centre.x + radius * angle.cos(),
+        y: centre.y + radius * angle.sin(),
+    }
+}
+
+/// Returns the signed angle swept by an arc, following the conventions of
+/// `cairo::Context::arc` and `cairo::Context::arc_negative`.
+fn arc_sweep(angle_0: f64, angle_1: f64, negative: bool) -> f64 {
+    if negative {
+        let mut angle_1 = angle_1;
+        while angle_1 > angle_0 {
+            angle_1 -= 2.0 * PI;
+        }
+        angle_1 - angle_0
+    } else {
+        let mut angle_1 = angle_1;
+        while angle_1 < angle_0 {
+            angle_1 += 2.0 * PI;
+        }
+        angle_1 - angle_0
+    }
+}
+
+/// Returns the distance from `pt` to the line segment from `start` to
+/// `end`.
+pub(crate) fn distance_to_segment(
+    start: &Coord,
+    end: &Coord,
+    pt: &Coord,
+) -> f64 {
+    let dir = end - start;
+    let length_sq = dir.x * dir.x + dir.y * dir.y;
+    let rel = pt - start;
+    if length_sq == 0.0 {
+        return rel.magnitude();
+    }
+    let frac = ((rel.x * dir.x + rel.y * dir.y) / length_sq).clamp(0.0, 1.0);
+    (pt - &start.interpolate(end, frac)).magnitude()
+}
+
+/// Returns the distance from `pt` to the convex polygon with the provided
+/// corners, which is zero if `pt` lies inside the polygon.
+pub(crate) fn distance_to_polygon(corners: &[Coord], pt: &Coord) -> f64 {
+    let n = corners.len();
+    let edges = (0..n).map(|i| (&corners[i], &corners[(i + 1) % n]));
+    // NOTE: the point is inside a convex polygon if it lies on the same side
+    // of every edge.
+    let sides: Vec<f64> = edges
+        .clone()
+        .map(|(a, b)| {
+            let edge = b - a;
+            let rel = pt - a;
+            edge.x * rel.y - edge.y * rel.x
+        })
+        .collect();
+    if sides.iter().all(|s| *s >= 0.0) || sides.iter().all(|s| *s <= 0.0) {
+        return 0.0;
+    }
+    edges
+        .map(|(a, b)| distance_to_segment(a, b, pt))
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Returns `true` if a distance lies within the given radius, allowing for
+/// points on the boundary.
+pub(crate) fn within(distance: f64, radius: f64) -> bool {
+    distance <= radius + EPSILON
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pt(x: f64, y: f64) -> Coord {
+        Coord { x, y }
+    }
+
+    #[test]
+    fn line_strokes_have_butt_caps() {
+        let line = Piece::Line {
+            start: pt(0.0, 0.0),
+            end: pt(10.0, 0.0),
+        };
+        assert!(line.stroke_contains(2.0, &pt(5.0, 1.0)));
+        assert!(line.stroke_contains(2.0, &pt(10.0, -1.0)));
+        assert!(!line.stroke_contains(2.0, &pt(5.0, 1.1)));
+        assert!(!line.stroke_contains(2.0, &pt(10.1, 0.0)));
+        assert!(!line.stroke_contains(2.0, &pt(-0.1, 0.0)));
+
+        // Zero-length lines are not drawn.
+        let point = Piece::Line {
+            start: pt(1.0, 1.0),
+            end: pt(1.0, 1.0),
+        };
+        assert!(!point.stroke_contains(2.0, &pt(1.0, 1.0)));
+    }
+
+    #[test]
+    fn arc_strokes_follow_their_direction() {
+        // The quarter circle from the positive x-axis to the positive y-axis.
+        let arc = Piece::Arc {
+            centre: pt(0.0, 0.0),
+            radius: 10.0,
+            angle_0: 0.0,
+            angle_1: 0.5 * PI,
+            negative: false,
+        };
+        assert!((&arc.start() - &pt(10.0, 0.0)).magnitude() < 1e-8);
+        assert!((&arc.end() - &pt(0.0, 10.0)).magnitude() < 1e-8);
+        let mid = 10.0 * (0.25 * PI).cos();
+        assert!(arc.stroke_contains(2.0, &pt(mid, mid)));
+        assert!(!arc.stroke_contains(2.0, &pt(-mid, -mid)));
+        assert!(!arc.stroke_contains(2.0, &pt(0.5 * mid, 0.5 * mid)));
+
+        // The same end points, drawn in the other direction, cover the
+        // remaining three quarters of the circle.
+        let arc = Piece::Arc {
+            centre: pt(0.0, 0.0),
+            radius: 10.0,
+            angle_0: 0.0,
+            angle_1: 0.5 * PI,
+            negative: true,
+        };
+        assert!(!arc.stroke_contains(2.0, &pt(mid, mid)));
+        assert!(arc.stroke_contains(2.0, &pt(-mid, -mid)));
+    }
+
+    #[test]
+    fn joins_are_round() {
+        let join = Piece::Join { at: pt(1.0, 1.0) };
+        assert!(join.stroke_contains(2.0, &pt(1.0, 2.0)));
+        assert!(!join.stroke_contains(2.0, &pt(1.8, 1.8)));
+    }
+
+    #[test]
+    fn polygon_distances() {
+        let square = [pt(0.0, 0.0), pt(2.0, 0.0), pt(2.0, 2.0), pt(0.0, 2.0)];
+        assert_eq!(distance_to_polygon(&square, &pt(1.0, 1.0)), 0.0);
+        assert_eq!(distance_to_polygon(&square, &pt(2.0, 1.0)), 0.0);
+        assert!(
+            (distance_to_polygon(&square, &pt(3.0, 1.0)) - 1.0).abs() < 1e-8
+        );
+        assert!(within(
+            distance_to_segment(&square[0], &square[1], &pt(1.0, 1.0)),
+            1.0
+        ));
+        assert!(!within(
+            distance_to_segment(&square[0], &square[1], &pt(3.0, 1.0)),
+            1.0
+        ));
+    }
+}
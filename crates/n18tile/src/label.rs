@@ -1,10 +1,12 @@
+#[cfg(feature = "cairo")]
 use crate::Tile;
+#[cfg(feature = "cairo")]
 use cairo::Context;
+#[cfg(feature = "cairo")]
 use n18hex::consts::PI;
-use n18hex::{
-    Colour, Coord, Hex, HexColour, HexCorner, HexFace, HexPosition,
-    Orientation,
-};
+#[cfg(feature = "cairo")]
+use n18hex::{Colour, Coord, HexFace};
+use n18hex::{Hex, HexColour, HexCorner, HexPosition, Orientation};
 
 /// The different types of labels that may appear on a tile.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -55,6 +57,7 @@ impl Label {
     }
 
     /// Draw this label on a tile.
+    #[cfg(feature = "cairo")]
     pub fn draw(
         &self,
         ctx: &Context,
@@ -234,6 +237,7 @@ impl Label {
 
     /// Draw a tile name label with custom text, in the default position of
     /// the bottom-right corner.
+    #[cfg(feature = "cairo")]
     pub fn draw_custom_tile_name(ctx: &Context, hex: &Hex, name: &str) {
         let mut labeller = hex.theme.tile_label.labeller(ctx, hex);
         let pos = Self::tile_name_position(hex);
@@ -265,6 +269,7 @@ impl Label {
 
 /// Returns a vector of `(colour, label_text)` pairs for each phase of a phase
 /// revenue label.
+#[cfg(feature = "cairo")]
 fn get_boxes(
     amounts: &[(HexColour, usize, bool)],
 ) -> Vec<(HexColour, String)> {
@@ -275,6 +280,7 @@ fn get_boxes(
 }
 
 /// Returns the index of the active phase revenue box, if any.
+#[cfg(feature = "cairo")]
 fn active_box_ix(amounts: &[(HexColour, usize, bool)]) -> Option<usize> {
     amounts.iter().enumerate().find_map(
         |(ix, (_colour, _amount, active))| {
@@ -289,6 +295,7 @@ fn active_box_ix(amounts: &[(HexColour, usize, bool)]) -> Option<usize> {
 
 /// Returns the width and height, respectively, of each phase revenue box,
 /// including margins.
+#[cfg(feature = "cairo")]
 fn box_dims(
     boxes: &[(HexColour, String)],
     hex: &Hex,
@@ -306,8 +313,8 @@ fn box_dims(
                 (curr_w.max(new_w), curr_h.max(new_h))
             },
         );
-    let margin_width = hex.theme.phase_revenue_margin_x.absolute(hex);
-    let margin_height = hex.theme.phase_revenue_margin_y.absolute(hex);
+    let margin_width = hex.theme.phase_revenue_margin_x.margin(hex);
+    let margin_height = hex.theme.phase_revenue_margin_y.margin(hex);
     let box_width = box_width + 2.0 * margin_width;
     let box_height = box_height + 2.0 * margin_height;
     (box_width, box_height)
@@ -323,6 +330,7 @@ fn box_dims(
 /// degrees clockwise.
 /// This means that [HexFace::Top] is the upper-right face, [Corner::TopLeft]
 /// is the top corner, and so on.
+#[cfg(feature = "cairo")]
 fn h_align(hex: &Hex, pos: &HexPosition) -> n18hex::theme::AlignH {
     use n18hex::theme::AlignH;
     use HexCorner::*;
@@ -371,6 +379,7 @@ fn h_align(hex: &Hex, pos: &HexPosition) -> n18hex::theme::AlignH {
 /// degrees clockwise.
 /// This means that [HexFace::Top] is the upper-right face, [Corner::TopLeft]
 /// is the top corner, and so on.
+#[cfg(feature = "cairo")]
 fn v_align(hex: &Hex, pos: &HexPosition) -> n18hex::theme::AlignV {
     use n18hex::theme::AlignV;
     use HexCorner::*;
@@ -411,6 +420,7 @@ fn v_align(hex: &Hex, pos: &HexPosition) -> n18hex::theme::AlignV {
 /// The ratio of text width to text height at which we switch from drawing a
 /// circle around the text to drawing an ellipse around the text.
 /// This improves the appearance of, e.g., revenue labels for $100 and above.
+#[cfg(feature = "cairo")]
 const ELLIPSE_RATIO: f64 = 1.25;
 
 /// The scaling factor for the relationship between the text bounding box
@@ -418,8 +428,10 @@ const ELLIPSE_RATIO: f64 = 1.25;
 /// We want this radius to be larger than the minimum size required to include
 /// the text bounding box, so that there is some space between the text and
 /// the circle/ellipse border.
+#[cfg(feature = "cairo")]
 const ELLIPSE_RADIUS_SCALE: f64 = 4.0 / 3.0;
 
+#[cfg(feature = "cairo")]
 fn ellipse_height(radius: f64, ratio: f64) -> f64 {
     let diam = 2.0 * radius;
     if ratio >= ELLIPSE_RATIO {
@@ -429,6 +441,7 @@ fn ellipse_height(radius: f64, ratio: f64) -> f64 {
     }
 }
 
+#[cfg(feature = "cairo")]
 fn define_ellipse(ctx: &Context, radius: f64, ratio: f64, centre: Coord) {
     if ratio >= ELLIPSE_RATIO {
         let matrix = ctx.matrix();
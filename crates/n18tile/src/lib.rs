@@ -1,15 +1,21 @@
 /// Generic trait for tiles and tile elements that draw themselves.
+#[cfg(feature = "cairo")]
 pub mod draw;
 
 /// Cities and token spaces.
 pub mod city;
 
+/// Arrows that point out of a tile through a hex face.
+pub mod arrow;
+
 /// Tile labels, such as tile names, city names, and revenue.
 pub mod label;
 
 /// Track segments.
 pub mod track;
 
+mod geometry;
+
 /// Connections between track segments, cities, and tile edges.
 pub mod connection;
 
@@ -20,12 +26,16 @@ pub mod upgrade;
 
 pub mod ekmf;
 
+#[doc(inline)]
+pub use arrow::Arrow;
+
 #[doc(inline)]
 pub use city::{City, Rotation, Tokens};
 
 #[doc(inline)]
-pub use connection::{Connection, Connections, Dit};
+pub use connection::{Connection, Connections, Connectivity, Dit};
 
+#[cfg(feature = "cairo")]
 #[doc(inline)]
 pub use draw::Draw;
 
@@ -36,4 +46,4 @@ pub use label::Label;
 pub use tile::{LabelAndPos, Tile, TokenSpace};
 
 #[doc(inline)]
-pub use track::{DitShape, Track, TrackCurve, TrackEnd};
+pub use track::{DitShape, PhaseRevenues, Track, TrackCurve, TrackEnd};
@@ -1,6 +1,13 @@
-use crate::{City, Connection, Connections, Dit, Draw, Label, Track};
+#[cfg(feature = "cairo")]
+use crate::Draw;
+use crate::{
+    Arrow, City, Connection, Connections, Connectivity, Dit, Label, Track,
+};
+#[cfg(feature = "cairo")]
 use cairo::Context;
-use n18hex::{Colour, Hex, HexColour, HexFace, HexPosition};
+#[cfg(feature = "cairo")]
+use n18hex::Colour;
+use n18hex::{Hex, HexColour, HexFace, HexPosition, RotateCW};
 use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -52,6 +59,8 @@ pub struct Tile {
     revenues: Vec<usize>,
     // Tile labels: tile name, revenue, city name, etc.
     labels: Vec<LabelAndPos>,
+    // Arrows that point out of the tile through hex faces.
+    arrows: Vec<Arrow>,
     // Whether to show the tile name.
     show_tile_name: bool,
     // Connections between tracks, dits, cities, and hex faces.
@@ -83,7 +92,6 @@ impl Tile {
         // part of the clipped path) and break them into separate segments
         // (e.g., straight -> mid + mid; gentle_l -> ...)
         // Hmmm ... maybe not
-        let ctx = hex.context();
         let mut tracks_tbl = BTreeMap::new();
         let mut cities_tbl = BTreeMap::new();
         let default_layer = DrawLayer::Normal;
@@ -98,7 +106,7 @@ impl Tile {
         }
         for (i, track) in tracks.iter().enumerate() {
             for (j, other) in tracks.iter().enumerate().skip(i + 1) {
-                if track.crosses(other, hex, dt, ctx) {
+                if track.crosses(other, hex, dt) {
                     if verbose {
                         println!("    Tracks {} and {} cross", i, j);
                     }
@@ -141,10 +149,10 @@ impl Tile {
                 // through a city. This allows routes to identify a track
                 // by tile and the track index, rather than needing to worry
                 // about subsets of a track.
-                if track.connected_to_fill(city, hex, ctx) {
+                if track.connected_to_fill(city, hex) {
                     let track_layer = track_layers.get(&i).unwrap_or(&layer);
                     layer = std::cmp::max(layer, *track_layer);
-                } else if track.intersects_fill(city, hex, dt, ctx) {
+                } else if track.intersects_fill(city, hex, dt) {
                     println!("WARNING: track crosses city, tile {}", name);
                     let track_layer = track_layers.get(&i).unwrap_or(&layer);
                     layer = std::cmp::max(layer, *track_layer);
@@ -169,6 +177,7 @@ impl Tile {
             cities_tbl,
             revenues,
             labels: vec![],
+            arrows: vec![],
             show_tile_name: true,
             conns,
             offboard_faces: None,
@@ -225,6 +234,19 @@ impl Tile {
         self.conns.from(from)
     }
 
+    /// Returns the connectivity between this tile's hex faces, cities, and
+    /// dits, where the hex faces are relative to the tile's orientation.
+    pub fn connectivity(&self) -> Connectivity {
+        self.conns.connectivity()
+    }
+
+    /// Returns the connectivity between this tile's hex faces, cities, and
+    /// dits when the tile is placed with the specified rotation, where the
+    /// hex faces are relative to the map.
+    pub fn connectivity_at(&self, rotation: RotateCW) -> Connectivity {
+        self.conns.connectivity().rotate(rotation)
+    }
+
     /// Returns all connections that can be reached from `start`.
     pub fn all_connections_from<T>(&self, start: T) -> BTreeSet<Connection>
     where
@@ -372,6 +394,21 @@ impl Tile {
         self
     }
 
+    /// Adds an arrow that points out of the tile through a hex face (e.g.,
+    /// to show the map edges to which an off-board tile connects).
+    pub fn arrow<A>(mut self, arrow: A) -> Self
+    where
+        A: Into<Arrow>,
+    {
+        self.arrows.push(arrow.into());
+        self
+    }
+
+    /// Returns the arrows that point out of the tile through hex faces.
+    pub fn arrows(&self) -> &[Arrow] {
+        self.arrows.as_slice()
+    }
+
     pub fn tracks(&self) -> &[Track] {
         self.tracks.as_slice()
     }
@@ -388,11 +425,77 @@ impl Tile {
         self.labels.as_slice()
     }
 
+    /// Returns the revenue shown by this tile's phase revenue label (see
+    /// [Label::PhaseRevenue]) for the game phase in which tiles of colour
+    /// `phase` are introduced.
+    ///
+    /// If the label has no entry for this colour, the entry for the most
+    /// recent earlier phase is used.
+    /// Returns `None` if the tile has no phase revenue label, or if the
+    /// label has no entry for this phase or any earlier phase.
+    pub fn phase_revenue(&self, phase: HexColour) -> Option<usize> {
+        let phase_num = phase.phase_number()?;
+        self.labels.iter().find_map(|(label, _pos)| match label {
+            Label::PhaseRevenue(amounts)
+            | Label::PhaseRevenueVert(amounts) => amounts
+                .iter()
+                .filter_map(|(colour, revenue, _active)| {
+                    colour
+                        .phase_number()
+                        .filter(|&num| num <= phase_num)
+                        .map(|num| (num, *revenue))
+                })
+                .max_by_key(|(num, _revenue)| *num)
+                .map(|(_num, revenue)| revenue),
+            _ => None,
+        })
+    }
+
+    /// Highlights the revenue for the game phase in which tiles of colour
+    /// `phase` are introduced, in this tile's phase revenue label (see
+    /// [Label::PhaseRevenue]).
+    ///
+    /// As per [Tile::phase_revenue], if the label has no entry for this
+    /// colour, the entry for the most recent earlier phase is highlighted.
+    /// The label is not modified if it has no entry for this phase or any
+    /// earlier phase.
+    pub fn set_revenue_phase(&mut self, phase: HexColour) {
+        let Some(phase_num) = phase.phase_number() else {
+            return;
+        };
+        for (label, _pos) in self.labels.iter_mut() {
+            let amounts = match label {
+                Label::PhaseRevenue(amounts)
+                | Label::PhaseRevenueVert(amounts) => amounts,
+                _ => continue,
+            };
+            let active_ix = amounts
+                .iter()
+                .enumerate()
+                .filter_map(|(ix, (colour, _revenue, _active))| {
+                    colour
+                        .phase_number()
+                        .filter(|&num| num <= phase_num)
+                        .map(|num| (num, ix))
+                })
+                .max_by_key(|(num, _ix)| *num)
+                .map(|(_num, ix)| ix);
+            if let Some(active_ix) = active_ix {
+                for (ix, (_colour, _revenue, active)) in
+                    amounts.iter_mut().enumerate()
+                {
+                    *active = ix == active_ix;
+                }
+            }
+        }
+    }
+
     /// Returns the city that corresponds to the provided token location.
     pub fn city(&self, space: &TokenSpace) -> Option<&City> {
         self.cities.get(space.city_ix)
     }
 
+    #[cfg(feature = "cairo")]
     fn layer_bg(&self, layer: &DrawLayer, ctx: &Context, hex: &Hex) {
         let empty = vec![];
         for ix in self.tracks_tbl.get(layer).unwrap_or(&empty) {
@@ -406,6 +509,7 @@ impl Tile {
         }
     }
 
+    #[cfg(feature = "cairo")]
     #[allow(dead_code)]
     fn coords_in_red(&self, layer: &DrawLayer, ctx: &Context, hex: &Hex) {
         let empty = vec![];
@@ -425,6 +529,7 @@ impl Tile {
         }
     }
 
+    #[cfg(feature = "cairo")]
     #[allow(dead_code)]
     fn dit_coords_in_red(&self, ctx: &Context, hex: &Hex) {
         use DrawLayer::*;
@@ -448,6 +553,7 @@ impl Tile {
         }
     }
 
+    #[cfg(feature = "cairo")]
     fn layer_fg(&self, layer: &DrawLayer, ctx: &Context, hex: &Hex) {
         let empty = vec![];
         for ix in self.tracks_tbl.get(layer).unwrap_or(&empty) {
@@ -487,6 +593,7 @@ impl Tile {
     ///
     /// If no such segment should be drawn, this has no effect and returns
     /// `false`.
+    #[cfg(feature = "cairo")]
     pub fn define_offboard_track_inner_path(
         &self,
         ctx: &Context,
@@ -502,8 +609,8 @@ impl Tile {
         }
 
         // Extract relevant theme settings.
-        let inner_w = hex.theme.track_inner.width.absolute(hex);
-        let outer_w = hex.theme.track_outer.width.absolute(hex);
+        let inner_w = hex.theme.track_inner.line_width(hex);
+        let outer_w = hex.theme.track_outer.line_width(hex);
         let len = hex.theme.track_offboard_length.absolute(hex);
 
         // Calculate reference coordinates.
@@ -530,6 +637,7 @@ impl Tile {
     ///
     /// If no such segment should be drawn, this has no effect and returns
     /// `false`.
+    #[cfg(feature = "cairo")]
     pub fn draw_offboard_segment(
         &self,
         ctx: &Context,
@@ -545,8 +653,8 @@ impl Tile {
         }
 
         // Extract relevant theme settings.
-        let inner_w = hex.theme.track_inner.width.absolute(hex);
-        let outer_w = hex.theme.track_outer.width.absolute(hex);
+        let inner_w = hex.theme.track_inner.line_width(hex);
+        let outer_w = hex.theme.track_outer.line_width(hex);
         let len = hex.theme.track_offboard_length.absolute(hex);
 
         // Calculate reference coordinates.
@@ -582,11 +690,27 @@ impl Tile {
         true
     }
 
+    #[cfg(feature = "cairo")]
     pub fn draw(&self, ctx: &Context, hex: &Hex) {
-        use DrawLayer::*;
+        self.draw_background(ctx, hex);
+        self.draw_foreground(ctx, hex);
+    }
 
-        // Draw the tile background.
+    /// Fills the tile with its background colour.
+    #[cfg(feature = "cairo")]
+    pub fn draw_background(&self, ctx: &Context, hex: &Hex) {
         hex.draw_background(self.colour, ctx);
+    }
+
+    /// Draws everything except the tile background: track segments,
+    /// cities, dits, arrows, and labels.
+    ///
+    /// This allows other features (e.g., map terrain) to be drawn between
+    /// the tile background and the tile contents.
+    #[cfg(feature = "cairo")]
+    pub fn draw_foreground(&self, ctx: &Context, hex: &Hex) {
+        use DrawLayer::*;
+
         if let Some(ref faces) = self.offboard_faces {
             for face in faces {
                 self.draw_offboard_segment(ctx, hex, face);
@@ -606,6 +730,13 @@ impl Tile {
             self.layer_bg(&Topmost, ctx, hex);
             self.layer_fg(&Topmost, ctx, hex);
         }
+        // Draw any arrows over the track segments and cities.
+        for arrow in &self.arrows {
+            arrow.draw_bg(hex, ctx);
+        }
+        for arrow in &self.arrows {
+            arrow.draw_fg(hex, ctx);
+        }
         // Draw the tile name, except for special tiles such as those that are
         // part of the initial map and are not truly "tiles" as such.
         if self.show_tile_name {
@@ -651,6 +782,7 @@ impl Tile {
             .count()
     }
 
+    #[cfg(feature = "cairo")]
     pub fn define_token_space(
         &self,
         space: &TokenSpace,
@@ -664,6 +796,47 @@ impl Tile {
         city.define_token_path(space.token_ix, hex, ctx)
     }
 
+    /// Returns the token space that contains the point `(x, y)`, if any.
+    ///
+    /// The point is defined relative to the centre of the tile, in the
+    /// tile's own frame of reference (i.e., before the tile is rotated to
+    /// its orientation on the map).
+    /// City positions, nudges, and rotations are taken into account.
+    /// Where token spaces overlap, the space whose centre is closest to the
+    /// point is returned.
+    #[cfg(feature = "cairo")]
+    pub fn token_space_at_point(
+        &self,
+        x: f64,
+        y: f64,
+        hex: &Hex,
+    ) -> Option<TokenSpace> {
+        let ctx = hex.context();
+        let m = ctx.matrix();
+        ctx.identity_matrix();
+        let distance_sq = |space: &TokenSpace| {
+            if !self.define_token_space(space, hex, ctx) {
+                return None;
+            }
+            if !ctx.in_fill(x, y).unwrap_or(false) {
+                return None;
+            }
+            let (x0, y0, x1, y1) = ctx.fill_extents().ok()?;
+            let dx = x - (x0 + x1) / 2.0;
+            let dy = y - (y0 + y1) / 2.0;
+            Some(dx * dx + dy * dy)
+        };
+        let closest = self
+            .token_spaces()
+            .into_iter()
+            .filter_map(|space| distance_sq(&space).map(|d| (space, d)))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(space, _)| space);
+        ctx.new_path();
+        ctx.set_matrix(m);
+        closest
+    }
+
     /// Check whether a tile can be upgraded to another tile.
     pub fn can_upgrade_to(&self, other: &Tile) -> bool {
         // Check whether the new tile's colour is correct.
@@ -697,12 +870,14 @@ impl Tile {
 
     /// Determines the surface size for this tile, which includes a small
     /// margin on all four sides.
+    #[cfg(feature = "cairo")]
     fn surface_width(&self, hex: &Hex) -> f64 {
-        let margin = hex.theme.tile_margin.absolute(hex);
+        let margin = hex.theme.tile_margin.margin(hex);
         hex.max_d + 2.0 * margin
     }
 
     /// Saves the tile to a PNG file.
+    #[cfg(feature = "cairo")]
     pub fn save_png<P: AsRef<std::path::Path>>(
         &self,
         hex: &Hex,
@@ -713,6 +888,7 @@ impl Tile {
     }
 
     /// Saves the tile to an SVG file.
+    #[cfg(feature = "cairo")]
     pub fn save_svg<P: AsRef<std::path::Path>>(
         &self,
         hex: &Hex,
@@ -723,6 +899,7 @@ impl Tile {
     }
 
     /// Saves the tile to a PDF file.
+    #[cfg(feature = "cairo")]
     pub fn save_pdf<P: AsRef<std::path::Path>>(
         &self,
         hex: &Hex,
@@ -733,6 +910,7 @@ impl Tile {
     }
 
     /// Writes the tile as a PNG image to the provided stream.
+    #[cfg(feature = "cairo")]
     pub fn write_png<W: std::io::Write>(
         &self,
         hex: &Hex,
@@ -752,6 +930,7 @@ impl Tile {
     }
 
     /// Writes the tile as an SVG image to the provided stream.
+    #[cfg(feature = "cairo")]
     pub fn write_svg<W: std::io::Write + 'static>(
         &self,
         hex: &Hex,
@@ -771,6 +950,7 @@ impl Tile {
     }
 
     /// Writes the tile as a PDF image to the provided stream.
+    #[cfg(feature = "cairo")]
     pub fn write_pdf<W: std::io::Write + 'static>(
         &self,
         hex: &Hex,
@@ -912,4 +1092,147 @@ mod tests {
         }
         assert_eq!(tile.tracks_tbl.len(), 3, "Expected three drawing layers");
     }
+
+    #[test]
+    /// Constructs tiles with a nudged double city, with and without rotating
+    /// the city, and checks that points are matched to the correct token
+    /// spaces.
+    #[cfg(feature = "cairo")]
+    fn token_space_at_point_nudged_and_rotated() {
+        let hex = Hex::new(HEX_DIAMETER);
+        let radius = hex.theme.token_space_radius.absolute(&hex);
+        let dy = 0.2 * 0.5 * HEX_DIAMETER;
+        let city = City::double(20).in_dir(n18hex::Direction::S, 0.2);
+        let space = |token_ix| TokenSpace {
+            city_ix: 0,
+            token_ix,
+        };
+
+        let tile =
+            Tile::new(Yellow, "Test".to_string(), vec![], vec![city], &hex);
+        assert_eq!(
+            tile.token_space_at_point(radius, dy, &hex),
+            Some(space(0))
+        );
+        assert_eq!(
+            tile.token_space_at_point(-radius, dy, &hex),
+            Some(space(1))
+        );
+        // The un-nudged locations are only partially covered.
+        assert_eq!(
+            tile.token_space_at_point(radius, -0.5 * radius, &hex),
+            None
+        );
+        assert_eq!(tile.token_space_at_point(0.0, -radius, &hex), None);
+
+        let city = city.rotate(Rotation::Cw90);
+        let tile =
+            Tile::new(Yellow, "Test".to_string(), vec![], vec![city], &hex);
+        assert_eq!(
+            tile.token_space_at_point(0.0, dy + radius, &hex),
+            Some(space(0))
+        );
+        assert_eq!(
+            tile.token_space_at_point(0.0, dy - radius, &hex),
+            Some(space(1))
+        );
+        assert_eq!(tile.token_space_at_point(radius, dy, &hex), None);
+    }
+
+    #[test]
+    /// Checks that arrows are drawn inside the tile, next to their hex face,
+    /// and do not affect the tile's connectivity.
+    #[cfg(feature = "cairo")]
+    fn arrows_on_faces() {
+        let hex = Hex::new(HEX_DIAMETER);
+        let plain = Tile::new(Red, "Test", vec![], vec![], &hex);
+        let tile = plain
+            .clone()
+            .arrow(Top)
+            .arrow(Arrow::new(LowerRight).to_centre(0.2));
+        assert_eq!(tile.arrows().len(), 2);
+        assert_eq!(tile.arrows()[0], Arrow::new(Top));
+        assert_eq!(tile.connectivity(), plain.connectivity());
+        assert!(tile.connected_faces(Top).is_empty());
+
+        // The arrow covers a point just inside the top face.
+        let ctx = hex.context();
+        let top = hex.midpoint(&Top);
+        let inside = top.scale_by(0.95);
+        let outside = top.scale_by(1.05);
+        tile.arrows()[0].define_boundary(&hex, ctx);
+        assert!(ctx.in_fill(inside.x, inside.y).unwrap());
+        assert!(!ctx.in_fill(outside.x, outside.y).unwrap());
+        // The offset arrow does not reach its face.
+        let lower_right = hex.midpoint(&LowerRight).scale_by(0.95);
+        tile.arrows()[1].define_boundary(&hex, ctx);
+        assert!(!ctx.in_fill(lower_right.x, lower_right.y).unwrap());
+        ctx.new_path();
+    }
+
+    #[test]
+    /// Checks that the phase revenue label determines the revenue for each
+    /// game phase, using the most recent phase that has a revenue.
+    fn phase_revenue_from_label() {
+        let hex = Hex::new(HEX_DIAMETER);
+        let plain =
+            Tile::new(Red, "Test", vec![], vec![City::single(20)], &hex);
+        assert_eq!(plain.phase_revenue(Yellow), None);
+        let tile = plain.clone().label(
+            Label::PhaseRevenue(vec![
+                (Green, 30, true),
+                (Brown, 50, false),
+                (Grey, 60, false),
+            ]),
+            n18hex::HexCorner::Left.to_centre(0.1),
+        );
+        assert_eq!(tile.phase_revenue(Yellow), None);
+        assert_eq!(tile.phase_revenue(Green), Some(30));
+        assert_eq!(tile.phase_revenue(Brown), Some(50));
+        assert_eq!(tile.phase_revenue(Grey), Some(60));
+        assert_eq!(tile.phase_revenue(Red), None);
+        let tile = plain.label(
+            Label::PhaseRevenueVert(vec![
+                (Yellow, 20, true),
+                (Brown, 40, false),
+            ]),
+            n18hex::HexCorner::Left.to_centre(0.1),
+        );
+        assert_eq!(tile.phase_revenue(Yellow), Some(20));
+        assert_eq!(tile.phase_revenue(Green), Some(20));
+        assert_eq!(tile.phase_revenue(Grey), Some(40));
+    }
+
+    #[test]
+    /// Checks that the phase revenue label highlights the revenue for the
+    /// current game phase, using the most recent phase that has a revenue.
+    fn phase_revenue_highlight() {
+        let hex = Hex::new(HEX_DIAMETER);
+        let mut tile =
+            Tile::new(Red, "Test", vec![], vec![City::single(20)], &hex)
+                .label(
+                    Label::PhaseRevenue(vec![
+                        (Green, 30, true),
+                        (Brown, 50, false),
+                        (Grey, 60, false),
+                    ]),
+                    n18hex::HexCorner::Left.to_centre(0.1),
+                );
+        let active = |tile: &Tile| match &tile.labels()[0].0 {
+            Label::PhaseRevenue(amounts) => amounts
+                .iter()
+                .map(|(_colour, _revenue, active)| *active)
+                .collect::<Vec<_>>(),
+            _ => panic!("No phase revenue label"),
+        };
+        tile.set_revenue_phase(Brown);
+        assert_eq!(active(&tile), [false, true, false]);
+        tile.set_revenue_phase(Grey);
+        assert_eq!(active(&tile), [false, false, true]);
+        // The label is not modified if there is no revenue for this phase.
+        tile.set_revenue_phase(Yellow);
+        assert_eq!(active(&tile), [false, false, true]);
+        tile.set_revenue_phase(Green);
+        assert_eq!(active(&tile), [true, false, false]);
+    }
 }
@@ -1,11 +1,17 @@
+use crate::city::City;
+#[cfg(feature = "cairo")]
 use crate::draw::Draw;
+use crate::geometry::Piece;
+#[cfg(feature = "cairo")]
 use cairo::Context;
-use n18hex::theme::Length;
-use n18hex::{Coord, Hex, HexCorner, HexFace, PI};
+#[cfg(feature = "cairo")]
+use n18hex::Length;
+use n18hex::{Coord, Hex, HexColour, HexCorner, HexFace, PI};
 
 /// The shapes that track segments may take.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub enum TrackCurve {
+    #[default]
     Straight,
     GentleL,
     HardL,
@@ -13,12 +19,6 @@ pub enum TrackCurve {
     HardR,
 }
 
-impl Default for TrackCurve {
-    fn default() -> Self {
-        Self::Straight
-    }
-}
-
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
 enum TrackPath {
     Linear {
@@ -59,6 +59,96 @@ pub enum DitShape {
     Circle,
 }
 
+/// The number of game phases in which tiles are placed (yellow, green,
+/// brown, and grey).
+const NUM_PHASES: usize = 4;
+
+/// Phase-dependent revenue for a dit, where the revenue in each phase is
+/// identified by the colour of the tiles that become available in that
+/// phase.
+///
+/// # Examples
+///
+/// ```rust
+/// use n18hex::HexColour::*;
+/// use n18tile::PhaseRevenues;
+///
+/// let revenues = PhaseRevenues::new(&[(Green, 20), (Grey, 40)]);
+/// assert_eq!(revenues.revenue(Yellow), None);
+/// assert_eq!(revenues.revenue(Green), Some(20));
+/// assert_eq!(revenues.revenue(Brown), Some(20));
+/// assert_eq!(revenues.revenue(Grey), Some(40));
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PhaseRevenues {
+    amounts: [Option<usize>; NUM_PHASES],
+}
+
+impl PhaseRevenues {
+    /// Defines the revenue from the phase in which tiles of each colour
+    /// become available.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any colour is not part of the tile upgrade sequence (see
+    /// [HexColour::phase_number]).
+    /// Use [PhaseRevenues::try_new] to handle such colours without panicking.
+    pub fn new(revenues: &[(HexColour, usize)]) -> Self {
+        Self::try_new(revenues).unwrap_or_else(|colour| {
+            panic!("Invalid revenue phase: {:?}", colour)
+        })
+    }
+
+    /// Defines the revenue from the phase in which tiles of each colour
+    /// become available, and returns the first colour that is not part of
+    /// the tile upgrade sequence (see [HexColour::phase_number]) as an
+    /// error.
+    ///
+    /// ```rust
+    /// use n18hex::HexColour::*;
+    /// use n18tile::PhaseRevenues;
+    ///
+    /// assert!(PhaseRevenues::try_new(&[(Green, 20)]).is_ok());
+    /// assert_eq!(PhaseRevenues::try_new(&[(Red, 30)]), Err(Red));
+    /// ```
+    pub fn try_new(
+        revenues: &[(HexColour, usize)],
+    ) -> Result<Self, HexColour> {
+        let mut amounts = [None; NUM_PHASES];
+        for (colour, revenue) in revenues {
+            let ix = colour
+                .phase_number()
+                .filter(|&num| num > 0 && num <= NUM_PHASES)
+                .ok_or(*colour)?;
+            amounts[ix - 1] = Some(*revenue);
+        }
+        Ok(PhaseRevenues { amounts })
+    }
+
+    /// Returns whether no phase-dependent revenue has been defined.
+    pub fn is_empty(&self) -> bool {
+        self.amounts.iter().all(Option::is_none)
+    }
+
+    /// Returns the revenue for the game phase in which tiles of colour
+    /// `phase` are available, which is defined by the most recent phase
+    /// that has a revenue, or `None` if no such phase exists.
+    pub fn revenue(&self, phase: HexColour) -> Option<usize> {
+        let phase_num = phase.phase_number()?.min(NUM_PHASES);
+        self.amounts[..phase_num].iter().rev().find_map(|amt| *amt)
+    }
+
+    /// Returns the revenue that is defined for each phase, identified by the
+    /// colour of the tiles that become available in that phase.
+    pub fn iter(&self) -> impl Iterator<Item = (HexColour, usize)> + '_ {
+        let mut colour = HexColour::Empty;
+        self.amounts.iter().filter_map(move |amt| {
+            colour = colour.next_phase()?;
+            amt.map(|revenue| (colour, revenue))
+        })
+    }
+}
+
 /// Track segments along which trains can run routes.
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
 pub struct Track {
@@ -68,6 +158,9 @@ pub struct Track {
     pub x1: f64,
     pub clip: Option<(f64, f64)>,
     pub dit: Option<(TrackEnd, usize, DitShape)>,
+    /// The phase-dependent revenue of the dit, if any, which overrides the
+    /// dit revenue in each phase for which a revenue is defined.
+    pub dit_revenues: PhaseRevenues,
 }
 
 impl Default for Track {
@@ -79,6 +172,7 @@ impl Default for Track {
             x1: 1.0,
             clip: None,
             dit: None,
+            dit_revenues: PhaseRevenues::default(),
         }
     }
 }
@@ -100,6 +194,7 @@ impl Track {
             x1,
             clip,
             dit,
+            dit_revenues: PhaseRevenues::default(),
         }
     }
 
@@ -112,6 +207,7 @@ impl Track {
             x1: 1.0,
             clip: None,
             dit: None,
+            dit_revenues: PhaseRevenues::default(),
         }
     }
 
@@ -124,6 +220,7 @@ impl Track {
             x1: 0.5,
             clip: None,
             dit: None,
+            dit_revenues: PhaseRevenues::default(),
         }
     }
 
@@ -135,6 +232,7 @@ impl Track {
             x1: 1.0,
             clip: None,
             dit: None,
+            dit_revenues: PhaseRevenues::default(),
         }
     }
 
@@ -146,6 +244,7 @@ impl Track {
             x1: 1.0,
             clip: None,
             dit: None,
+            dit_revenues: PhaseRevenues::default(),
         }
     }
 
@@ -157,6 +256,7 @@ impl Track {
             x1: 1.0,
             clip: None,
             dit: None,
+            dit_revenues: PhaseRevenues::default(),
         }
     }
 
@@ -168,6 +268,7 @@ impl Track {
             x1: 1.0,
             clip: None,
             dit: None,
+            dit_revenues: PhaseRevenues::default(),
         }
     }
 
@@ -182,6 +283,37 @@ impl Track {
         self
     }
 
+    /// Adds a dit whose revenue depends on the current game phase, where
+    /// `revenue` is the revenue before the first phase in `revenues`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use n18hex::HexColour::*;
+    /// use n18hex::HexFace;
+    /// use n18tile::{DitShape, PhaseRevenues, Track, TrackEnd};
+    ///
+    /// let revenues = PhaseRevenues::new(&[(Brown, 20)]);
+    /// let track = Track::straight(HexFace::Top).with_phase_dit(
+    ///     TrackEnd::End,
+    ///     10,
+    ///     DitShape::Bar,
+    ///     revenues,
+    /// );
+    /// assert_eq!(track.dit_revenues.revenue(Brown), Some(20));
+    /// ```
+    pub fn with_phase_dit(
+        self,
+        end: TrackEnd,
+        revenue: usize,
+        shape: DitShape,
+        revenues: PhaseRevenues,
+    ) -> Self {
+        let mut track = self.with_dit(end, revenue, shape);
+        track.dit_revenues = revenues;
+        track
+    }
+
     pub fn with_clip(mut self, c0: f64, c1: f64) -> Self {
         // TODO: check c0 and c1.
         self.clip = Some((c0, c1));
@@ -269,7 +401,7 @@ impl Track {
                 let offset = hex.orientation().arc_offset();
                 let (angle_0, angle_1) = (angle_0 + offset, angle_1 + offset);
                 let centre = *hex.corner_coord(&corner);
-                let radius = hex.theme.track_hard_radius.absolute(hex);
+                let radius = hex.geometry().track_hard_radius.absolute(hex);
                 TrackPath::Curve {
                     centre,
                     radius,
@@ -301,7 +433,7 @@ impl Track {
                 let offset = hex.orientation().arc_offset();
                 let (angle_0, angle_1) = (angle_0 + offset, angle_1 + offset);
                 let centre = *hex.corner_coord(&corner);
-                let radius = hex.theme.track_hard_radius.absolute(hex);
+                let radius = hex.geometry().track_hard_radius.absolute(hex);
                 TrackPath::Curve {
                     centre,
                     radius,
@@ -340,7 +472,7 @@ impl Track {
                 // curve's start and end angles.
                 let offset = hex.orientation().arc_offset();
                 let (angle_0, angle_1) = (angle_0 + offset, angle_1 + offset);
-                let radius = hex.theme.track_gentle_radius.absolute(hex);
+                let radius = hex.geometry().track_gentle_radius.absolute(hex);
                 TrackPath::Curve {
                     centre,
                     radius,
@@ -378,7 +510,7 @@ impl Track {
                 // curve's start and end angles.
                 let offset = hex.orientation().arc_offset();
                 let (angle_0, angle_1) = (angle_0 + offset, angle_1 + offset);
-                let radius = hex.theme.track_gentle_radius.absolute(hex);
+                let radius = hex.geometry().track_gentle_radius.absolute(hex);
                 TrackPath::Curve {
                     centre,
                     radius,
@@ -392,6 +524,7 @@ impl Track {
         }
     }
 
+    #[cfg(feature = "cairo")]
     pub fn draw_circle_dit(&self, hex: &Hex, ctx: &Context) -> bool {
         if let Some((dit_end, _revenue, DitShape::Circle)) = self.dit {
             let dit_locn = self.end_coord(dit_end, hex);
@@ -410,6 +543,7 @@ impl Track {
         }
     }
 
+    #[cfg(feature = "cairo")]
     pub fn define_circle_dit(&self, hex: &Hex, ctx: &Context) -> bool {
         if let Some((dit_end, _revenue, DitShape::Circle)) = self.dit {
             let dit_locn = self.end_coord(dit_end, hex);
@@ -423,14 +557,17 @@ impl Track {
         }
     }
 
+    #[cfg(feature = "cairo")]
     pub fn draw_dit_ends_fg(&self, hex: &Hex, ctx: &Context) {
         self.draw_dit_ends(hex.theme.dit_inner_length, hex, ctx)
     }
 
+    #[cfg(feature = "cairo")]
     pub fn draw_dit_ends_bg(&self, hex: &Hex, ctx: &Context) {
         self.draw_dit_ends(hex.theme.dit_outer_length, hex, ctx)
     }
 
+    #[cfg(feature = "cairo")]
     fn draw_dit_ends(&self, length: Length, hex: &Hex, ctx: &Context) {
         use TrackPath::*;
 
@@ -531,6 +668,7 @@ impl Track {
     /// line and provide a complete border around the dit boundary, as long as
     /// [n18hex::Theme::dit_outer_length] is larger than
     /// [n18hex::Theme::dit_inner_length].
+    #[cfg(feature = "cairo")]
     pub fn define_path(&self, hex: &Hex, ctx: &Context) {
         use TrackPath::*;
 
@@ -645,7 +783,7 @@ impl Track {
         }
     }
 
-    pub fn coords(&self, hex: &Hex, dt: f64) -> TrackCoords {
+    pub fn coords(&self, hex: &Hex, dt: f64) -> TrackCoords<'_> {
         // TODO: test cases, ensure that all coords are in the stroke!
         // Also check that all strokes are within hex!
         TrackCoords::new(self, hex, dt)
@@ -737,50 +875,6 @@ impl Track {
         }
     }
 
-    pub fn intersects_fill<D: Draw>(
-        &self,
-        obj: &D,
-        hex: &Hex,
-        dt: f64,
-        ctx: &Context,
-    ) -> bool {
-        obj.define_boundary(hex, ctx);
-        self.coords(hex, dt).any(|c| ctx.in_fill(c.x, c.y).unwrap())
-    }
-
-    pub fn connected_to_fill<D: Draw>(
-        &self,
-        obj: &D,
-        hex: &Hex,
-        ctx: &Context,
-    ) -> bool {
-        obj.define_boundary(hex, ctx);
-        let start = self.start(hex);
-        let end = self.end(hex);
-        ctx.in_fill(start.x, start.y).unwrap()
-            || ctx.in_fill(end.x, end.y).unwrap()
-    }
-
-    pub fn connected(&self, other: &Self, hex: &Hex, ctx: &Context) -> bool {
-        // NOTE: in_stroke() isn't sufficient here, we need to check whether
-        // the track *ends* meet.
-        // Mind you, we could check if the start/end of *each* track is in the
-        // stroke of the other's boundary?
-        other.define_boundary(hex, ctx);
-        let c0 = self.start(hex);
-        let c1 = self.end(hex);
-        let self_in = ctx.in_stroke(c0.x, c0.y).unwrap()
-            || ctx.in_stroke(c1.x, c1.y).unwrap();
-
-        self.define_boundary(hex, ctx);
-        let c0 = other.start(hex);
-        let c1 = other.end(hex);
-        let other_in = ctx.in_stroke(c0.x, c0.y).unwrap()
-            || ctx.in_stroke(c1.x, c1.y).unwrap();
-
-        self_in && other_in
-    }
-
     pub fn connected_to_faces(&self) -> Vec<(TrackEnd, HexFace)> {
         use TrackCurve::*;
 
@@ -801,17 +895,155 @@ impl Track {
         faces
     }
 
-    pub fn connected_to_fill_at<D: Draw>(
+    /// Returns the pieces of the path that is defined by `define_path`, so
+    /// that they can be tested without a drawing context.
+    fn stroke_pieces(&self, hex: &Hex) -> Vec<Piece> {
+        use TrackPath::*;
+
+        let mut pieces = vec![];
+        let dit_width = hex.geometry().dit_length.absolute(hex);
+        match self.describe_path(hex) {
+            Linear { start, end } => {
+                let c0 = start.interpolate(&end, self.x0);
+                let c1 = start.interpolate(&end, self.x1);
+                if let Some((t0, t1)) = self.clip {
+                    let c0_end = start.interpolate(&end, t0);
+                    let c1_start = start.interpolate(&end, t1);
+                    pieces.push(Piece::Line {
+                        start: c0,
+                        end: c0_end,
+                    });
+                    pieces.push(Piece::Line {
+                        start: c1_start,
+                        end: c1,
+                    });
+                } else {
+                    pieces.push(Piece::Line { start: c0, end: c1 });
+                }
+                if let Some((dit_end, _revenue, DitShape::Bar)) = self.dit {
+                    let dit_locn = self.end_coord(dit_end, hex);
+                    let dit_dirn = Coord::unit_normal(&start, &end);
+                    let dit_dirn = &dit_dirn * dit_width;
+                    pieces.push(Piece::Line {
+                        start: &dit_locn - &dit_dirn,
+                        end: &dit_locn + &dit_dirn,
+                    });
+                }
+            }
+            Curve {
+                centre,
+                radius,
+                angle_0,
+                angle_1,
+                clockwise,
+            } => {
+                let (x0, x1) = if clockwise {
+                    (self.x0, self.x1)
+                } else {
+                    (1.0 - self.x0, 1.0 - self.x1)
+                };
+                let a0 = angle_0 + x0 * (angle_1 - angle_0);
+                let a1 = angle_0 + x1 * (angle_1 - angle_0);
+                let arc = |angle_0, angle_1| Piece::Arc {
+                    centre,
+                    radius,
+                    angle_0,
+                    angle_1,
+                    negative: !clockwise,
+                };
+                if let Some((t0, t1)) = self.clip {
+                    let a0_end = angle_0 + t0 * (angle_1 - angle_0);
+                    let a1_start = angle_0 + t1 * (angle_1 - angle_0);
+                    let first = arc(a0, a0_end);
+                    let second = arc(a1_start, a1);
+                    // NOTE: both arcs belong to the same sub-path, and so
+                    // they are connected by a straight line.
+                    pieces.push(first);
+                    pieces.push(Piece::Join { at: first.end() });
+                    pieces.push(Piece::Line {
+                        start: first.end(),
+                        end: second.start(),
+                    });
+                    pieces.push(Piece::Join { at: second.start() });
+                    pieces.push(second);
+                } else {
+                    pieces.push(arc(a0, a1));
+                }
+                if let Some((dit_end, _revenue, DitShape::Bar)) = self.dit {
+                    let angle = match dit_end {
+                        TrackEnd::Start => a0,
+                        TrackEnd::End => a1,
+                    };
+                    let dit_dx = radius * angle.cos();
+                    let dit_dy = radius * angle.sin();
+                    let dit_dirn = Coord {
+                        x: dit_dx,
+                        y: dit_dy,
+                    }
+                    .normalise();
+                    let dit_dirn = &dit_dirn * dit_width;
+                    let dit_mid = Coord {
+                        x: centre.x + dit_dx,
+                        y: centre.y + dit_dy,
+                    };
+                    pieces.push(Piece::Line {
+                        start: &dit_mid - &dit_dirn,
+                        end: &dit_mid + &dit_dirn,
+                    });
+                }
+            }
+        }
+        pieces
+    }
+
+    /// Returns `true` if `coord` lies inside the outer (background) stroke
+    /// of this track segment.
+    pub fn stroke_contains(&self, hex: &Hex, coord: &Coord) -> bool {
+        let width = hex.geometry().track_width.line_width(hex);
+        self.stroke_pieces(hex)
+            .iter()
+            .any(|piece| piece.stroke_contains(width, coord))
+    }
+
+    /// Returns `true` if any point along this track segment lies inside the
+    /// boundary of `city`.
+    pub fn intersects_fill(&self, city: &City, hex: &Hex, dt: f64) -> bool {
+        self.coords(hex, dt).any(|c| city.fill_contains(hex, &c))
+    }
+
+    /// Returns `true` if either end of this track segment lies inside the
+    /// boundary of `city`.
+    pub fn connected_to_fill(&self, city: &City, hex: &Hex) -> bool {
+        city.fill_contains(hex, &self.start(hex))
+            || city.fill_contains(hex, &self.end(hex))
+    }
+
+    /// Returns `true` if an end of each track segment lies inside the stroke
+    /// of the other track segment.
+    pub fn connected(&self, other: &Self, hex: &Hex) -> bool {
+        // NOTE: checking whether the tracks intersect isn't sufficient here,
+        // we need to check whether the track *ends* meet.
+        let self_in = other.stroke_contains(hex, &self.start(hex))
+            || other.stroke_contains(hex, &self.end(hex));
+        let other_in = self.stroke_contains(hex, &other.start(hex))
+            || self.stroke_contains(hex, &other.end(hex));
+        self_in && other_in
+    }
+
+    /// Returns the end of this track segment that lies inside the boundary
+    /// of `city`, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if both ends of this track segment lie inside the boundary of
+    /// `city`.
+    pub fn connected_to_fill_at(
         &self,
-        obj: &D,
+        city: &City,
         hex: &Hex,
-        ctx: &Context,
     ) -> Option<TrackEnd> {
-        obj.define_boundary(hex, ctx);
-        let start = self.start(hex);
-        let end = self.end(hex);
-        let conn_start = ctx.in_fill(start.x, start.y).unwrap();
-        let conn_end = ctx.in_fill(end.x, end.y).unwrap();
+        let conn_start = city.fill_contains(hex, &self.start(hex));
+        let conn_end = city.fill_contains(hex, &self.end(hex));
         if conn_start && conn_end {
             panic!("Track connects at both ends")
         } else if conn_start {
@@ -823,20 +1055,22 @@ impl Track {
         }
     }
 
+    /// Returns the ends at which this track segment and `other` meet, if
+    /// any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if both ends of either track segment lie inside the stroke of
+    /// the other track segment.
     pub fn connected_at(
         &self,
         other: &Self,
         hex: &Hex,
-        ctx: &Context,
     ) -> Option<(TrackEnd, TrackEnd)> {
         use TrackEnd::*;
 
-        other.define_boundary(hex, ctx);
-        let c0 = self.start(hex);
-        let c1 = self.end(hex);
-        let self_c0 = ctx.in_stroke(c0.x, c0.y).unwrap();
-        let self_c1 = ctx.in_stroke(c1.x, c1.y).unwrap();
-
+        let self_c0 = other.stroke_contains(hex, &self.start(hex));
+        let self_c1 = other.stroke_contains(hex, &self.end(hex));
         let self_conn = if self_c0 && self_c1 {
             panic!("Tracks connected at both ends")
         } else if self_c0 {
@@ -847,12 +1081,8 @@ impl Track {
             None
         };
 
-        self.define_boundary(hex, ctx);
-        let c0 = other.start(hex);
-        let c1 = other.end(hex);
-        let other_c0 = ctx.in_stroke(c0.x, c0.y).unwrap();
-        let other_c1 = ctx.in_stroke(c1.x, c1.y).unwrap();
-
+        let other_c0 = self.stroke_contains(hex, &other.start(hex));
+        let other_c1 = self.stroke_contains(hex, &other.end(hex));
         let other_conn = if other_c0 && other_c1 {
             panic!("Tracks connected at both ends")
         } else if other_c0 {
@@ -864,29 +1094,18 @@ impl Track {
         };
 
         match (self_conn, other_conn) {
-            (None, None) => None,
             (Some(end_1), Some(end_2)) => Some((end_1, end_2)),
-            _ => {
-                // println!("{:?} and {:?}", self_conn, other_conn);
-                // panic!("Inconsistent track connections")
-                None
-            }
+            _ => None,
         }
     }
 
-    pub fn crosses(
-        &self,
-        other: &Self,
-        hex: &Hex,
-        dt: f64,
-        ctx: &Context,
-    ) -> bool {
-        if self.connected(other, hex, ctx) {
+    /// Returns `true` if this track segment crosses `other`, sampling points
+    /// along this track segment at intervals of `dt`.
+    pub fn crosses(&self, other: &Self, hex: &Hex, dt: f64) -> bool {
+        if self.connected(other, hex) {
             return false;
         }
-        other.define_boundary(hex, ctx);
-        self.coords(hex, dt)
-            .any(|c| ctx.in_stroke(c.x, c.y).unwrap())
+        self.coords(hex, dt).any(|c| other.stroke_contains(hex, &c))
     }
 }
 
@@ -966,6 +1185,7 @@ impl<'a> Iterator for TrackCoords<'a> {
     }
 }
 
+#[cfg(feature = "cairo")]
 impl Draw for Track {
     fn define_boundary(&self, hex: &Hex, ctx: &Context) {
         self.define_path(hex, ctx);
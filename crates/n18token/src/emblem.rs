@@ -0,0 +1,206 @@
+//! A registry of images that can be drawn inside tokens, such as company
+//! logos.
+//!
+//! Each emblem is identified by a unique name, and tokens refer to emblems by
+//! [Emblem] handles, so that tokens remain cheap to copy and compare.
+//! An emblem name can be used before its image has been loaded, in which
+//! case tokens that refer to this emblem are drawn without the image.
+//!
+//! Emblems are ordered by name, rather than by the order in which they were
+//! added to the registry, so that tokens are sorted consistently:
+//!
+//! ```rust
+//! # use n18token::Emblem;
+//! let second = Emblem::named("doc-emblem-b");
+//! let first = Emblem::named("doc-emblem-a");
+//! assert!(first < second);
+//! assert_eq!(Emblem::find("doc-emblem-b"), Some(second));
+//! ```
+//!
+//! SVG images are not supported, because Cairo can only read PNG images.
+//! To use an SVG image as an emblem, render it to a [cairo::ImageSurface]
+//! (e.g., with [librsvg](https://crates.io/crates/librsvg)) and pass this
+//! surface to [Emblem::register].
+
+#[cfg(feature = "cairo")]
+use cairo::{Context, Format, ImageSurface};
+#[cfg(feature = "cairo")]
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// A handle to an image in the emblem registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Emblem {
+    ix: usize,
+}
+
+impl PartialOrd for Emblem {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Emblems are ordered by name.
+impl Ord for Emblem {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // NOTE: each name is only added to the registry once, so emblems
+        // with the same index also have the same name.
+        if self.ix == other.ix {
+            return std::cmp::Ordering::Equal;
+        }
+        let registry = REGISTRY.lock().unwrap();
+        registry[self.ix].name.cmp(&registry[other.ix].name)
+    }
+}
+
+/// The pixel data for an emblem image, stored in [Format::ARgb32] format.
+///
+/// We store the pixel data rather than a [cairo::ImageSurface], because
+/// Cairo surfaces cannot be shared between threads.
+///
+/// Images can only be loaded when the `cairo` feature is enabled.
+#[cfg_attr(not(feature = "cairo"), allow(dead_code))]
+struct Image {
+    width: i32,
+    height: i32,
+    stride: i32,
+    data: Arc<Vec<u8>>,
+}
+
+struct Entry {
+    name: String,
+    image: Option<Image>,
+}
+
+static REGISTRY: Mutex<Vec<Entry>> = Mutex::new(Vec::new());
+
+impl Emblem {
+    /// Returns the emblem with the given name, adding it to the registry if
+    /// necessary.
+    pub fn named(name: &str) -> Self {
+        let mut registry = REGISTRY.lock().unwrap();
+        let ix = registry
+            .iter()
+            .position(|entry| entry.name == name)
+            .unwrap_or_else(|| {
+                registry.push(Entry {
+                    name: name.to_string(),
+                    image: None,
+                });
+                registry.len() - 1
+            });
+        Emblem { ix }
+    }
+
+    /// Returns the emblem with the given name, if it is in the registry.
+    pub fn find(name: &str) -> Option<Self> {
+        REGISTRY
+            .lock()
+            .unwrap()
+            .iter()
+            .position(|entry| entry.name == name)
+            .map(|ix| Emblem { ix })
+    }
+
+    /// Uses the contents of `surface` as the image for the emblem with the
+    /// given name, replacing any existing image.
+    #[cfg(feature = "cairo")]
+    pub fn register(
+        name: &str,
+        surface: &ImageSurface,
+    ) -> Result<Self, cairo::Error> {
+        // Copy the surface contents into a new surface, to ensure that the
+        // pixel data has the expected format.
+        let (width, height) = (surface.width(), surface.height());
+        let mut copy = ImageSurface::create(Format::ARgb32, width, height)?;
+        {
+            let ctx = Context::new(&copy)?;
+            ctx.set_source_surface(surface, 0.0, 0.0)?;
+            ctx.paint()?;
+        }
+        copy.flush();
+        let stride = copy.stride();
+        let data = copy
+            .data()
+            .map_err(|_| cairo::Error::SurfaceFinished)?
+            .to_vec();
+        let image = Image {
+            width,
+            height,
+            stride,
+            data: Arc::new(data),
+        };
+        let emblem = Self::named(name);
+        REGISTRY.lock().unwrap()[emblem.ix].image = Some(image);
+        Ok(emblem)
+    }
+
+    /// Loads a PNG image and uses it as the image for the emblem with the
+    /// given name, replacing any existing image.
+    ///
+    /// SVG images are not supported (see [Emblem::register]).
+    #[cfg(feature = "cairo")]
+    pub fn load_png<P: AsRef<Path>>(
+        name: &str,
+        path: P,
+    ) -> Result<Self, cairo::IoError> {
+        let mut file = std::fs::File::open(path)?;
+        let surface = ImageSurface::create_from_png(&mut file)?;
+        let emblem = Self::register(name, &surface)?;
+        Ok(emblem)
+    }
+
+    /// Returns the name of this emblem.
+    pub fn name(&self) -> String {
+        REGISTRY.lock().unwrap()[self.ix].name.clone()
+    }
+
+    /// Returns `true` if an image has been loaded for this emblem.
+    pub fn has_image(&self) -> bool {
+        REGISTRY.lock().unwrap()[self.ix].image.is_some()
+    }
+
+    /// Returns a new surface that contains the image for this emblem, if an
+    /// image has been loaded.
+    #[cfg(feature = "cairo")]
+    fn surface(&self) -> Option<ImageSurface> {
+        let (width, height, stride, data) = {
+            let registry = REGISTRY.lock().unwrap();
+            let image = registry[self.ix].image.as_ref()?;
+            (image.width, image.height, image.stride, image.data.clone())
+        };
+        ImageSurface::create_for_data(
+            data.to_vec(),
+            Format::ARgb32,
+            width,
+            height,
+            stride,
+        )
+        .ok()
+    }
+
+    /// Draws the emblem image so that it is centred on the origin and fits
+    /// within a circle of the given radius.
+    ///
+    /// If no image has been loaded for this emblem, nothing is drawn.
+    #[cfg(feature = "cairo")]
+    pub(crate) fn draw(&self, ctx: &Context, radius: f64) {
+        let surface = match self.surface() {
+            Some(surface) => surface,
+            None => return,
+        };
+        let (width, height) =
+            (surface.width() as f64, surface.height() as f64);
+        if width <= 0.0 || height <= 0.0 {
+            return;
+        }
+        // Scale the image so that its corners lie on the circle.
+        let scale = 2.0 * radius / (width * width + height * height).sqrt();
+        ctx.save().unwrap();
+        ctx.scale(scale, scale);
+        ctx.set_source_surface(&surface, -0.5 * width, -0.5 * height)
+            .unwrap();
+        ctx.paint().unwrap();
+        ctx.restore().unwrap();
+    }
+}
@@ -1,6 +1,14 @@
+#[cfg(feature = "cairo")]
 use cairo::Context;
+#[cfg(feature = "cairo")]
 use n18hex::consts::*;
-use n18hex::{Colour, Hex};
+use n18hex::Colour;
+#[cfg(feature = "cairo")]
+use n18hex::Hex;
+
+mod emblem;
+
+pub use emblem::Emblem;
 
 /// The collection of tokens associated with each company.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -76,6 +84,18 @@ impl Tokens {
             .map(|ix| self.tokens[ix])
     }
 
+    /// Adds a token with the given name, and returns `true` if neither the
+    /// name nor the token were already defined.
+    pub fn add(&mut self, name: String, token: Token) -> bool {
+        if self.names.contains(&name) || self.tokens.contains(&token) {
+            return false;
+        }
+        self.names.push(name);
+        self.tokens.push(token);
+        self.count += 1;
+        true
+    }
+
     pub fn token(&self, name: &str) -> Option<&Token> {
         self.names
             .iter()
@@ -93,6 +113,67 @@ impl Tokens {
     }
 }
 
+/// The lines of text and the font size with which a token label is drawn,
+/// so that it fits inside the token (see [Token::fit_text]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenText {
+    /// The lines of text.
+    pub lines: Vec<String>,
+    /// The font size (in points).
+    pub font_size: f64,
+    /// Whether the text fits inside the token; text that does not fit is
+    /// drawn at the minimum font size.
+    pub fits: bool,
+}
+
+/// Returns the ways in which `text` can be split over two lines.
+///
+/// Text is split at spaces (which are removed), after ampersands, hyphens,
+/// and slashes, and in half.
+#[cfg(feature = "cairo")]
+fn two_line_splits(text: &str) -> Vec<Vec<String>> {
+    let mut splits: Vec<Vec<String>> = text
+        .char_indices()
+        .filter_map(|(ix, c)| match c {
+            ' ' => Some((&text[..ix], &text[ix + 1..])),
+            '&' | '-' | '/' => {
+                let at = ix + c.len_utf8();
+                Some((&text[..at], &text[at..]))
+            }
+            _ => None,
+        })
+        .filter(|(first, second)| {
+            !first.trim().is_empty() && !second.trim().is_empty()
+        })
+        .map(|(first, second)| {
+            vec![first.trim().to_string(), second.trim().to_string()]
+        })
+        .collect();
+    let char_count = text.chars().count();
+    if char_count > 1 {
+        let at = text
+            .char_indices()
+            .nth(char_count.div_ceil(2))
+            .map(|(ix, _c)| ix)
+            .unwrap_or(text.len());
+        let halves = vec![
+            text[..at].trim().to_string(),
+            text[at..].trim().to_string(),
+        ];
+        if !splits.contains(&halves) {
+            splits.push(halves);
+        }
+    }
+    splits
+}
+
+/// Ensures that the font size is no smaller than `min_font_size`.
+#[cfg(feature = "cairo")]
+fn clamp_font_size(mut text: TokenText, min_font_size: f64) -> TokenText {
+    text.font_size = text.font_size.max(min_font_size);
+    text
+}
+
 /// A token that may occupy a token space on a `Tile`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Token {
@@ -158,9 +239,16 @@ pub enum TokenStyle {
         bottom: Colour,
         text: Colour,
     },
+    /// Draws an image, such as a company logo, over a solid background.
+    Emblem {
+        bg: Colour,
+        emblem: Emblem,
+        text: Colour,
+    },
 }
 
 impl TokenStyle {
+    #[cfg(feature = "cairo")]
     fn draw_background(&self, hex: &Hex, ctx: &Context) {
         use TokenStyle::*;
 
@@ -369,6 +457,35 @@ impl TokenStyle {
                 bottom.apply_colour(ctx);
                 ctx.fill().unwrap();
             }
+            Emblem { bg, emblem, .. } => {
+                bg.apply_colour(ctx);
+                ctx.fill_preserve().unwrap();
+                ctx.clip_preserve();
+                ctx.new_path();
+                emblem.draw(ctx, 0.9 * radius);
+            }
+        }
+    }
+
+    /// Returns the dominant colour of the token background.
+    ///
+    /// For tokens with bands of colour, this is the colour of the outer
+    /// bands; for tokens with three colours, this is the middle colour.
+    pub fn background(&self) -> &Colour {
+        use TokenStyle::*;
+
+        match self {
+            SideArcs { bg, .. } => bg,
+            TopArcs { bg, .. } => bg,
+            TopSquares { bg, .. } => bg,
+            TopLines { bg, .. } => bg,
+            TopTriangles { bg, .. } => bg,
+            TripleTriangles { bg, .. } => bg,
+            TribandV { sides, .. } => sides,
+            TribandH { sides, .. } => sides,
+            TricolourV { middle, .. } => middle,
+            TricolourH { middle, .. } => middle,
+            Emblem { bg, .. } => bg,
         }
     }
 
@@ -386,6 +503,7 @@ impl TokenStyle {
             TribandH { text, .. } => text,
             TricolourV { text, .. } => text,
             TricolourH { text, .. } => text,
+            Emblem { text, .. } => text,
         }
     }
 }
@@ -405,9 +523,79 @@ impl Token {
         self
     }
 
+    /// Returns the lines of text and the font size with which `text` should
+    /// be drawn, so that it fits inside the token.
+    ///
+    /// The text is shrunk as necessary, but no smaller than the theme's
+    /// minimum font size for token labels.
+    /// If the theme allows it, the text is split over two lines when this
+    /// allows a larger font size than a single line.
+    /// Text that contains line breaks is always drawn on multiple lines.
+    #[cfg(feature = "cairo")]
+    pub fn fit_text(
+        &self,
+        hex: &Hex,
+        ctx: &Context,
+        text: &str,
+    ) -> TokenText {
+        let theme = &hex.theme;
+        let font_size = theme.token_label.get_font_size();
+        let min_font_size = theme.token_label_min_font_size.min(font_size);
+        let radius = theme.token_space_radius.absolute(hex)
+            - 0.5 * theme.token_space_inner.line_width(hex);
+        let mut style = theme.token_label;
+        style.no_max_width();
+        let labeller = style.labeller(ctx, hex);
+
+        // Returns the largest font size at which the lines of text fit
+        // inside the token, noting that text width and height are
+        // proportional to the font size.
+        let fit = |lines: Vec<String>| {
+            let size = labeller.size(&lines.join("\n"));
+            let half_diagonal = 0.5 * size.width.hypot(size.height);
+            let scale = if half_diagonal > 0.0 {
+                (radius / half_diagonal).min(1.0)
+            } else {
+                1.0
+            };
+            TokenText {
+                lines,
+                font_size: font_size * scale,
+                fits: font_size * scale >= min_font_size,
+            }
+        };
+
+        if text.contains('\n') {
+            let lines = text.lines().map(|line| line.to_string()).collect();
+            return clamp_font_size(fit(lines), min_font_size);
+        }
+        let single_line = fit(vec![text.to_string()]);
+        if !theme.token_label_two_lines {
+            return clamp_font_size(single_line, min_font_size);
+        }
+        // NOTE: prefer a single line, and then the earliest split, when
+        // they allow the same font size, so that separators are preferred.
+        let best = two_line_splits(text).into_iter().map(fit).fold(
+            single_line,
+            |best, two_lines| {
+                if two_lines.font_size > best.font_size {
+                    two_lines
+                } else {
+                    best
+                }
+            },
+        );
+        clamp_font_size(best, min_font_size)
+    }
+
+    #[cfg(feature = "cairo")]
     fn draw_text(&self, hex: &Hex, ctx: &Context, text: &str) {
-        // Draw the token text using the appropriate theme settings.
-        let mut labeller = hex.theme.token_label.labeller(ctx, hex);
+        // Draw the token text using the appropriate theme settings, at a
+        // font size that fits inside the token.
+        let token_text = self.fit_text(hex, ctx, text);
+        let mut style = hex.theme.token_label;
+        style.no_max_width().font_size(token_text.font_size);
+        let mut labeller = style.labeller(ctx, hex);
 
         // Ensure the text is centred and has the desired colour.
         labeller.halign(n18hex::theme::AlignH::Centre);
@@ -422,12 +610,13 @@ impl Token {
         let dy = radius * ((self.y_pcnt as f64 - 50.0) / 50.0);
         let text_centre = n18hex::Coord::from((x + dx, y + dy));
 
-        labeller.draw(text, text_centre);
+        labeller.draw(&token_text.lines.join("\n"), text_centre);
     }
 
     /// Draws the token so that it fills the current path.
     ///
     /// Define the token boundary before calling this function.
+    #[cfg(feature = "cairo")]
     pub fn draw(&self, hex: &Hex, ctx: &Context, text: &str, rotn: f64) {
         // Locate the centre of the token.
         let (x0, y0, x1, y1) = ctx.fill_extents().unwrap();
@@ -0,0 +1,176 @@
+//! Draws a scanned map image beneath the map, so that map authors can trace
+//! real boards.
+//!
+//! A [Background] is loaded from a PNG file (e.g., by dropping the file onto
+//! the window) and is drawn beneath the hex grid, which is drawn with the
+//! [map opacity](BackgroundSettings::map_opacity) so that the image remains
+//! visible.
+//! Its [settings](BackgroundSettings) are saved in the user interface
+//! [Session](crate::session::Session), and the image can be moved and scaled
+//! until the hexes on the image line up with the map hexes.
+//!
+//! The background image is only drawn in the window; it is never included in
+//! exported map images, saved or copied map regions, or game bundles.
+//! Note that screenshots contain the map as it is shown in the window, and
+//! so they include the background image if it is shown.
+
+use cairo::{Context, ImageSurface};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use n18hex::Hex;
+use n18io::Error;
+
+/// The distance (in pixels, when hexes are drawn with their default
+/// diameter) that the background image moves with each step.
+pub const OFFSET_STEP: f64 = 2.0;
+
+/// The factor by which the background image grows or shrinks with each
+/// step.
+pub const SCALE_STEP: f64 = 1.01;
+
+/// The settings that define where and how the background image is drawn.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackgroundSettings {
+    /// The PNG image file.
+    pub path: PathBuf,
+    /// The size of each image pixel, when hexes are drawn with their default
+    /// diameter.
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    /// The position of the image's top-left corner, when hexes are drawn
+    /// with their default diameter.
+    #[serde(default)]
+    pub offset: (f64, f64),
+    /// The opacity of the map that is drawn over the image, from `0.0`
+    /// (transparent) to `1.0` (opaque).
+    #[serde(default = "default_map_opacity")]
+    pub map_opacity: f64,
+    /// Whether the image is shown.
+    #[serde(default = "default_visible")]
+    pub visible: bool,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+fn default_map_opacity() -> f64 {
+    0.5
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+impl BackgroundSettings {
+    /// Returns the default settings for the PNG image file `path`.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        BackgroundSettings {
+            path: path.as_ref().to_path_buf(),
+            scale: default_scale(),
+            offset: (0.0, 0.0),
+            map_opacity: default_map_opacity(),
+            visible: default_visible(),
+        }
+    }
+}
+
+/// A background image and the settings that define how it is drawn.
+pub struct Background {
+    settings: BackgroundSettings,
+    image: ImageSurface,
+}
+
+impl Background {
+    /// Loads the PNG image file defined by `settings`.
+    pub fn load(settings: BackgroundSettings) -> Result<Self, Error> {
+        let path = &settings.path;
+        let mut file =
+            std::fs::File::open(path).map_err(|source| Error::Io {
+                path: Some(path.to_path_buf()),
+                source,
+            })?;
+        let image = ImageSurface::create_from_png(&mut file).map_err(
+            |e| match e {
+                cairo::IoError::Io(source) => Error::Io {
+                    path: Some(path.to_path_buf()),
+                    source,
+                },
+                cairo::IoError::Cairo(source) => Error::Image {
+                    path: path.to_path_buf(),
+                    source,
+                },
+            },
+        )?;
+        Ok(Background { settings, image })
+    }
+
+    /// Returns the settings that define how this image is drawn.
+    pub fn settings(&self) -> &BackgroundSettings {
+        &self.settings
+    }
+
+    /// Returns the width and height of the image, in pixels.
+    pub fn image_size(&self) -> (i32, i32) {
+        (self.image.width(), self.image.height())
+    }
+
+    /// Returns `true` if the image is shown.
+    pub fn is_visible(&self) -> bool {
+        self.settings.visible
+    }
+
+    /// Shows or hides the image.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.settings.visible = visible
+    }
+
+    /// Moves the image by `(dx, dy)`, in pixels when hexes are drawn with
+    /// their default diameter.
+    pub fn translate(&mut self, dx: f64, dy: f64) {
+        self.settings.offset.0 += dx;
+        self.settings.offset.1 += dy;
+    }
+
+    /// Multiplies the size of the image by `factor`, which must be
+    /// positive.
+    pub fn rescale(&mut self, factor: f64) {
+        if factor > 0.0 {
+            self.settings.scale *= factor;
+        }
+    }
+
+    /// Draws the image, scaled and positioned relative to the current hex
+    /// diameter.
+    pub fn draw(&self, hex: &Hex, ctx: &Context) {
+        let zoom = hex.max_d / Hex::default().max_d;
+        let (x, y) = self.settings.offset;
+        let scale = zoom * self.settings.scale;
+        let m = ctx.matrix();
+        ctx.translate(zoom * x, zoom * y);
+        ctx.scale(scale, scale);
+        ctx.set_source_surface(&self.image, 0.0, 0.0).unwrap();
+        ctx.paint().unwrap();
+        ctx.set_matrix(m);
+    }
+
+    /// Draws the image, and then calls `draw_map` to draw the map over the
+    /// image with the chosen [map opacity](BackgroundSettings::map_opacity).
+    ///
+    /// If the image is hidden, this only calls `draw_map`.
+    pub fn draw_beneath<F>(&self, hex: &Hex, ctx: &Context, draw_map: F)
+    where
+        F: FnOnce(&Context),
+    {
+        if !self.settings.visible {
+            draw_map(ctx);
+            return;
+        }
+        self.draw(hex, ctx);
+        ctx.push_group();
+        draw_map(ctx);
+        ctx.pop_group_to_source().unwrap();
+        ctx.paint_with_alpha(self.settings.map_opacity).unwrap();
+    }
+}
@@ -1,9 +1,11 @@
 use log::info;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::rc::Rc;
 use std::sync::RwLock;
 
 use crate::{Assets, State};
-use n18hex::Hex;
+use n18hex::{Colour, Hex};
 
 /// Returns the ink bounding box `(x0, y0, width, height)` for the provided
 /// state.
@@ -33,7 +35,15 @@ pub fn ink_extents_with_hex(
 }
 
 /// Returns the surface dimensions required to draw the provided state.
+///
+/// For states that draw the game map, these are the dimensions of the map
+/// as it is shown in the window (see [Assets::view_size]), so that the
+/// surface does not depend on what is drawn over the map.
 pub fn required_dims(state: &State, assets: &Assets) -> (i32, i32) {
+    if state.as_start().is_none() {
+        let (width, height) = assets.view_size();
+        return (width.ceil() as i32, height.ceil() as i32);
+    }
     let exts = ink_extents(state, assets);
     let want_width = (exts.2 + 2.0 * exts.0) as i32;
     let want_height = (exts.3 + 2.0 * exts.1) as i32;
@@ -48,27 +58,551 @@ pub fn max_surface_dims(
     hex_d: f64,
 ) -> (i32, i32) {
     // NOTE: this is the upper limit on the maximum hex size.
+    if state.as_start().is_none() {
+        let (width, height) = assets.map.drawing_size(&Hex::new(hex_d));
+        let (width, height) = assets.view().size(width, height);
+        return (width.ceil() as i32, height.ceil() as i32);
+    }
     let exts = ink_extents_with_hex(state, assets, hex_d);
     let want_width = (exts.2 + 2.0 * exts.0) as i32;
     let want_height = (exts.3 + 2.0 * exts.1) as i32;
     (want_width, want_height)
 }
 
+/// A rectangular region of the drawing surface, and the scale at which it
+/// should be exported as an image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Region {
+    /// The left edge of the region, in surface coordinates.
+    pub x: f64,
+    /// The top edge of the region, in surface coordinates.
+    pub y: f64,
+    /// The width of the region, in surface coordinates.
+    pub width: f64,
+    /// The height of the region, in surface coordinates.
+    pub height: f64,
+    /// The scale factor to apply when exporting the region.
+    pub scale: f64,
+}
+
+impl Region {
+    /// Returns the region defined by two opposite corners
+    /// `(x0, y0, x1, y1)`, such as those of [ButtonDrag::rect].
+    ///
+    /// [ButtonDrag::rect]: crate::ButtonDrag::rect
+    pub fn from_corners(corners: (f64, f64, f64, f64), scale: f64) -> Self {
+        let (x0, y0, x1, y1) = corners;
+        Region {
+            x: x0.min(x1),
+            y: y0.min(y1),
+            width: (x1 - x0).abs(),
+            height: (y1 - y0).abs(),
+            scale,
+        }
+    }
+
+    /// Returns the dimensions of the exported image, in pixels.
+    pub fn output_dims(&self) -> (i32, i32) {
+        let width = (self.width * self.scale).ceil().max(1.0) as i32;
+        let height = (self.height * self.scale).ceil().max(1.0) as i32;
+        (width, height)
+    }
+
+    /// Transforms `ctx` so that the top-left corner of this region is drawn
+    /// at the origin, at the chosen scale, and clips drawing to this region.
+    pub fn apply(&self, ctx: &cairo::Context) {
+        ctx.scale(self.scale, self.scale);
+        ctx.translate(-self.x, -self.y);
+        ctx.rectangle(self.x, self.y, self.width, self.height);
+        ctx.clip();
+    }
+}
+
+/// Draws a region of the provided state onto a new image surface, rather
+/// than copying the contents of the existing drawing surface, so that the
+/// region can be exported at any scale.
+///
+/// The `draw_fn` closure should draw the state onto the provided context.
+pub fn region_to_image<F>(
+    region: &Region,
+    draw_fn: F,
+) -> Result<cairo::ImageSurface, cairo::Error>
+where
+    F: Fn(&cairo::Context),
+{
+    let (width, height) = region.output_dims();
+    let surface =
+        cairo::ImageSurface::create(cairo::Format::ARgb32, width, height)?;
+    let ctx = cairo::Context::new(&surface)?;
+    region.apply(&ctx);
+    draw_fn(&ctx);
+    drop(ctx);
+    surface.flush();
+    Ok(surface)
+}
+
+/// Draws a region of the provided state and saves it as an SVG image.
+///
+/// The `draw_fn` closure should draw the state onto the provided context.
+pub fn region_to_svg<F, P>(
+    region: &Region,
+    path: P,
+    draw_fn: F,
+) -> Result<(), cairo::Error>
+where
+    F: Fn(&cairo::Context),
+    P: AsRef<std::path::Path>,
+{
+    let (width, height) = region.output_dims();
+    let surface =
+        cairo::SvgSurface::new(width as f64, height as f64, Some(path))?;
+    let ctx = cairo::Context::new(&surface)?;
+    region.apply(&ctx);
+    draw_fn(&ctx);
+    drop(ctx);
+    surface.finish();
+    surface.status()
+}
+
+/// A drawing surface onto which the user interface is drawn.
+///
+/// By default, a [Canvas] draws onto a [TiledSurface], which can be
+/// displayed by a GUI toolkit (e.g., in a GTK drawing area) with
+/// [Surface::paint].
+/// A single [cairo::ImageSurface] can be used instead (see [Canvas::image]).
+/// A [cairo::RecordingSurface] can be used instead (see
+/// [Canvas::recording]), so that the output of each UI state can be examined
+/// (e.g., by checking its ink extents) without a display server.
+pub trait Surface {
+    /// Returns a new drawing context for this surface.
+    fn new_context(&self) -> Result<cairo::Context, cairo::Error>;
+
+    /// Returns the width of this surface, in pixels.
+    fn width(&self) -> i32;
+
+    /// Returns the height of this surface, in pixels.
+    fn height(&self) -> i32;
+
+    /// Returns a new, empty surface of the same kind as this surface, with
+    /// the specified dimensions.
+    fn resized(
+        &self,
+        width: i32,
+        height: i32,
+    ) -> Result<Box<dyn Surface>, cairo::Error>;
+
+    /// Sets this surface as the source pattern for `ctx`, so that its
+    /// contents are drawn with their top-left corner at `(x, y)`.
+    fn set_as_source(
+        &self,
+        ctx: &cairo::Context,
+        x: f64,
+        y: f64,
+    ) -> Result<(), cairo::Error>;
+
+    /// Returns the ink bounding box `(x0, y0, width, height)` of everything
+    /// that has been drawn on this surface.
+    fn ink_extents(&self) -> (f64, f64, f64, f64);
+
+    /// Paints the contents of this surface onto `ctx`, with their top-left
+    /// corner at `(x, y)`.
+    ///
+    /// The default implementation calls [Surface::set_as_source] and paints
+    /// the entire surface.
+    fn paint(
+        &self,
+        ctx: &cairo::Context,
+        x: f64,
+        y: f64,
+    ) -> Result<(), cairo::Error> {
+        self.set_as_source(ctx, x, y)?;
+        ctx.paint()
+    }
+
+    /// Discards everything that has been drawn on this surface, before the
+    /// user interface is drawn again.
+    ///
+    /// The default implementation does nothing, so that anything that is not
+    /// drawn again remains visible.
+    fn discard(&self) -> Result<(), cairo::Error> {
+        Ok(())
+    }
+
+    /// Notifies this surface that the rectangle `(x, y, width, height)` has
+    /// been drawn again, so that any cached copy of this region is stale.
+    ///
+    /// The default implementation does nothing.
+    fn invalidate(&self, _x: f64, _y: f64, _width: f64, _height: f64) {}
+}
+
+impl Surface for cairo::ImageSurface {
+    fn new_context(&self) -> Result<cairo::Context, cairo::Error> {
+        cairo::Context::new(self)
+    }
+
+    fn width(&self) -> i32 {
+        cairo::ImageSurface::width(self)
+    }
+
+    fn height(&self) -> i32 {
+        cairo::ImageSurface::height(self)
+    }
+
+    fn resized(
+        &self,
+        width: i32,
+        height: i32,
+    ) -> Result<Box<dyn Surface>, cairo::Error> {
+        let surface =
+            cairo::ImageSurface::create(self.format(), width, height)?;
+        Ok(Box::new(surface))
+    }
+
+    fn set_as_source(
+        &self,
+        ctx: &cairo::Context,
+        x: f64,
+        y: f64,
+    ) -> Result<(), cairo::Error> {
+        ctx.set_source_surface(self, x, y)
+    }
+
+    /// Image surfaces do not record what has been drawn on them, and so this
+    /// returns the bounding box of the pixels that are not fully
+    /// transparent.
+    ///
+    /// Only [cairo::Format::ARgb32] surfaces record transparency, and so
+    /// this returns the bounding box of the entire surface for any other
+    /// format.
+    fn ink_extents(&self) -> (f64, f64, f64, f64) {
+        let (width, height) = (Surface::width(self), Surface::height(self));
+        let whole = (0.0, 0.0, width as f64, height as f64);
+        if self.format() != cairo::Format::ARgb32 {
+            return whole;
+        }
+        let stride = self.stride() as usize;
+        let mut bounds: Option<(usize, usize, usize, usize)> = None;
+        let result = self.with_data(|data| {
+            for y in 0..height as usize {
+                let row = &data[y * stride..y * stride + 4 * width as usize];
+                // NOTE: ARgb32 pixels are stored as native-endian 32-bit
+                // integers, with the alpha channel in the upper 8 bits.
+                let inked = |x: &usize| {
+                    let px = &row[4 * x..4 * x + 4];
+                    u32::from_ne_bytes([px[0], px[1], px[2], px[3]]) >> 24 > 0
+                };
+                let Some(x0) = (0..width as usize).find(inked) else {
+                    continue;
+                };
+                let x1 = (0..width as usize).rev().find(inked).unwrap_or(x0);
+                bounds = Some(match bounds {
+                    None => (x0, y, x1, y),
+                    Some((bx0, by0, bx1, _by1)) => {
+                        (bx0.min(x0), by0, bx1.max(x1), y)
+                    }
+                });
+            }
+        });
+        if result.is_err() {
+            // NOTE: the pixel data cannot be accessed if the surface is in
+            // an error state or has been finished.
+            return whole;
+        }
+        match bounds {
+            Some((x0, y0, x1, y1)) => (
+                x0 as f64,
+                y0 as f64,
+                (x1 - x0 + 1) as f64,
+                (y1 - y0 + 1) as f64,
+            ),
+            None => (0.0, 0.0, 0.0, 0.0),
+        }
+    }
+}
+
+impl Surface for cairo::RecordingSurface {
+    fn new_context(&self) -> Result<cairo::Context, cairo::Error> {
+        cairo::Context::new(self)
+    }
+
+    fn width(&self) -> i32 {
+        self.extents().map(|rect| rect.width() as i32).unwrap_or(0)
+    }
+
+    fn height(&self) -> i32 {
+        self.extents().map(|rect| rect.height() as i32).unwrap_or(0)
+    }
+
+    fn resized(
+        &self,
+        width: i32,
+        height: i32,
+    ) -> Result<Box<dyn Surface>, cairo::Error> {
+        let surface = recording_surface(width, height)?;
+        Ok(Box::new(surface))
+    }
+
+    fn set_as_source(
+        &self,
+        ctx: &cairo::Context,
+        x: f64,
+        y: f64,
+    ) -> Result<(), cairo::Error> {
+        ctx.set_source_surface(self, x, y)
+    }
+
+    fn ink_extents(&self) -> (f64, f64, f64, f64) {
+        cairo::RecordingSurface::ink_extents(self)
+    }
+}
+
+/// The default width and height of each tile in a [TiledSurface], in
+/// pixels.
+pub const TILE_SIZE: i32 = 512;
+
+/// A drawing surface that records drawing operations, and stores its
+/// contents as square image tiles that are only created when they are
+/// painted (see [Surface::paint]).
+///
+/// This avoids allocating a single image surface for the entire map, which
+/// can be enormous at the maximum zoom level, since only the tiles that
+/// have been displayed are stored.
+/// Tiles are discarded when the surface is drawn again (see
+/// [Surface::discard]), and when a region that contains them is drawn again
+/// (see [Surface::invalidate]).
+pub struct TiledSurface {
+    recording: cairo::RecordingSurface,
+    width: i32,
+    height: i32,
+    tile_size: i32,
+    tiles: RefCell<BTreeMap<(i32, i32), cairo::ImageSurface>>,
+}
+
+impl TiledSurface {
+    /// Returns a new tiled surface with the specified dimensions, which uses
+    /// tiles of [TILE_SIZE] pixels.
+    pub fn new(width: i32, height: i32) -> Result<Self, cairo::Error> {
+        TiledSurface::with_tile_size(width, height, TILE_SIZE)
+    }
+
+    /// Returns a new tiled surface with the specified dimensions, which uses
+    /// tiles of `tile_size` pixels.
+    pub fn with_tile_size(
+        width: i32,
+        height: i32,
+        tile_size: i32,
+    ) -> Result<Self, cairo::Error> {
+        if tile_size < 1 {
+            return Err(cairo::Error::InvalidSize);
+        }
+        let recording = recording_surface(width, height)?;
+        Ok(TiledSurface {
+            recording,
+            width,
+            height,
+            tile_size,
+            tiles: RefCell::new(BTreeMap::new()),
+        })
+    }
+
+    /// Returns the width and height of each tile, in pixels.
+    pub fn tile_size(&self) -> i32 {
+        self.tile_size
+    }
+
+    /// Returns the number of tiles that are currently stored.
+    pub fn tile_count(&self) -> usize {
+        self.tiles.borrow().len()
+    }
+
+    /// Returns the `(column, row)` of each tile that overlaps the rectangle
+    /// `(x0, y0)` to `(x1, y1)`.
+    fn tiles_within(
+        &self,
+        x0: f64,
+        y0: f64,
+        x1: f64,
+        y1: f64,
+    ) -> Vec<(i32, i32)> {
+        let size = self.tile_size as f64;
+        let cols = (self.width + self.tile_size - 1) / self.tile_size;
+        let rows = (self.height + self.tile_size - 1) / self.tile_size;
+        let col_range = |a: f64, b: f64, n: i32| {
+            let first = ((a / size).floor() as i32).max(0);
+            let last = ((b / size).ceil() as i32).min(n);
+            first..last
+        };
+        col_range(x0, x1, cols)
+            .flat_map(|col| {
+                col_range(y0, y1, rows).map(move |row| (col, row))
+            })
+            .collect()
+    }
+
+    /// Returns the tile at `(column, row)`, drawing it if necessary.
+    fn tile(
+        &self,
+        col: i32,
+        row: i32,
+    ) -> Result<cairo::ImageSurface, cairo::Error> {
+        if let Some(tile) = self.tiles.borrow().get(&(col, row)) {
+            return Ok(tile.clone());
+        }
+        let x = col * self.tile_size;
+        let y = row * self.tile_size;
+        let width = self.tile_size.min(self.width - x);
+        let height = self.tile_size.min(self.height - y);
+        let tile = cairo::ImageSurface::create(
+            cairo::Format::ARgb32,
+            width,
+            height,
+        )?;
+        let ctx = cairo::Context::new(&tile)?;
+        ctx.set_source_surface(&self.recording, -x as f64, -y as f64)?;
+        ctx.set_operator(cairo::Operator::Source);
+        ctx.paint()?;
+        drop(ctx);
+        tile.flush();
+        self.tiles.borrow_mut().insert((col, row), tile.clone());
+        Ok(tile)
+    }
+}
+
+impl Surface for TiledSurface {
+    fn new_context(&self) -> Result<cairo::Context, cairo::Error> {
+        cairo::Context::new(&self.recording)
+    }
+
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn resized(
+        &self,
+        width: i32,
+        height: i32,
+    ) -> Result<Box<dyn Surface>, cairo::Error> {
+        let surface =
+            TiledSurface::with_tile_size(width, height, self.tile_size)?;
+        Ok(Box::new(surface))
+    }
+
+    /// Uses the recorded drawing operations as the source pattern, so that
+    /// copying the surface contents does not create any tiles.
+    fn set_as_source(
+        &self,
+        ctx: &cairo::Context,
+        x: f64,
+        y: f64,
+    ) -> Result<(), cairo::Error> {
+        ctx.set_source_surface(&self.recording, x, y)
+    }
+
+    fn ink_extents(&self) -> (f64, f64, f64, f64) {
+        self.recording.ink_extents()
+    }
+
+    /// Paints the tiles that overlap the clip region of `ctx`, creating any
+    /// of these tiles that are not already stored.
+    fn paint(
+        &self,
+        ctx: &cairo::Context,
+        x: f64,
+        y: f64,
+    ) -> Result<(), cairo::Error> {
+        let (x0, y0, x1, y1) = ctx.clip_extents()?;
+        for (col, row) in self.tiles_within(x0 - x, y0 - y, x1 - x, y1 - y) {
+            let tile = self.tile(col, row)?;
+            let tile_x = x + (col * self.tile_size) as f64;
+            let tile_y = y + (row * self.tile_size) as f64;
+            ctx.set_source_surface(&tile, tile_x, tile_y)?;
+            ctx.rectangle(
+                tile_x,
+                tile_y,
+                tile.width() as f64,
+                tile.height() as f64,
+            );
+            ctx.fill()?;
+        }
+        Ok(())
+    }
+
+    /// Discards all of the recorded drawing operations and stored tiles.
+    ///
+    /// Painting the entire surface white allows cairo to discard the
+    /// recorded drawing operations, which would otherwise accumulate each
+    /// time that the surface is drawn.
+    fn discard(&self) -> Result<(), cairo::Error> {
+        let ctx = cairo::Context::new(&self.recording)?;
+        n18brush::clear_surface(&ctx, Colour::WHITE);
+        self.tiles.borrow_mut().clear();
+        Ok(())
+    }
+
+    /// Discards the stored tiles that overlap this rectangle.
+    fn invalidate(&self, x: f64, y: f64, width: f64, height: f64) {
+        let stale = self.tiles_within(x, y, x + width, y + height);
+        let mut tiles = self.tiles.borrow_mut();
+        for ix in stale {
+            tiles.remove(&ix);
+        }
+    }
+}
+
+/// Returns a recording surface with the specified dimensions.
+fn recording_surface(
+    width: i32,
+    height: i32,
+) -> Result<cairo::RecordingSurface, cairo::Error> {
+    let extents =
+        cairo::Rectangle::new(0.0, 0.0, width as f64, height as f64);
+    cairo::RecordingSurface::create(cairo::Content::ColorAlpha, Some(extents))
+}
+
+/// The drawing surface for the user interface, and its drawing context.
 pub struct Canvas {
     // NOTE: we need to share the surface with the main event loop and the UI.
-    surface: Rc<RwLock<cairo::ImageSurface>>,
+    surface: Rc<RwLock<Box<dyn Surface>>>,
     context: cairo::Context,
     width: i32,
     height: i32,
 }
 
 impl Canvas {
+    /// Creates a canvas that draws onto a [TiledSurface].
     pub fn new(width: i32, height: i32) -> Self {
+        let surface = TiledSurface::new(width, height)
+            .expect("Could not create TiledSurface");
+        Canvas::with_surface(Box::new(surface))
+    }
+
+    /// Creates a canvas that draws onto a single image surface.
+    pub fn image(width: i32, height: i32) -> Self {
         let surface =
             cairo::ImageSurface::create(cairo::Format::ARgb32, width, height)
                 .expect("Could not create ImageSurface");
-        let context = cairo::Context::new(&surface)
+        Canvas::with_surface(Box::new(surface))
+    }
+
+    /// Creates a canvas that draws onto a recording surface, which does not
+    /// require a display server.
+    pub fn recording(width: i32, height: i32) -> Self {
+        let surface = recording_surface(width, height)
+            .expect("Could not create RecordingSurface");
+        Canvas::with_surface(Box::new(surface))
+    }
+
+    /// Creates a canvas that draws onto the provided surface.
+    pub fn with_surface(surface: Box<dyn Surface>) -> Self {
+        let context = surface
+            .new_context()
             .expect("Could not create cairo::Context");
+        let width = surface.width();
+        let height = surface.height();
         let surface = Rc::new(RwLock::new(surface));
         Canvas {
             surface,
@@ -78,48 +612,77 @@ impl Canvas {
         }
     }
 
-    pub fn surface(&self) -> Rc<RwLock<cairo::ImageSurface>> {
+    pub fn surface(&self) -> Rc<RwLock<Box<dyn Surface>>> {
         Rc::clone(&self.surface)
     }
 
-    pub fn copy_surface(&self) -> cairo::ImageSurface {
+    /// Discards everything that has been drawn on this canvas, before it is
+    /// drawn again; see [Surface::discard].
+    pub fn discard(&self) {
+        self.surface
+            .read()
+            .expect("Could not access drawing surface")
+            .discard()
+            .expect("Could not discard drawing surface")
+    }
+
+    /// Notifies the drawing surface that the rectangle
+    /// `(x, y, width, height)` has been drawn again; see
+    /// [Surface::invalidate].
+    ///
+    /// This allows part of the canvas to be drawn again without discarding
+    /// the rest of its contents.
+    pub fn invalidate(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.surface
+            .read()
+            .expect("Could not access drawing surface")
+            .invalidate(x, y, width, height)
+    }
+
+    /// Returns the ink bounding box `(x0, y0, width, height)` of everything
+    /// that has been drawn on this canvas; see [Surface::ink_extents].
+    pub fn ink_extents(&self) -> (f64, f64, f64, f64) {
+        self.surface
+            .read()
+            .expect("Could not access drawing surface")
+            .ink_extents()
+    }
+
+    /// Returns a new image surface that contains a copy of part of the
+    /// canvas, whose top-left corner is drawn at `(x0, y0)`.
+    fn copy_to_image(
+        &self,
+        width: i32,
+        height: i32,
+        x0: f64,
+        y0: f64,
+    ) -> cairo::ImageSurface {
         let source = self
             .surface
             .read()
             .expect("Could not access drawing surface");
-        let new_surface = cairo::ImageSurface::create(
-            source.format(),
-            source.width(),
-            source.height(),
-        )
-        .expect("Could not create image surface");
+        let new_surface =
+            cairo::ImageSurface::create(cairo::Format::ARgb32, width, height)
+                .expect("Could not create image surface");
         let ctx = cairo::Context::new(&new_surface)
             .expect("Could not create image context");
-        ctx.set_source_surface(&*source, 0.0, 0.0).unwrap();
+        source.set_as_source(&ctx, x0, y0).unwrap();
         ctx.set_operator(cairo::Operator::Source);
         ctx.paint().unwrap();
         new_surface
     }
 
+    pub fn copy_surface(&self) -> cairo::ImageSurface {
+        self.copy_to_image(self.width, self.height, 0.0, 0.0)
+    }
+
     pub fn copy_ink(
         &self,
         state: &State,
         assets: &Assets,
     ) -> cairo::ImageSurface {
-        let source = self
-            .surface
-            .read()
-            .expect("Could not access drawing surface");
         let (width, height) = required_dims(state, assets);
-        let new_surface =
-            cairo::ImageSurface::create(source.format(), width, height)
-                .expect("Could not create image surface");
-        let ctx = cairo::Context::new(&new_surface)
-            .expect("Could not create image context");
-        ctx.set_source_surface(&*source, 0.0, 0.0).unwrap();
-        ctx.set_operator(cairo::Operator::Source);
-        ctx.paint().unwrap();
-        new_surface
+        self.copy_to_image(width, height, 0.0, 0.0)
     }
 
     pub fn copy_ink_with_margin(
@@ -128,24 +691,12 @@ impl Canvas {
         assets: &Assets,
         margin: i32,
     ) -> cairo::ImageSurface {
-        let source = self
-            .surface
-            .read()
-            .expect("Could not access drawing surface");
         let exts = ink_extents(state, assets);
         let width = (exts.2 + 2.0 * margin as f64) as i32;
         let height = (exts.3 + 2.0 * margin as f64) as i32;
         let x0 = margin as f64 - exts.0;
         let y0 = margin as f64 - exts.1;
-        let new_surface =
-            cairo::ImageSurface::create(source.format(), width, height)
-                .expect("Could not create image surface");
-        let ctx = cairo::Context::new(&new_surface)
-            .expect("Could not create image context");
-        ctx.set_source_surface(&*source, x0, y0).unwrap();
-        ctx.set_operator(cairo::Operator::Source);
-        ctx.paint().unwrap();
-        new_surface
+        self.copy_to_image(width, height, x0, y0)
     }
 
     pub fn context(&self) -> &cairo::Context {
@@ -160,23 +711,23 @@ impl Canvas {
         self.height
     }
 
+    /// Replaces the drawing surface with a new, empty surface of the same
+    /// kind, with the specified dimensions.
     pub fn resize(&mut self, new_width: i32, new_height: i32) {
         info!(
-            "Resizing image surface from ({}, {}) to ({}, {})",
+            "Resizing drawing surface from ({}, {}) to ({}, {})",
             self.width, self.height, new_width, new_height
         );
-        let surface = cairo::ImageSurface::create(
-            cairo::Format::ARgb32,
-            new_width,
-            new_height,
-        )
-        .expect("Could not create ImageSurface");
-        self.context = cairo::Context::new(&surface)
-            .expect("Could not create cairo::Context");
         let mut surf_ref = self
             .surface
             .write()
             .expect("Could not modify drawing surface");
+        let surface = surf_ref
+            .resized(new_width, new_height)
+            .expect("Could not create drawing surface");
+        self.context = surface
+            .new_context()
+            .expect("Could not create cairo::Context");
         *surf_ref = surface;
         self.width = new_width;
         self.height = new_height;
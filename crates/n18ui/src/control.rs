@@ -1,13 +1,32 @@
-use n18game::{DividendOptions, Game};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use n18game::bank::{TrainSupply, TrainTransfer};
+use n18game::{DividendOptions, Game, Journal, PhaseEffects};
 use n18route::Trains;
 
-use crate::PingDest;
+use crate::panel::{Panel, PanelKind};
+use crate::status::StatusBar;
+use crate::{PingDest, UiAction};
 
 mod _gtk;
 
 #[doc(inline)]
 pub use _gtk::GtkController;
 
+/// Returns `true` if each character of `query` (ignoring whitespace) occurs
+/// in `text`, in the same order, ignoring case.
+///
+/// For example, the query `"sgm"` matches `"Save game"`.
+pub fn fuzzy_match(query: &str, text: &str) -> bool {
+    let mut chars = text.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .all(|q| chars.any(|c| c == q))
+}
+
 #[derive(Clone)]
 pub enum PingSender {
     Async(async_channel::Sender<PingDest>),
@@ -29,17 +48,162 @@ impl PingSender {
     }
 }
 
+/// Returns the user's response to a dialog to the user interface, by sending
+/// a [UiAction] and then pinging the user interface (see
+/// [PingDest::TopLevel]) so that the action is handled by the main loop.
+///
+/// Dialogs never block the main loop: each dialog method of [UiController]
+/// returns immediately and passes the user's response to a callback, and
+/// these callbacks should use a [Responder] rather than modifying the user
+/// interface directly.
+#[derive(Clone)]
+pub struct Responder {
+    sender: std::sync::mpsc::Sender<UiAction>,
+    ping_tx: PingSender,
+}
+
+impl Responder {
+    pub fn new(
+        sender: std::sync::mpsc::Sender<UiAction>,
+        ping_tx: PingSender,
+    ) -> Self {
+        Responder { sender, ping_tx }
+    }
+
+    /// Sends an action to the user interface, and returns `false` if the
+    /// user interface no longer exists.
+    pub fn send<A: Into<UiAction>>(&self, action: A) -> bool {
+        self.sender.send(action.into()).is_ok()
+            && self.ping_tx.send_ping(PingDest::TopLevel).is_ok()
+    }
+}
+
+/// The maximum number of files in the recent files list.
+pub const MAX_RECENT_FILES: usize = 10;
+
+/// Records the game files that were most recently loaded or saved, and the
+/// directory in which files for each game were most recently loaded or
+/// saved, so that file dialogs can start in this directory.
+///
+/// These are saved in the user interface session (see [crate::session]).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileHistory {
+    recent_files: Vec<PathBuf>,
+    game_dirs: BTreeMap<String, PathBuf>,
+    active_game: Option<String>,
+}
+
+impl FileHistory {
+    /// Creates a file history from the recent files (most recent first) and
+    /// the default directory for each game, which are identified by name.
+    pub fn new(
+        recent_files: Vec<PathBuf>,
+        game_dirs: BTreeMap<String, PathBuf>,
+    ) -> Self {
+        let mut history = FileHistory {
+            recent_files: vec![],
+            game_dirs,
+            active_game: None,
+        };
+        for path in recent_files.into_iter().rev() {
+            history.add_recent_file(path)
+        }
+        history
+    }
+
+    /// Returns the recently loaded or saved files, most recent first.
+    pub fn recent_files(&self) -> &[PathBuf] {
+        &self.recent_files
+    }
+
+    /// Returns the default directory for each game, identified by name.
+    pub fn game_dirs(&self) -> &BTreeMap<String, PathBuf> {
+        &self.game_dirs
+    }
+
+    /// Sets the name of the current game, which determines the default
+    /// directory (see [FileHistory::default_dir]).
+    pub fn set_active_game(&mut self, game: &str) {
+        self.active_game = Some(game.to_string())
+    }
+
+    /// Adds a file to the start of the recent files list, removing any
+    /// earlier entry for this file and the oldest entries, so that the list
+    /// contains at most [MAX_RECENT_FILES] files.
+    pub fn add_recent_file(&mut self, path: PathBuf) {
+        self.recent_files.retain(|p| *p != path);
+        self.recent_files.insert(0, path);
+        self.recent_files.truncate(MAX_RECENT_FILES);
+    }
+
+    /// Records a game file that was loaded or saved for the current game,
+    /// and makes its directory the default directory for this game.
+    pub fn record(&mut self, path: &Path) {
+        if let Some(game) = &self.active_game {
+            let dir = path.parent().filter(|dir| dir.is_dir());
+            if let Some(dir) = dir {
+                self.game_dirs.insert(game.clone(), dir.to_path_buf());
+            }
+        }
+        self.add_recent_file(path.to_path_buf())
+    }
+
+    /// Replaces a renamed file in the recent files list.
+    pub fn rename(&mut self, old: &Path, new: &Path) {
+        for path in self.recent_files.iter_mut() {
+            if path == old {
+                *path = new.to_path_buf()
+            }
+        }
+    }
+
+    /// Returns the directory in which file dialogs should start: the
+    /// default directory for the current game, if any, otherwise the
+    /// directory of the most recent file.
+    pub fn default_dir(&self) -> Option<&Path> {
+        self.active_game
+            .as_ref()
+            .and_then(|game| self.game_dirs.get(game))
+            .map(|dir| dir.as_path())
+            .or_else(|| {
+                self.recent_files.first().and_then(|path| path.parent())
+            })
+            .filter(|dir| !dir.as_os_str().is_empty())
+    }
+}
+
 pub trait UiController {
     fn window_title(&self) -> Option<String>;
 
+    /// Returns the recently loaded and saved game files.
+    fn file_history(&self) -> &FileHistory;
+
+    /// Returns the recently loaded and saved game files, so that they can be
+    /// updated.
+    fn file_history_mut(&mut self) -> &mut FileHistory;
+
     fn set_window_title(&mut self, title: &str);
 
+    /// Displays the segments of the status bar.
+    fn set_status(&mut self, status: &StatusBar);
+
     fn quit(&mut self);
 
     fn redraw(&self);
 
     fn resize(&mut self, width: i32, height: i32);
 
+    /// Returns the horizontal and vertical scroll offsets of the map.
+    fn viewport(&self) -> Option<(f64, f64)>;
+
+    /// Scrolls the map to the provided horizontal and vertical offsets.
+    fn set_viewport(&mut self, x: f64, y: f64);
+
+    /// Returns the width and height of the window.
+    fn window_size(&self) -> Option<(i32, i32)>;
+
+    fn set_window_size(&mut self, width: i32, height: i32);
+
     fn ping_tx(&self) -> PingSender;
 
     fn select_string<F>(
@@ -56,16 +220,59 @@ pub trait UiController {
         Self: Sized,
         F: Fn(Option<usize>) + 'static;
 
+    /// Prompts the user to select one item from `strings`, which can be
+    /// filtered by entering a search query (see [fuzzy_match]), and provides
+    /// the index of the selected item (if any) to `callback`.
+    fn search_index<F>(&mut self, title: &str, strings: &[&str], callback: F)
+    where
+        Self: Sized,
+        F: Fn(Option<usize>) + 'static;
+
     fn select_trains<F>(&mut self, game: &dyn Game, title: &str, callback: F)
     where
         Self: Sized,
         F: Fn(Option<(Trains, Vec<bool>)>) + 'static;
 
-    fn select_phase<F>(&mut self, game: &dyn Game, callback: F)
-    where
+    /// Displays each game phase, with the current phase marked, and a
+    /// preview of the changes that will occur in the selected phase (see
+    /// [Game::preview_phase]), and provides the index of the selected phase
+    /// (if any) to `callback`.
+    fn select_phase<F>(
+        &mut self,
+        game: &dyn Game,
+        effects: &[PhaseEffects],
+        callback: F,
+    ) where
         Self: Sized,
         F: Fn(Option<usize>) + 'static;
 
+    /// Displays the trains held by the bank and available in the open
+    /// market, and prompts the user to select a train purchase (or discard)
+    /// to record.
+    fn select_train_transfer<F>(&mut self, supply: &TrainSupply, callback: F)
+    where
+        Self: Sized,
+        F: Fn(Option<TrainTransfer>) + 'static;
+
+    /// Prompts the user to enter a line of text, and provides the entered
+    /// text (if any) to `callback`.
+    fn enter_text<F>(
+        &mut self,
+        title: &str,
+        prompt: &str,
+        default_text: Option<&str>,
+        callback: F,
+    ) where
+        Self: Sized,
+        F: Fn(Option<String>) + 'static;
+
+    /// Asks the user to confirm an action, and provides their answer to
+    /// `callback`.
+    fn confirm<F>(&mut self, title: &str, message: &str, callback: F)
+    where
+        Self: Sized,
+        F: Fn(bool) + 'static;
+
     fn select_screenshot_save<F>(
         &mut self,
         title: &str,
@@ -93,6 +300,14 @@ pub trait UiController {
         Self: Sized,
         F: Fn(Option<std::path::PathBuf>) + 'static;
 
+    /// Prompts the user to select a list of company token placements (see
+    /// [n18io::read_token_placements]), and passes the selected path (if
+    /// any) to `callback`.
+    fn select_token_list<F>(&mut self, title: &str, callback: F)
+    where
+        Self: Sized,
+        F: Fn(Option<std::path::PathBuf>) + 'static;
+
     fn show_dividends<F>(
         &mut self,
         abbrev: &str,
@@ -102,8 +317,55 @@ pub trait UiController {
     ) where
         Self: Sized,
         F: Fn() + 'static;
+
+    /// Shows a context menu at the point `(x, y)` on the map, which offers
+    /// each item in `items`, and provides the index of the selected item (if
+    /// any) to `callback`.
+    fn show_context_menu<F>(
+        &mut self,
+        x: f64,
+        y: f64,
+        items: &[&str],
+        callback: F,
+    ) where
+        Self: Sized,
+        F: Fn(Option<usize>) + 'static;
+
+    /// Displays an error message, such as the reason why a saved game could
+    /// not be loaded.
+    fn show_error(&mut self, title: &str, message: &str);
+
+    /// Displays the moves that have been made in the current game.
+    fn show_journal(&mut self, journal: &Journal);
+
+    /// Copies an image to the system clipboard, so that it can be pasted
+    /// into other applications.
+    fn copy_image(
+        &mut self,
+        image: &cairo::ImageSurface,
+    ) -> Result<(), String>;
+
+    /// Displays an auxiliary panel alongside the map, replacing the title
+    /// and contents of any open panel of the same kind.
+    fn show_panel(&mut self, title: &str, panel: &Panel);
+
+    /// Closes the auxiliary panel of the given kind, if it is open.
+    fn close_panel(&mut self, kind: PanelKind);
+
+    /// Returns `true` if an auxiliary panel of the given kind is open.
+    fn is_panel_open(&self, kind: PanelKind) -> bool;
+
+    /// Calls `callback` with the path of each file that is dropped onto the
+    /// window.
+    fn on_file_drop<F>(&mut self, callback: F)
+    where
+        Self: Sized,
+        F: Fn(std::path::PathBuf) + 'static;
 }
 
+// NOTE: there is only ever a single controller, so the size difference
+// between the variants is not a concern.
+#[allow(clippy::large_enum_variant)]
 pub enum Controller {
     Gtk(GtkController),
     Dummy(DummyController),
@@ -122,6 +384,22 @@ impl From<DummyController> for Controller {
 }
 
 impl UiController for Controller {
+    fn file_history(&self) -> &FileHistory {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.file_history(),
+            Dummy(ctrl) => ctrl.file_history(),
+        }
+    }
+
+    fn file_history_mut(&mut self) -> &mut FileHistory {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.file_history_mut(),
+            Dummy(ctrl) => ctrl.file_history_mut(),
+        }
+    }
+
     fn quit(&mut self) {
         use Controller::*;
         match self {
@@ -154,6 +432,14 @@ impl UiController for Controller {
         }
     }
 
+    fn set_status(&mut self, status: &StatusBar) {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.set_status(status),
+            Dummy(ctrl) => ctrl.set_status(status),
+        }
+    }
+
     fn resize(&mut self, width: i32, height: i32) {
         use Controller::*;
         match self {
@@ -162,6 +448,38 @@ impl UiController for Controller {
         }
     }
 
+    fn viewport(&self) -> Option<(f64, f64)> {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.viewport(),
+            Dummy(ctrl) => ctrl.viewport(),
+        }
+    }
+
+    fn set_viewport(&mut self, x: f64, y: f64) {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.set_viewport(x, y),
+            Dummy(ctrl) => ctrl.set_viewport(x, y),
+        }
+    }
+
+    fn window_size(&self) -> Option<(i32, i32)> {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.window_size(),
+            Dummy(ctrl) => ctrl.window_size(),
+        }
+    }
+
+    fn set_window_size(&mut self, width: i32, height: i32) {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.set_window_size(width, height),
+            Dummy(ctrl) => ctrl.set_window_size(width, height),
+        }
+    }
+
     fn ping_tx(&self) -> PingSender {
         use Controller::*;
         match self {
@@ -192,6 +510,17 @@ impl UiController for Controller {
         }
     }
 
+    fn search_index<F>(&mut self, title: &str, strings: &[&str], callback: F)
+    where
+        F: Fn(Option<usize>) + 'static,
+    {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.search_index(title, strings, callback),
+            Dummy(ctrl) => ctrl.search_index(title, strings, callback),
+        }
+    }
+
     fn select_trains<F>(&mut self, game: &dyn Game, title: &str, callback: F)
     where
         F: Fn(Option<(Trains, Vec<bool>)>) + 'static,
@@ -203,14 +532,60 @@ impl UiController for Controller {
         }
     }
 
-    fn select_phase<F>(&mut self, game: &dyn Game, callback: F)
-    where
+    fn select_phase<F>(
+        &mut self,
+        game: &dyn Game,
+        effects: &[PhaseEffects],
+        callback: F,
+    ) where
         F: Fn(Option<usize>) + 'static,
     {
         use Controller::*;
         match self {
-            Gtk(ctrl) => ctrl.select_phase(game, callback),
-            Dummy(ctrl) => ctrl.select_phase(game, callback),
+            Gtk(ctrl) => ctrl.select_phase(game, effects, callback),
+            Dummy(ctrl) => ctrl.select_phase(game, effects, callback),
+        }
+    }
+
+    fn select_train_transfer<F>(&mut self, supply: &TrainSupply, callback: F)
+    where
+        F: Fn(Option<TrainTransfer>) + 'static,
+    {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.select_train_transfer(supply, callback),
+            Dummy(ctrl) => ctrl.select_train_transfer(supply, callback),
+        }
+    }
+
+    fn enter_text<F>(
+        &mut self,
+        title: &str,
+        prompt: &str,
+        default_text: Option<&str>,
+        callback: F,
+    ) where
+        F: Fn(Option<String>) + 'static,
+    {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => {
+                ctrl.enter_text(title, prompt, default_text, callback)
+            }
+            Dummy(ctrl) => {
+                ctrl.enter_text(title, prompt, default_text, callback)
+            }
+        }
+    }
+
+    fn confirm<F>(&mut self, title: &str, message: &str, callback: F)
+    where
+        F: Fn(bool) + 'static,
+    {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.confirm(title, message, callback),
+            Dummy(ctrl) => ctrl.confirm(title, message, callback),
         }
     }
 
@@ -267,6 +642,17 @@ impl UiController for Controller {
         }
     }
 
+    fn select_token_list<F>(&mut self, title: &str, callback: F)
+    where
+        F: Fn(Option<std::path::PathBuf>) + 'static,
+    {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.select_token_list(title, callback),
+            Dummy(ctrl) => ctrl.select_token_list(title, callback),
+        }
+    }
+
     fn show_dividends<F>(
         &mut self,
         abbrev: &str,
@@ -287,6 +673,85 @@ impl UiController for Controller {
             }
         }
     }
+
+    fn show_context_menu<F>(
+        &mut self,
+        x: f64,
+        y: f64,
+        items: &[&str],
+        callback: F,
+    ) where
+        Self: Sized,
+        F: Fn(Option<usize>) + 'static,
+    {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.show_context_menu(x, y, items, callback),
+            Dummy(ctrl) => ctrl.show_context_menu(x, y, items, callback),
+        }
+    }
+
+    fn show_error(&mut self, title: &str, message: &str) {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.show_error(title, message),
+            Dummy(ctrl) => ctrl.show_error(title, message),
+        }
+    }
+
+    fn show_journal(&mut self, journal: &Journal) {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.show_journal(journal),
+            Dummy(ctrl) => ctrl.show_journal(journal),
+        }
+    }
+
+    fn copy_image(
+        &mut self,
+        image: &cairo::ImageSurface,
+    ) -> Result<(), String> {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.copy_image(image),
+            Dummy(ctrl) => ctrl.copy_image(image),
+        }
+    }
+
+    fn show_panel(&mut self, title: &str, panel: &Panel) {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.show_panel(title, panel),
+            Dummy(ctrl) => ctrl.show_panel(title, panel),
+        }
+    }
+
+    fn close_panel(&mut self, kind: PanelKind) {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.close_panel(kind),
+            Dummy(ctrl) => ctrl.close_panel(kind),
+        }
+    }
+
+    fn is_panel_open(&self, kind: PanelKind) -> bool {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.is_panel_open(kind),
+            Dummy(ctrl) => ctrl.is_panel_open(kind),
+        }
+    }
+
+    fn on_file_drop<F>(&mut self, callback: F)
+    where
+        F: Fn(std::path::PathBuf) + 'static,
+    {
+        use Controller::*;
+        match self {
+            Gtk(ctrl) => ctrl.on_file_drop(callback),
+            Dummy(ctrl) => ctrl.on_file_drop(callback),
+        }
+    }
 }
 
 #[derive(Default)]
@@ -294,10 +759,26 @@ pub struct DummyController {
     game_load: Option<std::path::PathBuf>,
     game_save: Option<std::path::PathBuf>,
     screenshot_save: Option<std::path::PathBuf>,
+    token_list: Option<std::path::PathBuf>,
     phase: Option<usize>,
     index: Option<usize>,
     string: Option<String>,
+    text: Option<String>,
+    decline: bool,
     trains: Option<(Trains, Vec<bool>)>,
+    train_transfer: Option<TrainTransfer>,
+    error: Option<String>,
+    viewport: Option<(f64, f64)>,
+    window_size: Option<(i32, i32)>,
+    file_drop: Option<Box<dyn Fn(std::path::PathBuf)>>,
+    panels: std::collections::BTreeMap<PanelKind, Panel>,
+    window_title: Option<String>,
+    status: StatusBar,
+    clipboard: Option<cairo::ImageSurface>,
+    history: FileHistory,
+    dialog_dir: Option<PathBuf>,
+    context_menu: Option<Vec<String>>,
+    context_menu_posn: Option<(f64, f64)>,
 }
 
 impl DummyController {
@@ -320,24 +801,144 @@ impl DummyController {
         self.screenshot_save = path
     }
 
+    /// Sets the token list that will be selected by the user, where `None`
+    /// means that the user will cancel the selection.
+    pub fn set_token_list_path(&mut self, path: Option<std::path::PathBuf>) {
+        self.token_list = path
+    }
+
     pub fn set_phase(&mut self, phase: Option<usize>) {
         self.phase = phase
     }
+
+    pub fn set_train_transfer(&mut self, transfer: Option<TrainTransfer>) {
+        self.train_transfer = transfer
+    }
+
+    /// Sets the index of the item that will be selected by the user (from
+    /// a dialog or a context menu), where `None` means that the user will
+    /// cancel the selection.
+    pub fn set_index(&mut self, index: Option<usize>) {
+        self.index = index
+    }
+
+    /// Sets the string that will be selected by the user, where `None`
+    /// means that the user will cancel the selection.
+    pub fn set_string(&mut self, string: Option<String>) {
+        self.string = string
+    }
+
+    /// Sets the trains and bonuses that will be selected by the user, where
+    /// `None` means that the user will cancel the selection.
+    pub fn set_trains(&mut self, trains: Option<(Trains, Vec<bool>)>) {
+        self.trains = trains
+    }
+
+    /// Sets the text that will be entered by the user, where `None` means
+    /// that the user will cancel text entry.
+    pub fn set_text(&mut self, text: Option<String>) {
+        self.text = text
+    }
+
+    /// Sets whether the user will confirm actions (the default) or decline
+    /// them.
+    pub fn set_confirm(&mut self, confirm: bool) {
+        self.decline = !confirm
+    }
+
+    /// Returns the most recent error message, if any.
+    pub fn last_error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Returns the segments that are displayed in the status bar.
+    pub fn status(&self) -> &StatusBar {
+        &self.status
+    }
+
+    /// Returns the image that was most recently copied to the clipboard, if
+    /// any.
+    pub fn clipboard_image(&self) -> Option<&cairo::ImageSurface> {
+        self.clipboard.as_ref()
+    }
+
+    /// Returns the directory in which the most recent game file dialog
+    /// started, if any.
+    pub fn dialog_dir(&self) -> Option<&Path> {
+        self.dialog_dir.as_deref()
+    }
+
+    /// Returns the items in the most recently shown context menu, if any.
+    pub fn context_menu(&self) -> Option<&[String]> {
+        self.context_menu.as_deref()
+    }
+
+    /// Returns the window coordinates at which the most recent context menu
+    /// was shown, if any.
+    pub fn context_menu_position(&self) -> Option<(f64, f64)> {
+        self.context_menu_posn
+    }
+
+    /// Returns the contents of the open panel of the given kind, if any.
+    pub fn panel(&self, kind: PanelKind) -> Option<&Panel> {
+        self.panels.get(&kind)
+    }
+
+    /// Simulates dropping a file onto the window, and returns `false` if
+    /// there is no callback for dropped files.
+    pub fn drop_file(&self, path: std::path::PathBuf) -> bool {
+        if let Some(callback) = &self.file_drop {
+            callback(path);
+            true
+        } else {
+            false
+        }
+    }
 }
 
 impl UiController for DummyController {
+    fn file_history(&self) -> &FileHistory {
+        &self.history
+    }
+
+    fn file_history_mut(&mut self) -> &mut FileHistory {
+        &mut self.history
+    }
+
     fn quit(&mut self) {}
 
     fn redraw(&self) {}
 
-    fn set_window_title(&mut self, _title: &str) {}
+    fn set_window_title(&mut self, title: &str) {
+        self.window_title = Some(title.to_string())
+    }
 
     fn window_title(&self) -> Option<String> {
-        None
+        self.window_title.clone()
+    }
+
+    fn set_status(&mut self, status: &StatusBar) {
+        self.status = status.clone()
     }
 
     fn resize(&mut self, _width: i32, _height: i32) {}
 
+    fn viewport(&self) -> Option<(f64, f64)> {
+        self.viewport
+    }
+
+    fn set_viewport(&mut self, x: f64, y: f64) {
+        self.viewport = Some((x, y))
+    }
+
+    fn window_size(&self) -> Option<(i32, i32)> {
+        self.window_size
+    }
+
+    fn set_window_size(&mut self, width: i32, height: i32) {
+        self.window_size = Some((width, height))
+    }
+
     fn ping_tx(&self) -> PingSender {
         PingSender::IgnorePings
     }
@@ -364,6 +965,17 @@ impl UiController for DummyController {
         callback(self.index)
     }
 
+    fn search_index<F>(
+        &mut self,
+        _title: &str,
+        _strings: &[&str],
+        callback: F,
+    ) where
+        F: Fn(Option<usize>) + 'static,
+    {
+        callback(self.index)
+    }
+
     fn select_trains<F>(
         &mut self,
         _game: &dyn Game,
@@ -380,14 +992,47 @@ impl UiController for DummyController {
         callback(trains)
     }
 
-    fn select_phase<F>(&mut self, _game: &dyn Game, callback: F)
-    where
+    fn select_phase<F>(
+        &mut self,
+        _game: &dyn Game,
+        _effects: &[PhaseEffects],
+        callback: F,
+    ) where
         Self: Sized,
         F: Fn(Option<usize>) + 'static,
     {
         callback(self.phase)
     }
 
+    fn select_train_transfer<F>(&mut self, _supply: &TrainSupply, callback: F)
+    where
+        Self: Sized,
+        F: Fn(Option<TrainTransfer>) + 'static,
+    {
+        callback(self.train_transfer.clone())
+    }
+
+    fn enter_text<F>(
+        &mut self,
+        _title: &str,
+        _prompt: &str,
+        _default_text: Option<&str>,
+        callback: F,
+    ) where
+        Self: Sized,
+        F: Fn(Option<String>) + 'static,
+    {
+        callback(self.text.clone())
+    }
+
+    fn confirm<F>(&mut self, _title: &str, _message: &str, callback: F)
+    where
+        Self: Sized,
+        F: Fn(bool) + 'static,
+    {
+        callback(!self.decline)
+    }
+
     fn select_screenshot_save<F>(
         &mut self,
         _title: &str,
@@ -409,6 +1054,7 @@ impl UiController for DummyController {
         Self: Sized,
         F: Fn(Option<std::path::PathBuf>) + 'static,
     {
+        self.dialog_dir = self.history.default_dir().map(Path::to_path_buf);
         callback(self.game_save.clone())
     }
 
@@ -421,9 +1067,18 @@ impl UiController for DummyController {
         Self: Sized,
         F: Fn(Option<std::path::PathBuf>) + 'static,
     {
+        self.dialog_dir = self.history.default_dir().map(Path::to_path_buf);
         callback(self.game_load.clone())
     }
 
+    fn select_token_list<F>(&mut self, _title: &str, callback: F)
+    where
+        Self: Sized,
+        F: Fn(Option<std::path::PathBuf>) + 'static,
+    {
+        callback(self.token_list.clone())
+    }
+
     fn show_dividends<F>(
         &mut self,
         _abbrev: &str,
@@ -436,4 +1091,53 @@ impl UiController for DummyController {
     {
         callback()
     }
+
+    fn show_context_menu<F>(
+        &mut self,
+        x: f64,
+        y: f64,
+        items: &[&str],
+        callback: F,
+    ) where
+        Self: Sized,
+        F: Fn(Option<usize>) + 'static,
+    {
+        self.context_menu =
+            Some(items.iter().map(|item| item.to_string()).collect());
+        self.context_menu_posn = Some((x, y));
+        callback(self.index)
+    }
+
+    fn show_error(&mut self, title: &str, message: &str) {
+        self.error = Some(format!("{}: {}", title, message))
+    }
+
+    fn show_journal(&mut self, _journal: &Journal) {}
+
+    fn copy_image(
+        &mut self,
+        image: &cairo::ImageSurface,
+    ) -> Result<(), String> {
+        self.clipboard = Some(image.clone());
+        Ok(())
+    }
+
+    fn show_panel(&mut self, _title: &str, panel: &Panel) {
+        self.panels.insert(panel.kind(), panel.clone());
+    }
+
+    fn close_panel(&mut self, kind: PanelKind) {
+        self.panels.remove(&kind);
+    }
+
+    fn is_panel_open(&self, kind: PanelKind) -> bool {
+        self.panels.contains_key(&kind)
+    }
+
+    fn on_file_drop<F>(&mut self, callback: F)
+    where
+        F: Fn(std::path::PathBuf) + 'static,
+    {
+        self.file_drop = Some(Box::new(callback))
+    }
 }
@@ -2,11 +2,17 @@
 
 use gtk::prelude::*;
 use gtk4 as gtk;
+use std::cell::{Cell, RefCell};
 use std::collections::BTreeMap;
+use std::rc::Rc;
 
-use n18game::{DividendOptions, Game};
+use n18game::bank::{TrainSupply, TrainTransfer};
+use n18game::{DividendOptions, Game, Journal, PhaseEffects};
 use n18route::{Train, Trains};
 
+use crate::control::{fuzzy_match, FileHistory};
+use crate::panel::{Panel, PanelKind};
+use crate::status::StatusBar;
 use crate::{PingDest, PingSender, UiController};
 
 /// Prompts the user to select one string from `items` and provides the
@@ -98,6 +104,97 @@ pub fn select_index<F>(
     dialog.show();
 }
 
+/// Prompts the user to select one item from `items`, which can be filtered by
+/// entering a search query, and provides the index of the selected item (if
+/// any) to `callback`.
+pub fn search_index<F>(
+    parent: &gtk::Window,
+    title: &str,
+    items: &[&str],
+    callback: F,
+) where
+    F: Fn(Option<usize>) + 'static,
+{
+    let buttons = [("OK", gtk::ResponseType::Accept)];
+    let flags = gtk::DialogFlags::all();
+    let dialog =
+        gtk::Dialog::with_buttons(Some(title), Some(parent), flags, &buttons);
+
+    let padding = 4;
+    let content = dialog.content_area();
+
+    let entry = gtk::SearchEntry::builder()
+        .margin_top(padding)
+        .margin_bottom(padding)
+        .margin_start(padding)
+        .margin_end(padding)
+        .build();
+
+    let list = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::Browse)
+        .activate_on_single_click(false)
+        .margin_top(padding)
+        .margin_bottom(padding)
+        .margin_start(padding)
+        .margin_end(padding)
+        .build();
+
+    items.iter().for_each(|item| {
+        let item_label = gtk::Label::new(Some(item));
+        item_label.set_halign(gtk::Align::Start);
+        list.append(&item_label);
+    });
+
+    // Only show the items that match the search query.
+    let labels: Vec<String> = items.iter().map(|s| s.to_string()).collect();
+    let query = entry.clone();
+    list.set_filter_func(move |row| {
+        let ix = row.index() as usize;
+        fuzzy_match(&query.text(), &labels[ix])
+    });
+
+    // Select the first item that matches the search query.
+    let select_first = |list: &gtk::ListBox| {
+        let first = (0..)
+            .map_while(|ix| list.row_at_index(ix))
+            .find(|row| row.is_child_visible());
+        list.select_row(first.as_ref());
+    };
+    select_first(&list);
+
+    let lst = list.clone();
+    entry.connect_search_changed(move |_| {
+        lst.invalidate_filter();
+        select_first(&lst);
+    });
+
+    // Make activating a row, or the search entry, pick the selected item
+    // and close the dialog.
+    let dlg = dialog.clone();
+    list.connect_row_activated(move |_, _| {
+        dlg.response(gtk::ResponseType::Accept)
+    });
+    let dlg = dialog.clone();
+    entry.connect_activate(move |_| dlg.response(gtk::ResponseType::Accept));
+
+    content.set_spacing(padding);
+    content.set_orientation(gtk::Orientation::Vertical);
+    content.append(&entry);
+    content.append(&list);
+
+    dialog.connect_response(move |dlg, response| {
+        dlg.hide();
+        let ix = if response == gtk::ResponseType::Accept {
+            list.selected_row().map(|row| row.index() as usize)
+        } else {
+            None
+        };
+        callback(ix)
+    });
+    dialog.show();
+    entry.grab_focus();
+}
+
 /// Prompts the user to select the trains and operating bonuses for a company,
 /// and provides these details (if any) to `callback`.
 #[allow(clippy::needless_collect)]
@@ -207,10 +304,15 @@ pub fn select_trains<F>(
     dialog.show();
 }
 
-/// Prompts the user to select a game phase, and provides this phase (if any)
-/// to `callback`.
-pub fn select_phase<F>(parent: &gtk::Window, game: &dyn Game, callback: F)
-where
+/// Displays each game phase, with the current phase marked, and a preview
+/// of the changes that will occur in the selected phase, and provides the
+/// index of the selected phase (if any) to `callback`.
+pub fn select_phase<F>(
+    parent: &gtk::Window,
+    game: &dyn Game,
+    effects: &[PhaseEffects],
+    callback: F,
+) where
     F: Fn(Option<usize>) + 'static,
 {
     let title = "Select Game Phase";
@@ -219,40 +321,71 @@ where
 
     let buttons = [
         ("OK", gtk::ResponseType::Accept),
-        // ("Cancel", gtk::ResponseType::Cancel),
+        ("Cancel", gtk::ResponseType::Cancel),
     ];
     let flags = gtk::DialogFlags::all();
     let dialog =
         gtk::Dialog::with_buttons(Some(title), Some(parent), flags, &buttons);
+    dialog.set_default_response(gtk::ResponseType::Accept);
 
     let padding = 4;
     let content = dialog.content_area();
 
+    // Identify each phase by its index, and mark the current phase.
     let combo = gtk::ComboBoxText::new();
-    phase_names
-        .iter()
-        .for_each(|name| combo.append(Some(name), name));
-    combo.set_active_id(phase_names.get(current_phase).copied());
+    phase_names.iter().enumerate().for_each(|(ix, name)| {
+        let text = if ix == current_phase {
+            format!("{} (current)", name)
+        } else {
+            name.to_string()
+        };
+        combo.append(Some(&ix.to_string()), &text)
+    });
+    combo.set_active_id(Some(&current_phase.to_string()));
     combo.set_margin_bottom(padding);
     combo.set_margin_top(padding);
     combo.set_margin_start(padding);
     combo.set_margin_end(padding);
     content.append(&combo);
 
-    let phase_names: Vec<String> =
-        phase_names.iter().map(|s| s.to_string()).collect();
+    // Show the changes that will occur in the selected phase.
+    let preview = gtk::Label::builder()
+        .use_markup(false)
+        .wrap(true)
+        .xalign(0.0)
+        .margin_bottom(padding)
+        .margin_top(padding)
+        .margin_start(padding)
+        .margin_end(padding)
+        .build();
+    content.append(&preview);
+
+    let effects = effects.to_vec();
+    let describe = move |ix: usize| {
+        if ix == current_phase {
+            return "This is the current phase.".to_string();
+        }
+        match effects.iter().find(|e| e.phase_ix == ix) {
+            Some(effect) if !effect.is_empty() => effect.summary().join("\n"),
+            _ => "No changes to the map.".to_string(),
+        }
+    };
+    let selected_ix = |combo: &gtk::ComboBoxText| {
+        combo
+            .active_id()
+            .and_then(|id| id.as_str().parse::<usize>().ok())
+    };
+    preview.set_text(&describe(current_phase));
+    combo.connect_changed(move |combo| {
+        if let Some(ix) = selected_ix(combo) {
+            preview.set_text(&describe(ix))
+        }
+    });
+
     dialog.connect_response(move |dlg, response| {
         dlg.hide();
         if response == gtk::ResponseType::Accept {
-            let ix = combo.active_text().and_then(|text| {
-                let text = text.as_str();
-                phase_names
-                    .iter()
-                    .enumerate()
-                    .find(|(_ix, name)| text == *name)
-                    .map(|(ix, _name)| ix)
-            });
-            callback(ix)
+            callback(selected_ix(&combo))
         } else {
             callback(None)
         }
@@ -260,6 +393,190 @@ where
     dialog.show();
 }
 
+/// Displays the trains held by the bank and available in the open market,
+/// prompts the user to select a train purchase (or discard), and provides
+/// the selected transfer (if any) to `callback`.
+pub fn select_train_transfer<F>(
+    parent: &gtk::Window,
+    supply: &TrainSupply,
+    callback: F,
+) where
+    F: Fn(Option<TrainTransfer>) + 'static,
+{
+    let title = "Record Train Purchase";
+    let buttons = [
+        ("OK", gtk::ResponseType::Accept),
+        ("Cancel", gtk::ResponseType::Cancel),
+    ];
+    let flags = gtk::DialogFlags::all();
+    let dialog =
+        gtk::Dialog::with_buttons(Some(title), Some(parent), flags, &buttons);
+
+    let padding = 4;
+    let content = dialog.content_area();
+    content.set_spacing(padding);
+    content.set_orientation(gtk::Orientation::Vertical);
+
+    // Display the number of trains of each type in the bank and in the open
+    // market.
+    let grid = gtk::Grid::builder()
+        .column_spacing(16)
+        .row_spacing(padding)
+        .margin_top(padding)
+        .margin_bottom(padding)
+        .margin_start(padding)
+        .margin_end(padding)
+        .build();
+    let headings = ["Train", "Bank", "Open market"];
+    for (col, heading) in headings.iter().enumerate() {
+        let label = gtk::Label::new(None);
+        label.set_markup(&format!("<b>{}</b>", heading));
+        grid.attach(&label, col as i32, 0, 1, 1);
+    }
+    for (ix, stock) in supply.stocks().iter().enumerate() {
+        let bank = stock
+            .bank
+            .map(|count| count.to_string())
+            .unwrap_or_else(|| "Unlimited".to_string());
+        let cells = [stock.name.clone(), bank, stock.pool.to_string()];
+        for (col, text) in cells.iter().enumerate() {
+            let label = gtk::Label::new(Some(text));
+            grid.attach(&label, col as i32, ix as i32 + 1, 1, 1);
+        }
+    }
+    content.append(&grid);
+
+    // Display the allowed transfers as a list.
+    let transfers = supply.transfers();
+    let list = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::Browse)
+        .activate_on_single_click(false)
+        .margin_top(padding)
+        .margin_bottom(padding)
+        .margin_start(padding)
+        .margin_end(padding)
+        .build();
+    transfers.iter().for_each(|transfer| {
+        let label = gtk::Label::new(Some(&transfer.to_string()));
+        list.append(&label);
+    });
+    list.select_row(list.row_at_index(0).as_ref());
+    let dlg = dialog.clone();
+    list.connect_row_activated(move |_, _| {
+        dlg.response(gtk::ResponseType::Accept)
+    });
+    content.append(&list);
+
+    dialog.connect_response(move |dlg, response| {
+        dlg.hide();
+        let transfer = if response == gtk::ResponseType::Accept {
+            list.selected_row()
+                .and_then(|row| transfers.get(row.index() as usize))
+                .cloned()
+        } else {
+            None
+        };
+        callback(transfer)
+    });
+    dialog.show();
+}
+
+/// Prompts the user to enter a line of text, and provides the entered text
+/// (if any) to `callback`.
+pub fn enter_text<F>(
+    parent: &gtk::Window,
+    title: &str,
+    prompt: &str,
+    default_text: Option<&str>,
+    callback: F,
+) where
+    F: Fn(Option<String>) + 'static,
+{
+    let buttons = [
+        ("Cancel", gtk::ResponseType::Cancel),
+        ("OK", gtk::ResponseType::Accept),
+    ];
+    let flags = gtk::DialogFlags::all();
+    let dialog =
+        gtk::Dialog::with_buttons(Some(title), Some(parent), flags, &buttons);
+
+    let padding = 4;
+    let content = dialog.content_area();
+
+    let prompt_label = gtk::Label::new(Some(prompt));
+    prompt_label.set_margin_top(padding);
+    prompt_label.set_margin_bottom(padding);
+    prompt_label.set_margin_start(padding);
+    prompt_label.set_margin_end(padding);
+
+    let entry = gtk::Entry::new();
+    if let Some(text) = default_text {
+        entry.set_text(text);
+    }
+    entry.set_margin_top(padding);
+    entry.set_margin_bottom(padding);
+    entry.set_margin_start(padding);
+    entry.set_margin_end(padding);
+
+    // Make pressing Enter accept the text and close the dialog.
+    let dlg = dialog.clone();
+    entry.connect_activate(move |_| dlg.response(gtk::ResponseType::Accept));
+
+    content.set_spacing(padding);
+    content.set_orientation(gtk::Orientation::Vertical);
+    content.append(&prompt_label);
+    content.append(&entry);
+
+    dialog.connect_response(move |dlg, response| {
+        dlg.hide();
+        if response == gtk::ResponseType::Accept {
+            callback(Some(entry.text().to_string()))
+        } else {
+            callback(None)
+        }
+    });
+    dialog.show();
+}
+
+/// Asks the user to confirm an action, and provides their answer to
+/// `callback`.
+pub fn confirm<F>(
+    parent: &gtk::Window,
+    title: &str,
+    message: &str,
+    callback: F,
+) where
+    F: Fn(bool) + 'static,
+{
+    let buttons = [
+        ("No", gtk::ResponseType::Reject),
+        ("Yes", gtk::ResponseType::Accept),
+    ];
+    let flags = gtk::DialogFlags::all();
+    let dialog =
+        gtk::Dialog::with_buttons(Some(title), Some(parent), flags, &buttons);
+    dialog.set_default_response(gtk::ResponseType::Accept);
+
+    let label = gtk::Label::builder()
+        .use_markup(false)
+        .wrap(true)
+        .label(message)
+        .margin_bottom(16)
+        .margin_top(16)
+        .margin_start(16)
+        .margin_end(16)
+        .build();
+
+    let content = dialog.content_area();
+    content.append(&label);
+
+    dialog.connect_response(move |dlg, response| {
+        dlg.hide();
+        callback(response == gtk::ResponseType::Accept)
+    });
+    dialog.show();
+}
+
 /// Returns a `gtk::Box` that contains a `gtk::SpinButton` and a `gtk::Label`,
 /// and adds `(train, spin_button)` to the vector `trains`.
 fn add_spinner<'a>(
@@ -288,11 +605,14 @@ fn add_spinner<'a>(
 
 /// Prompts the user to select a file to which data will be saved, and
 /// provides the selected filename (if any) to `callback`.
+///
+/// If `folder` is provided, the dialog starts in this directory.
 pub fn select_file_save<F>(
     window: &gtk::Window,
     title: &str,
     filters: &[gtk::FileFilter],
     default_path: Option<&str>,
+    folder: Option<&std::path::Path>,
     callback: F,
 ) where
     F: Fn(Option<std::path::PathBuf>) + 'static,
@@ -312,6 +632,12 @@ pub fn select_file_save<F>(
     if let Some(path) = default_path {
         dialog.set_current_name(path);
     }
+    if let Some(folder) = folder {
+        let dir = gtk::gio::File::for_path(folder);
+        if let Err(e) = dialog.set_current_folder(Some(&dir)) {
+            log::warn!("Could not open {}: {}", folder.display(), e)
+        }
+    }
 
     // NOTE: we need to clone `dialog` so that we can keep it alive.
     // Otherwise it will be dropped when this function returns, and the dialog
@@ -338,11 +664,14 @@ pub fn select_file_save<F>(
 
 /// Prompts the user to select a file from which data will be read, and
 /// provides the selected filename (if any) to `callback`.
+///
+/// If `folder` is provided, the dialog starts in this directory.
 pub fn select_file_load<F>(
     window: &gtk::Window,
     title: &str,
     filters: &[gtk::FileFilter],
     default_path: Option<&str>,
+    folder: Option<&std::path::Path>,
     callback: F,
 ) where
     F: Fn(Option<std::path::PathBuf>) + 'static,
@@ -361,6 +690,12 @@ pub fn select_file_load<F>(
     if let Some(path) = default_path {
         dialog.set_current_name(path);
     }
+    if let Some(folder) = folder {
+        let dir = gtk::gio::File::for_path(folder);
+        if let Err(e) = dialog.set_current_folder(Some(&dir)) {
+            log::warn!("Could not open {}: {}", folder.display(), e)
+        }
+    }
 
     // NOTE: we need to clone `dialog` so that we can keep it alive.
     // Otherwise it will be dropped when this function returns, and the dialog
@@ -391,10 +726,13 @@ pub fn image_file_filters() -> Vec<gtk::FileFilter> {
     let filter_png = gtk::FileFilter::new();
     filter_png.set_name(Some("PNG images"));
     filter_png.add_mime_type("image/png");
+    let filter_svg = gtk::FileFilter::new();
+    filter_svg.set_name(Some("SVG images"));
+    filter_svg.add_mime_type("image/svg+xml");
     let filter_all = gtk::FileFilter::new();
     filter_all.set_name(Some("All files"));
     filter_all.add_pattern("*");
-    vec![filter_png, filter_all]
+    vec![filter_png, filter_svg, filter_all]
 }
 
 /// Returns the default file filters when loading/saving a game state.
@@ -402,10 +740,72 @@ pub fn game_file_filters() -> Vec<gtk::FileFilter> {
     let filter_game = gtk::FileFilter::new();
     filter_game.set_name(Some("Game files"));
     filter_game.add_pattern("*.game");
+    let filter_bundle = gtk::FileFilter::new();
+    filter_bundle.set_name(Some("Game bundles"));
+    filter_bundle.add_pattern("*.bundle");
     let filter_all = gtk::FileFilter::new();
     filter_all.set_name(Some("All files"));
     filter_all.add_pattern("*");
-    vec![filter_game, filter_all]
+    vec![filter_game, filter_bundle, filter_all]
+}
+
+/// Returns the default file filters when loading a list of company token
+/// placements.
+pub fn token_list_filters() -> Vec<gtk::FileFilter> {
+    let filter_list = gtk::FileFilter::new();
+    filter_list.set_name(Some("Token lists"));
+    filter_list.add_pattern("*.json");
+    filter_list.add_pattern("*.csv");
+    let filter_all = gtk::FileFilter::new();
+    filter_all.set_name(Some("All files"));
+    filter_all.add_pattern("*");
+    vec![filter_list, filter_all]
+}
+
+/// Shows a popover menu at the point `(x, y)` on `parent`, which offers each
+/// item in `items`, and provides the index of the selected item (if any) to
+/// `callback` when the menu is closed.
+pub fn context_menu<F>(
+    parent: &gtk::DrawingArea,
+    x: f64,
+    y: f64,
+    items: &[&str],
+    callback: F,
+) where
+    F: Fn(Option<usize>) + 'static,
+{
+    let menu = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    let popover = gtk::Popover::builder()
+        .child(&menu)
+        .has_arrow(false)
+        .build();
+    popover.set_parent(parent);
+    let rect = gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1);
+    popover.set_pointing_to(Some(&rect));
+
+    let selected = Rc::new(Cell::new(None));
+    items.iter().enumerate().for_each(|(ix, item)| {
+        let label = gtk::Label::builder().label(*item).xalign(0.0).build();
+        let button = gtk::Button::builder()
+            .child(&label)
+            .has_frame(false)
+            .build();
+        let selected = selected.clone();
+        let popover = popover.clone();
+        button.connect_clicked(move |_| {
+            selected.set(Some(ix));
+            popover.popdown();
+        });
+        menu.append(&button);
+    });
+
+    popover.connect_closed(move |popover| {
+        callback(selected.get());
+        // NOTE: the popover cannot be removed while handling this signal.
+        let popover = popover.clone();
+        glib::idle_add_local_once(move || popover.unparent());
+    });
+    popover.popup();
 }
 
 /// A user interface controller for GTK, which draws the game map on a
@@ -414,6 +814,9 @@ pub struct GtkController {
     window: gtk::Window,
     draw_area: gtk::DrawingArea,
     ping_tx: async_channel::Sender<PingDest>,
+    panels: Rc<RefCell<BTreeMap<PanelKind, gtk::Window>>>,
+    status_label: Option<gtk::Label>,
+    history: FileHistory,
 }
 
 impl GtkController {
@@ -426,11 +829,35 @@ impl GtkController {
             window,
             draw_area,
             ping_tx,
+            panels: Default::default(),
+            status_label: None,
+            history: FileHistory::default(),
         }
     }
+
+    /// Displays the status bar in `label`.
+    pub fn with_status_label(mut self, label: gtk::Label) -> Self {
+        self.status_label = Some(label);
+        self
+    }
+
+    /// Returns the scrolled window that contains the drawing area, if any.
+    fn scrolled_window(&self) -> Option<gtk::ScrolledWindow> {
+        self.draw_area
+            .ancestor(gtk::ScrolledWindow::static_type())
+            .and_then(|widget| widget.downcast().ok())
+    }
 }
 
 impl UiController for GtkController {
+    fn file_history(&self) -> &FileHistory {
+        &self.history
+    }
+
+    fn file_history_mut(&mut self) -> &mut FileHistory {
+        &mut self.history
+    }
+
     fn quit(&mut self) {
         self.window.close();
     }
@@ -447,10 +874,45 @@ impl UiController for GtkController {
         self.window.title().map(|gs| gs.to_string())
     }
 
+    fn set_status(&mut self, status: &StatusBar) {
+        if let Some(label) = &self.status_label {
+            label.set_text(&status.text())
+        }
+    }
+
     fn resize(&mut self, width: i32, height: i32) {
         self.draw_area.set_size_request(width, height);
     }
 
+    fn viewport(&self) -> Option<(f64, f64)> {
+        let win = self.scrolled_window()?;
+        Some((win.hadjustment().value(), win.vadjustment().value()))
+    }
+
+    fn set_viewport(&mut self, x: f64, y: f64) {
+        if let Some(win) = self.scrolled_window() {
+            // NOTE: wait until the drawing area has been resized, otherwise
+            // the offsets will be clamped to the previous map size.
+            glib::idle_add_local_once(move || {
+                win.hadjustment().set_value(x);
+                win.vadjustment().set_value(y);
+            });
+        }
+    }
+
+    fn window_size(&self) -> Option<(i32, i32)> {
+        let (width, height) = (self.window.width(), self.window.height());
+        if width > 0 && height > 0 {
+            Some((width, height))
+        } else {
+            None
+        }
+    }
+
+    fn set_window_size(&mut self, width: i32, height: i32) {
+        self.window.set_default_size(width, height)
+    }
+
     fn ping_tx(&self) -> PingSender {
         PingSender::Async(self.ping_tx.clone())
     }
@@ -469,6 +931,13 @@ impl UiController for GtkController {
         select_index(&self.window, title, strings, callback)
     }
 
+    fn search_index<F>(&mut self, title: &str, strings: &[&str], callback: F)
+    where
+        F: Fn(Option<usize>) + 'static,
+    {
+        search_index(&self.window, title, strings, callback)
+    }
+
     fn select_trains<F>(&mut self, game: &dyn Game, title: &str, callback: F)
     where
         Self: Sized,
@@ -477,12 +946,45 @@ impl UiController for GtkController {
         select_trains(&self.window, game, title, callback)
     }
 
-    fn select_phase<F>(&mut self, game: &dyn Game, callback: F)
-    where
+    fn select_phase<F>(
+        &mut self,
+        game: &dyn Game,
+        effects: &[PhaseEffects],
+        callback: F,
+    ) where
         Self: Sized,
         F: Fn(Option<usize>) + 'static,
     {
-        select_phase(&self.window, game, callback)
+        select_phase(&self.window, game, effects, callback)
+    }
+
+    fn select_train_transfer<F>(&mut self, supply: &TrainSupply, callback: F)
+    where
+        Self: Sized,
+        F: Fn(Option<TrainTransfer>) + 'static,
+    {
+        select_train_transfer(&self.window, supply, callback)
+    }
+
+    fn enter_text<F>(
+        &mut self,
+        title: &str,
+        prompt: &str,
+        default_text: Option<&str>,
+        callback: F,
+    ) where
+        Self: Sized,
+        F: Fn(Option<String>) + 'static,
+    {
+        enter_text(&self.window, title, prompt, default_text, callback)
+    }
+
+    fn confirm<F>(&mut self, title: &str, message: &str, callback: F)
+    where
+        Self: Sized,
+        F: Fn(bool) + 'static,
+    {
+        confirm(&self.window, title, message, callback)
     }
 
     fn select_screenshot_save<F>(
@@ -500,6 +1002,7 @@ impl UiController for GtkController {
             title,
             &filters,
             default_path,
+            None,
             callback,
         )
     }
@@ -519,6 +1022,7 @@ impl UiController for GtkController {
             title,
             &filters,
             default_path,
+            self.history.default_dir(),
             callback,
         )
     }
@@ -538,6 +1042,23 @@ impl UiController for GtkController {
             title,
             &filters,
             default_path,
+            self.history.default_dir(),
+            callback,
+        )
+    }
+
+    fn select_token_list<F>(&mut self, title: &str, callback: F)
+    where
+        Self: Sized,
+        F: Fn(Option<std::path::PathBuf>) + 'static,
+    {
+        let filters = token_list_filters();
+        select_file_load(
+            &self.window,
+            title,
+            &filters,
+            None,
+            self.history.default_dir(),
             callback,
         )
     }
@@ -646,4 +1167,225 @@ impl UiController for GtkController {
 
         dialog.show();
     }
+    fn show_context_menu<F>(
+        &mut self,
+        x: f64,
+        y: f64,
+        items: &[&str],
+        callback: F,
+    ) where
+        F: Fn(Option<usize>) + 'static,
+    {
+        context_menu(&self.draw_area, x, y, items, callback)
+    }
+
+    fn show_error(&mut self, title: &str, message: &str) {
+        let buttons = [("OK", gtk::ResponseType::Accept)];
+        let flags = gtk::DialogFlags::all();
+        let dialog = gtk::Dialog::with_buttons(
+            Some(title),
+            Some(&self.window),
+            flags,
+            &buttons,
+        );
+
+        let label = gtk::Label::builder()
+            .use_markup(false)
+            .selectable(true)
+            .wrap(true)
+            .label(message)
+            .margin_bottom(16)
+            .margin_top(16)
+            .margin_start(16)
+            .margin_end(16)
+            .build();
+
+        let content = dialog.content_area();
+        content.append(&label);
+
+        dialog.connect_response(move |dlg, _response| {
+            dlg.hide();
+        });
+
+        dialog.show();
+    }
+
+    fn on_file_drop<F>(&mut self, callback: F)
+    where
+        F: Fn(std::path::PathBuf) + 'static,
+    {
+        let target = gtk::DropTarget::new(
+            gtk::gio::File::static_type(),
+            gtk::gdk::DragAction::COPY,
+        );
+        target.connect_drop(move |_target, value, _x, _y| {
+            let path_opt = value
+                .get::<gtk::gio::File>()
+                .ok()
+                .and_then(|file| file.path());
+            if let Some(path) = path_opt {
+                callback(path);
+                true
+            } else {
+                false
+            }
+        });
+        self.window.add_controller(target);
+    }
+
+    fn copy_image(
+        &mut self,
+        image: &cairo::ImageSurface,
+    ) -> Result<(), String> {
+        if image.format() != cairo::Format::ARgb32 {
+            return Err("Only ARGB32 images can be copied".to_string());
+        }
+        // NOTE: cairo stores each ARGB32 pixel as a native-endian 32-bit
+        // value with premultiplied alpha.
+        let format = if cfg!(target_endian = "little") {
+            gtk::gdk::MemoryFormat::B8g8r8a8Premultiplied
+        } else {
+            gtk::gdk::MemoryFormat::A8r8g8b8Premultiplied
+        };
+        image.flush();
+        let mut data = vec![];
+        image
+            .with_data(|bytes| data.extend_from_slice(bytes))
+            .map_err(|e| e.to_string())?;
+        let texture = gtk::gdk::MemoryTexture::new(
+            image.width(),
+            image.height(),
+            format,
+            &glib::Bytes::from_owned(data),
+            image.stride() as usize,
+        );
+        self.window.clipboard().set_texture(&texture);
+        Ok(())
+    }
+
+    fn show_journal(&mut self, journal: &Journal) {
+        let buttons = [("OK", gtk::ResponseType::Accept)];
+        let flags = gtk::DialogFlags::all();
+        let dialog = gtk::Dialog::with_buttons(
+            Some("Game journal"),
+            Some(&self.window),
+            flags,
+            &buttons,
+        );
+
+        let text = if journal.is_empty() {
+            "No moves have been made.".to_string()
+        } else {
+            journal
+                .entries()
+                .iter()
+                .enumerate()
+                .map(|(ix, entry)| format!("{}. {}", ix + 1, entry))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        let label = gtk::Label::builder()
+            .use_markup(false)
+            .selectable(true)
+            .label(text)
+            .halign(gtk::Align::Start)
+            .valign(gtk::Align::Start)
+            .margin_bottom(16)
+            .margin_top(16)
+            .margin_start(16)
+            .margin_end(16)
+            .build();
+        let scroll = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .min_content_width(400)
+            .min_content_height(300)
+            .hexpand(true)
+            .vexpand(true)
+            .child(&label)
+            .build();
+
+        let content = dialog.content_area();
+        content.append(&scroll);
+
+        dialog.connect_response(move |dlg, _response| {
+            dlg.hide();
+        });
+
+        dialog.show();
+    }
+
+    fn show_panel(&mut self, title: &str, panel: &Panel) {
+        let kind = panel.kind();
+        let existing = self.panels.borrow().get(&kind).cloned();
+        let window = existing.unwrap_or_else(|| {
+            let window = gtk::Window::builder()
+                .transient_for(&self.window)
+                .modal(false)
+                .default_width(400)
+                .default_height(300)
+                .build();
+            // Forget this panel when the user closes its window.
+            let panels = Rc::clone(&self.panels);
+            window.connect_close_request(move |_window| {
+                panels.borrow_mut().remove(&kind);
+                glib::Propagation::Proceed
+            });
+            self.panels.borrow_mut().insert(kind, window.clone());
+            window
+        });
+        window.set_title(Some(title));
+        window.set_child(Some(&panel_table(panel)));
+        window.show();
+    }
+
+    fn close_panel(&mut self, kind: PanelKind) {
+        let window = self.panels.borrow_mut().remove(&kind);
+        if let Some(window) = window {
+            window.close();
+        }
+    }
+
+    fn is_panel_open(&self, kind: PanelKind) -> bool {
+        self.panels.borrow().contains_key(&kind)
+    }
+}
+
+/// Returns a scrollable table that displays the contents of `panel`.
+fn panel_table(panel: &Panel) -> gtk::ScrolledWindow {
+    let (headings, rows) = panel.table();
+    let grid = gtk::Grid::builder()
+        .column_spacing(16)
+        .row_spacing(4)
+        .margin_bottom(16)
+        .margin_top(16)
+        .margin_start(16)
+        .margin_end(16)
+        .build();
+    headings.iter().enumerate().for_each(|(col, heading)| {
+        let label = gtk::Label::builder()
+            .use_markup(true)
+            .label(format!("<b>{}</b>", heading))
+            .halign(gtk::Align::Start)
+            .build();
+        grid.attach(&label, col as i32, 0, 1, 1);
+    });
+    rows.iter().enumerate().for_each(|(row, cells)| {
+        cells.iter().enumerate().for_each(|(col, text)| {
+            let label = gtk::Label::builder()
+                .use_markup(false)
+                .selectable(true)
+                .label(text.as_str())
+                .halign(gtk::Align::Start)
+                .build();
+            grid.attach(&label, col as i32, row as i32 + 1, 1, 1);
+        });
+    });
+    gtk::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Automatic)
+        .min_content_width(400)
+        .min_content_height(300)
+        .hexpand(true)
+        .vexpand(true)
+        .child(&grid)
+        .build()
 }
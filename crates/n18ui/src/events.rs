@@ -0,0 +1,118 @@
+//! Notifies registered listeners when the map or the game state changes.
+//!
+//! Changes are made through methods of [Assets](crate::Assets) such as
+//! [place_tile](crate::Assets::place_tile) and
+//! [record_token_changes](crate::Assets::record_token_changes), which emit a
+//! [MapEvent] for each change.
+//! Listeners are registered with [Assets::subscribe](crate::Assets::subscribe)
+//! and can be used to, e.g., save the game automatically, redraw only the
+//! affected hexes, or share each change with other players.
+
+use n18hex::RotateCW;
+use n18map::{HexAddress, TokenChange};
+
+/// Describes a change to the map or the game state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MapEvent {
+    /// A tile was placed on a map hex, replacing the `previous` tile (if
+    /// any).
+    TilePlaced {
+        addr: HexAddress,
+        tile: String,
+        rotation: RotateCW,
+        previous: Option<String>,
+    },
+    /// The tile on a map hex was rotated.
+    TileRotated {
+        addr: HexAddress,
+        rotation: RotateCW,
+    },
+    /// The tile on a map hex was removed.
+    TileRemoved { addr: HexAddress, tile: String },
+    /// The tiles on several map hexes were changed at once (e.g., by cutting
+    /// or pasting a selection of hexes).
+    HexesChanged(Vec<HexAddress>),
+    /// Tokens were placed, removed, or replaced.
+    TokensChanged(Vec<TokenChange>),
+    /// The game moved to a new phase.
+    PhaseChanged { phase_ix: usize, name: String },
+    /// The map was replaced, such as when starting a new game or loading a
+    /// saved game, or its tile definitions were changed.
+    MapReset,
+}
+
+impl MapEvent {
+    /// Returns the map hexes that were affected by this change, or `None`
+    /// if the entire map may have been affected.
+    pub fn hexes(&self) -> Option<Vec<HexAddress>> {
+        use MapEvent::*;
+        match self {
+            TilePlaced { addr, .. }
+            | TileRotated { addr, .. }
+            | TileRemoved { addr, .. } => Some(vec![*addr]),
+            HexesChanged(addrs) => Some(addrs.clone()),
+            TokensChanged(changes) => {
+                let mut addrs: Vec<HexAddress> =
+                    changes.iter().map(|change| change.addr).collect();
+                addrs.sort();
+                addrs.dedup();
+                Some(addrs)
+            }
+            PhaseChanged { .. } | MapReset => None,
+        }
+    }
+}
+
+/// Identifies a listener that was registered with [Listeners::add].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ListenerId(usize);
+
+/// A function that is called with each [MapEvent].
+type Listener = Box<dyn FnMut(&MapEvent)>;
+
+/// The listeners that are notified of each [MapEvent].
+#[derive(Default)]
+pub struct Listeners {
+    next_id: usize,
+    listeners: Vec<(ListenerId, Listener)>,
+}
+
+impl Listeners {
+    /// Registers a listener, and returns an identifier that can be used to
+    /// remove this listener.
+    pub fn add<F>(&mut self, listener: F) -> ListenerId
+    where
+        F: FnMut(&MapEvent) + 'static,
+    {
+        let id = ListenerId(self.next_id);
+        self.next_id += 1;
+        self.listeners.push((id, Box::new(listener)));
+        id
+    }
+
+    /// Removes a listener, and returns `false` if there was no such
+    /// listener.
+    pub fn remove(&mut self, id: ListenerId) -> bool {
+        let count = self.listeners.len();
+        self.listeners.retain(|(listener_id, _)| *listener_id != id);
+        self.listeners.len() < count
+    }
+
+    /// Returns the number of registered listeners.
+    pub fn len(&self) -> usize {
+        self.listeners.len()
+    }
+
+    /// Returns `true` if there are no registered listeners.
+    pub fn is_empty(&self) -> bool {
+        self.listeners.is_empty()
+    }
+
+    /// Notifies each listener of an event, in the order that they were
+    /// registered.
+    pub fn notify(&mut self, event: &MapEvent) {
+        for (_id, listener) in self.listeners.iter_mut() {
+            listener(event)
+        }
+    }
+}
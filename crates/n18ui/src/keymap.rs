@@ -1,11 +1,17 @@
 use gdk4 as gdk;
 
+use n18map::HexAddress;
+
+use crate::lang::Strings;
+use crate::panel::{HexSummary, Panel, PanelKind, TileInventory};
 use crate::state::edit_tokens::EditTokens;
 use crate::state::replace_tile::ReplaceTile;
-use crate::state::search::SelectCompany;
+use crate::state::screenshot::{Screenshot, SCALES};
+use crate::state::search::{Goal, SelectCompany, SelectOpenTrain};
+use crate::state::select::Select;
 use crate::{
-    Action, Assets, Canvas, Controller, PingDest, Sender, State, UiAction,
-    UiController, UiResponse,
+    Action, Assets, Canvas, Controller, Region, Responder, Sender, State,
+    UiAction, UiController, UiResponse,
 };
 
 /// Type alias for key identifiers.
@@ -20,6 +26,67 @@ pub struct ButtonPress {
     /// The button that was clicked; `1` typically corresponds to the left
     /// button, `2` to the middle button, and `3` to the right button.
     pub button: u32,
+    /// Whether the Control key was also pressed.
+    pub ctrl: bool,
+    /// Whether the Alt key was also pressed.
+    pub alt: bool,
+    /// Whether the Shift key was also pressed.
+    pub shift: bool,
+}
+
+impl ButtonPress {
+    /// The primary (typically left) mouse button.
+    pub const PRIMARY: u32 = 1;
+    /// The middle mouse button.
+    pub const MIDDLE: u32 = 2;
+    /// The secondary (typically right) mouse button.
+    pub const SECONDARY: u32 = 3;
+}
+
+/// Describes the mouse being dragged while a button is held down.
+pub struct ButtonDrag {
+    /// The x coordinate where the drag started.
+    pub x0: f64,
+    /// The y coordinate where the drag started.
+    pub y0: f64,
+    /// The current x coordinate.
+    pub x: f64,
+    /// The current y coordinate.
+    pub y: f64,
+    /// The button that is held down; see [ButtonPress::button].
+    pub button: u32,
+    /// Whether the Shift key was also pressed.
+    pub shift: bool,
+    /// Whether the button has been released, ending the drag.
+    pub finished: bool,
+}
+
+impl ButtonDrag {
+    /// The minimum distance (in pixels) that the mouse must be dragged
+    /// before the drag is treated as selecting an area, rather than a click.
+    pub const MIN_DISTANCE: f64 = 4.0;
+
+    /// Returns the rectangle defined by the starting and current positions,
+    /// as `(x0, y0, x1, y1)`.
+    pub fn rect(&self) -> (f64, f64, f64, f64) {
+        (self.x0, self.y0, self.x, self.y)
+    }
+
+    /// Returns `true` if the mouse has been dragged far enough to select an
+    /// area.
+    pub fn is_area(&self) -> bool {
+        let dx = self.x - self.x0;
+        let dy = self.y - self.y0;
+        (dx * dx + dy * dy).sqrt() >= Self::MIN_DISTANCE
+    }
+}
+
+/// Describes the mouse pointer moving, whether or not a button is held down.
+pub struct PointerMotion {
+    /// The x coordinate of the pointer.
+    pub x: f64,
+    /// The y coordinate of the pointer.
+    pub y: f64,
 }
 
 /// Describes a keyboard key being pressed.
@@ -50,9 +117,405 @@ impl From<(Key, gdk::ModifierType)> for KeyPress {
     }
 }
 
+/// A named action that is triggered by a key binding, which allows the
+/// action to be listed in (and executed from) the command palette.
+#[derive(Clone, Copy, Debug)]
+pub struct Command {
+    /// A short description of the action.
+    pub name: &'static str,
+    /// The key that triggers the action.
+    pub key: Key,
+    /// Whether the Control key must also be pressed.
+    pub ctrl: bool,
+    /// Whether the Shift key must also be pressed.
+    pub shift: bool,
+}
+
+impl Command {
+    /// Defines an action that is triggered by pressing `key`.
+    pub const fn key(name: &'static str, key: Key) -> Self {
+        Command {
+            name,
+            key,
+            ctrl: false,
+            shift: false,
+        }
+    }
+
+    /// Defines an action that is triggered by pressing `Ctrl` and `key`.
+    pub const fn ctrl(name: &'static str, key: Key) -> Self {
+        Command {
+            name,
+            key,
+            ctrl: true,
+            shift: false,
+        }
+    }
+
+    /// Defines an action that is triggered by pressing `Ctrl`, `Shift`, and
+    /// `key`.
+    pub const fn ctrl_shift(name: &'static str, key: Key) -> Self {
+        Command {
+            name,
+            key,
+            ctrl: true,
+            shift: true,
+        }
+    }
+
+    /// Returns the key press that triggers this action.
+    pub fn key_press(&self) -> KeyPress {
+        KeyPress {
+            key: self.key,
+            ctrl: self.ctrl,
+            alt: false,
+            shift: self.shift,
+        }
+    }
+
+    /// Returns the key binding for this action, such as `Ctrl+s`.
+    pub fn shortcut(&self) -> String {
+        let key = self
+            .key
+            .name()
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| "?".to_string());
+        match (self.ctrl, self.shift) {
+            (true, true) => format!("Ctrl+Shift+{}", key),
+            (true, false) => format!("Ctrl+{}", key),
+            (false, true) => format!("Shift+{}", key),
+            (false, false) => key,
+        }
+    }
+
+    /// Returns the text that describes this action in the command palette,
+    /// translated into the chosen language.
+    pub fn label(&self, strings: &Strings) -> String {
+        format!("{} ({})", strings.tr(self.name), self.shortcut())
+    }
+}
+
+/// Binds a mouse button, and any modifier keys, to a named action (see
+/// [Command]).
+///
+/// When the button is clicked and the named action is available in the
+/// current UI state, the click is first handled as a plain click of the
+/// primary button (e.g., to select the hex or token space under the mouse
+/// pointer), and the action is then triggered as though its key binding had
+/// been pressed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ButtonBinding {
+    /// The name of the action; see [Command::name].
+    pub name: &'static str,
+    /// The button that triggers the action; see [ButtonPress::button].
+    pub button: u32,
+    /// Whether the Control key must also be pressed.
+    pub ctrl: bool,
+    /// Whether the Shift key must also be pressed.
+    pub shift: bool,
+}
+
+impl ButtonBinding {
+    /// Binds the action `name` to clicking `button`.
+    pub const fn button(name: &'static str, button: u32) -> Self {
+        ButtonBinding {
+            name,
+            button,
+            ctrl: false,
+            shift: false,
+        }
+    }
+
+    /// Binds the action `name` to clicking `button` while pressing `Ctrl`.
+    pub const fn ctrl(name: &'static str, button: u32) -> Self {
+        ButtonBinding {
+            name,
+            button,
+            ctrl: true,
+            shift: false,
+        }
+    }
+
+    /// Binds the action `name` to clicking `button` while pressing `Shift`.
+    pub const fn shift(name: &'static str, button: u32) -> Self {
+        ButtonBinding {
+            name,
+            button,
+            ctrl: false,
+            shift: true,
+        }
+    }
+
+    /// Returns `true` if this binding is triggered by the button press
+    /// `event`.
+    pub fn matches(&self, event: &ButtonPress) -> bool {
+        self.button == event.button
+            && self.ctrl == event.ctrl
+            && self.shift == event.shift
+    }
+
+    /// Returns the mouse binding for this action, such as `Ctrl+Click`.
+    pub fn shortcut(&self) -> String {
+        let button = match self.button {
+            ButtonPress::PRIMARY => "Click".to_string(),
+            ButtonPress::MIDDLE => "Middle-click".to_string(),
+            ButtonPress::SECONDARY => "Right-click".to_string(),
+            button => format!("Button{}", button),
+        };
+        match (self.ctrl, self.shift) {
+            (true, true) => format!("Ctrl+Shift+{}", button),
+            (true, false) => format!("Ctrl+{}", button),
+            (false, true) => format!("Shift+{}", button),
+            (false, false) => button,
+        }
+    }
+}
+
+/// The default mouse bindings, which trigger actions defined by the
+/// [Global] and [EditTokensMode] keymaps.
+const DEFAULT_BUTTON_BINDINGS: &[ButtonBinding] = &[
+    ButtonBinding::button(
+        "Show actions for the active hex",
+        ButtonPress::SECONDARY,
+    ),
+    ButtonBinding::ctrl("Remove the token", ButtonPress::PRIMARY),
+];
+
+/// Determines whether an action is available in the current UI state.
+type IsAvailable = fn(&State) -> bool;
+
+/// The actions defined by the [Global] keymap, other than opening the
+/// command palette.
+/// Each action is paired with a predicate that determines whether it is
+/// available in the current UI state.
+const GLOBAL_COMMANDS: &[(Command, IsAvailable)] = &[
+    (Command::key("Quit", Key::q), any_state),
+    (Command::ctrl("New game", Key::n), any_state),
+    (Command::ctrl("Load game", Key::o), any_state),
+    (Command::ctrl_shift("Load a recent game", Key::o), any_state),
+    (Command::key("Reload the current game", Key::F5), any_state),
+    (Command::ctrl("Save game", Key::s), not_start),
+    (
+        Command::ctrl("Rename the saved game file", Key::r),
+        not_start,
+    ),
+    (Command::key("Save screenshot", Key::s), not_start),
+    (Command::ctrl("Export map image", Key::e), not_start),
+    (Command::ctrl("Export game bundle", Key::b), not_start),
+    (Command::ctrl("Import game bundle", Key::i), any_state),
+    (
+        Command::ctrl("Replace company tokens", Key::t),
+        State::is_default_state,
+    ),
+    (
+        Command::ctrl_shift("Import company tokens from a list", Key::t),
+        State::is_default_state,
+    ),
+    (
+        Command::ctrl("Undo token changes", Key::z),
+        State::is_default_state,
+    ),
+    (Command::key("Show game journal", Key::j), not_start),
+    (
+        Command::key("Show actions for the active hex", Key::Menu),
+        not_start,
+    ),
+    (
+        Command::ctrl("Add a note to the journal", Key::m),
+        not_start,
+    ),
+    (Command::ctrl("Set the game title", Key::g), not_start),
+    (
+        Command::ctrl("Select the interface language", Key::k),
+        any_state,
+    ),
+    (
+        Command::ctrl_shift("Select the map theme", Key::k),
+        any_state,
+    ),
+    (Command::ctrl("Toggle the journal panel", Key::j), not_start),
+    (
+        Command::ctrl("Toggle the tile inventory panel", Key::l),
+        not_start,
+    ),
+    (Command::key("Zoom in", Key::plus), not_start),
+    (Command::key("Zoom out", Key::minus), not_start),
+    (Command::ctrl("Zoom to fit the window", Key::_0), not_start),
+    (
+        Command::ctrl_shift("Rotate the map view clockwise", Key::r),
+        not_start,
+    ),
+    (
+        Command::ctrl_shift("Mirror the map view", Key::m),
+        not_start,
+    ),
+    (
+        Command::ctrl_shift("Copy the map view to the clipboard", Key::c),
+        not_start,
+    ),
+    (
+        Command::ctrl_shift("Show or hide the background image", Key::h),
+        not_start,
+    ),
+    (
+        Command::ctrl_shift("Move the background image left", Key::Left),
+        not_start,
+    ),
+    (
+        Command::ctrl_shift("Move the background image right", Key::Right),
+        not_start,
+    ),
+    (
+        Command::ctrl_shift("Move the background image up", Key::Up),
+        not_start,
+    ),
+    (
+        Command::ctrl_shift("Move the background image down", Key::Down),
+        not_start,
+    ),
+    (
+        Command::ctrl_shift("Enlarge the background image", Key::Page_Up),
+        not_start,
+    ),
+    (
+        Command::ctrl_shift("Shrink the background image", Key::Page_Down),
+        not_start,
+    ),
+    (
+        Command::ctrl_shift("Remove the background image", Key::Delete),
+        not_start,
+    ),
+];
+
+/// The actions defined by the [DefaultMode] keymap.
+const DEFAULT_COMMANDS: &[Command] = &[
+    Command::key("Place or replace a tile", Key::e),
+    Command::key("Upgrade the tile", Key::u),
+    Command::key("Rotate the tile anti-clockwise", Key::less),
+    Command::key("Rotate the tile clockwise", Key::greater),
+    Command::key("Remove the tile", Key::Delete),
+    Command::key("Reset the hex to its initial tile", Key::x),
+    Command::key("Edit tokens", Key::t),
+    Command::key("Find routes for a company", Key::r),
+    Command::key("Find open routes from this city", Key::o),
+    Command::key("Rank token spaces for a company", Key::v),
+    Command::key("Show the route graph for a company", Key::w),
+    Command::key("Show a company's network", Key::f),
+    Command::key("Start or advance the operating round", Key::a),
+    Command::ctrl("End the operating round", Key::a),
+    Command::key("Select the game phase", Key::p),
+    Command::key("Buy trains from the bank", Key::b),
+    Command::key("Form the national company", Key::n),
+    Command::key("Toggle hex coordinates", Key::c),
+    Command::key("Save a region of the map", Key::i),
+    Command::key("Inspect the active hex", Key::h),
+    Command::ctrl("Paste tiles", Key::v),
+    Command::key("Start or stop planning tiles", Key::l),
+    Command::key("Show or hide the planned tiles", Key::k),
+    Command::ctrl("Place the planned tiles", Key::Return),
+    Command::ctrl("Discard the planned tiles", Key::Delete),
+];
+
+/// The actions defined by the [SelectMode] keymap.
+const SELECT_COMMANDS: &[Command] = &[
+    Command::key("Finish selecting hexes", Key::Return),
+    Command::ctrl("Copy the selection", Key::c),
+    Command::ctrl("Cut the selection", Key::x),
+    Command::ctrl("Paste tiles", Key::v),
+    Command::key("Rotate the selection anti-clockwise", Key::less),
+    Command::key("Rotate the selection clockwise", Key::greater),
+    Command::key("Remove the selected tiles", Key::Delete),
+];
+
+/// The actions defined by the [ScreenshotMode] keymap.
+const SCREENSHOT_COMMANDS: &[Command] = &[
+    Command::key("Finish saving map regions", Key::Return),
+    Command::key("Save regions at 1x scale", Key::_1),
+    Command::key("Save regions at 2x scale", Key::_2),
+    Command::key("Save regions at 3x scale", Key::_3),
+    Command::key("Save regions at 4x scale", Key::_4),
+    Command::key("Switch between saving and copying regions", Key::c),
+];
+
+/// The actions defined by the [FoundRoutesMode] keymap.
+const FOUND_ROUTES_COMMANDS: &[Command] = &[
+    Command::key("Finish showing routes", Key::Return),
+    Command::key("Highlight the previous route", Key::Left),
+    Command::key("Highlight the next route", Key::Right),
+    Command::key("Step through the route's stops", Key::v),
+    Command::key("Toggle the route summary panel", Key::p),
+    Command::key("Pay dividends", Key::d),
+];
+
+/// The actions defined by the [FoundRoutesMode] keymap when stepping
+/// through a route.
+const STEP_ROUTE_COMMANDS: &[Command] = &[
+    Command::key("Finish showing routes", Key::Return),
+    Command::key("Highlight the previous stop", Key::Left),
+    Command::key("Highlight the next stop", Key::Right),
+    Command::key("Step through the previous route", Key::Up),
+    Command::key("Step through the next route", Key::Down),
+    Command::key("Stop stepping through the route", Key::v),
+    Command::key("Toggle the route summary panel", Key::p),
+    Command::key("Pay dividends", Key::d),
+];
+
+/// The actions defined by the [RankedTokenSpacesMode] keymap.
+const RANKED_TOKEN_SPACES_COMMANDS: &[Command] = &[
+    Command::key("Finish showing token spaces", Key::Return),
+    Command::key("Highlight the next best token space", Key::Right),
+    Command::key("Highlight the next worst token space", Key::Left),
+];
+
+/// The actions defined by the [RouteGraphMode] keymap.
+const ROUTE_GRAPH_COMMANDS: &[Command] =
+    &[Command::key("Finish showing the route graph", Key::Return)];
+
+/// The actions defined by the [CompanyNetworkMode] keymap.
+const COMPANY_NETWORK_COMMANDS: &[Command] = &[Command::key(
+    "Finish showing the company network",
+    Key::Return,
+)];
+
+/// The actions defined by the [EditTokensMode] keymap.
+const EDIT_TOKENS_COMMANDS: &[Command] = &[
+    Command::key("Save token changes", Key::Return),
+    Command::key("Discard token changes", Key::Escape),
+    Command::key("Select the previous token space", Key::Left),
+    Command::key("Select the next token space", Key::Right),
+    Command::key("Place the next token", Key::Up),
+    Command::key("Place the previous token", Key::Down),
+    Command::key("Remove the token", Key::Delete),
+];
+
+/// The actions defined by the [ReplaceTileMode] keymap.
+const REPLACE_TILE_COMMANDS: &[Command] = &[
+    Command::key("Place the candidate tile", Key::Return),
+    Command::key("Cancel tile placement", Key::Escape),
+    Command::key("Show the next candidate tile", Key::Up),
+    Command::key("Show the previous candidate tile", Key::Down),
+    Command::key("Rotate the candidate anti-clockwise", Key::less),
+    Command::key("Rotate the candidate clockwise", Key::greater),
+    Command::key("Toggle the original tile", Key::o),
+];
+
+fn any_state(_state: &State) -> bool {
+    true
+}
+
+fn not_start(state: &State) -> bool {
+    state.as_start().is_none()
+}
+
 pub trait Submap {
     fn name(&self) -> &str;
 
+    /// Returns the named actions that this keymap provides in the current UI
+    /// state, which are listed in the command palette.
+    fn commands(&self, _state: &State) -> Vec<Command> {
+        vec![]
+    }
+
     fn handle_key_press(
         &self,
         _assets: &mut Assets,
@@ -76,10 +539,51 @@ pub trait Submap {
     ) -> Option<(UiResponse, Option<State>)> {
         None
     }
+
+    fn handle_button_drag(
+        &self,
+        _assets: &mut Assets,
+        _controller: &mut Controller,
+        _state: &mut State,
+        _canvas: &mut Canvas,
+        _sender: &Sender<UiAction>,
+        _event: &ButtonDrag,
+    ) -> Option<(UiResponse, Option<State>)> {
+        None
+    }
+
+    fn handle_pointer_motion(
+        &self,
+        _assets: &mut Assets,
+        _controller: &mut Controller,
+        _state: &mut State,
+        _canvas: &mut Canvas,
+        _sender: &Sender<UiAction>,
+        _event: &PointerMotion,
+    ) -> Option<(UiResponse, Option<State>)> {
+        None
+    }
+}
+
+/// Returns the address of the map hex that contains the point `(x, y)`, if
+/// any.
+fn hex_at(assets: &Assets, x: f64, y: f64) -> Option<HexAddress> {
+    let hex = &assets.hex;
+    let map = &assets.map;
+    let ctx = hex.context();
+    map.hex_address_iter()
+        .find(|addr| {
+            let m = map.prepare_to_draw(**addr, hex, ctx);
+            hex.define_boundary(ctx);
+            ctx.set_matrix(m);
+            ctx.in_fill(x, y).unwrap()
+        })
+        .copied()
 }
 
 pub struct Keymap {
     submaps: Vec<Box<dyn Submap>>,
+    buttons: Vec<ButtonBinding>,
 }
 
 impl Default for Keymap {
@@ -90,20 +594,103 @@ impl Default for Keymap {
         submaps.push(Box::new(DefaultMode {}));
         submaps.push(Box::new(ReplaceTileMode {}));
         submaps.push(Box::new(EditTokensMode {}));
+        submaps.push(Box::new(SelectMode {}));
+        submaps.push(Box::new(ScreenshotMode {}));
         submaps.push(Box::new(FoundRoutesMode {}));
-        Keymap { submaps }
+        submaps.push(Box::new(RankedTokenSpacesMode {}));
+        submaps.push(Box::new(RouteGraphMode {}));
+        submaps.push(Box::new(CompanyNetworkMode {}));
+        let buttons = DEFAULT_BUTTON_BINDINGS.to_vec();
+        Keymap { submaps, buttons }
     }
 }
 
 impl Keymap {
     pub fn empty() -> Self {
-        Keymap { submaps: vec![] }
+        Keymap {
+            submaps: vec![],
+            buttons: vec![],
+        }
     }
 
     pub fn add_submap(&mut self, submap: Box<dyn Submap>) {
         self.submaps.push(submap);
     }
 
+    /// Returns the mouse bindings, in the order that they were added.
+    pub fn button_bindings(&self) -> &[ButtonBinding] {
+        &self.buttons
+    }
+
+    /// Binds a mouse button, and any modifier keys, to a named action,
+    /// replacing any existing binding for the same button and modifier keys.
+    pub fn bind_button(&mut self, binding: ButtonBinding) {
+        self.unbind_button(binding.button, binding.ctrl, binding.shift);
+        self.buttons.push(binding);
+    }
+
+    /// Removes the binding (if any) for a mouse button and modifier keys,
+    /// and returns `true` if a binding was removed.
+    pub fn unbind_button(
+        &mut self,
+        button: u32,
+        ctrl: bool,
+        shift: bool,
+    ) -> bool {
+        let count = self.buttons.len();
+        self.buttons.retain(|binding| {
+            (binding.button, binding.ctrl, binding.shift)
+                != (button, ctrl, shift)
+        });
+        self.buttons.len() != count
+    }
+
+    /// Returns the named actions that can be applied to the active hex in
+    /// the current UI state, which are offered in the hex context menu (see
+    /// [UiState::hex_actions](crate::UiState::hex_actions)).
+    pub fn hex_commands(
+        &self,
+        assets: &Assets,
+        state: &State,
+    ) -> Vec<Command> {
+        let Some(addr) = state.active_hex() else {
+            return vec![];
+        };
+        let commands = self.commands(state);
+        state
+            .as_ref()
+            .hex_actions(assets, addr)
+            .iter()
+            .filter_map(|action| {
+                let name = action.command_name();
+                commands.iter().find(|command| command.name == name)
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Returns the action that is bound to the button press `event`, if the
+    /// action is available in the current UI state.
+    fn bound_command(
+        &self,
+        state: &State,
+        event: &ButtonPress,
+    ) -> Option<Command> {
+        let binding = self.buttons.iter().find(|b| b.matches(event))?;
+        self.commands(state)
+            .into_iter()
+            .find(|command| command.name == binding.name)
+    }
+
+    /// Returns the named actions that are available in the current UI
+    /// state.
+    pub fn commands(&self, state: &State) -> Vec<Command> {
+        self.submaps
+            .iter()
+            .flat_map(|submap| submap.commands(state))
+            .collect()
+    }
+
     pub fn handle_key_press(
         &self,
         assets: &mut Assets,
@@ -129,8 +716,77 @@ impl Keymap {
         sender: &Sender<UiAction>,
         event: &ButtonPress,
     ) -> Option<(UiResponse, Option<State>)> {
-        self.submaps.iter().find_map(|submap| {
+        let Some(command) = self.bound_command(state, event) else {
+            return self.submaps.iter().find_map(|submap| {
+                submap.handle_button_press(
+                    assets, controller, state, canvas, sender, event,
+                )
+            });
+        };
+        // Handle this as a plain click, and only trigger the action if the
+        // click selected a hex or token space in the current UI state.
+        let click = ButtonPress {
+            x: event.x,
+            y: event.y,
+            button: ButtonPress::PRIMARY,
+            ctrl: false,
+            alt: false,
+            shift: false,
+        };
+        let clicked = self.submaps.iter().find_map(|submap| {
             submap.handle_button_press(
+                assets, controller, state, canvas, sender, &click,
+            )
+        });
+        match clicked {
+            Some((UiResponse::Redraw, None)) => {}
+            other => return other,
+        }
+        let response = self.handle_key_press(
+            assets,
+            controller,
+            state,
+            canvas,
+            sender,
+            &command.key_press(),
+        );
+        // The click has changed the UI state, so it must be redrawn.
+        match response {
+            Some((UiResponse::None, new_state)) => {
+                Some((UiResponse::Redraw, new_state))
+            }
+            None => Some((UiResponse::Redraw, None)),
+            response => response,
+        }
+    }
+
+    pub fn handle_button_drag(
+        &self,
+        assets: &mut Assets,
+        controller: &mut Controller,
+        state: &mut State,
+        canvas: &mut Canvas,
+        sender: &Sender<UiAction>,
+        event: &ButtonDrag,
+    ) -> Option<(UiResponse, Option<State>)> {
+        self.submaps.iter().find_map(|submap| {
+            submap.handle_button_drag(
+                assets, controller, state, canvas, sender, event,
+            )
+        })
+    }
+
+    pub fn handle_pointer_motion(
+        &self,
+        assets: &mut Assets,
+        controller: &mut Controller,
+        state: &mut State,
+        canvas: &mut Canvas,
+        sender: &Sender<UiAction>,
+        event: &PointerMotion,
+    ) -> Option<(UiResponse, Option<State>)> {
+        self.submaps.iter().find_map(|submap| {
+            submap.handle_pointer_motion(
                 assets, controller, state, canvas, sender, event,
             )
         })
@@ -145,6 +801,14 @@ impl Submap for DefaultMode {
         "Default mode"
     }
 
+    fn commands(&self, state: &State) -> Vec<Command> {
+        if state.as_default().is_some() {
+            DEFAULT_COMMANDS.to_vec()
+        } else {
+            vec![]
+        }
+    }
+
     fn handle_key_press(
         &self,
         assets: &mut Assets,
@@ -163,10 +827,63 @@ impl Submap for DefaultMode {
                         })
                         .or(Some((UiResponse::None, None)))
                 }
+                (&Key::c, false) | (&Key::C, false) => {
+                    assets.show_coordinates = !assets.show_coordinates;
+                    Some((UiResponse::Redraw, None))
+                }
+                (&Key::h, false) | (&Key::H, false) => {
+                    let addr = state.active_hex();
+                    if let Some(summary) = HexSummary::new(assets, addr) {
+                        assets.inspected_hex = Some(addr);
+                        toggle_panel(
+                            controller,
+                            &assets.strings,
+                            Panel::Hex(summary),
+                        );
+                    }
+                    Some((UiResponse::None, None))
+                }
+                (&Key::g, false) | (&Key::G, false) => {
+                    assets.set_border_labels(!assets.show_border_labels());
+                    // NOTE: the border labels change the map margins, so
+                    // the drawing surface may need to be resized.
+                    Some((UiResponse::ResetGame, None))
+                }
+                (&Key::i, false) | (&Key::I, false) => {
+                    let new_state = Screenshot::new(state.active_hex());
+                    controller
+                        .set_window_title(&new_state.window_title(assets));
+                    Some((UiResponse::Redraw, Some(new_state.into())))
+                }
+                (&Key::a, false) | (&Key::A, false) => {
+                    Some(state.advance_operating_round(assets, controller))
+                }
+                (&Key::a, true) | (&Key::A, true) => {
+                    let action = if assets.end_operating_round() {
+                        UiResponse::Redraw
+                    } else {
+                        UiResponse::None
+                    };
+                    Some((action, None))
+                }
+                (&Key::b, false) | (&Key::B, false) => {
+                    state.select_train_transfer(assets, controller);
+                    Some((UiResponse::None, None))
+                }
                 (&Key::p, false) | (&Key::P, false) => {
                     state.select_phase(assets, controller);
                     Some((UiResponse::None, None))
                 }
+                (&Key::n, false) | (&Key::N, false) => {
+                    // Form the national company, if it can be formed in the
+                    // current game phase.
+                    let action = if state.form_national(assets) {
+                        UiResponse::Redraw
+                    } else {
+                        UiResponse::None
+                    };
+                    Some((action, None))
+                }
                 (&Key::r, false) | (&Key::R, false) => {
                     // Allow the user to select a company and trains, and find the
                     // routes that earn the most revenue.
@@ -176,6 +893,64 @@ impl Submap for DefaultMode {
                         })
                         .or(Some((UiResponse::None, None)))
                 }
+                (&Key::o, false) | (&Key::O, false) => {
+                    // Allow the user to select a train, and find the route
+                    // from the active hex that earns the most revenue, for a
+                    // company that has no tokens.
+                    SelectOpenTrain::new(
+                        assets,
+                        controller,
+                        state.active_hex(),
+                    )
+                    .map(|new_state| {
+                        (UiResponse::Redraw, Some(new_state.into()))
+                    })
+                    .or(Some((UiResponse::None, None)))
+                }
+                (&Key::v, false) | (&Key::V, false) => {
+                    // Allow the user to select a company and trains, and rank
+                    // the token spaces in which the company could place a
+                    // token by the revenue that its trains could earn.
+                    SelectCompany::with_goal(
+                        assets,
+                        controller,
+                        state.active_hex(),
+                        Goal::TokenSpaces,
+                    )
+                    .map(|new_state| {
+                        (UiResponse::Redraw, Some(new_state.into()))
+                    })
+                    .or(Some((UiResponse::None, None)))
+                }
+                (&Key::w, false) | (&Key::W, false) => {
+                    // Allow the user to select a company and trains, and show
+                    // the route graph and the number of paths from each of
+                    // the company's tokens.
+                    SelectCompany::with_goal(
+                        assets,
+                        controller,
+                        state.active_hex(),
+                        Goal::RouteGraph,
+                    )
+                    .map(|new_state| {
+                        (UiResponse::Redraw, Some(new_state.into()))
+                    })
+                    .or(Some((UiResponse::None, None)))
+                }
+                (&Key::f, false) | (&Key::F, false) => {
+                    // Allow the user to select a company, and highlight the
+                    // track that it can reach from its tokens.
+                    SelectCompany::with_goal(
+                        assets,
+                        controller,
+                        state.active_hex(),
+                        Goal::Network,
+                    )
+                    .map(|new_state| {
+                        (UiResponse::Redraw, Some(new_state.into()))
+                    })
+                    .or(Some((UiResponse::None, None)))
+                }
                 (&Key::t, false) | (&Key::T, false) => {
                     EditTokens::try_new(&assets.map, state.active_hex())
                         .map(|new_state| {
@@ -241,26 +1016,76 @@ impl Submap for DefaultMode {
                     // This additional state information should then presumably be
                     // discarded once the user chooses *any* action except further
                     // rotations of the current tile.
-                    if let Some(hs) =
-                        assets.map.hex_state_mut(state.active_hex())
-                    {
-                        hs.rotate_anti_cw()
+                    if assets.planning {
+                        assets.rotate_planned_tile(state.active_hex(), false);
+                    } else {
+                        assets.rotate_tile(state.active_hex(), false);
                     }
                     Some((UiResponse::Redraw, None))
                 }
                 (&Key::greater, false) | (&Key::period, false) => {
-                    if let Some(hs) =
-                        assets.map.hex_state_mut(state.active_hex())
-                    {
-                        hs.rotate_cw()
+                    if assets.planning {
+                        assets.rotate_planned_tile(state.active_hex(), true);
+                    } else {
+                        assets.rotate_tile(state.active_hex(), true);
                     }
                     Some((UiResponse::Redraw, None))
                 }
                 (&Key::BackSpace, false) | (&Key::Delete, false) => {
                     // TODO: allow this action to be undone?
-                    assets.map.remove_tile(state.active_hex());
+                    if assets.planning {
+                        assets.map.unplan_tile(state.active_hex());
+                    } else {
+                        assets.remove_tile(state.active_hex());
+                    }
+                    Some((UiResponse::Redraw, None))
+                }
+                (&Key::x, false) | (&Key::X, false) => {
+                    // Restore the tile that was on this hex at the start of
+                    // the game, or remove the tile if the hex was empty.
+                    if assets.reset_hex(state.active_hex()) {
+                        Some((UiResponse::Redraw, None))
+                    } else {
+                        Some((UiResponse::None, None))
+                    }
+                }
+                (&Key::l, false) | (&Key::L, false) => {
+                    // Record tile changes in the planning layer, rather than
+                    // applying them to the map.
+                    assets.planning = !assets.planning;
+                    if assets.planning {
+                        assets.show_plan = true;
+                    }
+                    Some((UiResponse::Redraw, None))
+                }
+                (&Key::k, false) | (&Key::K, false) => {
+                    assets.show_plan = !assets.show_plan;
                     Some((UiResponse::Redraw, None))
                 }
+                (&Key::Return, true) => {
+                    if assets.commit_plan() > 0 {
+                        Some((UiResponse::Redraw, None))
+                    } else {
+                        Some((UiResponse::None, None))
+                    }
+                }
+                (&Key::Delete, true) => {
+                    if assets.map.discard_plan() > 0 {
+                        Some((UiResponse::Redraw, None))
+                    } else {
+                        Some((UiResponse::None, None))
+                    }
+                }
+                (&Key::v, true) | (&Key::V, true) => {
+                    // Paste the clipboard contents at the active hex, and
+                    // select the pasted hexes.
+                    let mut new_state = Select::new(state.active_hex(), []);
+                    if new_state.paste(assets) {
+                        Some((UiResponse::Redraw, Some(new_state.into())))
+                    } else {
+                        Some((UiResponse::None, None))
+                    }
+                }
                 _ => None,
             }
         })
@@ -277,55 +1102,340 @@ impl Submap for DefaultMode {
     ) -> Option<(UiResponse, Option<State>)> {
         // Allow the user to select hexes with a single click of any button.
         state.as_default_mut().map(|state| {
-            let hex = &assets.hex;
-            let map = &mut assets.map;
-            let ctx = hex.context();
-            let addr = map.hex_address_iter().find(|addr| {
-                let m = map.prepare_to_draw(**addr, hex, ctx);
-                hex.define_boundary(ctx);
-                ctx.set_matrix(m);
-                ctx.in_fill(event.x, event.y).unwrap()
-            });
-            if let Some(a) = addr {
-                state.set_active_hex(*a);
-                (UiResponse::Redraw, None)
+            if let Some(addr) = hex_at(assets, event.x, event.y) {
+                if event.shift {
+                    // Select multiple hexes.
+                    let active = state.active_hex();
+                    let new_state = Select::new(active, [active, addr]);
+                    (UiResponse::Redraw, Some(new_state.into()))
+                } else {
+                    state.set_active_hex(addr);
+                    (UiResponse::Redraw, None)
+                }
             } else {
                 (UiResponse::None, None)
             }
         })
     }
+
+    fn handle_button_drag(
+        &self,
+        assets: &mut Assets,
+        _controller: &mut Controller,
+        state: &mut State,
+        _canvas: &mut Canvas,
+        _sender: &Sender<UiAction>,
+        event: &ButtonDrag,
+    ) -> Option<(UiResponse, Option<State>)> {
+        // Allow the user to select multiple hexes by dragging the mouse.
+        state.as_default_mut().map(|state| {
+            if !event.is_area() {
+                return (UiResponse::None, None);
+            }
+            let mut new_state = Select::new(state.active_hex(), []);
+            if event.finished {
+                new_state.select_within(assets, event.rect(), false);
+            } else {
+                new_state.set_drag_rect(Some(event.rect()));
+            }
+            (UiResponse::Redraw, Some(new_state.into()))
+        })
+    }
 }
 
-/// The keymap for the found routes UI mode.
-pub struct FoundRoutesMode {}
+/// The keymap for the select hexes UI mode.
+///
+/// - `Escape`, `Return`: return to the default mode;
+/// - `Shift+click`: add a hex to, or remove a hex from, the selection;
+/// - Drag: select all hexes in an area (`Shift`: add to the selection);
+/// - `Ctrl+c`, `Ctrl+x`, `Ctrl+v`: copy, cut, and paste the selection;
+/// - `<`, `>`: rotate the selection around the active hex;
+/// - `Backspace`, `Delete`: remove the tiles from the selected hexes.
+pub struct SelectMode {}
 
-impl Submap for FoundRoutesMode {
+impl Submap for SelectMode {
     fn name(&self) -> &str {
-        "Find routes mode"
+        "Select hexes mode"
+    }
+
+    fn commands(&self, state: &State) -> Vec<Command> {
+        if state.as_select().is_some() {
+            SELECT_COMMANDS.to_vec()
+        } else {
+            vec![]
+        }
     }
 
     fn handle_key_press(
         &self,
         assets: &mut Assets,
-        controller: &mut Controller,
+        _controller: &mut Controller,
         state: &mut State,
         _canvas: &mut Canvas,
         _sender: &Sender<UiAction>,
         event: &KeyPress,
     ) -> Option<(UiResponse, Option<State>)> {
-        state.as_find_routes_found_mut().and_then(|state| {
+        state.as_select_mut().and_then(|state| {
+            let redraw_if = |changed: bool| {
+                if changed {
+                    Some((UiResponse::Redraw, None))
+                } else {
+                    Some((UiResponse::None, None))
+                }
+            };
             match (&event.key, event.ctrl) {
                 (&Key::Escape, false) | (&Key::Return, false) => {
                     // Exit this mode.
                     let new_state = State::default_state(state.active_hex());
                     Some((UiResponse::Redraw, Some(new_state)))
                 }
-                (&Key::Left, _) | (&Key::Up, _) => {
-                    let action = if state.highlight_previous_route() {
-                        controller
-                            .set_window_title(&state.window_title(assets));
-                        UiResponse::Redraw
-                    } else {
+                (&Key::c, true) | (&Key::C, true) => {
+                    state.copy(assets);
+                    Some((UiResponse::None, None))
+                }
+                (&Key::x, true) | (&Key::X, true) => {
+                    redraw_if(state.cut(assets))
+                }
+                (&Key::v, true) | (&Key::V, true) => {
+                    redraw_if(state.paste(assets))
+                }
+                (&Key::less, false) | (&Key::comma, false) => {
+                    redraw_if(state.rotate_anti_cw(assets))
+                }
+                (&Key::greater, false) | (&Key::period, false) => {
+                    redraw_if(state.rotate_cw(assets))
+                }
+                (&Key::BackSpace, false) | (&Key::Delete, false) => {
+                    state.clear(assets);
+                    Some((UiResponse::Redraw, None))
+                }
+                _ => None,
+            }
+        })
+    }
+
+    fn handle_button_press(
+        &self,
+        assets: &mut Assets,
+        _controller: &mut Controller,
+        state: &mut State,
+        _canvas: &mut Canvas,
+        _sender: &Sender<UiAction>,
+        event: &ButtonPress,
+    ) -> Option<(UiResponse, Option<State>)> {
+        state.as_select_mut().map(|state| {
+            if let Some(addr) = hex_at(assets, event.x, event.y) {
+                if event.shift {
+                    state.toggle(addr)
+                } else {
+                    state.select_only(addr)
+                }
+                (UiResponse::Redraw, None)
+            } else {
+                (UiResponse::None, None)
+            }
+        })
+    }
+
+    fn handle_button_drag(
+        &self,
+        assets: &mut Assets,
+        _controller: &mut Controller,
+        state: &mut State,
+        _canvas: &mut Canvas,
+        _sender: &Sender<UiAction>,
+        event: &ButtonDrag,
+    ) -> Option<(UiResponse, Option<State>)> {
+        state.as_select_mut().map(|state| {
+            if event.finished {
+                state.set_drag_rect(None);
+                if event.is_area() {
+                    state.select_within(assets, event.rect(), event.shift);
+                }
+            } else if event.is_area() {
+                state.set_drag_rect(Some(event.rect()));
+            } else {
+                state.set_drag_rect(None);
+            }
+            (UiResponse::Redraw, None)
+        })
+    }
+}
+
+/// The keymap for the save map region UI mode.
+///
+/// - `Escape`, `Return`: return to the default mode;
+/// - `1`, `2`, `3`, `4`: set the scale at which the region will be saved;
+/// - `c`, `C`: switch between saving the region as an image and copying it
+///   to the clipboard;
+/// - Drag: select the region to save (or copy).
+pub struct ScreenshotMode {}
+
+impl Submap for ScreenshotMode {
+    fn name(&self) -> &str {
+        "Save map region mode"
+    }
+
+    fn commands(&self, state: &State) -> Vec<Command> {
+        if state.as_screenshot().is_some() {
+            SCREENSHOT_COMMANDS.to_vec()
+        } else {
+            vec![]
+        }
+    }
+
+    fn handle_key_press(
+        &self,
+        assets: &mut Assets,
+        controller: &mut Controller,
+        state: &mut State,
+        _canvas: &mut Canvas,
+        _sender: &Sender<UiAction>,
+        event: &KeyPress,
+    ) -> Option<(UiResponse, Option<State>)> {
+        state.as_screenshot_mut().and_then(|state| {
+            let scale_ix = match (&event.key, event.ctrl) {
+                (&Key::Escape, false) | (&Key::Return, false) => {
+                    // Exit this mode.
+                    let new_state = State::default_state(state.active_hex());
+                    return Some((UiResponse::Redraw, Some(new_state)));
+                }
+                (&Key::_1, false) | (&Key::KP_1, false) => 0,
+                (&Key::_2, false) | (&Key::KP_2, false) => 1,
+                (&Key::_3, false) | (&Key::KP_3, false) => 2,
+                (&Key::_4, false) | (&Key::KP_4, false) => 3,
+                (&Key::c, false) | (&Key::C, false) => {
+                    state.toggle_copy();
+                    controller.set_window_title(&state.window_title(assets));
+                    return Some((UiResponse::None, None));
+                }
+                _ => return None,
+            };
+            state.set_scale(SCALES[scale_ix]);
+            controller.set_window_title(&state.window_title(assets));
+            Some((UiResponse::None, None))
+        })
+    }
+
+    fn handle_button_drag(
+        &self,
+        assets: &mut Assets,
+        controller: &mut Controller,
+        state: &mut State,
+        _canvas: &mut Canvas,
+        sender: &Sender<UiAction>,
+        event: &ButtonDrag,
+    ) -> Option<(UiResponse, Option<State>)> {
+        state.as_screenshot_mut().map(|state| {
+            if !event.finished {
+                if event.is_area() {
+                    state.set_drag_rect(Some(event.rect()));
+                } else {
+                    state.set_drag_rect(None);
+                }
+                return (UiResponse::Redraw, None);
+            }
+            state.set_drag_rect(None);
+            if !event.is_area() {
+                return (UiResponse::Redraw, None);
+            }
+            let region = Region::from_corners(event.rect(), state.scale());
+            let responder =
+                Responder::new(sender.clone(), controller.ping_tx());
+            if state.copies() {
+                responder.send(Action::CopyRegion(region));
+                return (UiResponse::Redraw, None);
+            }
+            // Suggest a filename that contains the current date and time.
+            let now = chrono::Local::now();
+            let default_dest =
+                now.format("screenshot-%Y-%m-%d-%H%M%S.png").to_string();
+            controller.select_screenshot_save(
+                assets.strings.tr("Save map region"),
+                Some(&default_dest),
+                move |path_opt| {
+                    if let Some(path) = path_opt {
+                        responder.send(Action::SaveRegion(path, region));
+                    }
+                },
+            );
+            (UiResponse::Redraw, None)
+        })
+    }
+}
+
+/// The keymap for the found routes UI mode.
+pub struct FoundRoutesMode {}
+
+impl Submap for FoundRoutesMode {
+    fn name(&self) -> &str {
+        "Find routes mode"
+    }
+
+    fn commands(&self, state: &State) -> Vec<Command> {
+        if let Some(state) = state.as_find_routes_found() {
+            if state.is_stepping() {
+                STEP_ROUTE_COMMANDS.to_vec()
+            } else {
+                FOUND_ROUTES_COMMANDS.to_vec()
+            }
+        } else {
+            vec![]
+        }
+    }
+
+    fn handle_key_press(
+        &self,
+        assets: &mut Assets,
+        controller: &mut Controller,
+        state: &mut State,
+        _canvas: &mut Canvas,
+        _sender: &Sender<UiAction>,
+        event: &KeyPress,
+    ) -> Option<(UiResponse, Option<State>)> {
+        state.as_find_routes_found_mut().and_then(|state| {
+            match (&event.key, event.ctrl) {
+                (&Key::Escape, false) | (&Key::Return, false) => {
+                    // Exit this mode.
+                    let new_state = State::default_state(state.active_hex());
+                    Some((UiResponse::Redraw, Some(new_state)))
+                }
+                (&Key::v, false) | (&Key::V, false) => {
+                    let action = if state.toggle_stepping() {
+                        controller
+                            .set_window_title(&state.window_title(assets));
+                        UiResponse::Redraw
+                    } else {
+                        UiResponse::None
+                    };
+                    Some((action, None))
+                }
+                (&Key::Left, _) if state.is_stepping() => {
+                    let action = if state.step_to_previous_visit() {
+                        controller
+                            .set_window_title(&state.window_title(assets));
+                        UiResponse::Redraw
+                    } else {
+                        UiResponse::None
+                    };
+                    Some((action, None))
+                }
+                (&Key::Right, _) if state.is_stepping() => {
+                    let action = if state.step_to_next_visit() {
+                        controller
+                            .set_window_title(&state.window_title(assets));
+                        UiResponse::Redraw
+                    } else {
+                        UiResponse::None
+                    };
+                    Some((action, None))
+                }
+                (&Key::Left, _) | (&Key::Up, _) => {
+                    let action = if state.highlight_previous_route() {
+                        controller
+                            .set_window_title(&state.window_title(assets));
+                        UiResponse::Redraw
+                    } else {
                         UiResponse::None
                     };
                     Some((action, None))
@@ -340,6 +1450,16 @@ impl Submap for FoundRoutesMode {
                     };
                     Some((action, None))
                 }
+                (&Key::p, false) | (&Key::P, false) => {
+                    if let Some(summary) = state.route_summary(assets) {
+                        toggle_panel(
+                            controller,
+                            &assets.strings,
+                            Panel::Routes(summary),
+                        );
+                    }
+                    Some((UiResponse::None, None))
+                }
                 (&Key::d, _) | (&Key::D, _) => {
                     let action = if state.show_dividends(assets, controller) {
                         UiResponse::Redraw
@@ -354,7 +1474,140 @@ impl Submap for FoundRoutesMode {
     }
 }
 
+/// The keymap for the ranked token spaces UI mode.
+pub struct RankedTokenSpacesMode {}
+
+impl Submap for RankedTokenSpacesMode {
+    fn name(&self) -> &str {
+        "Ranked token spaces mode"
+    }
+
+    fn commands(&self, state: &State) -> Vec<Command> {
+        if state.as_ranked_token_spaces().is_some() {
+            RANKED_TOKEN_SPACES_COMMANDS.to_vec()
+        } else {
+            vec![]
+        }
+    }
+
+    fn handle_key_press(
+        &self,
+        assets: &mut Assets,
+        controller: &mut Controller,
+        state: &mut State,
+        _canvas: &mut Canvas,
+        _sender: &Sender<UiAction>,
+        event: &KeyPress,
+    ) -> Option<(UiResponse, Option<State>)> {
+        state.as_ranked_token_spaces_mut().and_then(|state| {
+            let changed = match (&event.key, event.ctrl) {
+                (&Key::Escape, false) | (&Key::Return, false) => {
+                    // Exit this mode.
+                    let new_state = State::default_state(state.active_hex());
+                    return Some((UiResponse::Redraw, Some(new_state)));
+                }
+                (&Key::Right, _) | (&Key::Down, _) => state.highlight_next(),
+                (&Key::Left, _) | (&Key::Up, _) => state.highlight_previous(),
+                _ => return None,
+            };
+            let action = if changed {
+                controller.set_window_title(&state.window_title(assets));
+                UiResponse::Redraw
+            } else {
+                UiResponse::None
+            };
+            Some((action, None))
+        })
+    }
+}
+
+/// The keymap for the route graph UI mode.
+pub struct RouteGraphMode {}
+
+impl Submap for RouteGraphMode {
+    fn name(&self) -> &str {
+        "Route graph mode"
+    }
+
+    fn commands(&self, state: &State) -> Vec<Command> {
+        if state.as_route_graph().is_some() {
+            ROUTE_GRAPH_COMMANDS.to_vec()
+        } else {
+            vec![]
+        }
+    }
+
+    fn handle_key_press(
+        &self,
+        _assets: &mut Assets,
+        _controller: &mut Controller,
+        state: &mut State,
+        _canvas: &mut Canvas,
+        _sender: &Sender<UiAction>,
+        event: &KeyPress,
+    ) -> Option<(UiResponse, Option<State>)> {
+        state.as_route_graph().and_then(|state| {
+            match (&event.key, event.ctrl) {
+                (&Key::Escape, false) | (&Key::Return, false) => {
+                    // Exit this mode.
+                    let new_state = State::default_state(state.active_hex());
+                    Some((UiResponse::Redraw, Some(new_state)))
+                }
+                _ => None,
+            }
+        })
+    }
+}
+
+/// The keymap for the company network UI mode.
+pub struct CompanyNetworkMode {}
+
+impl Submap for CompanyNetworkMode {
+    fn name(&self) -> &str {
+        "Company network mode"
+    }
+
+    fn commands(&self, state: &State) -> Vec<Command> {
+        if state.as_company_network().is_some() {
+            COMPANY_NETWORK_COMMANDS.to_vec()
+        } else {
+            vec![]
+        }
+    }
+
+    fn handle_key_press(
+        &self,
+        _assets: &mut Assets,
+        _controller: &mut Controller,
+        state: &mut State,
+        _canvas: &mut Canvas,
+        _sender: &Sender<UiAction>,
+        event: &KeyPress,
+    ) -> Option<(UiResponse, Option<State>)> {
+        state.as_company_network().and_then(|state| {
+            match (&event.key, event.ctrl) {
+                (&Key::Escape, false) | (&Key::Return, false) => {
+                    // Exit this mode.
+                    let new_state = State::default_state(state.active_hex());
+                    Some((UiResponse::Redraw, Some(new_state)))
+                }
+                _ => None,
+            }
+        })
+    }
+}
+
 /// The keymap for the edit tokens UI mode.
+///
+/// - `Escape`: return to the default mode, discarding any changes;
+/// - `Return`: return to the default mode, retaining any changes;
+/// - `Left`, `Right`: select the previous/next token space;
+/// - `Up`, `Down`: replace the token in the selected space;
+/// - `0`, `Backspace`, `Delete`: remove the token in the selected space;
+/// - Click: select the token space under the mouse pointer, which is
+///   highlighted as the pointer moves over the active tile.
+/// - `Ctrl+click`: remove the token in the token space under the mouse
+///   pointer (see [ButtonBinding]).
 pub struct EditTokensMode {}
 
 impl Submap for EditTokensMode {
@@ -362,6 +1615,14 @@ impl Submap for EditTokensMode {
         "Edit tokens mode"
     }
 
+    fn commands(&self, state: &State) -> Vec<Command> {
+        if state.as_edit_tokens().is_some() {
+            EDIT_TOKENS_COMMANDS.to_vec()
+        } else {
+            vec![]
+        }
+    }
+
     fn handle_key_press(
         &self,
         assets: &mut Assets,
@@ -381,6 +1642,7 @@ impl Submap for EditTokensMode {
                 }
                 (&Key::Return, false) => {
                     // Exit this mode, retaining any changes.
+                    state.record_changes(assets);
                     let new_state = State::default_state(state.active_hex());
                     Some((UiResponse::Redraw, Some(new_state)))
                 }
@@ -412,6 +1674,44 @@ impl Submap for EditTokensMode {
             }
         })
     }
+
+    fn handle_button_press(
+        &self,
+        assets: &mut Assets,
+        _controller: &mut Controller,
+        state: &mut State,
+        _canvas: &mut Canvas,
+        _sender: &Sender<UiAction>,
+        event: &ButtonPress,
+    ) -> Option<(UiResponse, Option<State>)> {
+        // Allow the user to select a token space with a single click.
+        state.as_edit_tokens_mut().map(|state| {
+            if state.select_token_space_at(assets, event.x, event.y) {
+                (UiResponse::Redraw, None)
+            } else {
+                (UiResponse::None, None)
+            }
+        })
+    }
+
+    fn handle_pointer_motion(
+        &self,
+        assets: &mut Assets,
+        _controller: &mut Controller,
+        state: &mut State,
+        _canvas: &mut Canvas,
+        _sender: &Sender<UiAction>,
+        event: &PointerMotion,
+    ) -> Option<(UiResponse, Option<State>)> {
+        // Highlight the token space under the mouse pointer.
+        state.as_edit_tokens_mut().map(|state| {
+            if state.hover_token_space_at(assets, event.x, event.y) {
+                (UiResponse::Redraw, None)
+            } else {
+                (UiResponse::None, None)
+            }
+        })
+    }
 }
 
 /// The keymap for the replace tile UI mode.
@@ -422,6 +1722,14 @@ impl Submap for ReplaceTileMode {
         "Replace tile mode"
     }
 
+    fn commands(&self, state: &State) -> Vec<Command> {
+        if state.as_replace_tile().is_some() {
+            REPLACE_TILE_COMMANDS.to_vec()
+        } else {
+            vec![]
+        }
+    }
+
     fn handle_key_press(
         &self,
         assets: &mut Assets,
@@ -440,7 +1748,7 @@ impl Submap for ReplaceTileMode {
                 }
                 (&Key::Return, false) => {
                     // Exit this mode, retaining any changes.
-                    let action = if state.place_candidate(&mut assets.map) {
+                    let action = if state.place_candidate(assets) {
                         UiResponse::Redraw
                     } else {
                         UiResponse::None
@@ -490,14 +1798,97 @@ impl Submap for ReplaceTileMode {
     }
 }
 
+/// Closes the panel of the same kind as `panel` if it is open, and otherwise
+/// shows `panel`.
+fn toggle_panel(
+    controller: &mut Controller,
+    strings: &Strings,
+    panel: Panel,
+) {
+    let kind = panel.kind();
+    if controller.is_panel_open(kind) {
+        controller.close_panel(kind)
+    } else {
+        controller.show_panel(&panel.title(strings), &panel)
+    }
+}
+
+/// The resolutions (in dots per inch) at which the map can be exported.
+const EXPORT_DPI: [f64; 4] = [72.0, 150.0, 300.0, 600.0];
+
+/// Moves, scales, shows, hides, or removes the background image in response
+/// to a `Ctrl+Shift` key press.
+///
+/// Returns `None` if there is no background image, or if the key is not
+/// bound to any of these actions.
+fn adjust_background(
+    assets: &mut Assets,
+    key: &Key,
+) -> Option<(UiResponse, Option<State>)> {
+    use crate::background::{OFFSET_STEP, SCALE_STEP};
+
+    // NOTE: these actions are listed in the command palette even when there
+    // is no background image, in which case they have no effect.
+    let Some(background) = assets.background.as_mut() else {
+        return Some((UiResponse::None, None));
+    };
+    match *key {
+        Key::h | Key::H => {
+            background.set_visible(!background.is_visible());
+        }
+        Key::Left => background.translate(-OFFSET_STEP, 0.0),
+        Key::Right => background.translate(OFFSET_STEP, 0.0),
+        Key::Up => background.translate(0.0, -OFFSET_STEP),
+        Key::Down => background.translate(0.0, OFFSET_STEP),
+        Key::Page_Up => background.rescale(SCALE_STEP),
+        Key::Page_Down => background.rescale(1.0 / SCALE_STEP),
+        Key::Delete => assets.background = None,
+        _ => return None,
+    }
+    Some((UiResponse::Redraw, None))
+}
+
 /// The global keymap defines key bindings that apply regardless of the
 /// current UI state.
 ///
-/// - `q`, `Q`: quit;
+/// - `q`, `Q`: quit, after asking the user to confirm (unless no game has
+///   been started);
 /// - `s`, `S`: save a screenshot of the current map;
 /// - `Ctrl+n`, `Ctrl+N`: load the starting map.
 /// - `Ctrl+o`, `Ctrl+O`: load a map from disk.
 /// - `Ctrl+s`, `Ctrl+S`: save the current map to disk.
+/// - `Ctrl+r`, `Ctrl+R`: rename the most recently loaded or saved game file.
+/// - `Ctrl+e`, `Ctrl+E`: export an image of the map at a chosen resolution.
+/// - `Ctrl+b`, `Ctrl+B`: export the game state and an image of the map as a
+///   single bundle (e.g., for play-by-email games).
+/// - `Ctrl+i`, `Ctrl+I`: import the game state from a bundle.
+/// - `Ctrl+t`, `Ctrl+T`: replace all of one company's tokens with another
+///   company's tokens.
+/// - `Ctrl+Shift+t`: place company tokens from a list of token placements.
+/// - `Ctrl+z`, `Ctrl+Z`: undo the most recent change to the placed tokens,
+///   or the most recent hex reset.
+/// - `Ctrl+p`, `Ctrl+P`: search for and run an action that is available in
+///   the current UI state.
+/// - `j`, `J`: show the moves that have been made in the current game.
+/// - `Ctrl+m`, `Ctrl+M`: add a note to the journal.
+/// - `Ctrl+g`, `Ctrl+G`: set a custom title for the current game.
+/// - `Ctrl+k`, `Ctrl+K`: select the user interface language.
+/// - `Ctrl+Shift+k`: select the map theme.
+/// - `Ctrl+j`, `Ctrl+J`: show or hide the journal panel.
+/// - `Ctrl+l`, `Ctrl+L`: show or hide the tile inventory panel.
+/// - `Ctrl+0`: zoom in or out so that the entire map fits in the window.
+/// - `Ctrl+Shift+r`: rotate the map view 90 degrees clockwise.
+/// - `Ctrl+Shift+m`: mirror the map view from left to right.
+/// - `Ctrl+Shift+c`: copy the visible part of the map to the clipboard.
+/// - `Ctrl+Shift+h`: show or hide the background image (if any).
+/// - `Ctrl+Shift+Left`, `Ctrl+Shift+Right`, `Ctrl+Shift+Up`,
+///   `Ctrl+Shift+Down`: move the background image.
+/// - `Ctrl+Shift+Page_Up`, `Ctrl+Shift+Page_Down`: enlarge or shrink the
+///   background image.
+/// - `Ctrl+Shift+Delete`: remove the background image.
+///
+/// Background images are loaded by dropping PNG files onto the window (see
+/// the [background](crate::background) module).
 pub struct Global {}
 
 impl Submap for Global {
@@ -505,6 +1896,14 @@ impl Submap for Global {
         "Global"
     }
 
+    fn commands(&self, state: &State) -> Vec<Command> {
+        GLOBAL_COMMANDS
+            .iter()
+            .filter(|(_command, available)| available(state))
+            .map(|(command, _available)| *command)
+            .collect()
+    }
+
     fn handle_key_press(
         &self,
         assets: &mut Assets,
@@ -517,37 +1916,84 @@ impl Submap for Global {
         let is_start = state.as_start().is_some();
         match (&event.key, event.ctrl) {
             (&Key::q, false) | (&Key::Q, false) => {
-                Some((UiResponse::Quit, None))
+                if is_start {
+                    return Some((UiResponse::Quit, None));
+                }
+                // Ask the user to confirm, so that the current game is not
+                // closed by accident.
+                let responder =
+                    Responder::new(sender.clone(), controller.ping_tx());
+                controller.confirm(
+                    assets.strings.tr("Quit"),
+                    assets.strings.tr("Do you want to quit?"),
+                    move |confirmed| {
+                        if confirmed {
+                            responder.send(Action::Quit);
+                        }
+                    },
+                );
+                Some((UiResponse::None, None))
             }
             (&Key::n, true) | (&Key::N, true) => {
                 // Prompt the user to select a game, and load its starting map.
                 let game_names: Vec<&str> = assets.games.names();
-                let ping_tx = controller.ping_tx();
-                let send_tx = sender.clone();
+                let responder =
+                    Responder::new(sender.clone(), controller.ping_tx());
                 controller.select_index(
-                    "Select a game",
+                    assets.strings.tr("Select a game"),
                     &game_names,
                     move |ix_opt| {
                         if let Some(ix) = ix_opt {
-                            send_tx.send(Action::NewGame(ix).into()).unwrap();
-                            ping_tx.send_ping(PingDest::TopLevel).unwrap();
+                            responder.send(Action::NewGame(ix));
+                        }
+                    },
+                );
+                Some((UiResponse::None, None))
+            }
+            (&Key::o, true) | (&Key::O, true) if event.shift => {
+                // Prompt the user to select a recently loaded or saved game.
+                let paths = controller.file_history().recent_files().to_vec();
+                if paths.is_empty() {
+                    controller.show_error(
+                        assets.strings.tr("Could not load game"),
+                        assets.strings.tr("There is no recent game file"),
+                    );
+                    return Some((UiResponse::None, None));
+                }
+                let names: Vec<String> = paths
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect();
+                let names: Vec<&str> =
+                    names.iter().map(|name| name.as_str()).collect();
+                let responder =
+                    Responder::new(sender.clone(), controller.ping_tx());
+                controller.select_index(
+                    assets.strings.tr("Load a recent game"),
+                    &names,
+                    move |ix_opt| {
+                        if let Some(ix) = ix_opt {
+                            responder
+                                .send(Action::LoadGame(paths[ix].clone()));
                         }
                     },
                 );
                 Some((UiResponse::None, None))
             }
+            (&Key::F5, false) => {
+                Responder::new(sender.clone(), controller.ping_tx())
+                    .send(Action::ReloadGame);
+                Some((UiResponse::None, None))
+            }
             (&Key::o, true) | (&Key::O, true) => {
-                let ping_tx = controller.ping_tx();
-                let send_tx = sender.clone();
+                let responder =
+                    Responder::new(sender.clone(), controller.ping_tx());
                 controller.select_game_load(
-                    "Load game",
+                    assets.strings.tr("Load game"),
                     None,
                     move |path_opt| {
                         if let Some(path) = path_opt {
-                            send_tx
-                                .send(Action::LoadGame(path).into())
-                                .unwrap();
-                            ping_tx.send_ping(PingDest::TopLevel).unwrap();
+                            responder.send(Action::LoadGame(path));
                         }
                     },
                 );
@@ -557,17 +2003,14 @@ impl Submap for Global {
                 if is_start {
                     return None;
                 }
-                let ping_tx = controller.ping_tx();
-                let send_tx = sender.clone();
+                let responder =
+                    Responder::new(sender.clone(), controller.ping_tx());
                 controller.select_game_save(
-                    "Save game",
+                    assets.strings.tr("Save game"),
                     None,
                     move |path_opt| {
                         if let Some(path) = path_opt {
-                            send_tx
-                                .send(Action::SaveGame(path).into())
-                                .unwrap();
-                            ping_tx.send_ping(PingDest::TopLevel).unwrap();
+                            responder.send(Action::SaveGame(path));
                         }
                     },
                 );
@@ -577,8 +2020,8 @@ impl Submap for Global {
                 if is_start {
                     return None;
                 }
-                let ping_tx = controller.ping_tx();
-                let send_tx = sender.clone();
+                let responder =
+                    Responder::new(sender.clone(), controller.ping_tx());
                 // NOTE: save the current image contents, so that subsequent
                 // updates (e.g., finding optimal routes) do not affect the
                 // saved image.
@@ -591,24 +2034,285 @@ impl Submap for Global {
                 let default_dest =
                     now.format("screenshot-%Y-%m-%d-%H%M%S.png").to_string();
                 controller.select_screenshot_save(
-                    "Save screenshot",
+                    assets.strings.tr("Save screenshot"),
                     Some(&default_dest),
                     move |path_opt| {
                         if let Some(path) = path_opt {
                             // NOTE: need to clone `ss_surf`, because this is
                             // a `Fn` closure, not a `FnOnce` closure.
-                            send_tx
-                                .send(
-                                    Action::SaveImage(path, image.clone())
-                                        .into(),
-                                )
-                                .unwrap();
-                            ping_tx.send_ping(PingDest::TopLevel).unwrap();
+                            responder
+                                .send(Action::SaveImage(path, image.clone()));
+                        }
+                    },
+                );
+                Some((UiResponse::None, None))
+            }
+            (&Key::e, true) | (&Key::E, true) => {
+                if is_start {
+                    return None;
+                }
+                let responder =
+                    Responder::new(sender.clone(), controller.ping_tx());
+                let labels: Vec<String> = EXPORT_DPI
+                    .iter()
+                    .map(|dpi| {
+                        assets.strings.format("{dpi} DPI", &[("dpi", dpi)])
+                    })
+                    .collect();
+                let labels: Vec<&str> =
+                    labels.iter().map(|label| label.as_str()).collect();
+                controller.select_index(
+                    assets.strings.tr("Select the image resolution"),
+                    &labels,
+                    move |ix_opt| {
+                        if let Some(dpi) = ix_opt.map(|ix| EXPORT_DPI[ix]) {
+                            responder.send(Action::ExportMap(dpi));
+                        }
+                    },
+                );
+                Some((UiResponse::None, None))
+            }
+            (&Key::b, true) | (&Key::B, true) => {
+                if is_start {
+                    return None;
+                }
+                let responder =
+                    Responder::new(sender.clone(), controller.ping_tx());
+                // Suggest a filename that contains the game name and the
+                // current date and time.
+                let now = chrono::Local::now();
+                let default_dest = format!(
+                    "{}-{}.bundle",
+                    assets.games.active().name(),
+                    now.format("%Y-%m-%d-%H%M%S")
+                );
+                controller.select_game_save(
+                    assets.strings.tr("Export game bundle"),
+                    Some(&default_dest),
+                    move |path_opt| {
+                        if let Some(path) = path_opt {
+                            responder.send(Action::ExportBundle(path));
+                        }
+                    },
+                );
+                Some((UiResponse::None, None))
+            }
+            (&Key::i, true) | (&Key::I, true) => {
+                let responder =
+                    Responder::new(sender.clone(), controller.ping_tx());
+                controller.select_game_load(
+                    assets.strings.tr("Import game bundle"),
+                    None,
+                    move |path_opt| {
+                        if let Some(path) = path_opt {
+                            responder.send(Action::ImportBundle(path));
+                        }
+                    },
+                );
+                Some((UiResponse::None, None))
+            }
+            (&Key::t, true) | (&Key::T, true) if event.shift => {
+                if !state.is_default_state() {
+                    return None;
+                }
+                // Prompt the user to select a list of token placements, such
+                // as when reconstructing a game from a physical board.
+                let responder =
+                    Responder::new(sender.clone(), controller.ping_tx());
+                controller.select_token_list(
+                    assets.strings.tr("Import company tokens"),
+                    move |path_opt| {
+                        if let Some(path) = path_opt {
+                            responder.send(Action::ImportTokens(path));
+                        }
+                    },
+                );
+                Some((UiResponse::None, None))
+            }
+            (&Key::t, true) | (&Key::T, true) => {
+                if !state.is_default_state() {
+                    return None;
+                }
+                // Prompt the user to select a company whose tokens will be
+                // replaced, such as when it merges into another company.
+                let placed = assets.map.unique_placed_tokens();
+                let from_ixs: Vec<usize> = assets
+                    .map
+                    .token_names()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_ix, name)| {
+                        placed.contains(&assets.map.token(name))
+                    })
+                    .map(|(ix, _name)| ix)
+                    .collect();
+                if from_ixs.is_empty() {
+                    return Some((UiResponse::None, None));
+                }
+                let names: Vec<&str> = from_ixs
+                    .iter()
+                    .map(|ix| assets.map.token_names()[*ix].as_str())
+                    .collect();
+                let responder =
+                    Responder::new(sender.clone(), controller.ping_tx());
+                controller.select_index(
+                    assets.strings.tr("Replace the tokens of"),
+                    &names,
+                    move |ix_opt| {
+                        if let Some(ix) = ix_opt.map(|ix| from_ixs[ix]) {
+                            responder.send(Action::ReplaceTokens(ix));
                         }
                     },
                 );
                 Some((UiResponse::None, None))
             }
+            (&Key::z, true) | (&Key::Z, true) => {
+                if !state.is_default_state() {
+                    return None;
+                }
+                if assets.undo() {
+                    Some((UiResponse::Redraw, None))
+                } else {
+                    Some((UiResponse::None, None))
+                }
+            }
+            (&Key::p, true) | (&Key::P, true) => {
+                if is_start {
+                    return None;
+                }
+                Responder::new(sender.clone(), controller.ping_tx())
+                    .send(Action::ShowCommandPalette);
+                Some((UiResponse::None, None))
+            }
+            (&Key::r, true) | (&Key::R, true) if event.shift => {
+                if is_start {
+                    return None;
+                }
+                // Rotate the map as it is shown in the window, so that it
+                // matches the orientation of a physical map.
+                let view = assets.view().rotated_clockwise();
+                Responder::new(sender.clone(), controller.ping_tx())
+                    .send(Action::SetView(view));
+                Some((UiResponse::None, None))
+            }
+            (&Key::m, true) | (&Key::M, true) if event.shift => {
+                if is_start {
+                    return None;
+                }
+                let view = assets.view().mirrored();
+                Responder::new(sender.clone(), controller.ping_tx())
+                    .send(Action::SetView(view));
+                Some((UiResponse::None, None))
+            }
+            (&Key::r, true) | (&Key::R, true) => {
+                if is_start {
+                    return None;
+                }
+                Responder::new(sender.clone(), controller.ping_tx())
+                    .send(Action::RenameGame);
+                Some((UiResponse::None, None))
+            }
+            (&Key::c, true) | (&Key::C, true) if event.shift => {
+                if is_start {
+                    return None;
+                }
+                Responder::new(sender.clone(), controller.ping_tx())
+                    .send(Action::CopyView);
+                Some((UiResponse::None, None))
+            }
+            (&Key::h, true)
+            | (&Key::H, true)
+            | (&Key::Left, true)
+            | (&Key::Right, true)
+            | (&Key::Up, true)
+            | (&Key::Down, true)
+            | (&Key::Page_Up, true)
+            | (&Key::Page_Down, true)
+            | (&Key::Delete, true)
+                if event.shift =>
+            {
+                if is_start {
+                    return None;
+                }
+                adjust_background(assets, &event.key)
+            }
+            (&Key::k, true) | (&Key::K, true) if event.shift => {
+                Responder::new(sender.clone(), controller.ping_tx())
+                    .send(Action::SelectTheme);
+                Some((UiResponse::None, None))
+            }
+            (&Key::k, true) | (&Key::K, true) => {
+                Responder::new(sender.clone(), controller.ping_tx())
+                    .send(Action::SelectLanguage);
+                Some((UiResponse::None, None))
+            }
+            (&Key::j, false) | (&Key::J, false) => {
+                if is_start {
+                    return None;
+                }
+                controller.show_journal(&assets.journal);
+                Some((UiResponse::None, None))
+            }
+            (&Key::Menu, false) => {
+                if is_start {
+                    return None;
+                }
+                Responder::new(sender.clone(), controller.ping_tx())
+                    .send(Action::ShowHexMenu);
+                Some((UiResponse::None, None))
+            }
+            (&Key::m, true) | (&Key::M, true) => {
+                if is_start {
+                    return None;
+                }
+                let responder =
+                    Responder::new(sender.clone(), controller.ping_tx());
+                controller.enter_text(
+                    assets.strings.tr("Add a note"),
+                    assets.strings.tr("Note:"),
+                    None,
+                    move |text_opt| {
+                        if let Some(text) = text_opt {
+                            responder.send(Action::AddNote(text));
+                        }
+                    },
+                );
+                Some((UiResponse::None, None))
+            }
+            (&Key::g, true) | (&Key::G, true) => {
+                if is_start {
+                    return None;
+                }
+                let responder =
+                    Responder::new(sender.clone(), controller.ping_tx());
+                controller.enter_text(
+                    assets.strings.tr("Set the game title"),
+                    assets.strings.tr("Title:"),
+                    assets.title.as_deref(),
+                    move |text_opt| {
+                        if let Some(text) = text_opt {
+                            responder.send(Action::SetTitle(text));
+                        }
+                    },
+                );
+                Some((UiResponse::None, None))
+            }
+            (&Key::j, true) | (&Key::J, true) => {
+                if is_start {
+                    return None;
+                }
+                let panel = Panel::Journal(assets.journal.clone());
+                toggle_panel(controller, &assets.strings, panel);
+                Some((UiResponse::None, None))
+            }
+            (&Key::l, true) | (&Key::L, true) => {
+                if is_start {
+                    return None;
+                }
+                let panel = Panel::Tiles(TileInventory::new(&assets.map));
+                toggle_panel(controller, &assets.strings, panel);
+                Some((UiResponse::None, None))
+            }
             (&Key::plus, false) | (&Key::equal, false) => {
                 if is_start {
                     return None;
@@ -621,7 +2325,39 @@ impl Submap for Global {
                 }
                 Some((UiResponse::ZoomOut, None))
             }
+            (&Key::_0, true) | (&Key::KP_0, true) => {
+                if is_start {
+                    return None;
+                }
+                Some((UiResponse::ZoomToFit, None))
+            }
             _ => None,
         }
     }
+
+    fn handle_pointer_motion(
+        &self,
+        assets: &mut Assets,
+        controller: &mut Controller,
+        _state: &mut State,
+        _canvas: &mut Canvas,
+        _sender: &Sender<UiAction>,
+        event: &PointerMotion,
+    ) -> Option<(UiResponse, Option<State>)> {
+        // Show the hex under the mouse pointer in the hex inspector panel.
+        // NOTE: return `None` so that other keymaps can also respond to
+        // pointer motion.
+        if !controller.is_panel_open(PanelKind::Hex) {
+            return None;
+        }
+        let addr = hex_at(assets, event.x, event.y)?;
+        if assets.inspected_hex != Some(addr) {
+            if let Some(summary) = HexSummary::new(assets, addr) {
+                assets.inspected_hex = Some(addr);
+                let panel = Panel::Hex(summary);
+                controller.show_panel(&panel.title(&assets.strings), &panel);
+            }
+        }
+        None
+    }
 }
@@ -0,0 +1,160 @@
+//! Translate the messages, dialog titles, and prompts that are shown to the
+//! user.
+//!
+//! Each message is identified by its English text, so English is used for
+//! any message that has not been translated, and the English [Strings] is
+//! simply an empty catalogue.
+//! Messages may contain named placeholders (e.g., `"Select a {train}"`),
+//! which are replaced by [Strings::format].
+//!
+//! Other languages are defined by JSON files, such as:
+//!
+//! ```json
+//! {
+//!   "language": "Français",
+//!   "strings": {
+//!     "Select a game": "Choisissez un jeu",
+//!     "Could not load game": "Impossible de charger la partie"
+//!   }
+//! }
+//! ```
+//!
+//! Note that game data, such as tile names, city names, and company names,
+//! are not translated.
+
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use n18io::Error;
+
+/// The name of the default language.
+pub const ENGLISH: &str = "English";
+
+/// The translations of user interface messages into a single language.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Strings {
+    /// The name of this language, as it should be shown to the user.
+    pub language: String,
+    /// The translation of each message, identified by its English text.
+    #[serde(default)]
+    pub strings: BTreeMap<String, String>,
+}
+
+impl Default for Strings {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+impl Strings {
+    /// Returns the default language, which does not translate any
+    /// messages.
+    pub fn english() -> Self {
+        Strings {
+            language: ENGLISH.to_string(),
+            strings: BTreeMap::new(),
+        }
+    }
+
+    /// Returns `true` if this is the default language.
+    pub fn is_english(&self) -> bool {
+        self.language == ENGLISH
+    }
+
+    /// Returns the translation of `text`, or `text` itself if it has not
+    /// been translated.
+    pub fn tr<'a>(&'a self, text: &'a str) -> &'a str {
+        self.strings.get(text).map(|s| s.as_str()).unwrap_or(text)
+    }
+
+    /// Returns the translation of `text`, where each placeholder (e.g.,
+    /// `{name}`) is replaced by the value of the matching argument.
+    pub fn format(
+        &self,
+        text: &str,
+        args: &[(&str, &dyn std::fmt::Display)],
+    ) -> String {
+        args.iter().fold(
+            self.tr(text).to_string(),
+            |result, (name, value)| {
+                result.replace(&format!("{{{}}}", name), &value.to_string())
+            },
+        )
+    }
+
+    /// Reads a language from the JSON file `path`.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text =
+            std::fs::read_to_string(path).map_err(|source| Error::Io {
+                path: Some(path.to_path_buf()),
+                source,
+            })?;
+        serde_json::from_str(&text).map_err(|source| Error::Json {
+            path: Some(path.to_path_buf()),
+            line: source.line(),
+            column: source.column(),
+            source,
+        })
+    }
+
+    /// Writes this language to the JSON file `path`.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let json_error = |source: serde_json::Error| Error::Json {
+            path: Some(path.to_path_buf()),
+            line: source.line(),
+            column: source.column(),
+            source,
+        };
+        let text = serde_json::to_string_pretty(self).map_err(json_error)?;
+        std::fs::write(path, text).map_err(|source| Error::Io {
+            path: Some(path.to_path_buf()),
+            source,
+        })
+    }
+}
+
+/// Reads each language defined in a JSON file in the directory `dir`, in
+/// order of their file names.
+///
+/// Files that cannot be read are ignored, and an error is logged.
+pub fn find_languages<P: AsRef<Path>>(dir: P) -> Vec<Strings> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .map(|ext| ext.eq_ignore_ascii_case("json"))
+                .unwrap_or(false)
+        })
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .filter_map(|path| match Strings::read(path) {
+            Ok(strings) => Some(strings),
+            Err(e) => {
+                error!("Could not read language: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Returns the directory that contains the available languages.
+///
+/// This is the `RUSTY_TRAIN_LANGUAGES` environment variable, if it is
+/// defined.
+/// Otherwise, it is `rusty_train/languages` in the user's configuration
+/// directory (`$XDG_CONFIG_HOME`, or `$HOME/.config`).
+pub fn default_dir() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("RUSTY_TRAIN_LANGUAGES") {
+        return Some(path.into());
+    }
+    crate::session::config_dir().map(|dir| dir.join("languages"))
+}
@@ -9,7 +9,7 @@
 //! It is divided into several components:
 //!
 //! - [Assets]: the current [Map] and the available [Games].
-//! - [Canvas]: the surface on which the map is drawn.
+//! - [Canvas]: the [Surface] on which the map is drawn.
 //! - [State]: the current user interface state (or "mode"), which defines the
 //!   actions available to the user and how the map is drawn.
 //! - [Keymap]: responds to keyboard and mouse input by triggering state
@@ -19,11 +19,17 @@
 //!
 //! # Events and event handlers
 //!
-//! There are three types of events that [UserInterface] handles:
+//! There are five types of events that [UserInterface] handles:
 //!
 //! - Mouse button events, which are described by [ButtonPress] structs and
 //!   are handled by [UserInterface::handle_button_press].
 //!
+//! - Mouse drag events, which are described by [ButtonDrag] structs and are
+//!   handled by [UserInterface::handle_button_drag].
+//!
+//! - Mouse motion events, which are described by [PointerMotion] structs and
+//!   are handled by [UserInterface::handle_pointer_motion].
+//!
 //! - Keyboard events, which are described by [KeyPress] structs and are
 //!   handled by [UserInterface::handle_key_press].
 //!
@@ -33,6 +39,15 @@
 //!   Pings can be sent using a [PingSender], which can be obtained by calling
 //!   [Controller::ping_tx](UiController::ping_tx).
 //!
+//! Dialogs (e.g., selecting a file, entering text, or confirming an action)
+//! never block the main loop.
+//! Each [UiController] dialog method returns immediately, and passes the
+//! user's response to a callback, which uses a [Responder] to send an action
+//! to the [UserInterface] and ping it, so that the action is handled by
+//! [UserInterface::ping].
+//! Similarly, long-running tasks such as route searches run in separate
+//! threads and ping the current [State] when they have finished.
+//!
 //! Note that event details, such as key identifiers, are defined by the
 //! [ButtonPress] and [KeyPress] structs, which use types from the GDK 3
 //! library.
@@ -45,33 +60,76 @@
 //! These actions are performed by [UserInterface::respond].
 //!
 
-use log::error;
+use log::{error, info, warn};
 use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{Arc, Mutex};
 
-use n18game::Game;
-use n18hex::{Colour, Hex};
-use n18map::Map;
+use n18brush::legend::Decorations;
+use n18brush::ImageFormat;
+use n18game::bank::TrainSupply;
+use n18game::private::PrivateHoldings;
+use n18game::sim::{Board, Move, MoveError};
+use n18game::treasury::Treasuries;
+use n18game::{Game, Journal};
+use n18hex::{Colour, Coord, Hex, RotateCW};
+use n18map::{Clipboard, HexAddress, Map, TokenChange};
+use n18route::{MapGraph, PathCache};
 
+/// Draw a scanned map image beneath the map.
+pub mod background;
 /// Manage drawing surfaces.
 pub mod canvas;
 /// Control UI elements.
 pub mod control;
+/// Notify listeners of changes to the map and game state.
+pub mod events;
 /// Response to keyboard and mouse events.
 pub mod keymap;
+/// Translate user interface messages into other languages.
+pub mod lang;
+/// Share the current game with other players over a network.
+#[cfg(feature = "net")]
+pub mod net;
+/// Show game information in auxiliary windows or panes.
+pub mod panel;
+/// Guide the user through an operating round.
+pub mod round;
+/// Record and replay user input.
+pub mod script;
+/// Save and restore the user interface session.
+pub mod session;
 /// The different states of the user interface.
 pub mod state;
+/// Summarise the current map and user interface state in a status bar.
+pub mod status;
+/// Rotate and mirror the map as it is shown in the window.
+pub mod view;
+#[cfg(feature = "hot-reload")]
+pub mod watch;
+
+#[doc(inline)]
+pub use canvas::{Canvas, Region, Surface, TiledSurface};
+
+#[doc(inline)]
+pub use control::{Controller, PingSender, Responder, UiController};
+
+#[doc(inline)]
+pub use events::{ListenerId, MapEvent};
 
 #[doc(inline)]
-pub use canvas::Canvas;
+pub use keymap::{
+    ButtonBinding, ButtonDrag, ButtonPress, Command, KeyPress, Keymap,
+    PointerMotion,
+};
 
 #[doc(inline)]
-pub use control::{Controller, PingSender, UiController};
+pub use panel::{Panel, PanelKind};
 
 #[doc(inline)]
-pub use keymap::{ButtonPress, KeyPress, Keymap};
+pub use state::{HexAction, State, UiState};
 
 #[doc(inline)]
-pub use state::{State, UiState};
+pub use status::{SegmentKind, StatusBar};
 
 /// Identify which part of the UI should respond to a "ping".
 pub enum PingDest {
@@ -79,6 +137,12 @@ pub enum PingDest {
     TopLevel,
     /// Ping the current [State].
     State,
+    /// Reload the watched tile catalogues and map configuration.
+    #[cfg(feature = "hot-reload")]
+    ReloadFiles,
+    /// Apply the moves that other players have made in a shared game.
+    #[cfg(feature = "net")]
+    Session,
 }
 
 /// Ordered collections of available games.
@@ -122,6 +186,11 @@ impl Games {
         self.games.iter().map(|g| &**g)
     }
 
+    /// Returns the index of the active game.
+    pub fn active_index(&self) -> usize {
+        self.game_ix
+    }
+
     /// Changes the active game by name.
     pub fn set_active_name(&mut self, name: &str) -> bool {
         let ix_opt = self
@@ -149,11 +218,589 @@ impl Games {
     }
 }
 
+/// A map hex that was reset to its initial state (see
+/// [Assets::reset_hex]), and the contents of the hex before it was reset.
+#[derive(Clone, Debug)]
+pub struct HexReset {
+    /// The map hex that was reset.
+    pub addr: HexAddress,
+    /// The journal entry that describes the reset.
+    pub entry: String,
+    /// The tile, rotation, and tokens on the hex before it was reset.
+    pub before: Clipboard,
+    /// The number of token changes that could be undone when the hex was
+    /// reset, so that changes are undone in the order that they were made.
+    pub token_changes: usize,
+}
+
 /// Defines the non-UI game state components.
 pub struct Assets {
     pub hex: Hex,
     pub map: Map,
     pub games: Games,
+    /// Map hexes that the user has copied, so that they can be pasted.
+    pub clipboard: Option<Clipboard>,
+    /// Whether to draw the coordinates of each map hex.
+    pub show_coordinates: bool,
+    /// Whether tile placements, rotations, and removals are recorded in the
+    /// map's planning layer (see [Map::plan]) rather than applied to the map.
+    pub planning: bool,
+    /// Whether to draw the tiles in the map's planning layer.
+    pub show_plan: bool,
+    /// Whether to draw row and column labels around the map border; use
+    /// [Assets::set_border_labels] to change this setting.
+    show_border_labels: bool,
+    /// How the map is rotated and mirrored when it is shown in the window;
+    /// use [UserInterface::set_view] to change this setting.
+    view: view::ViewTransform,
+    /// The moves that have been made in the current game.
+    pub journal: Journal,
+    /// The custom title of the current game, if any, which is shown in the
+    /// window title alongside the game name.
+    pub title: Option<String>,
+    /// The translations of user interface messages into the chosen
+    /// language.
+    pub strings: lang::Strings,
+    /// The trains held by the bank and available in the open market.
+    pub trains: TrainSupply,
+    /// The cash held by each company whose treasury is being tracked.
+    pub treasuries: Treasuries,
+    /// Changes to the placed tokens that can be undone, and the journal
+    /// entries that describe each of these changes.
+    pub token_history: Vec<(Vec<String>, Vec<TokenChange>)>,
+    /// Map hexes that were reset to their initial state, which can be
+    /// undone (see [Assets::reset_hex]).
+    pub reset_history: Vec<HexReset>,
+    /// The company and net revenue of the most recent route search, which is
+    /// shown in the status bar.
+    pub last_revenue: Option<(String, usize)>,
+    /// The most recent optimal routes for each company, which are reused
+    /// until the map is modified.
+    pub route_cache: state::search::RouteCache,
+    /// The map connectivity and the paths found by route searches, which are
+    /// shared with the threads that search for routes, and are updated or
+    /// discarded when the map is modified (see [PathCache::sync]).
+    pub path_cache: Arc<Mutex<PathCache>>,
+    /// The operating round that is in progress, if any.
+    pub operating_round: Option<round::OperatingRound>,
+    /// The revenue that each company has earned in each operating round.
+    pub revenue_history: Vec<round::Revenue>,
+    /// The map hex shown in the hex inspector panel, if it is open.
+    pub inspected_hex: Option<HexAddress>,
+    /// The scanned map image that is drawn beneath the map, if any.
+    pub background: Option<background::Background>,
+    /// The listeners that are notified of changes to the map.
+    listeners: events::Listeners,
+}
+
+impl Assets {
+    /// Returns the name of a map hex in the active game's coordinate
+    /// system (e.g., "D10").
+    pub fn hex_name(&self, addr: HexAddress) -> String {
+        self.games
+            .active()
+            .coordinate_system()
+            .format(&addr)
+            .unwrap_or_else(|| addr.to_string())
+    }
+
+    /// Returns whether row and column labels are drawn around the map
+    /// border.
+    pub fn show_border_labels(&self) -> bool {
+        self.show_border_labels
+    }
+
+    /// Shows or hides the row and column labels around the map border, using
+    /// the active game's coordinate system.
+    pub fn set_border_labels(&mut self, show: bool) {
+        self.show_border_labels = show;
+        self.update_border_labels();
+    }
+
+    /// Returns how the map is rotated and mirrored when it is shown in the
+    /// window.
+    pub fn view(&self) -> view::ViewTransform {
+        self.view
+    }
+
+    /// Returns the dimensions of the map as it is shown in the window (see
+    /// [ViewTransform::size](view::ViewTransform::size)).
+    pub fn view_size(&self) -> (f64, f64) {
+        let (width, height) = self.map.drawing_size(&self.hex);
+        self.view.size(width, height)
+    }
+
+    /// Ensures that the current map reserves space for, and defines the
+    /// coordinate system of, the border labels (if they are shown).
+    fn update_border_labels(&mut self) {
+        let coords = if self.show_border_labels {
+            Some(self.games.active().coordinate_system())
+        } else {
+            None
+        };
+        self.map.set_border_coordinates(coords);
+    }
+
+    /// Returns the window title for the current game, which is the game
+    /// name, preceded by the custom title (if any).
+    pub fn game_title(&self) -> String {
+        let name = self.games.active().name();
+        match &self.title {
+            Some(title) => format!("{} - {}", title, name),
+            None => name.to_string(),
+        }
+    }
+
+    /// Adds an entry to the journal for the current game.
+    pub fn record<S: Into<String>>(&mut self, entry: S) {
+        let entry = entry.into();
+        info!("{}", entry);
+        self.journal.record(entry)
+    }
+
+    /// Starts an operating round, in which the companies operate in the
+    /// order defined by [Game::operating_order], and records this in the
+    /// journal.
+    ///
+    /// Returns `false` if an operating round is already in progress, or if
+    /// the game has no companies.
+    pub fn start_operating_round(&mut self) -> bool {
+        if self.operating_round.is_some() {
+            return false;
+        }
+        let number = self
+            .revenue_history
+            .last()
+            .map(|revenue| revenue.round + 1)
+            .unwrap_or(1);
+        let order = self
+            .games
+            .active()
+            .operating_order()
+            .into_iter()
+            .map(|abbrev| abbrev.to_string())
+            .collect();
+        let Some(round) = round::OperatingRound::new(number, order) else {
+            return false;
+        };
+        self.record(format!("Operating round {} started", number));
+        self.operating_round = Some(round);
+        true
+    }
+
+    /// Ends the operating round that is in progress, and records this in the
+    /// journal.
+    ///
+    /// Returns `false` if there is no operating round in progress.
+    pub fn end_operating_round(&mut self) -> bool {
+        let Some(round) = self.operating_round.take() else {
+            return false;
+        };
+        self.record(format!("Operating round {} finished", round.number()));
+        true
+    }
+
+    /// Advances the operating round to its next step, and ends the operating
+    /// round once every company has operated.
+    ///
+    /// Returns `false` if there is no operating round in progress.
+    pub fn next_operating_step(&mut self) -> bool {
+        let Some(round) = &mut self.operating_round else {
+            return false;
+        };
+        if !round.next_step() {
+            self.end_operating_round();
+        }
+        true
+    }
+
+    /// Records the revenue earned by a company's trains in the journal and
+    /// in the revenue history, if this company is running its trains in the
+    /// current operating round, and advances to the next company.
+    ///
+    /// Returns `false` if this company is not running its trains in an
+    /// operating round.
+    pub fn record_operating_revenue(
+        &mut self,
+        company: &str,
+        trains: &[String],
+        revenue: usize,
+    ) -> bool {
+        let Some(round) = &self.operating_round else {
+            return false;
+        };
+        if round.company() != company
+            || round.step() != round::Step::RunRoutes
+        {
+            return false;
+        }
+        let number = round.number();
+        self.record(n18game::sim::routes_entry(company, trains, revenue));
+        self.revenue_history.push(round::Revenue {
+            round: number,
+            company: company.to_string(),
+            revenue,
+        });
+        self.next_operating_step()
+    }
+
+    /// Adds entries to the journal for the current game that describe
+    /// changes to the placed tokens, so that these changes can be undone.
+    pub fn record_token_changes(
+        &mut self,
+        entries: Vec<String>,
+        changes: Vec<TokenChange>,
+    ) {
+        if changes.is_empty() {
+            return;
+        }
+        for entry in &entries {
+            self.record(entry.as_str())
+        }
+        self.notify(MapEvent::TokensChanged(changes.clone()));
+        self.token_history.push((entries, changes))
+    }
+
+    /// Reverts the most recent changes to the placed tokens, and records
+    /// this in the journal.
+    ///
+    /// Returns `false` if there are no changes to undo.
+    pub fn undo_token_changes(&mut self) -> bool {
+        if let Some((entries, changes)) = self.token_history.pop() {
+            self.map.revert_token_changes(&changes);
+            for entry in entries.iter().rev() {
+                self.record(format!("Undo: {}", entry))
+            }
+            let reverted = changes
+                .iter()
+                .rev()
+                .map(|change| TokenChange {
+                    before: change.after,
+                    after: change.before,
+                    ..*change
+                })
+                .collect();
+            self.notify(MapEvent::TokensChanged(reverted));
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Reverts the most recent change that can be undone: either a change
+    /// to the placed tokens (see [Assets::undo_token_changes]) or a hex
+    /// reset (see [Assets::reset_hex]).
+    ///
+    /// Returns `false` if there are no changes to undo.
+    pub fn undo(&mut self) -> bool {
+        let reset_is_latest = self
+            .reset_history
+            .last()
+            .map(|reset| reset.token_changes == self.token_history.len())
+            .unwrap_or(false);
+        if reset_is_latest {
+            self.undo_hex_reset()
+        } else {
+            self.undo_token_changes()
+        }
+    }
+
+    /// Restores a map hex to its initial tile, rotation, and tokens (see
+    /// [Map::reset_hex]), and records this in the journal.
+    ///
+    /// This change can be undone with [Assets::undo].
+    /// Returns `false` if the hex cannot be reset (see [Map::can_reset_hex]).
+    pub fn reset_hex(&mut self, addr: HexAddress) -> bool {
+        if !self.map.can_reset_hex(addr) {
+            return false;
+        }
+        let before = Clipboard::copy(&self.map, addr, &[addr]);
+        self.map.reset_hex(addr);
+        let hex_name = self.hex_name(addr);
+        let entry = match self.map.tile_at(addr) {
+            Some(tile) => format!("Reset {} to tile {}", hex_name, tile.name),
+            None => format!("Reset {} to an empty hex", hex_name),
+        };
+        self.record(entry.as_str());
+        self.notify(MapEvent::HexesChanged(vec![addr]));
+        self.reset_history.push(HexReset {
+            addr,
+            entry,
+            before,
+            token_changes: self.token_history.len(),
+        });
+        true
+    }
+
+    /// Reverts the most recent hex reset, and records this in the journal.
+    ///
+    /// Returns `false` if there are no hex resets to undo.
+    fn undo_hex_reset(&mut self) -> bool {
+        let Some(reset) = self.reset_history.pop() else {
+            return false;
+        };
+        reset.before.paste(&mut self.map, reset.addr);
+        self.record(format!("Undo: {}", reset.entry));
+        self.notify(MapEvent::HexesChanged(vec![reset.addr]));
+        true
+    }
+
+    /// Applies a move to the current game (see [n18game::sim::Board]),
+    /// records it in the journal, and notifies each listener of the changes
+    /// to the map.
+    ///
+    /// Tiles can be placed on any map hex, regardless of the tile placement
+    /// rules, so that the user interface can also be used to edit maps.
+    /// Changes to the placed tokens can be undone (see [Assets::undo]).
+    pub fn apply_move(&mut self, mv: &Move) -> Result<(), MoveError> {
+        let previous = match mv {
+            Move::LayTile { addr, .. } | Move::RemoveTile { addr } => {
+                self.map.tile_at(*addr).map(|t| t.name.clone())
+            }
+            _ => None,
+        };
+        let token_space = match mv {
+            Move::PlaceToken { addr, space, .. }
+            | Move::RemoveToken { addr, space } => self
+                .map
+                .tile_at(*addr)
+                .and_then(|tile| tile.token_spaces().get(*space).copied())
+                .map(|token_space| (*addr, token_space)),
+            _ => None,
+        };
+        let token_at = |map: &Map| {
+            token_space.and_then(|(addr, space)| {
+                map.hex_state(addr)
+                    .and_then(|hs| hs.token_at(&space))
+                    .copied()
+            })
+        };
+        let token_before = token_at(&self.map);
+        let phase_ix = self.games.active().phase_ix();
+        let entry_count = self.journal.entries().len();
+        // NOTE: the user interface does not record which companies own each
+        // private company, and does not share its cached paths.
+        let mut board = Board {
+            game: self.games.active_mut(),
+            map: &mut self.map,
+            journal: &mut self.journal,
+            trains: &mut self.trains,
+            privates: &mut PrivateHoldings::default(),
+            treasuries: &mut self.treasuries,
+            path_cache: &mut PathCache::new(),
+            free_placement: true,
+        };
+        board.apply(mv)?;
+        let entries = self.journal.entries()[entry_count..].to_vec();
+        for entry in &entries {
+            info!("{}", entry);
+        }
+
+        match mv {
+            Move::LayTile {
+                addr,
+                tile,
+                rotation,
+                ..
+            } => self.notify(MapEvent::TilePlaced {
+                addr: *addr,
+                tile: tile.clone(),
+                rotation: *rotation,
+                previous,
+            }),
+            Move::RemoveTile { addr } => {
+                if let Some(tile) = previous {
+                    self.notify(MapEvent::TileRemoved { addr: *addr, tile })
+                }
+            }
+            Move::RotateTile { addr, rotation } => {
+                self.notify(MapEvent::TileRotated {
+                    addr: *addr,
+                    rotation: *rotation,
+                })
+            }
+            Move::PlaceToken { .. } | Move::RemoveToken { .. } => {
+                if let Some((addr, space)) = token_space {
+                    let changes = vec![TokenChange {
+                        addr,
+                        space,
+                        before: token_before,
+                        after: token_at(&self.map),
+                    }];
+                    self.notify(MapEvent::TokensChanged(changes.clone()));
+                    self.token_history.push((entries, changes))
+                }
+            }
+            _ => {
+                let game = self.games.active();
+                let set_phase =
+                    matches!(mv, Move::SetPhase(_) | Move::AdvancePhase);
+                if set_phase || game.phase_ix() != phase_ix {
+                    self.notify(MapEvent::PhaseChanged {
+                        phase_ix: game.phase_ix(),
+                        name: game.current_phase_name().to_string(),
+                    })
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Places a tile on a map hex, replacing the existing tile (if any),
+    /// and records this move in the journal.
+    ///
+    /// Returns `false` if the tile could not be placed.
+    pub fn place_tile(
+        &mut self,
+        addr: HexAddress,
+        tile: &str,
+        rotation: RotateCW,
+    ) -> bool {
+        let mv = Move::LayTile {
+            addr,
+            tile: tile.to_string(),
+            rotation,
+            company: None,
+        };
+        self.apply_move(&mv).is_ok()
+    }
+
+    /// Rotates the tile on a map hex by one turn, and records this move in
+    /// the journal.
+    ///
+    /// Returns `false` if there is no tile on this map hex.
+    pub fn rotate_tile(&mut self, addr: HexAddress, clockwise: bool) -> bool {
+        let rotation = match self.map.hex_state(addr) {
+            Some(hs) => {
+                if clockwise {
+                    hs.rotation().rotate_cw()
+                } else {
+                    hs.rotation().rotate_anti_cw()
+                }
+            }
+            None => return false,
+        };
+        self.set_tile_rotation(addr, rotation)
+    }
+
+    /// Changes the rotation of the tile on a map hex, and records this move
+    /// in the journal.
+    ///
+    /// Returns `false` if there is no tile on this map hex.
+    pub fn set_tile_rotation(
+        &mut self,
+        addr: HexAddress,
+        rotation: RotateCW,
+    ) -> bool {
+        self.apply_move(&Move::RotateTile { addr, rotation })
+            .is_ok()
+    }
+
+    /// Removes the tile from a map hex, and records this move in the
+    /// journal.
+    ///
+    /// Returns `false` if there is no tile on this map hex.
+    pub fn remove_tile(&mut self, addr: HexAddress) -> bool {
+        self.apply_move(&Move::RemoveTile { addr }).is_ok()
+    }
+
+    /// Rotates the tile planned for a map hex by one turn.
+    ///
+    /// Returns `false` if no tile is planned for this map hex.
+    pub fn rotate_planned_tile(
+        &mut self,
+        addr: HexAddress,
+        clockwise: bool,
+    ) -> bool {
+        let Some(planned) = self.map.plan().get(addr) else {
+            return false;
+        };
+        let rotation = if clockwise {
+            planned.rotation.rotate_cw()
+        } else {
+            planned.rotation.rotate_anti_cw()
+        };
+        let tile = planned.tile.clone();
+        self.map.plan_tile(addr, &tile, rotation)
+    }
+
+    /// Places every planned tile on the map, recording each placement in
+    /// the journal (see [Assets::place_tile]), and returns the number of
+    /// tiles that were placed.
+    pub fn commit_plan(&mut self) -> usize {
+        let plan = self.map.take_plan();
+        plan.iter()
+            .filter(|(addr, planned)| {
+                self.place_tile(*addr, &planned.tile, planned.rotation)
+            })
+            .count()
+    }
+
+    /// Changes the current phase of the active game, and records this in
+    /// the journal.
+    ///
+    /// Returns `false` if the phase was not changed.
+    pub fn set_phase(&mut self, phase_ix: usize) -> bool {
+        self.apply_move(&Move::SetPhase(phase_ix)).is_ok()
+    }
+
+    /// Registers a listener that will be notified of each change to the
+    /// map, and returns an identifier that can be used to unsubscribe.
+    pub fn subscribe<F>(&mut self, listener: F) -> ListenerId
+    where
+        F: FnMut(&MapEvent) + 'static,
+    {
+        self.listeners.add(listener)
+    }
+
+    /// Removes a listener, and returns `false` if there was no such
+    /// listener.
+    pub fn unsubscribe(&mut self, id: ListenerId) -> bool {
+        self.listeners.remove(id)
+    }
+
+    /// Notifies each listener of a change to the map.
+    ///
+    /// Methods such as [Assets::place_tile] call this automatically; it
+    /// only needs to be called after modifying the map directly.
+    ///
+    /// If the map was replaced, or its tile definitions were changed, this
+    /// also discards the cached map connectivity and paths.
+    pub fn notify(&mut self, event: MapEvent) {
+        if event == MapEvent::MapReset {
+            // NOTE: replace the cache rather than clearing it, because a
+            // route search may be using the previous cache.
+            self.path_cache = Default::default();
+        }
+        self.listeners.notify(&event)
+    }
+
+    /// Returns the current state of the game, including the journal, the
+    /// train supply, and company treasuries.
+    pub fn game_state(&self) -> n18game::GameState {
+        self.games
+            .active()
+            .save(&self.map)
+            .with_journal(self.journal.clone())
+            .with_train_supply(self.trains.clone())
+            .with_title(self.title.clone())
+            .with_treasuries(self.treasuries.clone())
+    }
+
+    /// Returns the connectivity of the current map, which is cached until
+    /// the map is modified.
+    ///
+    /// If a route search is using the cache, this returns the connectivity
+    /// of the current map without waiting for the search to finish.
+    pub fn map_graph(&self) -> MapGraph {
+        match self.path_cache.try_lock() {
+            Ok(mut cache) => {
+                cache.sync(&self.map);
+                cache.graph().clone()
+            }
+            Err(_) => MapGraph::new(&self.map),
+        }
+    }
 }
 
 /// Global UI actions, which are not specific to the current [State].
@@ -165,14 +812,128 @@ pub struct UiAction {
 /// We wrap this type in a [UiAction] struct, in order to make the type public
 /// while keeping the enum variants private.
 enum Action {
-    /// Create a new instance of the `nth` game in the library.
+    /// Create a new instance of the `nth` game in the library, prompting the
+    /// user to select a map variant if the game has more than one variant.
     NewGame(usize),
+    /// Create a new instance of the `nth` game in the library, using the
+    /// `nth` map variant.
+    NewGameVariant(usize, usize),
+    /// Create a new instance of the `nth` game in the library, using a
+    /// random setup with the provided seed, or the standard setup.
+    NewGameSetup(usize, Option<u64>),
     /// Load a game from the provided path.
     LoadGame(std::path::PathBuf),
+    /// Reload the most recently loaded or saved game file, discarding any
+    /// unsaved changes.
+    ReloadGame,
     /// Save the current game to the provided path.
     SaveGame(std::path::PathBuf),
     /// Save an image to the provided path.
     SaveImage(std::path::PathBuf, cairo::ImageSurface),
+    /// Save a region of the map, at the chosen scale, to the provided path.
+    SaveRegion(std::path::PathBuf, Region),
+    /// Copy the visible part of the map to the clipboard.
+    CopyView,
+    /// Copy a region of the map, at the chosen scale, to the clipboard.
+    CopyRegion(Region),
+    /// Ask the user whether to add a title block and legend to an image of
+    /// the map, at the chosen resolution (in dots per inch).
+    ExportMap(f64),
+    /// Prompt the user to select where to save an image of the map, at the
+    /// chosen resolution (in dots per inch), with or without a title block
+    /// and legend.
+    ExportMapTo(f64, bool),
+    /// Save an image of the map, at the chosen resolution (in dots per
+    /// inch), with or without a title block and legend, to the provided
+    /// path.
+    SaveMap(std::path::PathBuf, f64, bool),
+    /// Save the current game state and an image of the map as a single
+    /// bundle at the provided path.
+    ExportBundle(std::path::PathBuf),
+    /// Load a game state from the bundle at the provided path.
+    ImportBundle(std::path::PathBuf),
+    /// Prompt the user to select the company whose tokens will replace the
+    /// `nth` company's tokens.
+    ReplaceTokens(usize),
+    /// Place each company token listed in the file at the provided path.
+    ImportTokens(std::path::PathBuf),
+    /// Replace each token of the first company with a token of the second
+    /// company, where companies are identified by their index.
+    ReplaceCompanyTokens(usize, usize),
+    /// Prompt the user to select an action that is available in the current
+    /// state.
+    ShowCommandPalette,
+    /// Run an action that was selected from the command palette or the hex
+    /// context menu.
+    RunCommand(Command),
+    /// Show a context menu at the active hex, which lists the actions that
+    /// can be applied to this hex in the current state.
+    ShowHexMenu,
+    /// Merge the tile catalogue at the provided path into the current map's
+    /// tile catalogue.
+    MergeTiles(std::path::PathBuf),
+    /// Draw the PNG image at the provided path beneath the map.
+    SetBackground(std::path::PathBuf),
+    /// Quit the application, once the user has confirmed that they want to
+    /// quit.
+    Quit,
+    /// Prompt the user to enter a new name for the most recently loaded or
+    /// saved game file.
+    RenameGame,
+    /// Rename the most recently loaded or saved game file.
+    RenameGameTo(String),
+    /// Add a note to the journal for the current game.
+    AddNote(String),
+    /// Set the custom title of the current game, or remove the custom title
+    /// if the text is empty.
+    SetTitle(String),
+    /// Prompt the user to select the user interface language.
+    SelectLanguage,
+    /// Use the `nth` available language, where `0` is English.
+    SetLanguage(usize),
+    /// Prompt the user to select the map theme.
+    SelectTheme,
+    /// Use the `nth` predefined theme (see [n18hex::theme::THEME_NAMES]).
+    SetTheme(usize),
+    /// Rotate and mirror the map as it is shown in the window.
+    SetView(view::ViewTransform),
+}
+
+impl Action {
+    /// Returns the action for a file that was dropped onto the window:
+    /// tile catalogues are merged into the current map's catalogue, other
+    /// JSON files are loaded as saved games, and PNG images are drawn
+    /// beneath the map.
+    ///
+    /// Returns `None` if the file is not a JSON file or a PNG image.
+    fn for_dropped_file(path: std::path::PathBuf) -> Option<Self> {
+        let has_extension = |name: &str| {
+            path.extension()
+                .map(|ext| ext.eq_ignore_ascii_case(name))
+                .unwrap_or(false)
+        };
+        if has_extension("png") {
+            return Some(Action::SetBackground(path));
+        }
+        if !has_extension("json") {
+            return None;
+        }
+        // NOTE: tile catalogues are the only files that define "tiles";
+        // any other file is passed to load_game(), which will report an
+        // error if it is not a valid game state.
+        let is_catalogue = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| {
+                serde_json::from_str::<serde_json::Value>(&text).ok()
+            })
+            .map(|value| value.get("tiles").is_some())
+            .unwrap_or(false);
+        if is_catalogue {
+            Some(Action::MergeTiles(path))
+        } else {
+            Some(Action::LoadGame(path))
+        }
+    }
 }
 
 impl From<UiAction> for Action {
@@ -203,6 +964,8 @@ pub enum UiResponse {
     ZoomIn,
     /// Decrease the hex size.
     ZoomOut,
+    /// Change the hex size so that the entire map fits in the window.
+    ZoomToFit,
 }
 
 /// Defines the user interface.
@@ -216,6 +979,25 @@ pub struct UserInterface {
     receiver: Receiver<UiAction>,
     min_hex_diameter: f64,
     max_hex_diameter: f64,
+    /// The file in which the session is saved on exit, if any.
+    session_path: Option<std::path::PathBuf>,
+    /// The most recently loaded or saved game file, if any.
+    game_path: Option<std::path::PathBuf>,
+    /// The languages, other than English, that the user can choose.
+    languages: Vec<lang::Strings>,
+    /// The name of the map theme (see [n18hex::theme::THEME_NAMES]).
+    theme_name: &'static str,
+    /// Records user input events, if recording is enabled.
+    recorder: Option<script::Recorder>,
+    #[cfg(feature = "hot-reload")]
+    watched: watch::WatchedFiles,
+    /// Serves images of the current map to spectators, and records the
+    /// number of journal entries when the map was last published.
+    #[cfg(feature = "web")]
+    spectator: Option<(n18web::Spectator, usize)>,
+    /// Shares the current game with other players, if any.
+    #[cfg(feature = "net")]
+    shared: Option<net::Shared>,
 }
 
 impl UserInterface {
@@ -228,13 +1010,38 @@ impl UserInterface {
         let games = Games::new(games);
         let start_state = state::start::Start::new();
         let map = start_state.dummy_map();
-        let controller = controller.into();
+        let mut controller: Controller = controller.into();
 
         let hex = Hex::default();
         let min_hex_diameter: f64 = 56.0;
         let max_hex_diameter: f64 = 164.0;
 
-        let mut assets = Assets { hex, map, games };
+        let mut assets = Assets {
+            hex,
+            map,
+            games,
+            clipboard: None,
+            show_coordinates: false,
+            planning: false,
+            show_plan: true,
+            show_border_labels: false,
+            view: Default::default(),
+            journal: Journal::new(),
+            title: None,
+            strings: lang::Strings::english(),
+            trains: TrainSupply::default(),
+            treasuries: Treasuries::default(),
+            token_history: vec![],
+            reset_history: vec![],
+            last_revenue: None,
+            route_cache: Default::default(),
+            path_cache: Default::default(),
+            operating_round: None,
+            revenue_history: vec![],
+            inspected_hex: None,
+            background: None,
+            listeners: events::Listeners::default(),
+        };
         let state = State::Start(start_state);
 
         // Determine the surface dimensions necessary to contain the state
@@ -248,6 +1055,18 @@ impl UserInterface {
 
         let (sender, receiver) = std::sync::mpsc::channel();
 
+        // Load saved games and merge tile catalogues that are dropped onto
+        // the window.
+        let responder = Responder::new(sender.clone(), controller.ping_tx());
+        controller.on_file_drop(move |path| {
+            match Action::for_dropped_file(path.clone()) {
+                Some(action) => {
+                    responder.send(action);
+                }
+                None => info!("Ignoring dropped file {}", path.display()),
+            }
+        });
+
         UserInterface {
             assets,
             state,
@@ -258,12 +1077,95 @@ impl UserInterface {
             receiver,
             min_hex_diameter,
             max_hex_diameter,
+            session_path: None,
+            game_path: None,
+            languages: vec![],
+            theme_name: n18hex::theme::THEME_NAMES[0],
+            recorder: None,
+            #[cfg(feature = "hot-reload")]
+            watched: Default::default(),
+            #[cfg(feature = "web")]
+            spectator: None,
+            #[cfg(feature = "net")]
+            shared: None,
         }
     }
 
     /// Draws the current state of the user interface.
+    ///
+    /// Note that the background image (if any) is only drawn here, so that
+    /// it is never included in exported images.
     pub fn draw(&self) {
-        self.state.draw(&self.assets, self.canvas.context())
+        self.canvas.discard();
+        let ctx = self.canvas.context();
+        let m = ctx.matrix();
+        ctx.transform(self.view_matrix());
+        match &self.assets.background {
+            Some(background) if self.state.as_start().is_none() => background
+                .draw_beneath(&self.assets.hex, ctx, |ctx| self.draw_on(ctx)),
+            _ => self.draw_on(ctx),
+        }
+        ctx.set_matrix(m);
+    }
+
+    /// Returns the matrix that transforms map coordinates into window
+    /// coordinates (see [Assets::view]).
+    ///
+    /// This is the identity matrix for the start state, which does not show
+    /// a game map.
+    fn view_matrix(&self) -> cairo::Matrix {
+        if self.state.as_start().is_some() {
+            return cairo::Matrix::identity();
+        }
+        let (width, height) = self.assets.map.drawing_size(&self.assets.hex);
+        self.assets.view.matrix(width, height)
+    }
+
+    /// Converts the point `(x, y)` from window coordinates into map
+    /// coordinates, undoing the view transformation (see [Assets::view]).
+    pub fn window_to_map(&self, x: f64, y: f64) -> (f64, f64) {
+        if self.state.as_start().is_some() {
+            return (x, y);
+        }
+        let (width, height) = self.assets.map.drawing_size(&self.assets.hex);
+        self.assets.view.to_map(width, height, x, y)
+    }
+
+    /// Rotates and mirrors the map as it is shown in the window, and resizes
+    /// the drawing area to fit the transformed map.
+    ///
+    /// This does not change the map hex coordinates or the saved game.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn set_view(&mut self, view: view::ViewTransform) -> UiResponse {
+        if view == self.assets.view {
+            return UiResponse::None;
+        }
+        self.assets.view = view;
+        if self.state.as_start().is_none() {
+            // NOTE: the drawing surface may need to be enlarged, because the
+            // width and height of the map are swapped when it is rotated by
+            // 90 or 270 degrees.
+            self.reset_and_redraw();
+        }
+        UiResponse::None
+    }
+
+    /// Draws the current state of the user interface on the provided
+    /// context.
+    fn draw_on(&self, ctx: &cairo::Context) {
+        self.state.draw(&self.assets, ctx);
+        // NOTE: the start state draws a placeholder map, which has no
+        // meaningful coordinates.
+        if self.assets.show_coordinates && self.state.as_start().is_none() {
+            let hex = &self.assets.hex;
+            let coords = self.assets.games.active().coordinate_system();
+            let mut hex_iter = self.assets.map.hex_iter(hex, ctx);
+            n18brush::draw_hex_coordinates(hex, ctx, &mut hex_iter, &coords);
+        }
+        if self.state.as_start().is_none() {
+            let hex = &self.assets.hex;
+            n18brush::draw_border_labels(hex, ctx, &self.assets.map);
+        }
     }
 
     /// Returns the minimum allowed hex diameter, which limits zooming out.
@@ -278,6 +1180,9 @@ impl UserInterface {
 
     #[must_use = "pass the response to UserInterface::respond"]
     pub fn handle_key_press(&mut self, event: &KeyPress) -> UiResponse {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record(self.state.name(), event.into());
+        }
         let response = self.keymap.handle_key_press(
             &mut self.assets,
             &mut self.controller,
@@ -301,6 +1206,11 @@ impl UserInterface {
 
     #[must_use = "pass the response to UserInterface::respond"]
     pub fn handle_button_press(&mut self, event: &ButtonPress) -> UiResponse {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record(self.state.name(), event.into());
+        }
+        let (x, y) = self.window_to_map(event.x, event.y);
+        let event = &ButtonPress { x, y, ..*event };
         let response = self.keymap.handle_button_press(
             &mut self.assets,
             &mut self.controller,
@@ -322,6 +1232,66 @@ impl UserInterface {
         }
     }
 
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn handle_button_drag(&mut self, event: &ButtonDrag) -> UiResponse {
+        let (x0, y0) = self.window_to_map(event.x0, event.y0);
+        let (x, y) = self.window_to_map(event.x, event.y);
+        let event = &ButtonDrag {
+            x0,
+            y0,
+            x,
+            y,
+            ..*event
+        };
+        let response = self.keymap.handle_button_drag(
+            &mut self.assets,
+            &mut self.controller,
+            &mut self.state,
+            &mut self.canvas,
+            &self.sender,
+            event,
+        );
+        if let Some((response, new_state_opt)) = response {
+            if let Some(new_state) = new_state_opt {
+                if new_state.is_default_state() {
+                    self.set_default_title();
+                }
+                self.state = new_state
+            }
+            response
+        } else {
+            UiResponse::None
+        }
+    }
+
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn handle_pointer_motion(
+        &mut self,
+        event: &PointerMotion,
+    ) -> UiResponse {
+        let (x, y) = self.window_to_map(event.x, event.y);
+        let event = &PointerMotion { x, y };
+        let response = self.keymap.handle_pointer_motion(
+            &mut self.assets,
+            &mut self.controller,
+            &mut self.state,
+            &mut self.canvas,
+            &self.sender,
+            event,
+        );
+        if let Some((response, new_state_opt)) = response {
+            if let Some(new_state) = new_state_opt {
+                if new_state.is_default_state() {
+                    self.set_default_title();
+                }
+                self.state = new_state
+            }
+            response
+        } else {
+            UiResponse::None
+        }
+    }
+
     pub fn respond(&mut self, response: UiResponse) {
         match response {
             UiResponse::ZoomIn => {
@@ -340,20 +1310,88 @@ impl UserInterface {
                     self.zoom_and_redraw();
                 }
             }
+            UiResponse::ZoomToFit => {
+                if let Some(hex_d) = self.hex_diameter_to_fit() {
+                    self.assets.hex.resize(hex_d);
+                    self.zoom_and_redraw();
+                    self.controller.set_viewport(0.0, 0.0);
+                }
+            }
             UiResponse::Redraw => {
                 // NOTE: must redraw to the backing surface.
                 self.draw();
                 self.controller.redraw();
+                self.refresh_panels();
             }
             UiResponse::ResetGame => {
                 // NOTE: request this size request is only required when the
                 // game map has been replaced (e.g., by starting a new game or
                 // by loading a saved game).
                 self.reset_and_redraw();
+                self.refresh_panels();
+            }
+            UiResponse::Quit => {
+                self.save_session();
+                self.controller.quit()
             }
-            UiResponse::Quit => self.controller.quit(),
             UiResponse::None => {}
         }
+        self.update_status();
+        #[cfg(feature = "web")]
+        self.update_spectators(response);
+        #[cfg(feature = "net")]
+        self.share_changes();
+    }
+
+    /// Updates the status bar to show the contents for the current state
+    /// (see [State::status]).
+    pub fn update_status(&mut self) {
+        let status = self.state.status(&self.assets);
+        self.controller.set_status(&status);
+    }
+
+    /// Updates the contents of each open auxiliary panel.
+    ///
+    /// The route summary panel is only updated while showing the routes
+    /// that were found for a company.
+    ///
+    /// The hex inspector panel shows the active hex (if any).
+    pub fn refresh_panels(&mut self) {
+        use panel::{HexSummary, TileInventory};
+
+        if self.controller.is_panel_open(PanelKind::Journal) {
+            let panel = Panel::Journal(self.assets.journal.clone());
+            let title = panel.title(&self.assets.strings);
+            self.controller.show_panel(&title, &panel);
+        }
+        if self.controller.is_panel_open(PanelKind::Tiles) {
+            let panel = Panel::Tiles(TileInventory::new(&self.assets.map));
+            let title = panel.title(&self.assets.strings);
+            self.controller.show_panel(&title, &panel);
+        }
+        if self.controller.is_panel_open(PanelKind::Routes) {
+            let summary = self
+                .state
+                .as_find_routes_found()
+                .and_then(|found| found.route_summary(&self.assets));
+            if let Some(summary) = summary {
+                let panel = Panel::Routes(summary);
+                let title = panel.title(&self.assets.strings);
+                self.controller.show_panel(&title, &panel);
+            }
+        }
+        if self.controller.is_panel_open(PanelKind::Hex) {
+            let summary = self
+                .state
+                .active_hex()
+                .and_then(|addr| HexSummary::new(&self.assets, addr));
+            if let Some(summary) = summary {
+                self.assets.inspected_hex = Some(summary.description.addr);
+                let panel = Panel::Hex(summary);
+                let title = panel.title(&self.assets.strings);
+                self.controller.show_panel(&title, &panel);
+            }
+        }
     }
 
     /// Returns the dimensions of the current game map, in pixels.
@@ -361,6 +1399,30 @@ impl UserInterface {
         canvas::required_dims(&self.state, &self.assets)
     }
 
+    /// Returns the largest hex diameter for which the entire map fits in the
+    /// window, limited by the minimum and maximum hex diameters.
+    ///
+    /// Returns `None` if the window size is not known.
+    pub fn hex_diameter_to_fit(&self) -> Option<f64> {
+        let (win_width, win_height) = self.controller.window_size()?;
+        // NOTE: the map size is a linear function of the hex diameter, but
+        // the margin around the map edges may include a constant term.
+        let (d0, d1) = (self.min_hex_diameter, self.max_hex_diameter);
+        let size = |d: f64| {
+            let (width, height) = self.assets.map.drawing_size(&Hex::new(d));
+            self.assets.view.size(width, height)
+        };
+        let (w0, h0) = size(d0);
+        let (w1, h1) = size(d1);
+        let fit = |size: f64, s0: f64, s1: f64| {
+            d0 + (size - s0) * (d1 - d0) / (s1 - s0)
+        };
+        let hex_d = fit(win_width as f64, w0, w1)
+            .min(fit(win_height as f64, h0, h1))
+            .clamp(d0, d1);
+        Some(hex_d)
+    }
+
     /// Requests the drawing area to update its size, and redraws the current
     /// game state.
     ///
@@ -398,54 +1460,420 @@ impl UserInterface {
         self.zoom_and_redraw()
     }
 
-    /// Sets the window title to the game name, replacing any state-specific
-    /// title.
-    pub fn set_default_title(&mut self) {
-        let title = self.assets.games.active().name();
-        self.controller.set_window_title(title);
-    }
-
-    /// Responds to an event triggered by something other than a UI event
-    /// (e.g., a message from a task running in a separate thread).
+    /// Restores the session saved in `path`, and saves the session to this
+    /// file on exit.
+    ///
+    /// This reloads the most recent game (if any) and restores the zoom
+    /// level, the map scroll offsets, and the window size.
+    /// If the session cannot be read, the default session is used instead.
     #[must_use = "pass the response to UserInterface::respond"]
-    pub fn ping(&mut self, dest: PingDest) -> UiResponse {
-        match dest {
-            PingDest::State => {
-                let (response, new_state_opt) =
-                    self.state.ping(&mut self.assets, &mut self.controller);
-                if let Some(new_state) = new_state_opt {
-                    if new_state.is_default_state() {
-                        self.set_default_title();
-                    }
-                    self.state = new_state;
+    pub fn restore_session(
+        &mut self,
+        path: std::path::PathBuf,
+    ) -> UiResponse {
+        let session = session::Session::read(&path).unwrap_or_else(|e| {
+            error!("{}", e);
+            Default::default()
+        });
+        self.session_path = Some(path);
+        if let Some((width, height)) = session.window_size {
+            self.controller.set_window_size(width, height);
+        }
+        if let Some(hex_d) = session.hex_diameter {
+            if hex_d > self.min_hex_diameter && hex_d < self.max_hex_diameter
+            {
+                self.assets.hex.resize(hex_d);
+            }
+        }
+        *self.controller.file_history_mut() = control::FileHistory::new(
+            session.recent_files,
+            session.game_dirs,
+        );
+        // NOTE: the view is applied when a game is started or loaded.
+        self.assets.view = session.view;
+        if let Some(settings) = session.background {
+            // NOTE: the background image is not drawn until a game has
+            // been started or loaded, so there is no need to redraw.
+            let _response = self.set_background(settings);
+        }
+        if let Some(language) = &session.language {
+            let strings_opt =
+                self.languages.iter().find(|s| s.language == *language);
+            match strings_opt {
+                Some(strings) => self.assets.strings = strings.clone(),
+                None => error!("There is no language called '{}'", language),
+            }
+        }
+        if let Some(theme) = &session.theme {
+            let names = n18hex::theme::THEME_NAMES;
+            match names.iter().position(|name| name == theme) {
+                Some(ix) => {
+                    // NOTE: the map is drawn when a game is started or
+                    // loaded, so there is no need to redraw.
+                    let _response = self.set_theme(ix);
                 }
-                response
+                None => error!("There is no theme called '{}'", theme),
             }
-            PingDest::TopLevel => {
-                let msg = self.receiver.recv().unwrap();
+        }
+        let response = match session.game_path {
+            Some(game_path) if game_path.exists() => {
+                self.load_game(game_path)
+            }
+            _ => UiResponse::None,
+        };
+        if let UiResponse::ResetGame = response {
+            if let Some((x, y)) = session.viewport {
+                self.controller.set_viewport(x, y);
+            }
+        }
+        response
+    }
+
+    /// Returns the current session, which can be restored with
+    /// [UserInterface::restore_session].
+    pub fn session(&self) -> session::Session {
+        session::Session {
+            game_path: self.game_path.clone(),
+            hex_diameter: Some(self.assets.hex.max_d),
+            viewport: self.controller.viewport(),
+            window_size: self.controller.window_size(),
+            language: (!self.assets.strings.is_english())
+                .then(|| self.assets.strings.language.clone()),
+            theme: (self.theme_name != n18hex::theme::THEME_NAMES[0])
+                .then(|| self.theme_name.to_string()),
+            view: self.assets.view,
+            background: self
+                .assets
+                .background
+                .as_ref()
+                .map(|background| background.settings().clone()),
+            recent_files: self
+                .controller
+                .file_history()
+                .recent_files()
+                .to_vec(),
+            game_dirs: self.controller.file_history().game_dirs().clone(),
+        }
+    }
+
+    /// Saves the current session to the file provided to
+    /// [UserInterface::restore_session], if any.
+    pub fn save_session(&self) {
+        if let Some(path) = &self.session_path {
+            if let Err(e) = self.session().write(path) {
+                error!("Could not save session: {}", e)
+            }
+        }
+    }
+
+    /// Starts recording key-press and button-press events, discarding any
+    /// events that were previously recorded.
+    pub fn start_recording(&mut self) {
+        self.recorder = Some(script::Recorder::default())
+    }
+
+    /// Stops recording events, and returns the recorded events (if
+    /// recording was enabled).
+    pub fn stop_recording(&mut self) -> Option<script::Script> {
+        self.recorder.take().map(|recorder| recorder.finish())
+    }
+
+    /// Returns the events that have been recorded so far, if recording is
+    /// enabled.
+    pub fn recorded(&self) -> Option<&script::Script> {
+        self.recorder.as_ref().map(|recorder| recorder.script())
+    }
+
+    /// Passes each event in the script to the appropriate event handler,
+    /// and responds to each event.
+    ///
+    /// Returns an error, without handling the event, if an event was
+    /// recorded in a different state than the current state.
+    pub fn replay(
+        &mut self,
+        script: &script::Script,
+    ) -> Result<(), script::ReplayError> {
+        for (ix, step) in script.steps.iter().enumerate() {
+            let state = self.state.name();
+            if step.state != state {
+                return Err(script::ReplayError::StateMismatch {
+                    step: ix,
+                    expected: step.state.clone(),
+                    found: state.to_string(),
+                });
+            }
+            let response = if let Some(event) = step.event.key_press() {
+                self.handle_key_press(&event)
+            } else if let Some(event) = step.event.button_press() {
+                self.handle_button_press(&event)
+            } else {
+                UiResponse::None
+            };
+            self.respond(response);
+        }
+        Ok(())
+    }
+
+    /// Sets the window title to the game name and custom title (if any),
+    /// replacing any state-specific title.
+    pub fn set_default_title(&mut self) {
+        let title = self.assets.game_title();
+        self.controller.set_window_title(&title);
+    }
+
+    /// Responds to an event triggered by something other than a UI event
+    /// (e.g., a message from a task running in a separate thread).
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn ping(&mut self, dest: PingDest) -> UiResponse {
+        match dest {
+            PingDest::State => {
+                let (response, new_state_opt) =
+                    self.state.ping(&mut self.assets, &mut self.controller);
+                if let Some(new_state) = new_state_opt {
+                    if new_state.is_default_state() {
+                        self.set_default_title();
+                    }
+                    self.state = new_state;
+                }
+                response
+            }
+            PingDest::TopLevel => {
+                // NOTE: each ping should follow an action, but we must never
+                // block the main loop while waiting for an action.
+                let Ok(msg) = self.receiver.try_recv() else {
+                    return UiResponse::None;
+                };
                 let action = msg.into();
                 match action {
-                    Action::NewGame(game_ix) => self.new_game(game_ix),
+                    Action::NewGame(game_ix) => self.select_variant(game_ix),
+                    Action::NewGameVariant(game_ix, variant_ix) => {
+                        self.new_game_variant(game_ix, variant_ix)
+                    }
+                    Action::NewGameSetup(game_ix, seed) => {
+                        self.new_game_setup(game_ix, seed)
+                    }
                     Action::LoadGame(path) => self.load_game(path),
+                    Action::ReloadGame => self.reload_game(),
                     Action::SaveGame(path) => self.save_game(path),
                     Action::SaveImage(path, image) => {
                         self.save_image(path, image)
                     }
+                    Action::SaveRegion(path, region) => {
+                        self.save_region(path, region)
+                    }
+                    Action::CopyView => self.copy_view(),
+                    Action::CopyRegion(region) => self.copy_region(region),
+                    Action::ExportMap(dpi) => self.export_map(dpi),
+                    Action::ExportMapTo(dpi, legend) => {
+                        self.export_map_to(dpi, legend)
+                    }
+                    Action::SaveMap(path, dpi, legend) => {
+                        if legend {
+                            let decorations = self.export_decorations();
+                            self.save_map_with(path, dpi, &decorations)
+                        } else {
+                            self.save_map(path, dpi)
+                        }
+                    }
+                    Action::ExportBundle(path) => self.export_bundle(path),
+                    Action::ImportBundle(path) => self.import_bundle(path),
+                    Action::ReplaceTokens(from_ix) => {
+                        self.select_replacement_tokens(from_ix)
+                    }
+                    Action::ImportTokens(path) => self.import_tokens(path),
+                    Action::ReplaceCompanyTokens(from_ix, to_ix) => {
+                        self.replace_company_tokens(from_ix, to_ix)
+                    }
+                    Action::ShowCommandPalette => self.show_command_palette(),
+                    Action::RunCommand(command) => {
+                        self.handle_key_press(&command.key_press())
+                    }
+                    Action::ShowHexMenu => self.show_hex_menu(),
+                    Action::MergeTiles(path) => self.merge_tiles(path),
+                    Action::SetBackground(path) => {
+                        let settings =
+                            background::BackgroundSettings::new(path);
+                        self.set_background(settings)
+                    }
+                    Action::Quit => UiResponse::Quit,
+                    Action::RenameGame => self.rename_game(),
+                    Action::RenameGameTo(name) => self.rename_game_to(&name),
+                    Action::AddNote(note) => self.add_note(&note),
+                    Action::SetTitle(title) => self.set_title(&title),
+                    Action::SelectLanguage => self.select_language(),
+                    Action::SetLanguage(ix) => self.set_language(ix),
+                    Action::SelectTheme => self.select_theme(),
+                    Action::SetTheme(ix) => self.set_theme(ix),
+                    Action::SetView(view) => self.set_view(view),
                 }
             }
+            #[cfg(feature = "hot-reload")]
+            PingDest::ReloadFiles => self.reload_files(),
+            #[cfg(feature = "net")]
+            PingDest::Session => self.sync_session(),
         }
     }
 
     /// Creates a new game, identified by index into the game library.
+    ///
+    /// If this game has more than one map variant, the user is first asked
+    /// to select a variant.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn select_variant(&mut self, game_ix: usize) -> UiResponse {
+        let variants: Vec<&str> = match self.assets.games.iter().nth(game_ix)
+        {
+            Some(game) => game.variants().to_vec(),
+            None => return UiResponse::None,
+        };
+        if variants.len() < 2 {
+            return self.new_game_variant(game_ix, 0);
+        }
+        let responder =
+            Responder::new(self.sender.clone(), self.controller.ping_tx());
+        self.controller.select_index(
+            self.assets.strings.tr("Select a map variant"),
+            &variants,
+            move |ix_opt| {
+                if let Some(ix) = ix_opt {
+                    responder.send(Action::NewGameVariant(game_ix, ix));
+                }
+            },
+        );
+        UiResponse::None
+    }
+
+    /// Creates a new game with the `nth` map variant, where the game is
+    /// identified by index into the game library.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn new_game_variant(
+        &mut self,
+        game_ix: usize,
+        variant_ix: usize,
+    ) -> UiResponse {
+        if !self.assets.games.set_active_index(game_ix) {
+            return UiResponse::None;
+        }
+        let game = self.assets.games.active_mut();
+        let variant = game.variants().get(variant_ix).map(|v| v.to_string());
+        match variant {
+            Some(variant) if game.set_variant(&variant) => {
+                self.select_setup(game_ix)
+            }
+            _ => UiResponse::None,
+        }
+    }
+
+    /// Creates a new game, identified by index into the game library.
+    ///
+    /// If this game supports a random setup, the user is first asked to
+    /// choose between the standard setup and a random setup.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn select_setup(&mut self, game_ix: usize) -> UiResponse {
+        let random_setup = self
+            .assets
+            .games
+            .iter()
+            .nth(game_ix)
+            .is_some_and(|game| game.has_random_setup());
+        if !random_setup {
+            return self.new_game_setup(game_ix, None);
+        }
+        let responder =
+            Responder::new(self.sender.clone(), self.controller.ping_tx());
+        let setups = [
+            self.assets.strings.tr("Standard setup"),
+            self.assets.strings.tr("Random setup"),
+        ];
+        self.controller.select_index(
+            self.assets.strings.tr("Select the game setup"),
+            &setups,
+            move |ix_opt| {
+                if let Some(ix) = ix_opt {
+                    let seed = (ix == 1).then(n18game::setup::new_seed);
+                    responder.send(Action::NewGameSetup(game_ix, seed));
+                }
+            },
+        );
+        UiResponse::None
+    }
+
+    /// Creates a new game, identified by index into the game library, using
+    /// a random setup with the provided `seed`, or the standard setup if
+    /// `seed` is `None`.
+    ///
+    /// The random setup choices are recorded in the game journal.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn new_game_setup(
+        &mut self,
+        game_ix: usize,
+        seed: Option<u64>,
+    ) -> UiResponse {
+        if !self.assets.games.set_active_index(game_ix) {
+            return UiResponse::None;
+        }
+        let game = self.assets.games.active_mut();
+        let setup = match seed {
+            Some(seed) => match game.randomise_setup(seed) {
+                Some(setup) => Some(setup),
+                None => {
+                    let msg = self.assets.strings.format(
+                        "{game} does not support a random setup",
+                        &[("game", &game.name())],
+                    );
+                    error!("{}", msg);
+                    let title =
+                        self.assets.strings.tr("Could not start game");
+                    self.controller.show_error(title, &msg);
+                    return UiResponse::None;
+                }
+            },
+            None => {
+                game.standard_setup();
+                None
+            }
+        };
+        let response = self.new_game(game_ix);
+        if let Some(setup) = setup {
+            for entry in setup.describe() {
+                self.assets.record(entry);
+            }
+        }
+        response
+    }
+
+    /// Creates a new game, identified by index into the game library, using
+    /// the game's current map variant.
     #[must_use = "pass the response to UserInterface::respond"]
     pub fn new_game(&mut self, game_ix: usize) -> UiResponse {
         if self.assets.games.set_active_index(game_ix) {
-            self.assets.map =
-                self.assets.games.active().create_map(&self.assets.hex);
+            let game = self.assets.games.active();
+            let report = game.validate();
+            for problem in &report.problems {
+                warn!("{} ({}): {}", report.game, report.variant, problem);
+            }
+            self.assets.map = game.create_map(&self.assets.hex);
+            self.assets.update_border_labels();
             self.assets
                 .hex
                 .set_orientation(self.assets.map.orientation());
+            self.assets.title = None;
             self.set_default_title();
+            self.assets.journal.clear();
+            self.assets.token_history.clear();
+            self.assets.reset_history.clear();
+            self.assets.last_revenue = None;
+            self.assets.route_cache.clear();
+            self.assets.operating_round = None;
+            self.assets.revenue_history.clear();
+            self.assets.trains = self.assets.games.active().train_supply();
+            self.assets.treasuries = Treasuries::default();
+            self.assets.planning = false;
+            self.assets.notify(MapEvent::MapReset);
+            self.game_path = None;
+            let game = self.assets.games.active().name().to_string();
+            self.controller.file_history_mut().set_active_game(&game);
+            #[cfg(feature = "hot-reload")]
+            self.apply_watched_files();
             let active_hex = self.assets.map.default_hex();
             self.state = State::default_state(active_hex);
             UiResponse::ResetGame
@@ -454,58 +1882,1294 @@ impl UserInterface {
         }
     }
 
+    /// Records that the current game was loaded from, or saved to, `path`,
+    /// and adds this file to the recent files list (see
+    /// [control::FileHistory]).
+    fn set_game_path(&mut self, path: std::path::PathBuf) {
+        let game = self.assets.games.active().name().to_string();
+        let history = self.controller.file_history_mut();
+        history.set_active_game(&game);
+        history.record(&path);
+        self.game_path = Some(path);
+    }
+
+    /// Reloads the most recently loaded or saved game file, discarding any
+    /// unsaved changes to the current game.
+    ///
+    /// If no game file has been loaded or saved in this session, the most
+    /// recent file in the recent files list is loaded instead.
+    /// If there is no such file, an error message is displayed.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn reload_game(&mut self) -> UiResponse {
+        let path = self.game_path.clone().or_else(|| {
+            self.controller
+                .file_history()
+                .recent_files()
+                .first()
+                .cloned()
+        });
+        match path {
+            Some(path) => self.load_game(path),
+            None => {
+                self.controller.show_error(
+                    self.assets.strings.tr("Could not load game"),
+                    self.assets.strings.tr("There is no recent game file"),
+                );
+                UiResponse::None
+            }
+        }
+    }
+
     /// Loads a saved game state from `path`.
+    ///
+    /// If the game state cannot be loaded, an error message is displayed and
+    /// the current game is left unchanged.
     #[must_use = "pass the response to UserInterface::respond"]
     pub fn load_game(&mut self, path: std::path::PathBuf) -> UiResponse {
-        let game_state = n18io::read_game_state(&path).unwrap_or_else(|_| {
-            panic!("Could not read '{}'", path.display())
-        });
-        if self.assets.games.set_active_name(&game_state.game) {
-            if let Some(new_map) = self
+        let title =
+            &self.assets.strings.tr("Could not load game").to_string();
+        match n18io::read_game_state(&path) {
+            Ok(game_state) => {
+                let response = self.restore_game(title, game_state);
+                if let UiResponse::ResetGame = response {
+                    self.set_game_path(path);
+                }
+                response
+            }
+            Err(e) => {
+                error!("{}", e);
+                self.controller.show_error(title, &e.to_string());
+                UiResponse::None
+            }
+        }
+    }
+
+    /// Loads a background image, which is drawn beneath the map so that
+    /// map authors can trace a real board, and replaces the current
+    /// background image (if any).
+    ///
+    /// See the [background] module for details.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn set_background(
+        &mut self,
+        settings: background::BackgroundSettings,
+    ) -> UiResponse {
+        match background::Background::load(settings) {
+            Ok(background) => {
+                info!(
+                    "Loaded background image {}",
+                    background.settings().path.display()
+                );
+                self.assets.background = Some(background);
+                UiResponse::Redraw
+            }
+            Err(e) => {
+                error!("{}", e);
+                let title = self.assets.strings.tr("Could not load image");
+                self.controller.show_error(title, &e.to_string());
+                UiResponse::None
+            }
+        }
+    }
+
+    /// Removes the background image, if any.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn clear_background(&mut self) -> UiResponse {
+        if self.assets.background.take().is_some() {
+            UiResponse::Redraw
+        } else {
+            UiResponse::None
+        }
+    }
+
+    /// Merges the tile catalogue at `path` into the current map's tile
+    /// catalogue, replacing any tiles with the same name.
+    ///
+    /// If the catalogue cannot be loaded, or no game has been started, an
+    /// error message is displayed and the current map is left unchanged.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn merge_tiles(&mut self, path: std::path::PathBuf) -> UiResponse {
+        let title =
+            &self.assets.strings.tr("Could not add tiles").to_string();
+        // NOTE: there is no game map until the user starts a game.
+        if self.state.as_start().is_some() {
+            let msg = self
                 .assets
-                .games
-                .active_mut()
-                .load(&self.assets.hex, game_state)
-            {
-                self.assets.hex.set_orientation(new_map.orientation());
-                self.set_default_title();
-                self.assets.map = new_map;
-                let active_hex = self.assets.map.default_hex();
-                self.state = State::default_state(active_hex);
-                UiResponse::ResetGame
-            } else {
-                error!("game.load() returned None");
+                .strings
+                .tr("Start or load a game before adding tiles");
+            self.controller.show_error(title, msg);
+            return UiResponse::None;
+        }
+        match n18io::read_tiles(&path) {
+            Ok(tiles) => {
+                info!(
+                    "Added {} tile(s) from {}",
+                    tiles.len(),
+                    path.display()
+                );
+                self.assets.map.update_tiles(tiles);
+                self.assets.notify(MapEvent::MapReset);
+                UiResponse::Redraw
+            }
+            Err(e) => {
+                error!("{}", e);
+                self.controller.show_error(title, &e.to_string());
                 UiResponse::None
             }
+        }
+    }
+
+    /// Replaces the current game with a saved game state.
+    ///
+    /// If the game state is not valid, an error message is displayed with
+    /// the provided title and the current game is left unchanged.
+    fn restore_game(
+        &mut self,
+        title: &str,
+        mut game_state: n18game::GameState,
+    ) -> UiResponse {
+        let prev_ix = self.assets.games.active_index();
+        if !self.assets.games.set_active_name(&game_state.game) {
+            let msg = self.assets.strings.format(
+                "There is no game called '{game}'",
+                &[("game", &game_state.game)],
+            );
+            error!("{}", msg);
+            self.controller.show_error(title, &msg);
+            return UiResponse::None;
+        }
+
+        // Select the saved map variant, so that the saved map is checked
+        // against the correct map.
+        let game = self.assets.games.active_mut();
+        let prev_variant = game.variant().to_string();
+        if !game.set_variant(&game_state.variant) {
+            let msg = self.assets.strings.format(
+                "There is no map variant '{variant}'",
+                &[("variant", &game_state.variant)],
+            );
+            error!("{}", msg);
+            self.assets.games.set_active_index(prev_ix);
+            self.controller.show_error(title, &msg);
+            return UiResponse::None;
+        }
+
+        // Check that the saved map is consistent with the game, because
+        // game.load() will panic if it refers to unknown tiles or tokens.
+        let check = n18io::check_map_descr(
+            &game_state.map,
+            &game.create_map(&self.assets.hex),
+        );
+        let journal = std::mem::take(&mut game_state.journal);
+        let saved_trains = std::mem::take(&mut game_state.trains);
+        let saved_title = game_state.title.take();
+        let saved_treasuries = std::mem::take(&mut game_state.treasuries);
+        let map_opt = match check {
+            Ok(()) => game.load(&self.assets.hex, game_state),
+            Err(e) => {
+                error!("{}", e);
+                game.set_variant(&prev_variant);
+                self.assets.games.set_active_index(prev_ix);
+                self.controller.show_error(title, &e.to_string());
+                return UiResponse::None;
+            }
+        };
+
+        if let Some(new_map) = map_opt {
+            self.assets.hex.set_orientation(new_map.orientation());
+            self.assets.title = saved_title;
+            self.set_default_title();
+            self.assets.map = new_map;
+            self.assets.update_border_labels();
+            self.assets.journal = journal;
+            self.assets.token_history.clear();
+            self.assets.reset_history.clear();
+            self.assets.last_revenue = None;
+            self.assets.route_cache.clear();
+            self.assets.operating_round = None;
+            self.assets.revenue_history.clear();
+            let mut trains = self.assets.games.active().train_supply();
+            trains.restore_counts(&saved_trains);
+            self.assets.trains = trains;
+            self.assets.treasuries = saved_treasuries;
+            self.assets.planning = false;
+            self.assets.notify(MapEvent::MapReset);
+            let active_hex = self.assets.map.default_hex();
+            self.state = State::default_state(active_hex);
+            UiResponse::ResetGame
         } else {
-            error!("no game called '{}'", game_state.game);
+            error!("game.load() returned None");
+            self.assets.games.active_mut().set_variant(&prev_variant);
+            self.assets.games.set_active_index(prev_ix);
+            self.controller.show_error(
+                title,
+                self.assets
+                    .strings
+                    .tr("The saved game phase or map variant is not valid"),
+            );
             UiResponse::None
         }
     }
 
+    /// Loads tile catalogues and a map configuration from disk, and reloads
+    /// them whenever they are modified.
+    ///
+    /// The files are applied to the current game (if any), and to each new
+    /// game that is started.
+    #[cfg(feature = "hot-reload")]
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn watch_files(&mut self, files: watch::WatchedFiles) -> UiResponse {
+        if files.is_empty() {
+            return UiResponse::None;
+        }
+        info!("Watching {} file(s) for changes", files.paths().len());
+        watch::spawn_watcher(
+            &files,
+            self.controller.ping_tx(),
+            watch::POLL_INTERVAL,
+        );
+        self.watched = files;
+        self.reload_files()
+    }
+
+    /// Reloads the watched tile catalogues and map configuration, and
+    /// applies them to the current game.
+    #[cfg(feature = "hot-reload")]
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn reload_files(&mut self) -> UiResponse {
+        // NOTE: there is no game map until the user starts a game.
+        if self.state.as_start().is_some() {
+            return UiResponse::None;
+        }
+        if self.apply_watched_files() {
+            UiResponse::Redraw
+        } else {
+            UiResponse::None
+        }
+    }
+
+    /// Applies the watched tile catalogues and map configuration to the
+    /// current map, and returns `true` if they were successfully applied.
+    ///
+    /// If any file cannot be loaded, an error message is displayed and no
+    /// further files are applied.
+    #[cfg(feature = "hot-reload")]
+    fn apply_watched_files(&mut self) -> bool {
+        let title =
+            &self.assets.strings.tr("Could not reload files").to_string();
+        for path in &self.watched.tiles {
+            match n18io::read_tiles(path) {
+                Ok(tiles) => {
+                    info!("Reloaded tiles from {}", path.display());
+                    self.assets.map.update_tiles(tiles);
+                    self.assets.notify(MapEvent::MapReset);
+                }
+                Err(e) => {
+                    error!("{}", e);
+                    self.controller.show_error(title, &e.to_string());
+                    return false;
+                }
+            }
+        }
+        if let Some(path) = &self.watched.map {
+            // Check that the configuration is consistent with the map,
+            // because descr.update_map() will panic if it refers to unknown
+            // tiles or tokens.
+            let result = n18io::read_map_descr(path).and_then(|descr| {
+                n18io::check_map_descr(&descr, &self.assets.map)
+                    .map(|_| descr)
+            });
+            match result {
+                Ok(descr) => {
+                    let (_orientation, tiles) = (&descr).into();
+                    let map = &self.assets.map;
+                    if let Some(addr) =
+                        tiles.keys().find(|addr| !map.contains_hex(**addr))
+                    {
+                        let msg = format!(
+                            "{} refers to hex {}, which is not on the map",
+                            path.display(),
+                            addr
+                        );
+                        error!("{}", msg);
+                        self.controller.show_error(title, &msg);
+                        return false;
+                    }
+                    info!("Reloaded map from {}", path.display());
+                    descr.update_map(&mut self.assets.map);
+                    self.assets.notify(MapEvent::MapReset);
+                }
+                Err(e) => {
+                    error!("{}", e);
+                    self.controller.show_error(title, &e.to_string());
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Serves images of the current map over HTTP, so that remote players
+    /// can follow the game in a web browser, and returns the address on
+    /// which the server is listening.
+    ///
+    /// The images are updated after each move (i.e., each new journal
+    /// entry), when a game is started or loaded, and when the game is saved.
+    #[cfg(feature = "web")]
+    pub fn serve_spectators<A: std::net::ToSocketAddrs>(
+        &mut self,
+        addr: A,
+    ) -> std::io::Result<std::net::SocketAddr> {
+        let spectator = n18web::Spectator::bind(addr)?;
+        let local_addr = spectator.local_addr();
+        self.spectator = Some((spectator, 0));
+        self.publish_map();
+        Ok(local_addr)
+    }
+
+    /// Publishes the current map to spectators, if the map has changed.
+    #[cfg(feature = "web")]
+    fn update_spectators(&mut self, response: UiResponse) {
+        let published = match &self.spectator {
+            Some((_spectator, published)) => *published,
+            None => return,
+        };
+        let changed = self.assets.journal.len() != published;
+        if changed || response == UiResponse::ResetGame {
+            self.publish_map()
+        }
+    }
+
+    /// Publishes the current map to spectators, if any.
+    #[cfg(feature = "web")]
+    fn publish_map(&mut self) {
+        // NOTE: there is no game map until the user starts a game.
+        if self.state.as_start().is_some() {
+            return;
+        }
+        if let Some((spectator, published)) = &mut self.spectator {
+            // Draw the map at its default size, regardless of the current
+            // zoom level.
+            let mut hex = Hex::default();
+            hex.set_orientation(self.assets.map.orientation());
+            match spectator.publish(&hex, &self.assets.map) {
+                Ok(()) => *published = self.assets.journal.len(),
+                Err(e) => error!("Could not publish map: {}", e),
+            }
+        }
+    }
+
+    /// Shares the current game with other players, who can join the game
+    /// by connecting to the returned address (see
+    /// [UserInterface::join_session]).
+    ///
+    /// Each change to the map is sent to the other players, and the changes
+    /// made by the other players are applied to the map when they arrive.
+    #[cfg(feature = "net")]
+    pub fn host_session<A: std::net::ToSocketAddrs>(
+        &mut self,
+        addr: A,
+    ) -> Result<std::net::SocketAddr, Box<dyn std::error::Error>> {
+        // NOTE: there is no game map until the user starts a game.
+        if self.state.as_start().is_some() {
+            return Err("There is no game to share".into());
+        }
+        self.leave_session();
+        let game_state = self.assets.game_state();
+        let ping_tx = self.controller.ping_tx();
+        let session = n18net::Session::host(addr, game_state, move || {
+            let _ = ping_tx.send_ping(PingDest::Session);
+        })?;
+        let local_addr = session.local_addr();
+        self.shared = Some(net::Shared::new(&mut self.assets, session));
+        Ok(local_addr)
+    }
+
+    /// Joins a game that another player is sharing (see
+    /// [UserInterface::host_session]), and replaces the current game.
+    #[cfg(feature = "net")]
+    pub fn join_session<A: std::net::ToSocketAddrs>(
+        &mut self,
+        addr: A,
+    ) -> Result<UiResponse, Box<dyn std::error::Error>> {
+        self.leave_session();
+        let ping_tx = self.controller.ping_tx();
+        let session = n18net::Session::join(addr, move || {
+            let _ = ping_tx.send_ping(PingDest::Session);
+        })?;
+        self.shared = Some(net::Shared::new(&mut self.assets, session));
+        Ok(self.replay_session())
+    }
+
+    /// Stops sharing the current game with other players.
+    #[cfg(feature = "net")]
+    pub fn leave_session(&mut self) {
+        if let Some(shared) = self.shared.take() {
+            info!("Left the shared game");
+            shared.leave(&mut self.assets)
+        }
+    }
+
+    /// Replaces the current game with the initial state of the shared game,
+    /// and applies each move that has been accepted by the host.
+    ///
+    /// This discards any changes that conflict with other players' moves.
+    #[cfg(feature = "net")]
+    fn replay_session(&mut self) -> UiResponse {
+        let Some(shared) = &self.shared else {
+            return UiResponse::None;
+        };
+        let game_state = shared.session().initial_state().clone();
+        let moves = shared.session().moves().to_vec();
+        let title = self.assets.strings.tr("Could not join game").to_string();
+        let response = self.restore_game(&title, game_state);
+        if response != UiResponse::ResetGame {
+            self.leave_session();
+            return response;
+        }
+        for mv in &moves {
+            if !net::apply_move(&mut self.assets, mv) {
+                warn!("Could not apply {:?}", mv)
+            }
+        }
+        // NOTE: these changes were made by the host and other players, and
+        // should not be sent to them.
+        if let Some(shared) = &mut self.shared {
+            shared.discard_changes(&self.assets);
+        }
+        response
+    }
+
+    /// Sends the changes that have been made to the map to the other
+    /// players, if the current game is shared.
+    ///
+    /// If the map has been replaced (e.g., by loading a saved game) the game
+    /// is no longer shared.
+    #[cfg(feature = "net")]
+    fn share_changes(&mut self) {
+        let Some(shared) = &mut self.shared else {
+            return;
+        };
+        let Some(moves) = shared.take_moves(&self.assets) else {
+            self.leave_session();
+            return;
+        };
+        for mv in moves {
+            if let Err(e) = shared.session_mut().propose(mv) {
+                error!("Could not share move: {}", e);
+                self.leave_session();
+                return;
+            }
+        }
+    }
+
+    /// Applies the moves that other players have made, and restores the
+    /// shared game if any of this player's moves were rejected.
+    #[cfg(feature = "net")]
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn sync_session(&mut self) -> UiResponse {
+        // NOTE: share any local changes before receiving other moves, so
+        // that they are not mistaken for changes made by other players.
+        self.share_changes();
+        let mut response = UiResponse::None;
+        let mut rejected = false;
+        let mut diverged = false;
+        let mut disconnected = false;
+        while let Some(event) = self
+            .shared
+            .as_mut()
+            .and_then(|shared| shared.session_mut().try_recv())
+        {
+            match event {
+                n18net::Event::Applied { own: true, .. } => {}
+                n18net::Event::Applied { seq, mv, .. } => {
+                    if !net::apply_move(&mut self.assets, &mv) {
+                        error!("Could not apply move #{}: {:?}", seq, mv);
+                        diverged = true;
+                    }
+                    response = UiResponse::Redraw;
+                }
+                n18net::Event::Rejected(mv) => {
+                    warn!("Move was rejected: {:?}", mv);
+                    rejected = true;
+                }
+                n18net::Event::PlayerJoined(player) => {
+                    info!("Player {} joined the shared game", player)
+                }
+                n18net::Event::PlayerLeft(player) => {
+                    info!("Player {} left the shared game", player)
+                }
+                n18net::Event::Disconnected => disconnected = true,
+            }
+        }
+        if let Some(shared) = &mut self.shared {
+            shared.discard_changes(&self.assets);
+        }
+        if disconnected {
+            self.leave_session();
+            self.controller.show_error(
+                self.assets.strings.tr("Shared game"),
+                self.assets.strings.tr("The host closed the connection"),
+            );
+        } else if rejected {
+            response = self.replay_session();
+        } else if diverged {
+            self.controller.show_error(
+                self.assets.strings.tr("Shared game"),
+                self.assets
+                    .strings
+                    .tr("Could not apply a move made by another player"),
+            );
+        }
+        response
+    }
+
     /// Saves the current game state to `path`.
     #[must_use = "pass the response to UserInterface::respond"]
     pub fn save_game(&mut self, path: std::path::PathBuf) -> UiResponse {
-        let game_state = self.assets.games.active().save(&self.assets.map);
-        n18io::write_game_state(&path, game_state, true).unwrap_or_else(
-            |_| panic!("Could not write '{}'", path.display()),
+        let game_state = self.assets.game_state();
+        if let Err(e) = n18io::write_game_state(&path, game_state, true) {
+            error!("{}", e);
+            self.controller.show_error(
+                self.assets.strings.tr("Could not save game"),
+                &e.to_string(),
+            );
+        } else {
+            self.set_game_path(path);
+        }
+        #[cfg(feature = "web")]
+        self.publish_map();
+        UiResponse::None
+    }
+
+    /// Defines the languages, other than English, that the user can choose
+    /// (see [lang::find_languages]).
+    ///
+    /// This should be called before [UserInterface::restore_session], so
+    /// that the language used in the previous session can be restored.
+    pub fn set_languages(&mut self, languages: Vec<lang::Strings>) {
+        self.languages = languages
+    }
+
+    /// Returns the name of each language that the user can choose, starting
+    /// with English.
+    pub fn language_names(&self) -> Vec<&str> {
+        std::iter::once(lang::ENGLISH)
+            .chain(self.languages.iter().map(|s| s.language.as_str()))
+            .collect()
+    }
+
+    /// Prompts the user to select the user interface language.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn select_language(&mut self) -> UiResponse {
+        let names: Vec<String> = self
+            .language_names()
+            .into_iter()
+            .map(|name| name.to_string())
+            .collect();
+        let names: Vec<&str> = names.iter().map(|s| s.as_str()).collect();
+        let title = self.assets.strings.tr("Select a language");
+        let responder =
+            Responder::new(self.sender.clone(), self.controller.ping_tx());
+        self.controller.select_index(title, &names, move |ix_opt| {
+            if let Some(ix) = ix_opt {
+                responder.send(Action::SetLanguage(ix));
+            }
+        });
+        UiResponse::None
+    }
+
+    /// Translates user interface messages into the `nth` available
+    /// language, where `0` is English.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn set_language(&mut self, ix: usize) -> UiResponse {
+        let strings = if ix == 0 {
+            lang::Strings::english()
+        } else if let Some(strings) = self.languages.get(ix - 1) {
+            strings.clone()
+        } else {
+            return UiResponse::None;
+        };
+        info!("Using language {}", strings.language);
+        self.assets.strings = strings;
+        if self.state.is_default_state() {
+            self.set_default_title();
+        }
+        self.refresh_panels();
+        UiResponse::Redraw
+    }
+
+    /// Returns the name of the map theme (see
+    /// [n18hex::theme::THEME_NAMES]).
+    pub fn theme_name(&self) -> &str {
+        self.theme_name
+    }
+
+    /// Prompts the user to select the map theme.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn select_theme(&mut self) -> UiResponse {
+        let title = self.assets.strings.tr("Select the map theme");
+        let responder =
+            Responder::new(self.sender.clone(), self.controller.ping_tx());
+        let names = n18hex::theme::THEME_NAMES;
+        self.controller.select_index(title, &names, move |ix_opt| {
+            if let Some(ix) = ix_opt {
+                responder.send(Action::SetTheme(ix));
+            }
+        });
+        UiResponse::None
+    }
+
+    /// Draws the map with the `nth` predefined theme (see
+    /// [n18hex::theme::THEME_NAMES]).
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn set_theme(&mut self, ix: usize) -> UiResponse {
+        let Some(name) = n18hex::theme::THEME_NAMES.get(ix) else {
+            return UiResponse::None;
+        };
+        let Some(theme) = n18hex::Theme::named(name) else {
+            return UiResponse::None;
+        };
+        info!("Using theme {}", name);
+        self.theme_name = name;
+        self.assets.hex.theme = theme;
+        UiResponse::Redraw
+    }
+
+    /// Prompts the user to enter a new name for the most recently loaded or
+    /// saved game file.
+    ///
+    /// If the current game has not been loaded or saved, an error message
+    /// is displayed.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn rename_game(&mut self) -> UiResponse {
+        let Some(path) = &self.game_path else {
+            self.controller.show_error(
+                self.assets.strings.tr("Could not rename game"),
+                self.assets
+                    .strings
+                    .tr("The current game has not been saved"),
+            );
+            return UiResponse::None;
+        };
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned());
+        let responder =
+            Responder::new(self.sender.clone(), self.controller.ping_tx());
+        self.controller.enter_text(
+            self.assets.strings.tr("Rename game"),
+            self.assets.strings.tr("New file name:"),
+            name.as_deref(),
+            move |name_opt| {
+                if let Some(name) = name_opt {
+                    responder.send(Action::RenameGameTo(name));
+                }
+            },
         );
         UiResponse::None
     }
 
+    /// Renames the most recently loaded or saved game file, keeping it in
+    /// the same directory.
+    ///
+    /// If `name` has no extension, the file's current extension is kept.
+    /// If the file cannot be renamed (e.g., a file called `name` already
+    /// exists), an error message is displayed.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn rename_game_to(&mut self, name: &str) -> UiResponse {
+        let title =
+            &self.assets.strings.tr("Could not rename game").to_string();
+        let Some(path) = &self.game_path else {
+            self.controller.show_error(
+                title,
+                self.assets
+                    .strings
+                    .tr("The current game has not been saved"),
+            );
+            return UiResponse::None;
+        };
+        let name = name.trim();
+        let new_name = std::path::Path::new(name);
+        if name.is_empty() || new_name.file_name() != Some(name.as_ref()) {
+            let msg = self.assets.strings.format(
+                "'{name}' is not a valid file name",
+                &[("name", &name)],
+            );
+            self.controller.show_error(title, &msg);
+            return UiResponse::None;
+        }
+        let mut new_path = path.with_file_name(new_name);
+        if new_path.extension().is_none() {
+            if let Some(ext) = path.extension() {
+                new_path.set_extension(ext);
+            }
+        }
+        if new_path == *path {
+            return UiResponse::None;
+        }
+        if new_path.exists() {
+            let msg = self.assets.strings.format(
+                "{path} already exists",
+                &[("path", &new_path.display())],
+            );
+            self.controller.show_error(title, &msg);
+            return UiResponse::None;
+        }
+        match std::fs::rename(path, &new_path) {
+            Ok(()) => {
+                info!("Renamed {} to {}", path.display(), new_path.display());
+                self.controller.file_history_mut().rename(path, &new_path);
+                self.game_path = Some(new_path);
+            }
+            Err(e) => {
+                error!("{}", e);
+                self.controller.show_error(title, &e.to_string());
+            }
+        }
+        UiResponse::None
+    }
+
+    /// Adds a note to the journal for the current game.
+    ///
+    /// Notes that only contain whitespace are ignored.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn add_note(&mut self, note: &str) -> UiResponse {
+        let note = note.trim();
+        if note.is_empty() {
+            return UiResponse::None;
+        }
+        self.assets.record(format!("Note: {}", note));
+        self.refresh_panels();
+        UiResponse::None
+    }
+
+    /// Sets the custom title of the current game, which is shown in the
+    /// window title alongside the game name, and is saved with the game.
+    ///
+    /// The custom title is removed if `title` only contains whitespace.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn set_title(&mut self, title: &str) -> UiResponse {
+        let title = title.trim();
+        self.assets.title = (!title.is_empty()).then(|| title.to_string());
+        if self.state.is_default_state() {
+            self.set_default_title();
+        }
+        UiResponse::None
+    }
+
+    /// Saves the current game state and a PNG image of the entire map to a
+    /// single bundle file at `path`.
+    ///
+    /// The map is drawn at its default size, regardless of the current zoom
+    /// level.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn export_bundle(&mut self, path: std::path::PathBuf) -> UiResponse {
+        let title = &self
+            .assets
+            .strings
+            .tr("Could not export game bundle")
+            .to_string();
+        // Undo the effect of zooming in or out.
+        let scale = Hex::default().max_d / self.assets.hex.max_d;
+        let image = n18brush::image_size(|ctx| self.draw_on(ctx))
+            .ok_or_else(|| "Could not determine the image size".into())
+            .and_then(|(width, height)| {
+                ImageFormat::Png.image_bytes(width, height, scale, |ctx| {
+                    self.draw_on(ctx)
+                })
+            });
+        let image = match image {
+            Ok(image) => image,
+            Err(e) => {
+                error!("{}", e);
+                self.controller.show_error(title, &e.to_string());
+                return UiResponse::None;
+            }
+        };
+        let game_state = self.assets.game_state();
+        match n18io::write_bundle(&path, game_state, &image, false) {
+            Ok(()) => info!("Exported game bundle to {}", path.display()),
+            Err(e) => {
+                error!("{}", e);
+                self.controller.show_error(title, &e.to_string());
+            }
+        }
+        UiResponse::None
+    }
+
+    /// Loads the game state from a bundle at `path`.
+    ///
+    /// If a game is in progress, the bundle must contain a game state for
+    /// the same game.
+    /// If the bundle cannot be loaded, an error message is displayed and the
+    /// current game is left unchanged.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn import_bundle(&mut self, path: std::path::PathBuf) -> UiResponse {
+        let title = &self
+            .assets
+            .strings
+            .tr("Could not import game bundle")
+            .to_string();
+        let bundle = match n18io::read_bundle(&path) {
+            Ok(bundle) => bundle,
+            Err(e) => {
+                error!("{}", e);
+                self.controller.show_error(title, &e.to_string());
+                return UiResponse::None;
+            }
+        };
+        // NOTE: there is no current game until the user starts a game.
+        let active_name = self.assets.games.active().name();
+        if self.state.as_start().is_none() && bundle.state.game != active_name
+        {
+            let msg = self.assets.strings.format(
+                "This bundle is for {bundle}, but the current game is {game}",
+                &[("bundle", &bundle.state.game), ("game", &active_name)],
+            );
+            error!("{}", msg);
+            self.controller.show_error(title, &msg);
+            return UiResponse::None;
+        }
+        self.restore_game(title, bundle.state)
+    }
+
     /// Saves an image to `path`.
+    ///
+    /// If the image cannot be saved, an error message is displayed.
     #[must_use = "pass the response to UserInterface::respond"]
     pub fn save_image(
-        &self,
+        &mut self,
         path: std::path::PathBuf,
         image: cairo::ImageSurface,
     ) -> UiResponse {
-        let mut file = std::fs::File::create(&path).unwrap_or_else(|_| {
-            panic!("Couldn't create '{}'", path.display())
-        });
-        image.write_to_png(&mut file).unwrap_or_else(|_| {
-            panic!("Couldn't write '{}'", path.display())
+        let result = std::fs::File::create(&path)
+            .map_err(|e| {
+                format!("Couldn't create '{}': {}", path.display(), e)
+            })
+            .and_then(|mut file| {
+                image.write_to_png(&mut file).map_err(|e| {
+                    format!("Couldn't write '{}': {}", path.display(), e)
+                })
+            });
+        if let Err(e) = result {
+            error!("{}", e);
+            self.controller.show_error(
+                self.assets.strings.tr("Could not save image"),
+                &e,
+            );
+        } else {
+            info!("Saved image to {}", path.display());
+        }
+        UiResponse::None
+    }
+
+    /// Asks the user whether to add a title block and legend to an image of
+    /// the map, drawn at the chosen resolution (in dots per inch), and then
+    /// prompts the user to select where to save this image.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn export_map(&mut self, dpi: f64) -> UiResponse {
+        let responder =
+            Responder::new(self.sender.clone(), self.controller.ping_tx());
+        self.controller.confirm(
+            self.assets.strings.tr("Export map"),
+            self.assets
+                .strings
+                .tr("Add a title block and legend to the exported map?"),
+            move |legend| {
+                responder.send(Action::ExportMapTo(dpi, legend));
+            },
+        );
+        UiResponse::None
+    }
+
+    /// Prompts the user to select where to save an image of the map, drawn
+    /// at the chosen resolution (in dots per inch), with or without a title
+    /// block and legend (see [UserInterface::export_decorations]).
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn export_map_to(&mut self, dpi: f64, legend: bool) -> UiResponse {
+        let responder =
+            Responder::new(self.sender.clone(), self.controller.ping_tx());
+        // Suggest a filename that contains the current date and time.
+        let now = chrono::Local::now();
+        let default_dest = now.format("map-%Y-%m-%d-%H%M%S.png").to_string();
+        self.controller.select_screenshot_save(
+            self.assets.strings.tr("Export map"),
+            Some(&default_dest),
+            move |path_opt| {
+                if let Some(path) = path_opt {
+                    responder.send(Action::SaveMap(path, dpi, legend));
+                }
+            },
+        );
+        UiResponse::None
+    }
+
+    /// Draws the entire map and saves it to `path`, independent of the
+    /// current zoom level.
+    ///
+    /// The image format is chosen by the extension of `path` (PDF, PNG, or
+    /// SVG), and defaults to PNG.
+    /// PNG images are drawn at the chosen resolution (in dots per inch),
+    /// with the map drawn at its default size in points (1/72 of an inch).
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn save_map(
+        &mut self,
+        path: std::path::PathBuf,
+        dpi: f64,
+    ) -> UiResponse {
+        self.save_map_with(path, dpi, &Decorations::default())
+    }
+
+    /// Returns the title block and legend for exported maps, which contain
+    /// the game name, the current game phase, today's date, and keys for the
+    /// company tokens and terrain.
+    pub fn export_decorations(&self) -> Decorations {
+        let game = self.assets.games.active();
+        let phase = game.phase_names().get(game.phase_ix());
+        Decorations {
+            title: Some(game.name().to_string()),
+            phase: phase.map(|name| format!("Phase: {}", name)),
+            date: Some(chrono::Local::now().format("%Y-%m-%d").to_string()),
+            token_key: true,
+            terrain_key: true,
+        }
+    }
+
+    /// Draws the entire map and any `decorations` (which are drawn to the
+    /// right of the map), and saves it to `path`, independent of the current
+    /// zoom level.
+    ///
+    /// See [UserInterface::save_map] for details.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn save_map_with(
+        &mut self,
+        path: std::path::PathBuf,
+        dpi: f64,
+        decorations: &Decorations,
+    ) -> UiResponse {
+        let format = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(ImageFormat::from_extension)
+            .unwrap_or(ImageFormat::Png);
+        // Undo the effect of zooming in or out.
+        let zoom_scale = Hex::default().max_d / self.assets.hex.max_d;
+        let scale = match format {
+            ImageFormat::Png => zoom_scale * n18brush::dpi_scale(dpi),
+            _ => zoom_scale,
+        };
+        // Draw any decorations to the right of the map.
+        let (width, _) = self.assets.map.drawing_size(&self.assets.hex);
+        let draw = |ctx: &cairo::Context| {
+            self.draw_on(ctx);
+            if !decorations.is_empty() {
+                let hex = &self.assets.hex;
+                let margin = hex.theme.map_margin.absolute(hex);
+                n18brush::legend::draw_decorations(
+                    hex,
+                    ctx,
+                    &self.assets.map,
+                    decorations,
+                    Coord::from((width, margin)),
+                );
+            }
+        };
+        let result = n18brush::image_size(draw)
+            .ok_or_else(|| "Could not determine the image size".into())
+            .and_then(|(width, height)| {
+                format.save_image(width, height, scale, draw, &path)
+            });
+        if let Err(e) = result {
+            error!("{}", e);
+            self.controller.show_error(
+                self.assets.strings.tr("Could not export map"),
+                &e.to_string(),
+            );
+        } else {
+            info!("Exported map to {}", path.display());
+        }
+        UiResponse::None
+    }
+
+    /// Draws a region of the map at the chosen scale, and saves it to
+    /// `path`.
+    ///
+    /// The image is saved in SVG format if `path` has the extension `.svg`,
+    /// and in PNG format otherwise.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn save_region(
+        &mut self,
+        path: std::path::PathBuf,
+        region: Region,
+    ) -> UiResponse {
+        let is_svg = path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("svg"))
+            .unwrap_or(false);
+        let result = if is_svg {
+            canvas::region_to_svg(&region, &path, |ctx| self.draw_on(ctx))
+                .map_err(|e| e.to_string())
+        } else {
+            canvas::region_to_image(&region, |ctx| self.draw_on(ctx))
+                .map_err(|e| e.to_string())
+                .and_then(|image| {
+                    let mut file = std::fs::File::create(&path)
+                        .map_err(|e| e.to_string())?;
+                    image.write_to_png(&mut file).map_err(|e| e.to_string())
+                })
+        };
+        if let Err(e) = result {
+            error!("{}", e);
+            self.controller.show_error(
+                self.assets.strings.tr("Could not save image"),
+                &e,
+            );
+        }
+        UiResponse::None
+    }
+
+    /// Returns the region of the map that is visible in the window.
+    ///
+    /// If the window size is not known, this returns the entire map.
+    pub fn visible_region(&self) -> Region {
+        let (map_width, map_height) = self.assets.view_size();
+        let (x, y) = self.controller.viewport().unwrap_or((0.0, 0.0));
+        let (width, height) = self
+            .controller
+            .window_size()
+            .map(|(w, h)| (w as f64, h as f64))
+            .unwrap_or((map_width, map_height));
+        Region {
+            x,
+            y,
+            width: width.min(map_width - x),
+            height: height.min(map_height - y),
+            scale: 1.0,
+        }
+    }
+
+    /// Copies the visible part of the map to the clipboard, as it is shown
+    /// in the window (see [Assets::view]).
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn copy_view(&mut self) -> UiResponse {
+        let view_matrix = self.view_matrix();
+        let image = canvas::region_to_image(&self.visible_region(), |ctx| {
+            ctx.transform(view_matrix);
+            self.draw_on(ctx)
         });
+        self.copy_image(image)
+    }
+
+    /// Draws a region of the map at the chosen scale, and copies it to the
+    /// clipboard.
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn copy_region(&mut self, region: Region) -> UiResponse {
+        let image = canvas::region_to_image(&region, |ctx| self.draw_on(ctx));
+        self.copy_image(image)
+    }
+
+    /// Copies an image of the map to the clipboard, or reports the error
+    /// that occurred when drawing the image.
+    fn copy_image(
+        &mut self,
+        image: Result<cairo::ImageSurface, cairo::Error>,
+    ) -> UiResponse {
+        let result = image
+            .map_err(|e| e.to_string())
+            .and_then(|image| self.controller.copy_image(&image));
+        if let Err(e) = result {
+            error!("{}", e);
+            self.controller.show_error(
+                self.assets.strings.tr("Could not copy image"),
+                &e,
+            );
+        }
+        UiResponse::None
+    }
+
+    /// Prompts the user to select the company whose tokens will replace the
+    /// tokens of the `nth` company (e.g., when one company merges into
+    /// another).
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn select_replacement_tokens(
+        &mut self,
+        from_ix: usize,
+    ) -> UiResponse {
+        let names = self.assets.map.token_names();
+        let from_name = match names.get(from_ix) {
+            Some(name) => name,
+            None => return UiResponse::None,
+        };
+        let to_ixs: Vec<usize> =
+            (0..names.len()).filter(|ix| *ix != from_ix).collect();
+        let to_names: Vec<&str> =
+            to_ixs.iter().map(|ix| names[*ix].as_str()).collect();
+        let title = self.assets.strings.format(
+            "Replace {company} tokens with",
+            &[("company", &from_name)],
+        );
+        let responder =
+            Responder::new(self.sender.clone(), self.controller.ping_tx());
+        self.controller
+            .select_index(&title, &to_names, move |ix_opt| {
+                if let Some(to_ix) = ix_opt.map(|ix| to_ixs[ix]) {
+                    responder
+                        .send(Action::ReplaceCompanyTokens(from_ix, to_ix));
+                }
+            });
+        UiResponse::None
+    }
+
+    /// Replaces each placed token of the `nth` company with a token of
+    /// another company, and records this in the journal.
+    ///
+    /// This change can be undone with [Assets::undo_token_changes].
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn replace_company_tokens(
+        &mut self,
+        from_ix: usize,
+        to_ix: usize,
+    ) -> UiResponse {
+        let map = &self.assets.map;
+        let (from_name, to_name) = match (
+            map.token_names().get(from_ix),
+            map.token_names().get(to_ix),
+        ) {
+            (Some(from), Some(to)) => (from.clone(), to.clone()),
+            _ => return UiResponse::None,
+        };
+        let from = map.token(&from_name);
+        let to = map.token(&to_name);
+        let changes = self.assets.map.replace_company_tokens(&from, &to);
+        if changes.is_empty() {
+            return UiResponse::None;
+        }
+        let entries = changes
+            .iter()
+            .map(|change| {
+                let hex_name = self.assets.hex_name(change.addr);
+                if change.after.is_some() {
+                    format!(
+                        "Replaced {} token at {} with {} token",
+                        from_name, hex_name, to_name
+                    )
+                } else {
+                    n18game::sim::remove_token_entry(&from_name, &hex_name)
+                }
+            })
+            .collect();
+        self.assets.record_token_changes(entries, changes);
+        UiResponse::Redraw
+    }
+
+    /// Places each company token listed in the file at `path` (see
+    /// [n18io::read_token_placements]), and records this in the journal.
+    ///
+    /// If any token cannot be placed, an error message is displayed and no
+    /// tokens are placed.
+    /// This change can be undone with [Assets::undo_token_changes].
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn import_tokens(&mut self, path: std::path::PathBuf) -> UiResponse {
+        let title = &self
+            .assets
+            .strings
+            .tr("Could not import company tokens")
+            .to_string();
+        let coords = self.assets.games.active().coordinate_system();
+        let result =
+            n18io::read_token_placements(&path).and_then(|placements| {
+                n18io::apply_token_placements(
+                    &mut self.assets.map,
+                    &coords,
+                    &placements,
+                )
+            });
+        let changes = match result {
+            Ok(changes) => changes,
+            Err(e) => {
+                error!("{}", e);
+                self.controller.show_error(title, &e.to_string());
+                return UiResponse::None;
+            }
+        };
+        if changes.is_empty() {
+            return UiResponse::None;
+        }
+        info!("Imported {} tokens from {}", changes.len(), path.display());
+        let entries = changes
+            .iter()
+            .map(|change| {
+                let company = change
+                    .after
+                    .and_then(|token| self.assets.map.try_token_name(&token))
+                    .unwrap_or("?");
+                let hex_name = self.assets.hex_name(change.addr);
+                n18game::sim::place_token_entry(company, &hex_name)
+            })
+            .collect();
+        self.assets.record_token_changes(entries, changes);
+        UiResponse::Redraw
+    }
+
+    /// Prompts the user to search for an action that is available in the
+    /// current state, and runs the selected action (if any).
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn show_command_palette(&mut self) -> UiResponse {
+        let commands = self.keymap.commands(&self.state);
+        if commands.is_empty() || self.state.as_start().is_some() {
+            return UiResponse::None;
+        }
+        // NOTE: the palette takes ownership of the current state, and
+        // restores it once the user has selected an action (if any).
+        let previous = std::mem::replace(
+            &mut self.state,
+            state::start::Start::new().into(),
+        );
+        let palette = state::palette::CommandPalette::new(
+            &mut self.controller,
+            &self.sender,
+            previous,
+            commands,
+            &self.assets.strings,
+        );
+        self.state = palette.into();
+        UiResponse::None
+    }
+
+    /// Shows a context menu at the active hex, which lists the actions that
+    /// can be applied to this hex in the current state (see
+    /// [Keymap::hex_commands]), and runs the selected action (if any).
+    #[must_use = "pass the response to UserInterface::respond"]
+    pub fn show_hex_menu(&mut self) -> UiResponse {
+        let Some(addr) = self.state.active_hex() else {
+            return UiResponse::None;
+        };
+        let commands = self.keymap.hex_commands(&self.assets, &self.state);
+        if commands.is_empty() {
+            return UiResponse::None;
+        }
+        let labels: Vec<&str> = commands
+            .iter()
+            .map(|command| self.assets.strings.tr(command.name))
+            .collect();
+        // Show the menu at the centre of the active hex.
+        let hex = &self.assets.hex;
+        let centre = n18brush::badges::hex_centre_coord(
+            hex,
+            hex.context(),
+            &self.assets.map,
+            addr,
+        );
+        let (x, y) = self.view_matrix().transform_point(centre.x, centre.y);
+        let responder =
+            Responder::new(self.sender.clone(), self.controller.ping_tx());
+        self.controller
+            .show_context_menu(x, y, &labels, move |ix_opt| {
+                if let Some(command) = ix_opt.map(|ix| commands[ix]) {
+                    responder.send(Action::RunCommand(command));
+                }
+            });
         UiResponse::None
     }
 }
@@ -0,0 +1,286 @@
+//! Share the current game with other players over a network.
+//!
+//! This module requires the `net` feature.
+//! Each change to the map (see [MapEvent]) is converted into one or more
+//! moves (see [n18game::sim::Move]) and sent to the other players in a
+//! [n18net::Session], and the moves made by the other players are applied
+//! to the map.
+//! Every player applies each accepted move in the same way that they apply
+//! their own changes (see [Assets::apply_move]), so that tiles can be placed
+//! on any map hex, regardless of the tile placement rules.
+//!
+//! Use [UserInterface::host_session](crate::UserInterface::host_session) and
+//! [UserInterface::join_session](crate::UserInterface::join_session) to
+//! share a game.
+use log::warn;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use n18game::sim::Move;
+use n18map::{HexAddress, Map, TileDescr};
+
+use crate::events::{ListenerId, MapEvent};
+use crate::Assets;
+
+/// A game that is shared with other players, and the local changes that
+/// have not yet been sent to them.
+pub struct Shared {
+    session: n18net::Session,
+    changes: Rc<RefCell<Vec<MapEvent>>>,
+    listener: ListenerId,
+    /// The tile and tokens on each map hex, as last shared with (or received
+    /// from) the other players.
+    hexes: BTreeMap<HexAddress, Option<TileDescr>>,
+}
+
+impl Shared {
+    /// Records each subsequent change to the map, so that these changes can
+    /// be sent to the other players.
+    pub fn new(assets: &mut Assets, session: n18net::Session) -> Self {
+        let changes = Rc::new(RefCell::new(vec![]));
+        let recorded = Rc::clone(&changes);
+        let listener = assets.subscribe(move |event| {
+            recorded.borrow_mut().push(event.clone())
+        });
+        Shared {
+            session,
+            changes,
+            listener,
+            hexes: hex_contents(&assets.map),
+        }
+    }
+
+    /// Returns the shared game session.
+    pub fn session(&self) -> &n18net::Session {
+        &self.session
+    }
+
+    /// Returns the shared game session.
+    pub fn session_mut(&mut self) -> &mut n18net::Session {
+        &mut self.session
+    }
+
+    /// Removes and returns the changes that have been made since this
+    /// function was last called.
+    pub fn take_changes(&self) -> Vec<MapEvent> {
+        std::mem::take(&mut self.changes.borrow_mut())
+    }
+
+    /// Returns the moves that describe the changes that have been made since
+    /// the changes were last taken, or `None` if the map was replaced.
+    pub fn take_moves(&mut self, assets: &Assets) -> Option<Vec<Move>> {
+        let changes = self.take_changes();
+        if changes.contains(&MapEvent::MapReset) {
+            return None;
+        }
+        let mut moves = vec![];
+        for event in &changes {
+            match event {
+                // NOTE: these changes are only described by the final
+                // contents of each hex, so only share what has changed.
+                MapEvent::HexesChanged(addrs) => {
+                    for addr in addrs {
+                        let before = self.hexes.get(addr).cloned().flatten();
+                        let after = hex_descr(&assets.map, *addr);
+                        moves.extend(hex_moves(*addr, &before, &after));
+                    }
+                }
+                _ => moves.extend(moves_for_event(assets, event)),
+            }
+            self.update_hexes(assets, event);
+        }
+        Some(moves)
+    }
+
+    /// Discards the changes that have been made since the changes were last
+    /// taken, such as the moves made by other players.
+    pub fn discard_changes(&mut self, assets: &Assets) {
+        for event in self.take_changes() {
+            self.update_hexes(assets, &event)
+        }
+    }
+
+    /// Records the current contents of each map hex affected by a change.
+    fn update_hexes(&mut self, assets: &Assets, event: &MapEvent) {
+        match event.hexes() {
+            Some(addrs) => {
+                for addr in addrs {
+                    let contents = hex_descr(&assets.map, addr);
+                    self.hexes.insert(addr, contents);
+                }
+            }
+            None => self.hexes = hex_contents(&assets.map),
+        }
+    }
+
+    /// Stops recording changes to the map, and closes the session.
+    pub fn leave(self, assets: &mut Assets) {
+        assets.unsubscribe(self.listener);
+    }
+}
+
+/// Returns the moves that describe a change to the map, which has already
+/// been made.
+///
+/// The map can only be replaced by restoring the shared game, and so
+/// [MapEvent::MapReset] is not described by any moves.
+/// Changes to several hexes ([MapEvent::HexesChanged]) are not described by
+/// any moves, because they depend on the previous contents of each hex (see
+/// [Shared::take_moves]).
+pub fn moves_for_event(assets: &Assets, event: &MapEvent) -> Vec<Move> {
+    let map = &assets.map;
+    match event {
+        MapEvent::TilePlaced {
+            addr,
+            tile,
+            rotation,
+            ..
+        } => vec![Move::LayTile {
+            addr: *addr,
+            tile: tile.clone(),
+            rotation: *rotation,
+            company: None,
+        }],
+        MapEvent::TileRotated { addr, rotation } => vec![Move::RotateTile {
+            addr: *addr,
+            rotation: *rotation,
+        }],
+        MapEvent::TileRemoved { addr, .. } => {
+            vec![Move::RemoveTile { addr: *addr }]
+        }
+        MapEvent::TokensChanged(changes) => changes
+            .iter()
+            .filter_map(|change| {
+                let tile = map.tile_at(change.addr)?;
+                let space = tile
+                    .token_spaces()
+                    .iter()
+                    .position(|space| *space == change.space)?;
+                let mv = match change.after {
+                    Some(token) => Move::PlaceToken {
+                        addr: change.addr,
+                        space,
+                        company: map.try_token_name(&token)?.to_string(),
+                    },
+                    None => Move::RemoveToken {
+                        addr: change.addr,
+                        space,
+                    },
+                };
+                Some(mv)
+            })
+            .collect(),
+        MapEvent::PhaseChanged { phase_ix, .. } => {
+            vec![Move::SetPhase(*phase_ix)]
+        }
+        MapEvent::HexesChanged(_) | MapEvent::MapReset => vec![],
+    }
+}
+
+/// Returns the tile and tokens on each map hex.
+fn hex_contents(map: &Map) -> BTreeMap<HexAddress, Option<TileDescr>> {
+    map.hex_address_iter()
+        .map(|addr| (*addr, hex_descr(map, *addr)))
+        .collect()
+}
+
+/// Returns the tile and tokens on a map hex, if it contains a tile.
+fn hex_descr(map: &Map, addr: HexAddress) -> Option<TileDescr> {
+    let hex_state = map.hex_state(addr)?;
+    let mut descr: TileDescr = (map, addr, hex_state).into();
+    descr.tokens.sort();
+    Some(descr)
+}
+
+/// Returns the moves that change the tile and tokens on a map hex from
+/// `before` to `after`.
+///
+/// A different tile is placed on an empty hex, so that the tokens on the
+/// previous tile are not moved to the new tile.
+fn hex_moves(
+    addr: HexAddress,
+    before: &Option<TileDescr>,
+    after: &Option<TileDescr>,
+) -> Vec<Move> {
+    let mut moves = vec![];
+    let Some(after) = after else {
+        if before.is_some() {
+            moves.push(Move::RemoveTile { addr })
+        }
+        return moves;
+    };
+    let tokens_before: &[(usize, String)] = match before {
+        Some(before) if before.tile == after.tile => {
+            if before.rotation != after.rotation {
+                moves.push(Move::RotateTile {
+                    addr,
+                    rotation: after.rotation,
+                })
+            }
+            before.tokens.as_slice()
+        }
+        _ => {
+            if before.is_some() {
+                moves.push(Move::RemoveTile { addr })
+            }
+            moves.push(Move::LayTile {
+                addr,
+                tile: after.tile.clone(),
+                rotation: after.rotation,
+                company: None,
+            });
+            &[]
+        }
+    };
+    // NOTE: remove tokens before placing tokens, so that each token space is
+    // empty before a different token is placed in it.
+    for (space, company) in tokens_before {
+        if !after.tokens.contains(&(*space, company.clone())) {
+            moves.push(Move::RemoveToken {
+                addr,
+                space: *space,
+            })
+        }
+    }
+    for (space, company) in &after.tokens {
+        if !tokens_before.contains(&(*space, company.clone())) {
+            moves.push(Move::PlaceToken {
+                addr,
+                space: *space,
+                company: company.clone(),
+            })
+        }
+    }
+    moves
+}
+
+/// Applies a move that was made by another player, and records it in the
+/// journal.
+///
+/// The move is not checked against the game rules, because the host has
+/// already accepted it and every player must apply it (see
+/// [Assets::apply_move]).
+/// Returns `false` if the move could not be applied, which means that this
+/// player's copy of the game differs from the other players' copies.
+pub fn apply_move(assets: &mut Assets, mv: &Move) -> bool {
+    match mv {
+        Move::BuyPrivate { .. } | Move::ClosePrivate(_) => {
+            // NOTE: the user interface does not record which companies own
+            // each private company.
+            false
+        }
+        Move::RunRoutes { .. } => {
+            // NOTE: routes do not change the map, and the user interface
+            // never shares them.
+            false
+        }
+        _ => match assets.apply_move(mv) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("{}", e);
+                false
+            }
+        },
+    }
+}
@@ -0,0 +1,294 @@
+//! Auxiliary panels that show game information in separate windows or panes,
+//! rather than drawing it on the map.
+//!
+//! Each [Panel] contains structured data, which
+//! [UiController::show_panel](crate::UiController::show_panel)
+//! implementations can display however they choose.
+//! [Panel::table] provides a simple tabular view of this data.
+
+use n18game::Journal;
+use n18hex::HexColour;
+use n18map::{HexAddress, HexDescription, Map};
+use n18route::Routes;
+
+use crate::lang::Strings;
+use crate::Assets;
+
+/// Identifies the different kinds of auxiliary panels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PanelKind {
+    /// The optimal routes for a company.
+    Routes,
+    /// The moves that have been made in the current game.
+    Journal,
+    /// The number of each tile that has been placed, and how many remain.
+    Tiles,
+    /// The contents of a single map hex.
+    Hex,
+}
+
+/// The contents of an auxiliary panel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Panel {
+    Routes(RouteSummary),
+    Journal(Journal),
+    Tiles(TileInventory),
+    Hex(HexSummary),
+}
+
+impl Panel {
+    /// Returns the kind of this panel.
+    pub fn kind(&self) -> PanelKind {
+        match self {
+            Panel::Routes(_) => PanelKind::Routes,
+            Panel::Journal(_) => PanelKind::Journal,
+            Panel::Tiles(_) => PanelKind::Tiles,
+            Panel::Hex(_) => PanelKind::Hex,
+        }
+    }
+
+    /// Returns the panel title, translated into the given language.
+    pub fn title(&self, strings: &Strings) -> String {
+        match self {
+            Panel::Routes(summary) => strings.format(
+                "{company} routes: ${revenue}",
+                &[
+                    ("company", &summary.company),
+                    ("revenue", &summary.net_revenue),
+                ],
+            ),
+            Panel::Journal(_) => strings.tr("Game journal").to_string(),
+            Panel::Tiles(_) => strings.tr("Tile inventory").to_string(),
+            Panel::Hex(summary) => {
+                strings.format("Hex {name}", &[("name", &summary.name)])
+            }
+        }
+    }
+
+    /// Returns the column headings and the rows of a table that contains the
+    /// panel contents.
+    pub fn table(&self) -> (Vec<&'static str>, Vec<Vec<String>>) {
+        match self {
+            Panel::Routes(summary) => {
+                let rows = summary
+                    .routes
+                    .iter()
+                    .map(|route| {
+                        vec![
+                            route.train.clone(),
+                            format!("${}", route.revenue),
+                            route.stops.join(", "),
+                        ]
+                    })
+                    .collect();
+                (vec!["Train", "Revenue", "Stops"], rows)
+            }
+            Panel::Journal(journal) => {
+                let rows = journal
+                    .entries()
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, entry)| {
+                        vec![(ix + 1).to_string(), entry.clone()]
+                    })
+                    .collect();
+                (vec!["#", "Move"], rows)
+            }
+            Panel::Tiles(inventory) => {
+                let rows = inventory
+                    .tiles
+                    .iter()
+                    .map(|tile| {
+                        let remaining = tile
+                            .remaining()
+                            .map(|count| count.to_string())
+                            .unwrap_or_else(|| "Unlimited".to_string());
+                        vec![
+                            tile.name.clone(),
+                            format!("{:?}", tile.colour),
+                            tile.placed.to_string(),
+                            remaining,
+                        ]
+                    })
+                    .collect();
+                (vec!["Tile", "Colour", "Placed", "Remaining"], rows)
+            }
+            Panel::Hex(summary) => {
+                (vec!["Property", "Value"], summary.rows())
+            }
+        }
+    }
+}
+
+/// Summarises the optimal routes for a company.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouteSummary {
+    /// The company abbreviation.
+    pub company: String,
+    /// The net revenue earned by all of the routes.
+    pub net_revenue: usize,
+    /// The route operated by each train.
+    pub routes: Vec<RouteRow>,
+}
+
+/// Summarises a single train route.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouteRow {
+    /// The train name (e.g., "5", "4D").
+    pub train: String,
+    /// The revenue earned by this train.
+    pub revenue: usize,
+    /// The map hexes where the train earns revenue, in order.
+    pub stops: Vec<String>,
+}
+
+impl RouteSummary {
+    /// Summarises the optimal routes for a company in the active game.
+    pub fn new(assets: &Assets, company: &str, routes: &Routes) -> Self {
+        let game = assets.games.active();
+        let net_revenue = routes.net_revenue;
+        let routes = routes
+            .train_routes
+            .iter()
+            .map(|route| RouteRow {
+                train: game
+                    .train_name(&route.train)
+                    .unwrap_or("?")
+                    .to_string(),
+                revenue: route.revenue,
+                stops: route
+                    .route
+                    .visits
+                    .iter()
+                    .filter(|visit| visit.revenue > 0)
+                    .map(|visit| assets.hex_name(visit.addr))
+                    .collect(),
+            })
+            .collect();
+        RouteSummary {
+            company: company.to_string(),
+            net_revenue,
+            routes,
+        }
+    }
+}
+
+/// Describes the contents of a single map hex, for inspecting the hex.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HexSummary {
+    /// The hex coordinates (e.g., "E11").
+    pub name: String,
+    /// The tile, cities, tokens, and upgrade candidates of this hex.
+    pub description: HexDescription,
+}
+
+impl HexSummary {
+    /// Describes a map hex in the active game, or returns `None` if `addr`
+    /// is not a valid hex address for the map.
+    pub fn new(assets: &Assets, addr: HexAddress) -> Option<Self> {
+        let description = assets.map.describe_hex(addr)?;
+        Some(HexSummary {
+            name: assets.hex_name(addr),
+            description,
+        })
+    }
+
+    /// Returns a table row for each property of this hex.
+    fn rows(&self) -> Vec<Vec<String>> {
+        let descr = &self.description;
+        let mut rows = vec![
+            vec![
+                "Tile".to_string(),
+                descr.tile.clone().unwrap_or_else(|| "None".to_string()),
+            ],
+            vec!["Colour".to_string(), format!("{:?}", descr.colour)],
+        ];
+        if let Some(rotation) = descr.rotation {
+            rows.push(vec![
+                "Rotation".to_string(),
+                format!("{} clockwise", rotation.count_turns()),
+            ]);
+        }
+        for (ix, city) in descr.cities.iter().enumerate() {
+            let tokens: Vec<&str> = city
+                .tokens
+                .iter()
+                .map(|name| name.as_deref().unwrap_or("-"))
+                .collect();
+            let value = if tokens.is_empty() {
+                format!("${}", city.revenue)
+            } else {
+                format!("${} ({})", city.revenue, tokens.join(", "))
+            };
+            rows.push(vec![format!("City {}", ix + 1), value]);
+        }
+        for (ix, revenue) in descr.dits.iter().enumerate() {
+            rows.push(vec![
+                format!("Town {}", ix + 1),
+                format!("${}", revenue),
+            ]);
+        }
+        if let Some(location) = &descr.location {
+            rows.push(vec!["Location".to_string(), location.clone()]);
+        }
+        let upgrades = if descr.upgrades.is_empty() {
+            "None".to_string()
+        } else {
+            descr.upgrades.join(", ")
+        };
+        rows.push(vec!["Upgrades".to_string(), upgrades]);
+        rows
+    }
+}
+
+/// Lists the tiles that players can place on the map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TileInventory {
+    pub tiles: Vec<TileCount>,
+}
+
+/// Records how many copies of a tile have been placed on the map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TileCount {
+    /// The tile name.
+    pub name: String,
+    /// The tile colour.
+    pub colour: HexColour,
+    /// The number of copies placed on the map.
+    pub placed: usize,
+    /// The number of copies in the game, or `None` if unlimited.
+    pub limit: Option<usize>,
+}
+
+impl TileCount {
+    /// Returns the number of copies that can still be placed, or `None` if
+    /// unlimited.
+    pub fn remaining(&self) -> Option<usize> {
+        self.limit.map(|limit| limit.saturating_sub(self.placed))
+    }
+}
+
+impl TileInventory {
+    /// Lists each tile in the map catalogue that is available to players.
+    pub fn new(map: &Map) -> Self {
+        use n18catalogue::Availability::*;
+
+        let tiles = map
+            .tile_avail_iter()
+            .filter_map(|(tile, avail)| {
+                let limit = match avail {
+                    Limited(count) => Some(*count),
+                    Unlimited => None,
+                    Unavailable => return None,
+                };
+                Some(TileCount {
+                    name: tile.name.clone(),
+                    colour: tile.colour,
+                    placed: map.placed_count(&tile.name),
+                    limit,
+                })
+            })
+            .collect();
+        TileInventory { tiles }
+    }
+}
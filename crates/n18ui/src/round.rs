@@ -0,0 +1,125 @@
+//! Guide the user through an operating round, in which each company lays a
+//! tile, places a token, and runs its trains, in the order defined by
+//! [Game::operating_order](n18game::Game::operating_order).
+//!
+//! The operating round is layered over the existing user interface states:
+//! the user lays tiles and places tokens as usual, and advances to the next
+//! step when they are ready.
+//! When the current company runs its trains, the revenue is recorded in the
+//! journal and in the [revenue history](crate::Assets::revenue_history), and
+//! the next company begins to operate.
+
+/// The steps that each company takes when it operates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    LayTile,
+    PlaceToken,
+    RunRoutes,
+}
+
+impl Step {
+    /// Returns a short description of this step, which is shown in the
+    /// status bar.
+    pub fn prompt(&self) -> &'static str {
+        match self {
+            Step::LayTile => "lay a tile",
+            Step::PlaceToken => "place a token",
+            Step::RunRoutes => "run trains",
+        }
+    }
+}
+
+/// The revenue that a company earned in an operating round.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Revenue {
+    /// The operating round number, starting at one.
+    pub round: usize,
+    /// The abbreviated company name.
+    pub company: String,
+    /// The net revenue earned by the company's trains.
+    pub revenue: usize,
+}
+
+/// The companies that have yet to operate in an operating round, and the
+/// step that the current company is taking.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OperatingRound {
+    number: usize,
+    order: Vec<String>,
+    company_ix: usize,
+    step: Step,
+}
+
+impl OperatingRound {
+    /// Starts an operating round in which the companies operate in the
+    /// provided order.
+    ///
+    /// Returns `None` if there are no companies.
+    pub fn new(number: usize, order: Vec<String>) -> Option<Self> {
+        if order.is_empty() {
+            return None;
+        }
+        Some(OperatingRound {
+            number,
+            order,
+            company_ix: 0,
+            step: Step::LayTile,
+        })
+    }
+
+    /// Returns the operating round number, starting at one.
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    /// Returns the abbreviated name of the company that is operating.
+    pub fn company(&self) -> &str {
+        &self.order[self.company_ix]
+    }
+
+    /// Returns the order in which the companies operate.
+    pub fn order(&self) -> &[String] {
+        &self.order
+    }
+
+    /// Returns the position of the operating company in the operating
+    /// order, starting at one.
+    pub fn position(&self) -> usize {
+        self.company_ix + 1
+    }
+
+    /// Returns the step that the operating company is taking.
+    pub fn step(&self) -> Step {
+        self.step
+    }
+
+    /// Advances to the next step, or to the next company once the operating
+    /// company has run its trains.
+    ///
+    /// Returns `false` if every company has operated.
+    pub fn next_step(&mut self) -> bool {
+        match self.step {
+            Step::LayTile => {
+                self.step = Step::PlaceToken;
+                true
+            }
+            Step::PlaceToken => {
+                self.step = Step::RunRoutes;
+                true
+            }
+            Step::RunRoutes => self.next_company(),
+        }
+    }
+
+    /// Advances to the first step of the next company.
+    ///
+    /// Returns `false` if every company has operated.
+    pub fn next_company(&mut self) -> bool {
+        if self.company_ix + 1 >= self.order.len() {
+            return false;
+        }
+        self.company_ix += 1;
+        self.step = Step::LayTile;
+        true
+    }
+}
@@ -0,0 +1,239 @@
+//! Record user input as a script, and replay scripts to reproduce a sequence
+//! of user interface states.
+//!
+//! While recording (see
+//! [UserInterface::start_recording](crate::UserInterface::start_recording)),
+//! each [KeyPress] and [ButtonPress] event is appended to a [Script], along
+//! with the time since recording started and the name of the user interface
+//! [State](crate::State) that handled the event.
+//! Scripts are stored as JSON, and can be replayed with
+//! [UserInterface::replay](crate::UserInterface::replay), which passes each
+//! event to the appropriate event handler and checks that each event is
+//! handled by the same state as when it was recorded.
+//!
+//! This allows bugs to be reproduced, and allows the user interface to be
+//! tested without GTK windows and input devices.
+
+use gdk::glib::translate::{FromGlib, IntoGlib};
+use gdk4 as gdk;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use n18io::Error;
+
+use crate::{ButtonPress, KeyPress};
+
+/// A recorded sequence of user input events.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Script {
+    pub steps: Vec<Step>,
+}
+
+/// A single recorded user input event.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    /// The time at which the event occurred, in milliseconds since recording
+    /// started.
+    pub time_ms: u64,
+    /// The name of the user interface state that handled the event.
+    pub state: String,
+    /// The user input event.
+    pub event: Event,
+}
+
+/// The user input events that can be recorded.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Event {
+    /// A keyboard key was pressed.
+    KeyPress {
+        /// The key value, as defined by GDK.
+        keyval: u32,
+        ctrl: bool,
+        alt: bool,
+        shift: bool,
+    },
+    /// A mouse button was clicked.
+    ButtonPress {
+        x: f64,
+        y: f64,
+        button: u32,
+        #[serde(default)]
+        ctrl: bool,
+        #[serde(default)]
+        alt: bool,
+        shift: bool,
+    },
+}
+
+impl From<&KeyPress> for Event {
+    fn from(event: &KeyPress) -> Self {
+        Event::KeyPress {
+            keyval: event.key.into_glib(),
+            ctrl: event.ctrl,
+            alt: event.alt,
+            shift: event.shift,
+        }
+    }
+}
+
+impl From<&ButtonPress> for Event {
+    fn from(event: &ButtonPress) -> Self {
+        Event::ButtonPress {
+            x: event.x,
+            y: event.y,
+            button: event.button,
+            ctrl: event.ctrl,
+            alt: event.alt,
+            shift: event.shift,
+        }
+    }
+}
+
+impl Event {
+    /// Returns the key-press event, if this is a key press.
+    pub fn key_press(&self) -> Option<KeyPress> {
+        match *self {
+            Event::KeyPress {
+                keyval,
+                ctrl,
+                alt,
+                shift,
+            } => Some(KeyPress {
+                // SAFETY: every u32 value is a valid (if unassigned) key
+                // value.
+                key: unsafe { gdk::Key::from_glib(keyval) },
+                ctrl,
+                alt,
+                shift,
+            }),
+            Event::ButtonPress { .. } => None,
+        }
+    }
+
+    /// Returns the button-press event, if this is a button press.
+    pub fn button_press(&self) -> Option<ButtonPress> {
+        match *self {
+            Event::ButtonPress {
+                x,
+                y,
+                button,
+                ctrl,
+                alt,
+                shift,
+            } => Some(ButtonPress {
+                x,
+                y,
+                button,
+                ctrl,
+                alt,
+                shift,
+            }),
+            Event::KeyPress { .. } => None,
+        }
+    }
+}
+
+impl Script {
+    /// Reads a script from the JSON file `path`.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text =
+            std::fs::read_to_string(path).map_err(|source| Error::Io {
+                path: Some(path.to_path_buf()),
+                source,
+            })?;
+        serde_json::from_str(&text).map_err(|source| Error::Json {
+            path: Some(path.to_path_buf()),
+            line: source.line(),
+            column: source.column(),
+            source,
+        })
+    }
+
+    /// Writes this script to the JSON file `path`.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let text = serde_json::to_string_pretty(self).map_err(|source| {
+            Error::Json {
+                path: Some(path.to_path_buf()),
+                line: source.line(),
+                column: source.column(),
+                source,
+            }
+        })?;
+        std::fs::write(path, text).map_err(|source| Error::Io {
+            path: Some(path.to_path_buf()),
+            source,
+        })
+    }
+}
+
+/// Records user input events while the user interface is running.
+#[derive(Debug)]
+pub struct Recorder {
+    started: std::time::Instant,
+    script: Script,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Recorder {
+            started: std::time::Instant::now(),
+            script: Script::default(),
+        }
+    }
+}
+
+impl Recorder {
+    /// Records an event that is about to be handled by the named state.
+    pub fn record(&mut self, state: &str, event: Event) {
+        let time_ms = self.started.elapsed().as_millis() as u64;
+        self.script.steps.push(Step {
+            time_ms,
+            state: state.to_string(),
+            event,
+        })
+    }
+
+    /// Returns the events that have been recorded.
+    pub fn script(&self) -> &Script {
+        &self.script
+    }
+
+    /// Stops recording and returns the recorded events.
+    pub fn finish(self) -> Script {
+        self.script
+    }
+}
+
+/// Describes why a script could not be replayed.
+#[derive(Debug)]
+pub enum ReplayError {
+    /// The event at index `step` was recorded in a different state.
+    StateMismatch {
+        step: usize,
+        expected: String,
+        found: String,
+    },
+}
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::StateMismatch {
+                step,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Step {} was recorded in the {} state, not the {} state",
+                step + 1,
+                expected,
+                found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
@@ -0,0 +1,134 @@
+//! Saves the user interface session on exit, and restores it on launch.
+//!
+//! A [Session] records the most recently loaded or saved game, the zoom
+//! level, the position of the map within the window, the window size, the
+//! user interface language, the map theme, the map view rotation, the
+//! background image (if any), and the recently loaded and saved game files
+//! (see [crate::control::FileHistory]).
+//!
+//! Note that the background image settings are only saved here, and are
+//! never included in saved games or exported images.
+//! It is stored as JSON in the file returned by [default_path].
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use n18io::Error;
+
+use crate::background::BackgroundSettings;
+use crate::view::ViewTransform;
+
+/// The user interface state that is restored on the next launch.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// The most recently loaded or saved game.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_path: Option<PathBuf>,
+    /// The maximal hex diameter, which defines the zoom level.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hex_diameter: Option<f64>,
+    /// The horizontal and vertical scroll offsets of the map.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub viewport: Option<(f64, f64)>,
+    /// The window width and height, in pixels.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_size: Option<(i32, i32)>,
+    /// The name of the user interface language, which is omitted for the
+    /// default language (see [crate::lang]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// The name of the map theme, which is omitted for the default theme
+    /// (see [n18hex::theme::THEME_NAMES]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    /// How the map is rotated and mirrored when it is shown in the window
+    /// (see [crate::view]).
+    #[serde(default, skip_serializing_if = "ViewTransform::is_identity")]
+    pub view: ViewTransform,
+    /// The scanned map image that is drawn beneath the map, if any (see
+    /// [crate::background]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background: Option<BackgroundSettings>,
+    /// The recently loaded and saved game files, most recent first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_files: Vec<PathBuf>,
+    /// The directory in which files for each game (identified by name) were
+    /// most recently loaded or saved.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub game_dirs: BTreeMap<String, PathBuf>,
+}
+
+impl Session {
+    /// Reads a session from the JSON file `path`.
+    ///
+    /// Returns the default session if this file does not exist.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Session::default())
+            }
+            Err(source) => {
+                return Err(Error::Io {
+                    path: Some(path.to_path_buf()),
+                    source,
+                })
+            }
+        };
+        serde_json::from_str(&text).map_err(|source| Error::Json {
+            path: Some(path.to_path_buf()),
+            line: source.line(),
+            column: source.column(),
+            source,
+        })
+    }
+
+    /// Writes this session to the JSON file `path`, creating the parent
+    /// directory if necessary.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let io_error = |source| Error::Io {
+            path: Some(path.to_path_buf()),
+            source,
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        let text = serde_json::to_string_pretty(self).map_err(|source| {
+            Error::Json {
+                path: Some(path.to_path_buf()),
+                line: source.line(),
+                column: source.column(),
+                source,
+            }
+        })?;
+        std::fs::write(path, text).map_err(io_error)
+    }
+}
+
+/// Returns the path of the session file.
+///
+/// This is the `RUSTY_TRAIN_SESSION` environment variable, if it is defined.
+/// Otherwise, it is `rusty_train/session.json` in the user's configuration
+/// directory (`$XDG_CONFIG_HOME`, or `$HOME/.config`).
+pub fn default_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("RUSTY_TRAIN_SESSION") {
+        return Some(path.into());
+    }
+    config_dir().map(|dir| dir.join("session.json"))
+}
+
+/// Returns the `rusty_train` directory in the user's configuration directory
+/// (`$XDG_CONFIG_HOME`, or `$HOME/.config`).
+pub fn config_dir() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".config"))
+        })?;
+    Some(config_dir.join("rusty_train"))
+}
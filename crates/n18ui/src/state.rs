@@ -2,13 +2,23 @@ use cairo::Context;
 use n18hex::Colour;
 use n18map::HexAddress;
 
+use crate::status::{SegmentKind, StatusBar};
 use crate::{Assets, Controller, UiResponse};
 
 pub mod default;
 pub mod edit_tokens;
+pub mod network;
+pub mod palette;
 pub mod replace_tile;
+pub mod route_graph;
+pub mod screenshot;
 pub mod search;
+pub mod select;
 pub mod start;
+pub mod token_spaces;
+
+/// The opacity with which the tiles in the map's planning layer are drawn.
+const PLAN_ALPHA: f64 = 0.6;
 
 /// The methods that are required in order to manipulate the user interface.
 pub trait UiState {
@@ -26,6 +36,68 @@ pub trait UiState {
     ) -> (UiResponse, Option<State>) {
         (UiResponse::None, None)
     }
+
+    /// Adds state-specific segments to the status bar, or replaces the
+    /// common segments (see [State::status]).
+    ///
+    /// The default implementation does not modify the status bar.
+    fn status(&self, _assets: &Assets, _status: &mut StatusBar) {}
+
+    /// Returns the actions that can be applied to the map hex `addr` in this
+    /// state, which are offered in the hex context menu.
+    ///
+    /// The default implementation returns no actions.
+    fn hex_actions(
+        &self,
+        _assets: &Assets,
+        _addr: HexAddress,
+    ) -> Vec<HexAction> {
+        vec![]
+    }
+}
+
+/// The actions that can be applied to a single map hex (see
+/// [UiState::hex_actions]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HexAction {
+    /// Place any tile on the hex, replacing the current tile (if any).
+    PlaceTile,
+    /// Upgrade the current tile, or place a tile on an empty hex.
+    UpgradeTile,
+    /// Rotate the current tile anti-clockwise.
+    RotateAntiClockwise,
+    /// Rotate the current tile clockwise.
+    RotateClockwise,
+    /// Remove the current tile.
+    RemoveTile,
+    /// Restore the tile that was on the hex at the start of the game.
+    ResetHex,
+    /// Place, replace, or remove the tokens on the current tile.
+    EditTokens,
+    /// Add a note to the game journal.
+    AddNote,
+    /// Describe the hex in the hex inspector panel.
+    Inspect,
+}
+
+impl HexAction {
+    /// Returns the name of the keymap action that applies this action to the
+    /// active hex (see [Command::name](crate::Command::name)).
+    pub fn command_name(&self) -> &'static str {
+        match self {
+            HexAction::PlaceTile => "Place or replace a tile",
+            HexAction::UpgradeTile => "Upgrade the tile",
+            HexAction::RotateAntiClockwise => {
+                "Rotate the tile anti-clockwise"
+            }
+            HexAction::RotateClockwise => "Rotate the tile clockwise",
+            HexAction::RemoveTile => "Remove the tile",
+            HexAction::ResetHex => "Reset the hex to its initial tile",
+            HexAction::EditTokens => "Edit tokens",
+            HexAction::AddNote => "Add a note to the journal",
+            HexAction::Inspect => "Inspect the active hex",
+        }
+    }
 }
 
 /// The different user interface states.
@@ -34,10 +106,18 @@ pub enum State {
     Default(default::Default),
     EditTokens(edit_tokens::EditTokens),
     ReplaceTile(replace_tile::ReplaceTile),
+    Select(select::Select),
+    Screenshot(screenshot::Screenshot),
     FindRoutesCompany(search::SelectCompany),
     FindRoutesTrains(search::SelectTrains),
+    FindOpenRoutesTrain(search::SelectOpenTrain),
     FindRoutesSearch(search::Search),
     FindRoutesFound(search::Found),
+    RankTokenSpaces(token_spaces::Rank),
+    RankedTokenSpaces(token_spaces::Ranked),
+    RouteGraph(route_graph::RouteGraph),
+    CompanyNetwork(network::CompanyNetwork),
+    CommandPalette(palette::CommandPalette),
 }
 
 /// Returns the default UI state, with the provided active map hex.
@@ -71,6 +151,18 @@ impl From<replace_tile::ReplaceTile> for State {
     }
 }
 
+impl From<select::Select> for State {
+    fn from(state: select::Select) -> Self {
+        State::Select(state)
+    }
+}
+
+impl From<screenshot::Screenshot> for State {
+    fn from(state: screenshot::Screenshot) -> Self {
+        State::Screenshot(state)
+    }
+}
+
 impl From<search::SelectCompany> for State {
     fn from(state: search::SelectCompany) -> Self {
         State::FindRoutesCompany(state)
@@ -83,6 +175,12 @@ impl From<search::SelectTrains> for State {
     }
 }
 
+impl From<search::SelectOpenTrain> for State {
+    fn from(state: search::SelectOpenTrain) -> Self {
+        State::FindOpenRoutesTrain(state)
+    }
+}
+
 impl From<search::Search> for State {
     fn from(state: search::Search) -> Self {
         State::FindRoutesSearch(state)
@@ -95,12 +193,181 @@ impl From<search::Found> for State {
     }
 }
 
+impl From<token_spaces::Rank> for State {
+    fn from(state: token_spaces::Rank) -> Self {
+        State::RankTokenSpaces(state)
+    }
+}
+
+impl From<token_spaces::Ranked> for State {
+    fn from(state: token_spaces::Ranked) -> Self {
+        State::RankedTokenSpaces(state)
+    }
+}
+
+impl From<route_graph::RouteGraph> for State {
+    fn from(state: route_graph::RouteGraph) -> Self {
+        State::RouteGraph(state)
+    }
+}
+
+impl From<network::CompanyNetwork> for State {
+    fn from(state: network::CompanyNetwork) -> Self {
+        State::CompanyNetwork(state)
+    }
+}
+
+impl From<palette::CommandPalette> for State {
+    fn from(state: palette::CommandPalette) -> Self {
+        State::CommandPalette(state)
+    }
+}
+
 impl State {
     pub fn default_state(active_hex: HexAddress) -> Self {
         let state = default::Default::at_hex(active_hex);
         state.into()
     }
 
+    /// Returns the name of this state, as recorded in input
+    /// [scripts](crate::script::Script).
+    pub fn name(&self) -> &'static str {
+        match self {
+            State::Start(_) => "Start",
+            State::Default(_) => "Default",
+            State::EditTokens(_) => "EditTokens",
+            State::ReplaceTile(_) => "ReplaceTile",
+            State::Select(_) => "Select",
+            State::Screenshot(_) => "Screenshot",
+            State::FindRoutesCompany(_) => "FindRoutesCompany",
+            State::FindRoutesTrains(_) => "FindRoutesTrains",
+            State::FindOpenRoutesTrain(_) => "FindOpenRoutesTrain",
+            State::FindRoutesSearch(_) => "FindRoutesSearch",
+            State::FindRoutesFound(_) => "FindRoutesFound",
+            State::RankTokenSpaces(_) => "RankTokenSpaces",
+            State::RankedTokenSpaces(_) => "RankedTokenSpaces",
+            State::RouteGraph(_) => "RouteGraph",
+            State::CompanyNetwork(_) => "CompanyNetwork",
+            State::CommandPalette(_) => "CommandPalette",
+        }
+    }
+
+    /// Returns a short description of this state, which is shown in the
+    /// status bar.
+    pub fn mode(&self) -> &'static str {
+        match self {
+            State::Start(_) => "Start",
+            State::Default(_) => "Map",
+            State::EditTokens(_) => "Edit tokens",
+            State::ReplaceTile(_) => "Replace tile",
+            State::Select(_) => "Select hexes",
+            State::Screenshot(_) => "Screenshot",
+            State::FindRoutesCompany(_) => "Select a company",
+            State::FindRoutesTrains(_) => "Select trains",
+            State::FindOpenRoutesTrain(_) => "Select a train",
+            State::FindRoutesSearch(_) => "Finding routes",
+            State::FindRoutesFound(_) => "Routes",
+            State::RankTokenSpaces(_) => "Ranking token spaces",
+            State::RankedTokenSpaces(_) => "Token spaces",
+            State::RouteGraph(_) => "Route graph",
+            State::CompanyNetwork(_) => "Company network",
+            State::CommandPalette(_) => "Run a command",
+        }
+    }
+
+    /// Returns the active map hex, if any.
+    pub fn active_hex(&self) -> Option<HexAddress> {
+        match self {
+            State::Start(_) => None,
+            State::Default(state) => Some(state.active_hex()),
+            State::EditTokens(state) => Some(state.active_hex()),
+            State::ReplaceTile(state) => Some(state.active_hex()),
+            State::Select(state) => Some(state.active_hex()),
+            State::Screenshot(state) => Some(state.active_hex()),
+            State::FindRoutesCompany(state) => Some(state.active_hex()),
+            State::FindRoutesTrains(state) => Some(state.active_hex()),
+            State::FindOpenRoutesTrain(state) => Some(state.active_hex()),
+            State::FindRoutesSearch(state) => Some(state.active_hex()),
+            State::FindRoutesFound(state) => Some(state.active_hex()),
+            State::RankTokenSpaces(state) => Some(state.active_hex()),
+            State::RankedTokenSpaces(state) => Some(state.active_hex()),
+            State::RouteGraph(state) => Some(state.active_hex()),
+            State::CompanyNetwork(state) => Some(state.active_hex()),
+            State::CommandPalette(state) => {
+                state.previous().and_then(|state| state.active_hex())
+            }
+        }
+    }
+
+    /// Returns the contents of the status bar for this state.
+    ///
+    /// Once a game has started, the status bar shows the active hex and its
+    /// tile, the current game phase, the most recent revenue (if any), and
+    /// the progress of the operating round (if any).
+    /// Each state can add further segments, or replace these segments (see
+    /// [UiState::status]).
+    pub fn status(&self, assets: &Assets) -> StatusBar {
+        let mut status = match self {
+            State::CommandPalette(state) => state
+                .previous()
+                .map(|state| state.status(assets))
+                .unwrap_or_default(),
+            _ => StatusBar::new(),
+        };
+        status.set(SegmentKind::Mode, assets.strings.tr(self.mode()));
+        if assets.planning {
+            status.set(
+                SegmentKind::Mode,
+                assets.strings.format(
+                    "{mode} (planning)",
+                    &[("mode", &assets.strings.tr(self.mode()))],
+                ),
+            );
+        }
+        if self.as_start().is_some() {
+            return status;
+        }
+        if let Some(addr) = self.active_hex() {
+            status.set(SegmentKind::Hex, assets.hex_name(addr));
+            match assets.map.tile_at(addr) {
+                Some(tile) => status.set(SegmentKind::Tile, &tile.name),
+                None => status.remove(SegmentKind::Tile),
+            }
+        }
+        let phase = assets.games.active().current_phase_name();
+        status.set(
+            SegmentKind::Phase,
+            assets.strings.format("Phase {phase}", &[("phase", &phase)]),
+        );
+        if let Some((abbrev, revenue)) = &assets.last_revenue {
+            status.set(
+                SegmentKind::Revenue,
+                assets.strings.format(
+                    "{company}: ${revenue}",
+                    &[("company", abbrev), ("revenue", revenue)],
+                ),
+            );
+        }
+        if let Some(round) = &assets.operating_round {
+            status.set(
+                SegmentKind::Round,
+                assets.strings.format(
+                    "Round {round}: {company} to {step} ({position} of \
+                     {count})",
+                    &[
+                        ("round", &round.number()),
+                        ("company", &round.company()),
+                        ("step", &assets.strings.tr(round.step().prompt())),
+                        ("position", &round.position()),
+                        ("count", &round.order().len()),
+                    ],
+                ),
+            );
+        }
+        self.as_ref().status(assets, &mut status);
+        status
+    }
+
     pub fn is_default_state(&self) -> bool {
         matches!(self, State::Default(_))
     }
@@ -133,6 +400,20 @@ impl State {
         }
     }
 
+    pub fn as_select(&self) -> Option<&select::Select> {
+        match self {
+            State::Select(state) => Some(state),
+            _ => None,
+        }
+    }
+
+    pub fn as_screenshot(&self) -> Option<&screenshot::Screenshot> {
+        match self {
+            State::Screenshot(state) => Some(state),
+            _ => None,
+        }
+    }
+
     pub fn as_find_routes_company(&self) -> Option<&search::SelectCompany> {
         match self {
             State::FindRoutesCompany(state) => Some(state),
@@ -147,6 +428,15 @@ impl State {
         }
     }
 
+    pub fn as_find_open_routes_train(
+        &self,
+    ) -> Option<&search::SelectOpenTrain> {
+        match self {
+            State::FindOpenRoutesTrain(state) => Some(state),
+            _ => None,
+        }
+    }
+
     pub fn as_find_routes_search(&self) -> Option<&search::Search> {
         match self {
             State::FindRoutesSearch(state) => Some(state),
@@ -161,6 +451,34 @@ impl State {
         }
     }
 
+    pub fn as_rank_token_spaces(&self) -> Option<&token_spaces::Rank> {
+        match self {
+            State::RankTokenSpaces(state) => Some(state),
+            _ => None,
+        }
+    }
+
+    pub fn as_ranked_token_spaces(&self) -> Option<&token_spaces::Ranked> {
+        match self {
+            State::RankedTokenSpaces(state) => Some(state),
+            _ => None,
+        }
+    }
+
+    pub fn as_route_graph(&self) -> Option<&route_graph::RouteGraph> {
+        match self {
+            State::RouteGraph(state) => Some(state),
+            _ => None,
+        }
+    }
+
+    pub fn as_company_network(&self) -> Option<&network::CompanyNetwork> {
+        match self {
+            State::CompanyNetwork(state) => Some(state),
+            _ => None,
+        }
+    }
+
     pub fn as_default_mut(&mut self) -> Option<&mut default::Default> {
         match self {
             State::Default(state) => Some(state),
@@ -193,6 +511,22 @@ impl State {
         }
     }
 
+    pub fn as_select_mut(&mut self) -> Option<&mut select::Select> {
+        match self {
+            State::Select(state) => Some(state),
+            _ => None,
+        }
+    }
+
+    pub fn as_screenshot_mut(
+        &mut self,
+    ) -> Option<&mut screenshot::Screenshot> {
+        match self {
+            State::Screenshot(state) => Some(state),
+            _ => None,
+        }
+    }
+
     pub fn as_find_routes_company_mut(
         &mut self,
     ) -> Option<&mut search::SelectCompany> {
@@ -211,6 +545,15 @@ impl State {
         }
     }
 
+    pub fn as_find_open_routes_train_mut(
+        &mut self,
+    ) -> Option<&mut search::SelectOpenTrain> {
+        match self {
+            State::FindOpenRoutesTrain(state) => Some(state),
+            _ => None,
+        }
+    }
+
     pub fn as_find_routes_search_mut(
         &mut self,
     ) -> Option<&mut search::Search> {
@@ -227,13 +570,52 @@ impl State {
         }
     }
 
+    pub fn as_ranked_token_spaces_mut(
+        &mut self,
+    ) -> Option<&mut token_spaces::Ranked> {
+        match self {
+            State::RankedTokenSpaces(state) => Some(state),
+            _ => None,
+        }
+    }
+
+    pub fn as_route_graph_mut(
+        &mut self,
+    ) -> Option<&mut route_graph::RouteGraph> {
+        match self {
+            State::RouteGraph(state) => Some(state),
+            _ => None,
+        }
+    }
+
+    pub fn as_company_network_mut(
+        &mut self,
+    ) -> Option<&mut network::CompanyNetwork> {
+        match self {
+            State::CompanyNetwork(state) => Some(state),
+            _ => None,
+        }
+    }
+
     pub fn draw(&self, assets: &Assets, context: &Context) {
         Colour::WHITE.apply_colour(context);
         context.reset_clip();
         let (x1, y1, x2, y2) = context.clip_extents().unwrap();
         context.rectangle(x1, y1, x2, y2);
         context.fill().unwrap();
-        self.as_ref().draw(assets, context)
+        self.as_ref().draw(assets, context);
+        // Draw the planned tiles above the map, except for the hex whose
+        // tile is being replaced (if any).
+        if assets.show_plan && self.as_start().is_none() {
+            let skip = self.as_replace_tile().map(|state| state.active_hex());
+            n18brush::draw_planned_tiles(
+                &assets.hex,
+                context,
+                &assets.map,
+                PLAN_ALPHA,
+                |addr| Some(*addr) != skip,
+            );
+        }
     }
 
     pub fn ping(
@@ -264,10 +646,18 @@ impl AsRef<dyn UiState> for State {
             Default(state) => state,
             EditTokens(state) => state,
             ReplaceTile(state) => state,
+            Select(state) => state,
+            Screenshot(state) => state,
             FindRoutesCompany(state) => state,
             FindRoutesTrains(state) => state,
+            FindOpenRoutesTrain(state) => state,
             FindRoutesSearch(state) => state,
             FindRoutesFound(state) => state,
+            RankTokenSpaces(state) => state,
+            RankedTokenSpaces(state) => state,
+            RouteGraph(state) => state,
+            CompanyNetwork(state) => state,
+            CommandPalette(state) => state,
         }
     }
 }
@@ -280,10 +670,18 @@ impl AsMut<dyn UiState> for State {
             Default(state) => state,
             EditTokens(state) => state,
             ReplaceTile(state) => state,
+            Select(state) => state,
+            Screenshot(state) => state,
             FindRoutesCompany(state) => state,
             FindRoutesTrains(state) => state,
+            FindOpenRoutesTrain(state) => state,
             FindRoutesSearch(state) => state,
             FindRoutesFound(state) => state,
+            RankTokenSpaces(state) => state,
+            RankedTokenSpaces(state) => state,
+            RouteGraph(state) => state,
+            CompanyNetwork(state) => state,
+            CommandPalette(state) => state,
         }
     }
 }
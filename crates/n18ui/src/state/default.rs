@@ -2,17 +2,31 @@
 use cairo::Context;
 use std::sync::mpsc::{Receiver, Sender};
 
+use n18game::bank::TrainTransfer;
+use n18game::sim::Move;
 use n18map::{HexAddress, Map};
 
+use crate::round::Step;
+use crate::state::replace_tile::ReplaceTile;
+use crate::state::search::{Goal, SelectTrains};
+use crate::state::HexAction;
 use crate::{
-    Assets, Controller, PingDest, UiController, UiResponse, UiState,
+    Assets, Controller, PingDest, State, UiController, UiResponse, UiState,
 };
 
+/// The changes that the user can make from the default state via dialogs.
+enum Message {
+    /// Change the game phase.
+    Phase(usize),
+    /// Record a train purchase (or discard).
+    Trains(TrainTransfer),
+}
+
 /// The default state: selecting a tile.
 pub struct Default {
     active_hex: HexAddress,
-    sender: Sender<usize>,
-    receiver: Receiver<usize>,
+    sender: Sender<Message>,
+    receiver: Receiver<Message>,
 }
 
 impl Default {
@@ -42,16 +56,120 @@ impl Default {
         self.active_hex = addr;
     }
 
-    pub fn select_phase(&self, assets: &Assets, controller: &mut Controller) {
+    /// Prompts the user to select a game phase, showing a preview of the
+    /// changes that each phase will make, and changes to the selected phase
+    /// with [Assets::set_phase].
+    pub fn select_phase(
+        &self,
+        assets: &mut Assets,
+        controller: &mut Controller,
+    ) {
         let self_tx = self.sender.clone();
         let ping_tx = controller.ping_tx();
-        controller.select_phase(assets.games.active(), move |ix_opt| {
+        let game = assets.games.active_mut();
+        let effects: Vec<_> = (0..game.phase_count())
+            .filter_map(|ix| game.preview_phase(&assets.map, ix))
+            .collect();
+        let game = assets.games.active();
+        controller.select_phase(game, &effects, move |ix_opt| {
             if let Some(ix) = ix_opt {
-                self_tx.send(ix).unwrap();
+                self_tx.send(Message::Phase(ix)).unwrap();
                 ping_tx.send_ping(PingDest::State).unwrap();
             }
         });
     }
+
+    pub fn select_train_transfer(
+        &self,
+        assets: &Assets,
+        controller: &mut Controller,
+    ) {
+        let self_tx = self.sender.clone();
+        let ping_tx = controller.ping_tx();
+        controller.select_train_transfer(&assets.trains, move |opt| {
+            if let Some(transfer) = opt {
+                self_tx.send(Message::Trains(transfer)).unwrap();
+                ping_tx.send_ping(PingDest::State).unwrap();
+            }
+        });
+    }
+
+    /// Forms the national company, if it can be formed in the current game
+    /// phase, and records each converted company in the journal.
+    ///
+    /// Returns `true` if any company tokens were replaced.
+    pub fn form_national(&self, assets: &mut Assets) -> bool {
+        let game = assets.games.active();
+        let formation = match game.national_formation() {
+            Some(formation) => formation,
+            None => return false,
+        };
+        let converted = match game.form_national(&mut assets.map) {
+            Some(converted) => converted,
+            None => return false,
+        };
+        let national = &formation.national;
+        let mut entries = vec![format!(
+            "Formed the {} ({})",
+            national.full_name, national.abbrev
+        )];
+        let mut changes = vec![];
+        for (abbrev, company_changes) in converted {
+            entries.push(format!(
+                "Converted {} into {} share(s) of {}",
+                abbrev, formation.shares_per_company, national.abbrev
+            ));
+            changes.extend(company_changes)
+        }
+        if changes.is_empty() {
+            // No companies had placed tokens, or they were already converted.
+            return false;
+        }
+        assets.record_token_changes(entries, changes);
+        true
+    }
+
+    /// Starts an operating round, or advances the operating round to its
+    /// next step.
+    ///
+    /// When the operating company should run its trains, this prompts the
+    /// user to select the company's trains and returns the new state.
+    /// A company that has not placed any tokens does not run any trains.
+    pub fn advance_operating_round(
+        &self,
+        assets: &mut Assets,
+        controller: &mut Controller,
+    ) -> (UiResponse, Option<State>) {
+        let Some(round) = &assets.operating_round else {
+            let response = if assets.start_operating_round() {
+                UiResponse::Redraw
+            } else {
+                UiResponse::None
+            };
+            return (response, None);
+        };
+        if round.step() != Step::RunRoutes {
+            assets.next_operating_step();
+            return (UiResponse::Redraw, None);
+        }
+        let abbrev = round.company().to_string();
+        let token = assets.map.try_token(&abbrev).filter(|token| {
+            assets.map.unique_placed_tokens().contains(token)
+        });
+        let Some(token) = token else {
+            assets.record_operating_revenue(&abbrev, &[], 0);
+            return (UiResponse::Redraw, None);
+        };
+        let state = SelectTrains::new(
+            assets,
+            controller,
+            self.active_hex,
+            abbrev,
+            token,
+            Goal::Routes,
+        );
+        (UiResponse::Redraw, Some(state.into()))
+    }
 }
 
 impl UiState for Default {
@@ -76,13 +194,60 @@ impl UiState for Default {
     fn ping(
         &mut self,
         assets: &mut Assets,
-        _controller: &mut Controller,
+        controller: &mut Controller,
     ) -> (UiResponse, Option<crate::State>) {
-        let phase_ix = self.receiver.recv().unwrap();
-        assets
-            .games
-            .active_mut()
-            .set_phase_ix(&mut assets.map, phase_ix);
-        (UiResponse::Redraw, None)
+        let Ok(message) = self.receiver.try_recv() else {
+            return (UiResponse::None, None);
+        };
+        let phase_ix = assets.games.active().phase_ix();
+        let (mv, title) = match message {
+            Message::Phase(phase_ix) => {
+                (Move::SetPhase(phase_ix), "Could not change the game phase")
+            }
+            Message::Trains(transfer) => (
+                Move::TransferTrain(transfer),
+                "Could not record train purchase",
+            ),
+        };
+        if let Err(e) = assets.apply_move(&mv) {
+            controller.show_error(assets.strings.tr(title), &e.to_string());
+            return (UiResponse::None, None);
+        }
+        let changed_phase = assets.games.active().phase_ix() != phase_ix;
+        if changed_phase || matches!(mv, Move::SetPhase(_)) {
+            (UiResponse::Redraw, None)
+        } else {
+            (UiResponse::None, None)
+        }
+    }
+
+    fn hex_actions(
+        &self,
+        assets: &Assets,
+        addr: HexAddress,
+    ) -> Vec<HexAction> {
+        if !assets.map.contains_hex(addr) {
+            return vec![];
+        }
+        let mut actions = vec![];
+        if ReplaceTile::maybe_upgrade(assets, addr).is_some() {
+            actions.push(HexAction::UpgradeTile);
+        }
+        actions.push(HexAction::PlaceTile);
+        if let Some(tile) = assets.map.tile_at(addr) {
+            actions.extend([
+                HexAction::RotateAntiClockwise,
+                HexAction::RotateClockwise,
+                HexAction::RemoveTile,
+            ]);
+            if !tile.token_spaces().is_empty() {
+                actions.push(HexAction::EditTokens);
+            }
+        }
+        if assets.map.can_reset_hex(addr) {
+            actions.push(HexAction::ResetHex);
+        }
+        actions.extend([HexAction::AddNote, HexAction::Inspect]);
+        actions
     }
 }
@@ -1,8 +1,10 @@
 //! Adds and removes tokens from the current tile.
 use cairo::Context;
 
+use n18game::sim::{place_token_entry, remove_token_entry};
+use n18game::Game;
 use n18hex::HexColour;
-use n18map::{HexAddress, Map, TokensTable};
+use n18map::{HexAddress, Map, TokenChange, TokensTable};
 use n18tile::TokenSpace;
 use n18token::Token;
 
@@ -13,6 +15,7 @@ pub struct EditTokens {
     active_hex: HexAddress,
     token_spaces: Vec<TokenSpace>,
     selected: usize,
+    hovered: Option<usize>,
     original_tokens: TokensTable,
 }
 
@@ -32,6 +35,7 @@ impl EditTokens {
             active_hex: addr,
             token_spaces,
             selected: 0,
+            hovered: None,
             original_tokens,
         })
     }
@@ -51,6 +55,40 @@ impl EditTokens {
         }
     }
 
+    /// Records each token that has been placed or removed in the journal,
+    /// so that these changes can be undone.
+    pub fn record_changes(&self, assets: &mut Assets) {
+        let tokens = match assets.map.hex_state(self.active_hex) {
+            Some(hs) => hs.tokens().clone(),
+            None => return,
+        };
+        let hex_name = assets.hex_name(self.active_hex);
+        let mut entries = vec![];
+        let mut changes = vec![];
+        for token_space in &self.token_spaces {
+            let before = self.original_tokens.get(token_space);
+            let after = tokens.get(token_space);
+            if before == after {
+                continue;
+            }
+            if let Some(token) = before {
+                let name = assets.map.try_token_name(token).unwrap_or("?");
+                entries.push(remove_token_entry(name, &hex_name))
+            }
+            if let Some(token) = after {
+                let name = assets.map.try_token_name(token).unwrap_or("?");
+                entries.push(place_token_entry(name, &hex_name))
+            }
+            changes.push(TokenChange {
+                addr: self.active_hex,
+                space: *token_space,
+                before: before.copied(),
+                after: after.copied(),
+            })
+        }
+        assets.record_token_changes(entries, changes)
+    }
+
     pub fn clear_token_space(&self, map: &mut Map) {
         let token_space = &self.token_spaces[self.selected];
         if let Some(hs) = map.hex_state_mut(self.active_hex) {
@@ -73,33 +111,140 @@ impl EditTokens {
         }
     }
 
+    /// Returns the index of the token space on the active tile that
+    /// contains the point `(x, y)`, if any.
+    fn token_space_at(
+        &self,
+        assets: &Assets,
+        x: f64,
+        y: f64,
+    ) -> Option<usize> {
+        let space = assets.map.token_space_at_point(
+            self.active_hex,
+            x,
+            y,
+            &assets.hex,
+        )?;
+        self.token_spaces.iter().position(|ts| *ts == space)
+    }
+
+    /// Selects the token space that contains the point `(x, y)`, and returns
+    /// `true` if there is such a token space.
+    pub fn select_token_space_at(
+        &mut self,
+        assets: &Assets,
+        x: f64,
+        y: f64,
+    ) -> bool {
+        if let Some(ix) = self.token_space_at(assets, x, y) {
+            self.selected = ix;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Highlights the token space that contains the point `(x, y)`, if any,
+    /// and returns `true` if the highlighted token space has changed.
+    pub fn hover_token_space_at(
+        &mut self,
+        assets: &Assets,
+        x: f64,
+        y: f64,
+    ) -> bool {
+        let hovered = self.token_space_at(assets, x, y);
+        let changed = hovered != self.hovered;
+        self.hovered = hovered;
+        changed
+    }
+
     pub fn select_previous_token(&mut self, assets: &mut Assets) {
-        let token_space = &self.token_spaces[self.selected];
-        // NOTE: we cannot borrow map.tokens() to get the next token,
-        // so we have to take a reference to the game's tokens.
-        let game = assets.games.active();
-        if let Some(hs) = assets.map.hex_state_mut(self.active_hex) {
-            let next: &Token = hs
-                .token_at(token_space)
+        self.select_token(assets, |game, token| {
+            token
                 .and_then(|t| game.prev_token(t))
-                .unwrap_or_else(|| game.last_token());
-            hs.set_token_at(token_space, *next);
-        }
+                .unwrap_or_else(|| game.last_token())
+        })
     }
 
     pub fn select_next_token(&mut self, assets: &mut Assets) {
-        let token_space = &self.token_spaces[self.selected];
+        self.select_token(assets, |game, token| {
+            token
+                .and_then(|t| game.next_token(t))
+                .unwrap_or_else(|| game.first_token())
+        })
+    }
+
+    /// Replaces the token in the selected token space, using `step` to cycle
+    /// through the game's tokens, and skipping the tokens of companies that
+    /// have already placed all of their tokens (see
+    /// [TierRules::token_count](n18game::TierRules::token_count)) or that
+    /// cannot place another token in this city (see
+    /// [Game::token_rule](n18game::Game::token_rule)).
+    ///
+    /// Tokens cannot be placed in token spaces that are not available in the
+    /// current game phase (see [Map::token_space_available]).
+    fn select_token<F>(&mut self, assets: &mut Assets, step: F)
+    where
+        F: for<'a> Fn(&'a dyn Game, Option<&Token>) -> &'a Token,
+    {
+        let token_space = self.token_spaces[self.selected];
+        if !assets
+            .map
+            .token_space_available(self.active_hex, &token_space)
+        {
+            return;
+        }
         // NOTE: we cannot borrow map.tokens() to get the next token,
         // so we have to take a reference to the game's tokens.
         let game = assets.games.active();
+        let current = assets
+            .map
+            .hex_state(self.active_hex)
+            .and_then(|hs| hs.token_at(&token_space))
+            .copied();
+        let mut next = *step(game, current.as_ref());
+        for _ in 0..game.companies().len() {
+            if Some(next) == current
+                || (has_tokens_remaining(assets, &next)
+                    && self.token_allowed(assets, &token_space, &next))
+            {
+                break;
+            }
+            next = *step(game, Some(&next));
+        }
         if let Some(hs) = assets.map.hex_state_mut(self.active_hex) {
-            let next: &Token = hs
-                .token_at(token_space)
-                .and_then(|t| game.next_token(t))
-                .unwrap_or_else(|| game.first_token());
-            hs.set_token_at(token_space, *next);
+            hs.set_token_at(&token_space, next);
         }
     }
+
+    /// Returns `true` if the game rules allow `token` to be placed in the
+    /// token space `space` on the active tile.
+    fn token_allowed(
+        &self,
+        assets: &Assets,
+        space: &TokenSpace,
+        token: &Token,
+    ) -> bool {
+        let rule = assets.games.active().token_rule();
+        assets
+            .map
+            .check_token_placement(self.active_hex, space, token, rule)
+            .is_ok()
+    }
+}
+
+/// Returns `true` unless the company that owns `token` has already placed
+/// all of its tokens on the map.
+fn has_tokens_remaining(assets: &Assets, token: &Token) -> bool {
+    let limit = assets
+        .map
+        .try_token_name(token)
+        .and_then(|abbrev| assets.games.active().company_rules(abbrev))
+        .and_then(|rules| rules.token_count);
+    match limit {
+        Some(count) => assets.map.find_placed_tokens(token).len() < count,
+        None => true,
+    }
 }
 
 impl UiState for EditTokens {
@@ -110,6 +255,18 @@ impl UiState for EditTokens {
 
         n18brush::draw_map(hex, ctx, &mut hex_iter);
 
+        // Highlight the token space under the mouse pointer.
+        if let Some(ix) = self.hovered.filter(|ix| *ix != self.selected) {
+            n18brush::highlight_token_space(
+                hex,
+                ctx,
+                map,
+                self.active_hex,
+                &self.token_spaces[ix],
+                (229, 153, 153).into(),
+            );
+        }
+
         // Highlight the active token space.
         let token_space = &self.token_spaces[self.selected];
         n18brush::highlight_token_space(
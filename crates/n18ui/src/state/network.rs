@@ -0,0 +1,108 @@
+//! Highlights the track that a company can reach from its tokens, so that
+//! players can see the extent of the company's network.
+
+use cairo::Context;
+
+use n18hex::Colour;
+use n18map::HexAddress;
+use n18route::Reach;
+use n18token::Token;
+
+use crate::status::{SegmentKind, StatusBar};
+use crate::{Assets, UiController, UiState};
+
+/// Fades every map hex that is not part of the selected company's network
+/// (see [n18route::reachable_from_tokens]), and highlights the track and
+/// cities that the company can reach.
+pub struct CompanyNetwork {
+    active_hex: HexAddress,
+    abbrev: String,
+    reach: Reach,
+    /// The colour in which this company's network is highlighted.
+    colour: Colour,
+}
+
+impl CompanyNetwork {
+    pub fn new(
+        assets: &Assets,
+        controller: &mut dyn UiController,
+        active_hex: HexAddress,
+        abbrev: String,
+        token: Token,
+    ) -> Self {
+        let graph = assets.map_graph();
+        let reach =
+            n18route::reachable_from_tokens(&assets.map, &graph, token);
+        let colour = assets
+            .games
+            .active()
+            .route_colours()
+            .remove(&abbrev)
+            .unwrap_or_else(|| assets.hex.theme.nth_highlight_colour(0));
+        let state = CompanyNetwork {
+            active_hex,
+            abbrev,
+            reach,
+            colour,
+        };
+        controller.set_window_title(&state.window_title(assets));
+        state
+    }
+
+    pub fn active_hex(&self) -> HexAddress {
+        self.active_hex
+    }
+
+    /// Returns the map hexes and track that the company can reach.
+    pub fn reach(&self) -> &Reach {
+        &self.reach
+    }
+
+    /// Returns the window title, which shows the company name and the
+    /// number of map hexes in its network.
+    pub fn window_title(&self, assets: &Assets) -> String {
+        assets.strings.format(
+            "{company} network: {hexes} hexes, {cities} cities",
+            &[
+                ("company", &self.abbrev),
+                ("hexes", &self.reach.hexes().len()),
+                ("cities", &self.reach.cities().count()),
+            ],
+        )
+    }
+}
+
+impl UiState for CompanyNetwork {
+    fn draw(&self, assets: &Assets, ctx: &Context) {
+        let hex = &assets.hex;
+        let map = &assets.map;
+        let mut hex_iter = map.hex_iter(hex, ctx);
+        n18brush::draw_map(hex, ctx, &mut hex_iter);
+
+        // Fade hexes that are not part of the company's network.
+        n18brush::highlight_hexes(
+            hex,
+            ctx,
+            &mut hex_iter,
+            |addr| self.reach.contains_hex(*addr),
+            None,
+        );
+
+        self.colour.apply_colour(ctx);
+        n18brush::highlight_reach(hex, ctx, map, &self.reach);
+
+        // Draw a border around each hex that contains the company's tokens.
+        n18brush::highlight_hexes(
+            hex,
+            ctx,
+            &mut hex_iter,
+            |addr| self.reach.token_hexes().contains(addr),
+            Some(self.colour),
+        );
+    }
+
+    /// Shows the size of the network, as per [CompanyNetwork::window_title].
+    fn status(&self, assets: &Assets, status: &mut StatusBar) {
+        status.set(SegmentKind::Message, self.window_title(assets));
+    }
+}
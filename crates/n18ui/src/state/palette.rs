@@ -0,0 +1,90 @@
+//! Searches for and runs an action that is available in the current state.
+use cairo::Context;
+use std::sync::mpsc::{Receiver, Sender};
+
+use crate::keymap::Command;
+use crate::lang::Strings;
+use crate::{
+    Action, Assets, Controller, PingDest, PingSender, State, UiAction,
+    UiController, UiResponse, UiState,
+};
+
+/// Prompts the user to select an action from the command palette, and then
+/// returns to the previous state and runs the selected action.
+pub struct CommandPalette {
+    previous: Option<Box<State>>,
+    commands: Vec<Command>,
+    receiver: Receiver<Option<usize>>,
+    sender: Sender<UiAction>,
+    ping_tx: PingSender,
+}
+
+impl CommandPalette {
+    pub fn new(
+        controller: &mut Controller,
+        sender: &Sender<UiAction>,
+        previous: State,
+        commands: Vec<Command>,
+        strings: &Strings,
+    ) -> Self {
+        let labels: Vec<String> = commands
+            .iter()
+            .map(|command| command.label(strings))
+            .collect();
+        let labels: Vec<&str> =
+            labels.iter().map(|label| label.as_str()).collect();
+        let (tx, receiver) = std::sync::mpsc::channel();
+        let ping_tx = controller.ping_tx();
+        let state_ping_tx = ping_tx.clone();
+        let title = strings.tr("Run a command");
+        controller.search_index(title, &labels, move |ix_opt| {
+            tx.send(ix_opt).unwrap();
+            state_ping_tx.send_ping(PingDest::State).unwrap();
+        });
+        CommandPalette {
+            previous: Some(Box::new(previous)),
+            commands,
+            receiver,
+            sender: sender.clone(),
+            ping_tx,
+        }
+    }
+
+    /// Returns the state that will be restored when the user has selected
+    /// a command.
+    pub fn previous(&self) -> Option<&State> {
+        self.previous.as_deref()
+    }
+}
+
+impl UiState for CommandPalette {
+    fn draw(&self, assets: &Assets, ctx: &Context) {
+        if let Some(state) = &self.previous {
+            let state: &State = state;
+            state.as_ref().draw(assets, ctx)
+        }
+    }
+
+    fn ping(
+        &mut self,
+        _assets: &mut Assets,
+        _controller: &mut Controller,
+    ) -> (UiResponse, Option<State>) {
+        let Ok(ix_opt) = self.receiver.try_recv() else {
+            return (UiResponse::None, None);
+        };
+        let previous = match self.previous.take() {
+            Some(state) => *state,
+            None => return (UiResponse::None, None),
+        };
+        // Return to the previous state before running the selected action,
+        // since the action may depend on (and replace) that state.
+        if let Some(command) = ix_opt.map(|ix| self.commands[ix]) {
+            self.sender
+                .send(Action::RunCommand(command).into())
+                .unwrap();
+            self.ping_tx.send_ping(PingDest::TopLevel).unwrap();
+        }
+        (UiResponse::Redraw, Some(previous))
+    }
+}
@@ -15,24 +15,24 @@
 //!
 //! # Upgrading tiles
 //!
-//! This mode allows the user to replace a tile with any available tile, and
-//! does not enforce any criteria for upgrade tiles.
+//! This mode allows the user to replace a tile with any legal placement (see
+//! [Map::legal_placements]), and does not enforce any criteria for the track
+//! connections of upgrade tiles ([UpgradeRule::Any]).
 //! Note that 18xx games may involve one of three
 //! [different criteria](https://www.railsonboards.com/2020/12/26/permissive-restrictive-semi-restrictive-what-it-means/):
 //! permissive, semi-restrictive, and restrictive.
 //!
-//! To support any or all of these criteria, this mode would need to record
-//! the current tile's connections (including tokens) and only accept a
-//! replacement tile if it satisfies all of these connections with its chosen
-//! rotation.
+//! To support any or all of these criteria, this mode would need to only
+//! accept a replacement tile if its chosen rotation is one of the legal
+//! rotations returned by [Map::legal_placements].
 
 use cairo::Context;
 use log::info;
 
 use n18hex::RotateCW;
-use n18map::{HexAddress, Map};
-use n18tile::Tile;
+use n18map::{HexAddress, Map, PlacementRules, UpgradeRule};
 
+use crate::status::{SegmentKind, StatusBar};
 use crate::{Assets, UiState};
 
 /// Replacing one tile with another.
@@ -80,59 +80,31 @@ impl ReplaceTile {
     }
 
     pub fn maybe_upgrade(assets: &Assets, addr: HexAddress) -> Option<Self> {
-        if let Some(tile) = assets.map.tile_at(addr) {
-            Self::maybe_upgrade_tile(assets, addr, tile)
-        } else {
-            Self::maybe_place_on_empty(assets, addr)
-        }
-    }
-
-    fn maybe_upgrade_tile(
-        assets: &Assets,
-        addr: HexAddress,
-        tile: &Tile,
-    ) -> Option<Self> {
-        let candidates: Vec<usize> = assets
-            .map
-            .available_tiles_iter()
-            .enumerate()
-            .filter(|(_ix, t)| {
-                assets.map.can_upgrade_to(addr, t) && tile.can_upgrade_to(t)
-            })
-            .map(|(ix, _t)| ix)
+        let map = &assets.map;
+        let rules = PlacementRules::default().with_upgrades(UpgradeRule::Any);
+        let candidates: Vec<usize> = map
+            .legal_placements(addr, &rules)
+            .iter()
+            .filter_map(|(name, _rotations)| map.catalogue().index_of(name))
             .collect();
         if candidates.is_empty() {
-            info!("No candidates for tile {} at {}", tile.name, addr);
+            match map.tile_at(addr) {
+                Some(tile) => {
+                    info!("No candidates for tile {} at {}", tile.name, addr)
+                }
+                None => info!("No candidates for empty hex {}", addr),
+            }
             None
         } else {
             let mut state = Self::with_candidates(addr, candidates);
             // NOTE: record the current tile's rotation.
-            if let Some(hs) = assets.map.hex_state(addr) {
+            if let Some(hs) = map.hex_state(addr) {
                 state.original_rotation = *hs.rotation();
             }
             Some(state)
         }
     }
 
-    fn maybe_place_on_empty(
-        assets: &Assets,
-        addr: HexAddress,
-    ) -> Option<Self> {
-        let candidates: Vec<usize> = assets
-            .map
-            .available_tiles_iter()
-            .enumerate()
-            .filter(|(_ix, t)| assets.map.can_place_on_empty(addr, t))
-            .map(|(ix, _t)| ix)
-            .collect();
-        if candidates.is_empty() {
-            info!("No candidates for empty hex {}", addr);
-            None
-        } else {
-            Some(Self::with_candidates(addr, candidates))
-        }
-    }
-
     pub fn active_hex(&self) -> HexAddress {
         self.active_hex
     }
@@ -141,19 +113,30 @@ impl ReplaceTile {
         self.original_rotation + self.extra_rotation
     }
 
-    pub fn place_candidate(&self, map: &mut Map) -> bool {
+    /// Replaces the original tile (if any) with the selected candidate, and
+    /// records this move in the journal.
+    ///
+    /// When planning tiles (see [Assets::planning]), the candidate is added
+    /// to the map's planning layer instead, and the map is not modified.
+    pub fn place_candidate(&self, assets: &mut Assets) -> bool {
         if self.show_original {
             false
         } else {
             // Replace the original tile with the current selection.
             let tile_ix = self.candidates[self.selected];
-            let tile_name = map.nth_tile(tile_ix).name.clone();
+            let tile_name = assets.map.nth_tile(tile_ix).name.clone();
             // NOTE: the true rotation of the candidate is the sum of these
             // two rotations, because candidates are drawn with respect to the
             // original tile's rotation (if any).
             let tile_rotation = self.net_rotation();
-            map.place_tile(self.active_hex, &tile_name, tile_rotation);
-            true
+            if assets.planning {
+                return assets.map.plan_tile(
+                    self.active_hex,
+                    &tile_name,
+                    tile_rotation,
+                );
+            }
+            assets.place_tile(self.active_hex, &tile_name, tile_rotation)
         }
     }
 
@@ -267,4 +250,25 @@ impl UiState for ReplaceTile {
             border,
         );
     }
+
+    /// Shows the selected candidate tile, unless the original tile is
+    /// being shown.
+    fn status(&self, assets: &Assets, status: &mut StatusBar) {
+        if self.show_original {
+            return;
+        }
+        let tile_ix = self.candidates[self.selected];
+        let tile = assets.map.nth_tile(tile_ix);
+        status.set(
+            SegmentKind::Tile,
+            assets.strings.format(
+                "{tile} ({n} of {count})",
+                &[
+                    ("tile", &tile.name),
+                    ("n", &(self.selected + 1)),
+                    ("count", &self.candidates.len()),
+                ],
+            ),
+        );
+    }
 }
@@ -0,0 +1,176 @@
+//! Draws the route graph for a company, to help diagnose why an expected
+//! route was not found.
+
+use cairo::Context;
+use std::sync::mpsc::Receiver;
+
+use n18hex::Colour;
+use n18map::HexAddress;
+use n18route::{MapGraph, PathCounts, Trains};
+use n18token::Token;
+
+use crate::status::{SegmentKind, StatusBar};
+use crate::{
+    Assets, Controller, PingDest, State, UiController, UiResponse, UiState,
+};
+
+/// Draws the map connectivity (see [MapGraph::nodes] and [MapGraph::edges])
+/// on top of the map, and labels each city from which the selected company
+/// can start a path with the number of paths that pass through it.
+pub struct RouteGraph {
+    active_hex: HexAddress,
+    abbrev: String,
+    graph: MapGraph,
+    receiver: Receiver<Vec<PathCounts>>,
+    /// The number of paths that pass through each start city, once they
+    /// have been counted.
+    counts: Option<Vec<PathCounts>>,
+}
+
+impl RouteGraph {
+    pub fn new(
+        assets: &Assets,
+        controller: &mut dyn UiController,
+        active_hex: HexAddress,
+        abbrev: String,
+        token: Token,
+        trains: Trains,
+    ) -> Self {
+        let graph = assets.map_graph();
+        let game = assets.games.active();
+        let criteria = game.path_criteria(&assets.map, token, &trains);
+
+        // Count the paths in a separate thread, because this enumerates the
+        // paths from every start city, and ping this state when the counts
+        // are available.
+        let map = assets.map.clone();
+        let thread_graph = graph.clone();
+        let ping_tx = controller.ping_tx();
+        let (sender, receiver) = std::sync::mpsc::sync_channel(1);
+        std::thread::spawn(move || {
+            let counts =
+                n18route::path_counts_with(&map, &thread_graph, &criteria);
+            sender.send(counts).unwrap();
+            ping_tx.send_ping(PingDest::State).unwrap();
+        });
+
+        let state = RouteGraph {
+            active_hex,
+            abbrev,
+            graph,
+            receiver,
+            counts: None,
+        };
+        controller.set_window_title(&state.window_title(assets));
+        state
+    }
+
+    pub fn active_hex(&self) -> HexAddress {
+        self.active_hex
+    }
+
+    /// Returns the map connectivity that is drawn on top of the map.
+    pub fn graph(&self) -> &MapGraph {
+        &self.graph
+    }
+
+    /// Returns the number of paths that pass through each start city, or
+    /// `None` if the paths have not yet been counted.
+    pub fn counts(&self) -> Option<&[PathCounts]> {
+        self.counts.as_deref()
+    }
+
+    /// Returns the window title, which shows the company name and the total
+    /// number of paths.
+    pub fn window_title(&self, assets: &Assets) -> String {
+        let Some(counts) = &self.counts else {
+            return assets.strings.format(
+                "{company}: counting paths ...",
+                &[("company", &self.abbrev)],
+            );
+        };
+        let total: usize = counts.iter().map(|count| count.total()).sum();
+        assets.strings.format(
+            "{company}: {paths} paths from {cities} cities",
+            &[
+                ("company", &self.abbrev),
+                ("paths", &total),
+                ("cities", &counts.len()),
+            ],
+        )
+    }
+
+    /// Returns the label for a start city, which shows the number of paths
+    /// that pass through it and the largest number of stops on any of these
+    /// paths.
+    pub fn label(&self, assets: &Assets, count: &PathCounts) -> String {
+        match count.longest() {
+            Some(longest) => assets.strings.format(
+                "{paths} paths, {stops} stops",
+                &[("paths", &count.total()), ("stops", &longest)],
+            ),
+            None => assets.strings.tr("No paths").to_string(),
+        }
+    }
+}
+
+impl UiState for RouteGraph {
+    fn draw(&self, assets: &Assets, ctx: &Context) {
+        let hex = &assets.hex;
+        let map = &assets.map;
+        let mut hex_iter = map.hex_iter(hex, ctx);
+        n18brush::draw_map(hex, ctx, &mut hex_iter);
+
+        // NOTE: fade out the entire map, so that the graph stands out.
+        let fill = Colour::WHITE.with_alpha(159);
+        fill.apply_colour(ctx);
+        ctx.paint().unwrap();
+
+        let colour = Colour::from((31, 119, 180));
+        n18brush::draw_route_graph(hex, ctx, map, &self.graph, colour);
+
+        // Label each start city with the number of paths that pass through
+        // it, and the largest number of stops on any of these paths.
+        let Some(counts) = &self.counts else {
+            return;
+        };
+        let gap = 0.02 * hex.max_d;
+        let mut layout = n18brush::badges::BadgeLayout::new(gap);
+        for count in counts {
+            let anchor =
+                n18brush::badges::hex_centre_coord(hex, ctx, map, count.addr);
+            let text = self.label(assets, count);
+            let colour = if count.longest().is_some() {
+                colour
+            } else {
+                Colour::from((215, 48, 39))
+            };
+            n18brush::badges::place_badge(
+                hex,
+                ctx,
+                &mut layout,
+                &text,
+                anchor,
+                colour,
+            );
+        }
+    }
+
+    fn ping(
+        &mut self,
+        assets: &mut Assets,
+        controller: &mut Controller,
+    ) -> (UiResponse, Option<State>) {
+        let Ok(counts) = self.receiver.try_recv() else {
+            return (UiResponse::None, None);
+        };
+        self.counts = Some(counts);
+        controller.set_window_title(&self.window_title(assets));
+        (UiResponse::Redraw, None)
+    }
+
+    /// Shows the number of paths, as per [RouteGraph::window_title].
+    fn status(&self, assets: &Assets, status: &mut StatusBar) {
+        status.set(SegmentKind::Message, self.window_title(assets));
+    }
+}
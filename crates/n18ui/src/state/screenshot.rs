@@ -0,0 +1,91 @@
+//! Selects a region of the map to save as an image, or to copy to the
+//! clipboard.
+use cairo::Context;
+
+use n18map::HexAddress;
+
+use crate::status::{SegmentKind, StatusBar};
+use crate::{Assets, UiState};
+
+/// The scale factors that the user can choose from, in order.
+pub const SCALES: [f64; 4] = [1.0, 2.0, 3.0, 4.0];
+
+/// Selecting a rectangular region of the map by dragging the mouse, which is
+/// then saved as an image (or copied to the clipboard) at the chosen scale.
+pub struct Screenshot {
+    active_hex: HexAddress,
+    scale: f64,
+    drag_rect: Option<(f64, f64, f64, f64)>,
+    copy: bool,
+}
+
+impl Screenshot {
+    pub fn new(active_hex: HexAddress) -> Self {
+        Screenshot {
+            active_hex,
+            scale: SCALES[0],
+            drag_rect: None,
+            copy: false,
+        }
+    }
+
+    pub fn active_hex(&self) -> HexAddress {
+        self.active_hex
+    }
+
+    /// Returns the scale at which the selected region will be saved.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Sets the scale at which the selected region will be saved.
+    pub fn set_scale(&mut self, scale: f64) {
+        self.scale = scale
+    }
+
+    /// Returns `true` if the selected region will be copied to the
+    /// clipboard, rather than saved as an image.
+    pub fn copies(&self) -> bool {
+        self.copy
+    }
+
+    /// Switches between saving the selected region as an image and copying
+    /// it to the clipboard.
+    pub fn toggle_copy(&mut self) {
+        self.copy = !self.copy
+    }
+
+    /// Sets the area that the user is selecting by dragging the mouse.
+    pub fn set_drag_rect(&mut self, rect: Option<(f64, f64, f64, f64)>) {
+        self.drag_rect = rect
+    }
+
+    /// Returns the window title for this state, which shows the current
+    /// scale and whether the region will be saved or copied.
+    pub fn window_title(&self, assets: &Assets) -> String {
+        let text = if self.copy {
+            "Copy map region: drag to select (scale {scale}x)"
+        } else {
+            "Save map region: drag to select (scale {scale}x)"
+        };
+        assets.strings.format(text, &[("scale", &self.scale)])
+    }
+}
+
+impl UiState for Screenshot {
+    fn draw(&self, assets: &Assets, ctx: &Context) {
+        let hex = &assets.hex;
+        let mut hex_iter = assets.map.hex_iter(hex, ctx);
+
+        n18brush::draw_map(hex, ctx, &mut hex_iter);
+
+        if let Some(rect) = self.drag_rect {
+            let colour = n18hex::Colour::from((0, 76, 179));
+            n18brush::draw_selection_rect(hex, ctx, rect, colour);
+        }
+    }
+
+    fn status(&self, assets: &Assets, status: &mut StatusBar) {
+        status.set(SegmentKind::Message, self.window_title(assets));
+    }
+}
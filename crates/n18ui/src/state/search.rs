@@ -1,21 +1,47 @@
-//! Searches for the best routes that a company can operate.
+//! Searches for the best routes that a company can operate, or for the
+//! best token spaces in which a company could place its next token.
 
 use cairo::Context;
+use std::collections::BTreeMap;
 use std::sync::mpsc::Receiver;
 
-use n18game::Company;
-use n18map::HexAddress;
+use n18game::{colours, Company};
+use n18hex::Colour;
+use n18map::{HexAddress, Map};
 use n18route::{Routes, Trains};
 use n18token::Token;
 
+use crate::panel::RouteSummary;
+use crate::state::network::CompanyNetwork;
+use crate::state::route_graph::RouteGraph;
+use crate::state::token_spaces::Rank;
+use crate::status::{SegmentKind, StatusBar};
 use crate::{
     Assets, Controller, PingDest, State, UiController, UiResponse, UiState,
 };
 
+/// What to search for, once the user has selected a company and its trains.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Goal {
+    /// Find the routes that earn the most revenue.
+    Routes,
+    /// Rank the token spaces in which the company could place a token, by
+    /// the revenue that its trains could earn (see
+    /// [rank_token_spaces](n18game::analysis::rank_token_spaces)).
+    TokenSpaces,
+    /// Draw the route graph and count the paths from each start city (see
+    /// [RouteGraph](crate::state::route_graph::RouteGraph)).
+    RouteGraph,
+    /// Highlight the track that the company can reach from its tokens (see
+    /// [CompanyNetwork]), which does not require the user to select trains.
+    Network,
+}
+
 /// Prompts the user to select a company that has at least one token placed on
 /// the map.
 pub struct SelectCompany {
     active_hex: HexAddress,
+    goal: Goal,
     receiver: Receiver<Option<String>>,
 }
 
@@ -25,16 +51,31 @@ impl SelectCompany {
         controller: &mut Controller,
         active_hex: HexAddress,
     ) -> Option<Self> {
-        let companies = valid_companies(assets);
+        Self::with_goal(assets, controller, active_hex, Goal::Routes)
+    }
+
+    /// Prompts the user to select a company and then its trains, and then
+    /// searches for the specified goal.
+    ///
+    /// When ranking token spaces, the user can select any company, including
+    /// companies that have not placed any tokens.
+    pub fn with_goal(
+        assets: &Assets,
+        controller: &mut Controller,
+        active_hex: HexAddress,
+        goal: Goal,
+    ) -> Option<Self> {
+        let companies = valid_companies(assets, goal);
         if companies.is_empty() {
             return None;
         }
+        let labels = company_labels(assets, &companies);
         let company_names: Vec<&str> =
-            companies.iter().map(|c| c.full_name.as_str()).collect();
+            labels.iter().map(|label| label.as_str()).collect();
         let (sender, receiver) = std::sync::mpsc::channel();
         let ping_tx = controller.ping_tx();
         controller.select_string(
-            "Select a company",
+            assets.strings.tr("Select a company"),
             &company_names,
             move |name_opt| {
                 sender.send(name_opt).unwrap();
@@ -43,9 +84,14 @@ impl SelectCompany {
         );
         Some(SelectCompany {
             active_hex,
+            goal,
             receiver,
         })
     }
+
+    pub fn active_hex(&self) -> HexAddress {
+        self.active_hex
+    }
 }
 
 impl UiState for SelectCompany {
@@ -61,24 +107,40 @@ impl UiState for SelectCompany {
         assets: &mut Assets,
         controller: &mut Controller,
     ) -> (UiResponse, Option<State>) {
-        let name_opt = self.receiver.recv().unwrap();
+        let Ok(name_opt) = self.receiver.try_recv() else {
+            return (UiResponse::None, None);
+        };
         if let Some(chosen_name) = name_opt {
-            let companies = valid_companies(assets);
-            let abbrev_opt = companies.iter().find_map(|c| {
-                if c.full_name == chosen_name {
-                    Some(c.abbrev.clone())
-                } else {
-                    None
-                }
-            });
+            let companies = valid_companies(assets, self.goal);
+            let labels = company_labels(assets, &companies);
+            let abbrev_opt =
+                companies.iter().zip(labels).find_map(|(c, label)| {
+                    if label == chosen_name {
+                        Some(c.abbrev.clone())
+                    } else {
+                        None
+                    }
+                });
             if let Some(abbrev) = abbrev_opt {
                 if let Some(token) = assets.map.try_token(&abbrev) {
+                    if self.goal == Goal::Network {
+                        let state =
+                            State::CompanyNetwork(CompanyNetwork::new(
+                                assets,
+                                controller,
+                                self.active_hex,
+                                abbrev,
+                                token,
+                            ));
+                        return (UiResponse::Redraw, Some(state));
+                    }
                     let b = State::FindRoutesTrains(SelectTrains::new(
                         assets,
                         controller,
                         self.active_hex,
                         abbrev,
                         token,
+                        self.goal,
                     ));
                     return (UiResponse::Redraw, Some(b));
                 }
@@ -99,6 +161,7 @@ pub struct SelectTrains {
     receiver: Receiver<Option<(Trains, Vec<bool>)>>,
     abbrev: String,
     token: Token,
+    goal: Goal,
 }
 
 impl SelectTrains {
@@ -108,6 +171,7 @@ impl SelectTrains {
         active_hex: HexAddress,
         abbrev: String,
         token: Token,
+        goal: Goal,
     ) -> Self {
         let (sender, receiver) = std::sync::mpsc::channel();
         let ping_tx = controller.ping_tx();
@@ -124,8 +188,13 @@ impl SelectTrains {
             receiver,
             abbrev,
             token,
+            goal,
         }
     }
+
+    pub fn active_hex(&self) -> HexAddress {
+        self.active_hex
+    }
 }
 
 impl UiState for SelectTrains {
@@ -141,8 +210,68 @@ impl UiState for SelectTrains {
         assets: &mut Assets,
         controller: &mut Controller,
     ) -> (UiResponse, Option<State>) {
-        let trains_opt = self.receiver.recv().unwrap();
+        let Ok(trains_opt) = self.receiver.try_recv() else {
+            return (UiResponse::None, None);
+        };
         if let Some((trains, bonuses)) = trains_opt {
+            // Check that the company can operate this many trains.
+            let game = assets.games.active();
+            let limit = game
+                .train_limit(&self.abbrev)
+                .filter(|limit| trains.train_count() > *limit);
+            if let Some(limit) = limit {
+                let tier_limit = game
+                    .company_rules(&self.abbrev)
+                    .and_then(|rules| rules.train_limit);
+                let message = if tier_limit == Some(limit) {
+                    let tier = game.company(&self.abbrev).tier;
+                    assets.strings.format(
+                        "{company} is a {tier} company and can operate at \
+                         most {limit} trains",
+                        &[
+                            ("company", &self.abbrev),
+                            ("tier", &tier),
+                            ("limit", &limit),
+                        ],
+                    )
+                } else {
+                    assets.strings.format(
+                        "{company} can operate at most {limit} trains in \
+                         phase {phase}",
+                        &[
+                            ("company", &self.abbrev),
+                            ("limit", &limit),
+                            ("phase", &game.current_phase_name()),
+                        ],
+                    )
+                };
+                let title = assets.strings.tr("Too many trains");
+                controller.show_error(title, &message);
+                return (UiResponse::Redraw, Some(self.active_hex.into()));
+            }
+            if self.goal == Goal::TokenSpaces {
+                let state = State::RankTokenSpaces(Rank::new(
+                    assets,
+                    controller,
+                    self.active_hex,
+                    self.abbrev.clone(),
+                    self.token,
+                    trains,
+                    bonuses,
+                ));
+                return (UiResponse::Redraw, Some(state));
+            }
+            if self.goal == Goal::RouteGraph {
+                let state = State::RouteGraph(RouteGraph::new(
+                    assets,
+                    controller,
+                    self.active_hex,
+                    self.abbrev.clone(),
+                    self.token,
+                    trains,
+                ));
+                return (UiResponse::Redraw, Some(state));
+            }
             let state = State::FindRoutesSearch(Search::new(
                 assets,
                 controller,
@@ -160,11 +289,194 @@ impl UiState for SelectTrains {
     }
 }
 
+/// Prompts the user to select a train, in order to find the optimal route
+/// that this train could operate from the city on the active hex, for a
+/// company that has no tokens on the map.
+///
+/// This can help to decide where to place a token.
+pub struct SelectOpenTrain {
+    active_hex: HexAddress,
+    receiver: Receiver<Option<usize>>,
+}
+
+impl SelectOpenTrain {
+    /// Returns `None` if there is no city on the active hex.
+    pub fn new(
+        assets: &Assets,
+        controller: &mut Controller,
+        active_hex: HexAddress,
+    ) -> Option<Self> {
+        let has_city = assets
+            .map
+            .tile_at(active_hex)
+            .is_some_and(|tile| !tile.cities().is_empty());
+        if !has_city {
+            return None;
+        }
+        let game = assets.games.active();
+        let train_names = game.train_names();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let ping_tx = controller.ping_tx();
+        let title = assets.strings.tr("Select a train");
+        controller.search_index(title, &train_names, move |ix| {
+            sender.send(ix).unwrap();
+            ping_tx.send_ping(PingDest::State).unwrap();
+        });
+        Some(SelectOpenTrain {
+            active_hex,
+            receiver,
+        })
+    }
+
+    pub fn active_hex(&self) -> HexAddress {
+        self.active_hex
+    }
+}
+
+impl UiState for SelectOpenTrain {
+    fn draw(&self, assets: &Assets, ctx: &Context) {
+        let hex = &assets.hex;
+        let map = &assets.map;
+        let mut hex_iter = map.hex_iter(hex, ctx);
+        n18brush::draw_map(hex, ctx, &mut hex_iter);
+    }
+
+    fn ping(
+        &mut self,
+        assets: &mut Assets,
+        controller: &mut Controller,
+    ) -> (UiResponse, Option<State>) {
+        let Ok(ix_opt) = self.receiver.try_recv() else {
+            return (UiResponse::None, None);
+        };
+        let game = assets.games.active();
+        let train = ix_opt.and_then(|ix| game.train_types().get(ix).copied());
+        let (Some(train), Some(tile)) =
+            (train, assets.map.tile_at(self.active_hex))
+        else {
+            // Return to the default state.
+            return (UiResponse::Redraw, Some(self.active_hex.into()));
+        };
+
+        // Search from each city on the active hex, and keep the routes that
+        // earn the most revenue.
+        let trains: Trains = vec![*train].into();
+        let search_fns: Vec<SearchFn> = (0..tile.cities().len())
+            .map(|city_ix| {
+                game.open_routes_closure(
+                    assets.map.clone(),
+                    self.active_hex,
+                    city_ix,
+                    trains.clone(),
+                )
+            })
+            .collect();
+        let search_fn: SearchFn = Box::new(move || {
+            search_fns
+                .into_iter()
+                .filter_map(|search_fn| search_fn())
+                .max_by_key(|routes| routes.net_revenue)
+        });
+        let label = assets.strings.format(
+            "Open route from {hex}",
+            &[("hex", &assets.hex_name(self.active_hex))],
+        );
+        let state = State::FindRoutesSearch(Search::with_search_fn(
+            assets,
+            controller,
+            self.active_hex,
+            label,
+            search_fn,
+        ));
+        (UiResponse::Redraw, Some(state))
+    }
+}
+
+/// The closure that searches for the optimal routes, which is run in a
+/// separate thread.
+pub type SearchFn = Box<dyn FnOnce() -> Option<Routes> + Send>;
+
+/// The inputs that determine the optimal routes for a company.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct CacheKey {
+    revision: u64,
+    trains: Trains,
+    bonuses: Vec<bool>,
+}
+
+/// Records the most recent optimal routes for each company, so that these
+/// routes can be shown immediately if the company, its trains, its bonuses,
+/// and the map have not changed.
+///
+/// Each entry is identified by the [map revision](Map::revision) at the time
+/// of the search, and so becomes stale as soon as the map is modified.
+#[derive(Clone, Debug, Default)]
+pub struct RouteCache {
+    entries: BTreeMap<String, (CacheKey, Option<Routes>)>,
+}
+
+impl RouteCache {
+    /// Returns the cached routes for a company, if the company searched for
+    /// routes with these trains and bonuses since the map was last modified.
+    pub fn get(
+        &self,
+        map: &Map,
+        abbrev: &str,
+        trains: &Trains,
+        bonuses: &[bool],
+    ) -> Option<&Option<Routes>> {
+        let key = CacheKey {
+            revision: map.revision(),
+            trains: trains.clone(),
+            bonuses: bonuses.to_vec(),
+        };
+        self.entries
+            .get(abbrev)
+            .filter(|(cached_key, _)| *cached_key == key)
+            .map(|(_, routes)| routes)
+    }
+
+    /// Records the optimal routes for a company, replacing any previous
+    /// routes for this company.
+    pub fn insert(
+        &mut self,
+        map: &Map,
+        abbrev: &str,
+        trains: Trains,
+        bonuses: Vec<bool>,
+        routes: Option<Routes>,
+    ) {
+        let key = CacheKey {
+            revision: map.revision(),
+            trains,
+            bonuses,
+        };
+        self.entries.insert(abbrev.to_string(), (key, routes));
+    }
+
+    /// Returns the number of companies for which routes are cached.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no routes are cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all cached routes.
+    pub fn clear(&mut self) {
+        self.entries.clear()
+    }
+}
+
 /// Searches for the optimal routes for the selected company.
 pub struct Search {
     active_hex: HexAddress,
     abbrev: String,
-    receiver: std::sync::mpsc::Receiver<Option<(Token, Routes)>>,
+    receiver: std::sync::mpsc::Receiver<Option<Routes>>,
+    /// The key under which the routes are cached, once they are found.
+    cache_key: Option<CacheKey>,
 }
 
 impl Search {
@@ -178,7 +490,47 @@ impl Search {
         trains: Trains,
         bonuses: Vec<bool>,
     ) -> Self {
-        let new_title = format!("{}: searching ...", abbrev);
+        // NOTE: we also need to clone the map, because the thread cannot take
+        // a reference unless we somehow define an appropriate lifetime.
+        let cached = assets
+            .route_cache
+            .get(&assets.map, &abbrev, &trains, &bonuses)
+            .cloned();
+        let cache_key = CacheKey {
+            revision: assets.map.revision(),
+            trains: trains.clone(),
+            bonuses: bonuses.clone(),
+        };
+        let search_fn: SearchFn = if let Some(routes) = cached {
+            // The map has not changed, so reuse the previous routes.
+            Box::new(move || routes)
+        } else {
+            let map = assets.map.clone();
+            let active_game = assets.games.active();
+            let cache = assets.path_cache.clone();
+            active_game.best_routes_closure_cached(
+                map, token, trains, bonuses, cache,
+            )
+        };
+        let mut search = Search::with_search_fn(
+            assets, controller, active_hex, abbrev, search_fn,
+        );
+        search.cache_key = Some(cache_key);
+        search
+    }
+
+    /// Searches for the optimal routes with `search_fn`, and labels these
+    /// routes with `abbrev` (e.g., the company name).
+    pub fn with_search_fn(
+        assets: &Assets,
+        controller: &mut dyn UiController,
+        active_hex: HexAddress,
+        abbrev: String,
+        search_fn: SearchFn,
+    ) -> Self {
+        let new_title = assets
+            .strings
+            .format("{company}: searching ...", &[("company", &abbrev)]);
         controller.set_window_title(&new_title);
 
         // Search for the best routes in a separate thread, to avoid making
@@ -186,19 +538,13 @@ impl Search {
         // route-finding has finished.
         let ping_tx = controller.ping_tx();
 
-        // NOTE: we also need to clone the map, because the thread cannot take
-        // a reference unless we somehow define an appropriate lifetime.
-        let map = assets.map.clone();
         // Create a channel from which to retrieve the best routes.
         let (sender, receiver) = std::sync::mpsc::sync_channel(1);
 
         // Spawn the new thread.
-        let active_game = assets.games.active();
-        let search_fn =
-            active_game.best_routes_closure(map, token, trains, bonuses);
         std::thread::spawn(move || {
             // Find the best routes.
-            let best_routes = search_fn().map(|routes| (token, routes));
+            let best_routes = search_fn();
             // Send the best routes back to this state.
             sender.send(best_routes).unwrap();
             // Ping this state so that it can retrieve the best routes.
@@ -209,8 +555,13 @@ impl Search {
             active_hex,
             abbrev,
             receiver,
+            cache_key: None,
         }
     }
+
+    pub fn active_hex(&self) -> HexAddress {
+        self.active_hex
+    }
 }
 
 impl UiState for Search {
@@ -231,7 +582,35 @@ impl UiState for Search {
         assets: &mut Assets,
         controller: &mut Controller,
     ) -> (UiResponse, Option<State>) {
-        let best_routes = self.receiver.recv().unwrap();
+        let Ok(best_routes) = self.receiver.try_recv() else {
+            return (UiResponse::None, None);
+        };
+        // Cache these routes, unless the map was modified during the search.
+        if let Some(key) = self.cache_key.take() {
+            if key.revision == assets.map.revision() {
+                assets.route_cache.insert(
+                    &assets.map,
+                    &self.abbrev,
+                    key.trains,
+                    key.bonuses,
+                    best_routes.clone(),
+                );
+            }
+        }
+        if let Some(routes) = &best_routes {
+            assets.last_revenue =
+                Some((self.abbrev.clone(), routes.net_revenue));
+        }
+        // Record the revenue if this company is operating.
+        let game = assets.games.active();
+        let trains: Vec<String> = best_routes
+            .iter()
+            .flat_map(|routes| &routes.train_routes)
+            .filter_map(|tr| game.train_name(&tr.train))
+            .map(|name| name.to_string())
+            .collect();
+        let revenue = best_routes.as_ref().map_or(0, |r| r.net_revenue);
+        assets.record_operating_revenue(&self.abbrev, &trains, revenue);
         let state = State::FindRoutesFound(Found::new(
             assets,
             controller,
@@ -241,6 +620,16 @@ impl UiState for Search {
         ));
         (UiResponse::Redraw, Some(state))
     }
+
+    fn status(&self, assets: &Assets, status: &mut StatusBar) {
+        status.set(
+            SegmentKind::Message,
+            assets.strings.format(
+                "{company}: searching ...",
+                &[("company", &self.abbrev)],
+            ),
+        );
+    }
 }
 
 /// Displays the optimal routes for the selected company, once they have been
@@ -248,8 +637,12 @@ impl UiState for Search {
 pub struct Found {
     active_hex: HexAddress,
     abbrev: String,
-    best_routes: Option<(Token, Routes)>,
+    best_routes: Option<Routes>,
     active_route: Option<usize>,
+    /// The visit that is highlighted when stepping through the active route.
+    active_visit: Option<usize>,
+    /// The colour in which this company's routes are highlighted.
+    colour: Colour,
 }
 
 impl Found {
@@ -258,13 +651,21 @@ impl Found {
         controller: &mut dyn UiController,
         active_hex: HexAddress,
         abbrev: String,
-        best_routes: Option<(Token, Routes)>,
+        best_routes: Option<Routes>,
     ) -> Self {
+        let colour = assets
+            .games
+            .active()
+            .route_colours()
+            .remove(&abbrev)
+            .unwrap_or_else(|| assets.hex.theme.nth_highlight_colour(0));
         let state = Found {
             active_hex,
             abbrev,
             best_routes,
             active_route: None,
+            active_visit: None,
+            colour,
         };
         controller.set_window_title(&state.window_title(assets));
         state
@@ -290,8 +691,7 @@ impl Found {
         // `Found` can ignore the callback, we don't want to rely on the
         // caller being aware of this.
         // So `Found` must call `show_dividends()`.
-        if let Some(ref best_routes) = self.best_routes {
-            let (_token, routes) = best_routes;
+        if let Some(ref routes) = self.best_routes {
             let revenue = routes.net_revenue;
             let div_opts =
                 assets.games.active().dividend_options(&self.abbrev);
@@ -310,43 +710,89 @@ impl Found {
         false
     }
 
+    /// Returns a summary of the routes that were found, if any.
+    pub fn route_summary(&self, assets: &Assets) -> Option<RouteSummary> {
+        self.best_routes
+            .as_ref()
+            .map(|routes| RouteSummary::new(assets, &self.abbrev, routes))
+    }
+
     /// Returns the window title, which shows the company name and either the
     /// net revenue, or the revenue for the currently-selected route.
+    ///
+    /// When stepping through a route, this also shows the current visit and
+    /// the revenue earned from each visit up to and including this visit.
     pub fn window_title(&self, assets: &Assets) -> String {
-        if let Some((_token, routes)) = &self.best_routes {
+        if let Some(routes) = &self.best_routes {
             if let Some(ix) = self.active_route {
                 let route = &routes.train_routes[ix];
                 let train = &route.train;
                 let train_name =
                     assets.games.active().train_name(train).unwrap();
-                format!(
-                    "{} {}-train: ${}",
-                    self.abbrev, train_name, route.revenue
+                if let Some(visit_ix) = self.active_visit {
+                    let visits = &route.route.visits;
+                    let subtotal: usize = visits[..=visit_ix]
+                        .iter()
+                        .map(|visit| visit.revenue)
+                        .sum();
+                    return assets.strings.format(
+                        "{company} {train}-train: {hex} (stop {stop} of \
+                         {stops}), ${subtotal} of ${revenue}",
+                        &[
+                            ("company", &self.abbrev),
+                            ("train", &train_name),
+                            ("hex", &assets.hex_name(visits[visit_ix].addr)),
+                            ("stop", &(visit_ix + 1)),
+                            ("stops", &visits.len()),
+                            ("subtotal", &subtotal),
+                            ("revenue", &route.revenue),
+                        ],
+                    );
+                }
+                assets.strings.format(
+                    "{company} {train}-train: ${revenue}",
+                    &[
+                        ("company", &self.abbrev),
+                        ("train", &train_name),
+                        ("revenue", &route.revenue),
+                    ],
                 )
             } else {
-                format!("{}: ${}", self.abbrev, routes.net_revenue)
+                assets.strings.format(
+                    "{company}: ${revenue}",
+                    &[
+                        ("company", &self.abbrev),
+                        ("revenue", &routes.net_revenue),
+                    ],
+                )
             }
         } else {
-            format!("{}: No routes", &self.abbrev)
+            assets
+                .strings
+                .format("{company}: No routes", &[("company", &self.abbrev)])
         }
     }
 
     pub fn highlight_previous_route(&mut self) -> bool {
-        if let Some((_token, routes)) = &self.best_routes {
+        if let Some(routes) = &self.best_routes {
             let routes_vec = routes.routes();
             let num_routes = routes_vec.len();
             if num_routes < 2 {
                 return false;
             }
             if let Some(curr_ix) = self.active_route {
-                if curr_ix == 0 {
-                    self.active_route = None;
-                } else {
+                if curr_ix > 0 {
                     self.active_route = Some(curr_ix - 1);
+                } else if self.active_visit.is_some() {
+                    // Only show all routes when not stepping through a route.
+                    self.active_route = Some(num_routes - 1);
+                } else {
+                    self.active_route = None;
                 }
             } else {
                 self.active_route = Some(num_routes - 1);
             }
+            self.active_visit = self.active_visit.map(|_| 0);
             true
         } else {
             false
@@ -354,25 +800,89 @@ impl Found {
     }
 
     pub fn highlight_next_route(&mut self) -> bool {
-        if let Some((_token, routes)) = &self.best_routes {
+        if let Some(routes) = &self.best_routes {
             let num_routes = routes.routes().len();
             if num_routes < 2 {
                 return false;
             }
             if let Some(curr_ix) = self.active_route {
-                if curr_ix == num_routes - 1 {
-                    self.active_route = None;
-                } else {
+                if curr_ix < num_routes - 1 {
                     self.active_route = Some(curr_ix + 1);
+                } else if self.active_visit.is_some() {
+                    // Only show all routes when not stepping through a route.
+                    self.active_route = Some(0);
+                } else {
+                    self.active_route = None;
                 }
             } else {
                 self.active_route = Some(0);
             }
+            self.active_visit = self.active_visit.map(|_| 0);
             true
         } else {
             false
         }
     }
+
+    /// Returns `true` if stepping through the active route.
+    pub fn is_stepping(&self) -> bool {
+        self.active_visit.is_some()
+    }
+
+    /// Starts or stops stepping through the active route one visit at a
+    /// time, and returns `true` if the map should be redrawn.
+    ///
+    /// If all routes are currently shown, this steps through the first
+    /// route.
+    pub fn toggle_stepping(&mut self) -> bool {
+        if self.active_visit.is_some() {
+            self.active_visit = None;
+            return true;
+        }
+        let route_ix = self.active_route.unwrap_or(0);
+        let has_visits = self
+            .best_routes
+            .as_ref()
+            .and_then(|routes| routes.train_routes.get(route_ix))
+            .map(|route| !route.route.visits.is_empty())
+            .unwrap_or(false);
+        if !has_visits {
+            return false;
+        }
+        self.active_route = Some(route_ix);
+        self.active_visit = Some(0);
+        true
+    }
+
+    /// Highlights the previous visit along the active route, and returns
+    /// `true` if the map should be redrawn.
+    pub fn step_to_previous_visit(&mut self) -> bool {
+        match self.active_visit {
+            Some(ix) if ix > 0 => {
+                self.active_visit = Some(ix - 1);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Highlights the next visit along the active route, and returns `true`
+    /// if the map should be redrawn.
+    pub fn step_to_next_visit(&mut self) -> bool {
+        let num_visits = match (&self.best_routes, self.active_route) {
+            (Some(routes), Some(route_ix)) => {
+                routes.train_routes[route_ix].route.visits.len()
+            }
+            _ => return false,
+        };
+        match self.active_visit {
+            Some(ix) if ix + 1 < num_visits => {
+                self.active_visit = Some(ix + 1);
+                true
+            }
+            _ => false,
+        }
+    }
 }
 
 impl UiState for Found {
@@ -384,7 +894,7 @@ impl UiState for Found {
         n18brush::draw_map(hex, ctx, &mut hex_iter);
 
         // Slightly fade hexes that are not part of any route.
-        if let Some((_token, routes)) = &self.best_routes {
+        if let Some(routes) = &self.best_routes {
             let hexes: std::collections::BTreeSet<&HexAddress> = routes
                 .routes()
                 .iter()
@@ -401,12 +911,37 @@ impl UiState for Found {
 
         // Draw each route.
         // Note that this also redraws the token spaces at each visit.
-        if let Some((_token, routes)) = &self.best_routes {
-            if let Some(ix) = self.active_route {
+        if let Some(routes) = &self.best_routes {
+            if let (Some(ix), Some(visit_ix)) =
+                (self.active_route, self.active_visit)
+            {
+                // Draw the route up to the current visit, and then draw the
+                // current visit and its hex in a contrasting colour.
+                self.colour.apply_colour(ctx);
+                let route = routes.routes()[ix];
+                if let Some(part) = route.up_to_visit(visit_ix) {
+                    n18brush::highlight_route(hex, ctx, map, &part);
+                }
+                let visit = &route.visits[visit_ix];
+                let blue = Colour::from((0, 76, 179));
+                let step_colour =
+                    if self.colour.distance(&blue) < colours::MIN_DISTANCE {
+                        hex.theme.nth_highlight_colour(0)
+                    } else {
+                        blue
+                    };
+                step_colour.apply_colour(ctx);
+                n18brush::highlight_visit(hex, ctx, map, visit);
+                let m = map.prepare_to_draw(visit.addr, hex, ctx);
+                step_colour.apply_colour(ctx);
+                hex.theme.hex_highlight.apply_line(ctx, hex);
+                hex.define_boundary(ctx);
+                ctx.stroke().unwrap();
+                ctx.set_matrix(m);
+            } else if let Some(ix) = self.active_route {
                 // Draw only a single route, in the same colour as when
                 // drawing all routes.
-                let colour = hex.theme.nth_highlight_colour(ix);
-                colour.apply_colour(ctx);
+                self.colour.apply_colour(ctx);
                 let route = routes.routes()[ix];
                 n18brush::highlight_route(hex, ctx, map, route);
             } else {
@@ -415,7 +950,7 @@ impl UiState for Found {
                     ctx,
                     map,
                     &routes.routes(),
-                    |ix| hex.theme.nth_highlight_colour(ix),
+                    |_ix| self.colour,
                 );
             }
         }
@@ -437,12 +972,77 @@ impl UiState for Found {
             (230, 25, 25).into(),
             Some((230, 25, 25, 31).into()),
         );
+
+        // Label the routes that are shown, except when stepping through a
+        // route, with the train name and revenue, and number each stop.
+        // We do this last, so that the badges are not covered by any of the
+        // highlighted routes and tokens.
+        if let Some(routes) = &self.best_routes {
+            if self.active_visit.is_none() {
+                let game = assets.games.active();
+                let gap = 0.02 * hex.max_d;
+                let mut layout = n18brush::badges::BadgeLayout::new(gap);
+                for (ix, route) in routes.train_routes.iter().enumerate() {
+                    if self.active_route.is_some_and(|active| active != ix) {
+                        continue;
+                    }
+                    let train_name =
+                        game.train_name(&route.train).unwrap_or("?");
+                    let title = format!("{}: ${}", train_name, route.revenue);
+                    n18brush::badges::draw_route_badges(
+                        hex,
+                        ctx,
+                        map,
+                        &route.route,
+                        &title,
+                        self.colour,
+                        &mut layout,
+                    );
+                }
+            }
+        }
     }
+
+    /// Shows the selected route or visit, as per [Found::window_title].
+    fn status(&self, assets: &Assets, status: &mut StatusBar) {
+        status.set(SegmentKind::Message, self.window_title(assets));
+    }
+}
+
+/// Returns the name of each company, and also identifies minor companies in
+/// games that have both minor and major companies, and the cash held by
+/// each company whose treasury is being tracked.
+fn company_labels(assets: &Assets, companies: &[&Company]) -> Vec<String> {
+    let game = assets.games.active();
+    let has_tiers = game
+        .companies()
+        .iter()
+        .any(|c| c.tier != game.companies()[0].tier);
+    companies
+        .iter()
+        .map(|c| {
+            let label = if has_tiers {
+                format!("{} ({})", c.full_name, c.tier)
+            } else {
+                c.full_name.clone()
+            };
+            match assets.treasuries.cash(&c.abbrev) {
+                Some(cash) => format!("{}: ${}", label, cash),
+                None => label,
+            }
+        })
+        .collect()
 }
 
-/// Returns the companies that have placed tokens on the map.
-fn valid_companies(assets: &Assets) -> Vec<&Company> {
+/// Returns the companies that can be selected for this goal.
+fn valid_companies(assets: &Assets, goal: Goal) -> Vec<&Company> {
     let companies = assets.games.active().companies();
+    if goal == Goal::TokenSpaces {
+        return companies
+            .iter()
+            .filter(|c| assets.map.try_token(&c.abbrev).is_some())
+            .collect();
+    }
     let placed = assets.map.unique_placed_tokens();
     let placed_names: Vec<&str> = placed
         .iter()
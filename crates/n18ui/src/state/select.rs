@@ -0,0 +1,264 @@
+//! Selects multiple hexes, and copies, cuts, pastes, and rotates them.
+use cairo::Context;
+use std::collections::BTreeSet;
+
+use n18brush::badges::hex_centre_coord;
+use n18map::{Clipboard, HexAddress, Map};
+
+use crate::status::{SegmentKind, StatusBar};
+use crate::{Assets, MapEvent, UiState};
+
+/// Selecting multiple map hexes, which can be edited as a block.
+pub struct Select {
+    active_hex: HexAddress,
+    selected: BTreeSet<HexAddress>,
+    drag_rect: Option<(f64, f64, f64, f64)>,
+}
+
+impl Select {
+    /// Selects the provided map hexes, where `active_hex` is the anchor for
+    /// copying, pasting, and rotating the selection.
+    pub fn new<T>(active_hex: HexAddress, selected: T) -> Self
+    where
+        T: IntoIterator<Item = HexAddress>,
+    {
+        Select {
+            active_hex,
+            selected: selected.into_iter().collect(),
+            drag_rect: None,
+        }
+    }
+
+    pub fn active_hex(&self) -> HexAddress {
+        self.active_hex
+    }
+
+    pub fn selected(&self) -> &BTreeSet<HexAddress> {
+        &self.selected
+    }
+
+    /// Selects a single map hex, which also becomes the active hex.
+    pub fn select_only(&mut self, addr: HexAddress) {
+        self.selected.clear();
+        self.selected.insert(addr);
+        self.active_hex = addr;
+    }
+
+    /// Adds a map hex to the selection or, if it is already selected,
+    /// removes it from the selection.
+    ///
+    /// Newly-selected hexes become the active hex.
+    pub fn toggle(&mut self, addr: HexAddress) {
+        if !self.selected.remove(&addr) {
+            self.selected.insert(addr);
+            self.active_hex = addr;
+        }
+    }
+
+    /// Sets the area that the user is selecting by dragging the mouse.
+    pub fn set_drag_rect(&mut self, rect: Option<(f64, f64, f64, f64)>) {
+        self.drag_rect = rect
+    }
+
+    /// Selects each map hex whose centre lies within the rectangle
+    /// `(x0, y0, x1, y1)`, and returns `true` if any hexes were selected.
+    ///
+    /// If `extend` is `true`, these hexes are added to the current selection.
+    pub fn select_within(
+        &mut self,
+        assets: &Assets,
+        rect: (f64, f64, f64, f64),
+        extend: bool,
+    ) -> bool {
+        let (x0, y0, x1, y1) = rect;
+        let (x_min, x_max) = (x0.min(x1), x0.max(x1));
+        let (y_min, y_max) = (y0.min(y1), y0.max(y1));
+        let hex = &assets.hex;
+        let map = &assets.map;
+        let ctx = hex.context();
+        let within: Vec<HexAddress> = map
+            .hex_address_iter()
+            .filter(|addr| {
+                let centre = hex_centre_coord(hex, ctx, map, **addr);
+                let (x, y) = (centre.x, centre.y);
+                x >= x_min && x <= x_max && y >= y_min && y <= y_max
+            })
+            .copied()
+            .collect();
+        if within.is_empty() {
+            return false;
+        }
+        if !extend {
+            self.selected.clear();
+            self.active_hex = within[0];
+        }
+        self.selected.extend(within);
+        true
+    }
+
+    /// Copies the selected hexes to the clipboard, relative to the active
+    /// hex, and returns `true` if any hexes were copied.
+    pub fn copy(&self, assets: &mut Assets) -> bool {
+        if self.selected.is_empty() {
+            return false;
+        }
+        let clipboard =
+            Clipboard::copy(&assets.map, self.active_hex, &self.selected);
+        assets.clipboard = Some(clipboard);
+        true
+    }
+
+    /// Copies the selected hexes to the clipboard and removes their tiles,
+    /// and returns `true` if any hexes were cut.
+    pub fn cut(&self, assets: &mut Assets) -> bool {
+        if !self.copy(assets) {
+            return false;
+        }
+        self.remove_tiles(&mut assets.map);
+        self.record(assets, "Cut");
+        self.notify(assets, vec![]);
+        true
+    }
+
+    /// Removes the tiles from each of the selected hexes.
+    pub fn clear(&self, assets: &mut Assets) {
+        self.remove_tiles(&mut assets.map);
+        self.record(assets, "Removed tiles from");
+        self.notify(assets, vec![]);
+    }
+
+    fn remove_tiles(&self, map: &mut Map) {
+        for addr in &self.selected {
+            map.remove_tile(*addr)
+        }
+    }
+
+    /// Records an action that affected the selected hexes in the journal.
+    fn record(&self, assets: &mut Assets, action: &str) {
+        if self.selected.is_empty() {
+            return;
+        }
+        let count = self.selected.len();
+        let noun = if count == 1 { "hex" } else { "hexes" };
+        let entry = format!(
+            "{} {} {} around {}",
+            action,
+            count,
+            noun,
+            assets.hex_name(self.active_hex)
+        );
+        assets.record(entry);
+    }
+
+    /// Notifies listeners that the selected hexes, and any of the `other`
+    /// hexes, have been changed.
+    fn notify(&self, assets: &mut Assets, other: Vec<HexAddress>) {
+        let mut addrs = other;
+        addrs.extend(self.selected.iter().copied());
+        addrs.sort();
+        addrs.dedup();
+        if !addrs.is_empty() {
+            assets.notify(MapEvent::HexesChanged(addrs))
+        }
+    }
+
+    /// Pastes the clipboard contents at the active hex, selects the updated
+    /// hexes, and returns `true` if any hexes were updated.
+    pub fn paste(&mut self, assets: &mut Assets) -> bool {
+        let updated = if let Some(clipboard) = &assets.clipboard {
+            clipboard.paste(&mut assets.map, self.active_hex)
+        } else {
+            return false;
+        };
+        if updated.is_empty() {
+            return false;
+        }
+        self.selected = updated.into_iter().collect();
+        self.record(assets, "Pasted");
+        self.notify(assets, vec![]);
+        true
+    }
+
+    /// Rotates the selected hexes clockwise around the active hex, and
+    /// returns `true` if the selection was rotated.
+    ///
+    /// The selection is not rotated if any of the hexes would be moved off
+    /// the map.
+    pub fn rotate_cw(&mut self, assets: &mut Assets) -> bool {
+        self.rotate(assets, Clipboard::rotate_cw)
+    }
+
+    /// Rotates the selected hexes anti-clockwise around the active hex, and
+    /// returns `true` if the selection was rotated.
+    ///
+    /// The selection is not rotated if any of the hexes would be moved off
+    /// the map.
+    pub fn rotate_anti_cw(&mut self, assets: &mut Assets) -> bool {
+        self.rotate(assets, Clipboard::rotate_anti_cw)
+    }
+
+    fn rotate<F>(&mut self, assets: &mut Assets, rotate: F) -> bool
+    where
+        F: Fn(&mut Clipboard),
+    {
+        let map = &mut assets.map;
+        // NOTE: use a separate clipboard, so that the user's clipboard is
+        // not affected.
+        let mut block = Clipboard::copy(map, self.active_hex, &self.selected);
+        rotate(&mut block);
+        let targets = block.targets(self.active_hex);
+        if block.is_empty() || !targets.iter().all(|a| map.contains_hex(*a)) {
+            return false;
+        }
+        self.remove_tiles(map);
+        let previous: Vec<HexAddress> =
+            self.selected.iter().copied().collect();
+        self.selected =
+            block.paste(map, self.active_hex).into_iter().collect();
+        self.record(assets, "Rotated");
+        self.notify(assets, previous);
+        true
+    }
+}
+
+impl UiState for Select {
+    fn draw(&self, assets: &Assets, ctx: &Context) {
+        let hex = &assets.hex;
+        let map = &assets.map;
+        let mut hex_iter = map.hex_iter(hex, ctx);
+
+        n18brush::draw_map(hex, ctx, &mut hex_iter);
+
+        // Draw the selected hexes with a blue border.
+        let border = n18hex::Colour::from((0, 76, 179));
+        n18brush::highlight_hexes(
+            hex,
+            ctx,
+            &mut hex_iter,
+            |addr| self.selected.contains(addr),
+            Some(border),
+        );
+
+        // Draw the active hex, which is the anchor for pasting and rotating,
+        // with a red border.
+        let m = map.prepare_to_draw(self.active_hex, hex, ctx);
+        hex.theme.hex_highlight.apply_line_and_stroke(ctx, hex);
+        hex.define_boundary(ctx);
+        ctx.stroke().unwrap();
+        ctx.set_matrix(m);
+
+        if let Some(rect) = self.drag_rect {
+            n18brush::draw_selection_rect(hex, ctx, rect, border);
+        }
+    }
+
+    fn status(&self, assets: &Assets, status: &mut StatusBar) {
+        status.set(
+            SegmentKind::Message,
+            assets.strings.format(
+                "{count} hexes selected",
+                &[("count", &self.selected.len())],
+            ),
+        );
+    }
+}
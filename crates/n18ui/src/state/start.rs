@@ -26,8 +26,9 @@ impl Start {
 
 impl UiState for Start {
     fn draw(&self, assets: &Assets, ctx: &Context) {
-        let usage_str =
-            "Ctrl+N: Start a new game\nCtrl+O: Load a saved game\nQ: Quit";
+        let usage_str = assets.strings.tr(
+            "Ctrl+N: Start a new game\nCtrl+O: Load a saved game\nQ: Quit",
+        );
         let labeller = Text::new().font_size(16.0).labeller(ctx, &assets.hex);
         labeller.draw(usage_str, Coord::from((20.0, 20.0)));
     }
@@ -0,0 +1,332 @@
+//! Ranks the token spaces in which a company could place its next token.
+
+use cairo::Context;
+use std::sync::mpsc::Receiver;
+
+use n18game::analysis::TokenValue;
+use n18hex::Colour;
+use n18map::HexAddress;
+use n18route::Trains;
+use n18token::Token;
+
+use crate::status::{SegmentKind, StatusBar};
+use crate::{
+    Assets, Controller, PingDest, State, UiController, UiResponse, UiState,
+};
+
+/// Evaluates each candidate token space for the selected company, by finding
+/// the optimal routes for its trains if it placed a token in that space.
+pub struct Rank {
+    active_hex: HexAddress,
+    abbrev: String,
+    receiver: Receiver<Vec<TokenValue>>,
+}
+
+impl Rank {
+    pub fn new(
+        assets: &Assets,
+        controller: &mut dyn UiController,
+        active_hex: HexAddress,
+        abbrev: String,
+        token: Token,
+        trains: Trains,
+        bonuses: Vec<bool>,
+    ) -> Self {
+        let new_title = assets.strings.format(
+            "{company}: ranking token spaces ...",
+            &[("company", &abbrev)],
+        );
+        controller.set_window_title(&new_title);
+
+        // Evaluate the token spaces in a separate thread, because this runs
+        // the route optimiser once for every token space, and ping this
+        // state when the evaluation has finished.
+        let rank_fn = n18game::analysis::rank_token_spaces_closure(
+            assets.games.active(),
+            assets.map.clone(),
+            token,
+            trains,
+            bonuses,
+        );
+        let ping_tx = controller.ping_tx();
+        let (sender, receiver) = std::sync::mpsc::sync_channel(1);
+        std::thread::spawn(move || {
+            let values = rank_fn();
+            sender.send(values).unwrap();
+            ping_tx.send_ping(PingDest::State).unwrap();
+        });
+
+        Rank {
+            active_hex,
+            abbrev,
+            receiver,
+        }
+    }
+
+    pub fn active_hex(&self) -> HexAddress {
+        self.active_hex
+    }
+}
+
+impl UiState for Rank {
+    fn draw(&self, assets: &Assets, ctx: &Context) {
+        let hex = &assets.hex;
+        let map = &assets.map;
+        let mut hex_iter = map.hex_iter(hex, ctx);
+        n18brush::draw_map(hex, ctx, &mut hex_iter);
+
+        // NOTE: fade out the entire map and return.
+        let fill = n18hex::Colour::WHITE.with_alpha(159);
+        fill.apply_colour(ctx);
+        ctx.paint().unwrap();
+    }
+
+    fn ping(
+        &mut self,
+        assets: &mut Assets,
+        controller: &mut Controller,
+    ) -> (UiResponse, Option<State>) {
+        let Ok(values) = self.receiver.try_recv() else {
+            return (UiResponse::None, None);
+        };
+        let state = State::RankedTokenSpaces(Ranked::new(
+            assets,
+            controller,
+            self.active_hex,
+            self.abbrev.clone(),
+            values,
+        ));
+        (UiResponse::Redraw, Some(state))
+    }
+
+    fn status(&self, assets: &Assets, status: &mut StatusBar) {
+        status.set(
+            SegmentKind::Message,
+            assets.strings.format(
+                "{company}: ranking token spaces ...",
+                &[("company", &self.abbrev)],
+            ),
+        );
+    }
+}
+
+/// Displays each candidate token space, coloured by the revenue that the
+/// selected company could earn if it placed a token in that space.
+pub struct Ranked {
+    active_hex: HexAddress,
+    abbrev: String,
+    values: Vec<TokenValue>,
+    /// The index of the highlighted token space, if any.
+    active: Option<usize>,
+}
+
+impl Ranked {
+    pub fn new(
+        assets: &Assets,
+        controller: &mut dyn UiController,
+        active_hex: HexAddress,
+        abbrev: String,
+        values: Vec<TokenValue>,
+    ) -> Self {
+        let state = Ranked {
+            active_hex,
+            abbrev,
+            values,
+            active: None,
+        };
+        controller.set_window_title(&state.window_title(assets));
+        state
+    }
+
+    pub fn active_hex(&self) -> HexAddress {
+        self.active_hex
+    }
+
+    /// Returns the ranked token spaces, from highest to lowest revenue.
+    pub fn values(&self) -> &[TokenValue] {
+        &self.values
+    }
+
+    /// Returns the index of the highlighted token space, if any.
+    pub fn active(&self) -> Option<usize> {
+        self.active
+    }
+
+    /// Returns the window title, which shows the company name and either the
+    /// most valuable token space, or the highlighted token space.
+    pub fn window_title(&self, assets: &Assets) -> String {
+        let Some(best) = self.values.first() else {
+            return assets.strings.format(
+                "{company}: No token spaces",
+                &[("company", &self.abbrev)],
+            );
+        };
+        match self.active {
+            Some(ix) => {
+                let value = &self.values[ix];
+                assets.strings.format(
+                    "{company}: token at {hex} earns ${revenue} \
+                     ({rank} of {count})",
+                    &[
+                        ("company", &self.abbrev),
+                        ("hex", &assets.hex_name(value.addr)),
+                        ("revenue", &value.revenue),
+                        ("rank", &(ix + 1)),
+                        ("count", &self.values.len()),
+                    ],
+                )
+            }
+            None => assets.strings.format(
+                "{company}: best token at {hex} earns ${revenue}",
+                &[
+                    ("company", &self.abbrev),
+                    ("hex", &assets.hex_name(best.addr)),
+                    ("revenue", &best.revenue),
+                ],
+            ),
+        }
+    }
+
+    /// Highlights the next most valuable token space, and makes its hex the
+    /// active hex.
+    ///
+    /// Returns `true` if the map should be redrawn.
+    pub fn highlight_next(&mut self) -> bool {
+        if self.values.is_empty() {
+            return false;
+        }
+        let ix = match self.active {
+            Some(ix) => (ix + 1) % self.values.len(),
+            None => 0,
+        };
+        self.highlight(ix);
+        true
+    }
+
+    /// Highlights the next least valuable token space, and makes its hex the
+    /// active hex.
+    ///
+    /// Returns `true` if the map should be redrawn.
+    pub fn highlight_previous(&mut self) -> bool {
+        if self.values.is_empty() {
+            return false;
+        }
+        let ix = match self.active {
+            Some(ix) if ix > 0 => ix - 1,
+            _ => self.values.len() - 1,
+        };
+        self.highlight(ix);
+        true
+    }
+
+    fn highlight(&mut self, ix: usize) {
+        self.active = Some(ix);
+        self.active_hex = self.values[ix].addr;
+    }
+
+    /// Returns the fill colour for a token space, which ranges from red for
+    /// the least valuable token spaces to green for the most valuable.
+    fn value_colour(&self, revenue: usize) -> Colour {
+        let max = self.values.first().map(|v| v.revenue).unwrap_or(0);
+        let min = self.values.last().map(|v| v.revenue).unwrap_or(0);
+        let frac = if max > min {
+            (revenue - min) as f64 / (max - min) as f64
+        } else if max > 0 {
+            1.0
+        } else {
+            0.0
+        };
+        let low = (215.0, 48.0, 39.0);
+        let high = (26.0, 152.0, 80.0);
+        let mix = |a: f64, b: f64| (a + frac * (b - a)) / 255.0;
+        Colour::from((
+            mix(low.0, high.0),
+            mix(low.1, high.1),
+            mix(low.2, high.2),
+        ))
+        .with_alpha(191)
+    }
+}
+
+impl UiState for Ranked {
+    fn draw(&self, assets: &Assets, ctx: &Context) {
+        let hex = &assets.hex;
+        let map = &assets.map;
+        let mut hex_iter = map.hex_iter(hex, ctx);
+
+        n18brush::draw_map(hex, ctx, &mut hex_iter);
+
+        // Slightly fade hexes that do not contain any candidate token spaces.
+        n18brush::highlight_hexes(
+            hex,
+            ctx,
+            &mut hex_iter,
+            |addr| self.values.iter().any(|value| value.addr == *addr),
+            None,
+        );
+
+        // Colour each candidate token space by its value, drawing the least
+        // valuable token spaces first.
+        let border = Colour::from((76, 76, 76));
+        for value in self.values.iter().rev() {
+            n18brush::fill_token_space(
+                hex,
+                ctx,
+                map,
+                value.addr,
+                &value.space,
+                self.value_colour(value.revenue),
+                border,
+            );
+        }
+
+        // Label the highlighted token space, or each of the most valuable
+        // token spaces, with the revenue that it would earn.
+        let gap = 0.02 * hex.max_d;
+        let mut layout = n18brush::badges::BadgeLayout::new(gap);
+        let best = self.values.first().map(|v| v.revenue).unwrap_or(0);
+        for (ix, value) in self.values.iter().enumerate() {
+            let show = match self.active {
+                Some(active) => ix == active,
+                None => best > 0 && value.revenue == best,
+            };
+            if !show {
+                continue;
+            }
+            let anchor = n18brush::badges::token_space_coord(
+                hex,
+                ctx,
+                map,
+                value.addr,
+                &value.space,
+            );
+            if let Some(anchor) = anchor {
+                let colour = self.value_colour(value.revenue).with_alpha(255);
+                let text = format!("${}", value.revenue);
+                n18brush::badges::place_badge(
+                    hex,
+                    ctx,
+                    &mut layout,
+                    &text,
+                    anchor,
+                    colour,
+                );
+            }
+        }
+
+        if self.active.is_some() {
+            n18brush::highlight_active_hex(
+                hex,
+                ctx,
+                &mut hex_iter,
+                &Some(self.active_hex),
+                border,
+            );
+        }
+    }
+
+    /// Shows the highlighted token space, as per [Ranked::window_title].
+    fn status(&self, assets: &Assets, status: &mut StatusBar) {
+        status.set(SegmentKind::Message, self.window_title(assets));
+    }
+}
@@ -0,0 +1,89 @@
+//! Summarise the current map and user interface state in a status bar.
+//!
+//! Each [State](crate::State) populates a [StatusBar] with structured
+//! segments, such as the active hex, the selected tile, the current game
+//! phase, and the most recent revenue, and the
+//! [UiController](crate::UiController) renders these segments.
+//! Unlike the window title, which each state overwrites with its own message,
+//! the common segments are shown in every state.
+
+/// The kinds of information that can be shown in the status bar, in the
+/// order that they are displayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SegmentKind {
+    /// The current user interface mode.
+    Mode,
+    /// The active map hex.
+    Hex,
+    /// The tile on the active map hex, or the tile that has been selected
+    /// for placement.
+    Tile,
+    /// The current game phase.
+    Phase,
+    /// The most recent revenue that was calculated for a company.
+    Revenue,
+    /// The company that is operating, and the step that it is taking, if an
+    /// operating round is in progress.
+    Round,
+    /// A state-specific message.
+    Message,
+}
+
+/// A single piece of information in the status bar.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Segment {
+    pub kind: SegmentKind,
+    pub text: String,
+}
+
+/// The segments that are displayed in the status bar.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StatusBar {
+    segments: Vec<Segment>,
+}
+
+impl StatusBar {
+    /// The separator that is displayed between segments by [StatusBar::text].
+    pub const SEPARATOR: &'static str = "  |  ";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the segments in display order.
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Returns the text of the segment of the given kind, if any.
+    pub fn get(&self, kind: SegmentKind) -> Option<&str> {
+        self.segments
+            .iter()
+            .find(|segment| segment.kind == kind)
+            .map(|segment| segment.text.as_str())
+    }
+
+    /// Sets the text of the segment of the given kind, replacing any
+    /// existing text.
+    pub fn set<S: Into<String>>(&mut self, kind: SegmentKind, text: S) {
+        let text = text.into();
+        match self.segments.binary_search_by_key(&kind, |s| s.kind) {
+            Ok(ix) => self.segments[ix].text = text,
+            Err(ix) => self.segments.insert(ix, Segment { kind, text }),
+        }
+    }
+
+    /// Removes the segment of the given kind, if any.
+    pub fn remove(&mut self, kind: SegmentKind) {
+        self.segments.retain(|segment| segment.kind != kind)
+    }
+
+    /// Returns the text of each segment, separated by [StatusBar::SEPARATOR].
+    pub fn text(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| segment.text.as_str())
+            .collect::<Vec<_>>()
+            .join(Self::SEPARATOR)
+    }
+}
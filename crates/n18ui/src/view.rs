@@ -0,0 +1,175 @@
+//! Rotates and mirrors the map as it is shown in the window, so that the
+//! display can match the orientation of a physical map on the table.
+//!
+//! A [ViewTransform] is applied when drawing the map in the window, and is
+//! undone when mapping mouse events onto map hexes, so that clicking on a
+//! hex selects that hex regardless of how the map is oriented.
+//! It does not change the map hex coordinates, and it is never included in
+//! saved games, exported map images, saved map regions, or game bundles.
+//! It is saved in the user interface [Session](crate::session::Session).
+//!
+//! Note that screenshots and copies of the map view contain the map as it is
+//! shown in the window, and so they include the view transformation.
+
+use cairo::Matrix;
+use serde::{Deserialize, Serialize};
+
+/// The clockwise rotation of the map view.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum ViewRotation {
+    /// The map is shown in its canonical orientation.
+    #[default]
+    Zero,
+    /// The map is rotated 90 degrees clockwise.
+    Ninety,
+    /// The map is rotated 180 degrees.
+    OneEighty,
+    /// The map is rotated 270 degrees clockwise (i.e., 90 degrees
+    /// anti-clockwise).
+    TwoSeventy,
+}
+
+impl ViewRotation {
+    /// Returns the rotation that is 90 degrees clockwise from this rotation.
+    pub fn clockwise(&self) -> Self {
+        use ViewRotation::*;
+        match self {
+            Zero => Ninety,
+            Ninety => OneEighty,
+            OneEighty => TwoSeventy,
+            TwoSeventy => Zero,
+        }
+    }
+
+    /// Returns the rotation that is 90 degrees anti-clockwise from this
+    /// rotation.
+    pub fn anti_clockwise(&self) -> Self {
+        use ViewRotation::*;
+        match self {
+            Zero => TwoSeventy,
+            Ninety => Zero,
+            OneEighty => Ninety,
+            TwoSeventy => OneEighty,
+        }
+    }
+
+    /// Returns `true` if this rotation swaps the width and height of the
+    /// map.
+    pub fn is_sideways(&self) -> bool {
+        matches!(self, ViewRotation::Ninety | ViewRotation::TwoSeventy)
+    }
+}
+
+/// Defines how the map is rotated and mirrored when it is shown in the
+/// window.
+///
+/// The map is first rotated clockwise and is then mirrored (i.e., flipped
+/// from left to right), if requested.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub struct ViewTransform {
+    /// The clockwise rotation of the map.
+    #[serde(default)]
+    pub rotation: ViewRotation,
+    /// Whether the rotated map is flipped from left to right.
+    #[serde(default)]
+    pub mirror: bool,
+}
+
+impl ViewTransform {
+    /// Returns `true` if this transformation shows the map in its canonical
+    /// orientation.
+    pub fn is_identity(&self) -> bool {
+        *self == ViewTransform::default()
+    }
+
+    /// Returns the transformation with the map rotated 90 degrees clockwise,
+    /// as it appears in the window.
+    ///
+    /// Note that if the map is mirrored, this rotates the map anti-clockwise
+    /// before it is mirrored, so that the displayed map always turns
+    /// clockwise.
+    pub fn rotated_clockwise(&self) -> Self {
+        let rotation = if self.mirror {
+            self.rotation.anti_clockwise()
+        } else {
+            self.rotation.clockwise()
+        };
+        ViewTransform {
+            rotation,
+            mirror: self.mirror,
+        }
+    }
+
+    /// Returns the transformation with the displayed map flipped from left
+    /// to right.
+    pub fn mirrored(&self) -> Self {
+        ViewTransform {
+            rotation: self.rotation,
+            mirror: !self.mirror,
+        }
+    }
+
+    /// Returns the dimensions of the map as it is shown in the window, for a
+    /// map with the given `width` and `height`.
+    pub fn size(&self, width: f64, height: f64) -> (f64, f64) {
+        if self.rotation.is_sideways() {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Returns the matrix that transforms map coordinates into window
+    /// coordinates, for a map with the given `width` and `height`.
+    ///
+    /// The transformed map has the dimensions returned by
+    /// [ViewTransform::size], and its top-left corner is the origin.
+    pub fn matrix(&self, width: f64, height: f64) -> Matrix {
+        use ViewRotation::*;
+        let rotate = match self.rotation {
+            Zero => Matrix::identity(),
+            Ninety => Matrix::new(0.0, 1.0, -1.0, 0.0, height, 0.0),
+            OneEighty => Matrix::new(-1.0, 0.0, 0.0, -1.0, width, height),
+            TwoSeventy => Matrix::new(0.0, -1.0, 1.0, 0.0, 0.0, width),
+        };
+        if self.mirror {
+            let (view_width, _) = self.size(width, height);
+            let flip = Matrix::new(-1.0, 0.0, 0.0, 1.0, view_width, 0.0);
+            Matrix::multiply(&rotate, &flip)
+        } else {
+            rotate
+        }
+    }
+
+    /// Converts the point `(x, y)` from map coordinates into window
+    /// coordinates, for a map with the given `width` and `height`.
+    pub fn to_window(
+        &self,
+        width: f64,
+        height: f64,
+        x: f64,
+        y: f64,
+    ) -> (f64, f64) {
+        self.matrix(width, height).transform_point(x, y)
+    }
+
+    /// Converts the point `(x, y)` from window coordinates into map
+    /// coordinates, for a map with the given `width` and `height`.
+    pub fn to_map(
+        &self,
+        width: f64,
+        height: f64,
+        x: f64,
+        y: f64,
+    ) -> (f64, f64) {
+        // NOTE: rotations and reflections are always invertible.
+        self.matrix(width, height)
+            .try_invert()
+            .expect("Could not invert the view transformation")
+            .transform_point(x, y)
+    }
+}
@@ -0,0 +1,79 @@
+//! Watch tile catalogues and map configurations on disk, and reload them when
+//! they change.
+//!
+//! This module requires the `hot-reload` feature.
+//! Files are watched by periodically checking their modification times, so
+//! that no platform-specific file notification APIs are required.
+//!
+//! Use [UserInterface::watch_files](crate::UserInterface::watch_files) to
+//! watch files and reload them when they change.
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use crate::{PingDest, PingSender};
+
+/// The default interval between checking whether files have changed.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The tile catalogues and map configuration that should be loaded from disk
+/// and reloaded when they change.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WatchedFiles {
+    /// Tile catalogues, whose tiles will replace any tiles with the same
+    /// name in the current map.
+    pub tiles: Vec<PathBuf>,
+    /// A map configuration, which will be applied to the current map.
+    pub map: Option<PathBuf>,
+}
+
+impl WatchedFiles {
+    /// Returns `true` if there are no files to watch.
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty() && self.map.is_none()
+    }
+
+    /// Returns the path of each watched file.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.tiles.iter().chain(self.map.iter()).cloned().collect()
+    }
+}
+
+/// Returns the modification time of each file, or `None` for files that
+/// cannot be accessed (e.g., files that are being rewritten).
+fn modified_times(paths: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// Checks the modification times of the watched files in a separate thread,
+/// and sends [PingDest::ReloadFiles] whenever any of these files change.
+///
+/// The thread exits once the pings can no longer be delivered.
+pub fn spawn_watcher(
+    files: &WatchedFiles,
+    ping_tx: PingSender,
+    interval: Duration,
+) -> std::thread::JoinHandle<()> {
+    let paths = files.paths();
+    std::thread::spawn(move || {
+        let mut times = modified_times(&paths);
+        loop {
+            std::thread::sleep(interval);
+            let new_times = modified_times(&paths);
+            // NOTE: ignore files that cannot currently be accessed, since
+            // some editors delete a file before writing its new contents.
+            let changed = new_times
+                .iter()
+                .zip(times.iter())
+                .any(|(new, old)| new.is_some() && new != old);
+            if new_times.iter().all(|t| t.is_some()) || changed {
+                times = new_times;
+            }
+            if changed && ping_tx.send_ping(PingDest::ReloadFiles).is_err() {
+                break;
+            }
+        }
+    })
+}
@@ -0,0 +1,287 @@
+//! Provides the rules engine and route optimiser to front-ends that are
+//! compiled to WebAssembly (e.g., for the `wasm32-unknown-unknown` target).
+//!
+//! Every function accepts and returns JSON text, using the same formats as
+//! [n18io], so that games saved by `rusty_train` can be used by a browser
+//! front-end and vice versa:
+//!
+//! - Game states are encoded as per [n18io::game_state_to_string];
+//! - Moves are encoded as per [n18io::move_to_string];
+//! - Routes are encoded as per [n18io::routes_to_string]; and
+//! - Tile placements are encoded as per [n18io::placements_to_string].
+//!
+//! ```rust
+//! # use n18game::Game;
+//! # use n18hex::Hex;
+//! let game = n18game::new_1830();
+//! let map = game.create_map(&Hex::default());
+//! let state = n18io::game_state_to_string(game.save(&map), false).unwrap();
+//! let placements = n18wasm::legal_placements(&state, "C7").unwrap();
+//! assert!(placements.contains(r#""tile":"8""#));
+//! ```
+//!
+//! When compiled for the `wasm32` architecture, these functions are
+//! exported with [wasm-bindgen](https://rustwasm.github.io/wasm-bindgen/)
+//! as `applyMove`, `bestRoutes`, and `legalPlacements`, and errors are
+//! thrown as JavaScript `Error` values.
+//!
+//! Note that the route optimiser runs on a single thread, because there are
+//! no threads on the `wasm32-unknown-unknown` target.
+
+use n18game::sim::{Move, MoveError, Simulation};
+use n18game::{Game, GameState};
+use n18hex::Hex;
+
+/// The errors that can occur when loading a game state, or when applying a
+/// move to a game state.
+#[derive(Debug)]
+pub enum Error {
+    /// A game state or move could not be read, or a result could not be
+    /// written.
+    Json(n18io::Error),
+    /// There is no game with this name.
+    UnknownGame(String),
+    /// The game has no map variant with this name.
+    UnknownVariant { game: String, variant: String },
+    /// The game has no map hex with this name.
+    UnknownHex(String),
+    /// The move cannot be applied to the game state.
+    Move(MoveError),
+    /// The move does not run routes for a company (see [Move::RunRoutes]).
+    NotRunRoutes,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Error::*;
+
+        match self {
+            Json(error) => write!(f, "{}", error),
+            UnknownGame(game) => {
+                write!(f, "There is no game called '{}'", game)
+            }
+            UnknownVariant { game, variant } => {
+                write!(f, "{} has no map variant '{}'", game, variant)
+            }
+            UnknownHex(hex) => write!(f, "There is no map hex '{}'", hex),
+            Move(error) => write!(f, "{}", error),
+            NotRunRoutes => write!(f, "The move does not run any routes"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Json(error) => Some(error),
+            Error::Move(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<n18io::Error> for Error {
+    fn from(error: n18io::Error) -> Self {
+        Error::Json(error)
+    }
+}
+
+impl From<MoveError> for Error {
+    fn from(error: MoveError) -> Self {
+        Error::Move(error)
+    }
+}
+
+/// Reads a game state, and returns the game that it describes.
+///
+/// The saved map is checked against the game's map, because
+/// [Game::load] will panic if it refers to unknown tiles or tokens.
+fn load_game(state: &str) -> Result<(Box<dyn Game>, GameState), Error> {
+    let state = n18io::game_state_from_str(state)?;
+    let mut game = n18game::games()
+        .into_iter()
+        .find(|game| game.name() == state.game)
+        .ok_or_else(|| Error::UnknownGame(state.game.clone()))?;
+    if !game.set_variant(&state.variant) {
+        return Err(Error::UnknownVariant {
+            game: state.game,
+            variant: state.variant,
+        });
+    }
+    n18io::check_map_descr(&state.map, &game.create_map(&Hex::default()))?;
+    Ok((game, state))
+}
+
+/// Applies a move to a game state, and returns the new game state.
+///
+/// This applies the same checks as the `rusty_train` user interface, such
+/// as whether a tile can be placed on a hex, whether a company can place a
+/// token in a city, and whether a train can be bought.
+pub fn apply_move(state: &str, mv: &str) -> Result<String, Error> {
+    let (mut game, state) = load_game(state)?;
+    let mv = n18io::move_from_str(mv)?;
+    let state = n18game::sim::apply(game.as_mut(), &state, &mv)?;
+    Ok(n18io::game_state_to_string(state, false)?)
+}
+
+/// Finds the routes that earn the most revenue for a company, and returns
+/// these routes, or `null` if the company cannot operate any routes.
+///
+/// The company, its trains, and any bonuses are defined by a
+/// [Move::RunRoutes] move.
+pub fn best_routes(state: &str, run_routes: &str) -> Result<String, Error> {
+    let (mut game, state) = load_game(state)?;
+    let mv = n18io::move_from_str(run_routes)?;
+    if !matches!(mv, Move::RunRoutes { .. }) {
+        return Err(Error::NotRunRoutes);
+    }
+    let mut sim = Simulation::load(game.as_mut(), &state)?;
+    match sim.apply(&mv)? {
+        Some(routes) => Ok(n18io::routes_to_string(&routes, false)?),
+        None => Ok("null".to_string()),
+    }
+}
+
+/// Returns each tile that can be placed on a map hex in the current game
+/// phase, and the rotations with which it can be placed, without modifying
+/// the game state.
+///
+/// The hex is identified by its name in the game's coordinate system (e.g.,
+/// "C7").
+/// Note that this does not check whether the hex is reserved by a private
+/// company; [apply_move] checks this when a company places a tile.
+pub fn legal_placements(state: &str, hex: &str) -> Result<String, Error> {
+    let (mut game, state) = load_game(state)?;
+    let addr = game
+        .coordinate_system()
+        .parse(hex)
+        .map_err(|_| Error::UnknownHex(hex.to_string()))?;
+    let sim = Simulation::load(game.as_mut(), &state)?;
+    if !sim.map().contains_hex(addr) {
+        return Err(Error::UnknownHex(hex.to_string()));
+    }
+    let rules = sim.game().placement_rules();
+    let placements = sim.map().legal_placements(addr, &rules);
+    Ok(n18io::placements_to_string(&placements, false)?)
+}
+
+#[cfg(target_arch = "wasm32")]
+mod bindings {
+    use wasm_bindgen::prelude::*;
+
+    /// Applies a move to a game state, and returns the new game state.
+    #[wasm_bindgen(js_name = applyMove)]
+    pub fn apply_move(state: &str, mv: &str) -> Result<String, JsError> {
+        super::apply_move(state, mv).map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// Finds the routes that earn the most revenue for a company.
+    #[wasm_bindgen(js_name = bestRoutes)]
+    pub fn best_routes(
+        state: &str,
+        run_routes: &str,
+    ) -> Result<String, JsError> {
+        super::best_routes(state, run_routes)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// Returns each tile that can be placed on a map hex.
+    #[wasm_bindgen(js_name = legalPlacements)]
+    pub fn legal_placements(
+        state: &str,
+        hex: &str,
+    ) -> Result<String, JsError> {
+        super::legal_placements(state, hex)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the saved state of a new 1830 game.
+    fn new_1830_state() -> String {
+        let game = n18game::new_1830();
+        let map = game.create_map(&Hex::default());
+        n18io::game_state_to_string(game.save(&map), false).unwrap()
+    }
+
+    fn addr(name: &str) -> n18map::HexAddress {
+        n18game::new_1830().coordinate_system().parse(name).unwrap()
+    }
+
+    fn to_json(mv: &Move) -> String {
+        n18io::move_to_string(mv, false).unwrap()
+    }
+
+    #[test]
+    fn apply_valid_and_invalid_moves() {
+        let state = new_1830_state();
+        let lay_tile = |tile: &str| Move::LayTile {
+            addr: addr("C7"),
+            tile: tile.to_string(),
+            rotation: n18hex::RotateCW::Zero,
+            company: None,
+        };
+        let new_state = apply_move(&state, &to_json(&lay_tile("8"))).unwrap();
+        let new_state = n18io::game_state_from_str(&new_state).unwrap();
+        assert_eq!(
+            new_state.journal.entries(),
+            ["Placed tile 8 at C7 (rotation 0)"]
+        );
+
+        // Green tiles cannot be placed in the first phase.
+        let result = apply_move(&state, &to_json(&lay_tile("15")));
+        assert!(matches!(result, Err(Error::Move(MoveError::Tile(_)))));
+
+        // Invalid game states and moves are reported.
+        let result = apply_move(&state, "{}");
+        assert!(matches!(result, Err(Error::Json(_))));
+        let mut unknown = n18io::game_state_from_str(&state).unwrap();
+        unknown.game = "1999".to_string();
+        let unknown = n18io::game_state_to_string(unknown, false).unwrap();
+        let result = apply_move(&unknown, &to_json(&lay_tile("8")));
+        assert!(matches!(result, Err(Error::UnknownGame(_))));
+    }
+
+    #[test]
+    fn best_routes_for_a_company() {
+        let state = new_1830_state();
+        let run = to_json(&Move::RunRoutes {
+            company: "PRR".to_string(),
+            trains: vec!["2".to_string()],
+            bonuses: vec![],
+        });
+        // The company has no tokens on the map, and so it has no routes.
+        assert_eq!(best_routes(&state, &run).unwrap(), "null");
+
+        let place = to_json(&Move::PlaceToken {
+            addr: addr("W5"),
+            space: 0,
+            company: "PRR".to_string(),
+        });
+        let state = apply_move(&state, &place).unwrap();
+        let routes = best_routes(&state, &run).unwrap();
+        let routes = n18io::routes_from_str(&routes).unwrap();
+        assert_eq!(routes.net_revenue, 40);
+        assert_eq!(routes.train_routes.len(), 1);
+
+        // Only moves that run routes are accepted.
+        let result = best_routes(&state, &place);
+        assert!(matches!(result, Err(Error::NotRunRoutes)));
+    }
+
+    #[test]
+    fn legal_placements_on_a_hex() {
+        let state = new_1830_state();
+        let placements = legal_placements(&state, "C7").unwrap();
+        let tile_8 = r#"{"tile":"8","rotations":["Zero","Cw1","Cw2","Half","Acw2","Acw1"]}"#;
+        assert!(placements.contains(tile_8));
+        // Green tiles cannot be placed in the first phase.
+        assert!(!placements.contains(r#""tile":"15""#));
+
+        let result = legal_placements(&state, "Z99");
+        assert!(matches!(result, Err(Error::UnknownHex(_))));
+    }
+}
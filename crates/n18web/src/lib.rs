@@ -0,0 +1,379 @@
+//! Serve images of the current map over HTTP, so that remote players can
+//! follow a hot-seat game in a web browser.
+//!
+//! A [Spectator] server listens for HTTP requests in a separate thread, and
+//! serves the most recently published map:
+//!
+//! - `/`: a web page that shows the map and reloads it whenever it changes;
+//! - `/map.svg`: the map as an SVG image;
+//! - `/map.png`: the map as a PNG image; and
+//! - `/version`: the number of times that the map has been published.
+//!
+//! The map images are only drawn when [Spectator::publish] is called (e.g.,
+//! after each move, or when the game is saved), and are drawn with the same
+//! functions that are used to save images of the map, so this crate does not
+//! depend on GTK.
+//!
+//! ```rust,no_run
+//! # use n18hex::Hex;
+//! # use n18map::Map;
+//! # type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+//! # fn example(hex: &Hex, map: &Map) -> Result {
+//! let spectator = n18web::Spectator::bind("127.0.0.1:8018")?;
+//! println!("Serving the map at http://{}/", spectator.local_addr());
+//! spectator.publish(hex, map)?;
+//! # Ok(())
+//! # }
+//! ```
+use log::{info, warn};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use n18brush::ImageFormat;
+use n18hex::{Colour, Hex};
+use n18map::Map;
+
+/// The maximum time to wait for a client to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The maximum time to wait for a client to accept each part of a response.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The maximum number of bytes to read from the request line and headers.
+const MAX_REQUEST_LEN: u64 = 8 * 1024;
+
+/// The web page that displays the map, and checks for updates every two
+/// seconds.
+const INDEX_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Rusty Train</title>
+<style>body { margin: 0; } img { max-width: 100%; }</style>
+</head>
+<body>
+<img id="map" src="map.svg" alt="The current map">
+<script>
+let version = null;
+async function poll() {
+  try {
+    const resp = await fetch("version", { cache: "no-store" });
+    const latest = await resp.text();
+    if (version !== null && latest !== version) {
+      document.getElementById("map").src = "map.svg?v=" + latest;
+    }
+    version = latest;
+  } catch (e) {}
+  setTimeout(poll, 2000);
+}
+poll();
+</script>
+</body>
+</html>
+"#;
+
+/// The most recently published images of the map.
+#[derive(Default)]
+struct Images {
+    version: u64,
+    svg: Vec<u8>,
+    png: Vec<u8>,
+}
+
+/// Serves images of the most recently published map over HTTP.
+///
+/// The server runs until the application exits.
+pub struct Spectator {
+    addr: SocketAddr,
+    images: Arc<RwLock<Images>>,
+}
+
+impl Spectator {
+    /// Listens for HTTP requests on the provided address, and responds to
+    /// each connection in a separate thread, so that a slow client does not
+    /// delay the responses to other clients.
+    ///
+    /// Use port `0` to have the operating system choose an available port,
+    /// and [Spectator::local_addr] to identify the chosen port.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let images = Arc::new(RwLock::new(Images::default()));
+        let shared = Arc::clone(&images);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        warn!("Could not accept spectator: {}", e);
+                        continue;
+                    }
+                };
+                let images = Arc::clone(&shared);
+                std::thread::spawn(move || {
+                    if let Err(e) = respond(stream, &images) {
+                        warn!("Could not respond to spectator: {}", e)
+                    }
+                });
+            }
+        });
+        info!("Serving the map at http://{}/", addr);
+        Ok(Spectator { addr, images })
+    }
+
+    /// Returns the address on which this server is listening.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns the number of times that a map has been published.
+    pub fn version(&self) -> u64 {
+        self.images.read().map(|images| images.version).unwrap_or(0)
+    }
+
+    /// Draws the map as SVG and PNG images, and serves these images in
+    /// response to all subsequent requests.
+    pub fn publish(
+        &self,
+        hex: &Hex,
+        map: &Map,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let svg = render_map(hex, map, ImageFormat::Svg)?;
+        let png = render_map(hex, map, ImageFormat::Png)?;
+        let mut images = self
+            .images
+            .write()
+            .map_err(|_| "Could not update the map images")?;
+        images.version += 1;
+        images.svg = svg;
+        images.png = png;
+        Ok(())
+    }
+}
+
+/// Draws the entire map on a white background, and returns the image
+/// contents in the requested format.
+pub fn render_map(
+    hex: &Hex,
+    map: &Map,
+    format: ImageFormat,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let draw_map = |ctx: &_| {
+        let mut hex_iter = map.hex_iter(hex, ctx);
+        n18brush::draw_map(hex, ctx, &mut hex_iter);
+    };
+    // NOTE: measure the map before drawing the background, because painting
+    // the background would cover an unbounded area.
+    let (width, height) = n18brush::image_size(draw_map)
+        .ok_or("Could not determine the image size")?;
+    format.image_bytes(width, height, 1.0, |ctx| {
+        n18brush::clear_surface(ctx, Colour::WHITE);
+        draw_map(ctx)
+    })
+}
+
+/// Reads a single HTTP request and writes the response.
+fn respond(
+    stream: TcpStream,
+    images: &RwLock<Images>,
+) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    // NOTE: limit the request size, because each read resets the timeout
+    // and a client could otherwise send a line that never ends.
+    let mut reader = BufReader::new((&stream).take(MAX_REQUEST_LEN));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // NOTE: ignore the request headers, but read them so that the client
+    // does not see the connection being reset.
+    let mut header = String::new();
+    let complete = loop {
+        header.clear();
+        match reader.read_line(&mut header)? {
+            0 => break false,
+            n if n <= 2 => break header.ends_with('\n'),
+            _ => {}
+        }
+    };
+
+    let mut stream = &stream;
+    if !complete && reader.get_ref().limit() == 0 {
+        return write_response(
+            &mut stream,
+            "431 Request Header Fields Too Large",
+            "text/plain",
+            b"Request too large",
+            false,
+        );
+    }
+    let mut words = request_line.split_whitespace();
+    let method = words.next().unwrap_or("");
+    let target = words.next().unwrap_or("");
+    let path = target.split('?').next().unwrap_or("");
+    if method != "GET" && method != "HEAD" {
+        return write_response(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            b"Method not allowed",
+            false,
+        );
+    }
+    let head_only = method == "HEAD";
+    let images = match images.read() {
+        Ok(images) => images,
+        Err(_) => {
+            return write_response(
+                &mut stream,
+                "500 Internal Server Error",
+                "text/plain",
+                b"Could not read the map images",
+                head_only,
+            )
+        }
+    };
+    let image = |format: ImageFormat, bytes: &[u8]| {
+        if images.version == 0 {
+            ("503 Service Unavailable", "text/plain", b"No map".to_vec())
+        } else {
+            ("200 OK", format.mime_type(), bytes.to_vec())
+        }
+    };
+    let (status, content_type, body): (&str, &str, Vec<u8>) = match path {
+        "/" | "/index.html" => {
+            ("200 OK", "text/html; charset=utf-8", INDEX_HTML.into())
+        }
+        "/map.svg" => image(ImageFormat::Svg, &images.svg),
+        "/map.png" => image(ImageFormat::Png, &images.png),
+        "/version" => (
+            "200 OK",
+            "text/plain",
+            images.version.to_string().into_bytes(),
+        ),
+        _ => ("404 Not Found", "text/plain", b"Not found".to_vec()),
+    };
+    drop(images);
+    write_response(&mut stream, status, content_type, &body, head_only)
+}
+
+/// Writes an HTTP response and closes the connection.
+fn write_response<W: Write>(
+    stream: &mut W,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+    head_only: bool,
+) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+         Cache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    if !head_only {
+        stream.write_all(body)?;
+    }
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Spectator;
+    use n18hex::{Hex, Orientation, RotateCW};
+    use n18map::{HexAddress, Map};
+    use std::io::{Read, Write};
+    use std::net::{SocketAddr, TcpStream};
+
+    /// Sends a GET request and returns the response status line and body.
+    fn get(addr: SocketAddr, path: &str) -> (String, Vec<u8>) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: test\r\n\r\n", path)
+            .unwrap();
+        let mut response = vec![];
+        stream.read_to_end(&mut response).unwrap();
+        let split_at = response
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .expect("No end of headers");
+        let head = String::from_utf8_lossy(&response[..split_at]);
+        let status = head.lines().next().unwrap().to_string();
+        (status, response[split_at + 4..].to_vec())
+    }
+
+    #[test]
+    fn test_serve_map() {
+        let spectator = Spectator::bind("127.0.0.1:0").unwrap();
+        let addr = spectator.local_addr();
+
+        // The web page is available before a map has been published.
+        let (status, body) = get(addr, "/");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(String::from_utf8_lossy(&body).contains("map.svg"));
+        let (status, _body) = get(addr, "/map.svg");
+        assert_eq!(status, "HTTP/1.1 503 Service Unavailable");
+
+        // Publish a map that contains a single tile.
+        let hex = Hex::new(100.0);
+        let addrs: Vec<HexAddress> = vec![(0, 0).into(), (0, 1).into()];
+        let mut map = Map::new(
+            n18catalogue::tile_catalogue().into(),
+            vec![].into(),
+            addrs,
+            Orientation::FlatTop,
+        );
+        assert!(map.place_tile((0, 0).into(), "57", RotateCW::Zero));
+        spectator.publish(&hex, &map).unwrap();
+        assert_eq!(spectator.version(), 1);
+
+        let (status, body) = get(addr, "/version");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert_eq!(body, b"1");
+        let (status, body) = get(addr, "/map.svg?v=1");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(String::from_utf8_lossy(&body).contains("<svg"));
+        let (status, body) = get(addr, "/map.png");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(body.starts_with(b"\x89PNG"));
+        let (status, _body) = get(addr, "/missing");
+        assert_eq!(status, "HTTP/1.1 404 Not Found");
+    }
+
+    #[test]
+    fn test_slow_client() {
+        let spectator = Spectator::bind("127.0.0.1:0").unwrap();
+        let addr = spectator.local_addr();
+
+        // A client that connects but does not send a request should not
+        // prevent other clients from receiving a response.
+        let _idle = TcpStream::connect(addr).unwrap();
+        let (status, _body) = get(addr, "/version");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+    }
+
+    #[test]
+    fn test_request_too_large() {
+        let spectator = Spectator::bind("127.0.0.1:0").unwrap();
+        let addr = spectator.local_addr();
+
+        // A client that sends a header line that never ends should receive
+        // an error once the request size limit is reached.
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let start = "GET / HTTP/1.1\r\nX-Long: ";
+        let filler =
+            "x".repeat(super::MAX_REQUEST_LEN as usize - start.len());
+        write!(stream, "{}{}", start, filler).unwrap();
+        let mut response = vec![];
+        stream.read_to_end(&mut response).unwrap();
+        let response = String::from_utf8_lossy(&response);
+        assert!(response
+            .starts_with("HTTP/1.1 431 Request Header Fields Too Large"));
+
+        // Other clients should still receive a response.
+        let (status, _body) = get(addr, "/version");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+    }
+}
@@ -127,8 +127,12 @@ pub use n18game as game;
 pub use n18hex as hex;
 pub use n18io as io;
 pub use n18map as map;
+#[cfg(feature = "net")]
+pub use n18net as net;
 pub use n18route as route;
 pub use n18tile as tile;
 pub use n18token as token;
 #[cfg(feature = "ui")]
 pub use n18ui as ui;
+#[cfg(feature = "web")]
+pub use n18web as web;
@@ -108,6 +108,9 @@ pub use n18io::read_tiles;
 #[doc(inline)]
 pub use n18io::write_tiles;
 
+#[doc(inline)]
+pub use n18io::extend_catalogue;
+
 #[doc(inline)]
 pub use n18io::read_routes;
 
@@ -150,9 +153,15 @@ pub use n18route::Routes;
 #[doc(inline)]
 pub use n18route::ConflictRule;
 
+#[doc(inline)]
+pub use n18route::RouteConflictRules;
+
 #[doc(inline)]
 pub use n18route::Criteria;
 
+#[doc(inline)]
+pub use n18route::RouteTokenRule;
+
 #[doc(inline)]
 pub use n18route::paths_for_token;
 
@@ -177,6 +186,12 @@ pub use n18brush::ImageFormat;
 #[doc(inline)]
 pub use n18brush::image_size;
 
+#[doc(inline)]
+pub use n18brush::dpi_scale;
+
+#[doc(inline)]
+pub use n18brush::{save_tile_preview, TileOverlays};
+
 #[doc(inline)]
 pub use n18brush::draw_map;
 
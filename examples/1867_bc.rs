@@ -288,15 +288,16 @@ fn best_routes(
     company: &CompanyInfo,
 ) -> Routes {
     let bonuses = vec![];
-    let token = example.map().token(company.token_name);
+    let map = example.map();
+    let token = map.token(company.token_name);
     let path_limit = company.trains.path_limit();
     let criteria = Criteria {
         token,
         path_limit,
         conflict_rule: game.single_route_conflicts(),
         route_conflict_rule: game.multiple_routes_conflicts(),
+        token_rule: game.route_token_rule(map, company.token_name),
     };
-    let map = example.map();
     let start = Local::now();
     let paths = paths_for_token(map, &criteria);
     assert_eq!(paths.len(), company.num_paths);
@@ -0,0 +1,37 @@
+//! Summarise the built-in tile catalogue: count the tiles, cities, and dits
+//! of each colour, identify tiles that are identical except for their names,
+//! and list the tiles that are not used by any of the built-in games.
+//!
+//! Run this as an example to print the report:
+//!
+//!     cargo run --example catalogue_report
+//!
+use navig18xx::catalogue::{Builder, Report};
+use navig18xx::prelude::*;
+
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn test_catalogue_report() -> Result {
+    let catalogue = Builder::all_tiles().build();
+    let report = catalogue_report(&catalogue);
+    assert_eq!(report.total().tiles, catalogue.len());
+    assert!(report.duplicates.is_empty());
+    // Most of the built-in tiles are used by at least one game.
+    assert!(report.unused.len() < catalogue.len());
+    Ok(())
+}
+
+fn main() -> Result {
+    let catalogue = Builder::all_tiles().build();
+    let report = catalogue_report(&catalogue);
+    println!("{}", report);
+    Ok(())
+}
+
+/// Returns a report for the given catalogue, where tiles are considered to
+/// be used if they are included in any of the built-in games.
+fn catalogue_report(catalogue: &Catalogue) -> Report {
+    let games = navig18xx::game::games();
+    catalogue.report(games.iter().map(|game| game.catalogue()))
+}
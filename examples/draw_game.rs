@@ -11,10 +11,21 @@
 //! The default output filename is the input filename with the appropriate
 //! extension (pdf, png, or svg).
 //!
+//! The image can be drawn at a larger scale with the `--scale` option.
+//! PNG images can be drawn at a given print resolution (in dots per inch)
+//! with the `--dpi` option:
+//!
+//! ```text
+//! cargo run --example draw_game -- --png --scale 2 saved.game output.png
+//! cargo run --example draw_game -- --png --dpi 300 saved.game output.png
+//! ```
+//!
 
 use std::path::PathBuf;
 
-use navig18xx::prelude::{image_size, Game, Hex, ImageFormat, Map};
+use navig18xx::prelude::{
+    dpi_scale, image_size, Game, Hex, ImageFormat, Map,
+};
 
 /// Program settings, which can be overridden by command-line arguments.
 pub struct Settings {
@@ -22,6 +33,8 @@ pub struct Settings {
     pub format: ImageFormat,
     /// The map hexagon size.
     pub hex_size: f64,
+    /// The scale at which to draw the image.
+    pub scale: f64,
     /// The input game state file.
     pub input_file: Option<PathBuf>,
     /// The output image file.
@@ -32,6 +45,7 @@ impl Default for Settings {
     fn default() -> Self {
         Settings {
             hex_size: 125.0,
+            scale: 1.0,
             format: ImageFormat::Png,
             input_file: None,
             output_file: None,
@@ -46,7 +60,8 @@ impl Settings {
     pub fn try_from_args() -> Option<Self> {
         let mut settings = Settings::default();
         let mut parse_options = true;
-        for arg in std::env::args().skip(1) {
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
             if parse_options {
                 if !arg.starts_with('-') {
                     // Stop parsing options.
@@ -60,6 +75,13 @@ impl Settings {
                         "--pdf" => settings.format = ImageFormat::Pdf,
                         "--png" => settings.format = ImageFormat::Png,
                         "--svg" => settings.format = ImageFormat::Svg,
+                        "--scale" => {
+                            settings.scale = args.next()?.parse().ok()?
+                        }
+                        "--dpi" => {
+                            let dpi: f64 = args.next()?.parse().ok()?;
+                            settings.scale = dpi_scale(dpi)
+                        }
                         _ => return None,
                     }
                     continue;
@@ -124,6 +146,12 @@ pub fn main() {
     println!("Writing {} ...", output.to_str().unwrap());
     settings
         .format
-        .save_image(width, height, |ctx| draw(&map, &hex, ctx), &output)
+        .save_image(
+            width,
+            height,
+            settings.scale,
+            |ctx| draw(&map, &hex, ctx),
+            &output,
+        )
         .expect("Could not write output image")
 }
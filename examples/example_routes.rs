@@ -92,6 +92,7 @@ fn main() -> Result<()> {
         path_limit: None,
         conflict_rule: ConflictRule::TrackOrCityHex,
         route_conflict_rule: ConflictRule::TrackOnly,
+        token_rule: RouteTokenRule::AnyToken,
     };
     let paths = paths_for_token(map, &criteria);
     let trains = Trains::new(vec![TrainType::SkipTowns.with_max_stops(8)]);
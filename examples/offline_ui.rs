@@ -17,9 +17,9 @@ fn test() {
 fn main() {
     let image_dir = Dir::Examples;
     let output_file = image_dir.join("offline_ui.png");
-    let ui = build_ui();
+    let mut ui = build_ui();
     check_ui(&ui);
-    save_screenshot(&ui, output_file);
+    save_screenshot(&mut ui, output_file);
 }
 
 /// Creates a new game of 1861, places a token on the Moscow tile, rotates the
@@ -94,7 +94,7 @@ fn check_ui(ui: &UserInterface) {
 }
 
 /// Saves a screenshot of the current UI state.
-fn save_screenshot(ui: &UserInterface, dest: std::path::PathBuf) {
+fn save_screenshot(ui: &mut UserInterface, dest: std::path::PathBuf) {
     let margin = 1;
     let image = ui
         .canvas
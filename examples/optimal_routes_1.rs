@@ -58,6 +58,7 @@ fn optimal_routes_1(output_dir: &Dir) -> Result {
         path_limit: None,
         conflict_rule: ConflictRule::TrackOrCityHex,
         route_conflict_rule: ConflictRule::TrackOnly,
+        token_rule: RouteTokenRule::AnyToken,
     };
     let paths = paths_for_token(map, &criteria);
     let bonuses = vec![];
@@ -0,0 +1,112 @@
+//! Read a single tile from a JSON file and draw it as a large PNG file, with
+//! overlays that show track ends, token spaces, and label anchor points.
+//!
+//! Run this as an example to preview a tile definition:
+//!
+//!     cargo run --example preview_tile -- --help
+//!
+//! Run this as a test to preview a tile from the built-in catalogue:
+//!
+//!     cargo test --example preview_tile
+//!
+use navig18xx::prelude::*;
+
+mod output;
+
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn test_preview_tile() -> Result {
+    let output_dir = output::Dir::Examples;
+
+    // Save a tile with track, token spaces, and labels, and preview it.
+    let tiles = tile_catalogue();
+    let tile = tiles
+        .iter()
+        .find(|tile| tile.name == "63")
+        .expect("No tile 63");
+    let json_file = output_dir.join("preview_tile.json");
+    write_tile(&json_file, tile, true)?;
+
+    let png_file = output_dir.join("preview_tile.png");
+    preview_tile(&json_file, &png_file, 500.0, &TileOverlays::all())?;
+    assert!(png_file.exists());
+    Ok(())
+}
+
+fn main() -> Result {
+    let output_dir = output::Dir::Root;
+
+    let mut hex_size: f64 = 500.0;
+    let mut overlays = TileOverlays::all();
+    let mut json_files: Vec<String> = vec![];
+
+    // Skip the first argument, which is typically the path to this
+    // executable, but could conceivably contain anything.
+    let mut args = std::env::args();
+    args.next();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print_usage();
+                return Ok(());
+            }
+            "-s" => {
+                if let Some(size_str) = args.next() {
+                    hex_size = size_str.parse::<f64>()?
+                } else {
+                    panic!("Missing argument for {}", arg)
+                }
+            }
+            "--no-tracks" => overlays.track_ends = false,
+            "--no-tokens" => overlays.token_spaces = false,
+            "--no-labels" => overlays.label_anchors = false,
+            _ => json_files.push(arg),
+        }
+    }
+
+    if json_files.is_empty() {
+        println!("ERROR: No input files given");
+        print_usage();
+        return Ok(());
+    }
+
+    for json_file in &json_files {
+        let png_basename =
+            std::path::Path::new(json_file).with_extension("png");
+        let png_file = output_dir.join(png_basename);
+
+        preview_tile(json_file, &png_file, hex_size, &overlays)?;
+    }
+
+    Ok(())
+}
+
+fn preview_tile<P: AsRef<std::path::Path>>(
+    json_file: P,
+    png_file: &std::path::Path,
+    hex_size: f64,
+    overlays: &TileOverlays,
+) -> Result {
+    let hex = Hex::new(hex_size);
+
+    let json_str = json_file.as_ref().to_str().unwrap();
+    println!("Reading {} ...", json_str);
+    let tile = read_tile(json_file)?;
+
+    println!("Writing {} ...", png_file.to_str().unwrap());
+    save_tile_preview(&hex, &tile, overlays, png_file)
+}
+
+fn print_usage() {
+    println!();
+    println!("preview_tile [-s SIZE] [OPTIONS] JSON_FILES");
+    println!();
+    println!("    -s SIZE       The tile size (default: 500)");
+    println!("    --no-tracks   Do not mark the ends of each track segment");
+    println!("    --no-tokens   Do not mark the token spaces");
+    println!("    --no-labels   Do not mark the label anchor points");
+    println!("    JSON_FILES    The tile JSON file(s) to draw");
+    println!();
+}
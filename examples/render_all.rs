@@ -0,0 +1,247 @@
+//! Draws the starting map of every built-in game, and every tile in the tile
+//! catalogue and in each game, and writes these images to an output
+//! directory.
+//!
+//! # Command-line usage
+//!
+//! ```text
+//! cargo run --example render_all -- output_dir
+//! cargo run --example render_all -- --svg output_dir
+//! cargo run --example render_all -- --png --jobs 4 output_dir
+//! ```
+//!
+//! The default output directory is `./render`.
+//! Images are written in PNG format unless the `--pdf` or `--svg` option is
+//! provided, and are drawn by several threads in parallel; use the `--jobs`
+//! option to choose the number of threads.
+//! The hexagon size can be changed with the `--hex-size` option.
+//!
+//! The output directory contains a sub-directory for the tile catalogue and
+//! for each game (e.g., `catalogue` and `1830`).
+//! Each of these sub-directories contains a tile sheet (`tiles.png`) and an
+//! image of each tile (e.g., `tiles/57.png`), and each game directory also
+//! contains the game's starting map (`map.png`).
+//! File names are derived from game and tile names by
+//! [file_stems](navig18xx::brush::sheet::file_stems), so the same files are
+//! written every time.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use navig18xx::brush::sheet::{file_stems, save_tile, TileSheet};
+use navig18xx::prelude::{image_size, Colour, Game, Hex, ImageFormat, Tile};
+
+/// Program settings, which can be overridden by command-line arguments.
+pub struct Settings {
+    /// The output image format.
+    pub format: ImageFormat,
+    /// The map hexagon size.
+    pub hex_size: f64,
+    /// The number of threads that draw images.
+    pub jobs: usize,
+    /// The output directory.
+    pub output_dir: PathBuf,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let jobs = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        Settings {
+            format: ImageFormat::Png,
+            hex_size: 125.0,
+            jobs,
+            output_dir: PathBuf::from("render"),
+        }
+    }
+}
+
+impl Settings {
+    /// Returns the program settings after parsing any command-line arguments.
+    ///
+    /// Returns `None` if there were invalid arguments.
+    pub fn try_from_args() -> Option<Self> {
+        let mut settings = Settings::default();
+        let mut output_dir = None;
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--pdf" => settings.format = ImageFormat::Pdf,
+                "--png" => settings.format = ImageFormat::Png,
+                "--svg" => settings.format = ImageFormat::Svg,
+                "--hex-size" => {
+                    settings.hex_size = args.next()?.parse().ok()?
+                }
+                "--jobs" => {
+                    let jobs: usize = args.next()?.parse().ok()?;
+                    settings.jobs = jobs.max(1)
+                }
+                _ if arg.starts_with('-') => return None,
+                _ if output_dir.is_none() => {
+                    output_dir = Some(PathBuf::from(arg))
+                }
+                _ => return None,
+            }
+        }
+        if let Some(output_dir) = output_dir {
+            settings.output_dir = output_dir
+        }
+        Some(settings)
+    }
+}
+
+/// The tiles and (optional) starting map that are drawn for the tile
+/// catalogue or for a single game.
+enum Source {
+    /// The tile catalogue.
+    Catalogue,
+    /// The `nth` built-in game.
+    Game(usize),
+}
+
+/// A single output image, or a set of related output images.
+enum Job {
+    /// Draw the starting map of the `nth` built-in game.
+    Map(usize),
+    /// Draw each tile, and a tile sheet that contains every tile.
+    Tiles(Source),
+}
+
+/// Returns the name of the sub-directory for each built-in game, which is
+/// derived from the game number (e.g., "1830").
+fn game_dirs(games: &[Box<dyn Game>]) -> Vec<String> {
+    file_stems(
+        games
+            .iter()
+            .map(|game| game.name().split(':').next().unwrap_or_default()),
+    )
+}
+
+/// Draws the tiles and starting map for a single job, and returns the paths
+/// of the images that were written.
+fn run(
+    job: &Job,
+    settings: &Settings,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    // NOTE: each job creates its own games, which cannot be shared between
+    // threads.
+    let games = navig18xx::game::games();
+    let dirs = game_dirs(&games);
+    let hex = Hex::new(settings.hex_size);
+    let ext = settings.format.extension();
+    match job {
+        Job::Map(ix) => {
+            let map = games[*ix].create_map(&hex);
+            let draw = |ctx: &cairo::Context| {
+                let mut hex_iter = map.hex_iter(&hex, ctx);
+                navig18xx::brush::draw_map(&hex, ctx, &mut hex_iter);
+            };
+            let (width, height) =
+                image_size(draw).ok_or("Could not determine image size")?;
+            let dir = settings.output_dir.join(&dirs[*ix]);
+            std::fs::create_dir_all(&dir)?;
+            let path = dir.join(format!("map.{}", ext));
+            settings
+                .format
+                .save_image(width, height, 1.0, draw, &path)?;
+            Ok(vec![path])
+        }
+        Job::Tiles(source) => {
+            let (dir, tiles): (PathBuf, Vec<Tile>) = match source {
+                Source::Catalogue => (
+                    settings.output_dir.join("catalogue"),
+                    navig18xx::catalogue::tile_catalogue(),
+                ),
+                Source::Game(ix) => (
+                    settings.output_dir.join(&dirs[*ix]),
+                    games[*ix].clone_tiles(),
+                ),
+            };
+            save_tiles(&hex, &tiles, settings.format, &dir)
+        }
+    }
+}
+
+/// Saves an image of each tile, and a tile sheet that contains every tile,
+/// in the directory `dir`.
+fn save_tiles(
+    hex: &Hex,
+    tiles: &[Tile],
+    format: ImageFormat,
+    dir: &Path,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let ext = format.extension();
+    let tiles_dir = dir.join("tiles");
+    std::fs::create_dir_all(&tiles_dir)?;
+    let stems = file_stems(tiles.iter().map(|tile| &tile.name));
+    let mut paths = vec![];
+    for (tile, stem) in tiles.iter().zip(&stems) {
+        let path = tiles_dir.join(format!("{}.{}", stem, ext));
+        save_tile(hex, tile, Some(Colour::WHITE), format, &path)?;
+        paths.push(path);
+    }
+    let path = dir.join(format!("tiles.{}", ext));
+    TileSheet::default().save(hex, tiles, format, &path)?;
+    paths.push(path);
+    Ok(paths)
+}
+
+pub fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let settings = Settings::try_from_args().ok_or("Invalid arguments")?;
+    let num_games = navig18xx::game::games().len();
+    let mut jobs = vec![Job::Tiles(Source::Catalogue)];
+    for ix in 0..num_games {
+        jobs.push(Job::Map(ix));
+        jobs.push(Job::Tiles(Source::Game(ix)));
+    }
+
+    // Each thread takes the next job until there are no jobs remaining, and
+    // the results are reported in the order that the jobs were defined.
+    let next_job = AtomicUsize::new(0);
+    let mut results: Vec<(usize, Result<Vec<PathBuf>, String>)> =
+        std::thread::scope(|scope| {
+            let threads: Vec<_> = (0..settings.jobs.min(jobs.len()))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = vec![];
+                        loop {
+                            let ix = next_job.fetch_add(1, Ordering::SeqCst);
+                            let Some(job) = jobs.get(ix) else {
+                                break;
+                            };
+                            let result = run(job, &settings)
+                                .map_err(|e| e.to_string());
+                            results.push((ix, result));
+                        }
+                        results
+                    })
+                })
+                .collect();
+            threads
+                .into_iter()
+                .flat_map(|thread| thread.join().unwrap())
+                .collect()
+        });
+    results.sort_by_key(|(ix, _)| *ix);
+
+    let mut failed = false;
+    for (_ix, result) in results {
+        match result {
+            Ok(paths) => {
+                for path in paths {
+                    println!("Wrote {}", path.display())
+                }
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        Err("Could not draw every image".into())
+    } else {
+        Ok(())
+    }
+}
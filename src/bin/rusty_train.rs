@@ -35,8 +35,11 @@ pub fn main() {
 
 pub enum UiEvent {
     ButtonPress(navig18xx::ui::ButtonPress),
+    ButtonDrag(navig18xx::ui::ButtonDrag),
+    PointerMotion(navig18xx::ui::PointerMotion),
     KeyPress(navig18xx::ui::KeyPress),
     PingCurrentState(navig18xx::ui::PingDest),
+    Quit,
 }
 
 pub fn build(application: &gtk::Application) {
@@ -47,6 +50,18 @@ pub fn build(application: &gtk::Application) {
     let scrolled_win = gtk::ScrolledWindow::new();
     let drawing_area = DrawingArea::new();
     scrolled_win.set_child(Some(&drawing_area));
+    scrolled_win.set_vexpand(true);
+
+    // Show the status bar below the map content.
+    let status_label = gtk::Label::new(None);
+    status_label.set_xalign(0.0);
+    status_label.set_margin_start(6);
+    status_label.set_margin_end(6);
+    status_label.set_margin_top(2);
+    status_label.set_margin_bottom(2);
+    let content = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    content.append(&scrolled_win);
+    content.append(&status_label);
 
     // Create a second channel for sending "pings", which can be used to
     // trigger non-UI events, such as messages from tasks in other threads.
@@ -59,12 +74,73 @@ pub fn build(application: &gtk::Application) {
         win,
         drawing_area.clone(),
         ping_tx,
-    );
+    )
+    .with_status_label(status_label);
     let mut ui = navig18xx::ui::UserInterface::new(
         games,
         controller,
         Default::default(),
     );
+    #[cfg(feature = "hot-reload")]
+    {
+        let response = ui.watch_files(watched_files());
+        ui.respond(response);
+    }
+    #[cfg(feature = "web")]
+    if let Some(addr) = std::env::var_os("RUSTY_TRAIN_SPECTATOR") {
+        let addr = addr.to_string_lossy();
+        match ui.serve_spectators(addr.as_ref()) {
+            Ok(local_addr) => {
+                log::info!("Spectators can visit http://{}/", local_addr)
+            }
+            Err(e) => log::error!("Could not serve the map: {}", e),
+        }
+    }
+    // Load the available languages before restoring the previous session,
+    // so that the previously-selected language can be restored.
+    if let Some(dir) = navig18xx::ui::lang::default_dir() {
+        ui.set_languages(navig18xx::ui::lang::find_languages(dir));
+    }
+    // Restore the previous session, and save it again on exit.
+    if let Some(path) = navig18xx::ui::session::default_path() {
+        let response = ui.restore_session(path);
+        ui.respond(response);
+    }
+    // Share the current game with other players, or join a game that another
+    // player is sharing.
+    #[cfg(feature = "net")]
+    if let Some(addr) = std::env::var_os("RUSTY_TRAIN_HOST") {
+        let addr = addr.to_string_lossy();
+        match ui.host_session(addr.as_ref()) {
+            Ok(local_addr) => {
+                log::info!("Other players can join at {}", local_addr)
+            }
+            Err(e) => log::error!("Could not share the game: {}", e),
+        }
+    } else if let Some(addr) = std::env::var_os("RUSTY_TRAIN_JOIN") {
+        let addr = addr.to_string_lossy();
+        match ui.join_session(addr.as_ref()) {
+            Ok(response) => ui.respond(response),
+            Err(e) => log::error!("Could not join the game: {}", e),
+        }
+    }
+    // Replay the input events in the script listed in RUSTY_TRAIN_REPLAY.
+    if let Some(path) = std::env::var_os("RUSTY_TRAIN_REPLAY") {
+        match navig18xx::ui::script::Script::read(path) {
+            Ok(script) => {
+                if let Err(e) = ui.replay(&script) {
+                    log::error!("Could not replay script: {}", e)
+                }
+            }
+            Err(e) => log::error!("{}", e),
+        }
+    }
+    // Record input events, and save them to the file listed in
+    // RUSTY_TRAIN_RECORD on exit.
+    let record_path = std::env::var_os("RUSTY_TRAIN_RECORD");
+    if record_path.is_some() {
+        ui.start_recording();
+    }
     ui.draw();
 
     window.set_title(Some("Rusty Train"));
@@ -79,24 +155,75 @@ pub fn build(application: &gtk::Application) {
     let surface = ui.canvas.surface();
     drawing_area.set_draw_func(move |_da, ctx, _width, _height| {
         let surf = surface.read().expect("Could not access drawing surface");
-        ctx.set_source_surface(&*surf, 0.0, 0.0).unwrap();
-        ctx.paint().unwrap();
+        surf.paint(ctx, 0.0, 0.0).unwrap();
     });
 
     // Let the UI handle mouse button events.
     let tx_ = tx.clone();
-    let click_forwarder = gtk::GestureClick::builder()
-        .button(gdk::BUTTON_PRIMARY)
-        .n_points(1)
-        .build();
-    click_forwarder.connect_pressed(move |_self, _count, x, y| {
-        let button = gdk::BUTTON_PRIMARY;
-        let event = navig18xx::ui::ButtonPress { x, y, button };
+    // NOTE: a button value of zero listens for all mouse buttons.
+    let click_forwarder =
+        gtk::GestureClick::builder().button(0).n_points(1).build();
+    click_forwarder.connect_pressed(move |gesture, _count, x, y| {
+        let button = gesture.current_button();
+        let modifiers = gesture.current_event_state();
+        let event = navig18xx::ui::ButtonPress {
+            x,
+            y,
+            button,
+            ctrl: modifiers.contains(gdk::ModifierType::CONTROL_MASK),
+            alt: modifiers.contains(gdk::ModifierType::ALT_MASK),
+            shift: modifiers.contains(gdk::ModifierType::SHIFT_MASK),
+        };
         tx_.send_blocking(UiEvent::ButtonPress(event))
             .expect("Could not send ButtonPress event");
     });
     drawing_area.add_controller(click_forwarder);
 
+    // Let the UI handle mouse drag events.
+    let drag_forwarder = gtk::GestureDrag::builder()
+        .button(gdk::BUTTON_PRIMARY)
+        .build();
+    let drag_event = |gesture: &gtk::GestureDrag, dx, dy, finished| {
+        let (x0, y0) = gesture.start_point()?;
+        let shift = gesture
+            .current_event_state()
+            .contains(gdk::ModifierType::SHIFT_MASK);
+        Some(navig18xx::ui::ButtonDrag {
+            x0,
+            y0,
+            x: x0 + dx,
+            y: y0 + dy,
+            button: gdk::BUTTON_PRIMARY,
+            shift,
+            finished,
+        })
+    };
+    let tx_ = tx.clone();
+    drag_forwarder.connect_drag_update(move |gesture, dx, dy| {
+        if let Some(event) = drag_event(gesture, dx, dy, false) {
+            tx_.send_blocking(UiEvent::ButtonDrag(event))
+                .expect("Could not send ButtonDrag event");
+        }
+    });
+    let tx_ = tx.clone();
+    drag_forwarder.connect_drag_end(move |gesture, dx, dy| {
+        if let Some(event) = drag_event(gesture, dx, dy, true) {
+            tx_.send_blocking(UiEvent::ButtonDrag(event))
+                .expect("Could not send ButtonDrag event");
+        }
+    });
+    drawing_area.add_controller(drag_forwarder);
+
+    // Let the UI handle mouse motion events.
+    let tx_ = tx.clone();
+    let motion_forwarder = gtk::EventControllerMotion::new();
+    motion_forwarder.connect_motion(move |_self, x, y| {
+        let event = navig18xx::ui::PointerMotion { x, y };
+        tx_.send_blocking(UiEvent::PointerMotion(event))
+            .expect("Could not send PointerMotion event");
+    });
+    drawing_area.add_controller(motion_forwarder);
+
     // Let the UI handle keyboard events.
     let tx_ = tx.clone();
     let key_forwarder = gtk::EventControllerKey::new();
@@ -109,6 +236,20 @@ pub fn build(application: &gtk::Application) {
     );
     window.add_controller(key_forwarder);
 
+    // Save the session before the window is closed, by asking the UI to
+    // quit and then allowing the window to close once it has done so.
+    let quitting = std::cell::Cell::new(false);
+    let tx_ = tx.clone();
+    window.connect_close_request(move |_window| {
+        if quitting.replace(true) {
+            glib::Propagation::Proceed
+        } else {
+            tx_.send_blocking(UiEvent::Quit)
+                .expect("Could not send Quit event");
+            glib::Propagation::Stop
+        }
+    });
+
     // Pass each "ping" event to the current UI state.
     glib::spawn_future_local(async move {
         while let Ok(dest) = ping_rx.recv().await {
@@ -118,10 +259,15 @@ pub fn build(application: &gtk::Application) {
         }
     });
 
-    // Show the starting message, rather than the map content.
-    let mut start_visible = true;
-    let start_widget = start_message();
-    window.set_child(Some(&start_widget));
+    // Show the starting message, rather than the map content, unless a game
+    // was restored from the previous session.
+    let mut start_visible = ui.state.as_start().is_some();
+    if start_visible {
+        let start_widget = start_message();
+        window.set_child(Some(&start_widget));
+    } else {
+        window.set_child(Some(&content));
+    }
 
     // Dispatch events to the appropriate handler.
     // Note that this closure owns `ui_state`.
@@ -130,17 +276,32 @@ pub fn build(application: &gtk::Application) {
         while let Ok(event) = rx.recv().await {
             let response = match event {
                 UiEvent::ButtonPress(event) => ui.handle_button_press(&event),
+                UiEvent::ButtonDrag(event) => ui.handle_button_drag(&event),
+                UiEvent::PointerMotion(event) => {
+                    ui.handle_pointer_motion(&event)
+                }
                 UiEvent::KeyPress(event) => ui.handle_key_press(&event),
                 UiEvent::PingCurrentState(dest) => ui.ping(dest),
+                UiEvent::Quit => navig18xx::ui::UiResponse::Quit,
             };
+            let quit = response == navig18xx::ui::UiResponse::Quit;
             ui.respond(response);
 
+            // Save the recorded input events before the application exits.
+            if let (true, Some(path)) = (quit, record_path.as_ref()) {
+                if let Some(script) = ui.stop_recording() {
+                    if let Err(e) = script.write(path) {
+                        log::error!("Could not save recorded input: {}", e)
+                    }
+                }
+            }
+
             // When a game is started or loaded, hide the starting message and
             // show the map content instead.
             if start_visible && ui.state.as_start().is_none() {
                 // NOTE: unlike GTK 3, GTK 4 allows us to replace the existing
                 // child widget.
-                _window.set_child(Some(&scrolled_win));
+                _window.set_child(Some(&content));
                 start_visible = false;
             }
         }
@@ -150,6 +311,21 @@ pub fn build(application: &gtk::Application) {
     window.show();
 }
 
+/// Returns the tile catalogues and map configuration to load from disk and
+/// reload when they change, as listed in the `RUSTY_TRAIN_TILES` and
+/// `RUSTY_TRAIN_MAP` environment variables.
+///
+/// Multiple tile catalogues can be listed in `RUSTY_TRAIN_TILES`, separated
+/// in the same way as the `PATH` environment variable.
+#[cfg(feature = "hot-reload")]
+fn watched_files() -> navig18xx::ui::watch::WatchedFiles {
+    let tiles = std::env::var_os("RUSTY_TRAIN_TILES")
+        .map(|paths| std::env::split_paths(&paths).collect())
+        .unwrap_or_default();
+    let map = std::env::var_os("RUSTY_TRAIN_MAP").map(|path| path.into());
+    navig18xx::ui::watch::WatchedFiles { tiles, map }
+}
+
 /// Returns a Grid containing labels that identify the available key bindings
 /// when Rusty Train is launched.
 fn start_message() -> gtk::Grid {
@@ -0,0 +1,49 @@
+use navig18xx::prelude::*;
+
+#[test]
+/// This test checks that the Canadian National Railway can only be formed
+/// in phase 6 or later, and that it replaces the minor companies' tokens.
+fn test_form_canadian_national() {
+    let mut game = navig18xx::game::new_1867();
+    let coords = game.coordinate_system();
+    let hex = Hex::default();
+    let mut map = game.create_map(&hex);
+
+    // Place a minor company token and a major company token in Montreal.
+    let minor = *game.token("BBG");
+    let major = *game.token("CPR");
+    let national = *game.token("CN");
+    let addr_montreal = coords.parse("L12").unwrap();
+    assert!(map.place_tile(addr_montreal, "X5", RotateCW::Zero));
+    let token_spaces = map.tile_at(addr_montreal).unwrap().token_spaces();
+    let map_hex = map.hex_state_mut(addr_montreal).unwrap();
+    map_hex.set_token_at(&token_spaces[0], minor);
+    map_hex.set_token_at(&token_spaces[1], major);
+
+    // The national company cannot be formed before phase 6.
+    let formation = game.national_formation().unwrap();
+    assert_eq!(formation.national.abbrev, "CN");
+    assert!(formation.companies.contains(&"BBG".to_string()));
+    assert!(!formation.companies.contains(&"CPR".to_string()));
+    assert!(!game.can_form_national());
+    assert!(game.form_national(&mut map).is_none());
+
+    // The national company replaces the minor company's token.
+    assert!(game.set_phase_name(&mut map, "6"));
+    assert!(game.can_form_national());
+    let converted = game.form_national(&mut map).unwrap();
+    assert_eq!(converted.len(), 1);
+    assert_eq!(converted[0].0, "BBG");
+    assert!(map.find_placed_tokens(&minor).is_empty());
+    assert_eq!(map.find_placed_tokens(&major).len(), 1);
+    assert_eq!(map.find_placed_tokens(&national).len(), 1);
+
+    // Reverting the token changes restores the minor company's token.
+    map.revert_token_changes(&converted[0].1);
+    assert_eq!(map.find_placed_tokens(&minor).len(), 1);
+    assert!(map.find_placed_tokens(&national).is_empty());
+
+    // The national company can also be formed in later phases.
+    assert!(game.set_phase_name(&mut map, "8"));
+    assert!(game.can_form_national());
+}
@@ -0,0 +1,138 @@
+/// Check that a background image can be loaded by dropping a PNG file onto
+/// the window, that it can be moved, scaled, and hidden, that its settings
+/// are saved in the session, and that it is only drawn in the window.
+use navig18xx::prelude::*;
+use navig18xx::ui::background::{BackgroundSettings, OFFSET_STEP};
+use navig18xx::ui::session::Session;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+fn press_ctrl_shift(ui: &mut UserInterface, key: gdk::Key) -> UiResponse {
+    let event = KeyPress {
+        key,
+        ctrl: true,
+        alt: false,
+        shift: true,
+    };
+    ui.handle_key_press(&event)
+}
+
+/// Returns the `(red, green, blue)` components of the pixel at `(x, y)`.
+fn pixel(
+    image: &mut cairo::ImageSurface,
+    x: usize,
+    y: usize,
+) -> (u8, u8, u8) {
+    let stride = image.stride() as usize;
+    let data = image.data().unwrap();
+    let ix = y * stride + 4 * x;
+    // NOTE: ARgb32 pixels are stored as native-endian 32-bit integers.
+    let argb = u32::from_ne_bytes([
+        data[ix],
+        data[ix + 1],
+        data[ix + 2],
+        data[ix + 3],
+    ]);
+    ((argb >> 16) as u8, (argb >> 8) as u8, argb as u8)
+}
+
+/// Writes a solid red PNG image that is large enough to cover the map.
+fn write_background(path: &std::path::Path) {
+    let image =
+        cairo::ImageSurface::create(cairo::Format::ARgb32, 4000, 4000)
+            .unwrap();
+    let ctx = cairo::Context::new(&image).unwrap();
+    ctx.set_source_rgb(1.0, 0.0, 0.0);
+    ctx.paint().unwrap();
+    drop(ctx);
+    let mut file = std::fs::File::create(path).unwrap();
+    image.write_to_png(&mut file).unwrap();
+}
+
+#[test]
+fn background_image() {
+    let output_dir = std::path::Path::new("./tests/output");
+    std::fs::create_dir_all(output_dir).unwrap();
+    let image_path = output_dir.join("background-image.png");
+    write_background(&image_path);
+
+    let mut ui = new_ui();
+
+    // Draw the map without a background image.
+    ui.draw();
+    let mut plain = ui.canvas.copy_surface();
+    assert_eq!(pixel(&mut plain, 1, 1), (255, 255, 255));
+
+    // Load the background image by dropping it onto the window.
+    let Controller::Dummy(dummy) = &ui.controller else {
+        panic!("Expected a dummy controller")
+    };
+    assert!(dummy.drop_file(image_path.clone()));
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::Redraw);
+    let settings = ui.assets.background.as_ref().unwrap().settings();
+    assert_eq!(settings, &BackgroundSettings::new(&image_path));
+
+    // The image is drawn beneath the map, which is partly transparent.
+    ui.draw();
+    let mut image = ui.canvas.copy_surface();
+    let (red, green, blue) = pixel(&mut image, 1, 1);
+    assert_eq!(red, 255);
+    assert!(green < 192 && blue < 192);
+
+    // Move and scale the image.
+    let response = press_ctrl_shift(&mut ui, gdk::Key::Right);
+    assert_eq!(response, UiResponse::Redraw);
+    let response = press_ctrl_shift(&mut ui, gdk::Key::Down);
+    assert_eq!(response, UiResponse::Redraw);
+    let response = press_ctrl_shift(&mut ui, gdk::Key::Page_Up);
+    assert_eq!(response, UiResponse::Redraw);
+    let settings = ui.assets.background.as_ref().unwrap().settings();
+    assert_eq!(settings.offset, (OFFSET_STEP, OFFSET_STEP));
+    assert!(settings.scale > 1.0);
+
+    // The image is no longer drawn at the top-left corner.
+    ui.draw();
+    let mut image = ui.canvas.copy_surface();
+    assert_eq!(pixel(&mut image, 0, 0), (255, 255, 255));
+
+    // The image settings are saved in the session.
+    let session_path = output_dir.join("background-session.json");
+    ui.session().write(&session_path).unwrap();
+    let session = Session::read(&session_path).unwrap();
+    assert_eq!(session.background.as_ref(), Some(settings));
+
+    // The image is not included in exported maps.
+    let map_path = output_dir.join("background-export.png");
+    let response = ui.save_map(map_path.clone(), 72.0);
+    ui.respond(response);
+    let mut file = std::fs::File::open(&map_path).unwrap();
+    let mut exported =
+        cairo::ImageSurface::create_from_png(&mut file).unwrap();
+    assert_eq!(pixel(&mut exported, 1, 1), (255, 255, 255));
+
+    // Hide the image.
+    let response = press_ctrl_shift(&mut ui, gdk::Key::h);
+    assert_eq!(response, UiResponse::Redraw);
+    assert!(!ui.assets.background.as_ref().unwrap().is_visible());
+    ui.draw();
+    let mut image = ui.canvas.copy_surface();
+    assert_eq!(pixel(&mut image, 10, 10), (255, 255, 255));
+
+    // Remove the image; the key bindings are no longer handled.
+    let response = press_ctrl_shift(&mut ui, gdk::Key::Delete);
+    assert_eq!(response, UiResponse::Redraw);
+    assert!(ui.assets.background.is_none());
+    let response = press_ctrl_shift(&mut ui, gdk::Key::Right);
+    assert_eq!(response, UiResponse::None);
+    assert!(ui.session().background.is_none());
+
+    // Restoring the session loads the image again.
+    let response = ui.restore_session(session_path);
+    ui.respond(response);
+    let settings = ui.assets.background.as_ref().unwrap().settings();
+    assert_eq!(settings.offset, (OFFSET_STEP, OFFSET_STEP));
+}
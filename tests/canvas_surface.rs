@@ -0,0 +1,79 @@
+/// Check that a canvas can draw onto image and recording surfaces, that each
+/// kind of surface reports the ink extents of what was drawn on it, and
+/// that resizing a canvas retains the kind of surface.
+use navig18xx::ui::{Canvas, Surface};
+
+/// Draws a filled rectangle `(x, y, width, height)` on the canvas.
+fn fill_rect(canvas: &Canvas, x: f64, y: f64, width: f64, height: f64) {
+    let ctx = canvas.context();
+    ctx.set_source_rgb(0.0, 0.0, 1.0);
+    ctx.rectangle(x, y, width, height);
+    ctx.fill().unwrap();
+}
+
+/// Returns the ink extents of a canvas, rounded to the nearest pixel.
+fn rounded_extents(canvas: &Canvas) -> (i32, i32, i32, i32) {
+    let (x0, y0, width, height) = canvas.ink_extents();
+    (
+        x0.round() as i32,
+        y0.round() as i32,
+        width.round() as i32,
+        height.round() as i32,
+    )
+}
+
+#[test]
+fn image_canvas() {
+    let mut canvas = Canvas::image(400, 300);
+    assert_eq!((canvas.width(), canvas.height()), (400, 300));
+    assert_eq!(rounded_extents(&canvas), (0, 0, 0, 0));
+
+    // The ink extents only contain the pixels that have been drawn.
+    fill_rect(&canvas, 50.0, 60.0, 100.0, 40.0);
+    fill_rect(&canvas, 200.0, 150.0, 10.0, 10.0);
+    assert_eq!(rounded_extents(&canvas), (50, 60, 160, 100));
+    let image = canvas.copy_surface();
+    assert_eq!((image.width(), image.height()), (400, 300));
+
+    // Image surfaces retain their contents when drawn again.
+    canvas.discard();
+    assert_eq!(rounded_extents(&canvas), (50, 60, 160, 100));
+
+    // Resizing the canvas replaces it with an empty image surface.
+    canvas.resize(200, 100);
+    assert_eq!((canvas.width(), canvas.height()), (200, 100));
+    assert_eq!(rounded_extents(&canvas), (0, 0, 0, 0));
+    fill_rect(&canvas, 150.0, 50.0, 100.0, 100.0);
+    assert_eq!(rounded_extents(&canvas), (150, 50, 50, 50));
+
+    // Surfaces without transparency are entirely covered in ink.
+    let surface =
+        cairo::ImageSurface::create(cairo::Format::Rgb24, 40, 30).unwrap();
+    assert_eq!(surface.ink_extents(), (0.0, 0.0, 40.0, 30.0));
+}
+
+#[test]
+fn recording_canvas() {
+    let mut canvas = Canvas::recording(400, 300);
+    assert_eq!((canvas.width(), canvas.height()), (400, 300));
+    assert_eq!(rounded_extents(&canvas), (0, 0, 0, 0));
+
+    fill_rect(&canvas, 50.0, 60.0, 100.0, 40.0);
+    fill_rect(&canvas, 200.0, 150.0, 10.0, 10.0);
+    assert_eq!(rounded_extents(&canvas), (50, 60, 160, 100));
+
+    // The recorded contents can be copied to an image.
+    let image = canvas.copy_surface();
+    assert_eq!((image.width(), image.height()), (400, 300));
+    let surface: Box<dyn Surface> = Box::new(image);
+    let copy = Canvas::with_surface(surface);
+    assert_eq!(rounded_extents(&copy), (50, 60, 160, 100));
+
+    // Resizing the canvas replaces it with an empty recording surface, and
+    // drawing is clipped to the surface bounds.
+    canvas.resize(200, 100);
+    assert_eq!((canvas.width(), canvas.height()), (200, 100));
+    assert_eq!(rounded_extents(&canvas), (0, 0, 0, 0));
+    fill_rect(&canvas, 150.0, 50.0, 100.0, 100.0);
+    assert_eq!(rounded_extents(&canvas), (150, 50, 50, 50));
+}
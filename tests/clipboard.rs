@@ -0,0 +1,88 @@
+/// Check that the visible part of the map, or a selected region of the map,
+/// can be copied to the clipboard as an image.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+/// Returns a user interface whose window shows part of the map.
+fn new_windowed_ui() -> UserInterface {
+    let mut dummy = control::DummyController::new();
+    dummy.set_window_size(400, 300);
+    dummy.set_viewport(50.0, 20.0);
+    new_ui_with(dummy)
+}
+
+/// Returns the dimensions of the image in the clipboard, if any.
+fn clipboard_dims(ui: &UserInterface) -> Option<(i32, i32)> {
+    let Controller::Dummy(dummy) = &ui.controller else {
+        panic!("Expected a dummy controller")
+    };
+    dummy
+        .clipboard_image()
+        .map(|image| (image.width(), image.height()))
+}
+
+#[test]
+fn copy_visible_map() {
+    let mut ui = new_windowed_ui();
+    assert_eq!(clipboard_dims(&ui), None);
+
+    // Ctrl+c without Shift does not copy the map.
+    press_with(&mut ui, gdk::Key::c, true, false);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::None);
+    assert_eq!(clipboard_dims(&ui), None);
+
+    press_with(&mut ui, gdk::Key::C, true, true);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::None);
+    assert_eq!(clipboard_dims(&ui), Some((400, 300)));
+    let region = ui.visible_region();
+    assert_eq!((region.x, region.y), (50.0, 20.0));
+
+    // This action can also be run from the command palette.
+    let commands = ui.keymap.commands(&ui.state);
+    let command = commands
+        .iter()
+        .find(|c| c.name == "Copy the map view to the clipboard")
+        .unwrap();
+    assert!(command.ctrl && command.shift);
+}
+
+#[test]
+fn copy_map_region() {
+    let mut ui = new_windowed_ui();
+    press(&mut ui, gdk::Key::i);
+    assert!(ui.state.as_screenshot().is_some());
+    press(&mut ui, gdk::Key::c);
+    assert!(ui.state.as_screenshot().unwrap().copies());
+    let title = ui.controller.window_title().unwrap();
+    assert!(title.starts_with("Copy map region"));
+
+    let drag = |x: f64, y: f64| ButtonDrag {
+        x0: 10.0,
+        y0: 10.0,
+        x,
+        y,
+        button: 1,
+        shift: false,
+        finished: true,
+    };
+    let response = ui.handle_button_drag(&drag(110.0, 60.0));
+    ui.respond(response);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::None);
+    assert_eq!(clipboard_dims(&ui), Some((100, 50)));
+
+    // Regions are copied at the chosen scale.
+    press(&mut ui, gdk::Key::_2);
+    let response = ui.handle_button_drag(&drag(60.0, 30.0));
+    ui.respond(response);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::None);
+    assert_eq!(clipboard_dims(&ui), Some((100, 40)));
+
+    // Switch back to saving regions.
+    press(&mut ui, gdk::Key::c);
+    assert!(!ui.state.as_screenshot().unwrap().copies());
+}
@@ -0,0 +1,146 @@
+/// Check that the command palette lists the actions that are available in
+/// each state, that each listed action is bound to its key press, and that
+/// the palette's search query matches action names.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+/// Returns a user interface that is showing a new 1830 game, where the
+/// active hex contains a city tile.
+fn ui_with_city() -> UserInterface {
+    let mut ui = new_ui();
+    let addr = hex_addr("C7");
+    assert!(ui.assets.place_tile(addr, "57", RotateCW::Zero));
+    ui.state.as_default_mut().unwrap().set_active_hex(addr);
+    ui
+}
+
+/// Returns a user interface that is selecting several map hexes.
+fn ui_selecting_hexes() -> UserInterface {
+    let mut ui = ui_with_city();
+    let (x, y) = hex_centre(&ui, hex_addr("C9"));
+    let event = ButtonPress {
+        x,
+        y,
+        button: 1,
+        ctrl: false,
+        alt: false,
+        shift: true,
+    };
+    let response = ui.handle_button_press(&event);
+    ui.respond(response);
+    assert!(ui.state.as_select().is_some());
+    ui
+}
+
+/// Returns a user interface that has entered a new state by pressing `key`
+/// in the default state.
+fn ui_after_key(key: gdk::Key) -> UserInterface {
+    let mut ui = ui_with_city();
+    press(&mut ui, key);
+    assert!(!ui.state.is_default_state());
+    ui
+}
+
+/// Returns the names of the actions that are available in the current
+/// state.
+fn command_names(ui: &UserInterface) -> Vec<&'static str> {
+    ui.keymap
+        .commands(&ui.state)
+        .iter()
+        .map(|command| command.name)
+        .collect()
+}
+
+/// Checks that each action that is listed in the current state of a new
+/// user interface is handled by the keymap, and that no two actions share
+/// the same name or key press.
+fn check_commands<F>(new_ui: F)
+where
+    F: Fn() -> UserInterface,
+{
+    let commands = new_ui().keymap.commands(&new_ui().state);
+    assert!(!commands.is_empty());
+    for (ix, command) in commands.iter().enumerate() {
+        for other in &commands[ix + 1..] {
+            assert_ne!(command.name, other.name);
+            assert_ne!(
+                (command.key, command.ctrl, command.shift),
+                (other.key, other.ctrl, other.shift),
+                "{}",
+                other.name
+            );
+        }
+        // Run each action in a new user interface, because an action may
+        // leave the current state.
+        let mut ui = new_ui();
+        let (sender, _receiver) = std::sync::mpsc::channel();
+        let handled = ui.keymap.handle_key_press(
+            &mut ui.assets,
+            &mut ui.controller,
+            &mut ui.state,
+            &mut ui.canvas,
+            &sender,
+            &command.key_press(),
+        );
+        assert!(handled.is_some(), "'{}' is not bound", command.name);
+    }
+}
+
+#[test]
+fn fuzzy_match_command_names() {
+    use control::fuzzy_match;
+
+    assert!(fuzzy_match("sgm", "Save game"));
+    assert!(fuzzy_match("SAVE", "Save game"));
+    assert!(fuzzy_match("save game", "Save game"));
+    assert!(fuzzy_match("", "Save game"));
+    // Characters must occur in the same order.
+    assert!(!fuzzy_match("gs", "Save game"));
+    // Each character in the text can only be matched once.
+    assert!(!fuzzy_match("ssave", "Save game"));
+    assert!(!fuzzy_match("load", "Save game"));
+}
+
+#[test]
+fn commands_depend_on_state() {
+    let games: Vec<Box<dyn Game>> =
+        vec![Box::new(navig18xx::game::new_1830())];
+    let controller: Controller = control::DummyController::new().into();
+    let start = UserInterface::new(games, controller, Keymap::default());
+    let start_names = command_names(&start);
+    assert!(start_names.contains(&"New game"));
+    assert!(start_names.contains(&"Select the interface language"));
+    // Actions that require a game are not available in the start state.
+    assert!(!start_names.contains(&"Save game"));
+    assert!(!start_names.contains(&"Place or replace a tile"));
+
+    let ui = ui_with_city();
+    let names = command_names(&ui);
+    assert!(names.contains(&"Save game"));
+    assert!(names.contains(&"Place or replace a tile"));
+    assert!(!names.contains(&"Save token changes"));
+    assert!(start_names.iter().all(|name| names.contains(name)));
+
+    let ui = ui_after_key(gdk::Key::t);
+    let names = command_names(&ui);
+    assert!(names.contains(&"Save token changes"));
+    assert!(!names.contains(&"Place or replace a tile"));
+}
+
+#[test]
+fn default_commands_are_bound() {
+    check_commands(ui_with_city);
+}
+
+#[test]
+fn mode_commands_are_bound() {
+    check_commands(ui_selecting_hexes);
+    check_commands(|| ui_after_key(gdk::Key::e));
+    check_commands(|| ui_after_key(gdk::Key::t));
+    check_commands(|| ui_after_key(gdk::Key::i));
+}
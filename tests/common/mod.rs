@@ -0,0 +1,131 @@
+//! Helper functions and fixtures that are shared by the integration tests.
+//!
+//! Each test file that uses these functions declares `mod common;`, and not
+//! every test file uses every function.
+#![allow(dead_code)]
+
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+/// Returns a user interface that is showing a new 1830 game.
+pub fn new_ui() -> UserInterface {
+    new_ui_with(control::DummyController::new())
+}
+
+/// Returns a user interface that uses the provided controller, and is
+/// showing a new 1830 game.
+pub fn new_ui_with(dummy: control::DummyController) -> UserInterface {
+    new_ui_for(Box::new(navig18xx::game::new_1830()), dummy)
+}
+
+/// Returns a user interface that uses the provided controller, and is
+/// showing a new game of the provided game.
+pub fn new_ui_for(
+    game: Box<dyn Game>,
+    dummy: control::DummyController,
+) -> UserInterface {
+    let games: Vec<Box<dyn Game>> = vec![game];
+    let controller: Controller = dummy.into();
+    let mut ui = UserInterface::new(games, controller, Keymap::default());
+    let response = ui.new_game(0);
+    ui.respond(response);
+    ui
+}
+
+/// Returns the dummy controller of a user interface.
+pub fn dummy(ui: &mut UserInterface) -> &mut control::DummyController {
+    let Controller::Dummy(dummy) = &mut ui.controller else {
+        panic!("Expected a dummy controller")
+    };
+    dummy
+}
+
+/// Presses a key without any modifier keys, responds to the event, and
+/// returns the response.
+pub fn press(ui: &mut UserInterface, key: gdk::Key) -> UiResponse {
+    press_with(ui, key, false, false)
+}
+
+/// Presses a key with the Ctrl and/or Shift modifier keys, responds to the
+/// event, and returns the response.
+pub fn press_with(
+    ui: &mut UserInterface,
+    key: gdk::Key,
+    ctrl: bool,
+    shift: bool,
+) -> UiResponse {
+    let event = KeyPress {
+        key,
+        ctrl,
+        alt: false,
+        shift,
+    };
+    let response = ui.handle_key_press(&event);
+    ui.respond(response);
+    response
+}
+
+/// Returns the address of a hex on the 1830 map.
+pub fn hex_addr(name: &str) -> HexAddress {
+    let game = navig18xx::game::new_1830();
+    game.coordinate_system().parse(name).unwrap()
+}
+
+/// Returns a 1867 map where there are two routes from Montreal, which pass
+/// through different token spaces on the same tile.
+///
+/// The X5 tile is placed in Montreal, and the first company's tokens are
+/// placed in two of its three token spaces.
+pub fn montreal_map(game: &dyn Game, hex: &Hex) -> Map {
+    let coords = game.coordinate_system();
+    let mut map = game.create_map(hex);
+    let company_token = *game.first_token();
+    let montreal = coords.parse("L12").unwrap();
+    assert!(map.place_tile(montreal, "X5", RotateCW::Zero));
+    let spaces = map.tile_at(montreal).unwrap().token_spaces();
+    let map_hex = map.hex_state_mut(montreal).unwrap();
+    map_hex.set_token_at(&spaces[0], company_token);
+    map_hex.set_token_at(&spaces[1], company_token);
+    let tiles = [
+        ("M13", "4", RotateCW::Zero),
+        ("M11", "7", RotateCW::One),
+        ("M9", "57", RotateCW::One),
+        ("L10", "58", RotateCW::Zero),
+        ("K13", "3", RotateCW::Four),
+    ];
+    for (addr, name, rotn) in tiles {
+        assert!(map.place_tile(coords.parse(addr).unwrap(), name, rotn));
+    }
+    map
+}
+
+/// Returns the centre of a map hex, in map coordinates.
+///
+/// These are the same coordinates as button press and pointer motion events,
+/// unless the map view is rotated or mirrored.
+pub fn hex_centre(ui: &UserInterface, addr: HexAddress) -> (f64, f64) {
+    let hex = &ui.assets.hex;
+    let centre = navig18xx::brush::badges::hex_centre_coord(
+        hex,
+        hex.context(),
+        &ui.assets.map,
+        addr,
+    );
+    (centre.x, centre.y)
+}
+
+/// Pings the current state until `done` returns `true`, such as waiting for
+/// a route search to finish, and panics if this takes more than 60 seconds.
+pub fn wait_until<F>(ui: &mut UserInterface, done: F)
+where
+    F: Fn(&UserInterface) -> bool,
+{
+    let start = std::time::Instant::now();
+    while !done(ui) {
+        assert!(start.elapsed().as_secs() < 60, "Timed out");
+        let _ = ui.ping(PingDest::State);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+}
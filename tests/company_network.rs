@@ -0,0 +1,51 @@
+/// Show a company's network, and check that only the hexes that contain the
+/// company's tokens (and any track connected to them) are included.
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+#[test]
+fn company_network_contains_token_hexes() {
+    let mut dummy = control::DummyController::new();
+    dummy.set_string(Some("Pennsylvania".to_string()));
+    let mut ui = new_ui_with(dummy);
+
+    // No company has placed a token, so there are no networks to show.
+    assert_eq!(press(&mut ui, gdk::Key::f), UiResponse::None);
+    assert!(ui.state.is_default_state());
+
+    // Place tokens for two companies in their home cities.
+    let coords = ui.assets.games.active().coordinate_system();
+    let prr_home = coords.parse("J8").unwrap();
+    let bo_home = coords.parse("O9").unwrap();
+    for (abbrev, addr) in [("PRR", prr_home), ("B&O", bo_home)] {
+        let token = ui.assets.map.try_token(abbrev).unwrap();
+        let space = ui.assets.map.tile_at(addr).unwrap().token_spaces()[0];
+        ui.assets
+            .map
+            .hex_state_mut(addr)
+            .unwrap()
+            .set_token_at(&space, token);
+    }
+
+    // Select the company and show its network.
+    assert_eq!(press(&mut ui, gdk::Key::f), UiResponse::Redraw);
+    let _ = ui.ping(PingDest::State);
+    assert_eq!(ui.state.mode(), "Company network");
+    let reach = ui.state.as_company_network().unwrap().reach();
+    assert!(!reach.is_empty());
+    assert_eq!(reach.token_hexes().iter().collect::<Vec<_>>(), [&prr_home]);
+    assert!(reach.contains_hex(prr_home));
+    // No tiles have been placed, so the B&O token cannot be reached.
+    assert!(!reach.contains_hex(bo_home));
+    assert!(reach.cities().any(|(addr, _ix)| addr == prr_home));
+
+    // Draw the company network, and return to the default mode.
+    let ctx = ui.assets.hex.context();
+    ui.state.draw(&ui.assets, ctx);
+    press(&mut ui, gdk::Key::Escape);
+    assert!(ui.state.is_default_state());
+}
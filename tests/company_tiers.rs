@@ -0,0 +1,126 @@
+/// Check that minor companies have a single token and can operate at most
+/// two trains, and that the user interface enforces these limits.
+use navig18xx::game::{CompanyTier, TierRules};
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+/// Returns a user interface that uses the provided controller, and is
+/// showing a new 1867 game.
+fn new_1867_ui_with(dummy: control::DummyController) -> UserInterface {
+    new_ui_for(Box::new(navig18xx::game::new_1867()), dummy)
+}
+
+/// Places the X5 tile in Montreal, which has several token spaces, and
+/// returns the address of this hex.
+fn montreal(ui: &mut UserInterface) -> HexAddress {
+    let game = ui.assets.games.active();
+    let addr = game.coordinate_system().parse("L12").unwrap();
+    assert!(ui.assets.map.place_tile(addr, "X5", RotateCW::Zero));
+    addr
+}
+
+#[test]
+fn tier_token_counts() {
+    let minor = TierRules {
+        token_count: Some(1),
+        train_limit: Some(2),
+    };
+    let games: Vec<Box<dyn Game>> = vec![
+        Box::new(navig18xx::game::new_1861()),
+        Box::new(navig18xx::game::new_1867()),
+    ];
+    for game in &games {
+        assert_eq!(game.tier_rules(CompanyTier::Minor), minor);
+        assert_eq!(game.tier_rules(CompanyTier::Major), TierRules::default());
+        for company in game.companies() {
+            let rules = game.company_rules(&company.abbrev).unwrap();
+            assert_eq!(rules, game.tier_rules(company.tier));
+        }
+        let minors = game
+            .companies()
+            .iter()
+            .filter(|c| c.tier == CompanyTier::Minor)
+            .count();
+        assert_eq!(minors, 16);
+    }
+    let game = navig18xx::game::new_1867();
+    assert_eq!(game.company_rules("BBG"), Some(minor));
+    assert_eq!(game.company_rules("CPR"), Some(TierRules::default()));
+    assert_eq!(game.company_rules("XYZ"), None);
+
+    // Games without minor companies do not limit tokens or trains.
+    let game = navig18xx::game::new_1830();
+    assert!(game
+        .companies()
+        .iter()
+        .all(|c| c.tier == CompanyTier::Major));
+    assert_eq!(game.company_rules("PRR"), Some(TierRules::default()));
+}
+
+#[test]
+fn edit_tokens_skips_placed_minors() {
+    let mut ui = new_1867_ui_with(control::DummyController::new());
+    let addr = montreal(&mut ui);
+    let game = ui.assets.games.active();
+    let first = *game.first_token();
+    let second = *game.next_token(&first).unwrap();
+    assert_eq!(game.company_rules("BBG").unwrap().token_count, Some(1));
+    assert_eq!(first, *game.token("BBG"));
+
+    // Place the first minor company's only token in Montreal.
+    let spaces = ui.assets.map.tile_at(addr).unwrap().token_spaces();
+    let hex_state = ui.assets.map.hex_state_mut(addr).unwrap();
+    hex_state.set_token_at(&spaces[0], first);
+
+    // Selecting a token for the second token space skips this company.
+    ui.state.as_default_mut().unwrap().set_active_hex(addr);
+    assert_eq!(press(&mut ui, gdk::Key::t), UiResponse::Redraw);
+    assert!(ui.state.as_edit_tokens().is_some());
+    press(&mut ui, gdk::Key::Right);
+    press(&mut ui, gdk::Key::Up);
+    let hex_state = ui.assets.map.hex_state(addr).unwrap();
+    assert_eq!(hex_state.token_at(&spaces[1]), Some(&second));
+    assert_eq!(hex_state.token_at(&spaces[0]), Some(&first));
+
+    // The company's token can be replaced, and then selected again.
+    press(&mut ui, gdk::Key::Left);
+    press(&mut ui, gdk::Key::Up);
+    press(&mut ui, gdk::Key::Down);
+    let hex_state = ui.assets.map.hex_state(addr).unwrap();
+    assert_eq!(hex_state.token_at(&spaces[0]), Some(&first));
+}
+
+#[test]
+fn minor_train_limit() {
+    let game = navig18xx::game::new_1867();
+    let train = *game.try_train("2").unwrap();
+    let trains: Trains = vec![train, train, train].into();
+    let name = "Buffalo, Brantford and Goderich Railway (minor)";
+    let mut ctrl = control::DummyController::new();
+    ctrl.set_string(Some(name.to_string()));
+    ctrl.set_trains(Some((trains, vec![])));
+    let mut ui = new_1867_ui_with(ctrl);
+    let addr = montreal(&mut ui);
+    let token = *ui.assets.games.active().token("BBG");
+    let spaces = ui.assets.map.tile_at(addr).unwrap().token_spaces();
+    let hex_state = ui.assets.map.hex_state_mut(addr).unwrap();
+    hex_state.set_token_at(&spaces[0], token);
+
+    // The minor company cannot operate three trains.
+    assert_eq!(press(&mut ui, gdk::Key::r), UiResponse::Redraw);
+    let _ = ui.ping(PingDest::State);
+    let _ = ui.ping(PingDest::State);
+    assert_eq!(
+        dummy(&mut ui).last_error(),
+        Some(
+            "Too many trains: BBG is a minor company and can operate at \
+             most 2 trains"
+        )
+    );
+    assert!(ui.state.as_find_routes_found().is_none());
+}
@@ -149,6 +149,7 @@ fn test_connection_bonus_between_two_dits() {
         path_limit: limit,
         conflict_rule: ConflictRule::TrackOrCityHex,
         route_conflict_rule: ConflictRule::TrackOnly,
+        token_rule: RouteTokenRule::AnyToken,
     };
 
     let paths = paths_for_token(&map, &criteria);
@@ -0,0 +1,152 @@
+/// Check the dividends that companies pay in each of the built-in games,
+/// including how per-share dividends are rounded and how much revenue is
+/// withheld.
+use navig18xx::game::{
+    CompanyTier, DividendKind, DividendOptions, Dividends, Game, Rounding,
+};
+
+/// Returns the dividend options for the first company in the given tier.
+fn options_for(game: &dyn Game, tier: CompanyTier) -> DividendOptions {
+    let company = game
+        .companies()
+        .iter()
+        .find(|company| company.tier == tier)
+        .unwrap();
+    game.dividend_options(&company.abbrev).unwrap()
+}
+
+/// Returns the payment for a single share, and the withheld amount, for
+/// each dividend option.
+fn payouts(dividends: &[Dividends]) -> Vec<(DividendKind, usize, usize)> {
+    dividends
+        .iter()
+        .map(|d| (d.kind, d.share_payments[0], d.withheld.unwrap_or(0)))
+        .collect()
+}
+
+#[test]
+fn dividends_1830_and_1889() {
+    let games: Vec<Box<dyn Game>> = vec![
+        Box::new(navig18xx::game::new_1830()),
+        Box::new(navig18xx::game::new_1889()),
+    ];
+    for game in games {
+        assert_eq!(game.share_rounding(), Rounding::Down);
+        let options = options_for(game.as_ref(), CompanyTier::Major);
+        assert_eq!(options.share_count, 10);
+
+        // Revenue that divides evenly between the shares is paid in full.
+        let dividends = options.dividends(230);
+        assert_eq!(
+            payouts(&dividends),
+            vec![
+                (DividendKind::Full, 23, 0),
+                (DividendKind::Withhold, 0, 230)
+            ]
+        );
+        assert_eq!(dividends[0].share_payments[9], 230);
+        assert_eq!(dividends[1].share_payments[9], 0);
+
+        // The company withholds any remainder.
+        let dividends = options.dividends(235);
+        assert_eq!(
+            payouts(&dividends),
+            vec![
+                (DividendKind::Full, 23, 5),
+                (DividendKind::Withhold, 0, 235)
+            ]
+        );
+
+        // Revenue that is smaller than the number of shares is withheld.
+        let dividends = options.dividends(5);
+        assert_eq!(
+            payouts(&dividends),
+            vec![(DividendKind::Full, 0, 5), (DividendKind::Withhold, 0, 5)]
+        );
+
+        // Nothing is withheld when there is no revenue.
+        let dividends = options.dividends(0);
+        assert!(dividends.iter().all(|d| d.withheld.is_none()));
+    }
+}
+
+#[test]
+fn dividends_1861_and_1867() {
+    let games: Vec<Box<dyn Game>> = vec![
+        Box::new(navig18xx::game::new_1861()),
+        Box::new(navig18xx::game::new_1867()),
+    ];
+    let half_pay = DividendKind::Half {
+        rounding: Rounding::Up,
+        nearest: 10,
+    };
+    for game in games {
+        assert_eq!(game.share_rounding(), Rounding::Down);
+
+        // Minor companies must pay half of their revenue, rounded up to the
+        // nearest 10, to their owner.
+        let options = options_for(game.as_ref(), CompanyTier::Minor);
+        assert_eq!(options.share_count, 1);
+        assert_eq!(payouts(&options.dividends(40)), vec![(half_pay, 20, 20)]);
+        assert_eq!(payouts(&options.dividends(30)), vec![(half_pay, 20, 10)]);
+        assert_eq!(payouts(&options.dividends(10)), vec![(half_pay, 10, 0)]);
+        assert_eq!(payouts(&options.dividends(0)), vec![(half_pay, 0, 0)]);
+
+        // Major companies may pay their full revenue, half of their revenue,
+        // or withhold.
+        let options = options_for(game.as_ref(), CompanyTier::Major);
+        assert_eq!(options.share_count, 10);
+        assert_eq!(
+            payouts(&options.dividends(120)),
+            vec![
+                (DividendKind::Full, 12, 0),
+                (half_pay, 6, 60),
+                (DividendKind::Withhold, 0, 120),
+            ]
+        );
+
+        // Half-pay is rounded up to the nearest 10 before it is divided
+        // between the shares.
+        assert_eq!(
+            payouts(&options.dividends(130)),
+            vec![
+                (DividendKind::Full, 13, 0),
+                (half_pay, 7, 60),
+                (DividendKind::Withhold, 0, 130),
+            ]
+        );
+
+        // The per-share dividend is rounded down.
+        assert_eq!(
+            payouts(&options.dividends(15)),
+            vec![
+                (DividendKind::Full, 1, 5),
+                (half_pay, 1, 5),
+                (DividendKind::Withhold, 0, 15),
+            ]
+        );
+    }
+}
+
+#[test]
+fn dividends_round_up() {
+    // Rounding up never pays shareholders more than the company earned.
+    let options = DividendOptions {
+        share_count: 10,
+        dividend_options: vec![
+            (DividendKind::Full, Rounding::Up),
+            (
+                DividendKind::Half {
+                    rounding: Rounding::Down,
+                    nearest: 1,
+                },
+                Rounding::Up,
+            ),
+        ],
+    };
+    let dividends = options.dividends(25);
+    assert_eq!(dividends[0].share_payments[0], 2);
+    assert_eq!(dividends[0].withheld, Some(5));
+    assert_eq!(dividends[1].share_payments[0], 2);
+    assert_eq!(dividends[1].withheld, Some(5));
+}
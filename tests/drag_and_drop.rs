@@ -0,0 +1,82 @@
+/// Check that dropping a saved game onto the window loads the game, that
+/// dropping a tile catalogue merges its tiles into the current map, and that
+/// other files are ignored.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use std::path::{Path, PathBuf};
+
+mod common;
+use common::*;
+
+/// Returns the path of a file in the test output directory.
+fn output_file(name: &str) -> PathBuf {
+    let output_dir = Path::new("./tests/output/drag-and-drop");
+    std::fs::create_dir_all(output_dir).unwrap();
+    output_dir.join(name)
+}
+
+/// Drops a file onto the window, and returns the response to the action
+/// for this file (if any).
+fn drop_file(ui: &mut UserInterface, path: &Path) -> UiResponse {
+    assert!(dummy(ui).drop_file(path.to_path_buf()));
+    ui.ping(PingDest::TopLevel)
+}
+
+#[test]
+fn drop_saved_game() {
+    let path = output_file("game.json");
+    let addr = hex_addr("C7");
+    let mut ui = new_ui();
+    assert!(ui.assets.place_tile(addr, "8", RotateCW::Two));
+    let state = ui.assets.game_state();
+    navig18xx::io::write_game_state(&path, state, true).unwrap();
+
+    let mut ui = new_ui();
+    assert!(ui.assets.map.tile_at(addr).is_none());
+    assert_eq!(drop_file(&mut ui, &path), UiResponse::ResetGame);
+    assert_eq!(ui.assets.map.tile_at(addr).unwrap().name, "8");
+    assert!(dummy(&mut ui).last_error().is_none());
+
+    // Invalid game states are reported, and the current game is unchanged.
+    let invalid = output_file("invalid-game.json");
+    std::fs::write(&invalid, r#"{"game": "1830"}"#).unwrap();
+    assert_eq!(drop_file(&mut ui, &invalid), UiResponse::None);
+    assert!(dummy(&mut ui).last_error().is_some());
+    assert_eq!(ui.assets.map.tile_at(addr).unwrap().name, "8");
+}
+
+#[test]
+fn drop_tile_catalogue() {
+    let path = output_file("tiles.json");
+    let mut ui = new_ui();
+    let mut tile = ui.assets.map.tile("57").unwrap().clone();
+    tile.name = "57-dropped".to_string();
+    navig18xx::io::write_tiles(&path, [&tile], true).unwrap();
+
+    assert!(ui.assets.map.tile("57-dropped").is_none());
+    assert_eq!(drop_file(&mut ui, &path), UiResponse::Redraw);
+    assert!(ui.assets.map.tile("57-dropped").is_some());
+    let addr = hex_addr("C7");
+    assert!(ui.assets.place_tile(addr, "57-dropped", RotateCW::Zero));
+
+    // Tiles can only be added once a game has been started.
+    let games: Vec<Box<dyn Game>> =
+        vec![Box::new(navig18xx::game::new_1830())];
+    let controller: Controller = control::DummyController::new().into();
+    let mut ui = UserInterface::new(games, controller, Keymap::default());
+    assert_eq!(drop_file(&mut ui, &path), UiResponse::None);
+    let error = dummy(&mut ui).last_error().unwrap();
+    assert!(error.starts_with("Could not add tiles"));
+}
+
+#[test]
+fn drop_other_files() {
+    let path = output_file("map.pdf");
+    std::fs::write(&path, "Not a real PDF").unwrap();
+    let mut ui = new_ui();
+    let state = ui.assets.game_state();
+    assert_eq!(drop_file(&mut ui, &path), UiResponse::None);
+    assert!(dummy(&mut ui).last_error().is_none());
+    assert_eq!(ui.assets.game_state(), state);
+}
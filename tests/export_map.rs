@@ -0,0 +1,78 @@
+/// Check that the map can be exported as a PNG image at a chosen print
+/// resolution, independent of the current zoom level.
+use navig18xx::brush::{dpi_scale, ImageFormat};
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+/// Returns the width and height of a PNG image.
+fn png_dims(path: &std::path::Path) -> (i32, i32) {
+    let mut file = std::fs::File::open(path).unwrap();
+    let image = cairo::ImageSurface::create_from_png(&mut file).unwrap();
+    (image.width(), image.height())
+}
+
+/// Exports the map at the `nth` resolution that the user can choose, and
+/// returns the dimensions of the exported image.
+fn export_map(ui: &mut UserInterface, nth: usize, name: &str) -> (i32, i32) {
+    let output_dir = std::path::Path::new("./tests/output");
+    std::fs::create_dir_all(output_dir).unwrap();
+    let path = output_dir.join(name);
+    let ctrl = dummy(ui);
+    ctrl.set_index(Some(nth));
+    // Do not add a title block and legend.
+    ctrl.set_confirm(false);
+    ctrl.set_screenshot_save_path(Some(path.clone()));
+    assert_eq!(press_with(ui, gdk::Key::e, true, false), UiResponse::None);
+    // Select the resolution, decline the legend, and select the file.
+    for _ in 0..3 {
+        assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::None);
+    }
+    assert!(dummy(ui).last_error().is_none());
+    png_dims(&path)
+}
+
+#[test]
+fn save_image_at_scale() {
+    assert_eq!(dpi_scale(72.0), 1.0);
+    assert_eq!(dpi_scale(144.0), 2.0);
+
+    let output_dir = std::path::Path::new("./tests/output");
+    std::fs::create_dir_all(output_dir).unwrap();
+    let path = output_dir.join("export-scale.png");
+    let draw = |ctx: &cairo::Context| {
+        ctx.rectangle(0.0, 0.0, 100.0, 50.0);
+        ctx.fill().unwrap();
+    };
+    ImageFormat::Png
+        .save_image(100.0, 50.0, 2.5, draw, &path)
+        .unwrap();
+    assert_eq!(png_dims(&path), (250, 125));
+}
+
+#[test]
+fn export_map_at_chosen_resolution() {
+    let mut ui = new_ui();
+    // The first resolution is 72 DPI, at which the map is drawn at its
+    // default size.
+    let (width, height) = export_map(&mut ui, 0, "export-72dpi.png");
+    assert!(width > 0 && height > 0);
+
+    // The image dimensions are proportional to the chosen resolution.
+    let (width_300, height_300) = export_map(&mut ui, 2, "export-300dpi.png");
+    let ratio = 300.0 / 72.0;
+    assert!((width_300 as f64 - ratio * width as f64).abs() <= ratio);
+    assert!((height_300 as f64 - ratio * height as f64).abs() <= ratio);
+
+    // The image dimensions do not depend on the current zoom level, except
+    // for small differences due to margins that have absolute sizes.
+    press(&mut ui, gdk::Key::plus);
+    press(&mut ui, gdk::Key::plus);
+    let (zoomed_width, zoomed_height) =
+        export_map(&mut ui, 0, "export-zoomed.png");
+    assert!((zoomed_width - width).abs() <= width / 100);
+    assert!((zoomed_height - height).abs() <= height / 100);
+}
@@ -0,0 +1,53 @@
+/// Check that errors when loading and saving files are reported to the user,
+/// rather than causing a panic, and that the current game is unchanged.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use std::path::Path;
+
+mod common;
+use common::*;
+
+fn last_error(ui: &UserInterface) -> Option<String> {
+    let Controller::Dummy(dummy) = &ui.controller else {
+        panic!("Expected a dummy controller")
+    };
+    dummy.last_error().map(|msg| msg.to_string())
+}
+
+#[test]
+fn file_errors_are_reported() {
+    let output_dir = Path::new("./tests/output/file_errors");
+    std::fs::create_dir_all(output_dir).unwrap();
+    let missing_dir = output_dir.join("no-such-directory");
+    let mut ui = new_ui();
+    let revision = ui.assets.map.revision();
+
+    // Saving a game to a directory that does not exist.
+    let response = ui.save_game(missing_dir.join("game.json"));
+    assert_eq!(response, UiResponse::None);
+    let error = last_error(&ui).unwrap();
+    assert!(error.starts_with("Could not save game"));
+
+    // Saving an image to a directory that does not exist.
+    let image =
+        cairo::ImageSurface::create(cairo::Format::ARgb32, 10, 10).unwrap();
+    let response = ui.save_image(missing_dir.join("image.png"), image);
+    assert_eq!(response, UiResponse::None);
+    let error = last_error(&ui).unwrap();
+    assert!(error.starts_with("Could not save image"));
+
+    // Loading a malformed game file.
+    let bad_file = output_dir.join("malformed.json");
+    std::fs::write(&bad_file, "{ this is not a game").unwrap();
+    let response = ui.load_game(bad_file);
+    assert_eq!(response, UiResponse::None);
+    let error = last_error(&ui).unwrap();
+    assert!(error.starts_with("Could not load game"));
+
+    // The current game is unchanged, and can still be saved.
+    assert_eq!(ui.assets.map.revision(), revision);
+    let response = ui.save_game(output_dir.join("game.json"));
+    ui.respond(response);
+    assert_eq!(last_error(&ui), Some(error));
+}
@@ -0,0 +1,83 @@
+/// Check that the hex inspector panel describes the active hex, follows the
+/// mouse pointer, and can be closed again.
+use navig18xx::prelude::*;
+use navig18xx::ui::panel::HexSummary;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+fn hex_panel(ui: &UserInterface) -> Option<&HexSummary> {
+    let Controller::Dummy(dummy) = &ui.controller else {
+        panic!("Expected a dummy controller")
+    };
+    match dummy.panel(PanelKind::Hex) {
+        Some(Panel::Hex(summary)) => Some(summary),
+        Some(_) => panic!("Expected a hex panel"),
+        None => None,
+    }
+}
+
+#[test]
+fn inspect_hexes() {
+    let mut ui = new_ui();
+
+    // Inspect the active hex.
+    let active = ui.state.active_hex().unwrap();
+    press(&mut ui, gdk::Key::h);
+    let summary = hex_panel(&ui).unwrap();
+    assert_eq!(summary.description.addr, active);
+    assert_eq!(summary.name, ui.assets.hex_name(active));
+    let panel = Panel::Hex(summary.clone());
+    assert_eq!(
+        panel.title(&ui.assets.strings),
+        format!("Hex {}", summary.name)
+    );
+    // The panel title is shown in the selected language.
+    let mut strings = lang::Strings::english();
+    strings.language = "Français".to_string();
+    strings
+        .strings
+        .insert("Hex {name}".to_string(), "Case {name}".to_string());
+    assert_eq!(panel.title(&strings), format!("Case {}", summary.name));
+    let (headings, rows) = panel.table();
+    assert_eq!(headings, vec!["Property", "Value"]);
+    assert!(rows.iter().any(|row| row[0] == "Upgrades"));
+
+    // Move the mouse pointer over a city, and check that the panel shows
+    // this city's revenue.
+    let map = &ui.assets.map;
+    let city_hex = map
+        .hex_address_iter()
+        .copied()
+        .find(|addr| {
+            *addr != active
+                && map
+                    .tile_at(*addr)
+                    .is_some_and(|tile| !tile.cities().is_empty())
+        })
+        .unwrap();
+    let revenue = map.tile_at(city_hex).unwrap().cities()[0].revenue;
+    let (x, y) = hex_centre(&ui, city_hex);
+    let response = ui.handle_pointer_motion(&PointerMotion { x, y });
+    ui.respond(response);
+    let summary = hex_panel(&ui).unwrap();
+    assert_eq!(summary.description.addr, city_hex);
+    assert_eq!(summary.description.cities[0].revenue, revenue);
+    let (_headings, rows) = Panel::Hex(summary.clone()).table();
+    let city_row = rows.iter().find(|row| row[0] == "City 1").unwrap();
+    assert!(city_row[1].starts_with(&format!("${}", revenue)));
+
+    // Redrawing the map shows the active hex again.
+    ui.respond(UiResponse::Redraw);
+    assert_eq!(hex_panel(&ui).unwrap().description.addr, active);
+
+    // Close the panel; pointer motion should not re-open it.
+    press(&mut ui, gdk::Key::h);
+    assert!(hex_panel(&ui).is_none());
+    let response = ui.handle_pointer_motion(&PointerMotion { x, y });
+    ui.respond(response);
+    assert!(hex_panel(&ui).is_none());
+}
@@ -0,0 +1,108 @@
+/// Check that the hex context menu offers the actions that can be applied to
+/// the active hex, and runs the selected action.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+/// Makes `name` the active hex, and returns the names of the actions that
+/// can be applied to this hex.
+fn hex_actions(ui: &mut UserInterface, name: &str) -> Vec<&'static str> {
+    let game = navig18xx::game::new_1830();
+    let addr = game.coordinate_system().parse(name).unwrap();
+    ui.state.as_default_mut().unwrap().set_active_hex(addr);
+    ui.keymap
+        .hex_commands(&ui.assets, &ui.state)
+        .iter()
+        .map(|command| command.name)
+        .collect()
+}
+
+/// Opens the hex context menu, and returns the menu items.
+fn show_menu(ui: &mut UserInterface) -> Vec<String> {
+    let event = KeyPress {
+        key: gdk::Key::Menu,
+        ctrl: false,
+        alt: false,
+        shift: false,
+    };
+    let response = ui.handle_key_press(&event);
+    ui.respond(response);
+    let response = ui.ping(PingDest::TopLevel);
+    ui.respond(response);
+    dummy(ui).context_menu().unwrap().to_vec()
+}
+
+#[test]
+fn actions_depend_on_hex() {
+    let mut ui = new_ui();
+
+    // An empty hex.
+    let actions = hex_actions(&mut ui, "C7");
+    assert!(actions.contains(&HexAction::PlaceTile.command_name()));
+    assert!(!actions.contains(&HexAction::RemoveTile.command_name()));
+    assert!(!actions.contains(&HexAction::EditTokens.command_name()));
+    assert!(actions.contains(&HexAction::AddNote.command_name()));
+    assert!(actions.contains(&HexAction::Inspect.command_name()));
+    let items = show_menu(&mut ui);
+    assert_eq!(items.len(), actions.len());
+    assert!(items.contains(&"Inspect the active hex".to_string()));
+
+    // A hex with a city.
+    let actions = hex_actions(&mut ui, "B4");
+    assert!(actions.contains(&HexAction::RotateClockwise.command_name()));
+    assert!(actions.contains(&HexAction::RemoveTile.command_name()));
+    assert!(actions.contains(&HexAction::EditTokens.command_name()));
+    let items = show_menu(&mut ui);
+    assert_eq!(items.len(), actions.len());
+
+    // No actions are offered when selecting hexes.
+    press_select(&mut ui);
+    assert!(ui.state.as_select().is_some());
+    assert!(ui.keymap.hex_commands(&ui.assets, &ui.state).is_empty());
+}
+
+/// Selects the active hex, by clicking on it with the Shift key pressed.
+fn press_select(ui: &mut UserInterface) {
+    let addr = ui.state.active_hex().unwrap();
+    let (x, y) = hex_centre(ui, addr);
+    let event = ButtonPress {
+        x,
+        y,
+        button: ButtonPress::PRIMARY,
+        ctrl: false,
+        alt: false,
+        shift: true,
+    };
+    let response = ui.handle_button_press(&event);
+    ui.respond(response);
+}
+
+#[test]
+fn run_selected_action() {
+    let mut ui = new_ui();
+    let actions = hex_actions(&mut ui, "B4");
+    let edit_tokens = HexAction::EditTokens.command_name();
+    let ix = actions.iter().position(|name| *name == edit_tokens);
+    dummy(&mut ui).set_index(ix);
+    show_menu(&mut ui);
+    let response = ui.ping(PingDest::TopLevel);
+    ui.respond(response);
+    let state = ui.state.as_edit_tokens().unwrap();
+    assert_eq!(Some(state.active_hex()), ui.state.active_hex());
+}
+
+#[test]
+fn menu_shown_at_hex_centre() {
+    let mut ui = new_ui();
+    hex_actions(&mut ui, "B4");
+    show_menu(&mut ui);
+    // The centre of B4 on the 1830 map, with the default hex size.
+    let (x, y) = dummy(&mut ui).context_menu_position().unwrap();
+    assert!((x - 118.25).abs() < 0.01, "x = {}", x);
+    assert!((y - 353.75).abs() < 0.01, "y = {}", y);
+    assert_eq!((x, y), hex_centre(&ui, hex_addr("B4")));
+}
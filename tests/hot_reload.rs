@@ -0,0 +1,113 @@
+//! Check that watched tile catalogues and map configurations are applied to
+//! the current game, that they are reloaded when they change, and that
+//! invalid files are reported.
+#![cfg(feature = "hot-reload")]
+use navig18xx::prelude::*;
+use navig18xx::ui::watch::{spawn_watcher, WatchedFiles};
+use navig18xx::ui::*;
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, SystemTime};
+
+mod common;
+use common::*;
+
+/// Returns the path of a file in the test output directory, after removing
+/// any previous copy of this file.
+fn output_file(name: &str) -> PathBuf {
+    let output_dir = Path::new("./tests/output/hot-reload");
+    std::fs::create_dir_all(output_dir).unwrap();
+    let path = output_dir.join(name);
+    if path.exists() {
+        std::fs::remove_file(&path).unwrap();
+    }
+    path
+}
+
+/// Changes the modification time of a file, so that the change is detected
+/// even if the file system records modification times in whole seconds.
+fn touch(path: &Path, secs: u64) {
+    let file = std::fs::File::options().write(true).open(path).unwrap();
+    let time = SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+    file.set_modified(time).unwrap();
+}
+
+#[test]
+fn watcher_pings_when_files_change() {
+    let path = output_file("watched.json");
+    std::fs::write(&path, "[]").unwrap();
+    touch(&path, 1_000_000);
+    let files = WatchedFiles {
+        tiles: vec![path.clone()],
+        map: None,
+    };
+    assert!(!files.is_empty());
+    assert!(WatchedFiles::default().is_empty());
+
+    let (ping_tx, ping_rx) = std::sync::mpsc::channel();
+    let interval = Duration::from_millis(10);
+    let handle = spawn_watcher(&files, PingSender::Mpsc(ping_tx), interval);
+    // Unmodified files do not trigger a ping.
+    let timeout = Duration::from_millis(100);
+    assert!(ping_rx.recv_timeout(timeout).is_err());
+
+    touch(&path, 2_000_000);
+    let ping = ping_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(matches!(ping, PingDest::ReloadFiles));
+    assert!(ping_rx.recv_timeout(timeout).is_err());
+
+    // The watcher exits once the pings can no longer be delivered.
+    drop(ping_rx);
+    touch(&path, 3_000_000);
+    handle.join().unwrap();
+}
+
+#[test]
+fn reload_tiles_and_map() {
+    let tiles_path = output_file("tiles.json");
+    let map_path = output_file("map.json");
+    let mut ui = new_ui();
+    let addr = hex_addr("C7");
+
+    // Define a new tile, and a map configuration that places this tile.
+    let mut tile = ui.assets.map.tile("57").unwrap().clone();
+    tile.name = "57-test".to_string();
+    navig18xx::io::write_tiles(&tiles_path, [&tile], true).unwrap();
+    let mut map = ui.assets.map.clone();
+    map.update_tiles([tile]);
+    assert!(map.place_tile(addr, "57-test", RotateCW::One));
+    let descr: navig18xx::map::descr::Descr = (&map).into();
+    navig18xx::io::write_map_descr(&map_path, &descr, true).unwrap();
+
+    // Subscribers are told that the map was reset when the tiles and the
+    // map are reloaded, because any tile may have changed.
+    let events = Rc::new(RefCell::new(vec![]));
+    let received = events.clone();
+    ui.assets
+        .subscribe(move |event| received.borrow_mut().push(event.clone()));
+
+    let files = WatchedFiles {
+        tiles: vec![tiles_path.clone()],
+        map: Some(map_path.clone()),
+    };
+    assert_eq!(ui.watch_files(files), UiResponse::Redraw);
+    assert_eq!(ui.assets.map.tile_at(addr).unwrap().name, "57-test");
+    assert_eq!(*events.borrow(), vec![MapEvent::MapReset; 2]);
+
+    // Changes to the watched files are applied when they are reloaded.
+    ui.assets.map.remove_tile(addr);
+    assert!(ui.assets.map.tile_at(addr).is_none());
+    assert_eq!(ui.reload_files(), UiResponse::Redraw);
+    assert_eq!(ui.assets.map.tile_at(addr).unwrap().name, "57-test");
+    assert_eq!(events.borrow().len(), 4);
+
+    // Invalid files are reported, and are not applied.
+    std::fs::write(&tiles_path, "[{").unwrap();
+    ui.assets.map.remove_tile(addr);
+    assert_eq!(ui.reload_files(), UiResponse::None);
+    let error = dummy(&mut ui).last_error().unwrap().to_string();
+    assert!(error.starts_with("Could not reload files"));
+    assert!(ui.assets.map.tile_at(addr).is_none());
+}
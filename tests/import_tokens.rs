@@ -0,0 +1,82 @@
+/// Place company tokens from a list of token placements, check that these
+/// placements are recorded in the journal and can be undone, and check that
+/// an invalid list is reported without placing any tokens.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+use std::path::Path;
+
+mod common;
+use common::*;
+
+/// Presses a key with the Ctrl modifier key, and responds to the event and
+/// to any resulting message.
+fn press_ctrl(
+    ui: &mut UserInterface,
+    key: gdk::Key,
+    shift: bool,
+) -> UiResponse {
+    press_with(ui, key, true, shift);
+    let response = ui.ping(PingDest::TopLevel);
+    ui.respond(response);
+    response
+}
+
+#[test]
+fn import_company_tokens() {
+    let output_dir = Path::new("./tests/output/import_tokens");
+    std::fs::create_dir_all(output_dir).unwrap();
+    let valid_list = output_dir.join("valid.json");
+    std::fs::write(
+        &valid_list,
+        r#"[{"company": "PRR", "hex": "E5", "city": 1},
+            {"company": "NYC", "hex": "E5"}]"#,
+    )
+    .unwrap();
+    let invalid_list = output_dir.join("invalid.json");
+    std::fs::write(
+        &invalid_list,
+        r#"[{"company": "B&O", "hex": "O9"},
+            {"company": "XYZ", "hex": "E5"}]"#,
+    )
+    .unwrap();
+
+    let mut ui = new_ui();
+    assert!(ui.state.is_default_state());
+
+    let coords = ui.assets.games.active().coordinate_system();
+    let e5 = coords.parse("E5").unwrap();
+    let o9 = coords.parse("O9").unwrap();
+    let prr = ui.assets.map.try_token("PRR").unwrap();
+    let nyc = ui.assets.map.try_token("NYC").unwrap();
+    let original = ui.assets.map.clone();
+
+    // Import the valid list.
+    dummy(&mut ui).set_token_list_path(Some(valid_list));
+    assert_eq!(press_ctrl(&mut ui, gdk::Key::T, true), UiResponse::Redraw);
+    let spaces = ui.assets.map.tile_at(e5).unwrap().token_spaces();
+    let hex_state = ui.assets.map.hex_state(e5).unwrap();
+    assert_eq!(hex_state.token_at(&spaces[0]), Some(&nyc));
+    assert_eq!(hex_state.token_at(&spaces[1]), Some(&prr));
+    let entries = ui.assets.journal.entries();
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0].contains("PRR"));
+    assert!(entries[1].contains("NYC"));
+    assert!(dummy(&mut ui).last_error().is_none());
+
+    // Undo the imported tokens.
+    press_ctrl(&mut ui, gdk::Key::z, false);
+    assert_eq!(ui.assets.map.hex_state(e5), original.hex_state(e5));
+
+    // Import the invalid list, which places no tokens.
+    dummy(&mut ui).set_token_list_path(Some(invalid_list));
+    assert_eq!(press_ctrl(&mut ui, gdk::Key::T, true), UiResponse::None);
+    assert!(dummy(&mut ui).last_error().unwrap().contains("XYZ"));
+    assert_eq!(ui.assets.map.hex_state(o9), original.hex_state(o9));
+    assert_eq!(ui.assets.map.hex_state(e5), original.hex_state(e5));
+
+    // Cancelling the selection does nothing.
+    dummy(&mut ui).set_token_list_path(None);
+    assert_eq!(press_ctrl(&mut ui, gdk::Key::T, true), UiResponse::None);
+}
@@ -0,0 +1,119 @@
+/// Check that user interface messages can be translated into a language
+/// that is read from a JSON file, that the user can select this language,
+/// and that the selected language is saved with the session.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+/// Returns a language that translates a small number of messages.
+fn french() -> lang::Strings {
+    let mut strings = lang::Strings::english();
+    strings.language = "Français".to_string();
+    for (text, translation) in [
+        ("Select a game", "Choisissez un jeu"),
+        (
+            "There is no game called '{game}'",
+            "Il n'y a pas de jeu '{game}'",
+        ),
+        ("Select the interface language", "Choisissez la langue"),
+        ("No paths", "Aucun chemin"),
+        (
+            "{paths} paths, {stops} stops",
+            "{paths} chemins, {stops} arrêts",
+        ),
+    ] {
+        strings
+            .strings
+            .insert(text.to_string(), translation.to_string());
+    }
+    strings
+}
+
+#[test]
+fn translate_messages() {
+    let english = lang::Strings::default();
+    assert!(english.is_english());
+    assert_eq!(english.tr("Select a game"), "Select a game");
+
+    let strings = french();
+    assert!(!strings.is_english());
+    assert_eq!(strings.tr("Select a game"), "Choisissez un jeu");
+    // Messages that have not been translated are shown in English.
+    assert_eq!(strings.tr("Save game"), "Save game");
+    let msg = strings
+        .format("There is no game called '{game}'", &[("game", &"1234")]);
+    assert_eq!(msg, "Il n'y a pas de jeu '1234'");
+    let msg = strings.format("{n} tokens", &[("n", &3)]);
+    assert_eq!(msg, "3 tokens");
+    // Route graph labels are also translated.
+    assert_eq!(strings.tr("No paths"), "Aucun chemin");
+    let msg = strings.format(
+        "{paths} paths, {stops} stops",
+        &[("paths", &12), ("stops", &4)],
+    );
+    assert_eq!(msg, "12 chemins, 4 arrêts");
+}
+
+#[test]
+fn read_languages() {
+    let output_dir = std::path::Path::new("./tests/output/languages");
+    if output_dir.exists() {
+        std::fs::remove_dir_all(output_dir).unwrap();
+    }
+    std::fs::create_dir_all(output_dir).unwrap();
+    let strings = french();
+    strings.write(output_dir.join("fr.json")).unwrap();
+    // Files that do not define a valid language are ignored.
+    std::fs::write(output_dir.join("invalid.json"), "{}").unwrap();
+
+    let languages = lang::find_languages(output_dir);
+    assert_eq!(languages, vec![strings]);
+    assert!(lang::find_languages(output_dir.join("missing")).is_empty());
+}
+
+#[test]
+fn select_language() {
+    let games: Vec<Box<dyn Game>> =
+        vec![Box::new(navig18xx::game::new_1830())];
+    let mut dummy = control::DummyController::new();
+    dummy.set_index(Some(1));
+    let controller: Controller = dummy.into();
+    let mut ui = UserInterface::new(games, controller, Keymap::default());
+    ui.set_languages(vec![french()]);
+    assert_eq!(ui.language_names(), vec!["English", "Français"]);
+
+    let event = KeyPress {
+        key: gdk::Key::k,
+        ctrl: true,
+        alt: false,
+        shift: false,
+    };
+    assert_eq!(ui.handle_key_press(&event), UiResponse::None);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::None);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::Redraw);
+    assert_eq!(ui.assets.strings.language, "Français");
+    assert_eq!(ui.assets.strings.tr("Select a game"), "Choisissez un jeu");
+
+    // The command palette lists translated command names.
+    let commands = ui.keymap.commands(&ui.state);
+    let command = commands
+        .iter()
+        .find(|c| c.name == "Select the interface language")
+        .unwrap();
+    assert_eq!(ui.assets.strings.tr(command.name), "Choisissez la langue");
+
+    // The selected language is saved with the session.
+    let session = ui.session();
+    assert_eq!(session.language.as_deref(), Some("Français"));
+    let output_dir = std::path::Path::new("./tests/output");
+    std::fs::create_dir_all(output_dir).unwrap();
+    let path = output_dir.join("languages-session.json");
+    session.write(&path).unwrap();
+    assert!(ui.set_language(0) == UiResponse::Redraw);
+    assert!(ui.assets.strings.is_english());
+    assert!(ui.session().language.is_none());
+    let response = ui.restore_session(path);
+    ui.respond(response);
+    assert_eq!(ui.assets.strings.language, "Français");
+}
@@ -0,0 +1,85 @@
+/// Draw the title block and legend alongside the 1830 map, and check that
+/// the legend includes the company tokens and terrain hexes.
+use navig18xx::brush::legend::{draw_decorations, Decorations};
+use navig18xx::hex::Coord;
+use navig18xx::prelude::*;
+
+/// Draws the map and, if any decorations are provided, draws them to the
+/// right of the map.
+fn draw(map: &Map, hex: &Hex, ctx: &cairo::Context, decor: &Decorations) {
+    let mut hex_iter = map.hex_iter(hex, ctx);
+    navig18xx::brush::draw_map(hex, ctx, &mut hex_iter);
+    if decor.is_empty() {
+        return;
+    }
+    let map_size = image_size(|ctx| draw(map, hex, ctx, &Default::default()));
+    let (width, _height) = map_size.unwrap();
+    // Align the top of the decorations with the top of the map.
+    let margin = hex.theme.map_margin.absolute(hex);
+    let origin = Coord::from((width, margin));
+    let end = draw_decorations(hex, ctx, map, decor, origin);
+    assert!(end.y > origin.y);
+}
+
+#[test]
+fn legend_1830() -> Result<(), Box<dyn std::error::Error>> {
+    let hex = Hex::new(50.0);
+    let game = navig18xx::game::new_1830();
+    let map = game.create_map(&hex);
+
+    let plain = Decorations::default();
+    assert!(plain.is_empty());
+    let (width, _height) = image_size(|ctx| draw(&map, &hex, ctx, &plain))
+        .ok_or("No image size")?;
+
+    let title_only = Decorations {
+        title: Some(game.name().to_string()),
+        ..Default::default()
+    };
+    let decor = Decorations {
+        title: Some(game.name().to_string()),
+        phase: Some("Phase: 2".to_string()),
+        date: Some("2024-01-01".to_string()),
+        token_key: true,
+        terrain_key: true,
+    };
+    let (title_width, _) =
+        image_size(|ctx| draw(&map, &hex, ctx, &title_only))
+            .ok_or("No image size")?;
+    let (decor_width, decor_height) =
+        image_size(|ctx| draw(&map, &hex, ctx, &decor))
+            .ok_or("No image size")?;
+
+    // The decorations are drawn to the right of the map.
+    assert!(title_width > width);
+    assert!(decor_width >= title_width);
+
+    // Check that the legend includes an entry for each company token, and
+    // for each of the two building costs ($80 and $120).
+    let ctx = hex.context();
+    let origin = Coord::from((0.0, 0.0));
+    let title_end = draw_decorations(&hex, ctx, &map, &title_only, origin);
+    let tokens_only = Decorations {
+        token_key: true,
+        ..title_only.clone()
+    };
+    let tokens_end = draw_decorations(&hex, ctx, &map, &tokens_only, origin);
+    let decor_end = draw_decorations(&hex, ctx, &map, &decor, origin);
+    let entry = 0.6 * hex.max_d;
+    let num_tokens = map.token_names().len() as f64;
+    assert!((tokens_end.y - title_end.y - num_tokens * entry).abs() < 1e-6);
+    assert!(decor_end.y - tokens_end.y > 2.0 * entry);
+
+    let path = std::path::Path::new("./tests/output/1830_legend.png");
+    ImageFormat::Png.save_image(
+        decor_width,
+        decor_height,
+        1.0,
+        |ctx| {
+            navig18xx::brush::clear_surface(ctx, Colour::WHITE);
+            draw(&map, &hex, ctx, &decor)
+        },
+        path,
+    )?;
+    Ok(())
+}
@@ -0,0 +1,74 @@
+/// Check that subscribers are notified of each change to the map, and are
+/// no longer notified once they have unsubscribed.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+mod common;
+use common::*;
+
+/// Subscribes to map events, and returns the subscription identifier and
+/// the events that the subscriber has received.
+fn record_events(
+    ui: &mut UserInterface,
+) -> (ListenerId, Rc<RefCell<Vec<MapEvent>>>) {
+    let events = Rc::new(RefCell::new(vec![]));
+    let received = events.clone();
+    let id = ui
+        .assets
+        .subscribe(move |event| received.borrow_mut().push(event.clone()));
+    (id, events)
+}
+
+#[test]
+fn subscribers_are_notified_of_changes() {
+    let mut ui = new_ui();
+    let addr = hex_addr("C7");
+    let (id, events) = record_events(&mut ui);
+    let (other_id, other_events) = record_events(&mut ui);
+    assert_ne!(id, other_id);
+
+    assert!(ui.assets.place_tile(addr, "8", RotateCW::Zero));
+    assert!(ui.assets.rotate_tile(addr, true));
+    assert!(ui.assets.remove_tile(addr));
+    // Changes that fail are not reported.
+    assert!(!ui.assets.remove_tile(addr));
+    let expected = vec![
+        MapEvent::TilePlaced {
+            addr,
+            tile: "8".to_string(),
+            rotation: RotateCW::Zero,
+            previous: None,
+        },
+        MapEvent::TileRotated {
+            addr,
+            rotation: RotateCW::One,
+        },
+        MapEvent::TileRemoved {
+            addr,
+            tile: "8".to_string(),
+        },
+    ];
+    assert_eq!(*events.borrow(), expected);
+    assert_eq!(*other_events.borrow(), expected);
+    assert_eq!(expected[1].hexes(), Some(vec![addr]));
+
+    // Unsubscribed listeners are not notified of later changes.
+    assert!(ui.assets.unsubscribe(id));
+    assert!(!ui.assets.unsubscribe(id));
+    assert!(ui.assets.set_phase(1));
+    assert_eq!(*events.borrow(), expected);
+    let phase_changed = other_events.borrow().last().cloned().unwrap();
+    assert!(matches!(
+        phase_changed,
+        MapEvent::PhaseChanged { phase_ix: 1, .. }
+    ));
+    assert_eq!(phase_changed.hexes(), None);
+    assert_eq!(other_events.borrow().len(), expected.len() + 1);
+
+    assert!(ui.assets.unsubscribe(other_id));
+    ui.assets.notify(MapEvent::MapReset);
+    assert_eq!(other_events.borrow().len(), expected.len() + 1);
+}
@@ -0,0 +1,104 @@
+/// Check that the user can select a region of the map by dragging the mouse,
+/// and save this region as a PNG or SVG image at the chosen scale.
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+/// Returns a drag event from `(10, 10)` to `(x, y)`.
+fn drag_to(x: f64, y: f64, finished: bool) -> ButtonDrag {
+    ButtonDrag {
+        x0: 10.0,
+        y0: 10.0,
+        x,
+        y,
+        button: 1,
+        shift: false,
+        finished,
+    }
+}
+
+/// Selects a region by dragging the mouse, saves it to `path`, and returns
+/// the response to saving the region.
+fn save_region_to(
+    ui: &mut UserInterface,
+    path: &std::path::Path,
+    x: f64,
+    y: f64,
+) -> UiResponse {
+    dummy(ui).set_screenshot_save_path(Some(path.to_path_buf()));
+    let response = ui.handle_button_drag(&drag_to(x, y, true));
+    ui.respond(response);
+    ui.ping(PingDest::TopLevel)
+}
+
+#[test]
+fn region_dimensions() {
+    // Regions can be defined by any two opposite corners.
+    let region = Region::from_corners((110.0, 60.0, 10.0, 10.0), 2.0);
+    assert_eq!(
+        (region.x, region.y, region.width, region.height),
+        (10.0, 10.0, 100.0, 50.0)
+    );
+    assert_eq!(region.output_dims(), (200, 100));
+
+    // Partial pixels are included, and images contain at least one pixel.
+    let region = Region::from_corners((0.0, 0.0, 10.5, 0.0), 1.0);
+    assert_eq!(region.output_dims(), (11, 1));
+}
+
+#[test]
+fn save_map_regions() {
+    let output_dir = std::path::Path::new("./tests/output");
+    std::fs::create_dir_all(output_dir).unwrap();
+    let png_path = output_dir.join("map-region.png");
+    let svg_path = output_dir.join("map-region.svg");
+    for path in [&png_path, &svg_path] {
+        if path.exists() {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    let mut ui = new_ui();
+    assert_eq!(press(&mut ui, gdk::Key::i), UiResponse::Redraw);
+    assert!(ui.state.as_screenshot().is_some());
+    assert_eq!(ui.state.as_screenshot().unwrap().scale(), 1.0);
+
+    // The selected area is highlighted while the mouse is being dragged,
+    // and a region is only saved when the drag has finished.
+    let response = ui.handle_button_drag(&drag_to(110.0, 60.0, false));
+    assert_eq!(response, UiResponse::Redraw);
+    assert!(!png_path.exists());
+
+    // Regions are saved at the chosen scale.
+    press(&mut ui, gdk::Key::_3);
+    assert_eq!(ui.state.as_screenshot().unwrap().scale(), 3.0);
+    let title = ui.controller.window_title().unwrap();
+    assert!(title.starts_with("Save map region"));
+    assert_eq!(
+        save_region_to(&mut ui, &png_path, 110.0, 60.0),
+        UiResponse::None
+    );
+    let mut file = std::fs::File::open(&png_path).unwrap();
+    let image = cairo::ImageSurface::create_from_png(&mut file).unwrap();
+    assert_eq!((image.width(), image.height()), (300, 150));
+
+    // Regions are saved as SVG images when the file has an SVG extension.
+    press(&mut ui, gdk::Key::_1);
+    save_region_to(&mut ui, &svg_path, 110.0, 60.0);
+    let svg = std::fs::read_to_string(&svg_path).unwrap();
+    assert!(svg.contains(r#"width="100pt" height="50pt""#));
+    assert!(dummy(&mut ui).last_error().is_none());
+
+    // Errors are reported to the user.
+    let missing = output_dir.join("missing-dir").join("map-region.png");
+    save_region_to(&mut ui, &missing, 110.0, 60.0);
+    let error = dummy(&mut ui).last_error().unwrap();
+    assert!(error.starts_with("Could not save image"));
+    assert!(ui.state.as_screenshot().is_some());
+
+    assert_eq!(press(&mut ui, gdk::Key::Return), UiResponse::Redraw);
+    assert!(ui.state.is_default_state());
+}
@@ -0,0 +1,88 @@
+/// Check that mouse buttons, with and without modifier keys, can be bound to
+/// the named actions that are defined by the keymaps.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+fn click(ui: &mut UserInterface, addr: HexAddress, button: u32, ctrl: bool) {
+    let (x, y) = hex_centre(ui, addr);
+    let event = ButtonPress {
+        x,
+        y,
+        button,
+        ctrl,
+        alt: false,
+        shift: false,
+    };
+    let response = ui.handle_button_press(&event);
+    ui.respond(response);
+}
+
+/// Returns the items in the most recently shown context menu, if any.
+fn context_menu(ui: &UserInterface) -> Option<&[String]> {
+    let Controller::Dummy(dummy) = &ui.controller else {
+        panic!("Expected a dummy controller")
+    };
+    dummy.context_menu()
+}
+
+#[test]
+fn right_click_shows_hex_actions() {
+    let mut ui = new_ui();
+    let addr = hex_addr("B4");
+    assert_ne!(ui.state.active_hex(), Some(addr));
+
+    // A right-click selects the hex and shows the actions for this hex.
+    click(&mut ui, addr, ButtonPress::SECONDARY, false);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::None);
+    assert!(ui.state.as_default().is_some());
+    assert_eq!(ui.state.active_hex(), Some(addr));
+    assert!(context_menu(&ui).is_some());
+
+    // Unbound buttons are treated as a plain click.
+    ui.keymap
+        .unbind_button(ButtonPress::SECONDARY, false, false);
+    let other = hex_addr("C7");
+    click(&mut ui, other, ButtonPress::SECONDARY, false);
+    assert!(ui.state.as_default().is_some());
+    assert_eq!(ui.state.active_hex(), Some(other));
+
+    // The same button can be bound to a different action.
+    let binding =
+        ButtonBinding::button("Edit tokens", ButtonPress::SECONDARY);
+    assert_eq!(binding.shortcut(), "Right-click");
+    ui.keymap.bind_button(binding);
+    assert_eq!(ui.keymap.button_bindings().len(), 2);
+    click(&mut ui, addr, ButtonPress::SECONDARY, false);
+    let state = ui.state.as_edit_tokens().unwrap();
+    assert_eq!(state.active_hex(), addr);
+}
+
+#[test]
+fn ctrl_click_removes_token() {
+    let mut ui = new_ui();
+    let addr = hex_addr("B4");
+    assert!(ui.assets.map.place_tile(addr, "57", RotateCW::Zero));
+    let token = ui.assets.map.try_token("PRR").unwrap();
+    let space = ui.assets.map.tile_at(addr).unwrap().token_spaces()[0];
+    let hex_state = ui.assets.map.hex_state_mut(addr).unwrap();
+    hex_state.set_token_at(&space, token);
+
+    // Ctrl+click has no effect other than selecting the hex, because the
+    // bound action is only available when editing tokens.
+    click(&mut ui, addr, ButtonPress::PRIMARY, true);
+    assert!(ui.state.as_default().is_some());
+    assert_eq!(ui.state.active_hex(), Some(addr));
+    let hex_state = ui.assets.map.hex_state(addr).unwrap();
+    assert_eq!(hex_state.token_at(&space), Some(&token));
+
+    press(&mut ui, gdk::Key::t);
+    assert!(ui.state.as_edit_tokens().is_some());
+    click(&mut ui, addr, ButtonPress::PRIMARY, true);
+    let hex_state = ui.assets.map.hex_state(addr).unwrap();
+    assert_eq!(hex_state.token_at(&space), None);
+}
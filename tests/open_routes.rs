@@ -0,0 +1,99 @@
+/// Find the best route that a single train could run from a specific city,
+/// for a company that has no tokens on the map, and check that this route
+/// does not pass through cities whose token spaces are all occupied.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+/// Returns a 1867 map with tiles placed around Montreal, where Montreal is
+/// full (i.e., all of its token spaces are occupied).
+fn full_montreal_map(game: &dyn Game, hex: &Hex) -> Map {
+    let mut map = montreal_map(game, hex);
+    let montreal = game.coordinate_system().parse("L12").unwrap();
+    let spaces = map.tile_at(montreal).unwrap().token_spaces();
+    let map_hex = map.hex_state_mut(montreal).unwrap();
+    for space in &spaces {
+        map_hex.set_token_at(space, *game.first_token());
+    }
+    map
+}
+
+fn train_named(game: &dyn Game, name: &str) -> Train {
+    *game
+        .train_types()
+        .into_iter()
+        .find(|t| game.train_name(t) == Some(name))
+        .unwrap()
+}
+
+#[test]
+fn open_route_from_montreal() {
+    let game = navig18xx::game::new_1867();
+    let hex = Hex::new(125.0);
+    let map = full_montreal_map(&game, &hex);
+    let montreal = game.coordinate_system().parse("L12").unwrap();
+    let trains: Trains = vec![train_named(&game, "4")].into();
+
+    // There is a route from Montreal, but only for the cities that exist.
+    let routes = game.open_routes(&map, montreal, 0, &trains).unwrap();
+    assert_eq!(routes.train_routes.len(), 1);
+    let visits = &routes.train_routes[0].route.visits;
+    assert!(visits.iter().any(|visit| visit.addr == montreal));
+    assert!(routes.net_revenue > 0);
+    let num_cities = map.tile_at(montreal).unwrap().cities().len();
+    assert!(game
+        .open_routes(&map, montreal, num_cities, &trains)
+        .is_none());
+
+    // A company that has no tokens cannot run a route through Montreal.
+    let m9 = game.coordinate_system().parse("M9").unwrap();
+    if let Some(routes) = game.open_routes(&map, m9, 0, &trains) {
+        for route in &routes.train_routes {
+            let visits = &route.route.visits;
+            let n = visits.len();
+            assert!(visits[1..n - 1].iter().all(|v| v.addr != montreal));
+        }
+    }
+
+    // The company that fills Montreal can earn at least as much revenue
+    // from Montreal as a company that has no tokens.
+    let bonuses = vec![false; game.bonus_options().len()];
+    let company = game
+        .best_routes(&map, *game.first_token(), &trains, bonuses)
+        .unwrap();
+    assert!(company.net_revenue >= routes.net_revenue);
+}
+
+#[test]
+fn open_route_ui() {
+    let game = navig18xx::game::new_1867();
+    let montreal = game.coordinate_system().parse("L12").unwrap();
+    let train_ix = game.train_names().iter().position(|n| *n == "4");
+    let mut dummy = control::DummyController::new();
+    dummy.set_index(train_ix);
+    let mut ui = new_ui_for(Box::new(game), dummy);
+    let game = ui.assets.games.active();
+    ui.assets.map = full_montreal_map(game, &ui.assets.hex);
+    ui.state = State::default_state(montreal);
+
+    let event = KeyPress {
+        key: gdk::Key::o,
+        ctrl: false,
+        alt: false,
+        shift: false,
+    };
+    assert_eq!(ui.handle_key_press(&event), UiResponse::Redraw);
+    assert_eq!(ui.state.name(), "FindOpenRoutesTrain");
+
+    // Wait for the route search to finish.
+    wait_until(&mut ui, |ui| ui.state.as_find_routes_found().is_some());
+    let found = ui.state.as_find_routes_found().unwrap();
+    let summary = found.route_summary(&ui.assets).unwrap();
+    assert_eq!(summary.company, "Open route from L12");
+    assert_eq!(summary.routes.len(), 1);
+    assert_eq!(summary.routes[0].train, "4");
+}
@@ -0,0 +1,111 @@
+/// Step through an operating round, and check that the revenue earned by
+/// each company is recorded in the journal and the revenue history.
+use navig18xx::prelude::*;
+use navig18xx::ui::round::Step;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+fn last_entry(ui: &UserInterface) -> &str {
+    ui.assets.journal.entries().last().unwrap()
+}
+
+#[test]
+fn operating_round_ui() {
+    let game = navig18xx::game::new_1830();
+    let order: Vec<String> = game
+        .operating_order()
+        .into_iter()
+        .map(|abbrev| abbrev.to_string())
+        .collect();
+    let train = *game.try_train("2").unwrap();
+    let mut dummy = control::DummyController::new();
+    dummy.set_trains(Some((vec![train].into(), vec![])));
+    let mut ui = new_ui_with(dummy);
+
+    // Start the operating round.
+    assert_eq!(press(&mut ui, gdk::Key::a), UiResponse::Redraw);
+    assert_eq!(last_entry(&ui), "Operating round 1 started");
+    let round = ui.assets.operating_round.as_ref().unwrap();
+    assert_eq!(round.company(), order[0]);
+    assert_eq!(round.step(), Step::LayTile);
+    let status = ui.state.status(&ui.assets);
+    let expected =
+        format!("Round 1: {} to lay a tile (1 of {})", order[0], order.len());
+    assert_eq!(status.get(SegmentKind::Round), Some(expected.as_str()));
+
+    // The first company has not placed any tokens, so it cannot run any
+    // trains.
+    press(&mut ui, gdk::Key::a);
+    let round = ui.assets.operating_round.as_ref().unwrap();
+    assert_eq!(round.step(), Step::PlaceToken);
+    press(&mut ui, gdk::Key::a);
+    let round = ui.assets.operating_round.as_ref().unwrap();
+    assert_eq!(round.step(), Step::RunRoutes);
+    press(&mut ui, gdk::Key::a);
+    assert!(ui.state.is_default_state());
+    let expected = format!("{} ran no trains for $0", order[0]);
+    assert_eq!(last_entry(&ui), expected);
+    let round = ui.assets.operating_round.as_ref().unwrap();
+    assert_eq!(round.company(), order[1]);
+    assert_eq!(round.step(), Step::LayTile);
+
+    // Place a token for the second company in the most valuable token
+    // space, and run its trains.
+    let company = order[1].clone();
+    let game = ui.assets.games.active();
+    let token = *game.try_token(&company).unwrap();
+    let trains: Trains = vec![*game.try_train("2").unwrap()].into();
+    let best = navig18xx::game::analysis::rank_token_spaces(
+        game,
+        &ui.assets.map,
+        token,
+        &trains,
+        vec![],
+    )[0];
+    let hex_state = ui.assets.map.hex_state_mut(best.addr).unwrap();
+    hex_state.set_token_at(&best.space, token);
+    press(&mut ui, gdk::Key::a);
+    press(&mut ui, gdk::Key::a);
+    press(&mut ui, gdk::Key::a);
+    assert_eq!(ui.state.name(), "FindRoutesTrains");
+
+    // Wait for the route search to finish.
+    wait_until(&mut ui, |ui| ui.state.as_find_routes_found().is_some());
+    let (abbrev, revenue) = ui.assets.last_revenue.clone().unwrap();
+    assert_eq!(abbrev, company);
+    assert!(revenue > 0);
+    let expected = format!("{} ran 2-train for ${}", company, revenue);
+    assert_eq!(last_entry(&ui), expected);
+    let history: Vec<(usize, &str, usize)> = ui
+        .assets
+        .revenue_history
+        .iter()
+        .map(|r| (r.round, r.company.as_str(), r.revenue))
+        .collect();
+    assert_eq!(
+        history,
+        vec![(1, order[0].as_str(), 0), (1, company.as_str(), revenue)]
+    );
+    let round = ui.assets.operating_round.as_ref().unwrap();
+    assert_eq!(round.position(), 3);
+
+    // End the operating round early.
+    press(&mut ui, gdk::Key::Return);
+    assert!(ui.state.is_default_state());
+    assert_eq!(
+        press_with(&mut ui, gdk::Key::a, true, false),
+        UiResponse::Redraw
+    );
+    assert!(ui.assets.operating_round.is_none());
+    assert_eq!(last_entry(&ui), "Operating round 1 finished");
+    let status = ui.state.status(&ui.assets);
+    assert!(status.get(SegmentKind::Round).is_none());
+
+    // The next operating round has the next number.
+    press(&mut ui, gdk::Key::a);
+    assert_eq!(last_entry(&ui), "Operating round 2 started");
+}
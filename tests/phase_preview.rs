@@ -0,0 +1,72 @@
+/// Check that previewing a game phase describes the new tile colours and
+/// off-board revenues, without changing the game phase or the map, and that
+/// selecting a phase in the user interface changes the game phase.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+#[test]
+fn preview_1830_phase_5() {
+    let mut game = navig18xx::game::new_1830();
+    let hex = Hex::new(125.0);
+    let map = game.create_map(&hex);
+    let original = map.clone();
+    let phase_ix = game.phase_names().iter().position(|n| *n == "5").unwrap();
+
+    let effects = game.preview_phase(&map, phase_ix).unwrap();
+    assert_eq!(effects.phase_ix, phase_ix);
+    assert_eq!(effects.name, "5");
+    assert_eq!(
+        effects.tile_colours,
+        vec![HexColour::Yellow, HexColour::Green, HexColour::Brown]
+    );
+    assert_eq!(
+        effects.new_tile_colours,
+        vec![HexColour::Green, HexColour::Brown]
+    );
+    assert!(!effects.revenue_changes.is_empty());
+    for change in &effects.revenue_changes {
+        assert_ne!(change.before, change.after);
+    }
+    let summary = effects.summary();
+    assert_eq!(summary[0], "New tile colours: Green, Brown");
+    assert!(summary.contains(&"Chicago (B6): 40 -> 70".to_string()));
+    assert_eq!(summary.len(), effects.revenue_changes.len() + 1);
+
+    // The game phase and the map are not changed.
+    assert_eq!(game.phase_ix(), 0);
+    assert!(map == original);
+
+    // There are no changes when previewing the current phase.
+    let effects = game.preview_phase(&map, 0).unwrap();
+    assert!(effects.is_empty());
+    assert!(effects.summary().is_empty());
+
+    // There is no phase after the final phase.
+    assert!(game.preview_phase(&map, game.phase_count()).is_none());
+}
+
+#[test]
+fn select_phase_records_change() {
+    let mut dummy = control::DummyController::new();
+    dummy.set_phase(Some(2));
+    let mut ui = new_ui_with(dummy);
+
+    let event = KeyPress {
+        key: gdk::Key::p,
+        ctrl: false,
+        alt: false,
+        shift: false,
+    };
+    assert_eq!(ui.handle_key_press(&event), UiResponse::None);
+    assert_eq!(ui.ping(PingDest::State), UiResponse::Redraw);
+    assert_eq!(ui.assets.games.active().phase_ix(), 2);
+    assert_eq!(
+        ui.assets.journal.entries().last().map(|s| s.as_str()),
+        Some("Changed phase to 4")
+    );
+}
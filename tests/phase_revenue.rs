@@ -0,0 +1,77 @@
+/// Check that routes to off-board tiles earn the revenue for the current game
+/// phase, as shown by the tile's phase revenue label, and that this label
+/// highlights the revenue for the current game phase.
+use navig18xx::prelude::*;
+use navig18xx::route::{paths_from_city, PathLimit};
+
+/// Returns the revenue earned by visiting Chicago at the start of each path
+/// from Chicago.
+fn chicago_revenues(game: &dyn Game, map: &Map) -> Vec<usize> {
+    let chicago = game.coordinate_system().parse("B6").unwrap();
+    let criteria = Criteria {
+        token: *game.first_token(),
+        path_limit: Some(PathLimit::Cities { count: 2 }),
+        conflict_rule: ConflictRule::TrackOrCityHex,
+        route_conflict_rule: ConflictRule::TrackOnly,
+        token_rule: RouteTokenRule::AnyToken,
+    };
+    paths_from_city(map, &criteria, chicago, 0)
+        .iter()
+        .map(|path| {
+            assert_eq!(path.visits[0].addr, chicago);
+            path.visits[0].revenue
+        })
+        .collect()
+}
+
+/// Returns the highlighted entry of Chicago's phase revenue label.
+fn chicago_active(game: &dyn Game, map: &Map) -> Option<(HexColour, usize)> {
+    let chicago = game.coordinate_system().parse("B6").unwrap();
+    let tile = map.tile_at(chicago).unwrap();
+    tile.labels().iter().find_map(|(label, _pos)| match label {
+        Label::PhaseRevenue(amounts) => amounts
+            .iter()
+            .find(|(_colour, _revenue, active)| *active)
+            .map(|(colour, revenue, _active)| (*colour, *revenue)),
+        _ => None,
+    })
+}
+
+#[test]
+fn offboard_revenue_for_current_phase() {
+    let mut game = navig18xx::game::new_1830();
+    let hex = Hex::new(125.0);
+    let mut map = game.create_map(&hex);
+    let coords = game.coordinate_system();
+    let chicago = coords.parse("B6").unwrap();
+    assert!(map.place_tile(
+        coords.parse("C5").unwrap(),
+        "57",
+        RotateCW::Zero
+    ));
+    assert_eq!(map.revenue_phase(), Some(HexColour::Yellow));
+
+    let revenues = chicago_revenues(&game, &map);
+    assert!(!revenues.is_empty());
+    assert!(revenues.iter().all(|&revenue| revenue == 40));
+
+    assert_eq!(chicago_active(&game, &map), Some((HexColour::Yellow, 40)));
+
+    // Routes earn the brown phase revenue without replacing the tile.
+    map.set_revenue_phase(HexColour::Brown);
+    assert_eq!(map.tile_at(chicago).unwrap().name, "Chicago");
+    assert_eq!(chicago_active(&game, &map), Some((HexColour::Brown, 70)));
+    let revenues = chicago_revenues(&game, &map);
+    assert!(revenues.iter().all(|&revenue| revenue == 70));
+
+    // Changing the game phase sets the revenue phase.
+    assert!(game.set_phase_name(&mut map, "3"));
+    assert_eq!(map.revenue_phase(), Some(HexColour::Green));
+    assert_eq!(chicago_active(&game, &map), Some((HexColour::Green, 40)));
+    let revenues = chicago_revenues(&game, &map);
+    assert!(revenues.iter().all(|&revenue| revenue == 40));
+    assert!(game.set_phase_name(&mut map, "5"));
+    assert_eq!(map.revenue_phase(), Some(HexColour::Brown));
+    let revenues = chicago_revenues(&game, &map);
+    assert!(revenues.iter().all(|&revenue| revenue == 70));
+}
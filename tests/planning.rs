@@ -0,0 +1,86 @@
+/// Sketch tiles in the planning layer, check that the map is not modified
+/// until the planned tiles are placed, and check that planned tiles are
+/// saved with the game.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+#[test]
+fn plan_then_place_tiles() {
+    let mut ui = new_ui();
+
+    let coords = ui.assets.games.active().coordinate_system();
+    let addr = coords.parse("C7").unwrap();
+    assert!(ui.assets.map.tile_at(addr).is_none());
+    ui.state = State::default_state(addr);
+    let revision = ui.assets.map.revision();
+    let journal_len = ui.assets.journal.len();
+
+    // Plan to place a tile on the empty hex, and rotate the planned tile.
+    assert_eq!(press(&mut ui, gdk::Key::l), UiResponse::Redraw);
+    assert!(ui.assets.planning);
+    press(&mut ui, gdk::Key::e);
+    assert!(ui.state.as_replace_tile().is_some());
+    press(&mut ui, gdk::Key::Return);
+    assert!(ui.state.is_default_state());
+    press(&mut ui, gdk::Key::greater);
+    let planned = ui.assets.map.plan().get(addr).cloned().unwrap();
+    assert_eq!(planned.rotation, RotateCW::One);
+    assert!(ui.assets.map.tile_at(addr).is_none());
+    assert_eq!(ui.assets.map.revision(), revision);
+    assert_eq!(ui.assets.journal.len(), journal_len);
+
+    // Removing the tile only removes the planned tile.
+    press(&mut ui, gdk::Key::Delete);
+    assert!(ui.assets.map.plan().is_empty());
+    press(&mut ui, gdk::Key::e);
+    press(&mut ui, gdk::Key::Return);
+    assert_eq!(ui.assets.map.plan().len(), 1);
+
+    // Hide and show the planned tiles, and draw the map.
+    press(&mut ui, gdk::Key::k);
+    assert!(!ui.assets.show_plan);
+    press(&mut ui, gdk::Key::k);
+    assert!(ui.assets.show_plan);
+    let ctx = ui.assets.hex.context();
+    ui.state.draw(&ui.assets, ctx);
+
+    // Check that the planned tiles are saved with the game.
+    let path = std::path::Path::new("./tests/output").join("planning.json");
+    let response = ui.save_game(path.clone());
+    ui.respond(response);
+    let response = ui.load_game(path);
+    ui.respond(response);
+    assert!(!ui.assets.planning);
+    assert_eq!(ui.assets.map.plan().len(), 1);
+    assert!(ui.assets.map.tile_at(addr).is_none());
+
+    // Place the planned tiles on the map.
+    ui.state = State::default_state(addr);
+    let journal_len = ui.assets.journal.len();
+    assert_eq!(
+        press_with(&mut ui, gdk::Key::Return, true, false),
+        UiResponse::Redraw
+    );
+    assert!(ui.assets.map.plan().is_empty());
+    assert_eq!(ui.assets.map.tile_at(addr).unwrap().name, planned.tile);
+    assert_eq!(ui.assets.journal.len(), journal_len + 1);
+
+    // Discard planned tiles without placing them.
+    press(&mut ui, gdk::Key::l);
+    press(&mut ui, gdk::Key::greater);
+    assert!(ui.assets.map.plan().is_empty());
+    press(&mut ui, gdk::Key::e);
+    press(&mut ui, gdk::Key::Return);
+    assert_eq!(ui.assets.map.plan().len(), 1);
+    assert_eq!(
+        press_with(&mut ui, gdk::Key::Delete, true, false),
+        UiResponse::Redraw
+    );
+    assert!(ui.assets.map.plan().is_empty());
+    assert_eq!(ui.assets.map.tile_at(addr).unwrap().name, planned.tile);
+}
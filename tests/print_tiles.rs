@@ -0,0 +1,72 @@
+/// Check that print-and-play tile sheets arrange tiles on pages at their
+/// true physical size, and are saved as multi-page PDF files.
+use navig18xx::brush::sheet::{PaperSize, PrintLayout};
+use navig18xx::prelude::*;
+
+#[test]
+fn print_layout() {
+    let hex = Hex::new(125.0);
+    let layout = PrintLayout {
+        paper: PaperSize::A4,
+        hex_width_mm: 38.0,
+        margin_mm: 10.0,
+        gap_mm: 2.0,
+        cut_lines: true,
+    };
+    // Flat-top tiles are wider than they are tall.
+    let (width, height) = layout.cell_size_mm(&hex);
+    assert_eq!(height, 38.0);
+    assert!((width - 38.0 * 2.0 / 3.0_f64.sqrt()).abs() < 1e-9);
+    // The usable page area is 190 mm by 277 mm.
+    assert_eq!(layout.grid(&hex), (6, 4));
+    assert_eq!(layout.tiles_per_page(&hex), 24);
+    assert_eq!(layout.page_count(&hex, 0), Some(0));
+    assert_eq!(layout.page_count(&hex, 24), Some(1));
+    assert_eq!(layout.page_count(&hex, 25), Some(2));
+    let (x, y) = layout.centre_mm(&hex, 0);
+    assert!((x - (10.0 + 0.5 * width)).abs() < 1e-9);
+    assert_eq!(y, 29.0);
+    // The 25th tile is the first tile on the second page.
+    assert_eq!(layout.centre_mm(&hex, 24), layout.centre_mm(&hex, 0));
+
+    // Letter paper is wider and shorter than A4 paper.
+    let letter = PrintLayout {
+        paper: PaperSize::Letter,
+        ..layout
+    };
+    assert_eq!(letter.grid(&hex), (6, 4));
+
+    // Tiles that are larger than the page cannot be printed.
+    let huge = PrintLayout {
+        hex_width_mm: 300.0,
+        ..layout
+    };
+    assert_eq!(huge.grid(&hex), (0, 0));
+    assert_eq!(huge.page_count(&hex, 1), None);
+}
+
+#[test]
+fn print_tiles_pdf() -> Result<(), Box<dyn std::error::Error>> {
+    let hex = Hex::new(125.0);
+    let layout = PrintLayout::default();
+    let tiles = tile_catalogue();
+    // Print three copies of each of the first ten tiles.
+    let selected: Vec<&Tile> =
+        tiles.iter().take(10).flat_map(|t| [t, t, t]).collect();
+    let output_dir = std::path::Path::new("./tests/output");
+    std::fs::create_dir_all(output_dir)?;
+    let path = output_dir.join("print_tiles.pdf");
+    let pages = layout.save_pdf(&hex, selected, &path)?;
+    assert_eq!(pages, 2);
+    let bytes = std::fs::read(&path)?;
+    assert!(bytes.starts_with(b"%PDF"));
+
+    // Tiles that are larger than the page cannot be printed.
+    let huge = PrintLayout {
+        hex_width_mm: 300.0,
+        ..layout
+    };
+    let path = output_dir.join("print_tiles_huge.pdf");
+    assert!(huge.save_pdf(&hex, &tiles[..1], &path).is_err());
+    Ok(())
+}
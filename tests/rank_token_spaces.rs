@@ -0,0 +1,59 @@
+/// Rank the token spaces in which a company could place its next token, and
+/// check that the user interface shows the same ranking as the library.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+#[test]
+fn rank_token_spaces_ui() {
+    let game = navig18xx::game::new_1830();
+    let trains: Trains = vec![*game.try_train("2").unwrap()].into();
+    let mut dummy = control::DummyController::new();
+    dummy.set_string(Some("Pennsylvania".to_string()));
+    dummy.set_trains(Some((trains.clone(), vec![])));
+    let mut ui = new_ui_with(dummy);
+
+    // The company does not need to have placed any tokens.
+    assert!(ui.assets.map.unique_placed_tokens().is_empty());
+    assert_eq!(press(&mut ui, gdk::Key::v), UiResponse::Redraw);
+    assert_eq!(ui.state.name(), "FindRoutesCompany");
+
+    // Wait for the evaluation to finish.
+    wait_until(&mut ui, |ui| ui.state.as_ranked_token_spaces().is_some());
+
+    let game = ui.assets.games.active();
+    let token = *game.try_token("PRR").unwrap();
+    let expected = navig18xx::game::analysis::rank_token_spaces(
+        game,
+        &ui.assets.map,
+        token,
+        &trains,
+        vec![],
+    );
+    let ranked = ui.state.as_ranked_token_spaces().unwrap();
+    assert_eq!(ranked.values(), expected.as_slice());
+    assert!(ranked.values()[0].revenue > 0);
+    let title = ui.controller.window_title().unwrap();
+    assert!(title.starts_with("PRR: best token at"));
+
+    // Highlight the best and the worst token spaces.
+    press(&mut ui, gdk::Key::Right);
+    let best = expected[0];
+    assert_eq!(ui.state.as_ranked_token_spaces().unwrap().active(), Some(0));
+    assert_eq!(ui.state.active_hex(), Some(best.addr));
+    let title = ui.controller.window_title().unwrap();
+    assert!(title.ends_with(&format!("(1 of {})", expected.len())));
+    press(&mut ui, gdk::Key::Left);
+    let last = expected.len() - 1;
+    let ranked = ui.state.as_ranked_token_spaces().unwrap();
+    assert_eq!(ranked.active(), Some(last));
+
+    // The highlighted hex remains active when returning to the default mode.
+    press(&mut ui, gdk::Key::Return);
+    assert!(ui.state.is_default_state());
+    assert_eq!(ui.state.active_hex(), Some(expected[last].addr));
+}
@@ -0,0 +1,101 @@
+/// Check that loaded and saved games are added to the recent files list,
+/// that file dialogs start in the directory for the current game, that the
+/// current game can be reloaded, and that these are saved in the session.
+use navig18xx::prelude::*;
+use navig18xx::ui::control::{FileHistory, MAX_RECENT_FILES};
+use navig18xx::ui::session::Session;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+use std::path::{Path, PathBuf};
+
+mod common;
+use common::*;
+
+#[test]
+fn recent_files_list() {
+    let mut history = FileHistory::default();
+    for ix in 0..(MAX_RECENT_FILES + 2) {
+        history.add_recent_file(PathBuf::from(format!("game-{}.json", ix)));
+    }
+    let recent = history.recent_files();
+    assert_eq!(recent.len(), MAX_RECENT_FILES);
+    assert_eq!(recent[0], Path::new("game-11.json"));
+
+    // Adding an existing file moves it to the start of the list.
+    history.add_recent_file(PathBuf::from("game-5.json"));
+    assert_eq!(history.recent_files().len(), MAX_RECENT_FILES);
+    assert_eq!(history.recent_files()[0], Path::new("game-5.json"));
+    assert_eq!(history.recent_files()[1], Path::new("game-11.json"));
+
+    // The order is preserved when creating a history from a session.
+    let restored = FileHistory::new(
+        history.recent_files().to_vec(),
+        history.game_dirs().clone(),
+    );
+    assert_eq!(restored.recent_files(), history.recent_files());
+}
+
+#[test]
+fn recent_files_and_reload() {
+    let output_dir = Path::new("./tests/output/recent_files");
+    std::fs::create_dir_all(output_dir).unwrap();
+    let game_path = output_dir.join("recent-game.json");
+    let mut ui = new_ui();
+
+    // There is no game to reload.
+    assert_eq!(press(&mut ui, gdk::Key::F5), UiResponse::None);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::None);
+    assert!(dummy(&mut ui).last_error().is_some());
+
+    // Saving a game adds it to the recent files list, and its directory
+    // becomes the default directory for this game.
+    let response = ui.save_game(game_path.clone());
+    ui.respond(response);
+    let history = ui.controller.file_history();
+    assert_eq!(history.recent_files(), std::slice::from_ref(&game_path));
+    assert_eq!(history.default_dir(), Some(output_dir));
+    let game = ui.assets.games.active().name().to_string();
+    assert_eq!(history.game_dirs().get(&game).unwrap(), output_dir);
+
+    // The load dialog starts in this directory.
+    dummy(&mut ui).set_game_load_path(None);
+    assert_eq!(
+        press_with(&mut ui, gdk::Key::o, true, false),
+        UiResponse::None
+    );
+    assert_eq!(dummy(&mut ui).dialog_dir(), Some(output_dir));
+
+    // Reloading the game discards unsaved changes.
+    let c7 = ui.assets.games.active().coordinate_system().parse("C7");
+    let c7 = c7.unwrap();
+    assert!(ui.assets.map.place_tile(c7, "7", RotateCW::Zero));
+    assert_eq!(press(&mut ui, gdk::Key::F5), UiResponse::None);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::ResetGame);
+    assert!(ui.assets.map.tile_at(c7).is_none());
+
+    // Select the game from the recent files list.
+    dummy(&mut ui).set_index(Some(0));
+    assert_eq!(
+        press_with(&mut ui, gdk::Key::O, true, true),
+        UiResponse::None
+    );
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::ResetGame);
+
+    // The recent files and game directories are saved in the session.
+    let session_path = output_dir.join("session.json");
+    ui.session().write(&session_path).unwrap();
+    let session = Session::read(&session_path).unwrap();
+    assert_eq!(session.recent_files, std::slice::from_ref(&game_path));
+    assert_eq!(session.game_dirs.get(&game).unwrap(), output_dir);
+
+    // Restoring the session in a new window restores the recent files, and
+    // the current game can then be reloaded.
+    let mut ui = new_ui();
+    let response = ui.restore_session(session_path);
+    ui.respond(response);
+    assert_eq!(ui.controller.file_history().recent_files(), [game_path]);
+    assert_eq!(ui.controller.file_history().default_dir(), Some(output_dir));
+    assert_eq!(press(&mut ui, gdk::Key::F5), UiResponse::None);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::ResetGame);
+}
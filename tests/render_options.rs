@@ -0,0 +1,77 @@
+/// Check that rendering options are applied when drawing images, and that
+/// images drawn with the same options are identical.
+use navig18xx::brush::render::RenderOptions;
+use navig18xx::brush::snapshot::{render_png, render_png_with};
+use navig18xx::prelude::*;
+
+fn draw_tile(hex: &Hex, ctx: &cairo::Context) {
+    let tile = navig18xx::catalogue::tile_catalogue()
+        .into_iter()
+        .find(|tile| tile.name == "57")
+        .unwrap();
+    tile.draw(ctx, hex);
+}
+
+#[test]
+fn default_options_do_not_change_context() {
+    let options = RenderOptions::default();
+    let png = ImageFormat::Png;
+    let result = png.image_bytes_with(&options, 10.0, 10.0, 1.0, |ctx| {
+        assert_eq!(ctx.antialias(), cairo::Antialias::Default);
+        let font_options = ctx.font_options().unwrap();
+        assert_eq!(font_options.hint_style(), cairo::HintStyle::Default);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn deterministic_options_are_applied() {
+    let options = RenderOptions::deterministic();
+    assert_eq!(options.antialias, cairo::Antialias::Gray);
+    assert!(!options.hinting);
+    let png = ImageFormat::Png;
+    let result = png.image_bytes_with(&options, 10.0, 10.0, 1.0, |ctx| {
+        assert_eq!(ctx.antialias(), cairo::Antialias::Gray);
+        let font_options = ctx.font_options().unwrap();
+        assert_eq!(font_options.antialias(), cairo::Antialias::Gray);
+        assert_eq!(font_options.hint_style(), cairo::HintStyle::None);
+        assert_eq!(font_options.hint_metrics(), cairo::HintMetrics::Off);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn font_family_override() -> Result<(), Box<dyn std::error::Error>> {
+    let options = RenderOptions {
+        font_family: Some("Monospace".to_string()),
+        ..RenderOptions::deterministic()
+    };
+    let mut hex = Hex::new(125.0);
+    assert!(hex.theme.font_family.is_none());
+    options.apply_to_hex(&mut hex);
+    assert_eq!(hex.theme.font_family.as_deref(), Some("Monospace"));
+
+    // Options without a font family do not remove the override.
+    RenderOptions::deterministic().apply_to_hex(&mut hex);
+    assert_eq!(hex.theme.font_family.as_deref(), Some("Monospace"));
+
+    // Drawing the same tile with the same options produces the same image.
+    let size = 2.0 * hex.max_d;
+    let first = render_png_with(&options, size, size, |ctx| {
+        ctx.translate(hex.max_d, hex.max_d);
+        draw_tile(&hex, ctx)
+    })?;
+    let second = render_png_with(&options, size, size, |ctx| {
+        ctx.translate(hex.max_d, hex.max_d);
+        draw_tile(&hex, ctx)
+    })?;
+    assert_eq!(first, second);
+
+    // The deterministic options are used by default.
+    let third = render_png(size, size, |ctx| {
+        ctx.translate(hex.max_d, hex.max_d);
+        draw_tile(&hex, ctx)
+    })?;
+    assert_eq!(first, third);
+    Ok(())
+}
@@ -0,0 +1,104 @@
+/// Reset map hexes to their initial tiles, and check that each reset can be
+/// undone in the order that the changes were made.
+use navig18xx::map::TokenChange;
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+fn hex_actions(ui: &UserInterface) -> Vec<&'static str> {
+    ui.keymap
+        .hex_commands(&ui.assets, &ui.state)
+        .iter()
+        .map(|command| command.name)
+        .collect()
+}
+
+/// Places a token in the first token space of the tile on `addr`, and
+/// records this change so that it can be undone.
+fn place_token(ui: &mut UserInterface, addr: HexAddress, company: &str) {
+    let token = ui.assets.map.try_token(company).unwrap();
+    let space = ui.assets.map.tile_at(addr).unwrap().token_spaces()[0];
+    let hex_state = ui.assets.map.hex_state_mut(addr).unwrap();
+    let before = hex_state.token_at(&space).copied();
+    hex_state.set_token_at(&space, token);
+    let change = TokenChange {
+        addr,
+        space,
+        before,
+        after: Some(token),
+    };
+    let entry = format!("Placed {} token", company);
+    ui.assets.record_token_changes(vec![entry], vec![change]);
+}
+
+#[test]
+fn reset_hexes_and_undo() {
+    let mut ui = new_ui();
+
+    let coords = ui.assets.games.active().coordinate_system();
+    let empty = coords.parse("C7").unwrap();
+    let city = coords.parse("B4").unwrap();
+    let reset_name = HexAction::ResetHex.command_name();
+    assert!(ui.assets.map.tile_at(empty).is_none());
+    let (initial, rotation) = {
+        let (tile, rotation) = ui.assets.map.initial_tile(city).unwrap();
+        (tile.name.clone(), rotation)
+    };
+    assert_eq!(ui.assets.map.tile_at(city).unwrap().name, initial);
+    let original = ui.assets.map.clone();
+
+    // Hexes that are in their initial state cannot be reset.
+    ui.state.as_default_mut().unwrap().set_active_hex(city);
+    assert!(!hex_actions(&ui).contains(&reset_name));
+    assert_eq!(press(&mut ui, gdk::Key::x), UiResponse::None);
+
+    // Upgrade the city, place a token on it, and place a tile on the
+    // empty hex.
+    assert!(ui.assets.place_tile(city, "57", RotateCW::One));
+    place_token(&mut ui, city, "PRR");
+    assert!(ui.assets.place_tile(empty, "8", RotateCW::Two));
+    let modified = ui.assets.map.clone();
+    assert!(hex_actions(&ui).contains(&reset_name));
+
+    // Reset the city: the initial tile is restored and the token removed.
+    assert_eq!(press(&mut ui, gdk::Key::x), UiResponse::Redraw);
+    let tile = ui.assets.map.tile_at(city).unwrap();
+    assert_eq!(tile.name, initial);
+    let hex_state = ui.assets.map.hex_state(city).unwrap();
+    assert_eq!(*hex_state.rotation(), rotation);
+    assert!(hex_state.tokens().is_empty());
+    assert_eq!(ui.assets.map.hex_state(city), original.hex_state(city));
+    let entry = ui.assets.journal.entries().last().unwrap();
+    assert!(entry.starts_with("Reset B4"), "{}", entry);
+
+    // Place a token on the reset city, and reset the other hex.
+    place_token(&mut ui, city, "NYC");
+    ui.state.as_default_mut().unwrap().set_active_hex(empty);
+    assert_eq!(press(&mut ui, gdk::Key::X), UiResponse::Redraw);
+    assert!(ui.assets.map.tile_at(empty).is_none());
+
+    // Undo each change in reverse order.
+    assert_eq!(
+        press_with(&mut ui, gdk::Key::z, true, false),
+        UiResponse::Redraw
+    );
+    assert_eq!(ui.assets.map.hex_state(empty), modified.hex_state(empty));
+    let entry = ui.assets.journal.entries().last().unwrap();
+    assert!(entry.starts_with("Undo: Reset C7"), "{}", entry);
+    assert_eq!(
+        press_with(&mut ui, gdk::Key::z, true, false),
+        UiResponse::Redraw
+    );
+    assert_eq!(ui.assets.map.hex_state(city), original.hex_state(city));
+    assert_eq!(
+        press_with(&mut ui, gdk::Key::z, true, false),
+        UiResponse::Redraw
+    );
+    assert_eq!(ui.assets.map.hex_state(city), modified.hex_state(city));
+    assert_eq!(ui.assets.map, modified);
+    assert!(ui.assets.reset_history.is_empty());
+}
@@ -0,0 +1,110 @@
+/// Draw badges that show the train name and revenue for each route, and the
+/// stop numbers of each visit, and check that these badges do not overlap
+/// each other.
+use cairo::{Context, Format, ImageSurface};
+use navig18xx::brush;
+use navig18xx::brush::badges::{BadgeLayout, Rect};
+use navig18xx::hex::Coord;
+use navig18xx::prelude::*;
+
+mod common;
+use common::*;
+
+#[test]
+fn badges_at_same_anchor_do_not_overlap() {
+    let mut layout = BadgeLayout::new(2.0);
+    let anchor = Coord::from((100.0, 100.0));
+    let rects: Vec<Rect> =
+        (0..10).map(|_| layout.place(anchor, 30.0, 12.0)).collect();
+    for (ix, rect) in rects.iter().enumerate() {
+        for other in &rects[ix + 1..] {
+            assert!(!rect.overlaps(other));
+        }
+    }
+    // The first badge is placed directly above the anchor, and the second
+    // badge is placed below and to the right of the anchor, since the
+    // position above and to the right overlaps the first badge.
+    assert_eq!(rects[0].centre(), Coord::from((100.0, 92.0)));
+    assert_eq!(rects[1].centre(), Coord::from((117.0, 108.0)));
+    assert_eq!(layout.placed(), rects.as_slice());
+}
+
+#[test]
+fn route_badges_montreal() {
+    let game = navig18xx::game::new_1867();
+    let hex = Hex::new(125.0);
+    let map = montreal_map(&game, &hex);
+    let company_token = *game.first_token();
+    let train_4 = *game
+        .train_types()
+        .into_iter()
+        .find(|t| game.train_name(t) == Some("4"))
+        .unwrap();
+    let trains: Trains = vec![train_4, train_4].into();
+    let criteria = Criteria {
+        token: company_token,
+        path_limit: trains.path_limit(),
+        conflict_rule: ConflictRule::TrackOrCityHex,
+        route_conflict_rule: ConflictRule::TrackOnly,
+        token_rule: RouteTokenRule::AnyToken,
+    };
+    let paths = paths_for_token(&map, &criteria);
+    let best = trains.select_routes(paths, vec![]).unwrap();
+    assert_eq!(best.train_routes.len(), 2);
+
+    let (width, height) = brush::image_size(|ctx| {
+        let mut hex_iter = map.hex_iter(&hex, ctx);
+        brush::draw_map(&hex, ctx, &mut hex_iter);
+    })
+    .unwrap();
+    let surface =
+        ImageSurface::create(Format::ARgb32, width as i32, height as i32)
+            .unwrap();
+    let ctx = Context::new(&surface).unwrap();
+    let mut hex_iter = map.hex_iter(&hex, &ctx);
+    brush::clear_surface(&ctx, Colour::WHITE);
+    brush::draw_map(&hex, &ctx, &mut hex_iter);
+    brush::highlight_routes(&hex, &ctx, &map, &best.routes(), |ix| {
+        hex.theme.nth_highlight_colour(ix)
+    });
+
+    // Both routes start at Montreal, so their first badges must be moved
+    // apart.
+    let mut layout = BadgeLayout::new(0.02 * hex.max_d);
+    let mut num_badges = 0;
+    for (ix, route) in best.train_routes.iter().enumerate() {
+        let stops = route.route.visits.iter().filter(|v| v.revenue > 0);
+        num_badges += stops.count() + 1;
+        let title = format!("4: ${}", route.revenue);
+        brush::badges::draw_route_badges(
+            &hex,
+            &ctx,
+            &map,
+            &route.route,
+            &title,
+            hex.theme.nth_highlight_colour(ix),
+            &mut layout,
+        );
+    }
+    let placed = layout.placed();
+    assert_eq!(placed.len(), num_badges);
+    for (ix, rect) in placed.iter().enumerate() {
+        for other in &placed[ix + 1..] {
+            assert!(!rect.overlaps(other));
+        }
+    }
+
+    // Each stop badge is placed near the city or dit that it labels.
+    let first_visit = &best.train_routes[0].route.visits[0];
+    let coord =
+        brush::badges::visit_coord(&hex, &ctx, &map, first_visit).unwrap();
+    let m = map.prepare_to_draw(first_visit.addr, &hex, &ctx);
+    let (x, y) = ctx.matrix().transform_point(0.0, 0.0);
+    ctx.set_matrix(m);
+    assert!((coord.x - x).abs() < 0.5 * hex.max_d);
+    assert!((coord.y - y).abs() < 0.5 * hex.max_d);
+
+    let path = std::path::Path::new("./tests/output/route_badges.png");
+    let mut file = std::fs::File::create(path).unwrap();
+    surface.write_to_png(&mut file).unwrap();
+}
@@ -0,0 +1,87 @@
+/// Search for a company's optimal routes, and check that these routes are
+/// reused when searching again until the map is modified.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+fn find_routes(ui: &mut UserInterface) -> Option<usize> {
+    assert_eq!(press(ui, gdk::Key::r), UiResponse::Redraw);
+    wait_until(ui, |ui| ui.state.as_find_routes_found().is_some());
+    let found = ui.state.as_find_routes_found().unwrap();
+    let revenue = found.route_summary(&ui.assets).map(|s| s.net_revenue);
+    press(ui, gdk::Key::Escape);
+    assert!(ui.state.is_default_state());
+    revenue
+}
+
+#[test]
+fn route_cache_ui() {
+    let game = navig18xx::game::new_1830();
+    let trains: Trains = vec![*game.try_train("2").unwrap()].into();
+    let mut dummy = control::DummyController::new();
+    dummy.set_string(Some("Pennsylvania".to_string()));
+    dummy.set_trains(Some((trains.clone(), vec![])));
+    let mut ui = new_ui_with(dummy);
+    assert!(ui.assets.route_cache.is_empty());
+
+    // Place a token for the company in the most valuable token space.
+    let game = ui.assets.games.active();
+    let token = *game.try_token("PRR").unwrap();
+    let best = navig18xx::game::analysis::rank_token_spaces(
+        game,
+        &ui.assets.map,
+        token,
+        &trains,
+        vec![],
+    )[0];
+    let hex_state = ui.assets.map.hex_state_mut(best.addr).unwrap();
+    hex_state.set_token_at(&best.space, token);
+
+    // The routes are cached once they have been found.
+    let revenue = find_routes(&mut ui);
+    assert!(revenue.unwrap() > 0);
+    assert_eq!(ui.assets.route_cache.len(), 1);
+    // The paths from the company's token are also cached.
+    assert_eq!(ui.assets.path_cache.lock().unwrap().len(), 1);
+    let cached =
+        ui.assets
+            .route_cache
+            .get(&ui.assets.map, "PRR", &trains, &[]);
+    let cached = cached.unwrap().as_ref().map(|routes| routes.net_revenue);
+    assert_eq!(cached, revenue);
+
+    // The cached routes are not used for different trains or bonuses.
+    let cache = &ui.assets.route_cache;
+    let map = &ui.assets.map;
+    assert!(cache.get(map, "PRR", &trains, &[true]).is_none());
+    let game = ui.assets.games.active();
+    let other: Trains = vec![*game.try_train("3").unwrap()].into();
+    assert!(cache.get(map, "PRR", &other, &[]).is_none());
+
+    // Searching again reuses the cached routes, rather than searching.
+    let map = &ui.assets.map;
+    ui.assets
+        .route_cache
+        .insert(map, "PRR", trains.clone(), vec![], None);
+    assert_eq!(find_routes(&mut ui), None);
+
+    // Modifying the map invalidates the cached routes.
+    let coords = ui.assets.games.active().coordinate_system();
+    let addr = coords.parse("C7").unwrap();
+    assert!(ui.assets.map.place_tile(addr, "7", RotateCW::Zero));
+    let cache = &ui.assets.route_cache;
+    assert!(cache.get(&ui.assets.map, "PRR", &trains, &[]).is_none());
+    assert_eq!(find_routes(&mut ui), revenue);
+    // The cached map connectivity is updated to include the new tile.
+    let graph = navig18xx::route::MapGraph::new(&ui.assets.map);
+    assert_eq!(ui.assets.map_graph(), graph);
+    assert_eq!(ui.assets.path_cache.lock().unwrap().len(), 1);
+
+    // Replacing the map discards the cached paths.
+    ui.assets.notify(MapEvent::MapReset);
+    assert!(ui.assets.path_cache.lock().unwrap().is_empty());
+}
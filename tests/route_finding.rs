@@ -1,8 +1,12 @@
 use cairo::{Context, Format, ImageSurface};
 use navig18xx::brush;
 use navig18xx::prelude::*;
+use navig18xx::route::StandardScoring;
 use std::collections::BTreeMap;
 
+mod common;
+use common::*;
+
 fn new_context(width: i32, height: i32) -> (Context, ImageSurface) {
     let surface = ImageSurface::create(Format::ARgb32, width, height)
         .expect("Can't create surface");
@@ -86,6 +90,7 @@ fn test_dual_routes_from_montreal() {
         path_limit: limit,
         conflict_rule: ConflictRule::TrackOrCityHex,
         route_conflict_rule: ConflictRule::TrackOnly,
+        token_rule: RouteTokenRule::AnyToken,
     };
 
     let paths = paths_for_token(&map, &criteria);
@@ -108,3 +113,103 @@ fn test_dual_routes_from_montreal() {
     surf.write_to_png(&mut file)
         .expect("Couldn't write to output PNG file");
 }
+
+#[test]
+/// This test checks that when a train's routes cannot share a city hex with
+/// other routes, the two routes from Montreal cannot both be operated.
+fn test_dual_routes_with_train_conflict_rules() {
+    let game = navig18xx::game::new_1867();
+    let hex = Hex::new(125.0);
+    let map = montreal_map(&game, &hex);
+    let company_token = *game.first_token();
+    let train_4 = *game
+        .train_types()
+        .into_iter()
+        .find(|t| game.train_name(t) == Some("4"))
+        .unwrap();
+    let company_trains: Trains = vec![train_4, train_4].into();
+    let rules = RouteConflictRules::new(ConflictRule::TrackOnly)
+        .with_train_rule(train_4, ConflictRule::TrackOrCity);
+    let criteria = Criteria {
+        token: company_token,
+        path_limit: company_trains.path_limit(),
+        conflict_rule: ConflictRule::TrackOrCityHex,
+        route_conflict_rule: rules.most_permissive(),
+        token_rule: RouteTokenRule::AnyToken,
+    };
+    let scoring = StandardScoring;
+
+    // The default rule should find the same routes as select_routes().
+    let paths = paths_for_token(&map, &criteria);
+    let default_rules = RouteConflictRules::new(ConflictRule::TrackOnly);
+    let best = company_trains
+        .select_routes_with_rules(
+            &map,
+            paths,
+            vec![],
+            &scoring,
+            &default_rules,
+        )
+        .expect("No routes found");
+    assert_eq!(best.train_routes.len(), 2);
+    assert_eq!(best.net_revenue, 230);
+
+    // The two routes share Montreal, so only one route can be operated when
+    // this is prohibited.
+    let paths = paths_for_token(&map, &criteria);
+    let best = company_trains
+        .select_routes_with_rules(&map, paths, vec![], &scoring, &rules)
+        .expect("No routes found");
+    assert!(best.net_revenue < 230);
+    let cities: Vec<_> = best
+        .train_routes
+        .iter()
+        .flat_map(|route| route.route.visits.iter())
+        .map(|visit| (visit.addr, visit.visits))
+        .collect();
+    let unique: std::collections::BTreeSet<_> = cities.iter().collect();
+    assert_eq!(cities.len(), unique.len());
+}
+
+#[test]
+/// This test checks that pruning path combinations by their revenue upper
+/// bounds selects the same routes as an exhaustive search.
+fn test_pruning_matches_exhaustive_search() {
+    let game = navig18xx::game::new_1867();
+    let hex = Hex::new(125.0);
+    let map = montreal_map(&game, &hex);
+    let company_token = *game.first_token();
+    let all_trains: BTreeMap<&str, Train> = game
+        .train_types()
+        .into_iter()
+        .map(|t| (game.train_name(t).unwrap(), *t))
+        .collect();
+    let train_sets: [&[&str]; 4] = [
+        &["4", "4"],
+        &["2", "3", "4"],
+        &["3", "4", "5", "6"],
+        &["5+5E", "2", "3", "4"],
+    ];
+    for names in train_sets {
+        let trains: Trains =
+            names.iter().map(|name| all_trains[name]).collect();
+        let criteria = Criteria {
+            token: company_token,
+            path_limit: trains.path_limit(),
+            conflict_rule: ConflictRule::TrackOrCityHex,
+            route_conflict_rule: ConflictRule::TrackOnly,
+            token_rule: RouteTokenRule::AnyToken,
+        };
+        let paths = paths_for_token(&map, &criteria);
+        let pruned = trains
+            .clone()
+            .select_routes(paths.clone(), vec![])
+            .expect("No routes found");
+        let exhaustive = trains
+            .with_pruning(false)
+            .select_routes(paths, vec![])
+            .expect("No routes found");
+        assert_eq!(pruned.net_revenue, exhaustive.net_revenue);
+        assert_eq!(pruned.train_routes, exhaustive.train_routes);
+    }
+}
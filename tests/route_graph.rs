@@ -0,0 +1,54 @@
+/// Show the route graph for a company, and check that the paths from each of
+/// its tokens are counted, including tokens from which there are no paths.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+#[test]
+fn route_graph_counts_paths() {
+    let game = navig18xx::game::new_1830();
+    let trains: Trains = vec![*game.try_train("2").unwrap()].into();
+    let mut dummy = control::DummyController::new();
+    dummy.set_string(Some("Pennsylvania".to_string()));
+    dummy.set_trains(Some((trains, vec![])));
+    let mut ui = new_ui_with(dummy);
+
+    // Place a token for the company in its home city.
+    let addr = ui.assets.games.active().coordinate_system().parse("J8");
+    let addr = addr.unwrap();
+    let token = ui.assets.map.try_token("PRR").unwrap();
+    let space = ui.assets.map.tile_at(addr).unwrap().token_spaces()[0];
+    ui.assets
+        .map
+        .hex_state_mut(addr)
+        .unwrap()
+        .set_token_at(&space, token);
+
+    // Select the company and its trains, and wait for the paths to be
+    // counted.
+    assert_eq!(press(&mut ui, gdk::Key::w), UiResponse::Redraw);
+    wait_until(&mut ui, |ui| {
+        ui.state.as_route_graph().and_then(|s| s.counts()).is_some()
+    });
+    let state = ui.state.as_route_graph().unwrap();
+    assert!(!state.graph().nodes().is_empty());
+    let counts = state.counts().unwrap();
+    assert_eq!(counts.len(), 1);
+    assert_eq!(counts[0].addr, addr);
+    // No tiles have been placed, so there is no track that connects this
+    // city to any other location, and the city is labelled "No paths".
+    assert_eq!(counts[0].total(), 0);
+    assert_eq!(counts[0].longest(), None);
+    assert_eq!(state.label(&ui.assets, &counts[0]), "No paths");
+    assert_eq!(ui.state.mode(), "Route graph");
+
+    // Draw the route graph, and return to the default mode.
+    let ctx = ui.assets.hex.context();
+    ui.state.draw(&ui.assets, ctx);
+    press(&mut ui, gdk::Key::Escape);
+    assert!(ui.state.is_default_state());
+}
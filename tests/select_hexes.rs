@@ -0,0 +1,79 @@
+/// Select hexes by dragging a rectangle around them, and check that exactly
+/// the hexes whose centres lie inside the rectangle are selected.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+mod common;
+use common::*;
+
+/// Drags the primary mouse button from `(x0, y0)` to `(x, y)`.
+fn drag(
+    ui: &mut UserInterface,
+    from: (f64, f64),
+    to: (f64, f64),
+    shift: bool,
+) {
+    for finished in [false, true] {
+        let event = ButtonDrag {
+            x0: from.0,
+            y0: from.1,
+            x: to.0,
+            y: to.1,
+            button: ButtonPress::PRIMARY,
+            shift,
+            finished,
+        };
+        let response = ui.handle_button_drag(&event);
+        ui.respond(response);
+    }
+}
+
+/// Returns the selected hexes, if the user is selecting hexes.
+fn selected(ui: &UserInterface) -> Option<Vec<HexAddress>> {
+    ui.state
+        .as_select()
+        .map(|state| state.selected().iter().copied().collect())
+}
+
+#[test]
+fn drag_to_select_hexes() {
+    let mut ui = new_ui();
+    let b4 = hex_addr("B4");
+    let c5 = hex_addr("C5");
+
+    // The centres of these hexes on the 1830 map, with the default hex size.
+    let (b4_x, b4_y) = (118.25, 353.75);
+    let (c5_x, c5_y) = (172.38, 447.5);
+    let (x, y) = hex_centre(&ui, b4);
+    assert!((x - b4_x).abs() < 0.01 && (y - b4_y).abs() < 0.01);
+    let (x, y) = hex_centre(&ui, c5);
+    assert!((x - c5_x).abs() < 0.01 && (y - c5_y).abs() < 0.01);
+
+    // Drag a small rectangle around the centre of B4.
+    let d = 10.0;
+    drag(&mut ui, (b4_x - d, b4_y - d), (b4_x + d, b4_y + d), false);
+    assert_eq!(selected(&ui), Some(vec![b4]));
+
+    // Hold Shift and drag a rectangle around the centre of C5 to extend the
+    // selection.
+    drag(&mut ui, (c5_x + d, c5_y + d), (c5_x - d, c5_y - d), true);
+    assert_eq!(selected(&ui), Some(vec![b4, c5]));
+
+    // Drag a rectangle that covers both hexes, without holding Shift.
+    drag(&mut ui, (b4_x - d, b4_y - d), (c5_x + d, c5_y + d), false);
+    let hexes = selected(&ui).unwrap();
+    assert!(hexes.contains(&b4) && hexes.contains(&c5));
+
+    // Drag a rectangle around a point that is inside B4, but which does not
+    // contain its centre; no hexes are selected, so the selection does not
+    // change.
+    drag(&mut ui, (c5_x - d, c5_y - d), (c5_x + d, c5_y + d), false);
+    assert_eq!(selected(&ui), Some(vec![c5]));
+    drag(
+        &mut ui,
+        (b4_x + 20.0, b4_y - d),
+        (b4_x + 40.0, b4_y + d),
+        false,
+    );
+    assert_eq!(selected(&ui), Some(vec![c5]));
+}
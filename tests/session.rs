@@ -0,0 +1,87 @@
+/// Check that a session can be written to a file and read back unchanged,
+/// and that the selected map theme is saved with the session.
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+#[test]
+fn write_and_read_session() {
+    let output_dir = std::path::Path::new("./tests/output");
+    std::fs::create_dir_all(output_dir).unwrap();
+    let path = output_dir.join("session-round-trip.json");
+
+    let mut game_dirs = std::collections::BTreeMap::new();
+    game_dirs.insert("1830".to_string(), "games/1830".into());
+    let session = session::Session {
+        game_path: Some("games/1830/test.json".into()),
+        hex_diameter: Some(150.0),
+        viewport: Some((120.0, 80.0)),
+        window_size: Some((1024, 768)),
+        language: Some("Français".to_string()),
+        theme: Some("High contrast".to_string()),
+        view: view::ViewTransform::default()
+            .rotated_clockwise()
+            .mirrored(),
+        background: None,
+        recent_files: vec![
+            "games/1830/test.json".into(),
+            "games/1867/other.json".into(),
+        ],
+        game_dirs,
+    };
+    session.write(&path).unwrap();
+    assert_eq!(session::Session::read(&path).unwrap(), session);
+
+    // Fields that have default values are omitted.
+    let session = session::Session::default();
+    session.write(&path).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "{}");
+    assert_eq!(session::Session::read(&path).unwrap(), session);
+
+    // Reading a file that does not exist returns the default session, and
+    // reading an invalid file returns an error.
+    let missing = output_dir.join("session-missing.json");
+    assert_eq!(session::Session::read(missing).unwrap(), session);
+    std::fs::write(&path, "{\"hex_diameter\": \"large\"}").unwrap();
+    assert!(session::Session::read(&path).is_err());
+}
+
+#[test]
+fn select_theme() {
+    let mut ctrl = control::DummyController::new();
+    ctrl.set_index(Some(1));
+    let mut ui = new_ui_with(ctrl);
+    let default_theme = ui.assets.hex.theme.clone();
+    assert_eq!(ui.theme_name(), "Default");
+    assert!(ui.session().theme.is_none());
+
+    assert_eq!(
+        press_with(&mut ui, gdk::Key::k, true, true),
+        UiResponse::None
+    );
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::None);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::Redraw);
+    assert_eq!(ui.theme_name(), "High contrast");
+    assert_ne!(ui.assets.hex.theme, default_theme);
+
+    // The selected theme is saved with the session, and restored.
+    let session = ui.session();
+    assert_eq!(session.theme.as_deref(), Some("High contrast"));
+    let output_dir = std::path::Path::new("./tests/output");
+    std::fs::create_dir_all(output_dir).unwrap();
+    let path = output_dir.join("session-theme.json");
+    session.write(&path).unwrap();
+    assert_eq!(ui.set_theme(0), UiResponse::Redraw);
+    assert_eq!(ui.assets.hex.theme, default_theme);
+    assert!(ui.session().theme.is_none());
+    let _response = ui.restore_session(path);
+    assert_eq!(ui.theme_name(), "High contrast");
+    assert_ne!(ui.assets.hex.theme, default_theme);
+
+    // Unknown themes are ignored.
+    assert_eq!(ui.set_theme(2), UiResponse::None);
+    assert_eq!(ui.theme_name(), "High contrast");
+}
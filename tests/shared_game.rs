@@ -0,0 +1,119 @@
+//! Share a game with other players, and check that each player's moves are
+//! applied to every player's map.
+#![cfg(feature = "net")]
+use navig18xx::game::sim::Move;
+use navig18xx::map::{Clipboard, TileDescr};
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+/// Returns the tile and tokens on each map hex.
+fn map_contents(ui: &UserInterface) -> Vec<Option<TileDescr>> {
+    let map = &ui.assets.map;
+    map.hex_address_iter()
+        .map(|&addr| {
+            let hex_state = map.hex_state(addr)?;
+            let mut descr: TileDescr = (map, addr, hex_state).into();
+            descr.tokens.sort();
+            Some(descr)
+        })
+        .collect()
+}
+
+#[test]
+fn shared_game_applies_accepted_moves() {
+    let mut ui = new_ui();
+    let addr = ui.host_session("127.0.0.1:0").unwrap();
+    let mut player = navig18xx::net::Session::join(addr, || {}).unwrap();
+    let c7 = hex_addr("C7");
+    // Tiles can be placed on any map hex, as they can be when editing the
+    // map locally, and so green tiles can be placed in the first phase.
+    player
+        .propose(Move::LayTile {
+            addr: c7,
+            tile: "15".to_string(),
+            rotation: RotateCW::Zero,
+            company: None,
+        })
+        .unwrap();
+    player
+        .propose(Move::RotateTile {
+            addr: c7,
+            rotation: RotateCW::Two,
+        })
+        .unwrap();
+
+    let start = std::time::Instant::now();
+    while ui.assets.journal.entries().len() < 2 {
+        assert!(start.elapsed().as_secs() < 60, "Timed out");
+        let _ = ui.ping(PingDest::Session);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert_eq!(
+        ui.assets.journal.entries(),
+        [
+            "Placed tile 15 at C7 (rotation 0)",
+            "Rotated tile 15 at C7 to rotation 2"
+        ]
+    );
+    let hex_state = ui.assets.map.hex_state(c7).unwrap();
+    assert_eq!(*hex_state.rotation(), RotateCW::Two);
+    ui.leave_session();
+}
+
+#[test]
+fn shared_game_resets_and_pastes_hexes() {
+    let mut host = new_ui();
+    let addr = host.host_session("127.0.0.1:0").unwrap();
+    let mut player = new_ui();
+    let response = player.join_session(addr).unwrap();
+    player.respond(response);
+
+    // Upgrade a city, place a token on it, and place a tile on an empty hex.
+    let city = hex_addr("B4");
+    let c7 = hex_addr("C7");
+    assert!(host.assets.place_tile(city, "57", RotateCW::One));
+    let place_token = Move::PlaceToken {
+        addr: city,
+        space: 0,
+        company: "PRR".to_string(),
+    };
+    host.assets.apply_move(&place_token).unwrap();
+    assert!(host.assets.place_tile(c7, "8", RotateCW::Two));
+    host.respond(UiResponse::None);
+
+    // Reset the city to its initial tile, which removes the token, and then
+    // undo this reset.
+    assert!(host.assets.reset_hex(city));
+    host.respond(UiResponse::None);
+    assert!(host.assets.undo());
+    host.respond(UiResponse::None);
+
+    // Paste the city, and its token, onto the tile that was placed on C7,
+    // and then reset the city.
+    host.assets.clipboard =
+        Some(Clipboard::copy(&host.assets.map, city, &[city]));
+    host.state = State::default_state(c7);
+    press_with(&mut host, gdk::Key::v, true, false);
+    assert_eq!(host.assets.map.tile_at(c7).unwrap().name, "57");
+    assert!(host.assets.reset_hex(city));
+    host.respond(UiResponse::None);
+
+    let expected = map_contents(&host);
+    let start = std::time::Instant::now();
+    while map_contents(&player) != expected {
+        assert!(start.elapsed().as_secs() < 60, "Maps do not match");
+        let response = host.ping(PingDest::Session);
+        host.respond(response);
+        let response = player.ping(PingDest::Session);
+        player.respond(response);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert_eq!(map_contents(&host), map_contents(&player));
+    player.leave_session();
+    host.leave_session();
+}
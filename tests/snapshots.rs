@@ -0,0 +1,126 @@
+/// Compare rendered maps against reference images, to detect unintended
+/// changes in how maps are drawn.
+///
+/// This renders the initial map of each built-in game, and the map of each
+/// saved game in `SAVED_GAMES`, and compares each image to the reference
+/// image of the same name in `tests/reference`.
+/// Each rendered image is written to `tests/output` and, if it does not
+/// match the reference image, an image that highlights the differences is
+/// written alongside it.
+///
+/// A missing reference image is reported as a mismatch.
+/// Set the `UPDATE_SNAPSHOTS` environment variable to create or replace all
+/// of the reference images with the rendered images.
+use navig18xx::brush::snapshot::{compare_png, render_png, Tolerance};
+use navig18xx::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// The saved games whose maps should be compared to reference images.
+const SAVED_GAMES: [&str; 1] = ["./examples/output/1867_bc.game"];
+
+/// The hexagon size, which is small so that reference images are small.
+const HEX_SIZE: f64 = 50.0;
+
+/// The directory that contains the reference images.
+const REFERENCE_DIR: &str = "./tests/reference";
+
+/// The directory to which rendered images and differences are written.
+const OUTPUT_DIR: &str = "./tests/output";
+
+#[test]
+fn initial_maps_match_references() -> Result<(), Box<dyn std::error::Error>> {
+    let hex = Hex::new(HEX_SIZE);
+    let mismatches: Vec<String> = navig18xx::game::games()
+        .iter()
+        .map(|game| {
+            let map = game.create_map(&hex);
+            // NOTE: use the game number (e.g., "1830") from the game name.
+            let number = game.name().split(':').next().unwrap();
+            let name = format!("snapshot-{}-initial", number);
+            check_snapshot(&name, &map, &hex)
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+    assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+    Ok(())
+}
+
+#[test]
+fn saved_maps_match_references() -> Result<(), Box<dyn std::error::Error>> {
+    let hex = Hex::new(HEX_SIZE);
+    let mut games = navig18xx::game::games();
+    let mut mismatches = vec![];
+    for path in SAVED_GAMES {
+        let path = Path::new(path);
+        let state = read_game_state(path)?;
+        let game = games
+            .iter_mut()
+            .find(|game| game.name() == state.game)
+            .ok_or("No matching game for game file")?;
+        let map = game.load(&hex, state).ok_or("Could not load map")?;
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap();
+        let name = format!("snapshot-{}", stem);
+        mismatches.extend(check_snapshot(&name, &map, &hex)?);
+    }
+    assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+    Ok(())
+}
+
+/// Draws the map on the provided context.
+fn draw(map: &Map, hex: &Hex, ctx: &cairo::Context) {
+    let mut hex_iter = map.hex_iter(hex, ctx);
+    navig18xx::brush::draw_map(hex, ctx, &mut hex_iter);
+}
+
+/// Renders the map and compares it to the reference image, and returns a
+/// description of the differences if the images do not match.
+fn check_snapshot(
+    name: &str,
+    map: &Map,
+    hex: &Hex,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let (width, height) =
+        image_size(|ctx| draw(map, hex, ctx)).ok_or("No image size")?;
+    let rendered = render_png(width, height, |ctx| draw(map, hex, ctx))?;
+    let output = output_path(OUTPUT_DIR, name, "png");
+    std::fs::write(&output, &rendered)?;
+
+    let reference = output_path(REFERENCE_DIR, name, "png");
+    let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+    if update {
+        std::fs::create_dir_all(REFERENCE_DIR)?;
+        std::fs::write(&reference, &rendered)?;
+        return Ok(None);
+    }
+    if !reference.exists() {
+        return Ok(Some(format!(
+            "{} has no reference image: missing reference {}",
+            output.display(),
+            reference.display()
+        )));
+    }
+
+    let tolerance = Tolerance::default();
+    let comparison =
+        compare_png(&rendered, &std::fs::read(&reference)?, &tolerance)?;
+    if comparison.matches(&tolerance) {
+        return Ok(None);
+    }
+    let diff = output_path(OUTPUT_DIR, &format!("{}-diff", name), "png");
+    comparison.save_diff(&diff)?;
+    Ok(Some(format!(
+        "{} differs from {} in {} pixels ({:.3}%), see {}",
+        output.display(),
+        reference.display(),
+        comparison.differing,
+        100.0 * comparison.fraction(),
+        diff.display()
+    )))
+}
+
+/// Returns the path to a file in the given directory.
+fn output_path(dir: &str, name: &str, ext: &str) -> PathBuf {
+    Path::new(dir).join(name).with_extension(ext)
+}
@@ -0,0 +1,96 @@
+/// Check that each user interface state populates the status bar with the
+/// active hex, tile, game phase, and most recent revenue, and that these
+/// segments are preserved when switching between states.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+/// Returns a user interface that shows the start screen.
+fn start_ui() -> UserInterface {
+    let games: Vec<Box<dyn Game>> =
+        vec![Box::new(navig18xx::game::new_1830())];
+    let controller: Controller = control::DummyController::new().into();
+    UserInterface::new(games, controller, Keymap::default())
+}
+
+fn status(ui: &UserInterface) -> StatusBar {
+    let Controller::Dummy(dummy) = &ui.controller else {
+        panic!("Expected a dummy controller")
+    };
+    dummy.status().clone()
+}
+
+#[test]
+fn status_bar_segments() {
+    let mut ui = start_ui();
+    ui.respond(UiResponse::None);
+    let bar = status(&ui);
+    assert_eq!(bar.get(SegmentKind::Mode), Some("Start"));
+    assert_eq!(bar.get(SegmentKind::Hex), None);
+
+    let response = ui.new_game(0);
+    ui.respond(response);
+    let bar = status(&ui);
+    let addr = ui.state.active_hex().unwrap();
+    let hex_name = ui.assets.hex_name(addr);
+    assert_eq!(bar.get(SegmentKind::Mode), Some("Map"));
+    assert_eq!(bar.get(SegmentKind::Hex), Some(hex_name.as_str()));
+    assert_eq!(bar.get(SegmentKind::Phase), Some("Phase 2"));
+    assert_eq!(bar.get(SegmentKind::Revenue), None);
+    // Segments are displayed in a fixed order.
+    let kinds: Vec<SegmentKind> =
+        bar.segments().iter().map(|s| s.kind).collect();
+    let mut sorted = kinds.clone();
+    sorted.sort();
+    assert_eq!(kinds, sorted);
+
+    // Move to a hex that contains a tile.
+    let tile_hex = ui
+        .assets
+        .map
+        .hex_address_iter()
+        .copied()
+        .find(|addr| ui.assets.map.tile_at(*addr).is_some())
+        .unwrap();
+    ui.state = State::default_state(tile_hex);
+    ui.respond(UiResponse::None);
+    let bar = status(&ui);
+    let tile_name = &ui.assets.map.tile_at(tile_hex).unwrap().name;
+    assert_eq!(bar.get(SegmentKind::Tile), Some(tile_name.as_str()));
+
+    // Switching to the screenshot mode preserves the common segments.
+    press(&mut ui, gdk::Key::i);
+    let shot_bar = status(&ui);
+    assert_eq!(shot_bar.get(SegmentKind::Mode), Some("Screenshot"));
+    assert_eq!(shot_bar.get(SegmentKind::Hex), bar.get(SegmentKind::Hex));
+    assert_eq!(shot_bar.get(SegmentKind::Tile), bar.get(SegmentKind::Tile));
+    assert_eq!(
+        shot_bar.get(SegmentKind::Message),
+        ui.controller.window_title().as_deref()
+    );
+    press(&mut ui, gdk::Key::Escape);
+    assert_eq!(status(&ui).get(SegmentKind::Message), None);
+
+    // The most recent revenue is shown in every state.
+    ui.assets.last_revenue = Some(("PRR".to_string(), 120));
+    ui.respond(UiResponse::None);
+    let bar = status(&ui);
+    assert_eq!(bar.get(SegmentKind::Revenue), Some("PRR: $120"));
+    assert!(bar.text().contains(StatusBar::SEPARATOR));
+}
+
+#[test]
+fn replace_segments() {
+    let mut bar = StatusBar::new();
+    bar.set(SegmentKind::Phase, "Phase 3");
+    bar.set(SegmentKind::Mode, "Map");
+    assert_eq!(bar.text(), "Map  |  Phase 3");
+    bar.set(SegmentKind::Phase, "Phase 4");
+    assert_eq!(bar.get(SegmentKind::Phase), Some("Phase 4"));
+    bar.remove(SegmentKind::Mode);
+    assert_eq!(bar.text(), "Phase 4");
+}
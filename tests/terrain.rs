@@ -0,0 +1,64 @@
+/// Check that terrain is drawn on empty and pre-printed map hexes, and is
+/// hidden by tiles that are placed on these hexes.
+use navig18xx::brush::snapshot::render_png;
+use navig18xx::map::Terrain;
+use navig18xx::prelude::*;
+
+fn render(map: &Map, hex: &Hex) -> Vec<u8> {
+    let (width, height) = map.drawing_size(hex);
+    render_png(width, height, |ctx| {
+        let mut hex_iter = map.hex_iter(hex, ctx);
+        navig18xx::brush::draw_map(hex, ctx, &mut hex_iter);
+    })
+    .unwrap()
+}
+
+#[test]
+fn draw_terrain_beneath_tiles() {
+    let hex = Hex::new(50.0);
+    let game = navig18xx::game::new_1830();
+    let mut map = game.create_map(&hex);
+    let empty = map
+        .hex_address_iter()
+        .copied()
+        .find(|addr| map.tile_at(*addr).is_none())
+        .unwrap();
+    // Find a pre-printed hex, whose tile has no colour.
+    let printed = map
+        .hex_address_iter()
+        .copied()
+        .find(|addr| {
+            map.tile_at(*addr)
+                .map(|tile| tile.colour == HexColour::Empty)
+                .unwrap_or(false)
+        })
+        .unwrap();
+    let plain = render(&map, &hex);
+
+    // Terrain is drawn on empty hexes.
+    assert!(map.set_terrain(empty, Terrain::Mountain));
+    let mountain = render(&map, &hex);
+    assert_ne!(mountain, plain);
+    assert!(map.set_terrain(empty, Terrain::Water));
+    let water = render(&map, &hex);
+    assert_ne!(water, plain);
+    assert_ne!(water, mountain);
+
+    // Save an image of the map with terrain on an empty and a pre-printed
+    // hex, for visual inspection.
+    assert!(map.set_terrain(printed, Terrain::Mountain));
+    let with_printed = render(&map, &hex);
+    assert_ne!(with_printed, water);
+    let output_dir = std::path::Path::new("./tests/output");
+    std::fs::create_dir_all(output_dir).unwrap();
+    std::fs::write(output_dir.join("terrain_1830.png"), &with_printed)
+        .unwrap();
+    map.clear_terrain(printed);
+
+    // Terrain is hidden when a coloured tile is placed on the hex.
+    assert!(map.place_tile(empty, "57", RotateCW::Zero));
+    let with_terrain = render(&map, &hex);
+    map.clear_terrain(empty);
+    let without_terrain = render(&map, &hex);
+    assert_eq!(with_terrain, without_terrain);
+}
@@ -0,0 +1,112 @@
+/// Check that text entered by the user can set a custom game title, add
+/// notes to the game journal, and rename the saved game file.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+/// Creates a new game of 1830 with a controller whose text entry dialogs
+/// return `text`.
+fn new_ui(text: Option<&str>) -> UserInterface {
+    let mut dummy = control::DummyController::new();
+    dummy.set_text(text.map(|s| s.to_string()));
+    new_ui_with(dummy)
+}
+
+/// Presses `Ctrl` and the specified key.
+fn press_ctrl(ui: &mut UserInterface, key: gdk::Key) -> UiResponse {
+    let event = KeyPress {
+        key,
+        ctrl: true,
+        alt: false,
+        shift: false,
+    };
+    ui.handle_key_press(&event)
+}
+
+#[test]
+fn set_game_title() {
+    let mut ui = new_ui(Some("  Tuesday club game "));
+    let name = "1830: Railways and Robber Barons";
+    assert_eq!(ui.controller.window_title().as_deref(), Some(name));
+
+    assert_eq!(press_ctrl(&mut ui, gdk::Key::g), UiResponse::None);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::None);
+    assert_eq!(ui.assets.title.as_deref(), Some("Tuesday club game"));
+    let title = format!("Tuesday club game - {}", name);
+    assert_eq!(ui.controller.window_title(), Some(title));
+
+    // The title is saved with the game.
+    let state = ui
+        .assets
+        .games
+        .active()
+        .save(&ui.assets.map)
+        .with_title(ui.assets.title.clone());
+    let text = navig18xx::io::game_state_to_string(state, false).unwrap();
+    let state = navig18xx::io::game_state_from_str(&text).unwrap();
+    assert_eq!(state.title.as_deref(), Some("Tuesday club game"));
+
+    // Starting a new game removes the custom title.
+    let response = ui.new_game(0);
+    ui.respond(response);
+    assert_eq!(ui.assets.title, None);
+    assert_eq!(ui.controller.window_title().as_deref(), Some(name));
+}
+
+#[test]
+fn add_journal_note() {
+    let mut ui = new_ui(Some("Sold the B&O private company"));
+    assert_eq!(press_ctrl(&mut ui, gdk::Key::m), UiResponse::None);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::None);
+    assert_eq!(
+        ui.assets.journal.entries().last().map(|s| s.as_str()),
+        Some("Note: Sold the B&O private company")
+    );
+
+    // Empty notes are not recorded, and cancelling does nothing.
+    let num_entries = ui.assets.journal.len();
+    let response = ui.add_note("   ");
+    ui.respond(response);
+    assert_eq!(ui.assets.journal.len(), num_entries);
+    let mut ui = new_ui(None);
+    assert_eq!(press_ctrl(&mut ui, gdk::Key::m), UiResponse::None);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::None);
+    assert!(ui.assets.journal.is_empty());
+}
+
+#[test]
+fn rename_saved_game() {
+    let output_dir = std::path::Path::new("./tests/output");
+    let old_path = output_dir.join("text_entry_rename.json");
+    let new_path = output_dir.join("text_entry_renamed.json");
+    for path in [&old_path, &new_path] {
+        if path.exists() {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    // The game cannot be renamed before it has been saved.
+    let mut ui = new_ui(Some("text_entry_renamed"));
+    assert_eq!(press_ctrl(&mut ui, gdk::Key::r), UiResponse::None);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::None);
+    assert!(!new_path.exists());
+
+    // The new name keeps the current extension.
+    let response = ui.save_game(old_path.clone());
+    ui.respond(response);
+    assert!(old_path.exists());
+    assert_eq!(press_ctrl(&mut ui, gdk::Key::r), UiResponse::None);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::None);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::None);
+    assert!(!old_path.exists());
+    assert!(new_path.exists());
+
+    // The file cannot be moved into another directory.
+    let response = ui.rename_game_to("../renamed.json");
+    ui.respond(response);
+    assert!(new_path.exists());
+}
@@ -0,0 +1,58 @@
+/// Check that tile sheets arrange tiles in rows and columns, and that tiles
+/// are given distinct file names.
+use navig18xx::brush::sheet::{file_stems, TileSheet};
+use navig18xx::prelude::*;
+
+#[test]
+fn tile_sheet_layout() {
+    let hex = Hex::new(100.0);
+    let sheet = TileSheet {
+        columns: 4,
+        spacing: 10.0,
+        background: Some(Colour::WHITE),
+    };
+    assert_eq!(sheet.grid(0), (0, 0));
+    assert_eq!(sheet.grid(3), (1, 3));
+    assert_eq!(sheet.grid(4), (1, 4));
+    assert_eq!(sheet.grid(9), (3, 4));
+    assert_eq!(sheet.size(&hex, 9), (450.0, 340.0));
+    assert_eq!(sheet.centre(&hex, 0), (60.0, 60.0));
+    assert_eq!(sheet.centre(&hex, 5), (170.0, 170.0));
+
+    // Draw two tiles, and check that each tile covers part of its cell and
+    // that the space between the tiles is white.
+    let tiles = tile_catalogue();
+    let sheet_tiles: Vec<&Tile> = ["7", "57"]
+        .iter()
+        .map(|name| tiles.iter().find(|t| t.name == *name).unwrap())
+        .collect();
+    let (width, height) = sheet.size(&hex, sheet_tiles.len());
+    let mut image = cairo::ImageSurface::create(
+        cairo::Format::ARgb32,
+        width as i32,
+        height as i32,
+    )
+    .unwrap();
+    let ctx = cairo::Context::new(&image).unwrap();
+    sheet.draw(&hex, &ctx, sheet_tiles);
+    drop(ctx);
+    let stride = image.stride() as usize;
+    let data = image.data().unwrap();
+    let is_white = |x: f64, y: f64| {
+        let ix = (y as usize) * stride + 4 * (x as usize);
+        data[ix..ix + 4].iter().all(|byte| *byte == 255)
+    };
+    assert!(is_white(115.0, 60.0));
+    assert!(!is_white(sheet.centre(&hex, 1).0, 30.0));
+    assert!(!is_white(60.0, 30.0));
+}
+
+#[test]
+fn tile_file_names_are_distinct() {
+    let tiles = tile_catalogue();
+    let stems = file_stems(tiles.iter().map(|tile| &tile.name));
+    let unique: std::collections::BTreeSet<&String> = stems.iter().collect();
+    assert_eq!(unique.len(), tiles.len());
+    // The same names always result in the same file names.
+    assert_eq!(stems, file_stems(tiles.iter().map(|tile| &tile.name)));
+}
@@ -0,0 +1,113 @@
+/// Check that a tiled drawing surface only creates the tiles that are
+/// painted, and that these tiles contain what was drawn on the surface.
+use navig18xx::ui::{Canvas, Surface, TiledSurface};
+
+/// Returns the `(red, green, blue)` components of the pixel at `(x, y)`.
+fn pixel(
+    image: &mut cairo::ImageSurface,
+    x: usize,
+    y: usize,
+) -> (u8, u8, u8) {
+    let stride = image.stride() as usize;
+    let data = image.data().unwrap();
+    let ix = y * stride + 4 * x;
+    // NOTE: ARgb32 pixels are stored as native-endian 32-bit integers.
+    let argb = u32::from_ne_bytes([
+        data[ix],
+        data[ix + 1],
+        data[ix + 2],
+        data[ix + 3],
+    ]);
+    ((argb >> 16) as u8, (argb >> 8) as u8, argb as u8)
+}
+
+/// Paints the region `(x, y, width, height)` of a surface onto a new image.
+fn paint_region(
+    surface: &dyn Surface,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+) -> cairo::ImageSurface {
+    let image =
+        cairo::ImageSurface::create(cairo::Format::ARgb32, width, height)
+            .unwrap();
+    let ctx = cairo::Context::new(&image).unwrap();
+    surface.paint(&ctx, -x as f64, -y as f64).unwrap();
+    drop(ctx);
+    image
+}
+
+#[test]
+fn tiled_surface() {
+    let surface = TiledSurface::with_tile_size(1000, 600, 256).unwrap();
+    assert_eq!(surface.tile_size(), 256);
+    assert_eq!(surface.tile_count(), 0);
+
+    // Draw a red square that spans the corners of four tiles.
+    surface.discard().unwrap();
+    let ctx = surface.new_context().unwrap();
+    ctx.set_source_rgb(1.0, 0.0, 0.0);
+    ctx.rectangle(500.0, 240.0, 40.0, 40.0);
+    ctx.fill().unwrap();
+    assert_eq!(surface.tile_count(), 0);
+
+    // Painting a region that lies within a single tile only creates that
+    // tile.
+    let mut image = paint_region(&surface, 520, 200, 20, 20);
+    assert_eq!(surface.tile_count(), 1);
+    assert_eq!(pixel(&mut image, 10, 10), (255, 255, 255));
+
+    // Painting the red square creates the tiles that it overlaps.
+    let mut image = paint_region(&surface, 490, 230, 60, 60);
+    assert_eq!(surface.tile_count(), 4);
+    assert_eq!(pixel(&mut image, 5, 5), (255, 255, 255));
+    assert_eq!(pixel(&mut image, 30, 30), (255, 0, 0));
+
+    // Drawing part of the surface again only discards the tiles that it
+    // overlaps.
+    ctx.set_source_rgb(0.0, 0.0, 1.0);
+    ctx.rectangle(500.0, 240.0, 10.0, 10.0);
+    ctx.fill().unwrap();
+    surface.invalidate(500.0, 240.0, 10.0, 10.0);
+    assert_eq!(surface.tile_count(), 3);
+    let mut image = paint_region(&surface, 490, 230, 60, 60);
+    assert_eq!(surface.tile_count(), 4);
+    assert_eq!(pixel(&mut image, 12, 12), (0, 0, 255));
+    assert_eq!(pixel(&mut image, 30, 30), (255, 0, 0));
+
+    // Discarding the surface contents removes every tile.
+    surface.discard().unwrap();
+    assert_eq!(surface.tile_count(), 0);
+    let mut image = paint_region(&surface, 490, 230, 60, 60);
+    assert_eq!(pixel(&mut image, 30, 30), (255, 255, 255));
+
+    // Tiles on the edges of the surface are smaller than the others.
+    surface.discard().unwrap();
+    let mut image = paint_region(&surface, 800, 520, 200, 80);
+    assert_eq!(surface.tile_count(), 1);
+    assert_eq!(pixel(&mut image, 199, 79), (255, 255, 255));
+}
+
+#[test]
+fn tiled_canvas() {
+    let canvas = Canvas::new(800, 600);
+    canvas.discard();
+    let ctx = canvas.context();
+    ctx.set_source_rgb(0.0, 1.0, 0.0);
+    ctx.rectangle(700.0, 500.0, 50.0, 50.0);
+    ctx.fill().unwrap();
+
+    // Copying the canvas contents does not require any tiles.
+    let mut image = canvas.copy_surface();
+    assert_eq!((image.width(), image.height()), (800, 600));
+    assert_eq!(pixel(&mut image, 10, 10), (255, 255, 255));
+    assert_eq!(pixel(&mut image, 720, 520), (0, 255, 0));
+
+    // Resizing the canvas replaces it with an empty tiled surface.
+    let mut canvas = canvas;
+    canvas.resize(1200, 900);
+    assert_eq!((canvas.width(), canvas.height()), (1200, 900));
+    let (_x0, _y0, width, height) = canvas.ink_extents();
+    assert_eq!((width, height), (0.0, 0.0));
+}
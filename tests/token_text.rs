@@ -0,0 +1,72 @@
+/// Check that token labels are shrunk, and split over two lines, so that
+/// they fit inside the token.
+use navig18xx::prelude::*;
+
+fn token() -> Token {
+    Token::new(TokenStyle::SideArcs {
+        fg: (63, 153, 153).into(),
+        bg: (255, 127, 127).into(),
+        text: (0, 0, 0).into(),
+    })
+}
+
+fn context() -> cairo::Context {
+    let surface =
+        cairo::RecordingSurface::create(cairo::Content::ColorAlpha, None)
+            .unwrap();
+    cairo::Context::new(&surface).unwrap()
+}
+
+#[test]
+fn short_labels_are_not_shrunk() {
+    let ctx = context();
+    let token = token();
+    for hex_d in [50.0, 125.0, 250.0] {
+        let hex = Hex::new(hex_d);
+        let size = hex.theme.token_label.get_font_size();
+        for text in ["1", "KK", "PRR", "B&O", "ERIE"] {
+            let fit = token.fit_text(&hex, &ctx, text);
+            assert_eq!(fit.lines, vec![text.to_string()]);
+            assert_eq!(fit.font_size, size);
+            assert!(fit.fits);
+        }
+    }
+}
+
+#[test]
+fn long_labels_are_shrunk_or_wrapped() {
+    let ctx = context();
+    let token = token();
+    let mut hex = Hex::new(125.0);
+    let size = hex.theme.token_label.get_font_size();
+    let min_size = hex.theme.token_label_min_font_size;
+
+    // Longer labels are split over two lines, at a separator if possible.
+    let fit = token.fit_text(&hex, &ctx, "Grand Trunk");
+    assert_eq!(fit.lines, vec!["Grand".to_string(), "Trunk".to_string()]);
+    assert!(fit.font_size < size);
+    assert!(fit.fits);
+    let fit = token.fit_text(&hex, &ctx, "ABCDEFGH");
+    assert_eq!(fit.lines, vec!["ABCD".to_string(), "EFGH".to_string()]);
+    assert!(fit.fits);
+
+    // Explicit line breaks are always respected.
+    let fit = token.fit_text(&hex, &ctx, "NY\nNH");
+    assert_eq!(fit.lines, vec!["NY".to_string(), "NH".to_string()]);
+
+    // Labels can be prevented from being split over two lines, and are
+    // shrunk to fit on a single line instead.
+    hex.theme.token_label_two_lines = false;
+    let fit = token.fit_text(&hex, &ctx, "ABCDE");
+    assert_eq!(fit.lines, vec!["ABCDE".to_string()]);
+    assert!(fit.font_size < size);
+    assert!(fit.font_size >= min_size);
+    assert!(fit.fits);
+
+    // Labels that do not fit at the minimum font size are drawn at this
+    // size.
+    let fit = token.fit_text(&hex, &ctx, "ABCDEFGHIJKL");
+    assert_eq!(fit.lines.len(), 1);
+    assert_eq!(fit.font_size, min_size);
+    assert!(!fit.fits);
+}
@@ -18,14 +18,13 @@ fn new_context(width: i32, height: i32) -> (Context, ImageSurface) {
 #[test]
 fn two_straights_cross() {
     let hex = Hex::new(HEX_DIAMETER);
-    let ctx = hex.context();
     let dt = 0.1;
     let t1 = Track::straight(HexFace::Top);
     let t2 = Track::straight(HexFace::UpperLeft);
-    assert!(t1.crosses(&t2, &hex, dt, ctx));
-    assert!(t2.crosses(&t1, &hex, dt, ctx));
-    assert!(!t1.connected(&t2, &hex, ctx));
-    assert!(!t2.connected(&t1, &hex, ctx));
+    assert!(t1.crosses(&t2, &hex, dt));
+    assert!(t2.crosses(&t1, &hex, dt));
+    assert!(!t1.connected(&t2, &hex));
+    assert!(!t2.connected(&t1, &hex));
 }
 
 fn no_escape(track: &Track, hex: &Hex, dt: f64, ctx: &Context) -> bool {
@@ -0,0 +1,65 @@
+/// Track a company's treasury, check that its cash is shown when selecting a
+/// company, and check that the treasury is saved with the game.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+#[test]
+fn company_dialog_shows_cash() {
+    let game = navig18xx::game::new_1830();
+    let trains: Trains = vec![*game.try_train("2").unwrap()].into();
+    let mut dummy = control::DummyController::new();
+    dummy.set_string(Some("Pennsylvania: $380".to_string()));
+    dummy.set_trains(Some((trains.clone(), vec![])));
+    let mut ui = new_ui_with(dummy);
+
+    // Apply moves that track the company's treasury and pay for tokens.
+    let state = ui.assets.games.active().save(&ui.assets.map);
+    let game = ui.assets.games.active_mut();
+    let coords = game.coordinate_system();
+    let place = |name: &str| navig18xx::game::sim::Move::PlaceToken {
+        addr: coords.parse(name).unwrap(),
+        space: 0,
+        company: "PRR".to_string(),
+    };
+    let moves = [
+        navig18xx::game::sim::Move::SetCash {
+            company: "PRR".to_string(),
+            cash: 420,
+        },
+        place("J8"),
+        place("F6"),
+    ];
+    let mut sim =
+        navig18xx::game::sim::Simulation::load(game, &state).unwrap();
+    for mv in &moves {
+        sim.apply(mv).unwrap();
+    }
+    let state = sim.save();
+    assert_eq!(state.treasuries.cash("PRR"), Some(380));
+
+    // Save this game state and load it in the user interface.
+    let output_dir = std::path::Path::new("./tests/output");
+    let path = output_dir.join("treasury.json");
+    navig18xx::io::write_game_state(&path, state, true).unwrap();
+    let response = ui.load_game(path.clone());
+    ui.respond(response);
+    assert_eq!(ui.assets.treasuries.cash("PRR"), Some(380));
+
+    // Select the company by its name and cash.
+    assert_eq!(press(&mut ui, gdk::Key::r), UiResponse::Redraw);
+    wait_until(&mut ui, |ui| ui.state.as_find_routes_found().is_some());
+    press(&mut ui, gdk::Key::Escape);
+    let cache = &ui.assets.route_cache;
+    assert!(cache.get(&ui.assets.map, "PRR", &trains, &[]).is_some());
+
+    // Check that the treasury is saved with the game.
+    let response = ui.save_game(path.clone());
+    ui.respond(response);
+    let state = navig18xx::io::read_game_state(&path).unwrap();
+    assert_eq!(state.treasuries.cash("PRR"), Some(380));
+}
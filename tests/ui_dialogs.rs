@@ -0,0 +1,47 @@
+/// Check that dialog responses are returned to the user interface as
+/// actions, and that pinging the user interface never blocks.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+/// Creates a new game of 1830 with a controller whose confirmation dialogs
+/// return `confirm`.
+fn new_ui(confirm: bool) -> UserInterface {
+    let mut dummy = control::DummyController::new();
+    dummy.set_confirm(confirm);
+    new_ui_with(dummy)
+}
+
+/// Presses the `q` key, which should ask the user to confirm.
+fn press_quit(ui: &mut UserInterface) -> UiResponse {
+    let event = KeyPress {
+        key: gdk::Key::q,
+        ctrl: false,
+        alt: false,
+        shift: false,
+    };
+    ui.handle_key_press(&event)
+}
+
+#[test]
+fn confirm_quit() {
+    let mut ui = new_ui(true);
+    // The confirmation is returned as an action, rather than quitting
+    // immediately.
+    assert_eq!(press_quit(&mut ui), UiResponse::None);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::Quit);
+    // There are no further actions, and pinging does not block.
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::None);
+}
+
+#[test]
+fn decline_quit() {
+    let mut ui = new_ui(false);
+    assert_eq!(press_quit(&mut ui), UiResponse::None);
+    assert_eq!(ui.ping(PingDest::TopLevel), UiResponse::None);
+    assert_eq!(ui.ping(PingDest::State), UiResponse::None);
+}
@@ -0,0 +1,103 @@
+/// Record user input events while modifying a game of 1861, and check that
+/// replaying these events in a new user interface reproduces the same map.
+use navig18xx::prelude::*;
+use navig18xx::ui::script::{Event, ReplayError, Script};
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+/// Creates a new game of 1861, with Moscow as the active hex.
+fn new_1861_ui() -> UserInterface {
+    let mut ui = new_ui_for(
+        Box::new(navig18xx::game::new_1861()),
+        control::DummyController::new(),
+    );
+    let moscow: HexAddress = (4, 7).into();
+    ui.state = state::default::Default::at_hex(moscow).into();
+    ui
+}
+
+/// Feeds a key-press event to the user interface.
+fn feed_key(ui: &mut UserInterface, key: keymap::Key) {
+    let event = KeyPress {
+        key,
+        ctrl: false,
+        alt: false,
+        shift: false,
+    };
+    let response = ui.handle_key_press(&event);
+    ui.respond(response);
+}
+
+/// Returns the tile name, rotation, and number of tokens on each map hex.
+fn map_contents(ui: &UserInterface) -> Vec<(HexAddress, String, usize)> {
+    let map = &ui.assets.map;
+    map.hex_address_iter()
+        .filter_map(|&addr| {
+            let hex_state = map.hex_state(addr)?;
+            let tile = hex_state.tile(map);
+            let rotation = format!("{:?}", hex_state.rotation());
+            let tokens = hex_state.tokens().len();
+            Some((addr, format!("{} {}", tile.name, rotation), tokens))
+        })
+        .collect()
+}
+
+#[test]
+fn record_and_replay() -> Result<(), Box<dyn std::error::Error>> {
+    let mut ui = new_1861_ui();
+    let original = map_contents(&ui);
+    ui.start_recording();
+    // Place a token, rotate the tile, and upgrade the tile to green.
+    feed_key(&mut ui, gdk::Key::t);
+    feed_key(&mut ui, gdk::Key::Up);
+    feed_key(&mut ui, gdk::Key::Return);
+    feed_key(&mut ui, gdk::Key::period);
+    feed_key(&mut ui, gdk::Key::u);
+    feed_key(&mut ui, gdk::Key::Return);
+    let script = ui.stop_recording().ok_or("Not recording")?;
+    let modified = map_contents(&ui);
+    assert_ne!(original, modified);
+
+    // Check the recorded events and states.
+    assert_eq!(script.steps.len(), 6);
+    let states: Vec<&str> = script
+        .steps
+        .iter()
+        .map(|step| step.state.as_str())
+        .collect();
+    assert_eq!(
+        states,
+        vec![
+            "Default",
+            "EditTokens",
+            "EditTokens",
+            "Default",
+            "Default",
+            "ReplaceTile"
+        ]
+    );
+    assert!(matches!(script.steps[1].event, Event::KeyPress { .. }));
+
+    // Save the script, and replay it in a new user interface.
+    let path = std::path::Path::new("./tests/output/test-ui_script.json");
+    script.write(path)?;
+    let script = Script::read(path)?;
+    let mut replay_ui = new_1861_ui();
+    replay_ui.replay(&script)?;
+    assert_eq!(map_contents(&replay_ui), modified);
+
+    // Check that a script cannot be replayed in a different state.
+    let mut replay_ui = new_1861_ui();
+    replay_ui.state = state::default::Default::at_hex((4, 7).into()).into();
+    feed_key(&mut replay_ui, gdk::Key::t);
+    let result = replay_ui.replay(&script);
+    assert!(matches!(
+        result,
+        Err(ReplayError::StateMismatch { step: 0, .. })
+    ));
+    Ok(())
+}
@@ -0,0 +1,157 @@
+/// Check that the map view can be rotated and mirrored, and that clicking on
+/// the rotated map selects the hex under the mouse pointer.
+use navig18xx::prelude::*;
+use navig18xx::ui::view::{ViewRotation, ViewTransform};
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+/// Presses a key, and responds to the event and to any resulting message.
+fn press_and_ping(
+    ui: &mut UserInterface,
+    key: gdk::Key,
+    ctrl: bool,
+    shift: bool,
+) {
+    press_with(ui, key, ctrl, shift);
+    let response = ui.ping(PingDest::TopLevel);
+    ui.respond(response);
+}
+
+/// Returns the centre of a map hex, in window coordinates.
+fn window_centre(ui: &UserInterface, addr: HexAddress) -> (f64, f64) {
+    let (x, y) = hex_centre(ui, addr);
+    let (width, height) = ui.assets.map.drawing_size(&ui.assets.hex);
+    ui.assets.view().to_window(width, height, x, y)
+}
+
+fn click(ui: &mut UserInterface, addr: HexAddress) {
+    let (x, y) = window_centre(ui, addr);
+    let event = ButtonPress {
+        x,
+        y,
+        button: ButtonPress::PRIMARY,
+        ctrl: false,
+        alt: false,
+        shift: false,
+    };
+    let response = ui.handle_button_press(&event);
+    ui.respond(response);
+}
+
+#[test]
+fn view_transforms_are_invertible() {
+    let (width, height) = (300.0, 200.0);
+    let rotations = [
+        ViewRotation::Zero,
+        ViewRotation::Ninety,
+        ViewRotation::OneEighty,
+        ViewRotation::TwoSeventy,
+    ];
+    for rotation in rotations {
+        for mirror in [false, true] {
+            let view = ViewTransform { rotation, mirror };
+            let (view_width, view_height) = view.size(width, height);
+            for (x, y) in [(0.0, 0.0), (width, height), (10.0, 150.0)] {
+                let (wx, wy) = view.to_window(width, height, x, y);
+                // The transformed map lies within the window.
+                assert!((0.0..=view_width).contains(&wx));
+                assert!((0.0..=view_height).contains(&wy));
+                let (mx, my) = view.to_map(width, height, wx, wy);
+                assert!((mx - x).abs() < 1e-9 && (my - y).abs() < 1e-9);
+            }
+        }
+    }
+
+    // Rotating the view four times restores the original orientation,
+    // regardless of whether the view is mirrored.
+    for start in [ViewTransform::default().mirrored(), Default::default()] {
+        let mut view = start;
+        let mut corners = vec![];
+        for _ in 0..4 {
+            view = view.rotated_clockwise();
+            corners.push(view.to_window(width, height, 0.0, 0.0));
+        }
+        assert_eq!(view, start);
+        // The displayed map turns clockwise, so the top-left corner of the
+        // map moves from the top-right to the bottom-right of the window,
+        // and then to the bottom-left and top-left.
+        let sideways = (height, width);
+        let expect = [
+            (sideways.0, 0.0),
+            (width, height),
+            (0.0, sideways.1),
+            (0.0, 0.0),
+        ];
+        if start.mirror {
+            // The mirrored map starts with its top-left corner in the
+            // top-right of the window.
+            let expect = [
+                (sideways.0, sideways.1),
+                (0.0, height),
+                (0.0, 0.0),
+                (width, 0.0),
+            ];
+            assert_eq!(corners, expect);
+        } else {
+            assert_eq!(corners, expect);
+        }
+    }
+}
+
+#[test]
+fn rotate_and_mirror_the_map_view() {
+    let mut ui = new_ui();
+    let (width, height) = ui.assets.map.drawing_size(&ui.assets.hex);
+    assert!(ui.assets.view().is_identity());
+    let e5 = hex_addr("E5");
+    let o9 = hex_addr("O9");
+
+    // Rotate the map by 90 degrees; the width and height are swapped.
+    press_and_ping(&mut ui, gdk::Key::R, true, true);
+    assert_eq!(ui.assets.view().rotation, ViewRotation::Ninety);
+    assert!(!ui.assets.view().mirror);
+    assert_eq!(ui.assets.view_size(), (height, width));
+    let dims = navig18xx::ui::canvas::required_dims(&ui.state, &ui.assets);
+    assert_eq!(dims, (height.ceil() as i32, width.ceil() as i32));
+
+    // Clicking on a hex in the rotated map selects that hex.
+    click(&mut ui, e5);
+    assert_eq!(ui.state.active_hex(), Some(e5));
+    click(&mut ui, o9);
+    assert_eq!(ui.state.active_hex(), Some(o9));
+
+    // Mirror the rotated map, and check that clicks are still mapped onto
+    // the correct hexes.
+    press_and_ping(&mut ui, gdk::Key::M, true, true);
+    assert!(ui.assets.view().mirror);
+    click(&mut ui, e5);
+    assert_eq!(ui.state.active_hex(), Some(e5));
+
+    // The view is saved in the session, but not in the saved game.
+    assert_eq!(ui.session().view, ui.assets.view());
+    let output_dir = std::path::Path::new("./tests/output");
+    std::fs::create_dir_all(output_dir).unwrap();
+    let game_path = output_dir.join("view_transform_game.json");
+    let response = ui.save_game(game_path.clone());
+    ui.respond(response);
+    let text = std::fs::read_to_string(&game_path).unwrap();
+    assert!(!text.contains("mirror"));
+
+    // Ctrl+r without Shift does not rotate the map.
+    let before = ui.assets.view();
+    press_and_ping(&mut ui, gdk::Key::r, true, false);
+    assert_eq!(ui.assets.view(), before);
+
+    // Restore the canonical orientation.
+    press_and_ping(&mut ui, gdk::Key::M, true, true);
+    for _ in 0..3 {
+        press_and_ping(&mut ui, gdk::Key::R, true, true);
+    }
+    assert!(ui.assets.view().is_identity());
+    click(&mut ui, o9);
+    assert_eq!(ui.state.active_hex(), Some(o9));
+}
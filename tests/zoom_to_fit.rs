@@ -0,0 +1,68 @@
+/// Check that the drawing area is sized to fit the bounding box of the map
+/// hexes, and that zooming to fit the window chooses a hex size for which
+/// the entire map fits in the window.
+use navig18xx::prelude::*;
+use navig18xx::ui::*;
+
+use gdk4 as gdk;
+
+mod common;
+use common::*;
+
+fn new_1867_ui(window_size: (i32, i32)) -> UserInterface {
+    let mut dummy = control::DummyController::new();
+    dummy.set_window_size(window_size.0, window_size.1);
+    new_ui_for(Box::new(navig18xx::game::new_1867()), dummy)
+}
+
+fn press_ctrl_0(ui: &mut UserInterface) -> UiResponse {
+    let event = KeyPress {
+        key: gdk::Key::_0,
+        ctrl: true,
+        alt: false,
+        shift: false,
+    };
+    ui.handle_key_press(&event)
+}
+
+#[test]
+fn map_size_matches_bounding_box() {
+    let ui = new_1867_ui((800, 600));
+    let (x0, y0, width, height) = ui.assets.map.bounding_box(&ui.assets.hex);
+    assert!(x0 > 0.0 && y0 > 0.0);
+    let (map_width, map_height) = ui.map_size();
+    assert_eq!(map_width, (width + 2.0 * x0).ceil() as i32);
+    assert_eq!(map_height, (height + 2.0 * y0).ceil() as i32);
+}
+
+#[test]
+fn zoom_to_fit_window() {
+    let (win_width, win_height) = (1200, 800);
+    let mut ui = new_1867_ui((win_width, win_height));
+    let hex_d = ui.hex_diameter_to_fit().unwrap();
+    assert!(hex_d >= ui.min_hex_diameter());
+    assert!(hex_d <= ui.max_hex_diameter());
+
+    let response = press_ctrl_0(&mut ui);
+    assert_eq!(response, UiResponse::ZoomToFit);
+    ui.respond(response);
+    assert_eq!(ui.assets.hex.max_d, hex_d);
+    assert_eq!(ui.controller.viewport(), Some((0.0, 0.0)));
+
+    // Unless the hex size reached its lower limit, the map now fits in the
+    // window, and touches either the right or bottom edge.
+    if hex_d > ui.min_hex_diameter() {
+        let (width, height) = ui.assets.map.drawing_size(&ui.assets.hex);
+        assert!(width <= win_width as f64 + 1e-6);
+        assert!(height <= win_height as f64 + 1e-6);
+        assert!(
+            (width - win_width as f64).abs() < 1e-6
+                || (height - win_height as f64).abs() < 1e-6
+        );
+    }
+
+    // Zooming to fit a second time does not change the hex size.
+    let response = press_ctrl_0(&mut ui);
+    ui.respond(response);
+    assert!((ui.assets.hex.max_d - hex_d).abs() < 1e-6);
+}